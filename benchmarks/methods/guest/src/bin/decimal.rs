@@ -0,0 +1,35 @@
+// Copyright 2024 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use risc0_decimal::{Decimal, RoundingMode};
+use risc0_zkvm::guest::env;
+
+/// Fractional digits kept between two compounding periods.
+const SCALE: u32 = 18;
+
+fn main() {
+    let (principal, annual_rate, days): (String, String, u32) = env::read();
+    let principal: Decimal = principal.parse().unwrap();
+    let annual_rate: Decimal = annual_rate.parse().unwrap();
+
+    let daily_rate = annual_rate
+        .checked_div(&Decimal::from(365u32), SCALE, RoundingMode::HalfEven)
+        .unwrap();
+    let growth = &Decimal::from(1u32) + &daily_rate;
+    let mut balance = principal;
+    for _ in 0..days {
+        balance = (&balance * &growth).round(SCALE, RoundingMode::HalfEven);
+    }
+    env::commit(&balance.round(2, RoundingMode::HalfEven).to_string());
+}
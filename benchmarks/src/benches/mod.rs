@@ -16,6 +16,7 @@ pub mod big_blake2b;
 pub mod big_blake3;
 pub mod big_keccak;
 pub mod big_sha2;
+pub mod decimal;
 pub mod ecdsa_verify;
 pub mod ed25519_verify;
 pub mod fibonacci;
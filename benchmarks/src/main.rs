@@ -38,6 +38,7 @@ enum Command {
     BigBlake3,
     BigKeccak,
     BigSha2,
+    Decimal,
     EcdsaVerify,
     Ed25519Verify,
     Fibonacci,
@@ -60,6 +61,7 @@ impl Command {
             Command::BigBlake3 => big_blake3::new_jobs(),
             Command::BigKeccak => big_keccak::new_jobs(),
             Command::BigSha2 => big_sha2::new_jobs(),
+            Command::Decimal => decimal::new_jobs(),
             Command::EcdsaVerify => ecdsa_verify::new_jobs(),
             Command::Ed25519Verify => ed25519_verify::new_jobs(),
             Command::Fibonacci => fibonacci::new_jobs(),
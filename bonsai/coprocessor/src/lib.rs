@@ -0,0 +1,99 @@
+// Copyright 2024 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+#![deny(missing_docs)]
+
+//! Building blocks for on-chain applications using the RISC Zero zkVM as a
+//! coprocessor.
+//!
+//! An application contract requests a computation by emitting a
+//! [CallbackRequest] event, naming the image ID of the guest to run, its input
+//! and the function to call back with the result. A [Listener] watches for
+//! these events, proves the execution of the matching guest with a [Prover],
+//! and submits a [Callback] transaction carrying the journal and the Groth16
+//! seal, which the callback contract checks with the on-chain verifier.
+//!
+//! In Solidity, the request event and the callback function are:
+//!
+//! ```solidity
+//! event CallbackRequest(
+//!     address account,
+//!     bytes32 imageId,
+//!     bytes input,
+//!     address callbackContract,
+//!     bytes4 functionSelector,
+//!     uint64 gasLimit
+//! );
+//!
+//! function callback(
+//!     bytes32 imageId,
+//!     bytes calldata journal,
+//!     bytes32 postStateDigest,
+//!     bytes calldata seal
+//! ) external;
+//! ```
+//!
+//! The callback function may have any name, as long as its selector is the
+//! one given in the request. The input is passed to the guest as is, so it
+//! must be encoded the way the guest reads it.
+
+mod listener;
+mod prover;
+mod request;
+
+use risc0_zkvm::sha::Digest;
+use thiserror::Error;
+
+pub use crate::{
+    listener::Listener,
+    prover::{BonsaiProver, Proof, Prover},
+    request::{Callback, CallbackRequest},
+};
+
+/// Errors of the coprocessor.
+#[derive(Debug, Error)]
+pub enum Error {
+    /// The log is not a [CallbackRequest] event.
+    #[error("log is not a CallbackRequest event")]
+    UnexpectedEvent,
+
+    /// The event data could not be decoded.
+    #[error("malformed event data: {0}")]
+    Abi(#[from] ethers_core::abi::Error),
+
+    /// The ELF binary of a guest could not be loaded.
+    #[error("invalid guest: {0}")]
+    InvalidGuest(String),
+
+    /// No guest with the requested image ID is registered.
+    #[error("no guest registered for image ID {0}")]
+    UnknownImage(Digest),
+
+    /// Error from the Bonsai client.
+    #[error("Bonsai error: {0}")]
+    Bonsai(#[from] bonsai_sdk::alpha::SdkErr),
+
+    /// Proving did not succeed.
+    #[error("proving failed with status {status}: {message}")]
+    ProvingFailed {
+        /// The final status of the proving session.
+        status: String,
+        /// The error message reported by the prover.
+        message: String,
+    },
+
+    /// Error from the Ethereum node.
+    #[error("provider error: {0}")]
+    Provider(String),
+}
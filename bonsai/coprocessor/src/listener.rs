@@ -0,0 +1,220 @@
+// Copyright 2024 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::{collections::BTreeMap, sync::Arc, time::Duration};
+
+use ethers_core::types::{Address, Filter, TxHash};
+use ethers_providers::Middleware;
+use risc0_zkvm::{compute_image_id, sha::Digest};
+
+use crate::{Callback, CallbackRequest, Error, Prover};
+
+/// Watches a contract for [CallbackRequest] events, and answers them with a
+/// [Callback] transaction.
+///
+/// Requests for an image ID which was not registered with
+/// [Listener::add_guest], malformed requests and requests for which proving
+/// fails are logged and skipped.
+pub struct Listener<M, P> {
+    client: Arc<M>,
+    address: Address,
+    guests: BTreeMap<Digest, Arc<Vec<u8>>>,
+    prover: Arc<P>,
+    next_block: Option<u64>,
+}
+
+impl<M: Middleware, P: Prover + 'static> Listener<M, P> {
+    /// Creates a listener for the events emitted by the contract at
+    /// `address`.
+    ///
+    /// The transactions are sent through `client`, which is expected to sign
+    /// them, e.g. with a `SignerMiddleware`.
+    pub fn new(client: Arc<M>, address: Address, prover: P) -> Self {
+        Self {
+            client,
+            address,
+            guests: BTreeMap::new(),
+            prover: Arc::new(prover),
+            next_block: None,
+        }
+    }
+
+    /// Registers the guest `elf`, and returns its image ID.
+    pub fn add_guest(&mut self, elf: &[u8]) -> Result<Digest, Error> {
+        let image_id = compute_image_id(elf).map_err(|err| Error::InvalidGuest(err.to_string()))?;
+        self.guests.insert(image_id, Arc::new(elf.to_vec()));
+        Ok(image_id)
+    }
+
+    /// Starts listening from `block`, instead of the latest block at the time
+    /// of the first poll.
+    pub fn from_block(mut self, block: u64) -> Self {
+        self.next_block = Some(block);
+        self
+    }
+
+    /// Answers the requests emitted since the last poll, and returns the
+    /// hashes of the callback transactions.
+    ///
+    /// If a transaction cannot be sent, the next poll starts again from the
+    /// block of the failed request.
+    pub async fn poll(&mut self) -> Result<Vec<TxHash>, Error> {
+        let latest = self
+            .client
+            .get_block_number()
+            .await
+            .map_err(|err| Error::Provider(err.to_string()))?
+            .as_u64();
+        let from = self.next_block.unwrap_or(latest);
+        if from > latest {
+            return Ok(vec![]);
+        }
+
+        let filter = Filter::new()
+            .address(self.address)
+            .topic0(CallbackRequest::topic())
+            .from_block(from)
+            .to_block(latest);
+        let logs = self
+            .client
+            .get_logs(&filter)
+            .await
+            .map_err(|err| Error::Provider(err.to_string()))?;
+
+        let mut hashes = Vec::new();
+        for log in logs {
+            let request = match CallbackRequest::from_log(&log) {
+                Ok(request) => request,
+                Err(err) => {
+                    tracing::warn!("skipping request in {:?}: {err}", log.transaction_hash);
+                    continue;
+                }
+            };
+            let Some(elf) = self.guests.get(&request.image_id).cloned() else {
+                tracing::warn!(
+                    "skipping request: {}",
+                    Error::UnknownImage(request.image_id)
+                );
+                continue;
+            };
+
+            tracing::info!("proving request for image ID {}", request.image_id);
+            let prover = self.prover.clone();
+            let input = request.input.clone();
+            let proof = match tokio::task::spawn_blocking(move || prover.prove(&elf, &input)).await
+            {
+                Ok(proof) => proof,
+                Err(err) => std::panic::resume_unwind(err.into_panic()),
+            };
+            let proof = match proof {
+                Ok(proof) => proof,
+                Err(err) => {
+                    tracing::warn!("skipping request: {err}");
+                    continue;
+                }
+            };
+
+            let tx = Callback::new(&request, proof).transaction();
+            match self.client.send_transaction(tx, None).await {
+                Ok(pending) => hashes.push(*pending),
+                Err(err) => {
+                    self.next_block = Some(log.block_number.map_or(from, |n| n.as_u64()));
+                    return Err(Error::Provider(err.to_string()));
+                }
+            }
+        }
+
+        self.next_block = Some(latest + 1);
+        Ok(hashes)
+    }
+
+    /// Polls for new requests every `interval`, until an error occurs.
+    pub async fn run(&mut self, interval: Duration) -> Result<(), Error> {
+        loop {
+            for hash in self.poll().await? {
+                tracing::info!("sent callback {hash:?}");
+            }
+            tokio::time::sleep(interval).await;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ethers_core::types::{Log, H256, U256, U64};
+    use ethers_providers::Provider;
+    use risc0_groth16::Seal;
+
+    use super::*;
+    use crate::Proof;
+
+    struct FixedProver;
+
+    impl Prover for FixedProver {
+        fn prove(&self, _elf: &[u8], input: &[u8]) -> Result<Proof, Error> {
+            Ok(Proof {
+                journal: input.to_vec(),
+                seal: Seal {
+                    a: vec![],
+                    b: vec![],
+                    c: vec![],
+                },
+                post_state_digest: Digest::ZERO,
+            })
+        }
+    }
+
+    fn request_log(image_id: Digest) -> Log {
+        let request = CallbackRequest {
+            account: Address::repeat_byte(0x11),
+            image_id,
+            input: vec![1, 2, 3],
+            callback_contract: Address::repeat_byte(0x22),
+            function_selector: [0xde, 0xad, 0xbe, 0xef],
+            gas_limit: 300_000,
+        };
+        Log {
+            address: Address::repeat_byte(0x33),
+            topics: vec![CallbackRequest::topic()],
+            data: request.encode_data().into(),
+            block_number: Some(10.into()),
+            ..Default::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn poll() {
+        let (provider, mock) = Provider::mocked();
+        let mut listener =
+            Listener::new(Arc::new(provider), Address::repeat_byte(0x33), FixedProver);
+        let image_id = Digest::from([1, 2, 3, 4, 5, 6, 7, 8]);
+        listener.guests.insert(image_id, Arc::new(vec![]));
+
+        // Responses are returned in reverse order.
+        let tx_hash = H256::repeat_byte(0x44);
+        mock.push(tx_hash).unwrap();
+        mock.push(U256::from(1_000_000_000u64)).unwrap();
+        let unknown = request_log(Digest::from([8, 7, 6, 5, 4, 3, 2, 1]));
+        mock.push::<Vec<Log>, _>(vec![request_log(image_id), unknown])
+            .unwrap();
+        mock.push(U64::from(10)).unwrap();
+
+        assert_eq!(listener.poll().await.unwrap(), vec![tx_hash]);
+        assert_eq!(listener.next_block, Some(11));
+
+        // No new block.
+        mock.push(U64::from(10)).unwrap();
+        assert!(listener.poll().await.unwrap().is_empty());
+    }
+}
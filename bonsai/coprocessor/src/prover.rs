@@ -0,0 +1,114 @@
+// Copyright 2024 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::{thread, time::Duration};
+
+use bonsai_sdk::alpha::Client;
+use risc0_groth16::Seal;
+use risc0_zkvm::{compute_image_id, sha::Digest};
+
+use crate::Error;
+
+/// Result of proving the execution of a guest.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Proof {
+    /// The journal committed by the guest.
+    pub journal: Vec<u8>,
+    /// The Groth16 seal attesting to the execution of the guest.
+    pub seal: Seal,
+    /// The digest of the final state of the guest.
+    pub post_state_digest: Digest,
+}
+
+/// Produces Groth16 proofs of the execution of guests.
+///
+/// Implementations may block, and are called from a blocking task by the
+/// [Listener](crate::Listener).
+pub trait Prover: Send + Sync {
+    /// Proves the execution of the guest `elf` on `input`.
+    fn prove(&self, elf: &[u8], input: &[u8]) -> Result<Proof, Error>;
+}
+
+/// A [Prover] using the Bonsai proving service.
+pub struct BonsaiProver {
+    client: Client,
+    poll_interval: Duration,
+}
+
+impl BonsaiProver {
+    /// Creates a prover sending requests with `client`.
+    pub fn new(client: Client) -> Self {
+        Self {
+            client,
+            poll_interval: Duration::from_secs(5),
+        }
+    }
+
+    /// Creates a prover from the `BONSAI_API_URL` and `BONSAI_API_KEY`
+    /// environment variables.
+    pub fn from_env() -> Result<Self, Error> {
+        Ok(Self::new(Client::from_env(risc0_zkvm::VERSION)?))
+    }
+
+    /// Sets the interval between two queries of the status of a proof.
+    pub fn with_poll_interval(mut self, poll_interval: Duration) -> Self {
+        self.poll_interval = poll_interval;
+        self
+    }
+}
+
+impl Prover for BonsaiProver {
+    fn prove(&self, elf: &[u8], input: &[u8]) -> Result<Proof, Error> {
+        let image_id = compute_image_id(elf)
+            .map_err(|err| Error::InvalidGuest(err.to_string()))?
+            .to_string();
+        self.client.upload_img(&image_id, elf.to_vec())?;
+        let input_id = self.client.upload_input(input.to_vec())?;
+
+        let session = self.client.create_session(image_id, input_id, vec![])?;
+        let res = session.poll_until_done(&self.client, Duration::MAX, self.poll_interval)?;
+        if res.status != "SUCCEEDED" {
+            return Err(Error::ProvingFailed {
+                status: res.status,
+                message: res.error_msg.unwrap_or_default(),
+            });
+        }
+
+        let snark = self.client.create_snark(session.uuid)?;
+        loop {
+            let res = snark.status(&self.client)?;
+            match (res.status.as_str(), res.output) {
+                ("RUNNING", _) => thread::sleep(self.poll_interval),
+                ("SUCCEEDED", Some(output)) => {
+                    let post_state_digest = Digest::try_from(output.post_state_digest.as_slice())
+                        .map_err(|_| Error::ProvingFailed {
+                        status: res.status,
+                        message: "malformed post state digest".into(),
+                    })?;
+                    return Ok(Proof {
+                        journal: output.journal,
+                        seal: output.snark,
+                        post_state_digest,
+                    });
+                }
+                _ => {
+                    return Err(Error::ProvingFailed {
+                        status: res.status,
+                        message: res.error_msg.unwrap_or_default(),
+                    })
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,241 @@
+// Copyright 2024 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use ethers_core::{
+    abi::{self, ParamType, Token},
+    types::{
+        transaction::eip2718::TypedTransaction, Address, Bytes, Log, TransactionRequest, H256,
+    },
+    utils::keccak256,
+};
+use risc0_groth16::Seal;
+use risc0_zkvm::sha::Digest;
+
+use crate::{Error, Proof};
+
+/// Signature of the [CallbackRequest] event.
+pub const CALLBACK_REQUEST_SIGNATURE: &str =
+    "CallbackRequest(address,bytes32,bytes,address,bytes4,uint64)";
+
+const CALLBACK_REQUEST_PARAMS: [ParamType; 6] = [
+    ParamType::Address,
+    ParamType::FixedBytes(32),
+    ParamType::Bytes,
+    ParamType::Address,
+    ParamType::FixedBytes(4),
+    ParamType::Uint(64),
+];
+
+/// Request to prove the execution of a guest, emitted by an on-chain
+/// application.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CallbackRequest {
+    /// The account that made the request.
+    pub account: Address,
+    /// The image ID of the guest to run.
+    pub image_id: Digest,
+    /// The input of the guest.
+    pub input: Vec<u8>,
+    /// The contract to call back with the result.
+    pub callback_contract: Address,
+    /// The selector of the function to call back.
+    pub function_selector: [u8; 4],
+    /// The gas limit of the callback transaction.
+    pub gas_limit: u64,
+}
+
+impl CallbackRequest {
+    /// Returns the topic identifying the event in logs.
+    pub fn topic() -> H256 {
+        H256(keccak256(CALLBACK_REQUEST_SIGNATURE))
+    }
+
+    /// Decodes a request from an event log.
+    pub fn from_log(log: &Log) -> Result<Self, Error> {
+        if log.topics.first() != Some(&Self::topic()) {
+            return Err(Error::UnexpectedEvent);
+        }
+        Self::decode_data(&log.data)
+    }
+
+    /// Decodes a request from the ABI encoding of the event data.
+    pub fn decode_data(data: &[u8]) -> Result<Self, Error> {
+        let malformed = || Error::Abi(abi::Error::InvalidData);
+        let tokens = abi::decode(&CALLBACK_REQUEST_PARAMS, data)?;
+        match <[Token; 6]>::try_from(tokens).map_err(|_| malformed())? {
+            [Token::Address(account), Token::FixedBytes(image_id), Token::Bytes(input), Token::Address(callback_contract), Token::FixedBytes(function_selector), Token::Uint(gas_limit)] => {
+                Ok(Self {
+                    account,
+                    image_id: Digest::try_from(image_id.as_slice()).map_err(|_| malformed())?,
+                    input,
+                    callback_contract,
+                    function_selector: function_selector.try_into().map_err(|_| malformed())?,
+                    gas_limit: gas_limit.try_into().map_err(|_| malformed())?,
+                })
+            }
+            _ => Err(malformed()),
+        }
+    }
+
+    /// Returns the ABI encoding of the event data.
+    pub fn encode_data(&self) -> Vec<u8> {
+        abi::encode(&[
+            Token::Address(self.account),
+            Token::FixedBytes(self.image_id.as_bytes().to_vec()),
+            Token::Bytes(self.input.clone()),
+            Token::Address(self.callback_contract),
+            Token::FixedBytes(self.function_selector.to_vec()),
+            Token::Uint(self.gas_limit.into()),
+        ])
+    }
+}
+
+/// Result of a [CallbackRequest], delivered to the callback contract.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Callback {
+    /// The contract to call back.
+    pub callback_contract: Address,
+    /// The selector of the function to call back.
+    pub function_selector: [u8; 4],
+    /// The gas limit of the callback transaction.
+    pub gas_limit: u64,
+    /// The image ID of the guest that was run.
+    pub image_id: Digest,
+    /// The journal committed by the guest.
+    pub journal: Vec<u8>,
+    /// The digest of the final state of the guest.
+    pub post_state_digest: Digest,
+    /// The Groth16 seal attesting to the execution of the guest.
+    pub seal: Seal,
+}
+
+impl Callback {
+    /// Creates the callback answering `request` with `proof`.
+    pub fn new(request: &CallbackRequest, proof: Proof) -> Self {
+        Self {
+            callback_contract: request.callback_contract,
+            function_selector: request.function_selector,
+            gas_limit: request.gas_limit,
+            image_id: request.image_id,
+            journal: proof.journal,
+            post_state_digest: proof.post_state_digest,
+            seal: proof.seal,
+        }
+    }
+
+    /// Returns the calldata of the callback: the function selector followed
+    /// by the ABI encoding of the image ID, journal, post state digest and
+    /// seal.
+    pub fn calldata(&self) -> Bytes {
+        let mut calldata = self.function_selector.to_vec();
+        calldata.extend(abi::encode(&[
+            Token::FixedBytes(self.image_id.as_bytes().to_vec()),
+            Token::Bytes(self.journal.clone()),
+            Token::FixedBytes(self.post_state_digest.as_bytes().to_vec()),
+            Token::Bytes(self.seal.to_vec()),
+        ]));
+        calldata.into()
+    }
+
+    /// Returns the callback transaction, to be signed and sent by the caller.
+    pub fn transaction(&self) -> TypedTransaction {
+        TransactionRequest::new()
+            .to(self.callback_contract)
+            .data(self.calldata())
+            .gas(self.gas_limit)
+            .into()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn request() -> CallbackRequest {
+        CallbackRequest {
+            account: Address::repeat_byte(0x11),
+            image_id: Digest::from([1, 2, 3, 4, 5, 6, 7, 8]),
+            input: b"coprocessor input".to_vec(),
+            callback_contract: Address::repeat_byte(0x22),
+            function_selector: [0xde, 0xad, 0xbe, 0xef],
+            gas_limit: 300_000,
+        }
+    }
+
+    #[test]
+    fn request_roundtrip() {
+        let request = request();
+        let log = Log {
+            topics: vec![CallbackRequest::topic()],
+            data: request.encode_data().into(),
+            ..Default::default()
+        };
+        assert_eq!(CallbackRequest::from_log(&log).unwrap(), request);
+
+        let other = Log {
+            topics: vec![H256::repeat_byte(1)],
+            ..log.clone()
+        };
+        assert!(matches!(
+            CallbackRequest::from_log(&other),
+            Err(Error::UnexpectedEvent)
+        ));
+
+        let truncated = &log.data[..log.data.len() - 32];
+        assert!(CallbackRequest::decode_data(truncated).is_err());
+    }
+
+    #[test]
+    fn callback_calldata() {
+        let request = request();
+        let seal = Seal {
+            a: vec![vec![1; 32], vec![2; 32]],
+            b: vec![
+                vec![vec![3; 32], vec![4; 32]],
+                vec![vec![5; 32], vec![6; 32]],
+            ],
+            c: vec![vec![7; 32], vec![8; 32]],
+        };
+        let proof = Proof {
+            journal: vec![9; 40],
+            seal: seal.clone(),
+            post_state_digest: Digest::from([8, 7, 6, 5, 4, 3, 2, 1]),
+        };
+        let callback = Callback::new(&request, proof);
+        let calldata = callback.calldata();
+        assert_eq!(calldata[..4], request.function_selector);
+
+        let params = [
+            ParamType::FixedBytes(32),
+            ParamType::Bytes,
+            ParamType::FixedBytes(32),
+            ParamType::Bytes,
+        ];
+        let tokens = abi::decode(&params, &calldata[4..]).unwrap();
+        assert_eq!(
+            tokens,
+            vec![
+                Token::FixedBytes(request.image_id.as_bytes().to_vec()),
+                Token::Bytes(vec![9; 40]),
+                Token::FixedBytes(callback.post_state_digest.as_bytes().to_vec()),
+                Token::Bytes(seal.to_vec()),
+            ]
+        );
+
+        let tx = callback.transaction();
+        assert_eq!(tx.to_addr(), Some(&request.callback_contract));
+        assert_eq!(tx.gas(), Some(&request.gas_limit.into()));
+        assert_eq!(tx.data(), Some(&calldata));
+    }
+}
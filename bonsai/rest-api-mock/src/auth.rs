@@ -0,0 +1,112 @@
+// Copyright 2024 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::{collections::HashSet, sync::Arc};
+
+use axum::{
+    extract::{MatchedPath, Request, State},
+    http::{header, HeaderMap, Method, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use bonsai_sdk::API_KEY_HEADER;
+
+// Routes standing in for the presigned storage URLs of Bonsai, which do not
+// take an API key.
+const PRESIGNED_ROUTES: [(Method, &str); 4] = [
+    (Method::PUT, "/images/:image_id"),
+    (Method::PUT, "/inputs/:input_id"),
+    (Method::PUT, "/receipts/:session_id"),
+    (Method::GET, "/receipts/:session_id"),
+];
+
+/// API keys accepted by the mock, all keys being accepted if empty.
+#[derive(Clone, Default)]
+pub(crate) struct ApiKeys(Arc<HashSet<String>>);
+
+impl ApiKeys {
+    pub(crate) fn new(keys: impl IntoIterator<Item = String>) -> Self {
+        Self(Arc::new(keys.into_iter().collect()))
+    }
+
+    /// Whether `headers` carry an accepted key, in the `x-api-key` header or
+    /// as an `Authorization: Bearer` token.
+    fn authorized(&self, headers: &HeaderMap) -> bool {
+        if self.0.is_empty() {
+            return true;
+        }
+        let api_key = headers
+            .get(API_KEY_HEADER)
+            .and_then(|value| value.to_str().ok());
+        let bearer = headers
+            .get(header::AUTHORIZATION)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.strip_prefix("Bearer "));
+        [api_key, bearer]
+            .into_iter()
+            .flatten()
+            .any(|key| self.0.contains(key))
+    }
+}
+
+/// Middleware rejecting requests to the API without an accepted key with
+/// `401 Unauthorized`, as Bonsai does.
+pub(crate) async fn authenticate(
+    State(keys): State<ApiKeys>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let route = request
+        .extensions()
+        .get::<MatchedPath>()
+        .map(|path| path.as_str())
+        .unwrap_or_default();
+    let presigned = PRESIGNED_ROUTES
+        .iter()
+        .any(|(method, path)| request.method() == method && route == *path);
+    if !presigned && !keys.authorized(request.headers()) {
+        tracing::info!("Rejected unauthorized request to {route}");
+        return (StatusCode::UNAUTHORIZED, "invalid API key").into_response();
+    }
+    next.run(request).await
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::http::HeaderValue;
+
+    use super::*;
+
+    #[test]
+    fn authorized() {
+        let keys = ApiKeys::new(["secret".to_string()]);
+        let mut headers = HeaderMap::new();
+        assert!(!keys.authorized(&headers));
+        assert!(ApiKeys::default().authorized(&headers));
+
+        headers.insert(API_KEY_HEADER, HeaderValue::from_static("wrong"));
+        assert!(!keys.authorized(&headers));
+        headers.insert(API_KEY_HEADER, HeaderValue::from_static("secret"));
+        assert!(keys.authorized(&headers));
+
+        let mut headers = HeaderMap::new();
+        headers.insert(header::AUTHORIZATION, HeaderValue::from_static("secret"));
+        assert!(!keys.authorized(&headers));
+        headers.insert(
+            header::AUTHORIZATION,
+            HeaderValue::from_static("Bearer secret"),
+        );
+        assert!(keys.authorized(&headers));
+    }
+}
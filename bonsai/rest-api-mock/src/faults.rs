@@ -0,0 +1,167 @@
+// Copyright 2024 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use axum::{
+    extract::{MatchedPath, Request, State},
+    http::StatusCode,
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+/// Faults injected into the responses of one endpoint.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct EndpointFaults {
+    /// Delay before each response.
+    pub delay: Duration,
+    /// Probability, between 0 and 1, that a request fails with `status`.
+    pub failure_rate: f64,
+    /// Status of the failed requests, 503 Service Unavailable if unset.
+    pub status: Option<StatusCode>,
+}
+
+/// Faults injected by the mock, to test retry logic against a misbehaving
+/// Bonsai service.
+///
+/// Failures are drawn from a random generator seeded with `seed`, so a given
+/// sequence of requests always fails the same way.
+#[derive(Clone, Debug, Default)]
+pub struct Faults {
+    /// Seed of the random failures.
+    pub seed: u64,
+    /// Faults of each endpoint, by route such as `/sessions/status/:session_id`.
+    pub endpoints: HashMap<String, EndpointFaults>,
+    /// Faults of the endpoints not in `endpoints`.
+    pub default: EndpointFaults,
+    /// Extra time taken by each session and SNARK conversion before it
+    /// succeeds.
+    pub proving_delay: Duration,
+}
+
+impl Faults {
+    /// No faults, with the given seed for random failures.
+    pub fn new(seed: u64) -> Self {
+        Self {
+            seed,
+            ..Default::default()
+        }
+    }
+
+    /// Inject `faults` into the responses of `route`.
+    pub fn endpoint(mut self, route: impl Into<String>, faults: EndpointFaults) -> Self {
+        self.endpoints.insert(route.into(), faults);
+        self
+    }
+
+    /// Inject `faults` into the responses of all other endpoints.
+    pub fn default_endpoint(mut self, faults: EndpointFaults) -> Self {
+        self.default = faults;
+        self
+    }
+
+    /// Delay the completion of each session and SNARK conversion.
+    pub fn proving_delay(mut self, delay: Duration) -> Self {
+        self.proving_delay = delay;
+        self
+    }
+}
+
+#[derive(Clone)]
+pub(crate) struct FaultInjector {
+    faults: Arc<Faults>,
+    rng: Arc<Mutex<StdRng>>,
+}
+
+impl FaultInjector {
+    pub(crate) fn new(faults: Faults) -> Self {
+        Self {
+            rng: Arc::new(Mutex::new(StdRng::seed_from_u64(faults.seed))),
+            faults: Arc::new(faults),
+        }
+    }
+
+    fn get(&self, route: &str) -> &EndpointFaults {
+        self.faults
+            .endpoints
+            .get(route)
+            .unwrap_or(&self.faults.default)
+    }
+
+    fn fails(&self, faults: &EndpointFaults) -> bool {
+        if faults.failure_rate <= 0.0 {
+            return false;
+        }
+        let mut rng = self.rng.lock().unwrap_or_else(|err| err.into_inner());
+        rng.gen_bool(faults.failure_rate.min(1.0))
+    }
+}
+
+/// Middleware delaying or failing requests according to the [Faults] of their
+/// route.
+pub(crate) async fn inject_faults(
+    State(injector): State<FaultInjector>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let route = request
+        .extensions()
+        .get::<MatchedPath>()
+        .map(|path| path.as_str().to_string())
+        .unwrap_or_default();
+    let faults = *injector.get(&route);
+    if !faults.delay.is_zero() {
+        tokio::time::sleep(faults.delay).await;
+    }
+    if injector.fails(&faults) {
+        let status = faults.status.unwrap_or(StatusCode::SERVICE_UNAVAILABLE);
+        tracing::info!("Injected {status} into {route}");
+        return (status, "injected fault").into_response();
+    }
+    next.run(request).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn deterministic_failures() {
+        let faults = Faults::new(7).endpoint(
+            "/sessions/create",
+            EndpointFaults {
+                failure_rate: 0.5,
+                ..Default::default()
+            },
+        );
+        let draw = |injector: &FaultInjector, route: &str| -> Vec<bool> {
+            (0..32)
+                .map(|_| injector.fails(injector.get(route)))
+                .collect()
+        };
+
+        let first = draw(&FaultInjector::new(faults.clone()), "/sessions/create");
+        let second = draw(&FaultInjector::new(faults.clone()), "/sessions/create");
+        assert_eq!(first, second);
+        assert!(first.contains(&true) && first.contains(&false));
+
+        let injector = FaultInjector::new(faults);
+        assert!(!draw(&injector, "/inputs/upload").contains(&true));
+    }
+}
@@ -12,46 +12,72 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+mod auth;
 mod error;
+mod faults;
 mod prover;
 mod routes;
 mod state;
+mod storage;
 
 use std::sync::{Arc, RwLock};
 
 use anyhow::Context;
 use axum::{
     extract::DefaultBodyLimit,
-    routing::{get, post, put},
+    middleware,
+    routing::{get, head, post, put},
     Extension, Router,
 };
 use tokio::{net::TcpListener, sync::mpsc};
 use tower_http::trace::{DefaultOnRequest, TraceLayer};
 use tracing::{info, Level};
 
+#[cfg(feature = "sled")]
+pub use crate::storage::SledStorage;
 use crate::{
+    auth::{authenticate, ApiKeys},
+    faults::{inject_faults, FaultInjector},
     prover::{Prover, ProverHandle},
     routes::{
-        create_session, create_snark, get_image_upload, get_input_upload, get_receipt,
-        get_receipt_upload, put_image_upload, put_input_upload, put_receipt, session_status,
-        snark_status,
+        create_session, create_snark, get_exec_only_journal, get_image_upload, get_input_upload,
+        get_receipt, get_receipt_upload, get_version, head_image, put_image_upload,
+        put_input_upload, put_receipt, session_status, snark_status, stop_session,
     },
     state::BonsaiState,
 };
+pub use crate::{
+    faults::{EndpointFaults, Faults},
+    storage::{MemoryStorage, Storage, Table},
+};
 
-fn app(state: Arc<RwLock<BonsaiState>>, prover_handle: ProverHandle) -> Router {
+fn app(
+    state: Arc<RwLock<BonsaiState>>,
+    prover_handle: ProverHandle,
+    injector: FaultInjector,
+    api_keys: ApiKeys,
+) -> Router {
     Router::new()
         .route("/images/upload/:image_id", get(get_image_upload))
+        .route("/images/:image_id", head(head_image))
         .route("/images/:image_id", put(put_image_upload))
         .route("/inputs/upload", get(get_input_upload))
         .route("/inputs/:input_id", put(put_input_upload))
         .route("/sessions/create", post(create_session))
         .route("/sessions/status/:session_id", get(session_status))
+        .route("/sessions/stop/:session_id", get(stop_session))
+        .route(
+            "/sessions/exec_only_journal/:session_id",
+            get(get_exec_only_journal),
+        )
         .route("/snark/create", post(create_snark))
         .route("/snark/status/:snark_id", get(snark_status))
         .route("/receipts/:session_id", get(get_receipt))
         .route("/receipts/:session_id", put(put_receipt))
         .route("/receipts/upload", get(get_receipt_upload))
+        .route("/version", get(get_version))
+        .route_layer(middleware::from_fn_with_state(injector, inject_faults))
+        .route_layer(middleware::from_fn_with_state(api_keys, authenticate))
         .layer(Extension(prover_handle))
         .with_state(state)
         .layer(DefaultBodyLimit::max(256 * 1024 * 1024))
@@ -60,18 +86,74 @@ fn app(state: Arc<RwLock<BonsaiState>>, prover_handle: ProverHandle) -> Router {
         ))
 }
 
+/// Configuration of the mock.
+#[derive(Clone)]
+pub struct Config {
+    /// Store of the images, inputs, sessions and receipts of the mock, kept
+    /// in memory by default.
+    pub storage: Arc<dyn Storage>,
+
+    /// Faults injected into the responses and sessions of the mock.
+    pub faults: Faults,
+
+    /// Number of sessions and SNARK conversions run concurrently. Others
+    /// report the `Setup` state until a worker is available.
+    pub workers: usize,
+
+    /// API keys accepted in the `x-api-key` header or as a bearer token.
+    /// Requests to the API with another key are rejected with `401
+    /// Unauthorized`, except for the uploads and downloads standing in for
+    /// presigned URLs. All keys are accepted if empty.
+    pub api_keys: Vec<String>,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            storage: Arc::new(MemoryStorage::new()),
+            faults: Faults::default(),
+            workers: std::thread::available_parallelism().map_or(1, |n| n.get()),
+            api_keys: vec![],
+        }
+    }
+}
+
+impl std::fmt::Debug for Config {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Config")
+            .field("faults", &self.faults)
+            .field("workers", &self.workers)
+            .field("api_keys", &self.api_keys)
+            .finish_non_exhaustive()
+    }
+}
+
 /// Starts a mock of Bonsai on localhost at the given port. It exposes the same
 /// REST API of Bonsai alpha.
 ///
-/// Note that this mock only performs execution, no proving.
+/// Note that this mock only performs execution, no proving. SNARK conversions
+/// of its sessions return a canned seal, while receipts uploaded to it are
+/// wrapped with the local Groth16 prover when the `prove` feature is enabled.
 pub async fn serve(listener: TcpListener) -> anyhow::Result<()> {
+    serve_with_config(listener, Config::default()).await
+}
+
+/// Starts a mock of Bonsai on localhost at the given port, keeping its state
+/// and running its sessions as set by `config`.
+pub async fn serve_with_config(listener: TcpListener, config: Config) -> anyhow::Result<()> {
+    let Config {
+        storage,
+        faults,
+        workers,
+        api_keys,
+    } = config;
     let local_addr = listener.local_addr().unwrap();
     let port = local_addr.port();
     let local_url = format!("http://127.0.0.1:{port}");
-    let state = Arc::new(RwLock::new(BonsaiState::new(local_url)));
+    let state = Arc::new(RwLock::new(BonsaiState::new(local_url, storage)));
 
     let (sender, receiver) = mpsc::channel(8);
-    let mut prover = Prover::new(receiver, Arc::clone(&state));
+    let mut prover = Prover::new(receiver, Arc::clone(&state), faults.proving_delay, workers);
 
     let prover_handle = ProverHandle { sender };
 
@@ -79,7 +161,9 @@ pub async fn serve(listener: TcpListener) -> anyhow::Result<()> {
 
     info!("Local Bonsai started on {local_addr}");
 
-    axum::serve(listener, app(state, prover_handle))
+    let injector = FaultInjector::new(faults);
+    let api_keys = ApiKeys::new(api_keys);
+    axum::serve(listener, app(state, prover_handle, injector, api_keys))
         .await
         .context(format!("failed to serve Local Bonsai API on {local_addr}"))
 }
@@ -89,17 +173,21 @@ mod test {
     use std::time::Duration;
 
     use anyhow::{bail, Result};
-    use bonsai_sdk::alpha_async as bonsai_sdk;
-    use risc0_zkvm::compute_image_id;
+    use bonsai_sdk::{
+        alpha::{responses::SessionMetadata, SdkErr, SessionOpts},
+        alpha_async as bonsai_sdk,
+    };
+    use risc0_zkvm::{compute_image_id, Groth16Seal};
     use risc0_zkvm_methods::HELLO_COMMIT_ELF;
     use tokio::net::TcpListener;
 
-    use crate::serve;
+    use crate::{serve, serve_with_config, Config, Faults};
 
     async fn run_bonsai(bonsai_api_url: String, bonsai_api_key: String, elf: &[u8]) -> Result<()> {
         let client =
             bonsai_sdk::get_client_from_parts(bonsai_api_url, bonsai_api_key, risc0_zkvm::VERSION)
                 .await?;
+        bonsai_sdk::check_compat(client.clone()).await?;
 
         // Compute the image_id, then upload the ELF with the image_id as its key.
         // TODO: it would be nice if `bonsai_sdk::upload_img` only took the ELF
@@ -116,25 +204,37 @@ mod test {
         // Start a session running the prover
         let session =
             bonsai_sdk::create_session(client.clone(), image_id, input_id, receipts_ids).await?;
+        let res = bonsai_sdk::session_poll_until_done(
+            client.clone(),
+            session.clone(),
+            Duration::from_secs(300),
+            Duration::from_secs(1),
+        )
+        .await?;
+        if res.status != "SUCCEEDED" {
+            bail!("Error");
+        }
+
+        // Download the receipt, containing the output
+        let receipt_url = res
+            .receipt_url
+            .expect("API error, missing receipt on completed session");
+        bonsai_sdk::download(client.clone(), receipt_url)
+            .await
+            .unwrap();
+
+        // Convert the receipt to a SNARK, which has a canned seal since
+        // the mock only produces fake receipts.
+        let snark = bonsai_sdk::create_snark(client.clone(), session.uuid.clone()).await?;
         loop {
-            let res = bonsai_sdk::session_status(client.clone(), session.clone()).await?;
-            if res.status == "RUNNING" {
-                std::thread::sleep(Duration::from_secs(15));
-                continue;
-            }
-            if res.status == "SUCCEEDED" {
-                // Download the receipt, containing the output
-                let receipt_url = res
-                    .receipt_url
-                    .expect("API error, missing receipt on completed session");
-                bonsai_sdk::download(client.clone(), receipt_url)
-                    .await
-                    .unwrap();
-            } else {
-                bail!("Error");
+            let res = bonsai_sdk::snark_status(client.clone(), snark.clone()).await?;
+            match res.receipt()? {
+                Some(receipt) => {
+                    Groth16Seal::try_from(receipt)?;
+                    break;
+                }
+                None => std::thread::sleep(Duration::from_secs(1)),
             }
-
-            break;
         }
 
         Ok(())
@@ -183,4 +283,256 @@ mod test {
 
         local_bonsai_handle.abort();
     }
+
+    #[tokio::test]
+    async fn local_bonsai_execute_only() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let local_addr = listener.local_addr().unwrap();
+        let local_bonsai_handle = tokio::spawn(async move { serve(listener).await });
+        tokio::time::sleep(Duration::from_secs(1)).await;
+
+        let client = bonsai_sdk::get_client_from_parts(
+            format!("http://{local_addr}"),
+            "test_key".to_string(),
+            risc0_zkvm::VERSION,
+        )
+        .await
+        .unwrap();
+        let image_id = bonsai_sdk::upload_img_from_elf(client.clone(), HELLO_COMMIT_ELF.to_vec())
+            .await
+            .unwrap();
+        assert_eq!(
+            image_id,
+            hex::encode(compute_image_id(HELLO_COMMIT_ELF).unwrap())
+        );
+        let input_id = bonsai_sdk::upload_input(client.clone(), vec![])
+            .await
+            .unwrap();
+
+        let session = bonsai_sdk::create_session_with_opts(
+            client.clone(),
+            image_id,
+            input_id,
+            vec![],
+            SessionOpts {
+                execute_only: true,
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+        let res = bonsai_sdk::session_poll_until_done(
+            client.clone(),
+            session.clone(),
+            Duration::from_secs(300),
+            Duration::from_millis(200),
+        )
+        .await
+        .unwrap();
+        assert_eq!(res.status, "SUCCEEDED");
+        assert_eq!(res.receipt_url, None);
+        let stats = res.stats.unwrap();
+        assert!(stats.cycles > 0 && stats.cycles <= stats.total_cycles);
+
+        let journal = bonsai_sdk::session_exec_only_journal(client.clone(), session.clone())
+            .await
+            .unwrap();
+        assert_eq!(journal, b"hello world");
+
+        // Execute-only sessions have no receipt to convert.
+        assert!(bonsai_sdk::create_snark(client, session.uuid)
+            .await
+            .is_err());
+
+        local_bonsai_handle.abort();
+    }
+
+    #[tokio::test]
+    async fn local_bonsai_api_keys() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let local_addr = listener.local_addr().unwrap();
+        let config = Config {
+            api_keys: vec!["secret".to_string()],
+            ..Default::default()
+        };
+        let local_bonsai_handle =
+            tokio::spawn(async move { serve_with_config(listener, config).await });
+        tokio::time::sleep(Duration::from_secs(1)).await;
+
+        let client = bonsai_sdk::get_client_from_parts(
+            format!("http://{local_addr}"),
+            "wrong".to_string(),
+            risc0_zkvm::VERSION,
+        )
+        .await
+        .unwrap();
+        assert!(matches!(
+            bonsai_sdk::upload_input(client, vec![]).await,
+            Err(SdkErr::Unauthorized(_))
+        ));
+
+        run_bonsai(
+            format!("http://{local_addr}"),
+            "secret".to_string(),
+            HELLO_COMMIT_ELF,
+        )
+        .await
+        .unwrap();
+
+        local_bonsai_handle.abort();
+    }
+
+    #[tokio::test]
+    async fn local_bonsai_workers() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let local_addr = listener.local_addr().unwrap();
+        let config = Config {
+            faults: Faults::default().proving_delay(Duration::from_secs(2)),
+            workers: 1,
+            ..Default::default()
+        };
+        let local_bonsai_handle =
+            tokio::spawn(async move { serve_with_config(listener, config).await });
+        tokio::time::sleep(Duration::from_secs(1)).await;
+
+        let client = bonsai_sdk::get_client_from_parts(
+            format!("http://{local_addr}"),
+            "test_key".to_string(),
+            risc0_zkvm::VERSION,
+        )
+        .await
+        .unwrap();
+        let image_id = hex::encode(compute_image_id(HELLO_COMMIT_ELF).unwrap());
+        bonsai_sdk::upload_img(client.clone(), image_id.clone(), HELLO_COMMIT_ELF.to_vec())
+            .await
+            .unwrap();
+        let input_id = bonsai_sdk::upload_input(client.clone(), vec![])
+            .await
+            .unwrap();
+
+        let mut sessions = vec![];
+        for _ in 0..2 {
+            let session = bonsai_sdk::create_session(
+                client.clone(),
+                image_id.clone(),
+                input_id.clone(),
+                vec![],
+            )
+            .await
+            .unwrap();
+            sessions.push(session);
+        }
+
+        // The second session waits for the only worker.
+        tokio::time::sleep(Duration::from_millis(500)).await;
+        let res = bonsai_sdk::session_status(client.clone(), sessions[1].clone())
+            .await
+            .unwrap();
+        assert_eq!(res.status, "RUNNING");
+        assert_eq!(res.state.as_deref(), Some("Setup"));
+
+        for session in sessions {
+            let res = bonsai_sdk::session_poll_until_done(
+                client.clone(),
+                session,
+                Duration::from_secs(300),
+                Duration::from_millis(200),
+            )
+            .await
+            .unwrap();
+            assert_eq!(res.status, "SUCCEEDED");
+            assert_eq!(res.state, None);
+        }
+
+        local_bonsai_handle.abort();
+    }
+
+    #[tokio::test]
+    async fn local_bonsai_priorities() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let local_addr = listener.local_addr().unwrap();
+        let config = Config {
+            faults: Faults::default().proving_delay(Duration::from_secs(2)),
+            workers: 1,
+            ..Default::default()
+        };
+        let local_bonsai_handle =
+            tokio::spawn(async move { serve_with_config(listener, config).await });
+        tokio::time::sleep(Duration::from_secs(1)).await;
+
+        let client = bonsai_sdk::get_client_from_parts(
+            format!("http://{local_addr}"),
+            "test_key".to_string(),
+            risc0_zkvm::VERSION,
+        )
+        .await
+        .unwrap();
+        let image_id = hex::encode(compute_image_id(HELLO_COMMIT_ELF).unwrap());
+        bonsai_sdk::upload_img(client.clone(), image_id.clone(), HELLO_COMMIT_ELF.to_vec())
+            .await
+            .unwrap();
+        let input_id = bonsai_sdk::upload_input(client.clone(), vec![])
+            .await
+            .unwrap();
+
+        // The first session takes the only worker, and the others wait for it.
+        let mut sessions = vec![];
+        let past = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap()
+            .as_secs();
+        for (label, priority, deadline) in [
+            ("first", 0, None),
+            ("low", 0, None),
+            ("high", 5, None),
+            ("late", 0, Some(past)),
+        ] {
+            let metadata = SessionMetadata {
+                labels: [("name".to_string(), label.to_string())].into(),
+                priority,
+                deadline,
+            };
+            let opts = SessionOpts {
+                metadata,
+                ..Default::default()
+            };
+            let session = bonsai_sdk::create_session_with_opts(
+                client.clone(),
+                image_id.clone(),
+                input_id.clone(),
+                vec![],
+                opts,
+            )
+            .await
+            .unwrap();
+            sessions.push(session);
+        }
+
+        let poll = |session| {
+            bonsai_sdk::session_poll_until_done(
+                client.clone(),
+                session,
+                Duration::from_secs(300),
+                Duration::from_millis(200),
+            )
+        };
+
+        // The high priority session runs before the low priority one.
+        let res = poll(sessions[2].clone()).await.unwrap();
+        assert_eq!(res.status, "SUCCEEDED");
+        assert_eq!(res.metadata.unwrap().priority, 5);
+        let res = bonsai_sdk::session_status(client.clone(), sessions[1].clone())
+            .await
+            .unwrap();
+        assert_eq!(res.status, "RUNNING");
+        assert_eq!(res.metadata.unwrap().labels["name"], "low");
+
+        // The session past its deadline is not run.
+        let res = poll(sessions[3].clone()).await.unwrap();
+        assert_eq!(res.status, "TIMED_OUT");
+        let res = poll(sessions[1].clone()).await.unwrap();
+        assert_eq!(res.status, "SUCCEEDED");
+
+        local_bonsai_handle.abort();
+    }
 }
@@ -12,10 +12,26 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use bonsai_rest_api_mock::{serve_with_config, Config};
 use tokio::net::TcpListener;
 
 #[tokio::main]
 async fn main() {
     let listener = TcpListener::bind("0.0.0.0:8081").await.unwrap();
-    let _ = bonsai_rest_api_mock::serve(listener).await;
+    let mut config = Config::default();
+    if let Ok(workers) = std::env::var("BONSAI_MOCK_WORKERS") {
+        config.workers = workers
+            .parse()
+            .expect("BONSAI_MOCK_WORKERS must be a number of workers");
+    }
+    if let Ok(keys) = std::env::var("BONSAI_MOCK_API_KEYS") {
+        config.api_keys = keys.split(',').map(str::to_string).collect();
+    }
+    // Persist the state of the mock across restarts if a database is given.
+    #[cfg(feature = "sled")]
+    if let Ok(path) = std::env::var("BONSAI_MOCK_DB") {
+        let storage = bonsai_rest_api_mock::SledStorage::open(path).unwrap();
+        config.storage = std::sync::Arc::new(storage);
+    }
+    let _ = serve_with_config(listener, config).await;
 }
@@ -13,15 +13,23 @@
 // limitations under the License.
 
 use std::{
+    cmp::{Ordering, Reverse},
+    collections::BinaryHeap,
     fmt,
-    sync::{Arc, RwLock},
+    sync::{Arc, Mutex, RwLock},
+    time::{Duration, SystemTime, UNIX_EPOCH},
 };
 
 use anyhow::Context;
+use bonsai_sdk::alpha::responses::{SessionMetadata, SessionStats, SnarkReceipt};
 use risc0_zkvm::{
-    default_executor, sha::Digest, ExecutorEnv, InnerReceipt, MaybePruned, Receipt, ReceiptClaim,
+    default_executor,
+    sha::{Digest, Digestible},
+    ExecutorEnv, Groth16Seal, Groth16SealRef, InnerReceipt, MaybePruned, Receipt, ReceiptClaim,
 };
-use tokio::sync::mpsc;
+#[cfg(feature = "prove")]
+use risc0_zkvm::{get_prover_server, ProverOpts};
+use tokio::sync::{mpsc, Semaphore};
 
 use crate::{error::Error, state::BonsaiState};
 
@@ -31,11 +39,32 @@ pub(crate) struct Task {
     pub image_id: String,
     pub input_id: String,
     pub assumptions: Vec<String>,
+    pub execute_only: bool,
+    pub metadata: SessionMetadata,
+}
+
+impl Task {
+    /// Returns whether the deadline of the session has passed.
+    fn expired(&self) -> bool {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |elapsed| elapsed.as_secs());
+        self.metadata
+            .deadline
+            .is_some_and(|deadline| now >= deadline)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub(crate) struct SnarkTask {
+    pub snark_id: String,
+    pub session_id: String,
 }
 
 #[derive(Debug)]
 pub(crate) enum ProverMessage {
     RunSession(Task),
+    RunSnark(SnarkTask),
 }
 
 impl fmt::Display for ProverMessage {
@@ -44,6 +73,9 @@ impl fmt::Display for ProverMessage {
             ProverMessage::RunSession(task) => {
                 write!(f, "ProverMessage::RunSession: {{ task: {:?} }}", task)
             }
+            ProverMessage::RunSnark(task) => {
+                write!(f, "ProverMessage::RunSnark: {{ task: {:?} }}", task)
+            }
         }
     }
 }
@@ -55,8 +87,14 @@ pub(crate) struct ProverHandle {
 
 impl ProverHandle {
     pub(crate) async fn execute(&self, task: Task) {
-        let msg = ProverMessage::RunSession(task);
+        self.send(ProverMessage::RunSession(task)).await
+    }
 
+    pub(crate) async fn snark(&self, task: SnarkTask) {
+        self.send(ProverMessage::RunSnark(task)).await
+    }
+
+    async fn send(&self, msg: ProverMessage) {
         if (self.sender.send(msg).await).is_err() {
             tracing::info!("receiver dropped");
             assert!(self.sender.is_closed());
@@ -64,99 +102,212 @@ impl ProverHandle {
     }
 }
 
+/// A message waiting for a worker.
+struct Queued {
+    msg: ProverMessage,
+    /// Order of arrival, breaking ties between equally urgent messages.
+    seq: u64,
+}
+
+impl Queued {
+    /// Higher keys are more urgent: higher priorities first, then earlier
+    /// deadlines, then earlier arrivals. SNARK conversions have the default
+    /// priority and no deadline.
+    fn key(&self) -> (u32, Reverse<u64>, Reverse<u64>) {
+        let (priority, deadline) = match &self.msg {
+            ProverMessage::RunSession(task) => (
+                task.metadata.priority,
+                task.metadata.deadline.unwrap_or(u64::MAX),
+            ),
+            ProverMessage::RunSnark(_) => (0, u64::MAX),
+        };
+        (priority, Reverse(deadline), Reverse(self.seq))
+    }
+}
+
+impl PartialEq for Queued {
+    fn eq(&self, other: &Self) -> bool {
+        self.key() == other.key()
+    }
+}
+
+impl Eq for Queued {}
+
+impl PartialOrd for Queued {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Queued {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.key().cmp(&other.key())
+    }
+}
+
+/// Receives the tasks of the mock and runs them in the background, at most
+/// `workers` at a time, most urgent first.
 pub(crate) struct Prover {
     pub(crate) receiver: mpsc::Receiver<ProverMessage>,
-    pub(crate) storage: Arc<RwLock<BonsaiState>>,
+    pub(crate) worker: Worker,
+    pub(crate) workers: Arc<Semaphore>,
+    queue: Arc<Mutex<BinaryHeap<Queued>>>,
+    next_seq: u64,
 }
 
 impl Prover {
     pub(crate) fn new(
         receiver: mpsc::Receiver<ProverMessage>,
         storage: Arc<RwLock<BonsaiState>>,
+        proving_delay: Duration,
+        workers: usize,
     ) -> Self {
-        Prover { receiver, storage }
+        Prover {
+            receiver,
+            worker: Worker {
+                storage,
+                proving_delay,
+            },
+            workers: Arc::new(Semaphore::new(workers.max(1))),
+            queue: Arc::new(Mutex::new(BinaryHeap::new())),
+            next_seq: 0,
+        }
+    }
+
+    pub(crate) async fn run(&mut self) {
+        while let Some(msg) = self.receiver.recv().await {
+            tracing::info!("Receiver: {}", &msg);
+            // Tasks are queued here rather than on the channel, so that
+            // creating a session never waits for a busy worker. Each spawned
+            // task waits for a worker, then runs the most urgent message,
+            // which is not necessarily the one it was spawned for.
+            self.queue.lock().unwrap().push(Queued {
+                msg,
+                seq: self.next_seq,
+            });
+            self.next_seq += 1;
+            let worker = self.worker.clone();
+            let workers = Arc::clone(&self.workers);
+            let queue = Arc::clone(&self.queue);
+            tokio::spawn(async move {
+                let Ok(_permit) = workers.acquire_owned().await else {
+                    return;
+                };
+                let Some(Queued { msg, .. }) = queue.lock().unwrap().pop() else {
+                    return;
+                };
+                if let Err(err) = worker.run(&msg).await {
+                    tracing::error!("Task {} failed to report its status! - {:?}", msg, err)
+                }
+            });
+        }
+    }
+}
+
+#[derive(Clone)]
+pub(crate) struct Worker {
+    pub(crate) storage: Arc<RwLock<BonsaiState>>,
+    pub(crate) proving_delay: Duration,
+}
+
+impl Worker {
+    async fn run(&self, msg: &ProverMessage) -> Result<(), Error> {
+        match self.handle_message(msg).await {
+            Ok(_) => tracing::info!("Task done!"),
+            Err(err) => {
+                match msg {
+                    ProverMessage::RunSession(task) => self
+                        .storage
+                        .write()?
+                        .put_session(task.session_id.clone(), "FAILED".to_string())?,
+                    ProverMessage::RunSnark(task) => self
+                        .storage
+                        .write()?
+                        .put_snark(task.snark_id.clone(), "FAILED".to_string())?,
+                };
+                tracing::error!("Task {} failed! - {:?}", msg, err)
+            }
+        }
+        Ok(())
     }
 
-    pub async fn handle_message(&mut self, msg: &ProverMessage) -> Result<(), Error> {
+    pub async fn handle_message(&self, msg: &ProverMessage) -> Result<(), Error> {
         match msg {
             ProverMessage::RunSession(task) => {
+                if !self.start(task)? {
+                    tracing::info!("Skipping aborted or expired task");
+                    return Ok(());
+                }
                 tracing::info!("Running task...");
                 let image = self.get_image(task).await?;
                 let input = self.get_input(task).await?;
                 let receipts = self.get_receipts(task).await?;
-                let elf = image.as_slice();
-
-                let mut env = ExecutorEnv::builder();
-                for receipt in receipts {
-                    if receipt.len() < 1 {
-                        continue;
-                    }
-                    let deserialized_receipt: Receipt = bincode::deserialize(&receipt)?;
-                    env.add_assumption(deserialized_receipt);
+                let (receipt, stats) =
+                    tokio::task::spawn_blocking(move || execute(&image, &input, receipts))
+                        .await??;
+                if !task.execute_only {
+                    tokio::time::sleep(self.proving_delay).await;
                 }
-
-                let env = env
-                    .write_slice(&input)
-                    .session_limit(None)
-                    .segment_limit_po2(20)
-                    .build()
-                    .map_err(|e| {
-                        anyhow::anyhow!("failed to build executor environment: {:?}", e)
+                let mut storage = self.storage.write()?;
+                // The session may have been stopped while it was executing.
+                if storage.get_session(&task.session_id)?.as_deref() == Some("ABORTED") {
+                    return Ok(());
+                }
+                if task.expired() {
+                    storage.put_session(task.session_id.clone(), "TIMED_OUT".to_string())?;
+                    return Ok(());
+                }
+                storage.put_session_stats(task.session_id.clone(), stats)?;
+                if task.execute_only {
+                    storage.put_journal(task.session_id.clone(), receipt.journal.bytes)?;
+                } else {
+                    let receipt_bytes = bincode::serialize(&receipt)?;
+                    storage.put_receipt(task.session_id.clone(), receipt_bytes)?;
+                }
+                storage.put_session(task.session_id.clone(), "SUCCEEDED".to_string())?;
+            }
+            ProverMessage::RunSnark(task) => {
+                tracing::info!("Running SNARK task...");
+                let receipt = self
+                    .storage
+                    .read()?
+                    .get_receipt(&task.session_id)?
+                    .ok_or_else(|| {
+                        anyhow::anyhow!("Failed to get receipt for ID: {:?}", task.session_id)
                     })?;
-                let exec = default_executor();
-                let session = exec
-                    .execute(env, elf)
-                    .context("Executor failed to generate a successful session")?;
-
-                let receipt = Receipt {
-                    inner: InnerReceipt::Fake {
-                        claim: ReceiptClaim {
-                            pre: MaybePruned::Pruned(Digest::ZERO),
-                            post: MaybePruned::Pruned(Digest::ZERO),
-                            exit_code: session.exit_code,
-                            input: Digest::ZERO,
-                            output: None.into(),
-                        },
-                    },
-                    journal: session.journal,
-                };
-                let receipt_bytes = bincode::serialize(&receipt)?;
-                self.storage
-                    .write()?
-                    .put_receipt(task.session_id.clone(), receipt_bytes);
-                self.storage
-                    .write()?
-                    .put_session(task.session_id.clone(), "SUCCEEDED".to_string());
+                let receipt: Receipt = bincode::deserialize(&receipt)?;
+                let snark_receipt =
+                    tokio::task::spawn_blocking(move || to_snark(receipt)).await??;
+                tokio::time::sleep(self.proving_delay).await;
+                let mut storage = self.storage.write()?;
+                storage.put_snark_receipt(task.snark_id.clone(), snark_receipt)?;
+                storage.put_snark(task.snark_id.clone(), "SUCCEEDED".to_string())?;
             }
         }
 
         Ok(())
     }
 
-    pub(crate) async fn run(&mut self) -> Result<(), Error> {
-        while let Some(msg) = self.receiver.recv().await {
-            tracing::info!("Receiver: {}", &msg);
-            match self.handle_message(&msg).await {
-                Ok(_) => tracing::info!("Task done!"),
-                Err(err) => {
-                    match &msg {
-                        ProverMessage::RunSession(task) => self
-                            .storage
-                            .write()?
-                            .put_session(task.session_id.clone(), "FAILED".to_string()),
-                    };
-                    tracing::error!("Task {} failed! - {:?}", msg, err)
-                }
-            }
+    /// Moves a pending session to the `Executor` state, unless it was aborted
+    /// or its deadline passed while it waited for a worker.
+    fn start(&self, task: &Task) -> Result<bool, Error> {
+        let mut storage = self.storage.write()?;
+        if storage.get_session(&task.session_id)?.as_deref() == Some("ABORTED") {
+            return Ok(false);
         }
-        Ok(())
+        if task.expired() {
+            storage.put_session(task.session_id.clone(), "TIMED_OUT".to_string())?;
+            return Ok(false);
+        }
+        storage.put_session_state(task.session_id.clone(), "Executor".to_string())?;
+        Ok(true)
     }
 
     async fn get_image(&self, task: &Task) -> Result<Vec<u8>, Error> {
         Ok(self
             .storage
             .read()?
-            .get_image(&task.image_id)
+            .get_image(&task.image_id)?
             .ok_or_else(|| anyhow::anyhow!("Failed to get image for ID: {:?}", task.image_id))?)
     }
 
@@ -164,7 +315,7 @@ impl Prover {
         Ok(self
             .storage
             .read()?
-            .get_input(&task.input_id)
+            .get_input(&task.input_id)?
             .ok_or_else(|| anyhow::anyhow!("Failed to get input for ID: {:?}", task.input_id))?)
     }
 
@@ -174,7 +325,7 @@ impl Prover {
             let receipt = self
                 .storage
                 .read()?
-                .get_receipt(receipt_id)
+                .get_receipt(receipt_id)?
                 .ok_or_else(|| {
                     anyhow::anyhow!("Failed to get input for ID: {:?}", task.input_id)
                 })?;
@@ -183,3 +334,90 @@ impl Prover {
         Ok(assumptions)
     }
 }
+
+/// Executes `elf` with the given input and assumptions, returning a fake
+/// receipt of the session and its cycle counts.
+fn execute(
+    elf: &[u8],
+    input: &[u8],
+    receipts: Vec<Vec<u8>>,
+) -> Result<(Receipt, SessionStats), Error> {
+    let mut env = ExecutorEnv::builder();
+    for receipt in receipts {
+        if receipt.len() < 1 {
+            continue;
+        }
+        let deserialized_receipt: Receipt = bincode::deserialize(&receipt)?;
+        env.add_assumption(deserialized_receipt);
+    }
+
+    let env = env
+        .write_slice(input)
+        .session_limit(None)
+        .segment_limit_po2(20)
+        .build()
+        .map_err(|e| anyhow::anyhow!("failed to build executor environment: {:?}", e))?;
+    let exec = default_executor();
+    let session = exec
+        .execute(env, elf)
+        .context("Executor failed to generate a successful session")?;
+
+    let stats = SessionStats {
+        segments: session.segments.len(),
+        total_cycles: session.total_cycles,
+        cycles: session.user_cycles,
+    };
+    let receipt = Receipt {
+        inner: InnerReceipt::Fake {
+            claim: ReceiptClaim {
+                pre: MaybePruned::Pruned(Digest::ZERO),
+                post: MaybePruned::Pruned(Digest::ZERO),
+                exit_code: session.exit_code,
+                input: Digest::ZERO,
+                output: None.into(),
+            },
+        },
+        journal: session.journal,
+    };
+    Ok((receipt, stats))
+}
+
+/// Converts a receipt into the [SnarkReceipt] returned by Bonsai.
+///
+/// Fake receipts, such as those of the sessions executed by this mock, get a
+/// canned seal of zeros. Other receipts are wrapped by the identity_p254
+/// recursion program and the Groth16 prover.
+fn to_snark(receipt: Receipt) -> Result<SnarkReceipt, Error> {
+    let claim = receipt.get_claim().map_err(anyhow::Error::from)?;
+    let snark = match &receipt.inner {
+        InnerReceipt::Fake { .. } => Groth16Seal {
+            a: vec![vec![0; 32]; 2],
+            b: vec![vec![vec![0; 32]; 2]; 2],
+            c: vec![vec![0; 32]; 2],
+        },
+        InnerReceipt::Compact(compact) => Groth16SealRef::from_bytes(&compact.seal)?.to_seal(),
+        inner => wrap(inner)?,
+    };
+    Ok(SnarkReceipt {
+        snark,
+        post_state_digest: claim.post.digest().as_bytes().to_vec(),
+        journal: receipt.journal.bytes,
+    })
+}
+
+#[cfg(feature = "prove")]
+fn wrap(inner: &InnerReceipt) -> Result<Groth16Seal, Error> {
+    let prover = get_prover_server(&ProverOpts::default())?;
+    let succinct = match inner {
+        InnerReceipt::Succinct(succinct) => succinct.clone(),
+        InnerReceipt::Composite(composite) => prover.compress(composite)?,
+        _ => return Err(anyhow::anyhow!("Unsupported receipt for SNARK conversion").into()),
+    };
+    let compact = prover.compact(&succinct)?;
+    Ok(Groth16SealRef::from_bytes(&compact.seal)?.to_seal())
+}
+
+#[cfg(not(feature = "prove"))]
+fn wrap(_inner: &InnerReceipt) -> Result<Groth16Seal, Error> {
+    Err(anyhow::anyhow!("SNARK conversion of real receipts requires the `prove` feature").into())
+}
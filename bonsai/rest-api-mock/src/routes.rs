@@ -17,18 +17,21 @@ use std::vec;
 use axum::{
     body::Bytes,
     extract::{Path, State},
+    http::StatusCode,
     Extension, Json,
 };
-use bonsai_sdk::alpha::responses::{
-    CreateSessRes, ImgUploadRes, ProofReq, SessionStatusRes, SnarkReceipt, SnarkReq,
-    SnarkStatusRes, UploadRes,
+use bonsai_sdk::alpha::{
+    responses::{
+        CreateSessRes, ImgUploadRes, ProofReq, SessionStatusRes, SnarkReq, SnarkStatusRes,
+        UploadRes, VersionInfo,
+    },
+    API_VERSION,
 };
-use risc0_zkvm::{Groth16Seal, Receipt};
 use tracing::info;
 
 use crate::{
     error::Error,
-    prover::{ProverHandle, Task},
+    prover::{ProverHandle, SnarkTask, Task},
     state::AppState,
 };
 
@@ -37,7 +40,7 @@ pub(crate) async fn get_image_upload(
     Path(image_id): Path<String>,
 ) -> Result<Json<ImgUploadRes>, Error> {
     let state = &s.read()?;
-    match state.get_image(&image_id) {
+    match state.get_image(&image_id)? {
         Some(_) => Err(Error::ImageIdExists),
         None => Ok(Json(ImgUploadRes {
             url: format!("{}/images/{}", state.local_url, image_id),
@@ -45,12 +48,22 @@ pub(crate) async fn get_image_upload(
     }
 }
 
+pub(crate) async fn head_image(
+    State(s): State<AppState>,
+    Path(image_id): Path<String>,
+) -> Result<StatusCode, Error> {
+    match s.read()?.get_image(&image_id)? {
+        Some(_) => Ok(StatusCode::OK),
+        None => Ok(StatusCode::NOT_FOUND),
+    }
+}
+
 pub(crate) async fn put_image_upload(
     State(s): State<AppState>,
     Path(image_id): Path<String>,
     body: Bytes,
 ) -> Result<(), Error> {
-    s.write()?.put_image(image_id.clone(), body.to_vec());
+    s.write()?.put_image(image_id.clone(), body.to_vec())?;
     info!("ImageID {image_id} uploaded");
     Ok(())
 }
@@ -69,7 +82,7 @@ pub(crate) async fn put_input_upload(
     Path(input_id): Path<String>,
     body: Bytes,
 ) -> Result<(), Error> {
-    s.write()?.put_input(input_id, body.to_vec());
+    s.write()?.put_input(input_id, body.to_vec())?;
     Ok(())
 }
 
@@ -79,13 +92,20 @@ pub(crate) async fn create_session(
     Json(request): Json<ProofReq>,
 ) -> Result<Json<CreateSessRes>, Error> {
     let session_id = uuid::Uuid::new_v4();
-    s.write()?
-        .put_session(session_id.to_string(), "RUNNING".to_string());
+    {
+        // Sessions wait in the `Setup` state until a worker picks them up.
+        let mut storage = s.write()?;
+        storage.put_session(session_id.to_string(), "RUNNING".to_string())?;
+        storage.put_session_state(session_id.to_string(), "Setup".to_string())?;
+        storage.put_session_metadata(session_id.to_string(), &request.metadata)?;
+    }
     let task = Task {
         image_id: request.img,
         input_id: request.input,
         session_id: session_id.to_string(),
         assumptions: request.assumptions,
+        execute_only: request.execute_only,
+        metadata: request.metadata,
     };
     prover_handle.execute(task).await;
 
@@ -100,9 +120,15 @@ pub(crate) async fn session_status(
 ) -> Result<Json<SessionStatusRes>, Error> {
     let storage = s.read()?;
     let status = storage
-        .get_session(&session_id)
+        .get_session(&session_id)?
         .ok_or_else(|| anyhow::anyhow!("Session not found for session id: {:?}", &session_id))?;
-    let receipt = storage.get_receipt(&session_id);
+    let state = match status.as_str() {
+        "RUNNING" => storage.get_session_state(&session_id)?,
+        _ => None,
+    };
+    let stats = storage.get_session_stats(&session_id)?;
+    let metadata = storage.get_session_metadata(&session_id)?;
+    let receipt = storage.get_receipt(&session_id)?;
     match receipt {
         Some(_) => Ok(Json(SessionStatusRes {
             status,
@@ -110,24 +136,71 @@ pub(crate) async fn session_status(
             error_msg: None,
             state: None,
             elapsed_time: None,
-            stats: None,
+            stats,
+            metadata,
         })),
         None => Ok(Json(SessionStatusRes {
             status,
             receipt_url: None,
             error_msg: None,
-            state: None,
+            state,
             elapsed_time: None,
-            stats: None,
+            stats,
+            metadata,
         })),
     }
 }
 
+pub(crate) async fn get_exec_only_journal(
+    State(s): State<AppState>,
+    Path(session_id): Path<String>,
+) -> Result<Vec<u8>, Error> {
+    let storage = s.read()?;
+    let journal = storage
+        .get_journal(&session_id)?
+        .ok_or_else(|| anyhow::anyhow!("Journal not found for session id: {:?}", &session_id))?;
+    Ok(journal)
+}
+
+pub(crate) async fn stop_session(
+    State(s): State<AppState>,
+    Path(session_id): Path<String>,
+) -> Result<(), Error> {
+    let mut storage = s.write()?;
+    let status = storage
+        .get_session(&session_id)?
+        .ok_or_else(|| anyhow::anyhow!("Session not found for session id: {:?}", &session_id))?;
+    if status == "RUNNING" {
+        storage.put_session(session_id.clone(), "ABORTED".to_string())?;
+        info!("Session {session_id} aborted");
+    }
+    Ok(())
+}
+
 pub(crate) async fn create_snark(
+    Extension(prover_handle): Extension<ProverHandle>,
+    State(s): State<AppState>,
     Json(request): Json<SnarkReq>,
 ) -> Result<Json<CreateSessRes>, Error> {
+    let snark_id = uuid::Uuid::new_v4();
+    {
+        let mut storage = s.write()?;
+        storage.get_receipt(&request.session_id)?.ok_or_else(|| {
+            anyhow::anyhow!(
+                "Receipt not found for session id: {:?}",
+                &request.session_id
+            )
+        })?;
+        storage.put_snark(snark_id.to_string(), "RUNNING".to_string())?;
+    }
+    let task = SnarkTask {
+        snark_id: snark_id.to_string(),
+        session_id: request.session_id,
+    };
+    prover_handle.snark(task).await;
+
     Ok(Json(CreateSessRes {
-        uuid: request.session_id,
+        uuid: snark_id.to_string(),
     }))
 }
 
@@ -136,33 +209,14 @@ pub(crate) async fn snark_status(
     Path(snark_id): Path<String>,
 ) -> Result<Json<SnarkStatusRes>, Error> {
     let storage = s.read()?;
-    storage
-        .get_session(&snark_id)
+    let status = storage
+        .get_snark(&snark_id)?
         .ok_or_else(|| anyhow::anyhow!("Snark status not found for snark id: {:?}", &snark_id))?;
-    let receipt = storage.get_receipt(&snark_id);
-    match receipt {
-        Some(bytes) => {
-            let receipt: Receipt = bincode::deserialize(&bytes)?;
-            Ok(Json(SnarkStatusRes {
-                status: "SUCCEEDED".to_string(),
-                output: Some(SnarkReceipt {
-                    snark: Groth16Seal {
-                        a: vec![],
-                        b: vec![],
-                        c: vec![],
-                    },
-                    post_state_digest: vec![],
-                    journal: receipt.journal.bytes,
-                }),
-                error_msg: None,
-            }))
-        }
-        None => Ok(Json(SnarkStatusRes {
-            status: "RUNNING".to_string(),
-            output: None,
-            error_msg: None,
-        })),
-    }
+    Ok(Json(SnarkStatusRes {
+        status,
+        output: storage.get_snark_receipt(&snark_id)?,
+        error_msg: None,
+    }))
 }
 
 pub(crate) async fn get_receipt(
@@ -171,11 +225,18 @@ pub(crate) async fn get_receipt(
 ) -> Result<Vec<u8>, Error> {
     let storage = s.read()?;
     let receipt = storage
-        .get_receipt(&session_id)
+        .get_receipt(&session_id)?
         .ok_or_else(|| anyhow::anyhow!("Receipt not found for session id: {:?}", &session_id))?;
     Ok(receipt)
 }
 
+pub(crate) async fn get_version() -> Json<VersionInfo> {
+    Json(VersionInfo {
+        risc0_zkvm: vec![risc0_zkvm::VERSION.to_string()],
+        api: vec![API_VERSION.to_string()],
+    })
+}
+
 pub(crate) async fn get_receipt_upload(
     State(s): State<AppState>,
 ) -> Result<Json<UploadRes>, Error> {
@@ -192,6 +253,6 @@ pub(crate) async fn put_receipt(
     Path(receipt_id): Path<String>,
     body: Bytes,
 ) -> Result<(), Error> {
-    s.write()?.put_receipt(receipt_id.clone(), body.to_vec());
+    s.write()?.put_receipt(receipt_id.clone(), body.to_vec())?;
     Ok(())
 }
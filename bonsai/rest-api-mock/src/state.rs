@@ -12,58 +12,152 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use std::{
-    collections::HashMap,
-    sync::{Arc, RwLock},
+use std::sync::{Arc, RwLock};
+
+use bonsai_sdk::alpha::responses::{SessionMetadata, SessionStats, SnarkReceipt};
+
+use crate::{
+    error::Error,
+    storage::{Storage, Table},
 };
 
 pub(crate) type AppState = Arc<RwLock<BonsaiState>>;
 
-#[derive(Clone, Default)]
 pub(crate) struct BonsaiState {
     pub(crate) local_url: String,
-    // ImageID - MemoryImage
-    pub(crate) images: HashMap<String, Vec<u8>>,
-    // InputID - input
-    pub(crate) inputs: HashMap<String, Vec<u8>>,
-    // SessionID - Status
-    pub(crate) sessions: HashMap<String, String>,
-    // SessionID - Receipts
-    pub(crate) receipts: HashMap<String, Vec<u8>>,
+    pub(crate) storage: Arc<dyn Storage>,
 }
 
 impl BonsaiState {
-    pub(crate) fn new(local_url: String) -> Self {
-        Self {
-            local_url,
-            images: HashMap::new(),
-            inputs: HashMap::new(),
-            sessions: HashMap::new(),
-            receipts: HashMap::new(),
+    pub(crate) fn new(local_url: String, storage: Arc<dyn Storage>) -> Self {
+        Self { local_url, storage }
+    }
+    fn put_string(&self, table: Table, key: &str, value: String) -> Result<(), Error> {
+        Ok(self.storage.put(table, key, value.into_bytes())?)
+    }
+    fn get_string(&self, table: Table, key: &str) -> Result<Option<String>, Error> {
+        match self.storage.get(table, key)? {
+            Some(bytes) => Ok(Some(String::from_utf8(bytes).map_err(anyhow::Error::from)?)),
+            None => Ok(None),
         }
     }
-    pub(crate) fn put_image(&mut self, image_id: String, image: Vec<u8>) -> Option<Vec<u8>> {
-        self.images.insert(image_id, image)
+    pub(crate) fn put_image(&mut self, image_id: String, image: Vec<u8>) -> Result<(), Error> {
+        Ok(self.storage.put(Table::Images, &image_id, image)?)
+    }
+    pub(crate) fn get_image(&self, image_id: impl AsRef<str>) -> Result<Option<Vec<u8>>, Error> {
+        Ok(self.storage.get(Table::Images, image_id.as_ref())?)
     }
-    pub(crate) fn get_image(&self, image_id: impl AsRef<str>) -> Option<Vec<u8>> {
-        self.images.get(image_id.as_ref()).cloned()
+    pub(crate) fn put_input(&mut self, input_id: String, input: Vec<u8>) -> Result<(), Error> {
+        Ok(self.storage.put(Table::Inputs, &input_id, input)?)
     }
-    pub(crate) fn put_input(&mut self, input_id: String, input: Vec<u8>) -> Option<Vec<u8>> {
-        self.inputs.insert(input_id, input)
+    pub(crate) fn get_input(&self, input_id: impl AsRef<str>) -> Result<Option<Vec<u8>>, Error> {
+        Ok(self.storage.get(Table::Inputs, input_id.as_ref())?)
     }
-    pub(crate) fn get_input(&self, input_id: impl AsRef<str>) -> Option<Vec<u8>> {
-        self.inputs.get(input_id.as_ref()).cloned()
+    pub(crate) fn put_session(&mut self, session_id: String, status: String) -> Result<(), Error> {
+        self.put_string(Table::Sessions, &session_id, status)
     }
-    pub(crate) fn put_session(&mut self, session_id: String, status: String) -> Option<String> {
-        self.sessions.insert(session_id, status)
+    pub(crate) fn get_session(&self, session_id: impl AsRef<str>) -> Result<Option<String>, Error> {
+        self.get_string(Table::Sessions, session_id.as_ref())
     }
-    pub(crate) fn get_session(&self, session_id: impl AsRef<str>) -> Option<String> {
-        self.sessions.get(session_id.as_ref()).cloned()
+    pub(crate) fn put_session_state(
+        &mut self,
+        session_id: String,
+        state: String,
+    ) -> Result<(), Error> {
+        self.put_string(Table::SessionStates, &session_id, state)
     }
-    pub(crate) fn put_receipt(&mut self, session_id: String, receipt: Vec<u8>) -> Option<Vec<u8>> {
-        self.receipts.insert(session_id, receipt)
+    pub(crate) fn get_session_state(
+        &self,
+        session_id: impl AsRef<str>,
+    ) -> Result<Option<String>, Error> {
+        self.get_string(Table::SessionStates, session_id.as_ref())
     }
-    pub(crate) fn get_receipt(&self, session_id: impl AsRef<str>) -> Option<Vec<u8>> {
-        self.receipts.get(session_id.as_ref()).cloned()
+    pub(crate) fn put_session_stats(
+        &mut self,
+        session_id: String,
+        stats: SessionStats,
+    ) -> Result<(), Error> {
+        let bytes = serde_json::to_vec(&stats)?;
+        Ok(self.storage.put(Table::SessionStats, &session_id, bytes)?)
+    }
+    pub(crate) fn get_session_stats(
+        &self,
+        session_id: impl AsRef<str>,
+    ) -> Result<Option<SessionStats>, Error> {
+        match self.storage.get(Table::SessionStats, session_id.as_ref())? {
+            Some(bytes) => Ok(Some(serde_json::from_slice(&bytes)?)),
+            None => Ok(None),
+        }
+    }
+    pub(crate) fn put_session_metadata(
+        &mut self,
+        session_id: String,
+        metadata: &SessionMetadata,
+    ) -> Result<(), Error> {
+        let bytes = serde_json::to_vec(metadata)?;
+        Ok(self
+            .storage
+            .put(Table::SessionMetadata, &session_id, bytes)?)
+    }
+    pub(crate) fn get_session_metadata(
+        &self,
+        session_id: impl AsRef<str>,
+    ) -> Result<Option<SessionMetadata>, Error> {
+        match self
+            .storage
+            .get(Table::SessionMetadata, session_id.as_ref())?
+        {
+            Some(bytes) => Ok(Some(serde_json::from_slice(&bytes)?)),
+            None => Ok(None),
+        }
+    }
+    pub(crate) fn put_journal(
+        &mut self,
+        session_id: String,
+        journal: Vec<u8>,
+    ) -> Result<(), Error> {
+        Ok(self.storage.put(Table::Journals, &session_id, journal)?)
+    }
+    pub(crate) fn get_journal(
+        &self,
+        session_id: impl AsRef<str>,
+    ) -> Result<Option<Vec<u8>>, Error> {
+        Ok(self.storage.get(Table::Journals, session_id.as_ref())?)
+    }
+    pub(crate) fn put_receipt(
+        &mut self,
+        session_id: String,
+        receipt: Vec<u8>,
+    ) -> Result<(), Error> {
+        Ok(self.storage.put(Table::Receipts, &session_id, receipt)?)
+    }
+    pub(crate) fn get_receipt(
+        &self,
+        session_id: impl AsRef<str>,
+    ) -> Result<Option<Vec<u8>>, Error> {
+        Ok(self.storage.get(Table::Receipts, session_id.as_ref())?)
+    }
+    pub(crate) fn put_snark(&mut self, snark_id: String, status: String) -> Result<(), Error> {
+        self.put_string(Table::Snarks, &snark_id, status)
+    }
+    pub(crate) fn get_snark(&self, snark_id: impl AsRef<str>) -> Result<Option<String>, Error> {
+        self.get_string(Table::Snarks, snark_id.as_ref())
+    }
+    pub(crate) fn put_snark_receipt(
+        &mut self,
+        snark_id: String,
+        receipt: SnarkReceipt,
+    ) -> Result<(), Error> {
+        let bytes = serde_json::to_vec(&receipt)?;
+        Ok(self.storage.put(Table::SnarkReceipts, &snark_id, bytes)?)
+    }
+    pub(crate) fn get_snark_receipt(
+        &self,
+        snark_id: impl AsRef<str>,
+    ) -> Result<Option<SnarkReceipt>, Error> {
+        match self.storage.get(Table::SnarkReceipts, snark_id.as_ref())? {
+            Some(bytes) => Ok(Some(serde_json::from_slice(&bytes)?)),
+            None => Ok(None),
+        }
     }
 }
@@ -0,0 +1,158 @@
+// Copyright 2024 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::{collections::HashMap, sync::Mutex};
+
+use anyhow::{anyhow, Result};
+
+/// Table of a [Storage].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Table {
+    /// ELF images, by image ID.
+    Images,
+    /// Inputs, by input ID.
+    Inputs,
+    /// Session status, by session ID.
+    Sessions,
+    /// Proving state of running sessions, by session ID.
+    SessionStates,
+    /// JSON encoded cycle counts of executed sessions, by session ID.
+    SessionStats,
+    /// JSON encoded labels and scheduling hints of sessions, by session ID.
+    SessionMetadata,
+    /// Journals of execute-only sessions, by session ID.
+    Journals,
+    /// Serialized receipts, by session or receipt ID.
+    Receipts,
+    /// SNARK conversion status, by SNARK ID.
+    Snarks,
+    /// JSON encoded SNARK receipts, by SNARK ID.
+    SnarkReceipts,
+}
+
+impl Table {
+    /// Name of the table.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Table::Images => "images",
+            Table::Inputs => "inputs",
+            Table::Sessions => "sessions",
+            Table::SessionStates => "session_states",
+            Table::SessionStats => "session_stats",
+            Table::SessionMetadata => "session_metadata",
+            Table::Journals => "journals",
+            Table::Receipts => "receipts",
+            Table::Snarks => "snarks",
+            Table::SnarkReceipts => "snark_receipts",
+        }
+    }
+}
+
+/// Key-value store backing the state of the mock.
+pub trait Storage: Send + Sync {
+    /// Returns the value of `key` in `table`, if any.
+    fn get(&self, table: Table, key: &str) -> Result<Option<Vec<u8>>>;
+
+    /// Sets the value of `key` in `table`.
+    fn put(&self, table: Table, key: &str, value: Vec<u8>) -> Result<()>;
+}
+
+/// [Storage] in memory, lost when the mock stops.
+#[derive(Default)]
+pub struct MemoryStorage {
+    tables: Mutex<HashMap<(Table, String), Vec<u8>>>,
+}
+
+impl MemoryStorage {
+    /// Creates an empty [MemoryStorage].
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl Storage for MemoryStorage {
+    fn get(&self, table: Table, key: &str) -> Result<Option<Vec<u8>>> {
+        let tables = self.tables.lock().map_err(|_| anyhow!("poisoned lock"))?;
+        Ok(tables.get(&(table, key.to_string())).cloned())
+    }
+
+    fn put(&self, table: Table, key: &str, value: Vec<u8>) -> Result<()> {
+        let mut tables = self.tables.lock().map_err(|_| anyhow!("poisoned lock"))?;
+        tables.insert((table, key.to_string()), value);
+        Ok(())
+    }
+}
+
+/// [Storage] in a sled database, which survives restarts of the mock.
+///
+/// Sessions and SNARK conversions still running when the mock stops are not
+/// resumed, and keep their `RUNNING` status.
+#[cfg(feature = "sled")]
+pub struct SledStorage {
+    db: sled::Db,
+}
+
+#[cfg(feature = "sled")]
+impl SledStorage {
+    /// Opens the database at `path`, creating it if needed.
+    pub fn open(path: impl AsRef<std::path::Path>) -> Result<Self> {
+        Ok(Self {
+            db: sled::open(path)?,
+        })
+    }
+}
+
+#[cfg(feature = "sled")]
+impl Storage for SledStorage {
+    fn get(&self, table: Table, key: &str) -> Result<Option<Vec<u8>>> {
+        let tree = self.db.open_tree(table.name())?;
+        Ok(tree.get(key)?.map(|value| value.to_vec()))
+    }
+
+    fn put(&self, table: Table, key: &str, value: Vec<u8>) -> Result<()> {
+        let tree = self.db.open_tree(table.name())?;
+        tree.insert(key, value)?;
+        tree.flush()?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn round_trip(storage: &dyn Storage) {
+        assert_eq!(storage.get(Table::Images, "a").unwrap(), None);
+        storage.put(Table::Images, "a", vec![1, 2]).unwrap();
+        storage.put(Table::Inputs, "a", vec![3]).unwrap();
+        assert_eq!(storage.get(Table::Images, "a").unwrap(), Some(vec![1, 2]));
+        assert_eq!(storage.get(Table::Inputs, "a").unwrap(), Some(vec![3]));
+        storage.put(Table::Images, "a", vec![4]).unwrap();
+        assert_eq!(storage.get(Table::Images, "a").unwrap(), Some(vec![4]));
+    }
+
+    #[test]
+    fn memory() {
+        round_trip(&MemoryStorage::new());
+    }
+
+    #[cfg(feature = "sled")]
+    #[test]
+    fn sled_persists() {
+        let dir = tempfile::tempdir().unwrap();
+        round_trip(&SledStorage::open(dir.path()).unwrap());
+        let storage = SledStorage::open(dir.path()).unwrap();
+        assert_eq!(storage.get(Table::Images, "a").unwrap(), Some(vec![4]));
+    }
+}
@@ -12,27 +12,66 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use std::{fs::File, path::Path};
+use std::{
+    collections::hash_map::RandomState,
+    fs::{self, File},
+    hash::{BuildHasher, Hasher},
+    io::{self, BufRead, BufReader, Lines, Read, Write},
+    path::Path,
+    sync::Arc,
+    time::{Duration, Instant},
+};
 
-use reqwest::{blocking::Client as BlockingClient, header};
+use reqwest::{
+    blocking::{Body, Client as BlockingClient, RequestBuilder, Response},
+    header, StatusCode,
+};
 use serde::{Deserialize, Serialize};
+use sha2::{Digest as _, Sha256};
 use thiserror::Error;
 
 use self::responses::{
-    CreateSessRes, ImgUploadRes, ProofReq, Quotas, ReceiptDownload, SessionStatusRes, SnarkReq,
-    SnarkStatusRes, UploadRes, VersionInfo,
+    CreateSessRes, ImgUploadRes, ProofReq, Quotas, ReceiptDownload, SessionList, SessionMetadata,
+    SessionStatusRes, SnarkReq, SnarkStatusRes, UploadRes, VersionInfo,
 };
 use crate::{API_KEY_ENVVAR, API_KEY_HEADER, API_URL_ENVVAR, VERSION_HEADER};
 
+mod pool;
+
+pub use self::pool::{PoolJob, PoolLimits, PoolOutput, SessionPool};
+
 /// Bonsai Alpha SDK error classes
+///
+/// Failed responses of the REST api are classified by their status code, see
+/// [SdkErr::is_retryable].
 #[derive(Debug, Error)]
 pub enum SdkErr {
-    /// Server side failure
+    /// Server side failure, from a `5xx` status
     #[error("server error `{0}`")]
     InternalServerErr(String),
+    /// Missing or rejected API key, from a `401` or `403` status
+    #[error("unauthorized `{0}`")]
+    Unauthorized(String),
+    /// Rate limit or quota of the account exceeded, from a `429` status
+    #[error("quota exceeded `{0}`")]
+    QuotaExceeded(String),
+    /// Resource not found, from a `404` status
+    #[error("not found `{0}`")]
+    NotFound(String),
+    /// Request rejected with any other status
+    #[error("request failed with status {status}: `{body}`")]
+    RequestFailed {
+        /// HTTP status code
+        status: u16,
+        /// Response body
+        body: String,
+    },
+    /// Response body does not match the expected format
+    #[error("malformed response: {0}")]
+    MalformedResponse(String),
     /// http reqwest errors
     #[error("HTTP error from reqwest")]
-    HttpErr(#[from] reqwest::Error),
+    HttpErr(#[source] reqwest::Error),
     /// Header construction error
     #[error("HTTP header failed to construct")]
     HttpHeaderErr(#[from] reqwest::header::InvalidHeaderValue),
@@ -45,13 +84,107 @@ pub enum SdkErr {
     /// Missing file
     #[error("failed to find file on disk: {0:?}")]
     FileNotFound(#[from] std::io::Error),
+    /// Downloaded data does not match the length or checksum sent by the server
+    #[error("download integrity check failed: {0}")]
+    IntegrityErr(String),
+    /// SNARK conversion ended without a receipt
+    #[error("snark conversion {status}: {}", error_msg.as_deref().unwrap_or("no receipt"))]
+    SnarkFailed {
+        /// Final status: `[ FAILED | TIMED_OUT | ABORTED ]`, or `SUCCEEDED`
+        /// if the receipt is missing
+        status: String,
+        /// Snark error message
+        error_msg: Option<String>,
+    },
+    /// SNARK receipt does not have the shape of a Groth16 seal and claim
+    #[error("malformed snark receipt: {0}")]
+    MalformedSnark(String),
+    /// Server does not support the API or risc0-zkvm version of the client,
+    /// see [Client::check_compat]
+    #[error("unsupported {component} version {version}, server supports {supported:?}")]
+    IncompatibleVersion {
+        /// `api` or `risc0-zkvm`
+        component: &'static str,
+        /// Version of the client
+        version: String,
+        /// Versions advertised by the server
+        supported: Vec<String>,
+    },
+    /// Session still running when the polling timeout elapsed
+    #[error("session still running after {0:?}")]
+    Timeout(Duration),
+    /// Request not supported by the [Backend] of the client
+    #[error("not supported by this backend: {0}")]
+    Unsupported(&'static str),
+    /// Downloaded receipt rejected by the [ReceiptVerifier] of the client
+    #[error("receipt failed verification: {0}")]
+    InvalidReceipt(String),
+    /// Blocking task of the async client panicked or was cancelled
+    #[error("client task failed: {0}")]
+    TaskFailed(String),
+    /// Session ended without succeeding, see [SessionPool]
+    #[error("session {status}: {}", error_msg.as_deref().unwrap_or("no error message"))]
+    SessionFailed {
+        /// Final status: `[ FAILED | TIMED_OUT | ABORTED ]`
+        status: String,
+        /// Session error message
+        error_msg: Option<String>,
+    },
+    /// Guest binary is not a valid ELF file, see [Client::upload_img_from_elf]
+    #[error("invalid guest ELF: {0}")]
+    InvalidElf(String),
+}
+
+impl SdkErr {
+    /// Classifies a failed response of the REST api by its status code
+    pub(crate) fn from_status(status: StatusCode, body: String) -> Self {
+        match status {
+            StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN => SdkErr::Unauthorized(body),
+            StatusCode::TOO_MANY_REQUESTS => SdkErr::QuotaExceeded(body),
+            StatusCode::NOT_FOUND => SdkErr::NotFound(body),
+            status if status.is_server_error() => SdkErr::InternalServerErr(body),
+            status => SdkErr::RequestFailed {
+                status: status.as_u16(),
+                body,
+            },
+        }
+    }
+
+    /// Returns true if the failed request may succeed when sent again later
+    ///
+    /// Server failures, exceeded quotas, connection failures, timeouts and
+    /// corrupted downloads are transient. Authentication failures, missing
+    /// resources, rejected requests and malformed responses are not, and
+    /// are worth alerting on instead.
+    pub fn is_retryable(&self) -> bool {
+        match self {
+            SdkErr::InternalServerErr(_) | SdkErr::QuotaExceeded(_) | SdkErr::IntegrityErr(_) => {
+                true
+            }
+            SdkErr::HttpErr(err) => RetryPolicy::retry_err(err),
+            _ => false,
+        }
+    }
+}
+
+impl From<reqwest::Error> for SdkErr {
+    fn from(err: reqwest::Error) -> Self {
+        match err.is_decode() {
+            true => SdkErr::MalformedResponse(err.to_string()),
+            false => SdkErr::HttpErr(err),
+        }
+    }
 }
 
 /// Collection of serialization object for the REST api
 pub mod responses {
-    use risc0_groth16::Seal;
+    use std::collections::BTreeMap;
+
+    use risc0_groth16::{Seal, SealRef};
     use serde::{Deserialize, Serialize};
 
+    use super::SdkErr;
+
     /// Response of a upload request
     #[derive(Deserialize, Serialize)]
     pub struct UploadRes {
@@ -84,6 +217,36 @@ pub mod responses {
         pub input: String,
         /// List of receipt UUIDs
         pub assumptions: Vec<String>,
+        /// Only execute the guest, without proving it
+        #[serde(default)]
+        pub execute_only: bool,
+        /// Labels and scheduling hints of the session
+        #[serde(default)]
+        pub metadata: SessionMetadata,
+    }
+
+    /// Labels and scheduling hints of a Session
+    ///
+    /// Set when creating the session with [super::SessionOpts::metadata], and
+    /// returned as is in its [SessionStatusRes::metadata].
+    #[derive(Clone, Debug, Default, Deserialize, Serialize, PartialEq, Eq)]
+    pub struct SessionMetadata {
+        /// Free-form labels, e.g. the tenant or pipeline of the session
+        #[serde(default)]
+        pub labels: BTreeMap<String, String>,
+        /// Scheduling priority
+        ///
+        /// When the service is busy, sessions with a higher priority are
+        /// started first.
+        #[serde(default)]
+        pub priority: u32,
+        /// Time by which the session must be done, in seconds since the UNIX
+        /// epoch
+        ///
+        /// Among sessions of the same priority, those with the earliest
+        /// deadline are started first. A session not done by its deadline is
+        /// stopped with the `TIMED_OUT` status.
+        pub deadline: Option<u64>,
     }
 
     /// Session statistics metadata file
@@ -138,6 +301,37 @@ pub mod responses {
         /// - Count of segments in this proof request
         /// - User cycles run within guest, slightly below total overhead cycles
         pub stats: Option<SessionStats>,
+        /// Session Metadata
+        ///
+        /// Labels and scheduling hints the session was created with, absent
+        /// from services which do not support them.
+        pub metadata: Option<SessionMetadata>,
+    }
+
+    /// Session metadata, as listed by [super::Client::list_sessions]
+    #[derive(Clone, Debug, Deserialize, Serialize, PartialEq, Eq)]
+    pub struct SessionInfo {
+        /// Session UUID
+        pub uuid: String,
+        /// Current status
+        ///
+        /// values: `[ RUNNING | SUCCEEDED | FAILED | TIMED_OUT | ABORTED ]`
+        pub status: String,
+        /// Image ID the session was created with
+        pub image_id: String,
+        /// Creation time, in seconds since the UNIX epoch
+        pub created_at: u64,
+    }
+
+    /// A page of sessions, as returned by [super::Client::list_sessions]
+    #[derive(Deserialize, Serialize)]
+    pub struct SessionList {
+        /// Sessions of this page, most recently created first
+        pub sessions: Vec<SessionInfo>,
+        /// Cursor of the next page
+        ///
+        /// Absent on the last page.
+        pub next_cursor: Option<String>,
     }
 
     /// Response of the receipt/download method
@@ -158,7 +352,7 @@ pub mod responses {
     ///
     /// All relevant data to verify both the snark proof an corresponding
     /// imageId on chain.
-    #[derive(Debug, Deserialize, Serialize, PartialEq)]
+    #[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
     pub struct SnarkReceipt {
         /// SNARK Groth16 seal object encoded in big endian
         pub snark: Seal,
@@ -171,6 +365,27 @@ pub mod responses {
         pub journal: Vec<u8>,
     }
 
+    impl TryFrom<SnarkReceipt> for Seal {
+        type Error = SdkErr;
+
+        /// Extracts the Groth16 seal of a [SnarkReceipt], checking that it
+        /// has the shape expected by the verifier
+        ///
+        /// The seal and the claim of the receipt can be verified together
+        /// with `risc0_zkvm::CompactReceipt::from_bonsai_snark`.
+        fn try_from(receipt: SnarkReceipt) -> Result<Self, SdkErr> {
+            if receipt.post_state_digest.len() != 32 {
+                return Err(SdkErr::MalformedSnark(format!(
+                    "post state digest of {} bytes",
+                    receipt.post_state_digest.len()
+                )));
+            }
+            SealRef::try_from(&receipt.snark)
+                .map_err(|err| SdkErr::MalformedSnark(err.to_string()))?;
+            Ok(receipt.snark)
+        }
+    }
+
     /// Session Status response
     #[derive(Deserialize, Serialize)]
     pub struct SnarkStatusRes {
@@ -189,11 +404,33 @@ pub mod responses {
         pub error_msg: Option<String>,
     }
 
+    impl SnarkStatusRes {
+        /// Returns the [SnarkReceipt] of a completed conversion
+        ///
+        /// Returns [None] while the conversion is `RUNNING`, and
+        /// [SdkErr::SnarkFailed] if it ended without a receipt.
+        pub fn receipt(self) -> Result<Option<SnarkReceipt>, SdkErr> {
+            match (self.status.as_str(), self.output) {
+                ("RUNNING", _) => Ok(None),
+                ("SUCCEEDED", Some(receipt)) => Ok(Some(receipt)),
+                _ => Err(SdkErr::SnarkFailed {
+                    status: self.status,
+                    error_msg: self.error_msg,
+                }),
+            }
+        }
+    }
+
     /// Bonsai supported versions
     #[derive(Deserialize, Serialize)]
     pub struct VersionInfo {
         /// Supported versions of the risc0-zkvm crate
         pub risc0_zkvm: Vec<String>,
+        /// Supported versions of the REST api
+        ///
+        /// Empty if the server does not advertise them.
+        #[serde(default)]
+        pub api: Vec<String>,
     }
 
     /// User quotas and cycle budgets
@@ -212,6 +449,43 @@ pub mod responses {
     }
 }
 
+/// Filter of the sessions listed by [Client::list_sessions]
+///
+/// Fields left as [None] match all sessions.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct SessionFilter {
+    /// Only list sessions with this status
+    pub status: Option<String>,
+    /// Only list sessions created with this image ID
+    pub image_id: Option<String>,
+    /// Only list sessions created at or after this time, in seconds since the
+    /// UNIX epoch
+    pub created_after: Option<u64>,
+    /// Maximum number of sessions per page
+    ///
+    /// The server applies its own limit if unset.
+    pub limit: Option<u32>,
+}
+
+/// Options of the Sessions created by [Client::create_session_with_opts]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SessionOpts {
+    /// Only execute the guest, without proving it
+    ///
+    /// Execute-only sessions are much cheaper than proofs and succeed without
+    /// a receipt. Their [SessionStatusRes::stats] report the cycle counts of
+    /// the guest, and [SessionId::exec_only_journal] downloads its journal,
+    /// which is enough to estimate the cost of a proof and to validate the
+    /// guest and its input.
+    pub execute_only: bool,
+    /// Labels and scheduling hints of the Session
+    ///
+    /// Labels let pipelines sharing an account tag their sessions, and are
+    /// returned in [SessionStatusRes::metadata]. The priority and deadline
+    /// decide which sessions run first when the service is busy.
+    pub metadata: SessionMetadata,
+}
+
 /// Proof Session representation
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct SessionId {
@@ -227,16 +501,52 @@ impl SessionId {
 
     /// Fetches the current status of the Session
     pub fn status(&self, client: &Client) -> Result<SessionStatusRes, SdkErr> {
+        if let Some(backend) = &client.backend {
+            return backend.session_status(self);
+        }
+
         let url = format!("{}/sessions/status/{}", client.url, self.uuid);
-        let res = client.client.get(url).send()?;
+        let res = client.send(client.client.get(url))?;
 
         if !res.status().is_success() {
-            let body = res.text()?;
-            return Err(SdkErr::InternalServerErr(body));
+            return Err(SdkErr::from_status(res.status(), res.text()?));
         }
         Ok(res.json::<SessionStatusRes>()?)
     }
 
+    /// Polls the status of the Session until it is no longer `RUNNING`
+    ///
+    /// Returns the final status, whether the session succeeded or not, or
+    /// [SdkErr::Timeout] if the session is still running after `timeout`,
+    /// which may be [Duration::MAX] to wait as long as it takes.
+    /// Status requests failing with a [retryable](SdkErr::is_retryable) error
+    /// are tried again at the next poll, other errors are returned right away.
+    pub fn poll_until_done(
+        &self,
+        client: &Client,
+        timeout: Duration,
+        poll_interval: Duration,
+    ) -> Result<SessionStatusRes, SdkErr> {
+        let deadline = Instant::now().checked_add(timeout);
+        loop {
+            match self.status(client) {
+                Ok(res) if res.status != "RUNNING" => return Ok(res),
+                Ok(_) => {}
+                Err(err) if err.is_retryable() => {}
+                Err(err) => return Err(err),
+            }
+            let mut delay = poll_interval;
+            if let Some(deadline) = deadline {
+                let now = Instant::now();
+                if now >= deadline {
+                    return Err(SdkErr::Timeout(timeout));
+                }
+                delay = delay.min(deadline - now);
+            }
+            std::thread::sleep(delay);
+        }
+    }
+
     /// Fetches the zkvm guest logs for a session
     ///
     /// After the Execution phase of proving is completed, you can use this method
@@ -246,15 +556,178 @@ impl SessionId {
     /// It should contain the output of all writes to those file descriptors. But does NOT include output
     /// from `env::log`
     pub fn logs(&self, client: &Client) -> Result<String, SdkErr> {
+        if client.backend.is_some() {
+            return Err(SdkErr::Unsupported("session logs"));
+        }
         let url = format!("{}/sessions/logs/{}", client.url, self.uuid);
         let res = client.client.get(url).send()?;
 
         if !res.status().is_success() {
-            let body = res.text()?;
-            return Err(SdkErr::InternalServerErr(body));
+            return Err(SdkErr::from_status(res.status(), res.text()?));
         }
         Ok(res.text()?)
     }
+
+    /// Downloads the journal of a successful execute-only Session
+    ///
+    /// See [SessionOpts::execute_only].
+    pub fn exec_only_journal(&self, client: &Client) -> Result<Vec<u8>, SdkErr> {
+        if let Some(backend) = &client.backend {
+            return backend.exec_only_journal(self);
+        }
+
+        let url = format!("{}/sessions/exec_only_journal/{}", client.url, self.uuid);
+        let res = client.send(client.client.get(url))?;
+
+        if !res.status().is_success() {
+            return Err(SdkErr::from_status(res.status(), res.text()?));
+        }
+        Ok(res.bytes()?.into())
+    }
+
+    /// Stops a running Session
+    ///
+    /// Aborts proving of the session so that it does not consume any more of
+    /// the account's quota. The status of a stopped session is `ABORTED`.
+    pub fn cancel(&self, client: &Client) -> Result<(), SdkErr> {
+        if client.backend.is_some() {
+            return Err(SdkErr::Unsupported("stopping sessions"));
+        }
+        let url = format!("{}/sessions/stop/{}", client.url, self.uuid);
+        let res = client.send(client.client.get(url))?;
+
+        if !res.status().is_success() {
+            return Err(SdkErr::from_status(res.status(), res.text()?));
+        }
+        Ok(())
+    }
+}
+
+/// Progress of a Session, yielded by [Client::stream_status]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SessionEvent {
+    /// The session is running
+    Running {
+        /// Where in the proving pipeline the session currently is, see
+        /// [SessionStatusRes::state]
+        state: Option<String>,
+    },
+    /// The session completed successfully
+    Succeeded {
+        /// Final receipt download URL
+        receipt_url: Option<String>,
+    },
+    /// The session ended without a receipt
+    Failed {
+        /// Final status: `[ FAILED | TIMED_OUT | ABORTED ]`
+        status: String,
+        /// Session error message
+        error_msg: Option<String>,
+    },
+}
+
+impl SessionEvent {
+    /// Whether the session ended with this event
+    pub fn is_terminal(&self) -> bool {
+        !matches!(self, Self::Running { .. })
+    }
+}
+
+impl From<SessionStatusRes> for SessionEvent {
+    fn from(res: SessionStatusRes) -> Self {
+        match res.status.as_str() {
+            "RUNNING" => Self::Running { state: res.state },
+            "SUCCEEDED" => Self::Succeeded {
+                receipt_url: res.receipt_url,
+            },
+            _ => Self::Failed {
+                status: res.status,
+                error_msg: res.error_msg,
+            },
+        }
+    }
+}
+
+/// Iterator over the [SessionEvent]s of a Session, returned by
+/// [Client::stream_status]
+///
+/// Events are read from the server-sent events of the session when the API
+/// provides them, and otherwise by polling the session status. Only changes of
+/// status or state are yielded, and the iterator ends after the terminal event
+/// or the first error.
+pub struct StatusStream<'a> {
+    client: &'a Client,
+    session: SessionId,
+    interval: Duration,
+    events: Option<Lines<BufReader<Response>>>,
+    last: Option<SessionEvent>,
+    done: bool,
+}
+
+impl<'a> StatusStream<'a> {
+    /// Default delay between two status requests when polling
+    pub const DEFAULT_POLL_INTERVAL: Duration = Duration::from_secs(15);
+
+    /// Sets the delay between two status requests when polling
+    pub fn poll_interval(mut self, interval: Duration) -> Self {
+        self.interval = interval;
+        self
+    }
+
+    // Reads the status of the next server-sent event, or `None` once the
+    // stream is closed.
+    fn next_pushed(&mut self) -> Option<SessionStatusRes> {
+        let lines = self.events.as_mut()?;
+        let mut data = String::new();
+        for line in lines {
+            let Ok(line) = line else {
+                break;
+            };
+            if let Some(chunk) = line.strip_prefix("data:") {
+                data.push_str(chunk.trim_start());
+            } else if line.is_empty() && !data.is_empty() {
+                match serde_json::from_str(&data) {
+                    Ok(res) => return Some(res),
+                    Err(_) => break,
+                }
+            }
+        }
+        self.events = None;
+        None
+    }
+
+    fn next_status(&mut self) -> Result<SessionStatusRes, SdkErr> {
+        if let Some(res) = self.next_pushed() {
+            return Ok(res);
+        }
+        if self.last.is_some() {
+            std::thread::sleep(self.interval);
+        }
+        self.session.status(self.client)
+    }
+}
+
+impl<'a> Iterator for StatusStream<'a> {
+    type Item = Result<SessionEvent, SdkErr>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while !self.done {
+            let event = match self.next_status() {
+                Ok(res) => SessionEvent::from(res),
+                Err(err) => {
+                    self.done = true;
+                    return Some(Err(err));
+                }
+            };
+            if self.last.as_ref() == Some(&event) {
+                continue;
+            }
+            self.done = event.is_terminal();
+            self.last = Some(event.clone());
+            return Some(Ok(event));
+        }
+        None
+    }
 }
 
 /// Stark2Snark Session representation
@@ -272,37 +745,380 @@ impl SnarkId {
 
     /// Fetches the current status of the Snark Session
     pub fn status(&self, client: &Client) -> Result<SnarkStatusRes, SdkErr> {
+        if let Some(backend) = &client.backend {
+            return backend.snark_status(self);
+        }
+
         let url = format!("{}/snark/status/{}", client.url, self.uuid);
-        let res = client.client.get(url).send()?;
+        let res = client.send(client.client.get(url))?;
 
         if !res.status().is_success() {
-            let body = res.text()?;
-            return Err(SdkErr::InternalServerErr(body));
+            return Err(SdkErr::from_status(res.status(), res.text()?));
         }
         Ok(res.json::<SnarkStatusRes>()?)
     }
 }
 
+/// Policy for retrying requests that failed transiently
+///
+/// Uploads, session creation and status requests are retried on connection
+/// errors and timeouts, and, with `retry_on_status`, on `5xx` and `429 Too
+/// Many Requests` responses. Requests streaming a file body cannot be replayed
+/// and are sent once.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RetryPolicy {
+    /// Maximum number of attempts, including the first one
+    pub max_attempts: u32,
+    /// Delay before the first retry
+    pub initial_backoff: Duration,
+    /// Upper bound of the delay between two attempts
+    pub max_backoff: Duration,
+    /// Factor applied to the delay after each retry
+    pub multiplier: u32,
+    /// Randomize each delay between half and all of its value
+    pub jitter: bool,
+    /// Retry on `5xx` and `429` responses
+    pub retry_on_status: bool,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 3,
+            initial_backoff: Duration::from_millis(500),
+            max_backoff: Duration::from_secs(10),
+            multiplier: 2,
+            jitter: true,
+            retry_on_status: true,
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// A policy sending each request once
+    pub fn none() -> Self {
+        Self {
+            max_attempts: 1,
+            ..Default::default()
+        }
+    }
+
+    /// Delay before the given retry, starting at 1
+    pub fn backoff(&self, retry: u32) -> Duration {
+        let backoff = self
+            .initial_backoff
+            .saturating_mul(self.multiplier.saturating_pow(retry.saturating_sub(1)))
+            .min(self.max_backoff);
+        if !self.jitter {
+            return backoff;
+        }
+        // RandomState is seeded randomly, which is enough to spread out the
+        // retries of concurrent clients without depending on a RNG crate.
+        let random = RandomState::new().build_hasher().finish();
+        let half = backoff / 2;
+        half + Duration::from_nanos(random % (half.as_nanos() as u64 + 1))
+    }
+
+    pub(crate) fn retry_status(&self, status: StatusCode) -> bool {
+        self.retry_on_status
+            && (status.is_server_error() || status == StatusCode::TOO_MANY_REQUESTS)
+    }
+
+    pub(crate) fn retry_err(err: &reqwest::Error) -> bool {
+        err.is_connect() || err.is_timeout()
+    }
+}
+
+/// Progress of an upload
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct UploadProgress {
+    /// Bytes of the body sent so far
+    pub sent: u64,
+    /// Size of the body
+    pub total: u64,
+}
+
+type ProgressFn = Arc<dyn Fn(UploadProgress) + Send + Sync>;
+
+/// Reads an upload body, reporting the bytes read
+struct ProgressReader {
+    buf: Arc<Vec<u8>>,
+    pos: usize,
+    progress: ProgressFn,
+}
+
+impl Read for ProgressReader {
+    fn read(&mut self, out: &mut [u8]) -> std::io::Result<usize> {
+        let len = out.len().min(self.buf.len() - self.pos);
+        out[..len].copy_from_slice(&self.buf[self.pos..self.pos + len]);
+        self.pos += len;
+        if len > 0 {
+            (self.progress)(UploadProgress {
+                sent: self.pos as u64,
+                total: self.buf.len() as u64,
+            });
+        }
+        Ok(len)
+    }
+}
+
+/// Header carrying the base64 encoded SHA-256 digest of a download
+const CHECKSUM_HEADER: &str = "x-amz-checksum-sha256";
+
+/// Streams a receipt download, verifying it once the body is fully read
+///
+/// Returned by [Client::receipt_reader]. The body is hashed as it is read and
+/// reaching its end fails with [io::ErrorKind::InvalidData] if its length or
+/// SHA-256 digest do not match the `Content-Length` and
+/// `x-amz-checksum-sha256` headers sent by the server, when present.
+pub struct ReceiptReader {
+    res: Response,
+    hasher: Sha256,
+    read: u64,
+    expected_len: Option<u64>,
+    expected_sha256: Option<Vec<u8>>,
+}
+
+impl ReceiptReader {
+    fn new(res: Response) -> Result<Self, SdkErr> {
+        let expected_sha256 = match res.headers().get(CHECKSUM_HEADER) {
+            Some(value) => {
+                use base64::Engine as _;
+                let digest = value
+                    .to_str()
+                    .ok()
+                    .and_then(|value| base64::engine::general_purpose::STANDARD.decode(value).ok())
+                    .ok_or_else(|| {
+                        SdkErr::IntegrityErr(format!("malformed {CHECKSUM_HEADER} header"))
+                    })?;
+                Some(digest)
+            }
+            None => None,
+        };
+        Ok(Self {
+            expected_len: res.content_length(),
+            res,
+            hasher: Sha256::new(),
+            read: 0,
+            expected_sha256,
+        })
+    }
+
+    /// Number of bytes of the receipt read so far
+    pub fn bytes_read(&self) -> u64 {
+        self.read
+    }
+
+    /// Total size of the receipt, if sent by the server
+    pub fn content_length(&self) -> Option<u64> {
+        self.expected_len
+    }
+
+    fn verify(&self) -> io::Result<()> {
+        let invalid = |msg: String| io::Error::new(io::ErrorKind::InvalidData, msg);
+        if let Some(len) = self.expected_len {
+            if len != self.read {
+                return Err(invalid(format!(
+                    "expected {len} bytes, received {}",
+                    self.read
+                )));
+            }
+        }
+        if let Some(expected) = &self.expected_sha256 {
+            if self.hasher.clone().finalize().as_slice() != expected.as_slice() {
+                return Err(invalid("SHA-256 checksum mismatch".to_string()));
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Read for ReceiptReader {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let len = self.res.read(buf)?;
+        if len == 0 && !buf.is_empty() {
+            self.verify()?;
+        }
+        self.hasher.update(&buf[..len]);
+        self.read += len as u64;
+        Ok(len)
+    }
+}
+
 /// Represents a client of the REST api
 #[derive(Clone)]
 pub struct Client {
     pub(crate) url: String,
     pub(crate) client: BlockingClient,
+    pub(crate) risc0_version: String,
+    pub(crate) retry: RetryPolicy,
+    pub(crate) backend: Option<Arc<dyn Backend>>,
+    pub(crate) verifier: Option<Arc<dyn ReceiptVerifier>>,
+}
+
+/// Computes the hex encoded image ID of the guest `elf`
+pub(crate) fn elf_image_id(elf: &[u8]) -> Result<String, SdkErr> {
+    risc0_binfmt::compute_image_id(elf)
+        .map(|image_id| image_id.to_string())
+        .map_err(|err| SdkErr::InvalidElf(err.to_string()))
+}
+
+/// Checks `receipt` of a session of the image `image_id` with `verifier`
+pub(crate) fn verify_receipt(
+    verifier: Option<&dyn ReceiptVerifier>,
+    image_id: &str,
+    receipt: &[u8],
+) -> Result<(), SdkErr> {
+    let verifier = verifier
+        .ok_or_else(|| SdkErr::InvalidReceipt("the client has no receipt verifier".to_string()))?;
+    verifier
+        .verify(receipt, image_id)
+        .map_err(SdkErr::InvalidReceipt)
+}
+
+/// Check of the receipts downloaded by a [Client]
+///
+/// The SDK does not depend on the zkVM, so it cannot verify receipts itself.
+/// `risc0_zkvm::BonsaiReceiptVerifier` implements this trait with
+/// `Receipt::verify`.
+pub trait ReceiptVerifier: Send + Sync {
+    /// Verifies the serialized `receipt` of a session of the image
+    /// `image_id`, returning why it is invalid otherwise
+    fn verify(&self, receipt: &[u8], image_id: &str) -> Result<(), String>;
+}
+
+/// Service running the Sessions of a [Client]
+///
+/// A [Client] sends its requests to the Bonsai REST api, and itself
+/// implements [Backend] over it. A client constructed with
+/// [Client::from_backend] sends them to another backend instead, such as the
+/// in-process prover of `risc0_zkvm::LocalBackend`, so that applications can
+/// fall back to local proving, or run offline in development, without changing
+/// their call sites.
+///
+/// Requests a backend does not support fail with [SdkErr::Unsupported].
+pub trait Backend: Send + Sync {
+    /// Checks if a image exists, see [Client::has_img]
+    fn has_img(&self, image_id: &str) -> Result<bool, SdkErr>;
+
+    /// Uploads a image, see [Client::upload_img]
+    fn upload_img(&self, image_id: &str, buf: Vec<u8>) -> Result<ImgUploadOutcome, SdkErr>;
+
+    /// Uploads a input, returning its UUID, see [Client::upload_input]
+    fn upload_input(&self, buf: Vec<u8>) -> Result<String, SdkErr>;
+
+    /// Uploads a receipt, returning its UUID, see [Client::upload_receipt]
+    fn upload_receipt(&self, buf: Vec<u8>) -> Result<String, SdkErr>;
+
+    /// Creates a Session, see [Client::create_session_with_opts]
+    fn create_session(
+        &self,
+        img_id: String,
+        input_id: String,
+        assumptions: Vec<String>,
+        opts: SessionOpts,
+    ) -> Result<SessionId, SdkErr>;
+
+    /// Fetches the status of a Session, see [SessionId::status]
+    fn session_status(&self, session_id: &SessionId) -> Result<SessionStatusRes, SdkErr>;
+
+    /// Downloads the journal of an execute-only Session, see
+    /// [SessionId::exec_only_journal]
+    fn exec_only_journal(&self, session_id: &SessionId) -> Result<Vec<u8>, SdkErr>;
+
+    /// Downloads the receipt of a Session, see [Client::receipt_download]
+    fn receipt_download(&self, session_id: &SessionId) -> Result<Vec<u8>, SdkErr>;
+
+    /// Downloads a URL returned by this backend, such as a
+    /// [SessionStatusRes::receipt_url], see [Client::download]
+    fn download(&self, url: &str) -> Result<Vec<u8>, SdkErr>;
+
+    /// Requests a SNARK proof of a Session, see [Client::create_snark]
+    fn create_snark(&self, _session_id: String) -> Result<SnarkId, SdkErr> {
+        Err(SdkErr::Unsupported("SNARK conversion"))
+    }
+
+    /// Fetches the status of a SNARK conversion, see [SnarkId::status]
+    fn snark_status(&self, _snark_id: &SnarkId) -> Result<SnarkStatusRes, SdkErr> {
+        Err(SdkErr::Unsupported("SNARK conversion"))
+    }
+
+    /// Fetches the supported versions, see [Client::version]
+    fn version(&self) -> Result<VersionInfo, SdkErr>;
+}
+
+/// Version of the REST api implemented by this SDK
+pub const API_VERSION: &str = "v1";
+
+// Receipts are compatible between versions of risc0-zkvm with the same
+// major version, or the same minor version before 1.0, as for cargo.
+fn receipt_compat_key(version: &str) -> Option<(u64, u64)> {
+    let mut parts = version.split(['.', '-', '+']);
+    let major = parts.next()?.parse().ok()?;
+    let minor = parts.next()?.parse().ok()?;
+    Some(if major == 0 { (0, minor) } else { (major, 0) })
 }
 
-enum ImageExistsOpt {
+/// Checks that the server advertising `info` supports this SDK and receipts
+/// of `risc0_version`
+pub(crate) fn check_versions(info: &VersionInfo, risc0_version: &str) -> Result<(), SdkErr> {
+    if !info.api.is_empty() && !info.api.iter().any(|version| version == API_VERSION) {
+        return Err(SdkErr::IncompatibleVersion {
+            component: "api",
+            version: API_VERSION.to_string(),
+            supported: info.api.clone(),
+        });
+    }
+    let key = receipt_compat_key(risc0_version);
+    if key.is_none()
+        || !info
+            .risc0_zkvm
+            .iter()
+            .any(|version| receipt_compat_key(version) == key)
+    {
+        return Err(SdkErr::IncompatibleVersion {
+            component: "risc0-zkvm",
+            version: risc0_version.to_string(),
+            supported: info.risc0_zkvm.clone(),
+        });
+    }
+    Ok(())
+}
+
+pub(crate) enum ImageExistsOpt {
     Exists,
     New(ImgUploadRes),
 }
 
-/// Creates a [reqwest::Client] for internal connection pooling
-fn construct_req_client(api_key: &str, version: &str) -> Result<BlockingClient, SdkErr> {
+/// Outcome of a successful image upload
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ImgUploadOutcome {
+    /// The image was uploaded to bonsai
+    Uploaded,
+    /// The image already exists in bonsai, and was not uploaded again
+    AlreadyExists,
+}
+
+/// Constructs the headers sent with every request to the REST api
+pub(crate) fn default_headers(api_key: &str, version: &str) -> Result<header::HeaderMap, SdkErr> {
     let mut headers = header::HeaderMap::new();
     headers.insert(API_KEY_HEADER, header::HeaderValue::from_str(api_key)?);
     headers.insert(VERSION_HEADER, header::HeaderValue::from_str(version)?);
+    Ok(headers)
+}
+
+/// Reads the API url, without trailing slash, and key from env vars
+pub(crate) fn env_parts() -> Result<(String, String), SdkErr> {
+    let api_url = std::env::var(API_URL_ENVVAR).map_err(|_| SdkErr::MissingApiUrl)?;
+    let api_url = api_url.strip_suffix('/').unwrap_or(&api_url).to_string();
+    let api_key = std::env::var(API_KEY_ENVVAR).map_err(|_| SdkErr::MissingApiKey)?;
+    Ok((api_url, api_key))
+}
 
+/// Creates a [reqwest::Client] for internal connection pooling
+fn construct_req_client(api_key: &str, version: &str) -> Result<BlockingClient, SdkErr> {
     Ok(BlockingClient::builder()
-        .default_headers(headers)
+        .default_headers(default_headers(api_key, version)?)
         .pool_max_idle_per_host(0)
         .build()?)
 }
@@ -322,15 +1138,15 @@ impl Client {
     ///     .expect("Failed to construct sdk client");
     /// ```
     pub fn from_env(risc0_version: &str) -> Result<Self, SdkErr> {
-        let api_url = std::env::var(API_URL_ENVVAR).map_err(|_| SdkErr::MissingApiUrl)?;
-        let api_url = api_url.strip_suffix('/').unwrap_or(&api_url);
-        let api_key = std::env::var(API_KEY_ENVVAR).map_err(|_| SdkErr::MissingApiKey)?;
-
+        let (url, api_key) = env_parts()?;
         let client = construct_req_client(&api_key, risc0_version)?;
-
         Ok(Self {
-            url: api_url.to_string(),
+            url,
             client,
+            risc0_version: risc0_version.to_string(),
+            retry: RetryPolicy::default(),
+            backend: None,
+            verifier: None,
         })
     }
 
@@ -348,37 +1164,128 @@ impl Client {
     pub fn from_parts(url: String, key: String, risc0_version: &str) -> Result<Self, SdkErr> {
         let client = construct_req_client(&key, risc0_version)?;
         let url = url.strip_suffix('/').unwrap_or(&url).to_string();
-        Ok(Self { url, client })
+        Ok(Self {
+            url,
+            client,
+            risc0_version: risc0_version.to_string(),
+            retry: RetryPolicy::default(),
+            backend: None,
+            verifier: None,
+        })
+    }
+
+    /// Construct a [Client] sending its requests to `backend` rather than to
+    /// the Bonsai REST api
+    ///
+    /// The retry policy of the client does not apply to the requests of the
+    /// backend.
+    ///
+    /// # Example:
+    ///
+    /// ```no_run
+    /// use bonsai_sdk::alpha as bonsai_sdk;
+    /// // Prove on Bonsai when configured, and locally otherwise.
+    /// let client = match bonsai_sdk::Client::from_env(risc0_zkvm::VERSION) {
+    ///     Ok(client) => client,
+    ///     Err(_) => bonsai_sdk::Client::from_backend(
+    ///         risc0_zkvm::LocalBackend::new(),
+    ///         risc0_zkvm::VERSION,
+    ///     )
+    ///     .expect("Failed to construct sdk client"),
+    /// };
+    /// ```
+    pub fn from_backend(
+        backend: impl Backend + 'static,
+        risc0_version: &str,
+    ) -> Result<Self, SdkErr> {
+        let client = construct_req_client("", risc0_version)?;
+        Ok(Self {
+            url: String::new(),
+            client,
+            risc0_version: risc0_version.to_string(),
+            retry: RetryPolicy::default(),
+            backend: Some(Arc::new(backend)),
+            verifier: None,
+        })
+    }
+
+    /// Sets the [RetryPolicy] of uploads, session creation and status requests
+    ///
+    /// Clients use the default policy unless configured otherwise.
+    pub fn with_retry_policy(mut self, retry: RetryPolicy) -> Self {
+        self.retry = retry;
+        self
+    }
+
+    /// Verifies receipts with `verifier` before returning them
+    ///
+    /// Receipts downloaded by [Client::verified_receipt_download] are checked
+    /// against the image ID of their session, and rejected with
+    /// [SdkErr::InvalidReceipt] if Bonsai returns a receipt which does not
+    /// verify. The other downloads return receipts without verifying them.
+    pub fn with_receipt_verifier(mut self, verifier: impl ReceiptVerifier + 'static) -> Self {
+        self.verifier = Some(Arc::new(verifier));
+        self
+    }
+
+    /// Sends a request, retrying it according to the [RetryPolicy]
+    fn send(&self, req: RequestBuilder) -> Result<Response, SdkErr> {
+        match req.try_clone() {
+            Some(_) => self.send_with(|| req.try_clone().unwrap()),
+            None => Ok(req.send()?),
+        }
+    }
+
+    /// Sends the request built by `build`, building it again for each retry
+    fn send_with(&self, build: impl Fn() -> RequestBuilder) -> Result<Response, SdkErr> {
+        let mut attempt = 1;
+        loop {
+            let res = build().send();
+            if attempt >= self.retry.max_attempts {
+                return Ok(res?);
+            }
+            match res {
+                Ok(res) if !self.retry.retry_status(res.status()) => return Ok(res),
+                Err(err) if !RetryPolicy::retry_err(&err) => return Err(err.into()),
+                _ => std::thread::sleep(self.retry.backoff(attempt)),
+            }
+            attempt += 1;
+        }
     }
 
     /// Fetch a upload presigned url for a given route
     fn get_upload_url(&self, route: &str) -> Result<UploadRes, SdkErr> {
-        let res = self
-            .client
-            .get(format!("{}/{}/upload", self.url, route))
-            .send()?;
+        let res = self.send(self.client.get(format!("{}/{}/upload", self.url, route)))?;
 
         if !res.status().is_success() {
-            let body = res.text()?;
-            return Err(SdkErr::InternalServerErr(body));
+            return Err(SdkErr::from_status(res.status(), res.text()?));
         }
 
         Ok(res.json::<UploadRes>()?)
     }
 
     fn get_image_upload_url(&self, image_id: &str) -> Result<ImageExistsOpt, SdkErr> {
-        let res = self
-            .client
-            .get(format!("{}/images/upload/{}", self.url, image_id))
-            .send()?;
+        // Any answer but a 200, e.g. from servers without HEAD support, falls
+        // back to the GET, which answers 204 for existing images.
+        let res = self.send(
+            self.client
+                .head(format!("{}/images/{}", self.url, image_id)),
+        )?;
+        if res.status() == StatusCode::OK {
+            return Ok(ImageExistsOpt::Exists);
+        }
+
+        let res = self.send(
+            self.client
+                .get(format!("{}/images/upload/{}", self.url, image_id)),
+        )?;
 
         if res.status() == 204 {
             return Ok(ImageExistsOpt::Exists);
         }
 
         if !res.status().is_success() {
-            let body = res.text()?;
-            return Err(SdkErr::InternalServerErr(body));
+            return Err(SdkErr::from_status(res.status(), res.text()?));
         }
 
         Ok(ImageExistsOpt::New(res.json::<ImgUploadRes>()?))
@@ -386,50 +1293,147 @@ impl Client {
 
     /// Upload body to a given URL
     fn put_data<T: Into<reqwest::blocking::Body>>(&self, url: &str, body: T) -> Result<(), SdkErr> {
-        let res = self.client.put(url).body(body).send()?;
+        let res = self.send(self.client.put(url).body(body))?;
         if !res.status().is_success() {
-            let body = res.text()?;
-            return Err(SdkErr::InternalServerErr(body));
+            return Err(SdkErr::from_status(res.status(), res.text()?));
         }
 
         Ok(())
     }
 
+    /// Upload a buffer to a given URL, reporting its progress
+    fn put_buf(&self, url: &str, buf: Vec<u8>, progress: Option<ProgressFn>) -> Result<(), SdkErr> {
+        let Some(progress) = progress else {
+            return self.put_data(url, buf);
+        };
+        let total = buf.len() as u64;
+        let buf = Arc::new(buf);
+        let res = self.send_with(|| {
+            let reader = ProgressReader {
+                buf: buf.clone(),
+                pos: 0,
+                progress: progress.clone(),
+            };
+            self.client.put(url).body(Body::sized(reader, total))
+        })?;
+        if !res.status().is_success() {
+            return Err(SdkErr::from_status(res.status(), res.text()?));
+        }
+        if total == 0 {
+            progress(UploadProgress { sent: 0, total: 0 });
+        }
+        Ok(())
+    }
+
     // - /images
 
-    /// Upload a image buffer to the /images/ route
-    ///
-    /// The boolean return indicates if the image already exists in bonsai
+    /// Checks if a image exists in bonsai
+    ///
+    /// Sends a HEAD request to the /images/ route, without transferring the
+    /// image.
+    pub fn has_img(&self, image_id: &str) -> Result<bool, SdkErr> {
+        if let Some(backend) = &self.backend {
+            return backend.has_img(image_id);
+        }
+
+        let res = self.send(
+            self.client
+                .head(format!("{}/images/{}", self.url, image_id)),
+        )?;
+
+        if res.status() == StatusCode::NOT_FOUND {
+            return Ok(false);
+        }
+
+        if !res.status().is_success() {
+            return Err(SdkErr::from_status(res.status(), res.text()?));
+        }
+
+        Ok(true)
+    }
+
+    /// Upload a image buffer to the /images/ route
+    ///
+    /// The upload is skipped if the image already exists in bonsai, which is
+    /// reported by [ImgUploadOutcome::AlreadyExists]
     ///
     /// The image data can be either:
     /// * ELF file bytes
     /// * bincode encoded MemoryImage
-    pub fn upload_img(&self, image_id: &str, buf: Vec<u8>) -> Result<bool, SdkErr> {
+    pub fn upload_img(&self, image_id: &str, buf: Vec<u8>) -> Result<ImgUploadOutcome, SdkErr> {
+        if let Some(backend) = &self.backend {
+            return backend.upload_img(image_id, buf);
+        }
+        let res_or_exists = self.get_image_upload_url(image_id)?;
+        match res_or_exists {
+            ImageExistsOpt::Exists => Ok(ImgUploadOutcome::AlreadyExists),
+            ImageExistsOpt::New(upload_res) => {
+                self.put_buf(&upload_res.url, buf, None)?;
+                Ok(ImgUploadOutcome::Uploaded)
+            }
+        }
+    }
+
+    /// Upload the guest `elf` to the /images/ route, returning its image ID
+    ///
+    /// The image ID is computed from the ELF binary, as with
+    /// `risc0_zkvm::compute_image_id`, so that it always matches the image
+    /// proven by the sessions created with it. The upload is skipped if the
+    /// image already exists in bonsai.
+    pub fn upload_img_from_elf(&self, elf: &[u8]) -> Result<String, SdkErr> {
+        let image_id = elf_image_id(elf)?;
+        self.upload_img(&image_id, elf.to_vec())?;
+        Ok(image_id)
+    }
+
+    /// Upload a image buffer to the /images/ route, reporting its progress
+    ///
+    /// `progress` is called as the body is sent. The bytes sent can decrease
+    /// when the upload is retried, and `progress` is not called if the image
+    /// already exists in bonsai.
+    pub fn upload_img_with_progress(
+        &self,
+        image_id: &str,
+        buf: Vec<u8>,
+        progress: impl Fn(UploadProgress) + Send + Sync + 'static,
+    ) -> Result<ImgUploadOutcome, SdkErr> {
+        if let Some(backend) = &self.backend {
+            let total = buf.len() as u64;
+            let outcome = backend.upload_img(image_id, buf)?;
+            if outcome == ImgUploadOutcome::Uploaded {
+                progress(UploadProgress { sent: total, total });
+            }
+            return Ok(outcome);
+        }
         let res_or_exists = self.get_image_upload_url(image_id)?;
         match res_or_exists {
-            ImageExistsOpt::Exists => Ok(true),
+            ImageExistsOpt::Exists => Ok(ImgUploadOutcome::AlreadyExists),
             ImageExistsOpt::New(upload_res) => {
-                self.put_data(&upload_res.url, buf)?;
-                Ok(false)
+                self.put_buf(&upload_res.url, buf, Some(Arc::new(progress)))?;
+                Ok(ImgUploadOutcome::Uploaded)
             }
         }
     }
 
     /// Upload a image file to the /images/ route
     ///
-    /// The boolean return indicates if the image already exists in bonsai
+    /// The upload is skipped if the image already exists in bonsai, which is
+    /// reported by [ImgUploadOutcome::AlreadyExists]
     ///
     /// The image data can be either:
     /// * ELF file bytes
     /// * bincode encoded MemoryImage
-    pub fn upload_img_file(&self, image_id: &str, path: &Path) -> Result<bool, SdkErr> {
+    pub fn upload_img_file(&self, image_id: &str, path: &Path) -> Result<ImgUploadOutcome, SdkErr> {
+        if let Some(backend) = &self.backend {
+            return backend.upload_img(image_id, fs::read(path)?);
+        }
         let res_or_exists = self.get_image_upload_url(image_id)?;
         match res_or_exists {
-            ImageExistsOpt::Exists => Ok(true),
+            ImageExistsOpt::Exists => Ok(ImgUploadOutcome::AlreadyExists),
             ImageExistsOpt::New(upload_res) => {
                 let fd = File::open(path)?;
                 self.put_data(&upload_res.url, fd)?;
-                Ok(false)
+                Ok(ImgUploadOutcome::Uploaded)
             }
         }
     }
@@ -438,13 +1442,32 @@ impl Client {
 
     /// Upload a input buffer to the /inputs/ route
     pub fn upload_input(&self, buf: Vec<u8>) -> Result<String, SdkErr> {
-        let upload_data = self.get_upload_url("inputs")?;
-        self.put_data(&upload_data.url, buf)?;
-        Ok(upload_data.uuid)
+        self.upload_input_buf(buf, None)
+    }
+
+    /// Upload a input buffer to the /inputs/ route, reporting its progress
+    ///
+    /// `progress` is called as the body is sent, as in
+    /// [Client::upload_img_with_progress].
+    pub fn upload_input_with_progress(
+        &self,
+        buf: Vec<u8>,
+        progress: impl Fn(UploadProgress) + Send + Sync + 'static,
+    ) -> Result<String, SdkErr> {
+        if let Some(backend) = &self.backend {
+            let total = buf.len() as u64;
+            let input_id = backend.upload_input(buf)?;
+            progress(UploadProgress { sent: total, total });
+            return Ok(input_id);
+        }
+        self.upload_input_buf(buf, Some(Arc::new(progress)))
     }
 
     /// Upload a input file to the /inputs/ route
     pub fn upload_input_file(&self, path: &Path) -> Result<String, SdkErr> {
+        if let Some(backend) = &self.backend {
+            return backend.upload_input(fs::read(path)?);
+        }
         let upload_data = self.get_upload_url("inputs")?;
 
         let fd = File::open(path)?;
@@ -453,17 +1476,37 @@ impl Client {
         Ok(upload_data.uuid)
     }
 
+    /// Upload a input buffer, reporting its progress if given
+    fn upload_input_buf(
+        &self,
+        buf: Vec<u8>,
+        progress: Option<ProgressFn>,
+    ) -> Result<String, SdkErr> {
+        if let Some(backend) = &self.backend {
+            return backend.upload_input(buf);
+        }
+        let upload_data = self.get_upload_url("inputs")?;
+        self.put_buf(&upload_data.url, buf, progress)?;
+        Ok(upload_data.uuid)
+    }
+
     // - /receipts
 
     /// Upload a receipt buffer to the /receipts/ route
     pub fn upload_receipt(&self, buf: Vec<u8>) -> Result<String, SdkErr> {
+        if let Some(backend) = &self.backend {
+            return backend.upload_receipt(buf);
+        }
         let upload_data = self.get_upload_url("receipts")?;
-        self.put_data(&upload_data.url, buf)?;
+        self.put_buf(&upload_data.url, buf, None)?;
         Ok(upload_data.uuid)
     }
 
     /// Upload a receipt file to the /receipts/ route
     pub fn upload_receipt_file(&self, path: &Path) -> Result<String, SdkErr> {
+        if let Some(backend) = &self.backend {
+            return backend.upload_receipt(fs::read(path)?);
+        }
         let upload_data = self.get_upload_url("receipts")?;
 
         let fd = File::open(path)?;
@@ -476,18 +1519,97 @@ impl Client {
     ///
     /// Allows download of older receipts without checking the current session status.
     pub fn receipt_download(&self, session_id: &SessionId) -> Result<Vec<u8>, SdkErr> {
+        if let Some(backend) = &self.backend {
+            return backend.receipt_download(session_id);
+        }
+        let url = self.receipt_url(session_id)?;
+        self.download(&url)
+    }
+
+    /// Download a existing receipt of a session of the image `image_id`, and
+    /// verify it
+    ///
+    /// The receipt is checked with the [ReceiptVerifier] of the client, see
+    /// [Client::with_receipt_verifier], and [SdkErr::InvalidReceipt] is
+    /// returned if it does not verify or if the client has no verifier.
+    pub fn verified_receipt_download(
+        &self,
+        session_id: &SessionId,
+        image_id: &str,
+    ) -> Result<Vec<u8>, SdkErr> {
+        let receipt = self.receipt_download(session_id)?;
+        verify_receipt(self.verifier.as_deref(), image_id, &receipt)?;
+        Ok(receipt)
+    }
+
+    /// Stream an existing receipt, without buffering it in memory
+    ///
+    /// The returned [ReceiptReader] verifies the length and checksum of the
+    /// receipt once it has been read to the end.
+    pub fn receipt_reader(&self, session_id: &SessionId) -> Result<ReceiptReader, SdkErr> {
+        if self.backend.is_some() {
+            return Err(SdkErr::Unsupported("streaming receipts"));
+        }
+        let url = self.receipt_url(session_id)?;
+        let res = self.client.get(url).send()?;
+        if !res.status().is_success() {
+            return Err(SdkErr::from_status(res.status(), res.text()?));
+        }
+        ReceiptReader::new(res)
+    }
+
+    /// Stream an existing receipt into `writer`, returning its size in bytes
+    ///
+    /// Returns [SdkErr::IntegrityErr] if the receipt fails verification, in
+    /// which case `writer` has received unverified data.
+    pub fn download_receipt_to_writer(
+        &self,
+        session_id: &SessionId,
+        writer: &mut impl Write,
+    ) -> Result<u64, SdkErr> {
+        if let Some(backend) = &self.backend {
+            let receipt = backend.receipt_download(session_id)?;
+            writer.write_all(&receipt)?;
+            return Ok(receipt.len() as u64);
+        }
+        let mut reader = self.receipt_reader(session_id)?;
+        io::copy(&mut reader, writer).map_err(|err| match err.kind() {
+            io::ErrorKind::InvalidData => SdkErr::IntegrityErr(err.to_string()),
+            _ => SdkErr::FileNotFound(err),
+        })
+    }
+
+    /// Stream an existing receipt to the file at `path`, returning its size
+    /// in bytes
+    ///
+    /// The file is removed if the download fails or does not pass
+    /// verification.
+    pub fn download_receipt_to(&self, session_id: &SessionId, path: &Path) -> Result<u64, SdkErr> {
+        let mut file = File::create(path)?;
+        let res = self
+            .download_receipt_to_writer(session_id, &mut file)
+            .and_then(|len| {
+                file.sync_all()?;
+                Ok(len)
+            });
+        if res.is_err() {
+            drop(file);
+            let _ = fs::remove_file(path);
+        }
+        res
+    }
+
+    fn receipt_url(&self, session_id: &SessionId) -> Result<String, SdkErr> {
         let res = self
             .client
             .get(format!("{}/receipts/{}", self.url, session_id.uuid))
             .send()?;
 
         if !res.status().is_success() {
-            let body = res.text()?;
-            return Err(SdkErr::InternalServerErr(body));
+            return Err(SdkErr::from_status(res.status(), res.text()?));
         }
         let res: ReceiptDownload = res.json()?;
-
-        self.download(&res.url)
+        Ok(res.url)
     }
 
     // - /sessions
@@ -502,19 +1624,37 @@ impl Client {
         input_id: String,
         assumptions: Vec<String>,
     ) -> Result<SessionId, SdkErr> {
+        self.create_session_with_opts(img_id, input_id, assumptions, SessionOpts::default())
+    }
+
+    /// Create a new Session with the given [SessionOpts]
+    ///
+    /// See [Client::create_session].
+    pub fn create_session_with_opts(
+        &self,
+        img_id: String,
+        input_id: String,
+        assumptions: Vec<String>,
+        opts: SessionOpts,
+    ) -> Result<SessionId, SdkErr> {
+        if let Some(backend) = &self.backend {
+            return backend.create_session(img_id, input_id, assumptions, opts);
+        }
+
         let url = format!("{}/sessions/create", self.url);
 
         let req = ProofReq {
             img: img_id,
             input: input_id,
             assumptions,
+            execute_only: opts.execute_only,
+            metadata: opts.metadata,
         };
 
-        let res = self.client.post(url).json(&req).send()?;
+        let res = self.send(self.client.post(url).json(&req))?;
 
         if !res.status().is_success() {
-            let body = res.text()?;
-            return Err(SdkErr::InternalServerErr(body));
+            return Err(SdkErr::from_status(res.status(), res.text()?));
         }
 
         let res: CreateSessRes = res.json()?;
@@ -522,17 +1662,108 @@ impl Client {
         Ok(SessionId::new(res.uuid))
     }
 
+    /// Lists the sessions of this account matching `filter`
+    ///
+    /// Returns one page of sessions. Pass the [SessionList::next_cursor] of a
+    /// page as the `cursor` of the next call to fetch the following page:
+    ///
+    /// ```no_run
+    /// use bonsai_sdk::alpha as bonsai_sdk;
+    /// # fn main() -> Result<(), bonsai_sdk::SdkErr> {
+    /// let client = bonsai_sdk::Client::from_env(risc0_zkvm::VERSION)?;
+    /// let filter = bonsai_sdk::SessionFilter {
+    ///     status: Some("RUNNING".to_string()),
+    ///     ..Default::default()
+    /// };
+    /// let mut cursor = None;
+    /// loop {
+    ///     let page = client.list_sessions(&filter, cursor.as_deref())?;
+    ///     for session in page.sessions {
+    ///         println!("{} {}", session.uuid, session.image_id);
+    ///     }
+    ///     cursor = match page.next_cursor {
+    ///         Some(next) => Some(next),
+    ///         None => break,
+    ///     };
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn list_sessions(
+        &self,
+        filter: &SessionFilter,
+        cursor: Option<&str>,
+    ) -> Result<SessionList, SdkErr> {
+        if self.backend.is_some() {
+            return Err(SdkErr::Unsupported("listing sessions"));
+        }
+        let mut req = self
+            .client
+            .get(format!("{}/sessions", self.url))
+            .query(filter);
+        if let Some(cursor) = cursor {
+            req = req.query(&[("cursor", cursor)]);
+        }
+        let res = self.send(req)?;
+
+        if !res.status().is_success() {
+            return Err(SdkErr::from_status(res.status(), res.text()?));
+        }
+        Ok(res.json::<SessionList>()?)
+    }
+
     // Utilities
 
     /// Download a given url to a buffer
     ///
     /// Useful to download a [SessionId] receipt_url
     pub fn download(&self, url: &str) -> Result<Vec<u8>, SdkErr> {
+        if let Some(backend) = &self.backend {
+            return backend.download(url);
+        }
+
         let data = self.client.get(url).send()?.bytes()?;
 
         Ok(data.into())
     }
 
+    /// Streams the [SessionEvent]s of a Session until it ends
+    ///
+    /// Replaces polling [SessionId::status] in a loop. Server-sent events are
+    /// used when the API provides them, falling back to polling every
+    /// [StatusStream::DEFAULT_POLL_INTERVAL] otherwise.
+    pub fn stream_status(&self, session_id: &SessionId) -> StatusStream<'_> {
+        // Other backends do not push events, and are polled.
+        let events = self
+            .backend
+            .is_none()
+            .then(|| {
+                self.client
+                    .get(format!("{}/sessions/stream/{}", self.url, session_id.uuid))
+                    .header(header::ACCEPT, "text/event-stream")
+                    .send()
+                    .ok()
+                    .filter(|res| {
+                        res.status().is_success()
+                            && res
+                                .headers()
+                                .get(header::CONTENT_TYPE)
+                                .and_then(|value| value.to_str().ok())
+                                .is_some_and(|value| value.starts_with("text/event-stream"))
+                    })
+                    .map(|res| BufReader::new(res).lines())
+            })
+            .flatten();
+        StatusStream {
+            client: self,
+            session: session_id.clone(),
+            interval: StatusStream::DEFAULT_POLL_INTERVAL,
+            events,
+            last: None,
+            done: false,
+        }
+    }
+
     // - /snark
 
     /// Requests a SNARK proof be created from a existing sessionId
@@ -540,15 +1771,18 @@ impl Client {
     /// Supply a completed sessionId to convert the risc0 STARK proof into
     /// a SNARK proof that can be validated on ethereum-like blockchains
     pub fn create_snark(&self, session_id: String) -> Result<SnarkId, SdkErr> {
+        if let Some(backend) = &self.backend {
+            return backend.create_snark(session_id);
+        }
+
         let url = format!("{}/snark/create", self.url);
 
         let snark_req = SnarkReq { session_id };
 
-        let res = self.client.post(url).json(&snark_req).send()?;
+        let res = self.send(self.client.post(url).json(&snark_req))?;
 
         if !res.status().is_success() {
-            let body = res.text()?;
-            return Err(SdkErr::InternalServerErr(body));
+            return Err(SdkErr::from_status(res.status(), res.text()?));
         }
 
         // Reuse the session response because its the same member format
@@ -564,11 +1798,29 @@ impl Client {
     /// Fetches the risc0 zkvm supported versions as well as other
     /// sub-components of bonsai
     pub fn version(&self) -> Result<VersionInfo, SdkErr> {
-        Ok(self
-            .client
-            .get(format!("{}/version", self.url))
-            .send()?
-            .json::<VersionInfo>()?)
+        if let Some(backend) = &self.backend {
+            return backend.version();
+        }
+
+        let res = self.client.get(format!("{}/version", self.url)).send()?;
+
+        if !res.status().is_success() {
+            return Err(SdkErr::from_status(res.status(), res.text()?));
+        }
+        Ok(res.json::<VersionInfo>()?)
+    }
+
+    /// Checks that bonsai supports this SDK and the risc0-zkvm version of this
+    /// client
+    ///
+    /// Fails with [SdkErr::IncompatibleVersion] if bonsai does not advertise
+    /// [API_VERSION] or a risc0-zkvm version producing receipts compatible
+    /// with those of this client, rather than failing to decode responses
+    /// later in a workflow. Returns the versions advertised by bonsai.
+    pub fn check_compat(&self) -> Result<VersionInfo, SdkErr> {
+        let info = self.version()?;
+        check_versions(&info, &self.risc0_version)?;
+        Ok(info)
     }
 
     // - /user
@@ -577,6 +1829,9 @@ impl Client {
     ///
     /// Returns the [Quotas] structure with relevant data on cycle budget, quotas etc.
     pub fn quotas(&self) -> Result<Quotas, SdkErr> {
+        if self.backend.is_some() {
+            return Err(SdkErr::Unsupported("quotas"));
+        }
         Ok(self
             .client
             .get(format!("{}/user/quotas", self.url))
@@ -585,12 +1840,72 @@ impl Client {
     }
 }
 
+impl Backend for Client {
+    fn has_img(&self, image_id: &str) -> Result<bool, SdkErr> {
+        Client::has_img(self, image_id)
+    }
+
+    fn upload_img(&self, image_id: &str, buf: Vec<u8>) -> Result<ImgUploadOutcome, SdkErr> {
+        Client::upload_img(self, image_id, buf)
+    }
+
+    fn upload_input(&self, buf: Vec<u8>) -> Result<String, SdkErr> {
+        Client::upload_input(self, buf)
+    }
+
+    fn upload_receipt(&self, buf: Vec<u8>) -> Result<String, SdkErr> {
+        Client::upload_receipt(self, buf)
+    }
+
+    fn create_session(
+        &self,
+        img_id: String,
+        input_id: String,
+        assumptions: Vec<String>,
+        opts: SessionOpts,
+    ) -> Result<SessionId, SdkErr> {
+        self.create_session_with_opts(img_id, input_id, assumptions, opts)
+    }
+
+    fn session_status(&self, session_id: &SessionId) -> Result<SessionStatusRes, SdkErr> {
+        session_id.status(self)
+    }
+
+    fn exec_only_journal(&self, session_id: &SessionId) -> Result<Vec<u8>, SdkErr> {
+        session_id.exec_only_journal(self)
+    }
+
+    fn receipt_download(&self, session_id: &SessionId) -> Result<Vec<u8>, SdkErr> {
+        Client::receipt_download(self, session_id)
+    }
+
+    fn download(&self, url: &str) -> Result<Vec<u8>, SdkErr> {
+        Client::download(self, url)
+    }
+
+    fn create_snark(&self, session_id: String) -> Result<SnarkId, SdkErr> {
+        Client::create_snark(self, session_id)
+    }
+
+    fn snark_status(&self, snark_id: &SnarkId) -> Result<SnarkStatusRes, SdkErr> {
+        snark_id.status(self)
+    }
+
+    fn version(&self) -> Result<VersionInfo, SdkErr> {
+        Client::version(self)
+    }
+}
+
 #[cfg(test)]
 mod tests {
-    use httpmock::prelude::*;
+    use httpmock::{prelude::*, Method};
+    use risc0_groth16::Seal;
     use uuid::Uuid;
 
-    use super::*;
+    use super::{
+        responses::{SessionInfo, SnarkReceipt},
+        *,
+    };
 
     const TEST_KEY: &str = "TESTKEY";
     const TEST_ID: &str = "0x5891b5b522d5df086d0ff0b110fbd9d21bb4fc7163af34d08286a2e846f6be03";
@@ -634,6 +1949,71 @@ mod tests {
         );
     }
 
+    #[test]
+    fn retry_backoff() {
+        let retry = RetryPolicy {
+            jitter: false,
+            ..Default::default()
+        };
+        assert_eq!(retry.backoff(1), Duration::from_millis(500));
+        assert_eq!(retry.backoff(2), Duration::from_secs(1));
+        assert_eq!(retry.backoff(3), Duration::from_secs(2));
+        assert_eq!(retry.backoff(100), retry.max_backoff);
+
+        let retry = RetryPolicy::default();
+        for attempt in 1..10 {
+            let backoff = retry.backoff(attempt);
+            let max = RetryPolicy {
+                jitter: false,
+                ..retry.clone()
+            }
+            .backoff(attempt);
+            assert!(backoff >= max / 2 && backoff <= max);
+        }
+    }
+
+    #[test]
+    fn retry_server_error() {
+        let server = MockServer::start();
+
+        let get_mock = server.mock(|when, then| {
+            when.method(GET).path("/inputs/upload");
+            then.status(503).body("unavailable");
+        });
+
+        let retry = RetryPolicy {
+            initial_backoff: Duration::from_millis(1),
+            ..Default::default()
+        };
+        let server_url = format!("http://{}", server.address());
+        let client = super::Client::from_parts(server_url, TEST_KEY.to_string(), TEST_VERSION)
+            .unwrap()
+            .with_retry_policy(retry.clone());
+        let err = client.upload_input(vec![]).unwrap_err();
+        assert!(matches!(err, SdkErr::InternalServerErr(body) if body == "unavailable"));
+        get_mock.assert_hits(retry.max_attempts as usize);
+
+        let client = client.with_retry_policy(RetryPolicy::none());
+        client.upload_input(vec![]).unwrap_err();
+        get_mock.assert_hits(retry.max_attempts as usize + 1);
+    }
+
+    #[test]
+    fn retry_client_error() {
+        let server = MockServer::start();
+
+        let get_mock = server.mock(|when, then| {
+            when.method(GET).path("/inputs/upload");
+            then.status(401).body("unauthorized");
+        });
+
+        let server_url = format!("http://{}", server.address());
+        let client =
+            super::Client::from_parts(server_url, TEST_KEY.to_string(), TEST_VERSION).unwrap();
+        client.upload_input(vec![]).unwrap_err();
+        get_mock.assert_hits(1);
+    }
+
     #[test]
     fn image_upload() {
         let data = vec![];
@@ -661,14 +2041,33 @@ mod tests {
         let server_url = format!("http://{}", server.address());
         let client = super::Client::from_parts(server_url, TEST_KEY.to_string(), TEST_VERSION)
             .expect("Failed to construct client");
-        let exists = client
+        let outcome = client
             .upload_img(TEST_ID, data)
             .expect("Failed to upload input");
-        assert!(!exists);
+        assert_eq!(outcome, ImgUploadOutcome::Uploaded);
         get_mock.assert();
         put_mock.assert();
     }
 
+    #[test]
+    fn image_upload_from_elf() {
+        let server = MockServer::start();
+        let upload_mock = server.mock(|when, then| {
+            when.path_contains("/images/");
+            then.status(200);
+        });
+
+        let server_url = format!("http://{}", server.address());
+        let client = super::Client::from_parts(server_url, TEST_KEY.to_string(), TEST_VERSION)
+            .expect("Failed to construct client");
+
+        // No image ID can be computed from a file which is not an ELF
+        // binary, so nothing is uploaded.
+        let err = client.upload_img_from_elf(b"not an elf").unwrap_err();
+        assert!(matches!(err, SdkErr::InvalidElf(_)), "{err}");
+        upload_mock.assert_hits(0);
+    }
+
     #[test]
     fn image_upload_dup() {
         let data = vec![0x41];
@@ -694,8 +2093,88 @@ mod tests {
         let server_url = format!("http://{}", server.address());
         let client = super::Client::from_parts(server_url, TEST_KEY.to_string(), TEST_VERSION)
             .expect("Failed to construct client");
-        let exists = client.upload_img(TEST_ID, data).unwrap();
-        assert!(exists);
+        let outcome = client.upload_img(TEST_ID, data).unwrap();
+        assert_eq!(outcome, ImgUploadOutcome::AlreadyExists);
+    }
+
+    #[test]
+    fn image_upload_skip() {
+        let server = MockServer::start();
+
+        let head_mock = server.mock(|when, then| {
+            when.method(Method::HEAD)
+                .path(format!("/images/{TEST_ID}"))
+                .header(API_KEY_HEADER, TEST_KEY)
+                .header(VERSION_HEADER, TEST_VERSION);
+            then.status(200);
+        });
+
+        let get_mock = server.mock(|when, then| {
+            when.method(GET).path(format!("/images/upload/{TEST_ID}"));
+            then.status(500);
+        });
+
+        let server_url = format!("http://{}", server.address());
+        let client = super::Client::from_parts(server_url, TEST_KEY.to_string(), TEST_VERSION)
+            .expect("Failed to construct client");
+        assert!(client.has_img(TEST_ID).unwrap());
+        let outcome = client.upload_img(TEST_ID, vec![0x41]).unwrap();
+        assert_eq!(outcome, ImgUploadOutcome::AlreadyExists);
+        head_mock.assert_hits(2);
+        get_mock.assert_hits(0);
+    }
+
+    #[test]
+    fn image_upload_without_head() {
+        let server = MockServer::start();
+
+        let head_mock = server.mock(|when, then| {
+            when.method(Method::HEAD).path(format!("/images/{TEST_ID}"));
+            then.status(405);
+        });
+
+        let get_mock = server.mock(|when, then| {
+            when.method(GET).path(format!("/images/upload/{TEST_ID}"));
+            then.status(204);
+        });
+
+        let server_url = format!("http://{}", server.address());
+        let client = super::Client::from_parts(server_url, TEST_KEY.to_string(), TEST_VERSION)
+            .expect("Failed to construct client");
+        let outcome = client.upload_img(TEST_ID, vec![0x41]).unwrap();
+        assert_eq!(outcome, ImgUploadOutcome::AlreadyExists);
+        head_mock.assert();
+        get_mock.assert();
+    }
+
+    #[test]
+    fn has_image() {
+        let server = MockServer::start();
+
+        let head_mock = server.mock(|when, then| {
+            when.method(Method::HEAD).path(format!("/images/{TEST_ID}"));
+            then.status(404);
+        });
+
+        let server_url = format!("http://{}", server.address());
+        let client = super::Client::from_parts(server_url, TEST_KEY.to_string(), TEST_VERSION)
+            .expect("Failed to construct client");
+        assert!(!client.has_img(TEST_ID).unwrap());
+        head_mock.assert();
+
+        let server = MockServer::start();
+        server.mock(|when, then| {
+            when.method(Method::HEAD).path(format!("/images/{TEST_ID}"));
+            then.status(401);
+        });
+
+        let server_url = format!("http://{}", server.address());
+        let client = super::Client::from_parts(server_url, TEST_KEY.to_string(), TEST_VERSION)
+            .expect("Failed to construct client");
+        assert!(matches!(
+            client.has_img(TEST_ID),
+            Err(SdkErr::Unauthorized(_))
+        ));
     }
 
     #[test]
@@ -738,39 +2217,88 @@ mod tests {
     }
 
     #[test]
-    fn receipt_upload() {
-        let data = vec![];
+    fn input_upload_progress() {
+        let data = vec![0x41; 10];
 
         let server = MockServer::start();
 
-        let receipt_uuid = Uuid::new_v4();
-        let put_url = format!("http://{}/upload/{}", server.address(), receipt_uuid);
+        let input_uuid = Uuid::new_v4();
+        let put_url = format!("http://{}/upload/{}", server.address(), input_uuid);
         let response = UploadRes {
             url: put_url,
-            uuid: receipt_uuid.to_string(),
+            uuid: input_uuid.to_string(),
         };
 
-        let get_mock = server.mock(|when, then| {
-            when.method(GET)
-                .path("/receipts/upload")
-                .header(API_KEY_HEADER, TEST_KEY)
-                .header(VERSION_HEADER, TEST_VERSION);
+        server.mock(|when, then| {
+            when.method(GET).path("/inputs/upload");
             then.status(200)
                 .header("content-type", "application/json")
                 .json_body_obj(&response);
         });
 
         let put_mock = server.mock(|when, then| {
-            when.method(PUT).path(format!("/upload/{}", receipt_uuid));
+            when.method(PUT)
+                .path(format!("/upload/{}", input_uuid))
+                .body(String::from_utf8(data.clone()).unwrap());
             then.status(200);
         });
 
         let server_url = format!("http://{}", server.address());
-        let client = super::Client::from_parts(server_url, TEST_KEY.to_string(), TEST_VERSION)
-            .expect("Failed to construct client");
+        let client =
+            super::Client::from_parts(server_url, TEST_KEY.to_string(), TEST_VERSION).unwrap();
+        let events = Arc::new(std::sync::Mutex::new(vec![]));
+        let recorded = events.clone();
         let res = client
-            .upload_receipt(data)
-            .expect("Failed to upload receipt");
+            .upload_input_with_progress(data, move |progress| {
+                recorded.lock().unwrap().push(progress)
+            })
+            .unwrap();
+
+        assert_eq!(res, response.uuid);
+        put_mock.assert();
+        assert_eq!(
+            events.lock().unwrap().last(),
+            Some(&UploadProgress {
+                sent: 10,
+                total: 10
+            })
+        );
+    }
+
+    #[test]
+    fn receipt_upload() {
+        let data = vec![];
+
+        let server = MockServer::start();
+
+        let receipt_uuid = Uuid::new_v4();
+        let put_url = format!("http://{}/upload/{}", server.address(), receipt_uuid);
+        let response = UploadRes {
+            url: put_url,
+            uuid: receipt_uuid.to_string(),
+        };
+
+        let get_mock = server.mock(|when, then| {
+            when.method(GET)
+                .path("/receipts/upload")
+                .header(API_KEY_HEADER, TEST_KEY)
+                .header(VERSION_HEADER, TEST_VERSION);
+            then.status(200)
+                .header("content-type", "application/json")
+                .json_body_obj(&response);
+        });
+
+        let put_mock = server.mock(|when, then| {
+            when.method(PUT).path(format!("/upload/{}", receipt_uuid));
+            then.status(200);
+        });
+
+        let server_url = format!("http://{}", server.address());
+        let client = super::Client::from_parts(server_url, TEST_KEY.to_string(), TEST_VERSION)
+            .expect("Failed to construct client");
+        let res = client
+            .upload_receipt(data)
+            .expect("Failed to upload receipt");
 
         assert_eq!(res, response.uuid);
 
@@ -823,6 +2351,140 @@ mod tests {
         download_mock.assert();
     }
 
+    #[test]
+    fn receipt_verifier() {
+        struct ExpectReceipt(Vec<u8>);
+
+        impl ReceiptVerifier for ExpectReceipt {
+            fn verify(&self, receipt: &[u8], image_id: &str) -> Result<(), String> {
+                if image_id != TEST_ID {
+                    return Err(format!("unexpected image ID {image_id}"));
+                }
+                match receipt == self.0 {
+                    true => Ok(()),
+                    false => Err("bad seal".to_string()),
+                }
+            }
+        }
+
+        let server = MockServer::start();
+        let session_id = SessionId::new(Uuid::new_v4().to_string());
+        let download_url = format!("http://{}/download_path", server.address());
+        server.mock(|when, then| {
+            when.method(GET)
+                .path(format!("/receipts/{}", session_id.uuid));
+            then.status(200)
+                .header("content-type", "application/json")
+                .json_body_obj(&ReceiptDownload { url: download_url });
+        });
+        server.mock(|when, then| {
+            when.method(GET).path("/download_path");
+            then.status(200).body([1, 2, 3, 4]);
+        });
+
+        let server_url = format!("http://{}", server.address());
+        let client = |expected: Vec<u8>| {
+            super::Client::from_parts(server_url.clone(), TEST_KEY.to_string(), TEST_VERSION)
+                .unwrap()
+                .with_receipt_verifier(ExpectReceipt(expected))
+        };
+
+        let valid = client(vec![1, 2, 3, 4]);
+        assert_eq!(
+            valid
+                .verified_receipt_download(&session_id, TEST_ID)
+                .unwrap(),
+            vec![1, 2, 3, 4]
+        );
+        let err = valid
+            .verified_receipt_download(&session_id, "00")
+            .unwrap_err();
+        assert!(
+            matches!(&err, SdkErr::InvalidReceipt(msg) if msg.contains("unexpected image ID")),
+            "{err}"
+        );
+
+        let invalid = client(vec![5]);
+        let err = invalid
+            .verified_receipt_download(&session_id, TEST_ID)
+            .unwrap_err();
+        assert!(
+            matches!(&err, SdkErr::InvalidReceipt(msg) if msg == "bad seal"),
+            "{err}"
+        );
+        assert!(!err.is_retryable());
+
+        // Without a verifier, receipts are not returned as verified.
+        let unverified =
+            super::Client::from_parts(server_url, TEST_KEY.to_string(), TEST_VERSION).unwrap();
+        assert!(matches!(
+            unverified.verified_receipt_download(&session_id, TEST_ID),
+            Err(SdkErr::InvalidReceipt(_))
+        ));
+    }
+
+    #[test]
+    fn download_receipt_to() {
+        let server = MockServer::start();
+        let receipt_uuid = Uuid::new_v4();
+        let bad_uuid = Uuid::new_v4();
+
+        let receipt_data: Vec<u8> = vec![0x41, 0x41, 0x42, 0x42];
+        // base64 SHA-256 of `receipt_data`
+        let checksum = "L46g9ulVlLsYn6XSoeJqDNGVNEu5rKD82zUzCzsljkA=";
+
+        for (uuid, download_method, checksum) in [
+            (receipt_uuid, "download_path", checksum),
+            (bad_uuid, "bad_download_path", "AAAA"),
+        ] {
+            let download_url = format!("http://{}/{download_method}", server.address());
+            server.mock(|when, then| {
+                when.method(GET).path(format!("/receipts/{uuid}"));
+                then.status(200)
+                    .header("content-type", "application/json")
+                    .json_body_obj(&ReceiptDownload { url: download_url });
+            });
+            server.mock(|when, then| {
+                when.method(GET).path(format!("/{download_method}"));
+                then.status(200)
+                    .header("x-amz-checksum-sha256", checksum)
+                    .body(&receipt_data);
+            });
+        }
+
+        let server_url = format!("http://{}", server.address());
+        let client = super::Client::from_parts(server_url, TEST_KEY.to_string(), TEST_VERSION)
+            .expect("Failed to construct client");
+
+        let dir = std::env::temp_dir().join(format!("bonsai-sdk-{receipt_uuid}"));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("receipt.bin");
+        let len = client
+            .download_receipt_to(
+                &SessionId {
+                    uuid: receipt_uuid.to_string(),
+                },
+                &path,
+            )
+            .expect("Failed to download receipt");
+        assert_eq!(len, receipt_data.len() as u64);
+        assert_eq!(std::fs::read(&path).unwrap(), receipt_data);
+
+        let bad_path = dir.join("bad_receipt.bin");
+        let err = client
+            .download_receipt_to(
+                &SessionId {
+                    uuid: bad_uuid.to_string(),
+                },
+                &bad_path,
+            )
+            .unwrap_err();
+        assert!(matches!(err, SdkErr::IntegrityErr(_)));
+        assert!(!bad_path.exists());
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
     #[test]
     fn session_create() {
         let server = MockServer::start();
@@ -831,6 +2493,8 @@ mod tests {
             img: TEST_ID.to_string(),
             input: Uuid::new_v4().to_string(),
             assumptions: vec![],
+            execute_only: false,
+            metadata: SessionMetadata::default(),
         };
         let response = CreateSessRes {
             uuid: Uuid::new_v4().to_string(),
@@ -860,6 +2524,122 @@ mod tests {
         create_mock.assert();
     }
 
+    #[test]
+    fn session_execute_only() {
+        let server = MockServer::start();
+
+        let request = ProofReq {
+            img: TEST_ID.to_string(),
+            input: Uuid::new_v4().to_string(),
+            assumptions: vec![],
+            execute_only: true,
+            metadata: SessionMetadata::default(),
+        };
+        let session_id = SessionId::new(Uuid::new_v4().to_string());
+        let journal = vec![1, 2, 3, 4];
+
+        let create_mock = server.mock(|when, then| {
+            when.method(POST)
+                .path("/sessions/create")
+                .json_body_obj(&request);
+            then.status(200)
+                .header("content-type", "application/json")
+                .json_body_obj(&CreateSessRes {
+                    uuid: session_id.uuid.clone(),
+                });
+        });
+        let journal_mock = server.mock(|when, then| {
+            when.method(GET)
+                .path(format!("/sessions/exec_only_journal/{}", session_id.uuid))
+                .header(API_KEY_HEADER, TEST_KEY)
+                .header(VERSION_HEADER, TEST_VERSION);
+            then.status(200).body(&journal);
+        });
+
+        let server_url = format!("http://{}", server.address());
+        let client =
+            super::Client::from_parts(server_url, TEST_KEY.to_string(), TEST_VERSION).unwrap();
+
+        let res = client
+            .create_session_with_opts(
+                request.img,
+                request.input,
+                request.assumptions,
+                SessionOpts {
+                    execute_only: true,
+                    ..Default::default()
+                },
+            )
+            .unwrap();
+        assert_eq!(res, session_id);
+        assert_eq!(res.exec_only_journal(&client).unwrap(), journal);
+
+        create_mock.assert();
+        journal_mock.assert();
+    }
+
+    #[test]
+    fn session_metadata() {
+        let server = MockServer::start();
+
+        let metadata = SessionMetadata {
+            labels: [("tenant".to_string(), "acme".to_string())].into(),
+            priority: 7,
+            deadline: Some(1_700_000_000),
+        };
+        let request = ProofReq {
+            img: TEST_ID.to_string(),
+            input: Uuid::new_v4().to_string(),
+            assumptions: vec![],
+            execute_only: false,
+            metadata: metadata.clone(),
+        };
+        let session_id = SessionId::new(Uuid::new_v4().to_string());
+        let response = SessionStatusRes {
+            metadata: Some(metadata.clone()),
+            ..status_res("RUNNING", Some("Setup"))
+        };
+
+        let create_mock = server.mock(|when, then| {
+            when.method(POST)
+                .path("/sessions/create")
+                .json_body_obj(&request);
+            then.status(200)
+                .header("content-type", "application/json")
+                .json_body_obj(&CreateSessRes {
+                    uuid: session_id.uuid.clone(),
+                });
+        });
+        let status_mock = server.mock(|when, then| {
+            when.method(GET)
+                .path(format!("/sessions/status/{}", session_id.uuid));
+            then.status(200)
+                .header("content-type", "application/json")
+                .json_body_obj(&response);
+        });
+
+        let server_url = format!("http://{}", server.address());
+        let client =
+            super::Client::from_parts(server_url, TEST_KEY.to_string(), TEST_VERSION).unwrap();
+
+        let opts = SessionOpts {
+            metadata: metadata.clone(),
+            ..Default::default()
+        };
+        let res = client
+            .create_session_with_opts(request.img, request.input, request.assumptions, opts)
+            .unwrap();
+        assert_eq!(res, session_id);
+        assert_eq!(res.status(&client).unwrap().metadata, Some(metadata));
+
+        // Services without metadata leave it out of the status.
+        let status: SessionStatusRes = serde_json::from_str(r#"{"status":"RUNNING"}"#).unwrap();
+        assert_eq!(status.metadata, None);
+
+        create_mock.assert();
+        status_mock.assert();
+    }
+
     #[test]
     fn session_status() {
         let server = MockServer::start();
@@ -873,6 +2653,7 @@ mod tests {
             state: None,
             elapsed_time: None,
             stats: None,
+            metadata: None,
         };
 
         let create_mock = server.mock(|when, then| {
@@ -896,6 +2677,318 @@ mod tests {
         create_mock.assert();
     }
 
+    #[test]
+    fn session_status_errors() {
+        let server = MockServer::start();
+        let server_url = format!("http://{}", server.address());
+        let client = super::Client::from_parts(server_url, TEST_KEY.to_string(), TEST_VERSION)
+            .unwrap()
+            .with_retry_policy(RetryPolicy::none());
+
+        for (code, body) in [
+            (401, "bad key"),
+            (404, "no session"),
+            (429, "too many"),
+            (400, "bad request"),
+            (503, "unavailable"),
+            (200, "not json"),
+        ] {
+            let session_id = SessionId::new(Uuid::new_v4().to_string());
+            server.mock(|when, then| {
+                when.method(GET)
+                    .path(format!("/sessions/status/{}", session_id.uuid));
+                then.status(code).body(body);
+            });
+
+            let err = session_id.status(&client).err().unwrap();
+            match code {
+                401 => assert!(matches!(&err, SdkErr::Unauthorized(msg) if msg == body)),
+                404 => assert!(matches!(&err, SdkErr::NotFound(msg) if msg == body)),
+                429 => assert!(matches!(&err, SdkErr::QuotaExceeded(msg) if msg == body)),
+                400 => assert!(matches!(
+                    &err,
+                    SdkErr::RequestFailed { status: 400, body: msg } if msg == body
+                )),
+                503 => assert!(matches!(&err, SdkErr::InternalServerErr(msg) if msg == body)),
+                _ => assert!(matches!(&err, SdkErr::MalformedResponse(_))),
+            }
+            assert_eq!(err.is_retryable(), matches!(code, 429 | 503), "{err}");
+        }
+    }
+
+    fn status_res(status: &str, state: Option<&str>) -> SessionStatusRes {
+        SessionStatusRes {
+            status: status.to_string(),
+            receipt_url: (status == "SUCCEEDED").then(|| "http://receipt".to_string()),
+            error_msg: None,
+            state: state.map(str::to_string),
+            elapsed_time: None,
+            stats: None,
+            metadata: None,
+        }
+    }
+
+    #[test]
+    fn stream_status_pushed() {
+        let server = MockServer::start();
+
+        let session_id = SessionId::new(Uuid::new_v4().to_string());
+        let body: String = [
+            status_res("RUNNING", Some("Setup")),
+            status_res("RUNNING", Some("Setup")),
+            status_res("RUNNING", Some("Executor")),
+            status_res("SUCCEEDED", None),
+        ]
+        .iter()
+        .map(|res| format!("data: {}\n\n", serde_json::to_string(res).unwrap()))
+        .collect();
+
+        let stream_mock = server.mock(|when, then| {
+            when.method(GET)
+                .path(format!("/sessions/stream/{}", session_id.uuid))
+                .header("accept", "text/event-stream")
+                .header(API_KEY_HEADER, TEST_KEY)
+                .header(VERSION_HEADER, TEST_VERSION);
+            then.status(200)
+                .header("content-type", "text/event-stream")
+                .body(body);
+        });
+
+        let server_url = format!("http://{}", server.address());
+        let client =
+            super::Client::from_parts(server_url, TEST_KEY.to_string(), TEST_VERSION).unwrap();
+
+        let events: Vec<_> = client
+            .stream_status(&session_id)
+            .collect::<Result<_, _>>()
+            .unwrap();
+        assert_eq!(
+            events,
+            [
+                SessionEvent::Running {
+                    state: Some("Setup".to_string())
+                },
+                SessionEvent::Running {
+                    state: Some("Executor".to_string())
+                },
+                SessionEvent::Succeeded {
+                    receipt_url: Some("http://receipt".to_string())
+                },
+            ]
+        );
+        stream_mock.assert();
+    }
+
+    #[test]
+    fn stream_status_polling() {
+        let server = MockServer::start();
+
+        let session_id = SessionId::new(Uuid::new_v4().to_string());
+        let status_mock = server.mock(|when, then| {
+            when.method(GET)
+                .path(format!("/sessions/status/{}", session_id.uuid));
+            then.status(200)
+                .header("content-type", "application/json")
+                .json_body_obj(&status_res("FAILED", None));
+        });
+
+        let server_url = format!("http://{}", server.address());
+        let client =
+            super::Client::from_parts(server_url, TEST_KEY.to_string(), TEST_VERSION).unwrap();
+
+        let mut stream = client
+            .stream_status(&session_id)
+            .poll_interval(Duration::from_millis(1));
+        assert_eq!(
+            stream.next().unwrap().unwrap(),
+            SessionEvent::Failed {
+                status: "FAILED".to_string(),
+                error_msg: None
+            }
+        );
+        assert!(stream.next().is_none());
+        status_mock.assert_hits(1);
+    }
+
+    #[test]
+    fn client_from_backend() {
+        let server = MockServer::start();
+
+        let session_id = SessionId::new(Uuid::new_v4().to_string());
+        let status_mock = server.mock(|when, then| {
+            when.method(GET)
+                .path(format!("/sessions/status/{}", session_id.uuid));
+            then.status(200)
+                .header("content-type", "application/json")
+                .json_body_obj(&status_res("FAILED", None));
+        });
+        let stream_mock = server.mock(|when, then| {
+            when.method(GET)
+                .path(format!("/sessions/stream/{}", session_id.uuid));
+            then.status(404);
+        });
+
+        let server_url = format!("http://{}", server.address());
+        let http =
+            super::Client::from_parts(server_url, TEST_KEY.to_string(), TEST_VERSION).unwrap();
+        let client = super::Client::from_backend(http, TEST_VERSION).unwrap();
+
+        assert_eq!(session_id.status(&client).unwrap().status, "FAILED");
+        let events: Vec<_> = client
+            .stream_status(&session_id)
+            .collect::<Result<_, _>>()
+            .unwrap();
+        assert_eq!(events.len(), 1);
+        status_mock.assert_hits(2);
+        stream_mock.assert_hits(0);
+
+        assert!(matches!(client.quotas(), Err(SdkErr::Unsupported(_))));
+        assert!(matches!(
+            session_id.cancel(&client),
+            Err(SdkErr::Unsupported(_))
+        ));
+        assert!(matches!(
+            client.receipt_reader(&session_id),
+            Err(SdkErr::Unsupported(_))
+        ));
+    }
+
+    #[test]
+    fn poll_until_done_transient() {
+        let server = MockServer::start();
+
+        let session_id = SessionId::new(Uuid::new_v4().to_string());
+        let path = format!("/sessions/status/{}", session_id.uuid);
+        let mut failing_mock = server.mock(|when, then| {
+            when.method(GET).path(&path);
+            then.status(503).body("unavailable");
+        });
+
+        let server_url = format!("http://{}", server.address());
+        let client = super::Client::from_parts(server_url, TEST_KEY.to_string(), TEST_VERSION)
+            .unwrap()
+            .with_retry_policy(RetryPolicy::none());
+
+        let res = std::thread::scope(|scope| {
+            let poll = scope.spawn(|| {
+                session_id.poll_until_done(
+                    &client,
+                    Duration::from_secs(10),
+                    Duration::from_millis(10),
+                )
+            });
+            while failing_mock.hits() == 0 {
+                std::thread::sleep(Duration::from_millis(1));
+            }
+            failing_mock.delete();
+            server.mock(|when, then| {
+                when.method(GET).path(&path);
+                then.status(200)
+                    .header("content-type", "application/json")
+                    .json_body_obj(&status_res("SUCCEEDED", None));
+            });
+            poll.join().unwrap()
+        })
+        .unwrap();
+        assert_eq!(res.status, "SUCCEEDED");
+    }
+
+    #[test]
+    fn poll_until_done_timeout() {
+        let server = MockServer::start();
+
+        let session_id = SessionId::new(Uuid::new_v4().to_string());
+        let status_mock = server.mock(|when, then| {
+            when.method(GET)
+                .path(format!("/sessions/status/{}", session_id.uuid));
+            then.status(200)
+                .header("content-type", "application/json")
+                .json_body_obj(&status_res("RUNNING", Some("Prove")));
+        });
+
+        let server_url = format!("http://{}", server.address());
+        let client =
+            super::Client::from_parts(server_url, TEST_KEY.to_string(), TEST_VERSION).unwrap();
+
+        let err = session_id
+            .poll_until_done(
+                &client,
+                Duration::from_millis(50),
+                Duration::from_millis(10),
+            )
+            .err()
+            .unwrap();
+        assert!(matches!(err, SdkErr::Timeout(timeout) if timeout == Duration::from_millis(50)));
+        assert!(status_mock.hits() > 1);
+    }
+
+    #[test]
+    fn poll_until_done_unauthorized() {
+        let server = MockServer::start();
+
+        let session_id = SessionId::new(Uuid::new_v4().to_string());
+        let status_mock = server.mock(|when, then| {
+            when.method(GET)
+                .path(format!("/sessions/status/{}", session_id.uuid));
+            then.status(401).body("bad key");
+        });
+
+        let server_url = format!("http://{}", server.address());
+        let client =
+            super::Client::from_parts(server_url, TEST_KEY.to_string(), TEST_VERSION).unwrap();
+
+        let err = session_id
+            .poll_until_done(&client, Duration::from_secs(10), Duration::from_millis(10))
+            .err()
+            .unwrap();
+        assert!(matches!(err, SdkErr::Unauthorized(_)));
+        status_mock.assert_hits(1);
+    }
+
+    #[test]
+    fn list_sessions() {
+        let server = MockServer::start();
+
+        let session = SessionInfo {
+            uuid: Uuid::new_v4().to_string(),
+            status: "RUNNING".to_string(),
+            image_id: TEST_ID.to_string(),
+            created_at: 1700000000,
+        };
+        let response = SessionList {
+            sessions: vec![session.clone()],
+            next_cursor: Some("page3".to_string()),
+        };
+
+        let list_mock = server.mock(|when, then| {
+            when.method(GET)
+                .path("/sessions")
+                .query_param("status", "RUNNING")
+                .query_param("image_id", TEST_ID)
+                .query_param("cursor", "page2")
+                .header(API_KEY_HEADER, TEST_KEY)
+                .header(VERSION_HEADER, TEST_VERSION);
+            then.status(200)
+                .header("content-type", "application/json")
+                .json_body_obj(&response);
+        });
+
+        let server_url = format!("http://{}", server.address());
+        let client =
+            super::Client::from_parts(server_url, TEST_KEY.to_string(), TEST_VERSION).unwrap();
+
+        let filter = SessionFilter {
+            status: Some("RUNNING".to_string()),
+            image_id: Some(TEST_ID.to_string()),
+            ..Default::default()
+        };
+        let page = client.list_sessions(&filter, Some("page2")).unwrap();
+        assert_eq!(page.sessions, vec![session]);
+        assert_eq!(page.next_cursor.as_deref(), Some("page3"));
+
+        list_mock.assert();
+    }
+
     #[test]
     fn session_logs() {
         let server = MockServer::start();
@@ -925,6 +3018,30 @@ mod tests {
         create_mock.assert();
     }
 
+    #[test]
+    fn session_cancel() {
+        let server = MockServer::start();
+
+        let uuid = Uuid::new_v4().to_string();
+        let session_id = SessionId::new(uuid);
+
+        let cancel_mock = server.mock(|when, then| {
+            when.method(GET)
+                .path(format!("/sessions/stop/{}", session_id.uuid))
+                .header(API_KEY_HEADER, TEST_KEY)
+                .header(VERSION_HEADER, TEST_VERSION);
+            then.status(200);
+        });
+
+        let server_url = format!("http://{}", server.address());
+        let client =
+            super::Client::from_parts(server_url, TEST_KEY.to_string(), TEST_VERSION).unwrap();
+
+        session_id.cancel(&client).unwrap();
+
+        cancel_mock.assert();
+    }
+
     #[test]
     fn snark_create() {
         let server = MockServer::start();
@@ -987,16 +3104,74 @@ mod tests {
         let status = snark_id.status(&client).unwrap();
         assert_eq!(status.status, response.status);
         assert_eq!(status.output, None);
+        assert_eq!(status.receipt().unwrap(), None);
 
         create_mock.assert();
     }
 
+    #[test]
+    fn snark_receipt() {
+        let receipt = SnarkReceipt {
+            snark: Seal {
+                a: vec![vec![1; 32]; 2],
+                b: vec![vec![vec![2; 32]; 2]; 2],
+                c: vec![vec![3; 32]; 2],
+            },
+            post_state_digest: vec![4; 32],
+            journal: vec![5; 4],
+        };
+        let res = |status: &str, output: Option<SnarkReceipt>| SnarkStatusRes {
+            status: status.to_string(),
+            output,
+            error_msg: (status == "FAILED").then(|| "guest panicked".to_string()),
+        };
+
+        assert_eq!(
+            res("SUCCEEDED", Some(receipt.clone())).receipt().unwrap(),
+            Some(receipt)
+        );
+        let err = res("FAILED", None).receipt().unwrap_err();
+        assert!(matches!(&err, SdkErr::SnarkFailed { status, .. } if status == "FAILED"));
+        assert_eq!(err.to_string(), "snark conversion FAILED: guest panicked");
+        let err = res("SUCCEEDED", None).receipt().unwrap_err();
+        assert_eq!(err.to_string(), "snark conversion SUCCEEDED: no receipt");
+    }
+
+    #[test]
+    fn snark_receipt_seal() {
+        let receipt = SnarkReceipt {
+            snark: Seal {
+                a: vec![vec![1; 32]; 2],
+                b: vec![vec![vec![2; 32]; 2]; 2],
+                c: vec![vec![3; 32]; 2],
+            },
+            post_state_digest: vec![4; 32],
+            journal: vec![5; 4],
+        };
+        assert_eq!(Seal::try_from(receipt.clone()).unwrap(), receipt.snark);
+
+        let mut malformed = receipt.clone();
+        malformed.snark.b.pop();
+        assert!(matches!(
+            Seal::try_from(malformed),
+            Err(SdkErr::MalformedSnark(_))
+        ));
+
+        let mut malformed = receipt;
+        malformed.post_state_digest.pop();
+        assert!(matches!(
+            Seal::try_from(malformed),
+            Err(SdkErr::MalformedSnark(_))
+        ));
+    }
+
     #[test]
     fn version() {
         let server = MockServer::start();
 
         let response = VersionInfo {
             risc0_zkvm: vec![TEST_VERSION.into()],
+            api: vec![],
         };
 
         let get_mock = server.mock(|when, then| {
@@ -1017,6 +3192,60 @@ mod tests {
         get_mock.assert();
     }
 
+    #[test]
+    fn check_compat() {
+        let server = MockServer::start();
+
+        let get_mock = server.mock(|when, then| {
+            when.method(GET).path("/version");
+            then.status(200)
+                .header("content-type", "application/json")
+                .json_body_obj(&VersionInfo {
+                    risc0_zkvm: vec!["0.19.1".into(), "0.20.1".into()],
+                    api: vec![API_VERSION.into()],
+                });
+        });
+
+        let server_url = format!("http://{}", server.address());
+        let client = |version| {
+            super::Client::from_parts(server_url.clone(), TEST_KEY.to_string(), version).unwrap()
+        };
+        client("0.20.0").check_compat().unwrap();
+        client("0.20.2-rc.1").check_compat().unwrap();
+        let err = client("0.21.0").check_compat().err().unwrap();
+        assert!(matches!(
+            err,
+            SdkErr::IncompatibleVersion {
+                component: "risc0-zkvm",
+                ..
+            }
+        ));
+        get_mock.assert_hits(3);
+    }
+
+    #[test]
+    fn check_versions() {
+        let info = |risc0_zkvm: &[&str], api: &[&str]| VersionInfo {
+            risc0_zkvm: risc0_zkvm.iter().map(|v| v.to_string()).collect(),
+            api: api.iter().map(|v| v.to_string()).collect(),
+        };
+
+        // Servers that do not advertise api versions are assumed compatible.
+        super::check_versions(&info(&["1.2.0"], &[]), "1.0.3").unwrap();
+        super::check_versions(&info(&["1.2.0"], &[API_VERSION]), "1.3.0").unwrap();
+        assert!(matches!(
+            super::check_versions(&info(&["1.2.0"], &["v0"]), "1.2.0"),
+            Err(SdkErr::IncompatibleVersion {
+                component: "api",
+                ..
+            })
+        ));
+        assert!(super::check_versions(&info(&["1.2.0"], &[]), "2.0.0").is_err());
+        assert!(super::check_versions(&info(&["0.20.0"], &[]), "0.19.0").is_err());
+        assert!(super::check_versions(&info(&[], &[]), "0.20.0").is_err());
+        assert!(super::check_versions(&info(&["0.20.0"], &[]), "latest").is_err());
+    }
+
     #[test]
     fn quotas() {
         let server = MockServer::start();
@@ -0,0 +1,452 @@
+// Copyright 2024 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::{
+    collections::VecDeque,
+    sync::mpsc::{self, Receiver, RecvTimeoutError, Sender},
+    thread,
+    time::{Duration, Instant},
+};
+
+use super::{Client, SdkErr, SessionId, SessionOpts, StatusStream};
+
+/// Limits of the sessions run by a [SessionPool]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PoolLimits {
+    /// Maximum number of sessions running at once, at least one
+    pub max_sessions: usize,
+    /// Maximum number of requests sent by the pool per second, across all of
+    /// its sessions
+    ///
+    /// Requests are not throttled if unset.
+    pub max_requests_per_sec: Option<u32>,
+    /// Delay between two status polls of the running sessions
+    pub poll_interval: Duration,
+}
+
+impl Default for PoolLimits {
+    fn default() -> Self {
+        Self {
+            max_sessions: 8,
+            max_requests_per_sec: Some(10),
+            poll_interval: StatusStream::DEFAULT_POLL_INTERVAL,
+        }
+    }
+}
+
+/// A proof job submitted to a [SessionPool]
+///
+/// The image and input must already be uploaded, see [Client::upload_img]
+/// and [Client::upload_input].
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct PoolJob {
+    /// Image ID of the guest
+    pub image_id: String,
+    /// Input UUID
+    pub input_id: String,
+    /// Receipt UUIDs of the assumptions
+    pub assumptions: Vec<String>,
+    /// Options of the Session
+    pub opts: SessionOpts,
+}
+
+/// A job completed by a [SessionPool], whether it succeeded or not
+#[derive(Debug)]
+pub struct PoolOutput {
+    /// Index of the job, as returned by [SessionPool::submit]
+    pub job: u64,
+    /// Session of the job, unless it could not be created
+    pub session: Option<SessionId>,
+    /// Serialized receipt of the session, or journal of an execute-only
+    /// session
+    ///
+    /// Sessions ending without succeeding are reported as
+    /// [SdkErr::SessionFailed].
+    pub result: Result<Vec<u8>, SdkErr>,
+}
+
+/// Runs many proof jobs on Bonsai within [PoolLimits]
+///
+/// Jobs are started in the order they are submitted, as long as fewer than
+/// `max_sessions` of them are running. A single background thread creates
+/// the sessions, polls all the running ones every `poll_interval`, and
+/// downloads their receipts, spacing its requests to stay within
+/// `max_requests_per_sec`. Each completed job is sent to the channel returned
+/// by [SessionPool::new], in the order the jobs complete.
+///
+/// Status requests failing with a [retryable](SdkErr::is_retryable) error are
+/// tried again at the next poll, and other failures complete the job with an
+/// error. Dropping the pool stops accepting jobs, and the channel is closed
+/// once the submitted ones are complete.
+///
+/// ```no_run
+/// use bonsai_sdk::alpha::{Client, PoolJob, PoolLimits, SessionPool};
+///
+/// # fn run(client: Client, image_id: String, inputs: Vec<String>) {
+/// let (mut pool, outputs) = SessionPool::new(client, PoolLimits::default());
+/// for input_id in inputs {
+///     pool.submit(PoolJob {
+///         image_id: image_id.clone(),
+///         input_id,
+///         ..Default::default()
+///     });
+/// }
+/// drop(pool);
+/// for output in outputs {
+///     match output.result {
+///         Ok(receipt) => println!("job {}: {} bytes", output.job, receipt.len()),
+///         Err(err) => eprintln!("job {} failed: {err}", output.job),
+///     }
+/// }
+/// # }
+/// ```
+pub struct SessionPool {
+    jobs: Sender<(u64, PoolJob)>,
+    next_job: u64,
+}
+
+impl SessionPool {
+    /// Construct a [SessionPool] running jobs with `client`, and the channel
+    /// receiving the completed jobs
+    pub fn new(client: Client, limits: PoolLimits) -> (Self, Receiver<PoolOutput>) {
+        let (jobs, submitted) = mpsc::channel();
+        let (outputs, completed) = mpsc::channel();
+        let scheduler = Scheduler {
+            client,
+            limits,
+            submitted,
+            outputs,
+            queue: VecDeque::new(),
+            running: Vec::new(),
+            next_request: Instant::now(),
+        };
+        thread::spawn(move || scheduler.run());
+        (Self { jobs, next_job: 0 }, completed)
+    }
+
+    /// Queues a job, returning its index
+    ///
+    /// Jobs are indexed in the order they are submitted, starting from zero.
+    pub fn submit(&mut self, job: PoolJob) -> u64 {
+        let index = self.next_job;
+        self.next_job += 1;
+        // The scheduler only stops once the pool is dropped.
+        let _ = self.jobs.send((index, job));
+        index
+    }
+}
+
+struct Running {
+    job: u64,
+    session: SessionId,
+    execute_only: bool,
+}
+
+struct Scheduler {
+    client: Client,
+    limits: PoolLimits,
+    submitted: Receiver<(u64, PoolJob)>,
+    outputs: Sender<PoolOutput>,
+    queue: VecDeque<(u64, PoolJob)>,
+    running: Vec<Running>,
+    // Earliest time the next request may be sent.
+    next_request: Instant,
+}
+
+impl Scheduler {
+    fn run(mut self) {
+        let mut open = true;
+        let mut next_poll = Instant::now();
+        loop {
+            self.start_sessions();
+            if self.running.is_empty() {
+                if !open {
+                    return;
+                }
+                // Idle until the next job, or until the pool is dropped.
+                match self.submitted.recv() {
+                    Ok(job) => self.queue.push_back(job),
+                    Err(_) => open = false,
+                }
+                continue;
+            }
+
+            if Instant::now() >= next_poll {
+                self.poll_sessions();
+                next_poll = Instant::now() + self.limits.poll_interval;
+                continue;
+            }
+
+            // Accept new jobs while waiting for the next poll.
+            let delay = next_poll.saturating_duration_since(Instant::now());
+            if !open {
+                thread::sleep(delay);
+                continue;
+            }
+            match self.submitted.recv_timeout(delay) {
+                Ok(job) => self.queue.push_back(job),
+                Err(RecvTimeoutError::Timeout) => {}
+                Err(RecvTimeoutError::Disconnected) => open = false,
+            }
+        }
+    }
+
+    fn start_sessions(&mut self) {
+        while self.running.len() < self.limits.max_sessions.max(1) {
+            let Some((job, spec)) = self.queue.pop_front() else {
+                break;
+            };
+            let execute_only = spec.opts.execute_only;
+            self.throttle();
+            match self.client.create_session_with_opts(
+                spec.image_id,
+                spec.input_id,
+                spec.assumptions,
+                spec.opts,
+            ) {
+                Ok(session) => self.running.push(Running {
+                    job,
+                    session,
+                    execute_only,
+                }),
+                Err(err) => self.complete(job, None, Err(err)),
+            }
+        }
+    }
+
+    fn poll_sessions(&mut self) {
+        for running in std::mem::take(&mut self.running) {
+            self.throttle();
+            let res = match running.session.status(&self.client) {
+                Ok(res) if res.status == "RUNNING" => {
+                    self.running.push(running);
+                    continue;
+                }
+                Ok(res) => res,
+                Err(err) if err.is_retryable() => {
+                    self.running.push(running);
+                    continue;
+                }
+                Err(err) => {
+                    self.complete(running.job, Some(running.session), Err(err));
+                    continue;
+                }
+            };
+            let result = if res.status != "SUCCEEDED" {
+                Err(SdkErr::SessionFailed {
+                    status: res.status,
+                    error_msg: res.error_msg,
+                })
+            } else {
+                self.throttle();
+                match running.execute_only {
+                    true => running.session.exec_only_journal(&self.client),
+                    false => self.client.receipt_download(&running.session),
+                }
+            };
+            self.complete(running.job, Some(running.session), result);
+        }
+    }
+
+    // Waits until the next request is allowed by the rate limit.
+    fn throttle(&mut self) {
+        let Some(rate) = self.limits.max_requests_per_sec else {
+            return;
+        };
+        let now = Instant::now();
+        if self.next_request > now {
+            thread::sleep(self.next_request - now);
+        }
+        self.next_request = self.next_request.max(now) + Duration::from_secs(1) / rate.max(1);
+    }
+
+    fn complete(&self, job: u64, session: Option<SessionId>, result: Result<Vec<u8>, SdkErr>) {
+        // Completed jobs are dropped along with the receiver.
+        let _ = self.outputs.send(PoolOutput {
+            job,
+            session,
+            result,
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        collections::HashMap,
+        sync::{Arc, Mutex},
+        time::{Duration, Instant},
+    };
+
+    use super::*;
+    use crate::alpha::{
+        responses::{SessionStatusRes, VersionInfo},
+        Backend, ImgUploadOutcome,
+    };
+
+    // Sessions run for a number of status polls, and fail if their input is
+    // "fail".
+    #[derive(Clone, Default)]
+    struct FakeBonsai {
+        state: Arc<Mutex<FakeState>>,
+    }
+
+    #[derive(Default)]
+    struct FakeState {
+        sessions: HashMap<String, (String, u32)>,
+        running: usize,
+        max_running: usize,
+        requests: Vec<Instant>,
+    }
+
+    impl FakeBonsai {
+        fn request(&self) -> std::sync::MutexGuard<'_, FakeState> {
+            let mut state = self.state.lock().unwrap();
+            state.requests.push(Instant::now());
+            state
+        }
+    }
+
+    impl Backend for FakeBonsai {
+        fn has_img(&self, _image_id: &str) -> Result<bool, SdkErr> {
+            Ok(true)
+        }
+
+        fn upload_img(&self, _image_id: &str, _buf: Vec<u8>) -> Result<ImgUploadOutcome, SdkErr> {
+            Ok(ImgUploadOutcome::AlreadyExists)
+        }
+
+        fn upload_input(&self, _buf: Vec<u8>) -> Result<String, SdkErr> {
+            Err(SdkErr::Unsupported("inputs"))
+        }
+
+        fn upload_receipt(&self, _buf: Vec<u8>) -> Result<String, SdkErr> {
+            Err(SdkErr::Unsupported("receipts"))
+        }
+
+        fn create_session(
+            &self,
+            _img_id: String,
+            input_id: String,
+            _assumptions: Vec<String>,
+            _opts: SessionOpts,
+        ) -> Result<SessionId, SdkErr> {
+            let mut state = self.request();
+            let uuid = format!("session-{}", state.sessions.len());
+            state.sessions.insert(uuid.clone(), (input_id, 0));
+            state.running += 1;
+            state.max_running = state.max_running.max(state.running);
+            Ok(SessionId::new(uuid))
+        }
+
+        fn session_status(&self, session_id: &SessionId) -> Result<SessionStatusRes, SdkErr> {
+            let mut state = self.request();
+            let (input_id, polls) = state.sessions.get_mut(&session_id.uuid).unwrap();
+            *polls += 1;
+            let status = match (input_id.as_str(), *polls) {
+                (_, 1) => "RUNNING",
+                ("fail", _) => "FAILED",
+                _ => "SUCCEEDED",
+            };
+            if status != "RUNNING" {
+                state.running -= 1;
+            }
+            Ok(SessionStatusRes {
+                status: status.into(),
+                receipt_url: None,
+                error_msg: None,
+                state: None,
+                elapsed_time: None,
+                stats: None,
+                metadata: None,
+            })
+        }
+
+        fn exec_only_journal(&self, _session_id: &SessionId) -> Result<Vec<u8>, SdkErr> {
+            Err(SdkErr::Unsupported("execute-only sessions"))
+        }
+
+        fn receipt_download(&self, session_id: &SessionId) -> Result<Vec<u8>, SdkErr> {
+            let state = self.request();
+            Ok(state.sessions[&session_id.uuid].0.clone().into_bytes())
+        }
+
+        fn download(&self, _url: &str) -> Result<Vec<u8>, SdkErr> {
+            Err(SdkErr::Unsupported("downloads"))
+        }
+
+        fn version(&self) -> Result<VersionInfo, SdkErr> {
+            Err(SdkErr::Unsupported("versions"))
+        }
+    }
+
+    fn run_pool(bonsai: &FakeBonsai, limits: PoolLimits, inputs: &[&str]) -> Vec<PoolOutput> {
+        let client = Client::from_backend(bonsai.clone(), "0.21.0").unwrap();
+        let (mut pool, outputs) = SessionPool::new(client, limits);
+        for (index, input_id) in inputs.iter().enumerate() {
+            let job = pool.submit(PoolJob {
+                image_id: "image".into(),
+                input_id: input_id.to_string(),
+                ..Default::default()
+            });
+            assert_eq!(job, index as u64);
+        }
+        drop(pool);
+        let mut outputs: Vec<_> = outputs.iter().collect();
+        outputs.sort_by_key(|output| output.job);
+        outputs
+    }
+
+    #[test]
+    fn concurrency_cap() {
+        let bonsai = FakeBonsai::default();
+        let limits = PoolLimits {
+            max_sessions: 2,
+            max_requests_per_sec: None,
+            poll_interval: Duration::from_millis(1),
+        };
+        let outputs = run_pool(&bonsai, limits, &["a", "fail", "b", "c", "d"]);
+
+        assert_eq!(outputs.len(), 5);
+        for (output, input) in outputs.iter().zip(["a", "fail", "b", "c", "d"]) {
+            assert!(output.session.is_some());
+            match input {
+                "fail" => assert!(matches!(
+                    &output.result,
+                    Err(SdkErr::SessionFailed { status, .. }) if status == "FAILED"
+                )),
+                _ => assert_eq!(output.result.as_deref().unwrap(), input.as_bytes()),
+            }
+        }
+        assert_eq!(bonsai.state.lock().unwrap().max_running, 2);
+    }
+
+    #[test]
+    fn rate_limit() {
+        let bonsai = FakeBonsai::default();
+        let limits = PoolLimits {
+            max_sessions: 4,
+            max_requests_per_sec: Some(100),
+            poll_interval: Duration::from_millis(1),
+        };
+        let outputs = run_pool(&bonsai, limits, &["a", "b", "c"]);
+        assert_eq!(outputs.len(), 3);
+
+        let requests = &bonsai.state.lock().unwrap().requests;
+        // Each session is created, polled twice and downloaded.
+        assert_eq!(requests.len(), 12);
+        // Requests are spaced by 10ms, give or take the time to send them.
+        assert!(requests[11] - requests[0] >= Duration::from_millis(100));
+    }
+}
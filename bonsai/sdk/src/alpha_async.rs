@@ -12,9 +12,18 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use std::{path::Path, sync::Arc, time::Duration};
+
+use reqwest::{Client as HttpClient, RequestBuilder, Response, StatusCode};
+
 use crate::alpha::{
-    responses::{Quotas, SessionStatusRes, SnarkStatusRes},
-    Client, SdkErr, SessionId, SnarkId,
+    check_versions, default_headers, elf_image_id, env_parts,
+    responses::{
+        CreateSessRes, ImgUploadRes, ProofReq, Quotas, ReceiptDownload, SessionList,
+        SessionStatusRes, SnarkReq, SnarkStatusRes, UploadRes, VersionInfo,
+    },
+    verify_receipt, Client, ImageExistsOpt, ImgUploadOutcome, ReceiptVerifier, RetryPolicy, SdkErr,
+    SessionFilter, SessionId, SessionOpts, SnarkId,
 };
 
 /// Construct a Bonsai SDK Client from env var
@@ -24,7 +33,7 @@ use crate::alpha::{
 pub async fn get_client_from_env(risc0_version: &'static str) -> Result<Client, SdkErr> {
     tokio::task::spawn_blocking(|| Client::from_env(risc0_version))
         .await
-        .map_err(|err| SdkErr::InternalServerErr(format!("{err}")))?
+        .map_err(|err| SdkErr::TaskFailed(format!("{err}")))?
 }
 
 /// Construct a Bonsai SDK Client from url + api key strings
@@ -35,26 +44,27 @@ pub async fn get_client_from_parts(
 ) -> Result<Client, SdkErr> {
     tokio::task::spawn_blocking(move || Client::from_parts(url, api_key, risc0_version))
         .await
-        .map_err(|err| SdkErr::InternalServerErr(format!("{err}")))?
+        .map_err(|err| SdkErr::TaskFailed(format!("{err}")))?
 }
 
 /// Upload a input buffer to the /inputs/ route
 pub async fn upload_input(bonsai_client: Client, buf: Vec<u8>) -> Result<String, SdkErr> {
     tokio::task::spawn_blocking(move || bonsai_client.upload_input(buf))
         .await
-        .map_err(|err| SdkErr::InternalServerErr(format!("{err}")))?
+        .map_err(|err| SdkErr::TaskFailed(format!("{err}")))?
 }
 
 /// Upload a receipt buffer to the /receipts/ route
 pub async fn upload_receipt(bonsai_client: Client, buf: Vec<u8>) -> Result<String, SdkErr> {
     tokio::task::spawn_blocking(move || bonsai_client.upload_receipt(buf))
         .await
-        .map_err(|err| SdkErr::InternalServerErr(format!("{err}")))?
+        .map_err(|err| SdkErr::TaskFailed(format!("{err}")))?
 }
 
 /// Upload a image buffer to the /images/ route
 ///
-/// The boolean return indicates if the image already exists in bonsai
+/// The upload is skipped if the image already exists in bonsai, which is
+/// reported by [ImgUploadOutcome::AlreadyExists]
 ///
 /// The image data can be either:
 /// * ELF file bytes
@@ -63,10 +73,19 @@ pub async fn upload_img(
     bonsai_client: Client,
     image_id: String,
     image: Vec<u8>,
-) -> Result<bool, SdkErr> {
+) -> Result<ImgUploadOutcome, SdkErr> {
     tokio::task::spawn_blocking(move || bonsai_client.upload_img(&image_id, image))
         .await
-        .map_err(|err| SdkErr::InternalServerErr(format!("{err}")))?
+        .map_err(|err| SdkErr::TaskFailed(format!("{err}")))?
+}
+
+/// Upload the guest `elf` to the /images/ route, returning its image ID
+///
+/// See [Client::upload_img_from_elf].
+pub async fn upload_img_from_elf(bonsai_client: Client, elf: Vec<u8>) -> Result<String, SdkErr> {
+    tokio::task::spawn_blocking(move || bonsai_client.upload_img_from_elf(&elf))
+        .await
+        .map_err(|err| SdkErr::TaskFailed(format!("{err}")))?
 }
 
 /// Create a new proof request Session
@@ -81,7 +100,24 @@ pub async fn create_session(
 ) -> Result<SessionId, SdkErr> {
     tokio::task::spawn_blocking(move || bonsai_client.create_session(img_id, input_id, assumptions))
         .await
-        .map_err(|err| SdkErr::InternalServerErr(format!("{err}")))?
+        .map_err(|err| SdkErr::TaskFailed(format!("{err}")))?
+}
+
+/// Create a new Session with the given [SessionOpts]
+///
+/// See [Client::create_session_with_opts].
+pub async fn create_session_with_opts(
+    bonsai_client: Client,
+    img_id: String,
+    input_id: String,
+    assumptions: Vec<String>,
+    opts: SessionOpts,
+) -> Result<SessionId, SdkErr> {
+    tokio::task::spawn_blocking(move || {
+        bonsai_client.create_session_with_opts(img_id, input_id, assumptions, opts)
+    })
+    .await
+    .map_err(|err| SdkErr::TaskFailed(format!("{err}")))?
 }
 
 /// Fetches the current status of the Session
@@ -91,7 +127,23 @@ pub async fn session_status(
 ) -> Result<SessionStatusRes, SdkErr> {
     tokio::task::spawn_blocking(move || session.status(&bonsai_client))
         .await
-        .map_err(|err| SdkErr::InternalServerErr(format!("{err}")))?
+        .map_err(|err| SdkErr::TaskFailed(format!("{err}")))?
+}
+
+/// Polls the status of the Session until it is no longer `RUNNING`
+///
+/// See [SessionId::poll_until_done].
+pub async fn session_poll_until_done(
+    bonsai_client: Client,
+    session: SessionId,
+    timeout: Duration,
+    poll_interval: Duration,
+) -> Result<SessionStatusRes, SdkErr> {
+    tokio::task::spawn_blocking(move || {
+        session.poll_until_done(&bonsai_client, timeout, poll_interval)
+    })
+    .await
+    .map_err(|err| SdkErr::TaskFailed(format!("{err}")))?
 }
 
 /// Fetches the zkvm guest logs for a session
@@ -105,7 +157,28 @@ pub async fn session_status(
 pub async fn session_logs(bonsai_client: Client, session: SessionId) -> Result<String, SdkErr> {
     tokio::task::spawn_blocking(move || session.logs(&bonsai_client))
         .await
-        .map_err(|err| SdkErr::InternalServerErr(format!("{err}")))?
+        .map_err(|err| SdkErr::TaskFailed(format!("{err}")))?
+}
+
+/// Downloads the journal of a successful execute-only session
+///
+/// See [SessionId::exec_only_journal].
+pub async fn session_exec_only_journal(
+    bonsai_client: Client,
+    session: SessionId,
+) -> Result<Vec<u8>, SdkErr> {
+    tokio::task::spawn_blocking(move || session.exec_only_journal(&bonsai_client))
+        .await
+        .map_err(|err| SdkErr::TaskFailed(format!("{err}")))?
+}
+
+/// Stops a running session
+///
+/// See [SessionId::cancel].
+pub async fn session_cancel(bonsai_client: Client, session: SessionId) -> Result<(), SdkErr> {
+    tokio::task::spawn_blocking(move || session.cancel(&bonsai_client))
+        .await
+        .map_err(|err| SdkErr::TaskFailed(format!("{err}")))?
 }
 
 /// Requests a SNARK proof be created from a existing sessionId
@@ -115,14 +188,14 @@ pub async fn session_logs(bonsai_client: Client, session: SessionId) -> Result<S
 pub async fn create_snark(bonsai_client: Client, session_id: String) -> Result<SnarkId, SdkErr> {
     tokio::task::spawn_blocking(move || bonsai_client.create_snark(session_id))
         .await
-        .map_err(|err| SdkErr::InternalServerErr(format!("{err}")))?
+        .map_err(|err| SdkErr::TaskFailed(format!("{err}")))?
 }
 
 /// Fetches the current status of the Snark Session
 pub async fn snark_status(bonsai_client: Client, snark: SnarkId) -> Result<SnarkStatusRes, SdkErr> {
     tokio::task::spawn_blocking(move || snark.status(&bonsai_client))
         .await
-        .map_err(|err| SdkErr::InternalServerErr(format!("{err}")))?
+        .map_err(|err| SdkErr::TaskFailed(format!("{err}")))?
 }
 
 /// Download a given url to a buffer
@@ -131,7 +204,16 @@ pub async fn snark_status(bonsai_client: Client, snark: SnarkId) -> Result<Snark
 pub async fn download(bonsai_client: Client, url: String) -> Result<Vec<u8>, SdkErr> {
     tokio::task::spawn_blocking(move || bonsai_client.download(&url))
         .await
-        .map_err(|err| SdkErr::InternalServerErr(format!("{err}")))?
+        .map_err(|err| SdkErr::TaskFailed(format!("{err}")))?
+}
+
+/// Checks that bonsai supports this SDK and the risc0-zkvm version of the client
+///
+/// See [Client::check_compat].
+pub async fn check_compat(bonsai_client: Client) -> Result<VersionInfo, SdkErr> {
+    tokio::task::spawn_blocking(move || bonsai_client.check_compat())
+        .await
+        .map_err(|err| SdkErr::TaskFailed(format!("{err}")))?
 }
 
 /// Fetches your current users quotas
@@ -140,5 +222,854 @@ pub async fn download(bonsai_client: Client, url: String) -> Result<Vec<u8>, Sdk
 pub async fn quotas(bonsai_client: Client) -> Result<Quotas, SdkErr> {
     tokio::task::spawn_blocking(move || bonsai_client.quotas())
         .await
-        .map_err(|err| SdkErr::InternalServerErr(format!("{err}")))?
+        .map_err(|err| SdkErr::TaskFailed(format!("{err}")))?
+}
+
+/// Represents an async client of the REST api
+///
+/// Offers the same methods as the blocking [Client], without requiring
+/// requests to be run on the tokio blocking thread pool. It only talks to the
+/// REST api: the free functions of this module run a blocking [Client], which
+/// may be constructed with another [Backend](crate::alpha::Backend).
+#[derive(Clone)]
+pub struct AsyncClient {
+    pub(crate) url: String,
+    pub(crate) client: HttpClient,
+    pub(crate) risc0_version: String,
+    pub(crate) retry: RetryPolicy,
+    pub(crate) verifier: Option<Arc<dyn ReceiptVerifier>>,
+}
+
+/// Creates a [reqwest::Client] for internal connection pooling
+fn construct_req_client(api_key: &str, version: &str) -> Result<HttpClient, SdkErr> {
+    Ok(HttpClient::builder()
+        .default_headers(default_headers(api_key, version)?)
+        .pool_max_idle_per_host(0)
+        .build()?)
+}
+
+impl AsyncClient {
+    /// Construct an [AsyncClient] from env vars
+    ///
+    /// Uses the BONSAI_API_URL and BONSAI_API_KEY environment variables to
+    /// construct a client. The risc0_version should be the crate version of the
+    /// risc0-zkvm crate
+    ///
+    /// # Example:
+    ///
+    /// ```no_run
+    /// use bonsai_sdk::alpha_async::AsyncClient;
+    /// AsyncClient::from_env(risc0_zkvm::VERSION).expect("Failed to construct sdk client");
+    /// ```
+    pub fn from_env(risc0_version: &str) -> Result<Self, SdkErr> {
+        let (url, api_key) = env_parts()?;
+        let client = construct_req_client(&api_key, risc0_version)?;
+        Ok(Self {
+            url,
+            client,
+            risc0_version: risc0_version.to_string(),
+            retry: RetryPolicy::default(),
+            verifier: None,
+        })
+    }
+
+    /// Construct an [AsyncClient] from url, api key, and zkvm version
+    ///
+    /// # Example:
+    ///
+    /// ```
+    /// use bonsai_sdk::alpha_async::AsyncClient;
+    /// let url = "http://api.bonsai.xyz".to_string();
+    /// let api_key = "my_secret_key".to_string();
+    /// AsyncClient::from_parts(url, api_key, risc0_zkvm::VERSION)
+    ///     .expect("Failed to construct sdk client");
+    /// ```
+    pub fn from_parts(url: String, key: String, risc0_version: &str) -> Result<Self, SdkErr> {
+        let client = construct_req_client(&key, risc0_version)?;
+        let url = url.strip_suffix('/').unwrap_or(&url).to_string();
+        Ok(Self {
+            url,
+            client,
+            risc0_version: risc0_version.to_string(),
+            retry: RetryPolicy::default(),
+            verifier: None,
+        })
+    }
+
+    /// Sets the [RetryPolicy] of uploads, session creation and status requests
+    ///
+    /// Clients use the default policy unless configured otherwise.
+    pub fn with_retry_policy(mut self, retry: RetryPolicy) -> Self {
+        self.retry = retry;
+        self
+    }
+
+    /// Verifies receipts with `verifier` before returning them
+    ///
+    /// See [Client::with_receipt_verifier].
+    pub fn with_receipt_verifier(mut self, verifier: impl ReceiptVerifier + 'static) -> Self {
+        self.verifier = Some(Arc::new(verifier));
+        self
+    }
+
+    /// Sends a request, retrying it according to the [RetryPolicy]
+    async fn send(&self, req: RequestBuilder) -> Result<Response, SdkErr> {
+        let mut attempt = 1;
+        loop {
+            let retry = match req.try_clone() {
+                Some(retry) if attempt < self.retry.max_attempts => retry,
+                _ => return Ok(req.send().await?),
+            };
+            match retry.send().await {
+                Ok(res) if !self.retry.retry_status(res.status()) => return Ok(res),
+                Err(err) if !RetryPolicy::retry_err(&err) => return Err(err.into()),
+                _ => tokio::time::sleep(self.retry.backoff(attempt)).await,
+            }
+            attempt += 1;
+        }
+    }
+
+    /// Fetch a upload presigned url for a given route
+    async fn get_upload_url(&self, route: &str) -> Result<UploadRes, SdkErr> {
+        let res = self
+            .send(self.client.get(format!("{}/{}/upload", self.url, route)))
+            .await?;
+
+        if !res.status().is_success() {
+            return Err(SdkErr::from_status(res.status(), res.text().await?));
+        }
+
+        Ok(res.json::<UploadRes>().await?)
+    }
+
+    async fn get_image_upload_url(&self, image_id: &str) -> Result<ImageExistsOpt, SdkErr> {
+        // Any answer but a 200, e.g. from servers without HEAD support, falls
+        // back to the GET, which answers 204 for existing images.
+        let res = self
+            .send(
+                self.client
+                    .head(format!("{}/images/{}", self.url, image_id)),
+            )
+            .await?;
+        if res.status() == StatusCode::OK {
+            return Ok(ImageExistsOpt::Exists);
+        }
+
+        let res = self
+            .send(
+                self.client
+                    .get(format!("{}/images/upload/{}", self.url, image_id)),
+            )
+            .await?;
+
+        if res.status() == 204 {
+            return Ok(ImageExistsOpt::Exists);
+        }
+
+        if !res.status().is_success() {
+            return Err(SdkErr::from_status(res.status(), res.text().await?));
+        }
+
+        Ok(ImageExistsOpt::New(res.json::<ImgUploadRes>().await?))
+    }
+
+    /// Upload body to a given URL
+    async fn put_data<T: Into<reqwest::Body>>(&self, url: &str, body: T) -> Result<(), SdkErr> {
+        let res = self.send(self.client.put(url).body(body)).await?;
+        if !res.status().is_success() {
+            return Err(SdkErr::from_status(res.status(), res.text().await?));
+        }
+
+        Ok(())
+    }
+
+    // - /images
+
+    /// Checks if a image exists in bonsai
+    ///
+    /// Sends a HEAD request to the /images/ route, without transferring the
+    /// image.
+    pub async fn has_img(&self, image_id: &str) -> Result<bool, SdkErr> {
+        let res = self
+            .send(
+                self.client
+                    .head(format!("{}/images/{}", self.url, image_id)),
+            )
+            .await?;
+
+        if res.status() == StatusCode::NOT_FOUND {
+            return Ok(false);
+        }
+
+        if !res.status().is_success() {
+            return Err(SdkErr::from_status(res.status(), res.text().await?));
+        }
+
+        Ok(true)
+    }
+
+    /// Upload a image buffer to the /images/ route
+    ///
+    /// The upload is skipped if the image already exists in bonsai, which is
+    /// reported by [ImgUploadOutcome::AlreadyExists]
+    ///
+    /// The image data can be either:
+    /// * ELF file bytes
+    /// * bincode encoded MemoryImage
+    pub async fn upload_img(
+        &self,
+        image_id: &str,
+        buf: Vec<u8>,
+    ) -> Result<ImgUploadOutcome, SdkErr> {
+        match self.get_image_upload_url(image_id).await? {
+            ImageExistsOpt::Exists => Ok(ImgUploadOutcome::AlreadyExists),
+            ImageExistsOpt::New(upload_res) => {
+                self.put_data(&upload_res.url, buf).await?;
+                Ok(ImgUploadOutcome::Uploaded)
+            }
+        }
+    }
+
+    /// Upload the guest `elf` to the /images/ route, returning its image ID
+    ///
+    /// See [Client::upload_img_from_elf].
+    pub async fn upload_img_from_elf(&self, elf: &[u8]) -> Result<String, SdkErr> {
+        let image_id = elf_image_id(elf)?;
+        self.upload_img(&image_id, elf.to_vec()).await?;
+        Ok(image_id)
+    }
+
+    /// Upload a image file to the /images/ route
+    ///
+    /// The upload is skipped if the image already exists in bonsai, which is
+    /// reported by [ImgUploadOutcome::AlreadyExists]
+    ///
+    /// The image data can be either:
+    /// * ELF file bytes
+    /// * bincode encoded MemoryImage
+    pub async fn upload_img_file(
+        &self,
+        image_id: &str,
+        path: &Path,
+    ) -> Result<ImgUploadOutcome, SdkErr> {
+        match self.get_image_upload_url(image_id).await? {
+            ImageExistsOpt::Exists => Ok(ImgUploadOutcome::AlreadyExists),
+            ImageExistsOpt::New(upload_res) => {
+                let buf = tokio::fs::read(path).await?;
+                self.put_data(&upload_res.url, buf).await?;
+                Ok(ImgUploadOutcome::Uploaded)
+            }
+        }
+    }
+
+    // - /inputs
+
+    /// Upload a input buffer to the /inputs/ route
+    pub async fn upload_input(&self, buf: Vec<u8>) -> Result<String, SdkErr> {
+        let upload_data = self.get_upload_url("inputs").await?;
+        self.put_data(&upload_data.url, buf).await?;
+        Ok(upload_data.uuid)
+    }
+
+    /// Upload a input file to the /inputs/ route
+    pub async fn upload_input_file(&self, path: &Path) -> Result<String, SdkErr> {
+        let upload_data = self.get_upload_url("inputs").await?;
+
+        let buf = tokio::fs::read(path).await?;
+        self.put_data(&upload_data.url, buf).await?;
+
+        Ok(upload_data.uuid)
+    }
+
+    // - /receipts
+
+    /// Upload a receipt buffer to the /receipts/ route
+    pub async fn upload_receipt(&self, buf: Vec<u8>) -> Result<String, SdkErr> {
+        let upload_data = self.get_upload_url("receipts").await?;
+        self.put_data(&upload_data.url, buf).await?;
+        Ok(upload_data.uuid)
+    }
+
+    /// Upload a receipt file to the /receipts/ route
+    pub async fn upload_receipt_file(&self, path: &Path) -> Result<String, SdkErr> {
+        let upload_data = self.get_upload_url("receipts").await?;
+
+        let buf = tokio::fs::read(path).await?;
+        self.put_data(&upload_data.url, buf).await?;
+
+        Ok(upload_data.uuid)
+    }
+
+    /// Download a existing receipt
+    ///
+    /// Allows download of older receipts without checking the current session status.
+    /// The receipt is verified first if the client has a [ReceiptVerifier].
+    pub async fn receipt_download(&self, session_id: &SessionId) -> Result<Vec<u8>, SdkErr> {
+        let res = self
+            .client
+            .get(format!("{}/receipts/{}", self.url, session_id.uuid))
+            .send()
+            .await?;
+
+        if !res.status().is_success() {
+            return Err(SdkErr::from_status(res.status(), res.text().await?));
+        }
+        let res: ReceiptDownload = res.json().await?;
+
+        self.download(&res.url).await
+    }
+
+    /// Download a existing receipt of a session of the image `image_id`, and
+    /// verify it
+    ///
+    /// See [Client::verified_receipt_download].
+    pub async fn verified_receipt_download(
+        &self,
+        session_id: &SessionId,
+        image_id: &str,
+    ) -> Result<Vec<u8>, SdkErr> {
+        let receipt = self.receipt_download(session_id).await?;
+        verify_receipt(self.verifier.as_deref(), image_id, &receipt)?;
+        Ok(receipt)
+    }
+
+    // - /sessions
+
+    /// Create a new proof request Session
+    ///
+    /// Supply the image_id and input_id created from uploading those files in
+    /// previous steps
+    pub async fn create_session(
+        &self,
+        img_id: String,
+        input_id: String,
+        assumptions: Vec<String>,
+    ) -> Result<SessionId, SdkErr> {
+        self.create_session_with_opts(img_id, input_id, assumptions, SessionOpts::default())
+            .await
+    }
+
+    /// Create a new Session with the given [SessionOpts]
+    ///
+    /// See [Client::create_session_with_opts].
+    pub async fn create_session_with_opts(
+        &self,
+        img_id: String,
+        input_id: String,
+        assumptions: Vec<String>,
+        opts: SessionOpts,
+    ) -> Result<SessionId, SdkErr> {
+        let url = format!("{}/sessions/create", self.url);
+
+        let req = ProofReq {
+            img: img_id,
+            input: input_id,
+            assumptions,
+            execute_only: opts.execute_only,
+            metadata: opts.metadata,
+        };
+
+        let res = self.send(self.client.post(url).json(&req)).await?;
+
+        if !res.status().is_success() {
+            return Err(SdkErr::from_status(res.status(), res.text().await?));
+        }
+
+        let res: CreateSessRes = res.json().await?;
+
+        Ok(SessionId::new(res.uuid))
+    }
+
+    /// Lists the sessions of this account matching `filter`
+    ///
+    /// See [Client::list_sessions] for paginating with `cursor`.
+    pub async fn list_sessions(
+        &self,
+        filter: &SessionFilter,
+        cursor: Option<&str>,
+    ) -> Result<SessionList, SdkErr> {
+        let mut req = self
+            .client
+            .get(format!("{}/sessions", self.url))
+            .query(filter);
+        if let Some(cursor) = cursor {
+            req = req.query(&[("cursor", cursor)]);
+        }
+        let res = self.send(req).await?;
+
+        if !res.status().is_success() {
+            return Err(SdkErr::from_status(res.status(), res.text().await?));
+        }
+        Ok(res.json::<SessionList>().await?)
+    }
+
+    /// Fetches the current status of the Session
+    pub async fn session_status(&self, session: &SessionId) -> Result<SessionStatusRes, SdkErr> {
+        let url = format!("{}/sessions/status/{}", self.url, session.uuid);
+        let res = self.send(self.client.get(url)).await?;
+
+        if !res.status().is_success() {
+            return Err(SdkErr::from_status(res.status(), res.text().await?));
+        }
+        Ok(res.json::<SessionStatusRes>().await?)
+    }
+
+    /// Fetches the zkvm guest logs for a session
+    ///
+    /// See [SessionId::logs] for the contents of the logs.
+    pub async fn session_logs(&self, session: &SessionId) -> Result<String, SdkErr> {
+        let url = format!("{}/sessions/logs/{}", self.url, session.uuid);
+        let res = self.client.get(url).send().await?;
+
+        if !res.status().is_success() {
+            return Err(SdkErr::from_status(res.status(), res.text().await?));
+        }
+        Ok(res.text().await?)
+    }
+
+    /// Downloads the journal of a successful execute-only session
+    ///
+    /// See [SessionId::exec_only_journal].
+    pub async fn session_exec_only_journal(&self, session: &SessionId) -> Result<Vec<u8>, SdkErr> {
+        let url = format!("{}/sessions/exec_only_journal/{}", self.url, session.uuid);
+        let res = self.send(self.client.get(url)).await?;
+
+        if !res.status().is_success() {
+            return Err(SdkErr::from_status(res.status(), res.text().await?));
+        }
+        Ok(res.bytes().await?.into())
+    }
+
+    /// Stops a running session
+    ///
+    /// See [SessionId::cancel].
+    pub async fn session_cancel(&self, session: &SessionId) -> Result<(), SdkErr> {
+        let url = format!("{}/sessions/stop/{}", self.url, session.uuid);
+        let res = self.send(self.client.get(url)).await?;
+
+        if !res.status().is_success() {
+            return Err(SdkErr::from_status(res.status(), res.text().await?));
+        }
+        Ok(())
+    }
+
+    // Utilities
+
+    /// Download a given url to a buffer
+    ///
+    /// Useful to download a [SessionId] receipt_url
+    pub async fn download(&self, url: &str) -> Result<Vec<u8>, SdkErr> {
+        let data = self.client.get(url).send().await?.bytes().await?;
+
+        Ok(data.into())
+    }
+
+    // - /snark
+
+    /// Requests a SNARK proof be created from a existing sessionId
+    ///
+    /// Supply a completed sessionId to convert the risc0 STARK proof into
+    /// a SNARK proof that can be validated on ethereum-like blockchains
+    pub async fn create_snark(&self, session_id: String) -> Result<SnarkId, SdkErr> {
+        let url = format!("{}/snark/create", self.url);
+
+        let snark_req = SnarkReq { session_id };
+
+        let res = self.send(self.client.post(url).json(&snark_req)).await?;
+
+        if !res.status().is_success() {
+            return Err(SdkErr::from_status(res.status(), res.text().await?));
+        }
+
+        // Reuse the session response because its the same member format
+        let res: CreateSessRes = res.json().await?;
+
+        Ok(SnarkId::new(res.uuid))
+    }
+
+    /// Fetches the current status of the Snark Session
+    pub async fn snark_status(&self, snark: &SnarkId) -> Result<SnarkStatusRes, SdkErr> {
+        let url = format!("{}/snark/status/{}", self.url, snark.uuid);
+        let res = self.send(self.client.get(url)).await?;
+
+        if !res.status().is_success() {
+            return Err(SdkErr::from_status(res.status(), res.text().await?));
+        }
+        Ok(res.json::<SnarkStatusRes>().await?)
+    }
+
+    // - /version
+
+    /// Fetches the current component versions from bonsai
+    ///
+    /// Fetches the risc0 zkvm supported versions as well as other
+    /// sub-components of bonsai
+    pub async fn version(&self) -> Result<VersionInfo, SdkErr> {
+        let res = self
+            .client
+            .get(format!("{}/version", self.url))
+            .send()
+            .await?;
+
+        if !res.status().is_success() {
+            return Err(SdkErr::from_status(res.status(), res.text().await?));
+        }
+        Ok(res.json::<VersionInfo>().await?)
+    }
+
+    /// Checks that bonsai supports this SDK and the risc0-zkvm version of this
+    /// client
+    ///
+    /// See [Client::check_compat].
+    pub async fn check_compat(&self) -> Result<VersionInfo, SdkErr> {
+        let info = self.version().await?;
+        check_versions(&info, &self.risc0_version)?;
+        Ok(info)
+    }
+
+    // - /user
+
+    /// Fetches your current users quotas
+    ///
+    /// Returns the [Quotas] structure with relevant data on cycle budget, quotas etc.
+    pub async fn quotas(&self) -> Result<Quotas, SdkErr> {
+        Ok(self
+            .client
+            .get(format!("{}/user/quotas", self.url))
+            .send()
+            .await?
+            .json::<Quotas>()
+            .await?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use httpmock::{prelude::*, Method};
+    use uuid::Uuid;
+
+    use super::*;
+    use crate::{alpha::responses::SessionMetadata, API_KEY_HEADER, VERSION_HEADER};
+
+    const TEST_KEY: &str = "TESTKEY";
+    const TEST_ID: &str = "0x5891b5b522d5df086d0ff0b110fbd9d21bb4fc7163af34d08286a2e846f6be03";
+    const TEST_VERSION: &str = "0.1.0";
+
+    fn client(server: &MockServer) -> AsyncClient {
+        let server_url = format!("http://{}", server.address());
+        AsyncClient::from_parts(server_url, TEST_KEY.to_string(), TEST_VERSION)
+            .expect("Failed to construct client")
+    }
+
+    #[test]
+    fn client_from_parts() {
+        let url = "http://127.0.0.1/stage/".to_string();
+        let client = AsyncClient::from_parts(url, TEST_KEY.to_string(), TEST_VERSION).unwrap();
+
+        assert_eq!(client.url, "http://127.0.0.1/stage");
+    }
+
+    #[tokio::test]
+    async fn image_upload() {
+        let server = MockServer::start_async().await;
+
+        let put_url = format!("http://{}/upload/{TEST_ID}", server.address());
+        let response = ImgUploadRes { url: put_url };
+
+        let get_mock = server
+            .mock_async(|when, then| {
+                when.method(GET)
+                    .path(format!("/images/upload/{TEST_ID}"))
+                    .header(API_KEY_HEADER, TEST_KEY)
+                    .header(VERSION_HEADER, TEST_VERSION);
+                then.status(200)
+                    .header("content-type", "application/json")
+                    .json_body_obj(&response);
+            })
+            .await;
+
+        let put_mock = server
+            .mock_async(|when, then| {
+                when.method(PUT).path(format!("/upload/{TEST_ID}"));
+                then.status(200);
+            })
+            .await;
+
+        let outcome = client(&server)
+            .upload_img(TEST_ID, vec![])
+            .await
+            .expect("Failed to upload image");
+        assert_eq!(outcome, ImgUploadOutcome::Uploaded);
+        get_mock.assert_async().await;
+        put_mock.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn image_upload_skip() {
+        let server = MockServer::start_async().await;
+
+        let head_mock = server
+            .mock_async(|when, then| {
+                when.method(Method::HEAD).path(format!("/images/{TEST_ID}"));
+                then.status(200);
+            })
+            .await;
+
+        let get_mock = server
+            .mock_async(|when, then| {
+                when.method(GET).path(format!("/images/upload/{TEST_ID}"));
+                then.status(500);
+            })
+            .await;
+
+        let client = client(&server);
+        assert!(client.has_img(TEST_ID).await.unwrap());
+        let outcome = client.upload_img(TEST_ID, vec![0x41]).await.unwrap();
+        assert_eq!(outcome, ImgUploadOutcome::AlreadyExists);
+        head_mock.assert_hits_async(2).await;
+        get_mock.assert_hits_async(0).await;
+    }
+
+    #[tokio::test]
+    async fn input_upload() {
+        let server = MockServer::start_async().await;
+
+        let input_uuid = Uuid::new_v4();
+        let put_url = format!("http://{}/upload/{}", server.address(), input_uuid);
+        let response = UploadRes {
+            url: put_url,
+            uuid: input_uuid.to_string(),
+        };
+
+        let get_mock = server
+            .mock_async(|when, then| {
+                when.method(GET)
+                    .path("/inputs/upload")
+                    .header(API_KEY_HEADER, TEST_KEY)
+                    .header(VERSION_HEADER, TEST_VERSION);
+                then.status(200)
+                    .header("content-type", "application/json")
+                    .json_body_obj(&response);
+            })
+            .await;
+
+        let put_mock = server
+            .mock_async(|when, then| {
+                when.method(PUT).path(format!("/upload/{}", input_uuid));
+                then.status(200);
+            })
+            .await;
+
+        let res = client(&server)
+            .upload_input(vec![])
+            .await
+            .expect("Failed to upload input");
+
+        assert_eq!(res, response.uuid);
+        get_mock.assert_async().await;
+        put_mock.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn session_create_and_status() {
+        let server = MockServer::start_async().await;
+
+        let session_id = Uuid::new_v4().to_string();
+        let create_mock = server
+            .mock_async(|when, then| {
+                when.method(POST)
+                    .path("/sessions/create")
+                    .header(API_KEY_HEADER, TEST_KEY)
+                    .header(VERSION_HEADER, TEST_VERSION);
+                then.status(200)
+                    .header("content-type", "application/json")
+                    .json_body_obj(&CreateSessRes {
+                        uuid: session_id.clone(),
+                    });
+            })
+            .await;
+
+        let response = SessionStatusRes {
+            status: "RUNNING".to_string(),
+            receipt_url: None,
+            error_msg: None,
+            state: None,
+            elapsed_time: None,
+            stats: None,
+            metadata: None,
+        };
+        let status_mock = server
+            .mock_async(|when, then| {
+                when.method(GET)
+                    .path(format!("/sessions/status/{session_id}"))
+                    .header(API_KEY_HEADER, TEST_KEY)
+                    .header(VERSION_HEADER, TEST_VERSION);
+                then.status(200)
+                    .header("content-type", "application/json")
+                    .json_body_obj(&response);
+            })
+            .await;
+
+        let client = client(&server);
+        let session = client
+            .create_session(TEST_ID.to_string(), Uuid::new_v4().to_string(), vec![])
+            .await
+            .expect("Failed to create session");
+        assert_eq!(session.uuid, session_id);
+
+        let status = client
+            .session_status(&session)
+            .await
+            .expect("Failed to fetch status");
+        assert_eq!(status.status, response.status);
+
+        create_mock.assert_async().await;
+        status_mock.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn session_execute_only() {
+        let server = MockServer::start_async().await;
+
+        let session_id = Uuid::new_v4().to_string();
+        let request = ProofReq {
+            img: TEST_ID.to_string(),
+            input: Uuid::new_v4().to_string(),
+            assumptions: vec![],
+            execute_only: true,
+            metadata: SessionMetadata::default(),
+        };
+        let create_mock = server
+            .mock_async(|when, then| {
+                when.method(POST)
+                    .path("/sessions/create")
+                    .json_body_obj(&request);
+                then.status(200)
+                    .header("content-type", "application/json")
+                    .json_body_obj(&CreateSessRes {
+                        uuid: session_id.clone(),
+                    });
+            })
+            .await;
+        let journal_mock = server
+            .mock_async(|when, then| {
+                when.method(GET)
+                    .path(format!("/sessions/exec_only_journal/{session_id}"));
+                then.status(200).body([1, 2, 3, 4]);
+            })
+            .await;
+
+        let client = client(&server);
+        let session = client
+            .create_session_with_opts(
+                request.img.clone(),
+                request.input.clone(),
+                vec![],
+                SessionOpts {
+                    execute_only: true,
+                    ..Default::default()
+                },
+            )
+            .await
+            .expect("Failed to create session");
+        assert_eq!(session.uuid, session_id);
+
+        let journal = client
+            .session_exec_only_journal(&session)
+            .await
+            .expect("Failed to download journal");
+        assert_eq!(journal, vec![1, 2, 3, 4]);
+
+        create_mock.assert_async().await;
+        journal_mock.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn server_error() {
+        let server = MockServer::start_async().await;
+
+        let get_mock = server
+            .mock_async(|when, then| {
+                when.method(GET).path("/inputs/upload");
+                then.status(500).body("failure");
+            })
+            .await;
+
+        let retry = RetryPolicy {
+            initial_backoff: Duration::from_millis(1),
+            ..Default::default()
+        };
+        let err = client(&server)
+            .with_retry_policy(retry.clone())
+            .upload_input(vec![])
+            .await
+            .unwrap_err();
+        assert!(matches!(err, SdkErr::InternalServerErr(body) if body == "failure"));
+        assert_eq!(get_mock.hits_async().await, retry.max_attempts as usize);
+    }
+
+    // Panics on every request.
+    struct PanickingBackend;
+
+    impl crate::alpha::Backend for PanickingBackend {
+        fn has_img(&self, _image_id: &str) -> Result<bool, SdkErr> {
+            panic!("has_img")
+        }
+
+        fn upload_img(&self, _image_id: &str, _buf: Vec<u8>) -> Result<ImgUploadOutcome, SdkErr> {
+            panic!("upload_img")
+        }
+
+        fn upload_input(&self, _buf: Vec<u8>) -> Result<String, SdkErr> {
+            panic!("upload_input")
+        }
+
+        fn upload_receipt(&self, _buf: Vec<u8>) -> Result<String, SdkErr> {
+            panic!("upload_receipt")
+        }
+
+        fn create_session(
+            &self,
+            _img_id: String,
+            _input_id: String,
+            _assumptions: Vec<String>,
+            _opts: SessionOpts,
+        ) -> Result<SessionId, SdkErr> {
+            panic!("create_session")
+        }
+
+        fn session_status(&self, _session_id: &SessionId) -> Result<SessionStatusRes, SdkErr> {
+            panic!("session_status")
+        }
+
+        fn exec_only_journal(&self, _session_id: &SessionId) -> Result<Vec<u8>, SdkErr> {
+            panic!("exec_only_journal")
+        }
+
+        fn receipt_download(&self, _session_id: &SessionId) -> Result<Vec<u8>, SdkErr> {
+            panic!("receipt_download")
+        }
+
+        fn download(&self, _url: &str) -> Result<Vec<u8>, SdkErr> {
+            panic!("download")
+        }
+
+        fn version(&self) -> Result<VersionInfo, SdkErr> {
+            panic!("version")
+        }
+    }
+
+    #[test]
+    fn task_panic() {
+        // The blocking client cannot be constructed within the runtime.
+        let client = Client::from_backend(PanickingBackend, TEST_VERSION).unwrap();
+        let runtime = tokio::runtime::Runtime::new().unwrap();
+        let err = runtime
+            .block_on(super::upload_input(client, vec![]))
+            .unwrap_err();
+        assert!(matches!(err, SdkErr::TaskFailed(_)));
+        assert!(!err.is_retryable());
+    }
 }
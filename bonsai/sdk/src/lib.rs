@@ -32,6 +32,8 @@
 //!
 //! fn run_bonsai(input_data: Vec<u8>) -> Result<()> {
 //!     let client = bonsai_sdk::Client::from_env(risc0_zkvm::VERSION)?;
+//!     // Fail early if bonsai does not support this SDK or zkvm version.
+//!     client.check_compat()?;
 //!
 //!     // Compute the image_id, then upload the ELF with the image_id as its key.
 //!     let image_id = hex::encode(compute_image_id(METHOD_ELF)?);
@@ -47,37 +49,30 @@
 //!
 //!     // Start a session running the prover
 //!     let session = client.create_session(image_id, input_id, assumptions)?;
-//!     loop {
-//!         let res = session.status(&client)?;
-//!         if res.status == "RUNNING" {
-//!             eprintln!(
-//!                 "Current status: {} - state: {} - continue polling...",
-//!                 res.status,
-//!                 res.state.unwrap_or_default()
-//!             );
-//!             std::thread::sleep(Duration::from_secs(15));
-//!             continue;
-//!         }
-//!         if res.status == "SUCCEEDED" {
-//!             // Download the receipt, containing the output
-//!             let receipt_url = res
-//!                 .receipt_url
-//!                 .expect("API error, missing receipt on completed session");
-//!
-//!             let receipt_buf = client.download(&receipt_url)?;
-//!             let receipt: Receipt = bincode::deserialize(&receipt_buf)?;
-//!             receipt
-//!                 .verify(METHOD_ID)
-//!                 .expect("Receipt verification failed");
-//!         } else {
-//!             panic!(
-//!                 "Workflow exited: {} - | err: {}",
-//!                 res.status,
-//!                 res.error_msg.unwrap_or_default()
-//!             );
-//!         }
 //!
-//!         break;
+//!     // Wait for the session to finish, for at most an hour
+//!     let res = session.poll_until_done(
+//!         &client,
+//!         Duration::from_secs(60 * 60),
+//!         Duration::from_secs(15),
+//!     )?;
+//!     if res.status == "SUCCEEDED" {
+//!         // Download the receipt, containing the output
+//!         let receipt_url = res
+//!             .receipt_url
+//!             .expect("API error, missing receipt on completed session");
+//!
+//!         let receipt_buf = client.download(&receipt_url)?;
+//!         let receipt: Receipt = bincode::deserialize(&receipt_buf)?;
+//!         receipt
+//!             .verify(METHOD_ID)
+//!             .expect("Receipt verification failed");
+//!     } else {
+//!         panic!(
+//!             "Workflow exited: {} - | err: {}",
+//!             res.status,
+//!             res.error_msg.unwrap_or_default()
+//!         );
 //!     }
 //!
 //!     // Optionally run stark2snark
@@ -88,6 +83,36 @@
 //!
 //! ```
 //!
+//! ## Streaming session status
+//!
+//! Instead of polling the status of a session in a loop, `Client::stream_status`
+//! yields a `SessionEvent` each time the status or proving state of the session
+//! changes, and ends after the session completes. It reads the server-sent events
+//! of the session when the API provides them, and polls the status otherwise.
+//!
+//! ```no_run
+//! use anyhow::{bail, Result};
+//! use bonsai_sdk::alpha::{Client, SessionEvent, SessionId};
+//!
+//! fn wait_for_receipt(client: &Client, session: &SessionId) -> Result<Vec<u8>> {
+//!     for event in client.stream_status(session) {
+//!         match event? {
+//!             SessionEvent::Running { state } => {
+//!                 eprintln!("Current state: {}", state.unwrap_or_default())
+//!             }
+//!             SessionEvent::Succeeded { receipt_url } => {
+//!                 let receipt_url = receipt_url.expect("API error, missing receipt");
+//!                 return Ok(client.download(&receipt_url)?);
+//!             }
+//!             SessionEvent::Failed { status, error_msg } => {
+//!                 bail!("Workflow exited: {status} - | err: {}", error_msg.unwrap_or_default())
+//!             }
+//!         }
+//!     }
+//!     bail!("status stream ended early")
+//! }
+//! ```
+//!
 //! ## STARK to SNARK
 //!
 //! After a STARK proof is generated, it is possible to convert the proof to SNARK.
@@ -105,28 +130,16 @@
 //!
 //!     let snark_session = client.create_snark(session_id)?;
 //!     eprintln!("Created snark session: {}", snark_session.uuid);
-//!     loop {
-//!         let res = snark_session.status(&client)?;
-//!         match res.status.as_str() {
-//!             "RUNNING" => {
-//!                 eprintln!("Current status: {} - continue polling...", res.status,);
+//!     let snark_receipt = loop {
+//!         match snark_session.status(&client)?.receipt()? {
+//!             Some(snark_receipt) => break snark_receipt,
+//!             None => {
+//!                 eprintln!("Snark session running - continue polling...");
 //!                 std::thread::sleep(Duration::from_secs(15));
-//!                 continue;
-//!             }
-//!             "SUCCEEDED" => {
-//!                 let snark_receipt = res.output;
-//!                 eprintln!("Snark proof!: {snark_receipt:?}");
-//!                 break;
-//!             }
-//!             _ => {
-//!                 panic!(
-//!                     "Workflow exited: {} err: {}",
-//!                     res.status,
-//!                     res.error_msg.unwrap_or_default()
-//!                 );
 //!             }
 //!         }
-//!     }
+//!     };
+//!     eprintln!("Snark proof!: {snark_receipt:?}");
 //!     Ok(())
 //! }
 //! ```
@@ -25,8 +25,7 @@ impl CycleCounter for Job {
     fn run() -> Metrics {
         let data = include_str!("../../../json/res/example.json");
         let env = ExecutorEnv::builder()
-            .write(&data)
-            .unwrap()
+            .write_slice(data.as_bytes())
             .build()
             .unwrap();
 
@@ -18,6 +18,7 @@ pub mod digital_signature;
 pub mod ecdsa;
 pub mod hello_world;
 pub mod json;
+pub mod json_serde;
 pub mod password_checker;
 pub mod prorata;
 pub mod sha;
@@ -37,6 +37,7 @@ enum Command {
     Ecdsa,
     HelloWorld,
     Json,
+    JsonSerde,
     PasswordChecker,
     Prorata,
     Sha,
@@ -75,6 +76,10 @@ fn main() {
         run_jobs::<json::Job>(&cli.out);
     }
 
+    if cli.command == Command::All || cli.command == Command::JsonSerde {
+        run_jobs::<json_serde::Job>(&cli.out);
+    }
+
     if cli.command == Command::All || cli.command == Command::PasswordChecker {
         run_jobs::<password_checker::Job>(&cli.out);
     }
@@ -0,0 +1,596 @@
+// Copyright 2024 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! DKIM (RFC 6376) signature verification for use inside the zkVM guest.
+//!
+//! The guest has no network access, so the signer's public key is provided
+//! as the contents of its DNS TXT record. The digest of the key is included in
+//! the [VerifiedEmail] so that a verifier of the receipt can check it against
+//! the record published at `<selector>._domainkey.<domain>`.
+
+use base64::{engine::general_purpose::STANDARD, Engine};
+use risc0_zkvm::sha::{Digest, Impl, Sha256 as _};
+use rsa::{
+    pkcs1v15::{Signature, VerifyingKey},
+    pkcs8::DecodePublicKey,
+    signature::Verifier,
+    RsaPublicKey,
+};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest as _, Sha256};
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum Error {
+    #[error("malformed header at offset {0}")]
+    MalformedHeader(usize),
+
+    #[error("message has no DKIM-Signature header")]
+    MissingSignature,
+
+    #[error("malformed DKIM-Signature: {0}")]
+    MalformedSignature(&'static str),
+
+    #[error("unsupported signature algorithm {0}")]
+    UnsupportedAlgorithm(String),
+
+    #[error("unsupported canonicalization {0}")]
+    UnsupportedCanonicalization(String),
+
+    #[error("signatures with a body length limit are not supported")]
+    BodyLengthLimit,
+
+    #[error("malformed DKIM key record")]
+    MalformedKey,
+
+    #[error("DKIM key has been revoked")]
+    RevokedKey,
+
+    #[error("invalid base64: {0}")]
+    Base64(#[from] base64::DecodeError),
+
+    #[error("body hash does not match")]
+    BodyHashMismatch,
+
+    #[error("signature is invalid")]
+    InvalidSignature,
+
+    #[error("signed From header is missing or malformed")]
+    MalformedFrom,
+
+    #[error("header {0} is not covered by the signature")]
+    UnsignedHeader(String),
+}
+
+/// Input to the guest.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct DkimInput {
+    /// The raw message, with CRLF line endings.
+    pub email: Vec<u8>,
+    /// The signer's DNS TXT record, e.g. `v=DKIM1; k=rsa; p=...`.
+    pub dns_record: String,
+    /// Names of signed header fields to include in the output.
+    pub reveal: Vec<String>,
+}
+
+/// Claims about a message with a valid DKIM signature.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct VerifiedEmail {
+    /// The signing domain, from the `d=` tag.
+    pub signing_domain: String,
+    /// The key selector, from the `s=` tag.
+    pub selector: String,
+    /// The domain of the address in the signed From header, in lowercase.
+    pub from_domain: String,
+    /// SHA-256 digest of the DER-encoded public key used to verify the
+    /// signature.
+    pub public_key_digest: Digest,
+    /// The requested header fields, unfolded and trimmed.
+    pub headers: Vec<(String, String)>,
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum Canonicalization {
+    Simple,
+    Relaxed,
+}
+
+struct Header<'a> {
+    /// The complete header, including folded lines and the final CRLF.
+    raw: &'a [u8],
+    name: &'a [u8],
+    value: &'a [u8],
+}
+
+struct DkimSignature<'a> {
+    header: &'a Header<'a>,
+    domain: String,
+    selector: String,
+    signed_headers: Vec<String>,
+    body_hash: Vec<u8>,
+    signature: Vec<u8>,
+    header_canon: Canonicalization,
+    body_canon: Canonicalization,
+}
+
+/// Verify the DKIM signature of `email` using the key in `dns_record`, and
+/// reveal the signed header fields named in `reveal`.
+///
+/// If the message has several DKIM signatures, the first one that verifies
+/// with the given key is used.
+pub fn verify_email(
+    email: &[u8],
+    dns_record: &str,
+    reveal: &[String],
+) -> Result<VerifiedEmail, Error> {
+    let (headers, body) = split_message(email)?;
+    let (key, public_key_digest) = parse_key(dns_record)?;
+
+    let mut result = Err(Error::MissingSignature);
+    for header in headers.iter() {
+        if !header.name.eq_ignore_ascii_case(b"DKIM-Signature") {
+            continue;
+        }
+        result = parse_signature(header)
+            .and_then(|sig| verify_signature(&sig, &headers, body, &key).map(|()| sig));
+        if result.is_ok() {
+            break;
+        }
+    }
+    let sig = result?;
+
+    let signed = |name: &str| {
+        sig.signed_headers
+            .iter()
+            .any(|signed| signed.eq_ignore_ascii_case(name))
+    };
+    let from = last_header(&headers, "From").ok_or(Error::MalformedFrom)?;
+    let from_domain = parse_from_domain(&unfold(from.value)).ok_or(Error::MalformedFrom)?;
+    let headers = reveal
+        .iter()
+        .map(|name| {
+            if !signed(name) {
+                return Err(Error::UnsignedHeader(name.clone()));
+            }
+            let value = last_header(&headers, name)
+                .map(|header| unfold(header.value))
+                .unwrap_or_default();
+            Ok((name.clone(), value))
+        })
+        .collect::<Result<_, _>>()?;
+
+    Ok(VerifiedEmail {
+        signing_domain: sig.domain,
+        selector: sig.selector,
+        from_domain,
+        public_key_digest,
+        headers,
+    })
+}
+
+fn verify_signature(
+    sig: &DkimSignature,
+    headers: &[Header],
+    body: &[u8],
+    key: &RsaPublicKey,
+) -> Result<(), Error> {
+    let body_hash = Sha256::digest(canonicalize_body(body, sig.body_canon));
+    if body_hash[..] != sig.body_hash[..] {
+        return Err(Error::BodyHashMismatch);
+    }
+
+    // Each name in h= selects the last instance of that header not yet
+    // selected by a previous occurrence of the same name.
+    let mut data = Vec::new();
+    let mut used = vec![false; headers.len()];
+    for name in sig.signed_headers.iter() {
+        let found = headers
+            .iter()
+            .enumerate()
+            .rev()
+            .find(|(i, header)| !used[*i] && header.name.eq_ignore_ascii_case(name.as_bytes()));
+        if let Some((i, header)) = found {
+            used[i] = true;
+            canonicalize_header(&mut data, header.name, header.raw, sig.header_canon);
+        }
+    }
+
+    // The signature header itself is included with an empty b= value and
+    // without its trailing CRLF.
+    let stripped = strip_b_tag(sig.header.raw);
+    canonicalize_header(&mut data, sig.header.name, &stripped, sig.header_canon);
+    data.truncate(data.len() - 2);
+
+    let signature =
+        Signature::try_from(sig.signature.as_slice()).map_err(|_| Error::InvalidSignature)?;
+    VerifyingKey::<Sha256>::new(key.clone())
+        .verify(&data, &signature)
+        .map_err(|_| Error::InvalidSignature)
+}
+
+fn split_message(email: &[u8]) -> Result<(Vec<Header>, &[u8]), Error> {
+    let mut headers = Vec::new();
+    let mut pos = 0;
+    while pos < email.len() {
+        if email[pos..].starts_with(b"\r\n") {
+            return Ok((headers, &email[pos + 2..]));
+        }
+        // A header ends at a CRLF that is not followed by whitespace.
+        let mut end = pos;
+        loop {
+            let crlf = find(&email[end..], b"\r\n").ok_or(Error::MalformedHeader(pos))?;
+            end += crlf + 2;
+            if !matches!(email.get(end), Some(b' ' | b'\t')) {
+                break;
+            }
+        }
+        let raw = &email[pos..end];
+        let colon = raw
+            .iter()
+            .position(|&b| b == b':')
+            .ok_or(Error::MalformedHeader(pos))?;
+        headers.push(Header {
+            raw,
+            name: trim(&raw[..colon]),
+            value: &raw[colon + 1..raw.len() - 2],
+        });
+        pos = end;
+    }
+    Ok((headers, &[]))
+}
+
+fn parse_signature<'a>(header: &'a Header<'a>) -> Result<DkimSignature<'a>, Error> {
+    let mut version = None;
+    let mut algorithm = None;
+    let mut domain = None;
+    let mut selector = None;
+    let mut signed_headers = None;
+    let mut body_hash = None;
+    let mut signature = None;
+    let mut canon = None;
+    for (name, value) in parse_tags(header.value)? {
+        match name {
+            "v" => version = Some(value),
+            "a" => algorithm = Some(value),
+            "d" => domain = Some(value),
+            "s" => selector = Some(value),
+            "h" => signed_headers = Some(value),
+            "bh" => body_hash = Some(STANDARD.decode(strip_whitespace(value))?),
+            "b" => signature = Some(STANDARD.decode(strip_whitespace(value))?),
+            "c" => canon = Some(value),
+            "l" => return Err(Error::BodyLengthLimit),
+            _ => {}
+        }
+    }
+
+    if version != Some("1") {
+        return Err(Error::MalformedSignature("unsupported version"));
+    }
+    match algorithm {
+        Some("rsa-sha256") => {}
+        Some(other) => return Err(Error::UnsupportedAlgorithm(other.to_string())),
+        None => return Err(Error::MalformedSignature("missing a= tag")),
+    }
+    let (header_canon, body_canon) = parse_canonicalization(canon.unwrap_or("simple/simple"))?;
+    let signed_headers: Vec<String> = signed_headers
+        .ok_or(Error::MalformedSignature("missing h= tag"))?
+        .split(':')
+        .map(|name| name.trim().to_string())
+        .collect();
+    if !signed_headers
+        .iter()
+        .any(|name| name.eq_ignore_ascii_case("From"))
+    {
+        return Err(Error::MalformedSignature("From header is not signed"));
+    }
+
+    Ok(DkimSignature {
+        header,
+        domain: domain
+            .ok_or(Error::MalformedSignature("missing d= tag"))?
+            .to_ascii_lowercase(),
+        selector: selector
+            .ok_or(Error::MalformedSignature("missing s= tag"))?
+            .to_string(),
+        signed_headers,
+        body_hash: body_hash.ok_or(Error::MalformedSignature("missing bh= tag"))?,
+        signature: signature.ok_or(Error::MalformedSignature("missing b= tag"))?,
+        header_canon,
+        body_canon,
+    })
+}
+
+fn parse_canonicalization(value: &str) -> Result<(Canonicalization, Canonicalization), Error> {
+    let parse = |value: &str| match value {
+        "simple" => Ok(Canonicalization::Simple),
+        "relaxed" => Ok(Canonicalization::Relaxed),
+        other => Err(Error::UnsupportedCanonicalization(other.to_string())),
+    };
+    match value.split_once('/') {
+        Some((header, body)) => Ok((parse(header)?, parse(body)?)),
+        None => Ok((parse(value)?, Canonicalization::Simple)),
+    }
+}
+
+fn parse_key(dns_record: &str) -> Result<(RsaPublicKey, Digest), Error> {
+    let mut key = None;
+    for (name, value) in parse_tags(dns_record.as_bytes())? {
+        match name {
+            "k" if value != "rsa" => return Err(Error::UnsupportedAlgorithm(value.to_string())),
+            "p" => key = Some(strip_whitespace(value)),
+            _ => {}
+        }
+    }
+    let key = key.ok_or(Error::MalformedKey)?;
+    if key.is_empty() {
+        return Err(Error::RevokedKey);
+    }
+    let der = STANDARD.decode(key)?;
+    let key = RsaPublicKey::from_public_key_der(&der).map_err(|_| Error::MalformedKey)?;
+    Ok((key, *Impl::hash_bytes(&der)))
+}
+
+/// Parse a tag=value list, as used by both signatures and key records.
+fn parse_tags(list: &[u8]) -> Result<Vec<(&str, &str)>, Error> {
+    let list = std::str::from_utf8(list).map_err(|_| Error::MalformedSignature("not ASCII"))?;
+    list.split(';')
+        .map(str::trim)
+        .filter(|tag| !tag.is_empty())
+        .map(|tag| {
+            let (name, value) = tag
+                .split_once('=')
+                .ok_or(Error::MalformedSignature("malformed tag"))?;
+            Ok((name.trim(), value.trim()))
+        })
+        .collect()
+}
+
+/// Remove the value of the b= tag from a raw DKIM-Signature header.
+fn strip_b_tag(raw: &[u8]) -> Vec<u8> {
+    let colon = raw.iter().position(|&b| b == b':').unwrap_or(0);
+    let mut out = raw[..=colon].to_vec();
+    let value = &raw[colon + 1..raw.len() - 2];
+    for (i, tag) in value.split(|&b| b == b';').enumerate() {
+        if i > 0 {
+            out.push(b';');
+        }
+        match tag.iter().position(|&b| b == b'=') {
+            Some(eq) if trim(&tag[..eq]) == b"b" => out.extend_from_slice(&tag[..=eq]),
+            _ => out.extend_from_slice(tag),
+        }
+    }
+    out.extend_from_slice(b"\r\n");
+    out
+}
+
+fn canonicalize_header(out: &mut Vec<u8>, name: &[u8], raw: &[u8], canon: Canonicalization) {
+    match canon {
+        Canonicalization::Simple => out.extend_from_slice(raw),
+        Canonicalization::Relaxed => {
+            let colon = raw.iter().position(|&b| b == b':').unwrap_or(0);
+            out.extend(name.iter().map(u8::to_ascii_lowercase));
+            out.push(b':');
+            let value: Vec<u8> = raw[colon + 1..]
+                .iter()
+                .copied()
+                .filter(|&b| b != b'\r' && b != b'\n')
+                .collect();
+            out.extend_from_slice(&collapse_whitespace(trim(&value)));
+            out.extend_from_slice(b"\r\n");
+        }
+    }
+}
+
+fn canonicalize_body(body: &[u8], canon: Canonicalization) -> Vec<u8> {
+    match canon {
+        Canonicalization::Simple => {
+            let mut end = body.len();
+            while body[..end].ends_with(b"\r\n\r\n") {
+                end -= 2;
+            }
+            let mut out = body[..end].to_vec();
+            if !out.ends_with(b"\r\n") {
+                out.extend_from_slice(b"\r\n");
+            }
+            out
+        }
+        Canonicalization::Relaxed => {
+            let mut lines: Vec<Vec<u8>> = Vec::new();
+            let mut rest = body;
+            while !rest.is_empty() {
+                let (line, next) = match find(rest, b"\r\n") {
+                    Some(i) => (&rest[..i], &rest[i + 2..]),
+                    None => (rest, &[][..]),
+                };
+                let mut line = collapse_whitespace(line);
+                if line.last() == Some(&b' ') {
+                    line.pop();
+                }
+                lines.push(line);
+                rest = next;
+            }
+            while lines.last().is_some_and(Vec::is_empty) {
+                lines.pop();
+            }
+            let mut out = Vec::with_capacity(body.len());
+            for line in lines {
+                out.extend_from_slice(&line);
+                out.extend_from_slice(b"\r\n");
+            }
+            out
+        }
+    }
+}
+
+/// Replace each run of spaces and tabs with a single space.
+fn collapse_whitespace(input: &[u8]) -> Vec<u8> {
+    let mut out = Vec::with_capacity(input.len());
+    for &b in input {
+        if b == b' ' || b == b'\t' {
+            if out.last() != Some(&b' ') {
+                out.push(b' ');
+            }
+        } else {
+            out.push(b);
+        }
+    }
+    out
+}
+
+fn trim(input: &[u8]) -> &[u8] {
+    let is_space = |b: &u8| matches!(b, b' ' | b'\t' | b'\r' | b'\n');
+    let start = input
+        .iter()
+        .position(|b| !is_space(b))
+        .unwrap_or(input.len());
+    let end = input
+        .iter()
+        .rposition(|b| !is_space(b))
+        .map_or(start, |i| i + 1);
+    &input[start..end]
+}
+
+fn strip_whitespace(input: &str) -> String {
+    input.chars().filter(|c| !c.is_ascii_whitespace()).collect()
+}
+
+/// Unfold a header value and trim surrounding whitespace.
+fn unfold(value: &[u8]) -> String {
+    let value: Vec<u8> = value
+        .iter()
+        .copied()
+        .filter(|&b| b != b'\r' && b != b'\n')
+        .collect();
+    String::from_utf8_lossy(trim(&value)).into_owned()
+}
+
+fn last_header<'a>(headers: &'a [Header<'a>], name: &str) -> Option<&'a Header<'a>> {
+    headers
+        .iter()
+        .rev()
+        .find(|header| header.name.eq_ignore_ascii_case(name.as_bytes()))
+}
+
+fn parse_from_domain(from: &str) -> Option<String> {
+    let address = match (from.rfind('<'), from.rfind('>')) {
+        (Some(start), Some(end)) if start < end => &from[start + 1..end],
+        _ => from,
+    };
+    let (_, domain) = address.trim().rsplit_once('@')?;
+    (!domain.is_empty()).then(|| domain.to_ascii_lowercase())
+}
+
+fn find(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    haystack
+        .windows(needle.len())
+        .position(|window| window == needle)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RELAXED: &[u8] = include_bytes!("../testdata/relaxed.eml");
+    const SIMPLE: &[u8] = include_bytes!("../testdata/simple.eml");
+    const DNS_RECORD: &str = include_str!("../testdata/dns.txt");
+
+    fn reveal() -> Vec<String> {
+        vec!["Subject".to_string(), "To".to_string()]
+    }
+
+    #[test]
+    fn verify_relaxed() {
+        let email = verify_email(RELAXED, DNS_RECORD, &reveal()).unwrap();
+        assert_eq!(email.signing_domain, "example.com");
+        assert_eq!(email.selector, "test");
+        assert_eq!(email.from_domain, "mail.example.com");
+        assert_eq!(
+            email.headers,
+            vec![
+                ("Subject".to_string(), "Hello   from  the zkVM".to_string()),
+                ("To".to_string(), "bob@example.org".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn verify_simple() {
+        let email = verify_email(SIMPLE, DNS_RECORD, &reveal()).unwrap();
+        assert_eq!(email.from_domain, "mail.example.com");
+    }
+
+    #[test]
+    fn relaxed_tolerates_whitespace_changes() {
+        let email = [RELAXED, b"\r\n\r\n"].concat();
+        let email = replace(&email, b"Subject:   Hello", b"subject: Hello");
+        let email = replace(&email, b"is signed.", b"is   signed.");
+        verify_email(&email, DNS_RECORD, &[]).unwrap();
+    }
+
+    #[test]
+    fn simple_rejects_whitespace_changes() {
+        let email = replace(SIMPLE, b"is signed.", b"is   signed.");
+        assert!(matches!(
+            verify_email(&email, DNS_RECORD, &[]),
+            Err(Error::BodyHashMismatch)
+        ));
+    }
+
+    #[test]
+    fn reject_tampered_header() {
+        let email = replace(RELAXED, b"To: bob@", b"To: eve@");
+        assert!(matches!(
+            verify_email(&email, DNS_RECORD, &[]),
+            Err(Error::InvalidSignature)
+        ));
+    }
+
+    #[test]
+    fn reject_unsigned_header() {
+        let reveal = vec!["Message-ID".to_string()];
+        assert!(matches!(
+            verify_email(RELAXED, DNS_RECORD, &reveal),
+            Err(Error::UnsignedHeader(_))
+        ));
+    }
+
+    #[test]
+    fn reject_revoked_key() {
+        assert!(matches!(
+            verify_email(RELAXED, "v=DKIM1; k=rsa; p=", &[]),
+            Err(Error::RevokedKey)
+        ));
+    }
+
+    #[test]
+    fn canonicalize_body_examples() {
+        // Examples from RFC 6376 section 3.4.5.
+        let body = b" C \r\nD \t E\r\n\r\n\r\n";
+        assert_eq!(
+            canonicalize_body(body, Canonicalization::Relaxed),
+            b" C\r\nD E\r\n"
+        );
+        assert_eq!(
+            canonicalize_body(body, Canonicalization::Simple),
+            b" C \r\nD \t E\r\n"
+        );
+        assert_eq!(canonicalize_body(b"", Canonicalization::Simple), b"\r\n");
+        assert_eq!(canonicalize_body(b"", Canonicalization::Relaxed), b"");
+    }
+
+    fn replace(input: &[u8], from: &[u8], to: &[u8]) -> Vec<u8> {
+        let pos = find(input, from).unwrap();
+        [&input[..pos], to, &input[pos + from.len()..]].concat()
+    }
+}
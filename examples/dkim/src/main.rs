@@ -0,0 +1,71 @@
+// Copyright 2024 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use dkim_core::{DkimInput, VerifiedEmail};
+use dkim_methods::VERIFY_EMAIL_ELF;
+use risc0_zkvm::{default_prover, ExecutorEnv};
+
+const EMAIL: &[u8] = include_bytes!("../core/testdata/relaxed.eml");
+const DNS_RECORD: &str = include_str!("../core/testdata/dns.txt");
+
+fn main() {
+    let input = DkimInput {
+        email: EMAIL.to_vec(),
+        dns_record: DNS_RECORD.to_string(),
+        reveal: vec!["Subject".to_string()],
+    };
+    let email = verify(&input);
+
+    println!(
+        "Verified email from {} signed by {} (selector {})",
+        email.from_domain, email.signing_domain, email.selector
+    );
+    println!("  public key digest {}", email.public_key_digest);
+    for (name, value) in email.headers.iter() {
+        println!("  {name}: {value}");
+    }
+}
+
+fn verify(input: &DkimInput) -> VerifiedEmail {
+    let env = ExecutorEnv::builder()
+        .write(input)
+        .unwrap()
+        .build()
+        .unwrap();
+
+    // Obtain the default prover.
+    let prover = default_prover();
+
+    // Produce a receipt by proving the specified ELF binary.
+    let receipt = prover.prove(env, VERIFY_EMAIL_ELF).unwrap();
+
+    receipt.journal.decode().unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use dkim_core::DkimInput;
+
+    #[test]
+    fn main() {
+        let input = DkimInput {
+            email: super::EMAIL.to_vec(),
+            dns_record: super::DNS_RECORD.to_string(),
+            reveal: vec!["Subject".to_string()],
+        };
+        let email = super::verify(&input);
+        assert_eq!(email.signing_domain, "example.com");
+        assert_eq!(email.from_domain, "mail.example.com");
+    }
+}
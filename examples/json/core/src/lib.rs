@@ -12,9 +12,13 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+mod parser;
+
 use risc0_zkvm::sha::Digest;
 use serde::{Deserialize, Serialize};
 
+pub use self::parser::{parse, Error, Number, Str, Value};
+
 #[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
 pub struct Outputs {
     pub data: u32,
@@ -0,0 +1,538 @@
+// Copyright 2024 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! A JSON parser tuned for running inside the zkVM.
+//!
+//! General purpose parsers such as `serde_json` eagerly validate UTF-8,
+//! unescape every string and convert every number, which dominates the cycle
+//! count of guests that only inspect a handful of fields. This parser instead
+//! only checks the structure of the document and borrows strings and numbers
+//! from the input. Their contents are validated and decoded when they are
+//! accessed, so the cost of a value that is never read is a single scan over
+//! its bytes.
+
+use std::{borrow::Cow, ops::Index};
+
+use thiserror::Error;
+
+/// Maximum nesting depth of arrays and objects.
+const MAX_DEPTH: usize = 128;
+
+static NULL: Value<'static> = Value::Null;
+
+#[derive(Debug, Error, Eq, PartialEq)]
+pub enum Error {
+    #[error("unexpected end of input")]
+    UnexpectedEof,
+
+    #[error("unexpected character at offset {0}")]
+    UnexpectedChar(usize),
+
+    #[error("trailing characters at offset {0}")]
+    TrailingChars(usize),
+
+    #[error("maximum nesting depth exceeded")]
+    DepthLimitExceeded,
+
+    #[error("invalid escape sequence")]
+    InvalidEscape,
+
+    #[error("invalid UTF-8 in string")]
+    InvalidUtf8,
+}
+
+/// A parsed JSON value, borrowing from the input.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Value<'a> {
+    Null,
+    Bool(bool),
+    Number(Number<'a>),
+    String(Str<'a>),
+    Array(Vec<Value<'a>>),
+    Object(Vec<(Str<'a>, Value<'a>)>),
+}
+
+/// A JSON number, kept in its textual form until it is converted.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Number<'a>(&'a [u8]);
+
+/// A JSON string, kept in its escaped form until it is decoded.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Str<'a> {
+    raw: &'a [u8],
+    escaped: bool,
+}
+
+/// Parse a JSON document.
+pub fn parse(input: &[u8]) -> Result<Value<'_>, Error> {
+    let mut parser = Parser { input, pos: 0 };
+    let value = parser.value(0)?;
+    parser.skip_whitespace();
+    if parser.pos != input.len() {
+        return Err(Error::TrailingChars(parser.pos));
+    }
+    Ok(value)
+}
+
+impl<'a> Value<'a> {
+    /// Look up a field of an object. Returns `None` if this is not an object
+    /// or the field is missing. If a key is repeated, the last one wins.
+    pub fn get(&self, key: &str) -> Option<&Value<'a>> {
+        match self {
+            Value::Object(fields) => fields
+                .iter()
+                .rev()
+                .find(|(name, _)| name.eq_str(key))
+                .map(|(_, value)| value),
+            _ => None,
+        }
+    }
+
+    pub fn is_null(&self) -> bool {
+        matches!(self, Value::Null)
+    }
+
+    pub fn as_bool(&self) -> Option<bool> {
+        match self {
+            Value::Bool(value) => Some(*value),
+            _ => None,
+        }
+    }
+
+    pub fn as_u64(&self) -> Option<u64> {
+        match self {
+            Value::Number(number) => number.as_u64(),
+            _ => None,
+        }
+    }
+
+    pub fn as_u32(&self) -> Option<u32> {
+        self.as_u64().and_then(|value| value.try_into().ok())
+    }
+
+    pub fn as_i64(&self) -> Option<i64> {
+        match self {
+            Value::Number(number) => number.as_i64(),
+            _ => None,
+        }
+    }
+
+    pub fn as_f64(&self) -> Option<f64> {
+        match self {
+            Value::Number(number) => number.as_f64(),
+            _ => None,
+        }
+    }
+
+    /// Decode a string value. Returns `None` if this is not a string or the
+    /// string is not valid.
+    pub fn as_str(&self) -> Option<Cow<'a, str>> {
+        match self {
+            Value::String(value) => value.to_str().ok(),
+            _ => None,
+        }
+    }
+
+    pub fn as_array(&self) -> Option<&[Value<'a>]> {
+        match self {
+            Value::Array(values) => Some(values),
+            _ => None,
+        }
+    }
+
+    pub fn as_object(&self) -> Option<&[(Str<'a>, Value<'a>)]> {
+        match self {
+            Value::Object(fields) => Some(fields),
+            _ => None,
+        }
+    }
+}
+
+impl<'a> Index<&str> for Value<'a> {
+    type Output = Value<'a>;
+
+    /// Look up a field of an object, returning `Null` if it is missing.
+    fn index(&self, key: &str) -> &Self::Output {
+        self.get(key).unwrap_or(&NULL)
+    }
+}
+
+impl<'a> Index<usize> for Value<'a> {
+    type Output = Value<'a>;
+
+    /// Look up an element of an array, returning `Null` if it is missing.
+    fn index(&self, index: usize) -> &Self::Output {
+        self.as_array()
+            .and_then(|values| values.get(index))
+            .unwrap_or(&NULL)
+    }
+}
+
+impl<'a> Number<'a> {
+    /// The number as it appears in the input.
+    pub fn as_bytes(&self) -> &'a [u8] {
+        self.0
+    }
+
+    /// Convert to a `u64`, if the number is a non-negative integer in range.
+    pub fn as_u64(&self) -> Option<u64> {
+        self.0.iter().try_fold(0u64, |acc, byte| {
+            if !byte.is_ascii_digit() {
+                return None;
+            }
+            acc.checked_mul(10)?.checked_add((byte - b'0') as u64)
+        })
+    }
+
+    /// Convert to an `i64`, if the number is an integer in range.
+    pub fn as_i64(&self) -> Option<i64> {
+        match self.0.split_first() {
+            Some((b'-', digits)) => {
+                let value = Number(digits).as_u64()?;
+                0i64.checked_sub_unsigned(value)
+            }
+            _ => self.as_u64()?.try_into().ok(),
+        }
+    }
+
+    pub fn as_f64(&self) -> Option<f64> {
+        // The parser only accepts ASCII in numbers.
+        std::str::from_utf8(self.0).ok()?.parse().ok()
+    }
+}
+
+impl<'a> Str<'a> {
+    /// The string as it appears in the input, without the surrounding quotes
+    /// and with escape sequences left in place.
+    pub fn as_raw(&self) -> &'a [u8] {
+        self.raw
+    }
+
+    /// Validate and unescape the string. Only allocates if the string
+    /// contains escape sequences.
+    pub fn to_str(&self) -> Result<Cow<'a, str>, Error> {
+        if !self.escaped {
+            return std::str::from_utf8(self.raw)
+                .map(Cow::Borrowed)
+                .map_err(|_| Error::InvalidUtf8);
+        }
+
+        let mut out = Vec::with_capacity(self.raw.len());
+        let mut bytes = self.raw.iter().copied();
+        while let Some(byte) = bytes.next() {
+            if byte != b'\\' {
+                out.push(byte);
+                continue;
+            }
+            let unescaped = match bytes.next().ok_or(Error::InvalidEscape)? {
+                b'"' => b'"',
+                b'\\' => b'\\',
+                b'/' => b'/',
+                b'b' => 0x08,
+                b'f' => 0x0c,
+                b'n' => b'\n',
+                b'r' => b'\r',
+                b't' => b'\t',
+                b'u' => {
+                    let mut code = hex4(&mut bytes)?;
+                    if (0xd800..0xdc00).contains(&code) {
+                        // A high surrogate must be followed by a low surrogate.
+                        if bytes.next() != Some(b'\\') || bytes.next() != Some(b'u') {
+                            return Err(Error::InvalidEscape);
+                        }
+                        let low = hex4(&mut bytes)?;
+                        if !(0xdc00..0xe000).contains(&low) {
+                            return Err(Error::InvalidEscape);
+                        }
+                        code = 0x10000 + ((code - 0xd800) << 10) + (low - 0xdc00);
+                    }
+                    let ch = char::from_u32(code).ok_or(Error::InvalidEscape)?;
+                    out.extend_from_slice(ch.encode_utf8(&mut [0; 4]).as_bytes());
+                    continue;
+                }
+                _ => return Err(Error::InvalidEscape),
+            };
+            out.push(unescaped);
+        }
+        String::from_utf8(out)
+            .map(Cow::Owned)
+            .map_err(|_| Error::InvalidUtf8)
+    }
+
+    /// Compare with `other` without allocating when there are no escapes.
+    pub fn eq_str(&self, other: &str) -> bool {
+        if self.escaped {
+            self.to_str().is_ok_and(|value| value == other)
+        } else {
+            self.raw == other.as_bytes()
+        }
+    }
+}
+
+fn hex4(bytes: &mut impl Iterator<Item = u8>) -> Result<u32, Error> {
+    let mut code = 0;
+    for _ in 0..4 {
+        let digit = bytes
+            .next()
+            .and_then(|byte| (byte as char).to_digit(16))
+            .ok_or(Error::InvalidEscape)?;
+        code = code << 4 | digit;
+    }
+    Ok(code)
+}
+
+struct Parser<'a> {
+    input: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn peek(&self) -> Result<u8, Error> {
+        self.input
+            .get(self.pos)
+            .copied()
+            .ok_or(Error::UnexpectedEof)
+    }
+
+    fn skip_whitespace(&mut self) {
+        while let Some(b' ' | b'\t' | b'\n' | b'\r') = self.input.get(self.pos) {
+            self.pos += 1;
+        }
+    }
+
+    fn expect(&mut self, byte: u8) -> Result<(), Error> {
+        if self.peek()? != byte {
+            return Err(Error::UnexpectedChar(self.pos));
+        }
+        self.pos += 1;
+        Ok(())
+    }
+
+    fn literal(&mut self, literal: &[u8], value: Value<'a>) -> Result<Value<'a>, Error> {
+        let end = self.pos + literal.len();
+        match self.input.get(self.pos..end) {
+            Some(bytes) if bytes == literal => {
+                self.pos = end;
+                Ok(value)
+            }
+            Some(_) => Err(Error::UnexpectedChar(self.pos)),
+            None => Err(Error::UnexpectedEof),
+        }
+    }
+
+    fn value(&mut self, depth: usize) -> Result<Value<'a>, Error> {
+        self.skip_whitespace();
+        match self.peek()? {
+            b'{' => self.object(depth + 1),
+            b'[' => self.array(depth + 1),
+            b'"' => Ok(Value::String(self.string()?)),
+            b'-' | b'0'..=b'9' => Ok(Value::Number(self.number()?)),
+            b't' => self.literal(b"true", Value::Bool(true)),
+            b'f' => self.literal(b"false", Value::Bool(false)),
+            b'n' => self.literal(b"null", Value::Null),
+            _ => Err(Error::UnexpectedChar(self.pos)),
+        }
+    }
+
+    fn object(&mut self, depth: usize) -> Result<Value<'a>, Error> {
+        if depth > MAX_DEPTH {
+            return Err(Error::DepthLimitExceeded);
+        }
+        self.expect(b'{')?;
+        let mut fields = Vec::new();
+        self.skip_whitespace();
+        if self.peek()? == b'}' {
+            self.pos += 1;
+            return Ok(Value::Object(fields));
+        }
+        loop {
+            self.skip_whitespace();
+            let key = self.string()?;
+            self.skip_whitespace();
+            self.expect(b':')?;
+            fields.push((key, self.value(depth)?));
+            self.skip_whitespace();
+            match self.peek()? {
+                b',' => self.pos += 1,
+                b'}' => {
+                    self.pos += 1;
+                    return Ok(Value::Object(fields));
+                }
+                _ => return Err(Error::UnexpectedChar(self.pos)),
+            }
+        }
+    }
+
+    fn array(&mut self, depth: usize) -> Result<Value<'a>, Error> {
+        if depth > MAX_DEPTH {
+            return Err(Error::DepthLimitExceeded);
+        }
+        self.expect(b'[')?;
+        let mut values = Vec::new();
+        self.skip_whitespace();
+        if self.peek()? == b']' {
+            self.pos += 1;
+            return Ok(Value::Array(values));
+        }
+        loop {
+            values.push(self.value(depth)?);
+            self.skip_whitespace();
+            match self.peek()? {
+                b',' => self.pos += 1,
+                b']' => {
+                    self.pos += 1;
+                    return Ok(Value::Array(values));
+                }
+                _ => return Err(Error::UnexpectedChar(self.pos)),
+            }
+        }
+    }
+
+    /// Scan a string, leaving escape sequences and UTF-8 to be checked when
+    /// the string is decoded.
+    fn string(&mut self) -> Result<Str<'a>, Error> {
+        self.expect(b'"')?;
+        let start = self.pos;
+        let mut escaped = false;
+        loop {
+            match self.peek()? {
+                b'"' => break,
+                b'\\' => {
+                    escaped = true;
+                    self.pos += 2;
+                }
+                0x00..=0x1f => return Err(Error::UnexpectedChar(self.pos)),
+                _ => self.pos += 1,
+            }
+        }
+        let raw = &self.input[start..self.pos];
+        self.pos += 1;
+        Ok(Str { raw, escaped })
+    }
+
+    fn digits(&mut self) -> Result<(), Error> {
+        if !self.peek()?.is_ascii_digit() {
+            return Err(Error::UnexpectedChar(self.pos));
+        }
+        while self.input.get(self.pos).is_some_and(u8::is_ascii_digit) {
+            self.pos += 1;
+        }
+        Ok(())
+    }
+
+    fn number(&mut self) -> Result<Number<'a>, Error> {
+        let start = self.pos;
+        if self.peek()? == b'-' {
+            self.pos += 1;
+        }
+        if self.peek()? == b'0' {
+            self.pos += 1;
+        } else {
+            self.digits()?;
+        }
+        if self.input.get(self.pos) == Some(&b'.') {
+            self.pos += 1;
+            self.digits()?;
+        }
+        if let Some(b'e' | b'E') = self.input.get(self.pos) {
+            self.pos += 1;
+            if let Some(b'+' | b'-') = self.input.get(self.pos) {
+                self.pos += 1;
+            }
+            self.digits()?;
+        }
+        Ok(Number(&self.input[start..self.pos]))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = include_str!("../../res/example.json");
+
+    #[test]
+    fn parse_example() {
+        let value = parse(EXAMPLE.as_bytes()).unwrap();
+        assert_eq!(value["critical_data"].as_u32(), Some(47));
+        assert_eq!(value["boolean_field"].as_bool(), Some(true));
+        assert_eq!(
+            value["obj_field"]["string_subfield"].as_str().unwrap(),
+            "hello world"
+        );
+        assert_eq!(
+            value["obj_field"]["array_subfield"][1].as_str().unwrap(),
+            "example"
+        );
+        assert!(value["missing"].is_null());
+        assert!(value["obj_field"]["array_subfield"][3].is_null());
+    }
+
+    #[test]
+    fn parse_numbers() {
+        let value = parse(br#"[0, -12, 3.5, 1e3, -2.5E-2, 18446744073709551616]"#).unwrap();
+        assert_eq!(value[0].as_u64(), Some(0));
+        assert_eq!(value[1].as_u64(), None);
+        assert_eq!(value[1].as_i64(), Some(-12));
+        assert_eq!(value[2].as_u64(), None);
+        assert_eq!(value[2].as_f64(), Some(3.5));
+        assert_eq!(value[3].as_f64(), Some(1000.0));
+        assert_eq!(value[4].as_f64(), Some(-0.025));
+        assert_eq!(value[5].as_u64(), None);
+
+        for invalid in ["01", "-", "1.", ".5", "1e", "+1"] {
+            assert!(parse(invalid.as_bytes()).is_err(), "{invalid}");
+        }
+    }
+
+    #[test]
+    fn parse_strings() {
+        let value = parse(r#"{"a\"b": "é\n😀", "plain": "café"}"#.as_bytes()).unwrap();
+        assert_eq!(value["a\"b"].as_str().unwrap(), "é\n😀");
+        assert!(matches!(value["a\"b"].as_str(), Some(Cow::Owned(_))));
+        assert!(matches!(value["plain"].as_str(), Some(Cow::Borrowed(_))));
+
+        let value = parse(br#"["\x", "\ud800", "\u12"]"#).unwrap();
+        for index in 0..3 {
+            assert!(value[index].as_str().is_none());
+        }
+        assert!(parse(b"\"\n\"").is_err());
+    }
+
+    #[test]
+    fn invalid_utf8_is_lazy() {
+        let value = parse(b"{\"skipped\": \"\xff\", \"read\": 1}").unwrap();
+        assert_eq!(value["read"].as_u64(), Some(1));
+        assert!(value["skipped"].as_str().is_none());
+    }
+
+    #[test]
+    fn duplicate_keys() {
+        let value = parse(br#"{"a": 1, "a": 2}"#).unwrap();
+        assert_eq!(value["a"].as_u64(), Some(2));
+    }
+
+    #[test]
+    fn reject_malformed() {
+        assert_eq!(parse(b""), Err(Error::UnexpectedEof));
+        assert_eq!(parse(b"{\"a\" 1}"), Err(Error::UnexpectedChar(5)));
+        assert_eq!(parse(b"[1,]"), Err(Error::UnexpectedChar(3)));
+        assert_eq!(parse(b"true false"), Err(Error::TrailingChars(5)));
+        assert_eq!(parse(b"nul"), Err(Error::UnexpectedEof));
+        assert_eq!(
+            parse(&[b'['; MAX_DEPTH + 1]),
+            Err(Error::DepthLimitExceeded)
+        );
+    }
+}
@@ -0,0 +1,38 @@
+// Copyright 2024 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+// Equivalent to the `search_json` guest, but using `serde_json` to provide a
+// baseline for the cycle count of the `json_core` parser.
+
+use std::io::Read;
+
+use json_core::Outputs;
+use risc0_zkvm::{
+    guest::env,
+    sha::{Impl, Sha256},
+};
+use serde_json::Value;
+
+fn main() {
+    let mut data = Vec::new();
+    env::stdin().read_to_end(&mut data).unwrap();
+    let sha = *Impl::hash_bytes(&data);
+    let data: Value = serde_json::from_slice(&data).unwrap();
+    let proven_val = data["critical_data"].as_u64().unwrap() as u32;
+    let out = Outputs {
+        data: proven_val,
+        hash: sha,
+    };
+    env::commit(&out);
+}
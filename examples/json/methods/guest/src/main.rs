@@ -12,16 +12,18 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use json::parse;
-use json_core::Outputs;
+use std::io::Read;
+
+use json_core::{parse, Outputs};
 use risc0_zkvm::{
     guest::env,
     sha::{Impl, Sha256},
 };
 
 fn main() {
-    let data: String = env::read();
-    let sha = *Impl::hash_bytes(&data.as_bytes());
+    let mut data = Vec::new();
+    env::stdin().read_to_end(&mut data).unwrap();
+    let sha = *Impl::hash_bytes(&data);
     let data = parse(&data).unwrap();
     let proven_val = data["critical_data"].as_u32().unwrap();
     let out = Outputs {
@@ -29,8 +29,7 @@ fn main() {
 
 fn search_json(data: &str) -> Outputs {
     let env = ExecutorEnv::builder()
-        .write(&data)
-        .unwrap()
+        .write_slice(data.as_bytes())
         .build()
         .unwrap();
 
@@ -12,16 +12,24 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+//! Verification of RS256 and ES256 signed JSON Web Tokens.
+//!
+//! When built for the zkVM guest, patching the `sha2` and `crypto-bigint`
+//! crates in the guest workspace enables the SHA-256 and bigint accelerators
+//! for signature verification.
+
 use jwt_compact::{
-    alg::{Rsa, RsaPrivateKey, RsaPublicKey},
-    jwk::{JsonWebKey, JwkError},
-    AlgorithmExt, Claims, CreationError, Header, ParseError, Token, UntrustedToken,
-    ValidationError,
+    alg::{Es256, Rsa, RsaPrivateKey, RsaPublicKey},
+    jwk::{JsonWebKey, JwkError, KeyType},
+    AlgorithmExt, CreationError, Header, ParseError, TimeOptions, UntrustedToken, ValidationError,
 };
-use serde::{Deserialize, Serialize};
+use p256::ecdsa::{SigningKey as EcdsaPrivateKey, VerifyingKey as EcdsaPublicKey};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
 use std::str::FromStr;
 use thiserror::Error;
 
+pub use jwt_compact::{Claims, Token};
+
 #[derive(Error, Debug)]
 pub enum Err {
     #[error("Failed to generate token: {0}")]
@@ -38,6 +46,12 @@ pub enum Err {
 
     #[error("Failed to parse key string: {0}")]
     KeyParseError(#[from] serde_json::Error),
+
+    #[error("Unsupported key type: {0}")]
+    UnsupportedKeyType(KeyType),
+
+    #[error("Invalid timestamp: {0}")]
+    InvalidTimestamp(i64),
 }
 
 #[derive(Deserialize, Serialize)]
@@ -45,20 +59,33 @@ pub struct CustomClaims {
     pub subject: String,
 }
 
+enum SigningKey {
+    Rs256(RsaPrivateKey),
+    Es256(EcdsaPrivateKey),
+}
+
+enum VerifyingKey {
+    Rs256(RsaPublicKey),
+    Es256(EcdsaPublicKey),
+}
+
 pub struct Issuer {
-    alg: Rsa,
-    s_k: RsaPrivateKey,
-    pub p_k: RsaPublicKey,
+    s_k: SigningKey,
 }
 
 impl Issuer {
     pub fn generate_token(&self, claims: &CustomClaims) -> Result<String, Err> {
-        let header = Header::empty();
-        let claims = Claims::new(claims);
+        self.sign(&Claims::new(claims))
+    }
 
-        self.alg
-            .token(&header, &claims, &self.s_k)
-            .map_err(Err::TokenGenerationError)
+    /// Sign arbitrary claims, using the algorithm matching the issuer key.
+    pub fn sign<T: Serialize>(&self, claims: &Claims<T>) -> Result<String, Err> {
+        let header = Header::empty();
+        match &self.s_k {
+            SigningKey::Rs256(s_k) => Rsa::rs256().token(&header, claims, s_k),
+            SigningKey::Es256(s_k) => Es256.token(&header, claims, s_k),
+        }
+        .map_err(Err::TokenGenerationError)
     }
 }
 
@@ -66,26 +93,59 @@ impl FromStr for Issuer {
     type Err = Err;
     fn from_str(jwk_str: &str) -> Result<Self, Self::Err> {
         let jwk = serde_json::from_str::<JsonWebKey>(jwk_str)?;
-        let alg = Rsa::rs256();
-        let s_k = RsaPrivateKey::try_from(&jwk)?;
-        let p_k = RsaPublicKey::try_from(&jwk)?;
-        Ok(Self { alg, s_k, p_k })
+        let s_k = match jwk.key_type() {
+            KeyType::Rsa => SigningKey::Rs256(RsaPrivateKey::try_from(&jwk)?),
+            KeyType::EllipticCurve => SigningKey::Es256(EcdsaPrivateKey::try_from(&jwk)?),
+            key_type => return Err(Err::UnsupportedKeyType(key_type)),
+        };
+        Ok(Self { s_k })
     }
 }
 
+/// Validates tokens signed by a single issuer key.
+///
+/// The signing algorithm is determined by the key: RS256 for RSA keys and
+/// ES256 for P-256 keys. Tokens whose `alg` header does not match are
+/// rejected.
 pub struct Validator {
-    alg: Rsa,
-    pub p_k: RsaPublicKey,
+    p_k: VerifyingKey,
 }
 
 impl Validator {
     pub fn validate_token_integrity(&self, token: &str) -> Result<Token<CustomClaims>, Err> {
+        self.validate(token)
+    }
+
+    /// Verify the token signature and decode its claims, without checking
+    /// the expiration or maturity of the token.
+    pub fn validate<T: DeserializeOwned>(&self, token: &str) -> Result<Token<T>, Err> {
         let token: UntrustedToken = UntrustedToken::new(token).map_err(Err::TokenParseError)?;
 
-        self.alg
-            .validator(&self.p_k)
-            .validate(&token)
-            .map_err(Err::TokenValidationError)
+        match &self.p_k {
+            VerifyingKey::Rs256(p_k) => Rsa::rs256().validator(p_k).validate(&token),
+            VerifyingKey::Es256(p_k) => Es256.validator(p_k).validate(&token),
+        }
+        .map_err(Err::TokenValidationError)
+    }
+
+    /// Verify the token signature and decode its claims, then check that the
+    /// token is valid at `timestamp`, in seconds since the UNIX epoch.
+    ///
+    /// The guest has no clock, so the time must be provided as an input.
+    pub fn validate_at<T: DeserializeOwned>(
+        &self,
+        token: &str,
+        timestamp: i64,
+    ) -> Result<Token<T>, Err> {
+        let now = chrono::DateTime::from_timestamp(timestamp, 0)
+            .ok_or(Err::InvalidTimestamp(timestamp))?;
+        let options = TimeOptions::new(chrono::Duration::zero(), move || now);
+        let token = self.validate::<T>(token)?;
+        token
+            .claims()
+            .validate_expiration(&options)?
+            .validate_maturity(&options)?;
+        Ok(token)
     }
 }
 
@@ -93,9 +153,12 @@ impl FromStr for Validator {
     type Err = Err;
     fn from_str(jwk_str: &str) -> Result<Self, Self::Err> {
         let jwk = serde_json::from_str::<JsonWebKey>(jwk_str)?;
-        let alg = Rsa::rs256();
-        let p_k = RsaPublicKey::try_from(&jwk)?;
-        Ok(Self { alg, p_k })
+        let p_k = match jwk.key_type() {
+            KeyType::Rsa => VerifyingKey::Rs256(RsaPublicKey::try_from(&jwk)?),
+            KeyType::EllipticCurve => VerifyingKey::Es256(EcdsaPublicKey::try_from(&jwk)?),
+            key_type => return Err(Err::UnsupportedKeyType(key_type)),
+        };
+        Ok(Self { p_k })
     }
 }
 
@@ -138,6 +201,25 @@ mod tests {
     }
     "#;
 
+    const ES256_SECRET_KEY: &str = r#"
+    {
+      "kty": "EC",
+      "crv": "P-256",
+      "x": "x1sx4oEiqmbF5gPkpbYwS4jQAVV7nkj7RpqNFxY5q6I",
+      "y": "2c1RDmm5BI11u9AMsfXJuCoY6ryJfq3lsdWWxWp64tI",
+      "d": "OJJ5LGctThTfdc0lAxsmy57tq-6NmT9r0k0OA7F3KKM"
+    }
+    "#;
+
+    const ES256_PUBLIC_KEY: &str = r#"
+    {
+      "kty": "EC",
+      "crv": "P-256",
+      "x": "x1sx4oEiqmbF5gPkpbYwS4jQAVV7nkj7RpqNFxY5q6I",
+      "y": "2c1RDmm5BI11u9AMsfXJuCoY6ryJfq3lsdWWxWp64tI"
+    }
+    "#;
+
     #[test]
     fn it_works() {
         let claims = CustomClaims {
@@ -173,4 +255,64 @@ mod tests {
             "Token validation should fail, but it passed."
         );
     }
+
+    #[test]
+    fn it_works_with_es256() {
+        let claims = CustomClaims {
+            subject: "Hello, world!".to_string(),
+        };
+
+        let iss = ES256_SECRET_KEY.parse::<Issuer>().unwrap();
+        let token = iss.generate_token(&claims).unwrap();
+
+        let validator = ES256_PUBLIC_KEY.parse::<Validator>().unwrap();
+        let valid_token = validator.validate_token_integrity(&token).unwrap();
+
+        assert_eq!(valid_token.claims().custom.subject, "Hello, world!");
+    }
+
+    #[test]
+    fn it_fails_on_algorithm_mismatch() {
+        let claims = CustomClaims {
+            subject: "Test Subject".to_string(),
+        };
+
+        let iss = SECRET_KEY.parse::<Issuer>().unwrap();
+        let token = iss.generate_token(&claims).unwrap();
+
+        let validator = ES256_PUBLIC_KEY.parse::<Validator>().unwrap();
+        assert!(matches!(
+            validator.validate_token_integrity(&token),
+            Err(Err::TokenValidationError(
+                ValidationError::AlgorithmMismatch { .. }
+            ))
+        ));
+    }
+
+    #[test]
+    fn it_checks_expiration() {
+        // 2024-01-01T00:00:00Z
+        let now = 1704067200;
+        let mut claims = Claims::new(CustomClaims {
+            subject: "Test Subject".to_string(),
+        });
+        claims.not_before = chrono::DateTime::from_timestamp(now - 60, 0);
+        claims.expiration = chrono::DateTime::from_timestamp(now + 60, 0);
+
+        let iss = ES256_SECRET_KEY.parse::<Issuer>().unwrap();
+        let token = iss.sign(&claims).unwrap();
+
+        let validator = ES256_PUBLIC_KEY.parse::<Validator>().unwrap();
+        let valid_token = validator.validate_at::<CustomClaims>(&token, now).unwrap();
+        assert_eq!(valid_token.claims().custom.subject, "Test Subject");
+
+        assert!(matches!(
+            validator.validate_at::<CustomClaims>(&token, now + 120),
+            Err(Err::TokenValidationError(ValidationError::Expired))
+        ));
+        assert!(matches!(
+            validator.validate_at::<CustomClaims>(&token, now - 120),
+            Err(Err::TokenValidationError(ValidationError::NotMature))
+        ));
+    }
 }
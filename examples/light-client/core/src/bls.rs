@@ -0,0 +1,87 @@
+// Copyright 2024 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! BLS signatures over BLS12-381, as used by the Ethereum consensus layer.
+//!
+//! The curve arithmetic runs in software in the zkVM, so aggregate signature
+//! verification dominates the cycle count of the light client.
+
+use bls12_381::{
+    hash_to_curve::{ExpandMsgXmd, HashToCurve},
+    multi_miller_loop, G1Affine, G1Projective, G2Affine, G2Prepared, G2Projective, Gt,
+};
+
+use crate::{BlsPublicKey, BlsSignature, Error};
+
+/// Domain separation tag of the proof-of-possession ciphersuite.
+pub const DST: &[u8] = b"BLS_SIG_BLS12381G2_XMD:SHA-256_SSWU_RO_POP_";
+
+/// Verifies an aggregate signature of `message` by all of `public_keys`,
+/// following `FastAggregateVerify` of the IETF BLS signature draft.
+pub fn fast_aggregate_verify(
+    public_keys: &[&BlsPublicKey],
+    message: &[u8],
+    signature: &BlsSignature,
+) -> Result<(), Error> {
+    if public_keys.is_empty() {
+        return Err(Error::InvalidSignature);
+    }
+    let mut aggregate = G1Projective::identity();
+    for key in public_keys {
+        let point: Option<G1Affine> = G1Affine::from_compressed(&key.0).into();
+        match point {
+            Some(point) if !bool::from(point.is_identity()) => aggregate += point,
+            _ => return Err(Error::InvalidPublicKey),
+        }
+    }
+    let signature: Option<G2Affine> = G2Affine::from_compressed(&signature.0).into();
+    let signature = signature.ok_or(Error::InvalidSignature)?;
+
+    let hash =
+        <G2Projective as HashToCurve<ExpandMsgXmd<sha2_v0_9::Sha256>>>::hash_to_curve(message, DST);
+    let result = multi_miller_loop(&[
+        (
+            &G1Affine::from(aggregate),
+            &G2Prepared::from(G2Affine::from(hash)),
+        ),
+        (&-G1Affine::generator(), &G2Prepared::from(signature)),
+    ])
+    .final_exponentiation();
+    match result == Gt::identity() {
+        true => Ok(()),
+        false => Err(Error::InvalidSignature),
+    }
+}
+
+#[cfg(any(test, feature = "mock"))]
+pub(crate) mod mock {
+    use bls12_381::Scalar;
+
+    use super::*;
+
+    /// Derives the public key of a secret key.
+    pub(crate) fn public_key(secret_key: &Scalar) -> BlsPublicKey {
+        BlsPublicKey::from(G1Affine::from(G1Affine::generator() * secret_key).to_compressed())
+    }
+
+    /// Signs `message` with the sum of the given secret keys, which yields the
+    /// aggregate of the signatures by each of them.
+    pub(crate) fn sign(secret_keys: &[Scalar], message: &[u8]) -> BlsSignature {
+        let secret_key: Scalar = secret_keys.iter().sum();
+        let hash = <G2Projective as HashToCurve<ExpandMsgXmd<sha2_v0_9::Sha256>>>::hash_to_curve(
+            message, DST,
+        );
+        BlsSignature::from(G2Affine::from(hash * secret_key).to_compressed())
+    }
+}
@@ -0,0 +1,713 @@
+// Copyright 2024 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Ethereum beacon chain light client shared between the host and the zkVM
+//! guest.
+//!
+//! A [LightClientStore] is initialized from a [LightClientBootstrap] for a
+//! trusted block root, and then follows the finalized chain by verifying
+//! [LightClientUpdate]s signed by the sync committee, as specified by the
+//! [Altair light client sync protocol]. The containers use the Altair layout
+//! and the mainnet preset, and are exchanged in their SSZ encoding.
+//!
+//! [Altair light client sync protocol]: https://github.com/ethereum/consensus-specs/blob/dev/specs/altair/light-client/sync-protocol.md
+
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+pub mod bls;
+#[cfg(any(test, feature = "mock"))]
+pub mod mock;
+pub mod ssz;
+
+use ssz::{is_valid_merkle_branch, merkleize, pack, ByteVector, Decoder, SimpleSerialize};
+
+/// SSZ `Root`, a 32 byte hash.
+pub type Root = [u8; 32];
+
+/// Compressed BLS public key.
+pub type BlsPublicKey = ByteVector<48>;
+
+/// Compressed BLS signature.
+pub type BlsSignature = ByteVector<96>;
+
+/// Fork version.
+pub type Version = ByteVector<4>;
+
+/// Number of validators in a sync committee.
+pub const SYNC_COMMITTEE_SIZE: usize = 512;
+
+/// Number of slots in an epoch.
+pub const SLOTS_PER_EPOCH: u64 = 32;
+
+/// Number of epochs a sync committee serves for.
+pub const EPOCHS_PER_SYNC_COMMITTEE_PERIOD: u64 = 256;
+
+/// Minimum number of sync committee participants for an update to be valid.
+pub const MIN_SYNC_COMMITTEE_PARTICIPANTS: usize = 1;
+
+/// Domain type of the sync committee signatures.
+pub const DOMAIN_SYNC_COMMITTEE: [u8; 4] = [7, 0, 0, 0];
+
+// Positions of the finalized block root and of the sync committees in the
+// beacon state, as depth and index of their generalized indices 105, 54 and 55.
+const FINALIZED_ROOT_DEPTH: usize = 6;
+const FINALIZED_ROOT_INDEX: u64 = 41;
+const SYNC_COMMITTEE_DEPTH: usize = 5;
+const CURRENT_SYNC_COMMITTEE_INDEX: u64 = 22;
+const NEXT_SYNC_COMMITTEE_INDEX: u64 = 23;
+
+/// Error type for the light client.
+#[derive(Error, Debug, PartialEq)]
+pub enum Error {
+    #[error("invalid SSZ length: expected {expected} bytes, got {actual}")]
+    InvalidLength { expected: usize, actual: usize },
+    #[error("bootstrap header does not match the trusted block root")]
+    UntrustedBootstrap,
+    #[error("invalid sync committee branch")]
+    InvalidSyncCommitteeBranch,
+    #[error("invalid finality branch")]
+    InvalidFinalityBranch,
+    #[error("conflicting next sync committee")]
+    ConflictingSyncCommittee,
+    #[error("not enough sync committee participants")]
+    InsufficientParticipants,
+    #[error("invalid update slots")]
+    InvalidSlots,
+    #[error("update signed outside of the known sync committee periods")]
+    UnknownSyncCommittee,
+    #[error("update does not advance the light client")]
+    IrrelevantUpdate,
+    #[error("invalid BLS public key")]
+    InvalidPublicKey,
+    #[error("invalid sync committee signature")]
+    InvalidSignature,
+}
+
+/// Returns the sync committee period of a slot.
+pub fn sync_committee_period(slot: u64) -> u64 {
+    slot / SLOTS_PER_EPOCH / EPOCHS_PER_SYNC_COMMITTEE_PERIOD
+}
+
+/// Header of a beacon block.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BeaconBlockHeader {
+    pub slot: u64,
+    pub proposer_index: u64,
+    pub parent_root: Root,
+    pub state_root: Root,
+    pub body_root: Root,
+}
+
+impl SimpleSerialize for BeaconBlockHeader {
+    const SIZE: usize = 8 + 8 + 32 + 32 + 32;
+
+    fn encode(&self, out: &mut Vec<u8>) {
+        self.slot.encode(out);
+        self.proposer_index.encode(out);
+        self.parent_root.encode(out);
+        self.state_root.encode(out);
+        self.body_root.encode(out);
+    }
+
+    fn decode(bytes: &[u8]) -> Result<Self, Error> {
+        let mut decoder = Decoder::new::<Self>(bytes)?;
+        Ok(Self {
+            slot: decoder.field()?,
+            proposer_index: decoder.field()?,
+            parent_root: decoder.field()?,
+            state_root: decoder.field()?,
+            body_root: decoder.field()?,
+        })
+    }
+
+    fn hash_tree_root(&self) -> Root {
+        let fields = vec![
+            self.slot.hash_tree_root(),
+            self.proposer_index.hash_tree_root(),
+            self.parent_root,
+            self.state_root,
+            self.body_root,
+        ];
+        merkleize(fields, 5)
+    }
+}
+
+/// Public keys of a sync committee.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SyncCommittee {
+    pub pubkeys: Box<[BlsPublicKey; SYNC_COMMITTEE_SIZE]>,
+    pub aggregate_pubkey: BlsPublicKey,
+}
+
+impl SimpleSerialize for SyncCommittee {
+    const SIZE: usize = <[BlsPublicKey; SYNC_COMMITTEE_SIZE]>::SIZE + BlsPublicKey::SIZE;
+
+    fn encode(&self, out: &mut Vec<u8>) {
+        self.pubkeys.encode(out);
+        self.aggregate_pubkey.encode(out);
+    }
+
+    fn decode(bytes: &[u8]) -> Result<Self, Error> {
+        let mut decoder = Decoder::new::<Self>(bytes)?;
+        Ok(Self {
+            pubkeys: Box::new(decoder.field()?),
+            aggregate_pubkey: decoder.field()?,
+        })
+    }
+
+    fn hash_tree_root(&self) -> Root {
+        let fields = vec![
+            self.pubkeys.hash_tree_root(),
+            self.aggregate_pubkey.hash_tree_root(),
+        ];
+        merkleize(fields, 2)
+    }
+}
+
+/// Participation bits of the sync committee, as an SSZ
+/// `Bitvector[SYNC_COMMITTEE_SIZE]`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SyncCommitteeBits(pub [u8; SYNC_COMMITTEE_SIZE / 8]);
+
+impl SyncCommitteeBits {
+    /// Returns whether the member at `index` took part in the signature.
+    pub fn get(&self, index: usize) -> bool {
+        self.0[index / 8] >> (index % 8) & 1 == 1
+    }
+
+    /// Marks the member at `index` as participating.
+    pub fn set(&mut self, index: usize) {
+        self.0[index / 8] |= 1 << (index % 8);
+    }
+
+    /// Returns the number of participants.
+    pub fn count(&self) -> usize {
+        self.0.iter().map(|byte| byte.count_ones() as usize).sum()
+    }
+}
+
+impl Default for SyncCommitteeBits {
+    fn default() -> Self {
+        Self([0; SYNC_COMMITTEE_SIZE / 8])
+    }
+}
+
+impl SimpleSerialize for SyncCommitteeBits {
+    const SIZE: usize = SYNC_COMMITTEE_SIZE / 8;
+
+    fn encode(&self, out: &mut Vec<u8>) {
+        out.extend(self.0);
+    }
+
+    fn decode(bytes: &[u8]) -> Result<Self, Error> {
+        let bytes: ByteVector<{ SYNC_COMMITTEE_SIZE / 8 }> = SimpleSerialize::decode(bytes)?;
+        Ok(Self(bytes.0))
+    }
+
+    fn hash_tree_root(&self) -> Root {
+        merkleize(pack(&self.0), SYNC_COMMITTEE_SIZE.div_ceil(256))
+    }
+}
+
+/// Sync committee signature of a block.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct SyncAggregate {
+    pub sync_committee_bits: SyncCommitteeBits,
+    pub sync_committee_signature: BlsSignature,
+}
+
+impl SimpleSerialize for SyncAggregate {
+    const SIZE: usize = SyncCommitteeBits::SIZE + BlsSignature::SIZE;
+
+    fn encode(&self, out: &mut Vec<u8>) {
+        self.sync_committee_bits.encode(out);
+        self.sync_committee_signature.encode(out);
+    }
+
+    fn decode(bytes: &[u8]) -> Result<Self, Error> {
+        let mut decoder = Decoder::new::<Self>(bytes)?;
+        Ok(Self {
+            sync_committee_bits: decoder.field()?,
+            sync_committee_signature: decoder.field()?,
+        })
+    }
+
+    fn hash_tree_root(&self) -> Root {
+        let fields = vec![
+            self.sync_committee_bits.hash_tree_root(),
+            self.sync_committee_signature.hash_tree_root(),
+        ];
+        merkleize(fields, 2)
+    }
+}
+
+/// Fork data, from which the signature domains are derived.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ForkData {
+    pub current_version: Version,
+    pub genesis_validators_root: Root,
+}
+
+impl ForkData {
+    /// Computes the signature domain of the given type, following
+    /// `compute_domain`.
+    pub fn domain(&self, domain_type: [u8; 4]) -> Root {
+        let mut domain = Root::default();
+        domain[..4].copy_from_slice(&domain_type);
+        domain[4..].copy_from_slice(&self.hash_tree_root()[..28]);
+        domain
+    }
+
+    /// Computes the root signed for `object_root` in the given domain,
+    /// following `compute_signing_root`.
+    pub fn signing_root(&self, object_root: &Root, domain_type: [u8; 4]) -> Root {
+        merkleize(vec![*object_root, self.domain(domain_type)], 2)
+    }
+}
+
+impl SimpleSerialize for ForkData {
+    const SIZE: usize = Version::SIZE + 32;
+
+    fn encode(&self, out: &mut Vec<u8>) {
+        self.current_version.encode(out);
+        self.genesis_validators_root.encode(out);
+    }
+
+    fn decode(bytes: &[u8]) -> Result<Self, Error> {
+        let mut decoder = Decoder::new::<Self>(bytes)?;
+        Ok(Self {
+            current_version: decoder.field()?,
+            genesis_validators_root: decoder.field()?,
+        })
+    }
+
+    fn hash_tree_root(&self) -> Root {
+        let fields = vec![
+            self.current_version.hash_tree_root(),
+            self.genesis_validators_root,
+        ];
+        merkleize(fields, 2)
+    }
+}
+
+/// Header and current sync committee of a trusted block, used to initialize
+/// a [LightClientStore].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LightClientBootstrap {
+    pub header: BeaconBlockHeader,
+    pub current_sync_committee: SyncCommittee,
+    pub current_sync_committee_branch: [Root; SYNC_COMMITTEE_DEPTH],
+}
+
+impl SimpleSerialize for LightClientBootstrap {
+    const SIZE: usize =
+        BeaconBlockHeader::SIZE + SyncCommittee::SIZE + <[Root; SYNC_COMMITTEE_DEPTH]>::SIZE;
+
+    fn encode(&self, out: &mut Vec<u8>) {
+        self.header.encode(out);
+        self.current_sync_committee.encode(out);
+        self.current_sync_committee_branch.encode(out);
+    }
+
+    fn decode(bytes: &[u8]) -> Result<Self, Error> {
+        let mut decoder = Decoder::new::<Self>(bytes)?;
+        Ok(Self {
+            header: decoder.field()?,
+            current_sync_committee: decoder.field()?,
+            current_sync_committee_branch: decoder.field()?,
+        })
+    }
+
+    fn hash_tree_root(&self) -> Root {
+        let fields = vec![
+            self.header.hash_tree_root(),
+            self.current_sync_committee.hash_tree_root(),
+            self.current_sync_committee_branch.hash_tree_root(),
+        ];
+        merkleize(fields, 3)
+    }
+}
+
+/// Update of a light client, signed by the sync committee.
+///
+/// The next sync committee is optional: an update without it has a zero
+/// `next_sync_committee_branch`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LightClientUpdate {
+    pub attested_header: BeaconBlockHeader,
+    pub next_sync_committee: SyncCommittee,
+    pub next_sync_committee_branch: [Root; SYNC_COMMITTEE_DEPTH],
+    pub finalized_header: BeaconBlockHeader,
+    pub finality_branch: [Root; FINALIZED_ROOT_DEPTH],
+    pub sync_aggregate: SyncAggregate,
+    pub signature_slot: u64,
+}
+
+impl LightClientUpdate {
+    /// Returns whether the update carries the next sync committee.
+    pub fn is_sync_committee_update(&self) -> bool {
+        self.next_sync_committee_branch != [Root::default(); SYNC_COMMITTEE_DEPTH]
+    }
+}
+
+impl SimpleSerialize for LightClientUpdate {
+    const SIZE: usize = BeaconBlockHeader::SIZE * 2
+        + SyncCommittee::SIZE
+        + <[Root; SYNC_COMMITTEE_DEPTH]>::SIZE
+        + <[Root; FINALIZED_ROOT_DEPTH]>::SIZE
+        + SyncAggregate::SIZE
+        + 8;
+
+    fn encode(&self, out: &mut Vec<u8>) {
+        self.attested_header.encode(out);
+        self.next_sync_committee.encode(out);
+        self.next_sync_committee_branch.encode(out);
+        self.finalized_header.encode(out);
+        self.finality_branch.encode(out);
+        self.sync_aggregate.encode(out);
+        self.signature_slot.encode(out);
+    }
+
+    fn decode(bytes: &[u8]) -> Result<Self, Error> {
+        let mut decoder = Decoder::new::<Self>(bytes)?;
+        Ok(Self {
+            attested_header: decoder.field()?,
+            next_sync_committee: decoder.field()?,
+            next_sync_committee_branch: decoder.field()?,
+            finalized_header: decoder.field()?,
+            finality_branch: decoder.field()?,
+            sync_aggregate: decoder.field()?,
+            signature_slot: decoder.field()?,
+        })
+    }
+
+    fn hash_tree_root(&self) -> Root {
+        let fields = vec![
+            self.attested_header.hash_tree_root(),
+            self.next_sync_committee.hash_tree_root(),
+            self.next_sync_committee_branch.hash_tree_root(),
+            self.finalized_header.hash_tree_root(),
+            self.finality_branch.hash_tree_root(),
+            self.sync_aggregate.hash_tree_root(),
+            self.signature_slot.hash_tree_root(),
+        ];
+        merkleize(fields, 7)
+    }
+}
+
+/// State of a light client following the finalized chain.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LightClientStore {
+    pub finalized_header: BeaconBlockHeader,
+    pub current_sync_committee: SyncCommittee,
+    pub next_sync_committee: Option<SyncCommittee>,
+}
+
+impl LightClientStore {
+    /// Initializes a store from the bootstrap of a trusted block.
+    pub fn bootstrap(
+        trusted_block_root: &Root,
+        bootstrap: LightClientBootstrap,
+    ) -> Result<Self, Error> {
+        if bootstrap.header.hash_tree_root() != *trusted_block_root {
+            return Err(Error::UntrustedBootstrap);
+        }
+        if !is_valid_merkle_branch(
+            &bootstrap.current_sync_committee.hash_tree_root(),
+            &bootstrap.current_sync_committee_branch,
+            SYNC_COMMITTEE_DEPTH,
+            CURRENT_SYNC_COMMITTEE_INDEX,
+            &bootstrap.header.state_root,
+        ) {
+            return Err(Error::InvalidSyncCommitteeBranch);
+        }
+        Ok(Self {
+            finalized_header: bootstrap.header,
+            current_sync_committee: bootstrap.current_sync_committee,
+            next_sync_committee: None,
+        })
+    }
+
+    /// Validates an update, following `validate_light_client_update`.
+    ///
+    /// The update must prove finality. The fork version is the one in effect
+    /// at the epoch preceding `update.signature_slot`.
+    pub fn validate_update(
+        &self,
+        update: &LightClientUpdate,
+        genesis_validators_root: &Root,
+        fork_version: &Version,
+    ) -> Result<(), Error> {
+        let sync_aggregate = &update.sync_aggregate;
+        if sync_aggregate.sync_committee_bits.count() < MIN_SYNC_COMMITTEE_PARTICIPANTS {
+            return Err(Error::InsufficientParticipants);
+        }
+
+        let attested_slot = update.attested_header.slot;
+        if update.signature_slot <= attested_slot || attested_slot < update.finalized_header.slot {
+            return Err(Error::InvalidSlots);
+        }
+        let store_period = sync_committee_period(self.finalized_header.slot);
+        let signature_period = sync_committee_period(update.signature_slot);
+        let next_period_known = self.next_sync_committee.is_some();
+        if signature_period != store_period
+            && !(next_period_known && signature_period == store_period + 1)
+        {
+            return Err(Error::UnknownSyncCommittee);
+        }
+
+        // The update must either finalize a newer block, or provide the next
+        // sync committee while it is unknown.
+        let attested_period = sync_committee_period(attested_slot);
+        let provides_next_committee = update.is_sync_committee_update()
+            && !next_period_known
+            && attested_period == store_period;
+        if update.finalized_header.slot <= self.finalized_header.slot && !provides_next_committee {
+            return Err(Error::IrrelevantUpdate);
+        }
+
+        if !is_valid_merkle_branch(
+            &update.finalized_header.hash_tree_root(),
+            &update.finality_branch,
+            FINALIZED_ROOT_DEPTH,
+            FINALIZED_ROOT_INDEX,
+            &update.attested_header.state_root,
+        ) {
+            return Err(Error::InvalidFinalityBranch);
+        }
+
+        if update.is_sync_committee_update() {
+            if attested_period == store_period
+                && self
+                    .next_sync_committee
+                    .as_ref()
+                    .is_some_and(|next| *next != update.next_sync_committee)
+            {
+                return Err(Error::ConflictingSyncCommittee);
+            }
+            if !is_valid_merkle_branch(
+                &update.next_sync_committee.hash_tree_root(),
+                &update.next_sync_committee_branch,
+                SYNC_COMMITTEE_DEPTH,
+                NEXT_SYNC_COMMITTEE_INDEX,
+                &update.attested_header.state_root,
+            ) {
+                return Err(Error::InvalidSyncCommitteeBranch);
+            }
+        }
+
+        let sync_committee = match signature_period == store_period {
+            true => &self.current_sync_committee,
+            false => self.next_sync_committee.as_ref().unwrap(),
+        };
+        let participants: Vec<&BlsPublicKey> = sync_committee
+            .pubkeys
+            .iter()
+            .enumerate()
+            .filter(|(i, _)| sync_aggregate.sync_committee_bits.get(*i))
+            .map(|(_, key)| key)
+            .collect();
+        let fork_data = ForkData {
+            current_version: *fork_version,
+            genesis_validators_root: *genesis_validators_root,
+        };
+        let signing_root = fork_data.signing_root(
+            &update.attested_header.hash_tree_root(),
+            DOMAIN_SYNC_COMMITTEE,
+        );
+        bls::fast_aggregate_verify(
+            &participants,
+            &signing_root,
+            &sync_aggregate.sync_committee_signature,
+        )
+    }
+
+    /// Validates and applies an update.
+    ///
+    /// Unlike `process_light_client_update`, the store does not keep track of
+    /// the best update seen so far: the update is rejected unless it is
+    /// signed by a supermajority of the sync committee.
+    pub fn process_update(
+        &mut self,
+        update: LightClientUpdate,
+        genesis_validators_root: &Root,
+        fork_version: &Version,
+    ) -> Result<(), Error> {
+        self.validate_update(&update, genesis_validators_root, fork_version)?;
+        let participants = update.sync_aggregate.sync_committee_bits.count();
+        if participants * 3 < SYNC_COMMITTEE_SIZE * 2 {
+            return Err(Error::InsufficientParticipants);
+        }
+
+        let store_period = sync_committee_period(self.finalized_header.slot);
+        let finalized_period = sync_committee_period(update.finalized_header.slot);
+        let is_sync_committee_update = update.is_sync_committee_update();
+        let next_sync_committee = is_sync_committee_update.then_some(update.next_sync_committee);
+        if self.next_sync_committee.is_none() {
+            if finalized_period != store_period {
+                return Err(Error::UnknownSyncCommittee);
+            }
+            self.next_sync_committee = next_sync_committee;
+        } else if finalized_period == store_period + 1 {
+            self.current_sync_committee = self.next_sync_committee.take().unwrap();
+            self.next_sync_committee = next_sync_committee;
+        }
+        if update.finalized_header.slot > self.finalized_header.slot {
+            self.finalized_header = update.finalized_header;
+        }
+        Ok(())
+    }
+}
+
+/// Input of the guest, with the containers in their SSZ encoding.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Input {
+    pub genesis_validators_root: Root,
+    pub fork_version: [u8; 4],
+    pub trusted_block_root: Root,
+    pub bootstrap: Vec<u8>,
+    pub updates: Vec<Vec<u8>>,
+}
+
+/// Output of the guest: the latest finalized header, reached from the
+/// trusted block root.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Journal {
+    pub genesis_validators_root: Root,
+    pub trusted_block_root: Root,
+    pub finalized_header: BeaconBlockHeader,
+}
+
+impl Input {
+    /// Runs the light client over the updates of the input.
+    pub fn process(&self) -> Result<Journal, Error> {
+        let bootstrap = LightClientBootstrap::decode(&self.bootstrap)?;
+        let mut store = LightClientStore::bootstrap(&self.trusted_block_root, bootstrap)?;
+        let fork_version = ByteVector(self.fork_version);
+        for update in &self.updates {
+            let update = LightClientUpdate::decode(update)?;
+            store.process_update(update, &self.genesis_validators_root, &fork_version)?;
+        }
+        Ok(Journal {
+            genesis_validators_root: self.genesis_validators_root,
+            trusted_block_root: self.trusted_block_root,
+            finalized_header: store.finalized_header,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock::MockChain;
+
+    #[test]
+    fn header_roundtrip() {
+        let header = BeaconBlockHeader {
+            slot: 1,
+            proposer_index: 2,
+            parent_root: [3; 32],
+            state_root: [4; 32],
+            body_root: [5; 32],
+        };
+        let encoded = header.to_ssz();
+        assert_eq!(encoded.len(), 112);
+        assert_eq!(&encoded[..8], &[1, 0, 0, 0, 0, 0, 0, 0]);
+        assert_eq!(BeaconBlockHeader::decode(&encoded).unwrap(), header);
+        assert_eq!(
+            BeaconBlockHeader::decode(&encoded[1..]),
+            Err(Error::InvalidLength {
+                expected: 112,
+                actual: 111
+            })
+        );
+    }
+
+    #[test]
+    fn follow_chain() {
+        let chain = MockChain::new();
+        let start = 3 * EPOCHS_PER_SYNC_COMMITTEE_PERIOD * SLOTS_PER_EPOCH + 100;
+        let (trusted_block_root, bootstrap) = chain.bootstrap(start);
+        let encoded = bootstrap.to_ssz();
+        assert_eq!(LightClientBootstrap::decode(&encoded).unwrap(), bootstrap);
+        let mut store = LightClientStore::bootstrap(&trusted_block_root, bootstrap).unwrap();
+
+        // Finalize a block of the same period and learn the next committee.
+        let update = chain.update(start + 96, start + 32, 400);
+        let encoded = update.to_ssz();
+        assert_eq!(LightClientUpdate::decode(&encoded).unwrap(), update);
+        store
+            .process_update(update, &chain.genesis_validators_root, &chain.fork_version)
+            .unwrap();
+        assert_eq!(store.finalized_header, chain.header(start + 32, None));
+
+        // Cross into the next period.
+        let period_start = 4 * EPOCHS_PER_SYNC_COMMITTEE_PERIOD * SLOTS_PER_EPOCH;
+        let update = chain.update(period_start + 64, period_start, 512);
+        store
+            .process_update(update, &chain.genesis_validators_root, &chain.fork_version)
+            .unwrap();
+        assert_eq!(store.finalized_header, chain.header(period_start, None));
+        assert_eq!(store.current_sync_committee, chain.committee(4).0);
+        assert_eq!(store.next_sync_committee, Some(chain.committee(5).0));
+    }
+
+    #[test]
+    fn reject_invalid_updates() {
+        let chain = MockChain::new();
+        let start = 3 * EPOCHS_PER_SYNC_COMMITTEE_PERIOD * SLOTS_PER_EPOCH + 100;
+        let (trusted_block_root, bootstrap) = chain.bootstrap(start);
+        assert_eq!(
+            LightClientStore::bootstrap(&[0; 32], bootstrap.clone()),
+            Err(Error::UntrustedBootstrap)
+        );
+        let store = LightClientStore::bootstrap(&trusted_block_root, bootstrap).unwrap();
+        let validate = |update: &LightClientUpdate| {
+            store.validate_update(update, &chain.genesis_validators_root, &chain.fork_version)
+        };
+        let update = chain.update(start + 96, start + 32, 400);
+        validate(&update).unwrap();
+
+        let mut tampered = update.clone();
+        tampered.finalized_header.body_root[0] ^= 1;
+        assert_eq!(validate(&tampered), Err(Error::InvalidFinalityBranch));
+
+        let mut tampered = update.clone();
+        tampered.next_sync_committee.aggregate_pubkey.0[0] ^= 1;
+        assert_eq!(validate(&tampered), Err(Error::InvalidSyncCommitteeBranch));
+
+        let mut tampered = update.clone();
+        tampered.sync_aggregate.sync_committee_bits.0[0] ^= 1;
+        assert_eq!(validate(&tampered), Err(Error::InvalidSignature));
+
+        let wrong_fork = ByteVector([1, 0, 0, 0]);
+        assert_eq!(
+            store.validate_update(&update, &chain.genesis_validators_root, &wrong_fork),
+            Err(Error::InvalidSignature)
+        );
+
+        let update = chain.update(start + 96, start + 32, 0);
+        assert_eq!(validate(&update), Err(Error::InsufficientParticipants));
+
+        // Signed by a minority, the update is valid but not applied.
+        let update = chain.update(start + 96, start + 32, 300);
+        validate(&update).unwrap();
+        let mut store = store.clone();
+        assert_eq!(
+            store.process_update(update, &chain.genesis_validators_root, &chain.fork_version),
+            Err(Error::InsufficientParticipants)
+        );
+    }
+}
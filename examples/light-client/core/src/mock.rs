@@ -0,0 +1,175 @@
+// Copyright 2024 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Mock beacon chain producing bootstraps and signed updates, for tests and
+//! demonstrations without a beacon node.
+//!
+//! The secret keys of the sync committees are derived from their period, so
+//! the chain must never be used outside of tests.
+
+use std::{cell::RefCell, collections::BTreeMap};
+
+use bls12_381::Scalar;
+
+use crate::{
+    bls::mock::{public_key, sign},
+    ssz::{hash_pair, merkleize, ByteVector, SimpleSerialize},
+    sync_committee_period, BeaconBlockHeader, BlsPublicKey, ForkData, LightClientBootstrap,
+    LightClientUpdate, Root, SyncAggregate, SyncCommittee, SyncCommitteeBits, Version,
+    DOMAIN_SYNC_COMMITTEE, SLOTS_PER_EPOCH, SYNC_COMMITTEE_SIZE,
+};
+
+// Indices of the fields of the beacon state used by the light client.
+const FINALIZED_CHECKPOINT: usize = 20;
+const CURRENT_SYNC_COMMITTEE: usize = 22;
+const NEXT_SYNC_COMMITTEE: usize = 23;
+
+/// Mock beacon chain, with a block at every slot.
+pub struct MockChain {
+    pub genesis_validators_root: Root,
+    pub fork_version: Version,
+    committees: RefCell<BTreeMap<u64, (SyncCommittee, Vec<Scalar>)>>,
+}
+
+impl Default for MockChain {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl MockChain {
+    pub fn new() -> Self {
+        Self {
+            genesis_validators_root: [0x4b; 32],
+            fork_version: ByteVector([4, 0, 0, 0]),
+            committees: RefCell::default(),
+        }
+    }
+
+    /// Returns the sync committee of a period, along with its secret keys.
+    pub fn committee(&self, period: u64) -> (SyncCommittee, Vec<Scalar>) {
+        self.committees
+            .borrow_mut()
+            .entry(period)
+            .or_insert_with(|| {
+                let secret_keys: Vec<Scalar> = (0..SYNC_COMMITTEE_SIZE as u64)
+                    .map(|i| Scalar::from(period * SYNC_COMMITTEE_SIZE as u64 + i + 1))
+                    .collect();
+                let pubkeys: Vec<BlsPublicKey> = secret_keys.iter().map(public_key).collect();
+                let committee = SyncCommittee {
+                    pubkeys: Box::new(pubkeys.try_into().unwrap()),
+                    aggregate_pubkey: public_key(&secret_keys.iter().sum()),
+                };
+                (committee, secret_keys)
+            })
+            .clone()
+    }
+
+    /// Returns the block header at `slot`, whose state finalizes `finalized`.
+    pub fn header(&self, slot: u64, finalized: Option<&BeaconBlockHeader>) -> BeaconBlockHeader {
+        BeaconBlockHeader {
+            slot,
+            proposer_index: slot % 1000,
+            parent_root: (slot - 1).hash_tree_root(),
+            state_root: merkleize(self.state(slot, finalized).to_vec(), 32),
+            body_root: hash_pair(&slot.hash_tree_root(), &[0xbb; 32]),
+        }
+    }
+
+    /// Returns the root and the bootstrap of the block at `slot`.
+    pub fn bootstrap(&self, slot: u64) -> (Root, LightClientBootstrap) {
+        let header = self.header(slot, None);
+        let bootstrap = LightClientBootstrap {
+            header,
+            current_sync_committee: self.committee(sync_committee_period(slot)).0,
+            current_sync_committee_branch: branch(&self.state(slot, None), CURRENT_SYNC_COMMITTEE)
+                .try_into()
+                .unwrap(),
+        };
+        (header.hash_tree_root(), bootstrap)
+    }
+
+    /// Returns an update attesting the block at `attested_slot`, which
+    /// finalizes the block at `finalized_slot`, signed at the next slot by
+    /// the first `participants` members of the sync committee.
+    pub fn update(
+        &self,
+        attested_slot: u64,
+        finalized_slot: u64,
+        participants: usize,
+    ) -> LightClientUpdate {
+        let finalized_header = self.header(finalized_slot, None);
+        let attested_header = self.header(attested_slot, Some(&finalized_header));
+        let state = self.state(attested_slot, Some(&finalized_header));
+        let mut finality_branch = vec![(finalized_slot / SLOTS_PER_EPOCH).hash_tree_root()];
+        finality_branch.extend(branch(&state, FINALIZED_CHECKPOINT));
+
+        let signature_slot = attested_slot + 1;
+        let (_, secret_keys) = self.committee(sync_committee_period(signature_slot));
+        let mut sync_committee_bits = SyncCommitteeBits::default();
+        for i in 0..participants {
+            sync_committee_bits.set(i);
+        }
+        let fork_data = ForkData {
+            current_version: self.fork_version,
+            genesis_validators_root: self.genesis_validators_root,
+        };
+        let signing_root =
+            fork_data.signing_root(&attested_header.hash_tree_root(), DOMAIN_SYNC_COMMITTEE);
+
+        LightClientUpdate {
+            attested_header,
+            next_sync_committee: self.committee(sync_committee_period(attested_slot) + 1).0,
+            next_sync_committee_branch: branch(&state, NEXT_SYNC_COMMITTEE).try_into().unwrap(),
+            finalized_header,
+            finality_branch: finality_branch.try_into().unwrap(),
+            sync_aggregate: SyncAggregate {
+                sync_committee_bits,
+                sync_committee_signature: sign(&secret_keys[..participants], &signing_root),
+            },
+            signature_slot,
+        }
+    }
+
+    // Returns the roots of the fields of the beacon state at `slot`, leaving
+    // the fields not used by the light client empty but for the slot.
+    fn state(&self, slot: u64, finalized: Option<&BeaconBlockHeader>) -> [Root; 32] {
+        let mut fields = [Root::default(); 32];
+        fields[2] = slot.hash_tree_root();
+        if let Some(finalized) = finalized {
+            let epoch = finalized.slot / SLOTS_PER_EPOCH;
+            fields[FINALIZED_CHECKPOINT] =
+                hash_pair(&epoch.hash_tree_root(), &finalized.hash_tree_root());
+        }
+        let period = sync_committee_period(slot);
+        fields[CURRENT_SYNC_COMMITTEE] = self.committee(period).0.hash_tree_root();
+        fields[NEXT_SYNC_COMMITTEE] = self.committee(period + 1).0.hash_tree_root();
+        fields
+    }
+}
+
+// Computes the Merkle branch of the leaf at `index`.
+fn branch(leaves: &[Root], mut index: usize) -> Vec<Root> {
+    let mut layer = leaves.to_vec();
+    let mut branch = Vec::new();
+    while layer.len() > 1 {
+        branch.push(layer[index ^ 1]);
+        layer = layer
+            .chunks_exact(2)
+            .map(|pair| hash_pair(&pair[0], &pair[1]))
+            .collect();
+        index /= 2;
+    }
+    branch
+}
@@ -0,0 +1,304 @@
+// Copyright 2024 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! SimpleSerialize (SSZ) encoding and Merkleization.
+//!
+//! Only fixed-size types are supported, which covers all the containers of
+//! the light client protocol. A container is serialized as the concatenation
+//! of its fields, and its root is the Merkle root of the roots of its fields.
+
+use sha2::{Digest, Sha256};
+
+use crate::{Error, Root};
+
+/// A type with a fixed-size SSZ encoding.
+pub trait SimpleSerialize: Sized {
+    /// Length of the SSZ encoding.
+    const SIZE: usize;
+
+    /// Whether the type is an SSZ basic type, which is packed into chunks
+    /// when used as the element of a vector.
+    const BASIC: bool = false;
+
+    /// Appends the SSZ encoding of the value to `out`.
+    fn encode(&self, out: &mut Vec<u8>);
+
+    /// Decodes a value from its SSZ encoding, which must be exactly
+    /// [Self::SIZE] bytes long.
+    fn decode(bytes: &[u8]) -> Result<Self, Error>;
+
+    /// Computes the `hash_tree_root` of the value.
+    fn hash_tree_root(&self) -> Root;
+
+    /// Returns the SSZ encoding of the value.
+    fn to_ssz(&self) -> Vec<u8> {
+        let mut out = Vec::with_capacity(Self::SIZE);
+        self.encode(&mut out);
+        out
+    }
+}
+
+/// Reads the fields of a container from its SSZ encoding.
+pub(crate) struct Decoder<'a>(&'a [u8]);
+
+impl<'a> Decoder<'a> {
+    /// Starts decoding `bytes` as a value of type `T`.
+    pub(crate) fn new<T: SimpleSerialize>(bytes: &'a [u8]) -> Result<Self, Error> {
+        check_len::<T>(bytes)?;
+        Ok(Self(bytes))
+    }
+
+    /// Decodes the next field.
+    pub(crate) fn field<T: SimpleSerialize>(&mut self) -> Result<T, Error> {
+        let (head, tail) = self.0.split_at(T::SIZE);
+        self.0 = tail;
+        T::decode(head)
+    }
+}
+
+fn check_len<T: SimpleSerialize>(bytes: &[u8]) -> Result<(), Error> {
+    match bytes.len() == T::SIZE {
+        true => Ok(()),
+        false => Err(Error::InvalidLength {
+            expected: T::SIZE,
+            actual: bytes.len(),
+        }),
+    }
+}
+
+/// Hashes two nodes of a Merkle tree.
+pub fn hash_pair(left: &Root, right: &Root) -> Root {
+    let mut hasher = Sha256::new();
+    hasher.update(left);
+    hasher.update(right);
+    hasher.finalize().into()
+}
+
+/// Packs serialized basic values into 32 byte chunks, padding the last chunk
+/// with zeros.
+pub fn pack(bytes: &[u8]) -> Vec<Root> {
+    bytes
+        .chunks(32)
+        .map(|chunk| {
+            let mut root = Root::default();
+            root[..chunk.len()].copy_from_slice(chunk);
+            root
+        })
+        .collect()
+}
+
+/// Computes the Merkle root of `chunks`, padded with zero chunks up to the
+/// next power of two of `limit`.
+pub fn merkleize(mut chunks: Vec<Root>, limit: usize) -> Root {
+    assert!(chunks.len() <= limit.max(1), "too many chunks");
+    let depth = limit.next_power_of_two().trailing_zeros();
+    let mut zero = Root::default();
+    for _ in 0..depth {
+        if chunks.len() % 2 == 1 {
+            chunks.push(zero);
+        }
+        chunks = chunks
+            .chunks_exact(2)
+            .map(|pair| hash_pair(&pair[0], &pair[1]))
+            .collect();
+        zero = hash_pair(&zero, &zero);
+    }
+    chunks.first().copied().unwrap_or(zero)
+}
+
+/// Checks that `leaf` is at position `index` of a tree of the given `depth`
+/// with the given `root`, where `branch` lists the sibling nodes from the
+/// bottom up.
+pub fn is_valid_merkle_branch(
+    leaf: &Root,
+    branch: &[Root],
+    depth: usize,
+    index: u64,
+    root: &Root,
+) -> bool {
+    if branch.len() != depth {
+        return false;
+    }
+    let mut value = *leaf;
+    for (i, sibling) in branch.iter().enumerate() {
+        value = match (index >> i) & 1 {
+            1 => hash_pair(sibling, &value),
+            _ => hash_pair(&value, sibling),
+        };
+    }
+    value == *root
+}
+
+impl SimpleSerialize for u64 {
+    const SIZE: usize = 8;
+    const BASIC: bool = true;
+
+    fn encode(&self, out: &mut Vec<u8>) {
+        out.extend(self.to_le_bytes());
+    }
+
+    fn decode(bytes: &[u8]) -> Result<Self, Error> {
+        check_len::<Self>(bytes)?;
+        Ok(u64::from_le_bytes(bytes.try_into().unwrap()))
+    }
+
+    fn hash_tree_root(&self) -> Root {
+        pack(&self.to_le_bytes())[0]
+    }
+}
+
+impl SimpleSerialize for Root {
+    const SIZE: usize = 32;
+
+    fn encode(&self, out: &mut Vec<u8>) {
+        out.extend(self);
+    }
+
+    fn decode(bytes: &[u8]) -> Result<Self, Error> {
+        check_len::<Self>(bytes)?;
+        Ok(bytes.try_into().unwrap())
+    }
+
+    fn hash_tree_root(&self) -> Root {
+        *self
+    }
+}
+
+/// SSZ `ByteVector[N]`, used for keys, signatures and fork versions.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ByteVector<const N: usize>(pub [u8; N]);
+
+impl<const N: usize> Default for ByteVector<N> {
+    fn default() -> Self {
+        Self([0; N])
+    }
+}
+
+impl<const N: usize> From<[u8; N]> for ByteVector<N> {
+    fn from(bytes: [u8; N]) -> Self {
+        Self(bytes)
+    }
+}
+
+impl<const N: usize> SimpleSerialize for ByteVector<N> {
+    const SIZE: usize = N;
+
+    fn encode(&self, out: &mut Vec<u8>) {
+        out.extend(self.0);
+    }
+
+    fn decode(bytes: &[u8]) -> Result<Self, Error> {
+        check_len::<Self>(bytes)?;
+        Ok(Self(bytes.try_into().unwrap()))
+    }
+
+    fn hash_tree_root(&self) -> Root {
+        merkleize(pack(&self.0), N.div_ceil(32))
+    }
+}
+
+/// SSZ `Vector[T, N]`.
+impl<T: SimpleSerialize, const N: usize> SimpleSerialize for [T; N] {
+    const SIZE: usize = T::SIZE * N;
+
+    fn encode(&self, out: &mut Vec<u8>) {
+        for item in self {
+            item.encode(out);
+        }
+    }
+
+    fn decode(bytes: &[u8]) -> Result<Self, Error> {
+        check_len::<Self>(bytes)?;
+        let items = bytes
+            .chunks_exact(T::SIZE)
+            .map(T::decode)
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(items.try_into().ok().unwrap())
+    }
+
+    fn hash_tree_root(&self) -> Root {
+        match T::BASIC {
+            true => merkleize(pack(&self.to_ssz()), Self::SIZE.div_ceil(32)),
+            false => merkleize(self.iter().map(T::hash_tree_root).collect(), N),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hex_root(value: &str) -> Root {
+        let mut root = Root::default();
+        for (i, byte) in root.iter_mut().enumerate() {
+            *byte = u8::from_str_radix(&value[2 * i..2 * i + 2], 16).unwrap();
+        }
+        root
+    }
+
+    #[test]
+    fn zero_hashes() {
+        assert_eq!(merkleize(vec![], 1), Root::default());
+        assert_eq!(
+            merkleize(vec![], 2),
+            hex_root("f5a5fd42d16a20302798ef6ed309979b43003d2320d9f0e8ea9831a92759fb4b")
+        );
+        assert_eq!(
+            merkleize(vec![Root::default()], 5),
+            hex_root("c78009fdf07fc56a11f122370658a353aaa542ed63e44c4bc15ff4cd105ab33c")
+        );
+    }
+
+    #[test]
+    fn merkle_branch() {
+        let leaves: Vec<Root> = (0u8..5).map(|i| [i; 32]).collect();
+        let root = merkleize(leaves.clone(), 8);
+        let zero = Root::default();
+        let branch = [
+            leaves[2],
+            hash_pair(&leaves[0], &leaves[1]),
+            hash_pair(&hash_pair(&leaves[4], &zero), &hash_pair(&zero, &zero)),
+        ];
+        assert!(is_valid_merkle_branch(&leaves[3], &branch, 3, 3, &root));
+        assert!(!is_valid_merkle_branch(&leaves[3], &branch, 3, 2, &root));
+        assert!(!is_valid_merkle_branch(
+            &leaves[3],
+            &branch[..2],
+            2,
+            3,
+            &root
+        ));
+    }
+
+    #[test]
+    fn vectors() {
+        let values: [u64; 5] = [1, 2, 3, 4, 5];
+        let encoded = values.to_ssz();
+        assert_eq!(encoded.len(), 40);
+        assert_eq!(<[u64; 5]>::decode(&encoded).unwrap(), values);
+        assert!(<[u64; 5]>::decode(&encoded[1..]).is_err());
+        // Basic values are packed, four `u64` to a chunk.
+        assert_eq!(values.hash_tree_root(), merkleize(pack(&encoded), 2));
+
+        let key = ByteVector([7u8; 48]);
+        let mut tail = Root::default();
+        tail[..16].copy_from_slice(&[7u8; 16]);
+        assert_eq!(key.hash_tree_root(), hash_pair(&[7u8; 32], &tail));
+        let keys = [key, key, key];
+        assert_eq!(
+            keys.hash_tree_root(),
+            merkleize(vec![key.hash_tree_root(); 3], 3)
+        );
+    }
+}
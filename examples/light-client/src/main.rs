@@ -0,0 +1,93 @@
+// Copyright 2024 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use light_client_core::{
+    mock::MockChain, ssz::SimpleSerialize, Input, Journal, EPOCHS_PER_SYNC_COMMITTEE_PERIOD,
+    SLOTS_PER_EPOCH,
+};
+use light_client_methods::LIGHT_CLIENT_ELF;
+use risc0_zkvm::{default_prover, ExecutorEnv};
+
+const PERIOD_SLOTS: u64 = EPOCHS_PER_SYNC_COMMITTEE_PERIOD * SLOTS_PER_EPOCH;
+
+fn main() {
+    let chain = MockChain::new();
+    let input = mock_input(&chain);
+    let journal = verify(&input);
+
+    let header = journal.finalized_header;
+    println!(
+        "Verified finality of the block at slot {} starting from trusted block 0x{}",
+        header.slot,
+        hex(&journal.trusted_block_root)
+    );
+    println!("  block root 0x{}", hex(&header.hash_tree_root()));
+    println!("  state root 0x{}", hex(&header.state_root));
+}
+
+// Builds an input following the mock chain over a sync committee period
+// boundary: the first update finalizes a block of the bootstrap period and
+// provides the next sync committee, which signs the second update.
+fn mock_input(chain: &MockChain) -> Input {
+    let start = 3 * PERIOD_SLOTS + 100;
+    let (trusted_block_root, bootstrap) = chain.bootstrap(start);
+    let updates = [
+        chain.update(start + 96, start + 32, 400),
+        chain.update(4 * PERIOD_SLOTS + 64, 4 * PERIOD_SLOTS, 512),
+    ];
+    Input {
+        genesis_validators_root: chain.genesis_validators_root,
+        fork_version: chain.fork_version.0,
+        trusted_block_root,
+        bootstrap: bootstrap.to_ssz(),
+        updates: updates.iter().map(SimpleSerialize::to_ssz).collect(),
+    }
+}
+
+fn verify(input: &Input) -> Journal {
+    let env = ExecutorEnv::builder()
+        .write(input)
+        .unwrap()
+        .build()
+        .unwrap();
+
+    // Obtain the default prover.
+    let prover = default_prover();
+
+    // Produce a receipt by proving the specified ELF binary.
+    let receipt = prover.prove(env, LIGHT_CLIENT_ELF).unwrap();
+
+    receipt.journal.decode().unwrap()
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{byte:02x}")).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use light_client_core::mock::MockChain;
+
+    #[test]
+    fn main() {
+        let chain = MockChain::new();
+        let input = super::mock_input(&chain);
+        let journal = super::verify(&input);
+        assert_eq!(journal.trusted_block_root, input.trusted_block_root);
+        assert_eq!(
+            journal.finalized_header,
+            chain.header(4 * super::PERIOD_SLOTS, None)
+        );
+    }
+}
@@ -0,0 +1,115 @@
+// Copyright 2024 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! `COSE_Sign1` (RFC 9052) signature verification.
+
+use ciborium::Value;
+use p256::ecdsa::{signature::Verifier, Signature, VerifyingKey};
+
+use crate::{decode, encode, lookup, Error};
+
+/// CBOR tag of a `COSE_Sign1` message.
+pub const COSE_SIGN1_TAG: u64 = 18;
+
+/// COSE algorithm identifier of ECDSA with SHA-256.
+pub const ES256: i128 = -7;
+
+/// COSE header label of the algorithm.
+const ALG: i128 = 1;
+
+/// A `COSE_Sign1` message with an attached payload.
+#[derive(Clone, Debug, PartialEq)]
+pub struct CoseSign1 {
+    /// The encoded protected header.
+    pub protected: Vec<u8>,
+    /// The unprotected header.
+    pub unprotected: Vec<(Value, Value)>,
+    pub payload: Vec<u8>,
+    pub signature: Vec<u8>,
+}
+
+impl CoseSign1 {
+    /// Parses a `COSE_Sign1` message, optionally tagged.
+    pub fn from_value(value: &Value) -> Result<Self, Error> {
+        let value = match value.as_tag() {
+            Some((COSE_SIGN1_TAG, inner)) => inner,
+            Some(_) => return Err(Error::Malformed("unexpected tag on COSE_Sign1")),
+            None => value,
+        };
+        let Some([protected, unprotected, payload, signature]) =
+            value.as_array().map(Vec::as_slice)
+        else {
+            return Err(Error::Malformed("COSE_Sign1 is not an array of 4 items"));
+        };
+        let payload = match payload {
+            Value::Null => return Err(Error::Malformed("detached payloads are not supported")),
+            payload => payload.as_bytes(),
+        };
+        match (
+            protected.as_bytes(),
+            unprotected.as_map(),
+            payload,
+            signature.as_bytes(),
+        ) {
+            (Some(protected), Some(unprotected), Some(payload), Some(signature)) => Ok(Self {
+                protected: protected.clone(),
+                unprotected: unprotected.clone(),
+                payload: payload.clone(),
+                signature: signature.clone(),
+            }),
+            _ => Err(Error::Malformed("COSE_Sign1 has an item of the wrong type")),
+        }
+    }
+
+    /// Returns the algorithm of the protected header.
+    pub fn algorithm(&self) -> Result<i128, Error> {
+        if self.protected.is_empty() {
+            return Err(Error::Malformed("missing protected header"));
+        }
+        let header = decode(&self.protected)?;
+        let header = header
+            .as_map()
+            .ok_or(Error::Malformed("protected header is not a map"))?;
+        lookup(header, &Value::from(ALG))
+            .and_then(Value::as_integer)
+            .map(i128::from)
+            .ok_or(Error::Malformed("missing algorithm"))
+    }
+
+    /// Verifies the signature with `key`, over the payload and
+    /// `external_aad`.
+    ///
+    /// Only ES256 is supported, whose signature is the concatenation of the
+    /// 32 byte `r` and `s` values.
+    pub fn verify(&self, key: &VerifyingKey, external_aad: &[u8]) -> Result<(), Error> {
+        match self.algorithm()? {
+            ES256 => {}
+            other => return Err(Error::UnsupportedAlgorithm(other)),
+        }
+        let signature =
+            Signature::from_slice(&self.signature).map_err(|_| Error::InvalidSignature)?;
+        key.verify(&self.sig_structure(external_aad), &signature)
+            .map_err(|_| Error::InvalidSignature)
+    }
+
+    /// Returns the encoded `Sig_structure`, which is the signed data.
+    pub fn sig_structure(&self, external_aad: &[u8]) -> Vec<u8> {
+        encode(&Value::Array(vec![
+            Value::from("Signature1"),
+            Value::Bytes(self.protected.clone()),
+            Value::Bytes(external_aad.to_vec()),
+            Value::Bytes(self.payload.clone()),
+        ]))
+    }
+}
@@ -0,0 +1,407 @@
+// Copyright 2024 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! ISO/IEC 18013-5 mobile document (mdoc) verification for use inside the
+//! zkVM guest.
+//!
+//! An mdoc, such as a mobile driving licence, is issued as an `IssuerSigned`
+//! structure: the issuer signs a Mobile Security Object (MSO) holding salted
+//! digests of all the data elements of the document, so that each element can
+//! be disclosed independently of the others. Only the elements requested in
+//! `reveal` are checked against the MSO and included in the [VerifiedMdoc].
+//!
+//! The issuer certificate chain is not verified. Instead, the issuer's public
+//! key is provided as input and its digest is included in the [VerifiedMdoc],
+//! so that a verifier of the receipt can check it against a list of trusted
+//! issuers.
+
+pub mod cose;
+
+use std::collections::BTreeMap;
+
+use ciborium::Value;
+use p256::ecdsa::VerifyingKey;
+use risc0_zkvm::sha::{Digest, Impl, Sha256 as _};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest as _, Sha256};
+use thiserror::Error;
+
+use crate::cose::CoseSign1;
+
+/// CBOR tag of an embedded CBOR data item.
+pub const ENCODED_CBOR_TAG: u64 = 24;
+
+/// CBOR tag of a date and time string.
+const TDATE_TAG: u64 = 0;
+
+#[derive(Error, Debug)]
+pub enum Error {
+    #[error("malformed CBOR: {0}")]
+    Cbor(String),
+
+    #[error("malformed mdoc: {0}")]
+    Malformed(&'static str),
+
+    #[error("missing field {0}")]
+    MissingField(&'static str),
+
+    #[error("unsupported MSO version {0}")]
+    UnsupportedVersion(String),
+
+    #[error("unsupported COSE algorithm {0}")]
+    UnsupportedAlgorithm(i128),
+
+    #[error("unsupported digest algorithm {0}")]
+    UnsupportedDigestAlgorithm(String),
+
+    #[error("malformed issuer key")]
+    MalformedKey,
+
+    #[error("signature is invalid")]
+    InvalidSignature,
+
+    #[error("element {1} of namespace {0} is not in the document")]
+    MissingElement(String, String),
+
+    #[error("digest of element {1} of namespace {0} does not match")]
+    DigestMismatch(String, String),
+}
+
+/// Input to the guest.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct MdocInput {
+    /// The CBOR-encoded `IssuerSigned` structure of the document.
+    pub issuer_signed: Vec<u8>,
+    /// The SEC1 encoding of the issuer's P-256 public key.
+    pub issuer_key: Vec<u8>,
+    /// Namespaces and identifiers of the data elements to include in the
+    /// output.
+    pub reveal: Vec<(String, String)>,
+}
+
+/// Claims about a document with a valid issuer signature.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct VerifiedMdoc {
+    /// The document type, e.g. `org.iso.18013.5.1.mDL`.
+    pub doc_type: String,
+    /// SHA-256 digest of the SEC1-encoded public key used to verify the
+    /// signature.
+    pub issuer_key_digest: Digest,
+    /// Validity period of the MSO, as RFC 3339 date and time strings.
+    pub signed: String,
+    pub valid_from: String,
+    pub valid_until: String,
+    /// The requested data elements.
+    pub elements: Vec<DisclosedElement>,
+}
+
+/// A data element disclosed by the holder of the document.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct DisclosedElement {
+    pub namespace: String,
+    pub identifier: String,
+    /// The CBOR encoding of the element value.
+    pub value: Vec<u8>,
+}
+
+/// The Mobile Security Object, signed by the issuer.
+struct MobileSecurityObject {
+    doc_type: String,
+    /// Digests of the data elements, by namespace and digest ID.
+    value_digests: BTreeMap<String, BTreeMap<u64, Vec<u8>>>,
+    signed: String,
+    valid_from: String,
+    valid_until: String,
+}
+
+/// Verify the issuer signature of the `IssuerSigned` structure in
+/// `issuer_signed` using `issuer_key`, and reveal the data elements listed in
+/// `reveal`.
+pub fn verify_mdoc(
+    issuer_signed: &[u8],
+    issuer_key: &[u8],
+    reveal: &[(String, String)],
+) -> Result<VerifiedMdoc, Error> {
+    let key = VerifyingKey::from_sec1_bytes(issuer_key).map_err(|_| Error::MalformedKey)?;
+    let issuer_signed = decode(issuer_signed)?;
+    let issuer_signed = issuer_signed
+        .as_map()
+        .ok_or(Error::Malformed("IssuerSigned is not a map"))?;
+
+    let issuer_auth = CoseSign1::from_value(field(issuer_signed, "issuerAuth")?)?;
+    issuer_auth.verify(&key, &[])?;
+    let mso = parse_mso(&issuer_auth.payload)?;
+
+    let name_spaces = field(issuer_signed, "nameSpaces")?
+        .as_map()
+        .ok_or(Error::Malformed("nameSpaces is not a map"))?;
+    let elements = reveal
+        .iter()
+        .map(|(namespace, identifier)| {
+            let missing = || Error::MissingElement(namespace.clone(), identifier.clone());
+            let items = lookup(name_spaces, &Value::from(namespace.as_str()))
+                .and_then(Value::as_array)
+                .ok_or_else(missing)?;
+            for item in items {
+                let (digest_id, item_identifier, value) = parse_item(item)?;
+                if &item_identifier != identifier {
+                    continue;
+                }
+                // The digest covers the tagged item, as it appears in the
+                // namespace.
+                let digest = Sha256::digest(encode(item));
+                let expected = mso
+                    .value_digests
+                    .get(namespace)
+                    .and_then(|digests| digests.get(&digest_id));
+                return match expected {
+                    Some(expected) if expected[..] == digest[..] => Ok(DisclosedElement {
+                        namespace: namespace.clone(),
+                        identifier: identifier.clone(),
+                        value: encode(&value),
+                    }),
+                    _ => Err(Error::DigestMismatch(namespace.clone(), identifier.clone())),
+                };
+            }
+            Err(missing())
+        })
+        .collect::<Result<_, _>>()?;
+
+    Ok(VerifiedMdoc {
+        doc_type: mso.doc_type,
+        issuer_key_digest: *Impl::hash_bytes(issuer_key),
+        signed: mso.signed,
+        valid_from: mso.valid_from,
+        valid_until: mso.valid_until,
+        elements,
+    })
+}
+
+/// Parse the `MobileSecurityObjectBytes` payload of the issuer signature.
+fn parse_mso(payload: &[u8]) -> Result<MobileSecurityObject, Error> {
+    let mso = decode(embedded(&decode(payload)?)?)?;
+    let mso = mso.as_map().ok_or(Error::Malformed("MSO is not a map"))?;
+
+    let version = text(field(mso, "version")?)?;
+    if version != "1.0" {
+        return Err(Error::UnsupportedVersion(version.to_string()));
+    }
+    let digest_algorithm = text(field(mso, "digestAlgorithm")?)?;
+    if digest_algorithm != "SHA-256" {
+        return Err(Error::UnsupportedDigestAlgorithm(
+            digest_algorithm.to_string(),
+        ));
+    }
+
+    let malformed = || Error::Malformed("malformed valueDigests");
+    let value_digests = field(mso, "valueDigests")?
+        .as_map()
+        .ok_or_else(malformed)?
+        .iter()
+        .map(|(namespace, digests)| {
+            let digests = digests
+                .as_map()
+                .ok_or_else(malformed)?
+                .iter()
+                .map(|(id, digest)| {
+                    Ok((uint(id)?, digest.as_bytes().ok_or_else(malformed)?.clone()))
+                })
+                .collect::<Result<_, Error>>()?;
+            Ok((text(namespace)?.to_string(), digests))
+        })
+        .collect::<Result<_, Error>>()?;
+
+    let validity = field(mso, "validityInfo")?
+        .as_map()
+        .ok_or(Error::Malformed("validityInfo is not a map"))?;
+    Ok(MobileSecurityObject {
+        doc_type: text(field(mso, "docType")?)?.to_string(),
+        value_digests,
+        signed: tdate(field(validity, "signed")?)?,
+        valid_from: tdate(field(validity, "validFrom")?)?,
+        valid_until: tdate(field(validity, "validUntil")?)?,
+    })
+}
+
+/// Parse an `IssuerSignedItemBytes`, returning its digest ID, element
+/// identifier and element value.
+fn parse_item(item: &Value) -> Result<(u64, String, Value), Error> {
+    let item = decode(embedded(item)?)?;
+    let mut item = match item {
+        Value::Map(item) => item,
+        _ => return Err(Error::Malformed("IssuerSignedItem is not a map")),
+    };
+    let digest_id = uint(field(&item, "digestID")?)?;
+    let identifier = text(field(&item, "elementIdentifier")?)?.to_string();
+    field(&item, "random")?
+        .as_bytes()
+        .ok_or(Error::Malformed("random is not a byte string"))?;
+    let value = item
+        .iter()
+        .position(|(key, _)| key.as_text() == Some("elementValue"))
+        .ok_or(Error::MissingField("elementValue"))?;
+    Ok((digest_id, identifier, item.swap_remove(value).1))
+}
+
+/// Decode a single CBOR data item, which must span all of `bytes`.
+pub(crate) fn decode(mut bytes: &[u8]) -> Result<Value, Error> {
+    let value =
+        ciborium::de::from_reader(&mut bytes).map_err(|err| Error::Cbor(err.to_string()))?;
+    match bytes.is_empty() {
+        true => Ok(value),
+        false => Err(Error::Cbor("trailing bytes".to_string())),
+    }
+}
+
+/// Encode a CBOR data item, with the shortest form of each length and integer.
+pub(crate) fn encode(value: &Value) -> Vec<u8> {
+    let mut out = Vec::new();
+    ciborium::ser::into_writer(value, &mut out).unwrap();
+    out
+}
+
+/// Look up the value of `key` in a CBOR map.
+pub(crate) fn lookup<'a>(map: &'a [(Value, Value)], key: &Value) -> Option<&'a Value> {
+    map.iter().find(|(k, _)| k == key).map(|(_, v)| v)
+}
+
+fn field<'a>(map: &'a [(Value, Value)], name: &'static str) -> Result<&'a Value, Error> {
+    lookup(map, &Value::from(name)).ok_or(Error::MissingField(name))
+}
+
+/// Return the bytes of an embedded CBOR data item.
+fn embedded(value: &Value) -> Result<&[u8], Error> {
+    match value.as_tag() {
+        Some((ENCODED_CBOR_TAG, Value::Bytes(bytes))) => Ok(bytes),
+        _ => Err(Error::Malformed("expected embedded CBOR")),
+    }
+}
+
+fn text(value: &Value) -> Result<&str, Error> {
+    value
+        .as_text()
+        .ok_or(Error::Malformed("expected a text string"))
+}
+
+fn uint(value: &Value) -> Result<u64, Error> {
+    value
+        .as_integer()
+        .and_then(|int| u64::try_from(int).ok())
+        .ok_or(Error::Malformed("expected an unsigned integer"))
+}
+
+fn tdate(value: &Value) -> Result<String, Error> {
+    match value.as_tag() {
+        Some((TDATE_TAG, Value::Text(date))) => Ok(date.clone()),
+        _ => Err(Error::Malformed("expected a tagged date")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use p256::ecdsa::SigningKey;
+
+    use super::*;
+
+    const MDL: &[u8] = include_bytes!("../testdata/mdl.cbor");
+    const ISSUER_KEY: &[u8] = include_bytes!("../testdata/issuer_key.bin");
+    const NAMESPACE: &str = "org.iso.18013.5.1";
+
+    fn reveal(identifiers: &[&str]) -> Vec<(String, String)> {
+        identifiers
+            .iter()
+            .map(|id| (NAMESPACE.to_string(), id.to_string()))
+            .collect()
+    }
+
+    fn replace(data: &[u8], from: &[u8], to: &[u8]) -> Vec<u8> {
+        let pos = data
+            .windows(from.len())
+            .position(|window| window == from)
+            .unwrap();
+        [&data[..pos], to, &data[pos + from.len()..]].concat()
+    }
+
+    #[test]
+    fn verify() {
+        let mdoc = verify_mdoc(
+            MDL,
+            ISSUER_KEY,
+            &reveal(&["age_over_21", "issuing_country"]),
+        )
+        .unwrap();
+        assert_eq!(mdoc.doc_type, "org.iso.18013.5.1.mDL");
+        assert_eq!(mdoc.issuer_key_digest, *Impl::hash_bytes(ISSUER_KEY));
+        assert_eq!(mdoc.valid_from, "2024-01-15T10:00:00Z");
+        assert_eq!(mdoc.valid_until, "2029-01-15T10:00:00Z");
+        assert_eq!(mdoc.elements.len(), 2);
+        assert_eq!(mdoc.elements[0].identifier, "age_over_21");
+        assert_eq!(decode(&mdoc.elements[0].value).unwrap(), Value::Bool(true));
+        assert_eq!(decode(&mdoc.elements[1].value).unwrap(), Value::from("US"));
+    }
+
+    #[test]
+    fn reveal_nothing() {
+        let mdoc = verify_mdoc(MDL, ISSUER_KEY, &[]).unwrap();
+        assert!(mdoc.elements.is_empty());
+    }
+
+    #[test]
+    fn missing_element() {
+        let err = verify_mdoc(MDL, ISSUER_KEY, &reveal(&["portrait"])).unwrap_err();
+        assert!(matches!(err, Error::MissingElement(_, id) if id == "portrait"));
+        let reveal = [("org.example".to_string(), "age_over_21".to_string())];
+        let err = verify_mdoc(MDL, ISSUER_KEY, &reveal).unwrap_err();
+        assert!(matches!(err, Error::MissingElement(..)));
+    }
+
+    #[test]
+    fn tampered_element() {
+        let mdl = replace(MDL, b"cDoe", b"cRoe");
+        // Other elements can still be disclosed.
+        verify_mdoc(&mdl, ISSUER_KEY, &reveal(&["given_name"])).unwrap();
+        let err = verify_mdoc(&mdl, ISSUER_KEY, &reveal(&["family_name"])).unwrap_err();
+        assert!(matches!(err, Error::DigestMismatch(..)));
+    }
+
+    #[test]
+    fn tampered_mso() {
+        let mdl = replace(MDL, b"2029-01-15T10", b"2039-01-15T10");
+        let err = verify_mdoc(&mdl, ISSUER_KEY, &[]).unwrap_err();
+        assert!(matches!(err, Error::InvalidSignature));
+    }
+
+    #[test]
+    fn wrong_key() {
+        let key = SigningKey::from_slice(&[0x33; 32]).unwrap();
+        let key = key.verifying_key().to_encoded_point(false);
+        assert!(matches!(
+            verify_mdoc(MDL, key.as_bytes(), &[]).unwrap_err(),
+            Error::InvalidSignature
+        ));
+        assert!(matches!(
+            verify_mdoc(MDL, &ISSUER_KEY[1..], &[]).unwrap_err(),
+            Error::MalformedKey
+        ));
+    }
+
+    #[test]
+    fn trailing_bytes() {
+        let mdl = [MDL, &[0]].concat();
+        assert!(matches!(
+            verify_mdoc(&mdl, ISSUER_KEY, &[]).unwrap_err(),
+            Error::Cbor(_)
+        ));
+    }
+}
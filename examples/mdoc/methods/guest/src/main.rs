@@ -0,0 +1,22 @@
+// Copyright 2024 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use mdoc_core::{verify_mdoc, MdocInput};
+use risc0_zkvm::guest::env;
+
+fn main() {
+    let input: MdocInput = env::read();
+    let mdoc = verify_mdoc(&input.issuer_signed, &input.issuer_key, &input.reveal).unwrap();
+    env::commit(&mdoc);
+}
@@ -0,0 +1,82 @@
+// Copyright 2024 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use ciborium::Value;
+use mdoc_core::{MdocInput, VerifiedMdoc};
+use mdoc_methods::VERIFY_MDOC_ELF;
+use risc0_zkvm::{default_prover, ExecutorEnv};
+
+const MDL: &[u8] = include_bytes!("../core/testdata/mdl.cbor");
+const ISSUER_KEY: &[u8] = include_bytes!("../core/testdata/issuer_key.bin");
+const NAMESPACE: &str = "org.iso.18013.5.1";
+
+fn main() {
+    let input = MdocInput {
+        issuer_signed: MDL.to_vec(),
+        issuer_key: ISSUER_KEY.to_vec(),
+        reveal: reveal(),
+    };
+    let mdoc = verify(&input);
+
+    println!(
+        "Verified {} valid from {} until {}",
+        mdoc.doc_type, mdoc.valid_from, mdoc.valid_until
+    );
+    println!("  issuer key digest {}", mdoc.issuer_key_digest);
+    for element in mdoc.elements.iter() {
+        let value: Value = ciborium::de::from_reader(element.value.as_slice()).unwrap();
+        println!("  {}: {value:?}", element.identifier);
+    }
+}
+
+fn reveal() -> Vec<(String, String)> {
+    ["age_over_21", "issuing_country"]
+        .iter()
+        .map(|id| (NAMESPACE.to_string(), id.to_string()))
+        .collect()
+}
+
+fn verify(input: &MdocInput) -> VerifiedMdoc {
+    let env = ExecutorEnv::builder()
+        .write(input)
+        .unwrap()
+        .build()
+        .unwrap();
+
+    // Obtain the default prover.
+    let prover = default_prover();
+
+    // Produce a receipt by proving the specified ELF binary.
+    let receipt = prover.prove(env, VERIFY_MDOC_ELF).unwrap();
+
+    receipt.journal.decode().unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use mdoc_core::MdocInput;
+
+    #[test]
+    fn main() {
+        let input = MdocInput {
+            issuer_signed: super::MDL.to_vec(),
+            issuer_key: super::ISSUER_KEY.to_vec(),
+            reveal: super::reveal(),
+        };
+        let mdoc = super::verify(&input);
+        assert_eq!(mdoc.doc_type, "org.iso.18013.5.1.mDL");
+        assert_eq!(mdoc.elements.len(), 2);
+        assert_eq!(mdoc.elements[0].identifier, "age_over_21");
+    }
+}
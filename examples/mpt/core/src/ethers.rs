@@ -0,0 +1,59 @@
+// Copyright 2024 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Host-side helpers to build proofs from an Ethereum RPC endpoint.
+
+use ethers_core::types::{BlockId, EIP1186ProofResponse, H160, H256};
+use ethers_providers::Middleware;
+
+use crate::{AccountProof, StorageProof};
+
+impl From<EIP1186ProofResponse> for AccountProof {
+    fn from(response: EIP1186ProofResponse) -> Self {
+        Self {
+            address: response.address.0,
+            proof: response
+                .account_proof
+                .into_iter()
+                .map(|node| node.to_vec())
+                .collect(),
+            storage: response
+                .storage_proof
+                .into_iter()
+                .map(|slot| StorageProof {
+                    slot: slot.key.0,
+                    proof: slot.proof.into_iter().map(|node| node.to_vec()).collect(),
+                })
+                .collect(),
+        }
+    }
+}
+
+/// Fetches the proof of an account and some of its storage slots with
+/// `eth_getProof`, along with the state root of the block it was taken at.
+pub async fn fetch_proof<M: Middleware>(
+    client: &M,
+    address: [u8; 20],
+    slots: &[[u8; 32]],
+    block: BlockId,
+) -> Result<([u8; 32], AccountProof), M::Error> {
+    let state_root = client
+        .get_block(block)
+        .await?
+        .map(|block| block.state_root.0)
+        .unwrap_or_default();
+    let slots = slots.iter().map(|slot| H256(*slot)).collect();
+    let response = client.get_proof(H160(address), slots, Some(block)).await?;
+    Ok((state_root, response.into()))
+}
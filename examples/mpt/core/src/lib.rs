@@ -0,0 +1,827 @@
+// Copyright 2024 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Ethereum Merkle-Patricia-Trie utilities shared between the host and the
+//! zkVM guest.
+//!
+//! A [Trie] can be built from the nodes of one or more Merkle proofs, as
+//! returned by `eth_getProof`. Subtries not covered by the proofs are kept as
+//! their digest, so the trie can be queried for inclusion and exclusion of the
+//! proven keys, updated, and its new root computed, without knowing the rest
+//! of the state.
+
+use std::collections::HashMap;
+
+use rlp::{Rlp, RlpStream};
+use serde::{Deserialize, Serialize};
+use sha3::{Digest, Keccak256};
+use thiserror::Error;
+
+#[cfg(feature = "ethers")]
+pub mod ethers;
+
+/// A 256-bit hash.
+pub type B256 = [u8; 32];
+
+/// Root of the empty trie, `keccak256(rlp(""))`.
+pub const EMPTY_ROOT: B256 = [
+    0x56, 0xe8, 0x1f, 0x17, 0x1b, 0xcc, 0x55, 0xa6, 0xff, 0x83, 0x45, 0xe6, 0x92, 0xc0, 0xf8, 0x6e,
+    0x5b, 0x48, 0xe0, 0x1b, 0x99, 0x6c, 0xad, 0xc0, 0x01, 0x62, 0x2f, 0xb5, 0xe3, 0x63, 0xb4, 0x21,
+];
+
+/// Hash of the empty code, `keccak256("")`.
+pub const EMPTY_CODE_HASH: B256 = [
+    0xc5, 0xd2, 0x46, 0x01, 0x86, 0xf7, 0x23, 0x3c, 0x92, 0x7e, 0x7d, 0xb2, 0xdc, 0xc7, 0x03, 0xc0,
+    0xe5, 0x00, 0xb6, 0x53, 0xca, 0x82, 0x27, 0x3b, 0x7b, 0xfa, 0xd8, 0x04, 0x5d, 0x85, 0xa4, 0x70,
+];
+
+#[derive(Error, Debug, PartialEq)]
+pub enum Error {
+    #[error("invalid RLP: {0}")]
+    Rlp(#[from] rlp::DecoderError),
+
+    #[error("malformed trie node")]
+    MalformedNode,
+
+    #[error("trie node {} is not resolved", hex::encode(.0))]
+    UnresolvedNode(B256),
+
+    #[error("malformed account")]
+    MalformedAccount,
+
+    #[error("malformed storage value")]
+    MalformedValue,
+}
+
+/// Computes the Keccak-256 hash of `data`.
+pub fn keccak(data: impl AsRef<[u8]>) -> B256 {
+    Keccak256::digest(data).into()
+}
+
+/// A node of a Merkle-Patricia-Trie.
+///
+/// Paths are stored as nibbles.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+enum Node {
+    #[default]
+    Null,
+    Branch(Box<[Node; 16]>, Option<Vec<u8>>),
+    Leaf(Vec<u8>, Vec<u8>),
+    Extension(Vec<u8>, Box<Node>),
+    /// A node only known by its hash.
+    Digest(B256),
+}
+
+impl Node {
+    fn decode(data: &[u8], nodes: &HashMap<B256, &[u8]>) -> Result<Self, Error> {
+        let rlp = Rlp::new(data);
+        if !rlp.is_list() {
+            return Err(Error::MalformedNode);
+        }
+        match rlp.item_count()? {
+            17 => {
+                let mut children: [Node; 16] = Default::default();
+                for (i, child) in children.iter_mut().enumerate() {
+                    *child = Self::decode_child(&rlp.at(i)?, nodes)?;
+                }
+                let value = rlp.at(16)?.data()?;
+                let value = (!value.is_empty()).then(|| value.to_vec());
+                Ok(Node::Branch(Box::new(children), value))
+            }
+            2 => {
+                let (path, is_leaf) = decode_path(rlp.at(0)?.data()?)?;
+                if is_leaf {
+                    Ok(Node::Leaf(path, rlp.at(1)?.data()?.to_vec()))
+                } else {
+                    let child = Self::decode_child(&rlp.at(1)?, nodes)?;
+                    if path.is_empty() || child == Node::Null {
+                        return Err(Error::MalformedNode);
+                    }
+                    Ok(Node::Extension(path, Box::new(child)))
+                }
+            }
+            _ => Err(Error::MalformedNode),
+        }
+    }
+
+    fn decode_child(rlp: &Rlp, nodes: &HashMap<B256, &[u8]>) -> Result<Self, Error> {
+        if rlp.is_list() {
+            // Nodes shorter than 32 bytes are embedded in their parent.
+            return Self::decode(rlp.as_raw(), nodes);
+        }
+        match rlp.data()? {
+            [] => Ok(Node::Null),
+            hash => {
+                let hash: B256 = hash.try_into().map_err(|_| Error::MalformedNode)?;
+                Self::resolve(hash, nodes)
+            }
+        }
+    }
+
+    fn resolve(hash: B256, nodes: &HashMap<B256, &[u8]>) -> Result<Self, Error> {
+        match nodes.get(&hash) {
+            Some(data) => Self::decode(data, nodes),
+            None => Ok(Node::Digest(hash)),
+        }
+    }
+
+    fn encode(&self) -> Vec<u8> {
+        let mut stream = RlpStream::new();
+        match self {
+            Node::Null => {
+                stream.append_empty_data();
+            }
+            Node::Branch(children, value) => {
+                stream.begin_list(17);
+                for child in children.iter() {
+                    child.append_reference(&mut stream);
+                }
+                match value {
+                    Some(value) => stream.append(value),
+                    None => stream.append_empty_data(),
+                };
+            }
+            Node::Leaf(path, value) => {
+                stream.begin_list(2);
+                stream.append(&encode_path(path, true));
+                stream.append(value);
+            }
+            Node::Extension(path, child) => {
+                stream.begin_list(2);
+                stream.append(&encode_path(path, false));
+                child.append_reference(&mut stream);
+            }
+            Node::Digest(hash) => {
+                stream.append(&hash.as_slice());
+            }
+        }
+        stream.out().to_vec()
+    }
+
+    // Appends the reference to this node used by its parent: the node itself
+    // if its encoding is shorter than 32 bytes, its hash otherwise.
+    fn append_reference(&self, stream: &mut RlpStream) {
+        match self {
+            Node::Null => {
+                stream.append_empty_data();
+            }
+            Node::Digest(hash) => {
+                stream.append(&hash.as_slice());
+            }
+            _ => {
+                let encoded = self.encode();
+                if encoded.len() < 32 {
+                    stream.append_raw(&encoded, 1);
+                } else {
+                    stream.append(&keccak(&encoded).as_slice());
+                }
+            }
+        }
+    }
+
+    fn hash(&self) -> B256 {
+        match self {
+            Node::Null => EMPTY_ROOT,
+            Node::Digest(hash) => *hash,
+            _ => keccak(self.encode()),
+        }
+    }
+
+    fn get(&self, path: &[u8]) -> Result<Option<&[u8]>, Error> {
+        match self {
+            Node::Null => Ok(None),
+            Node::Branch(children, value) => match path.split_first() {
+                None => Ok(value.as_deref()),
+                Some((i, rest)) => children[*i as usize].get(rest),
+            },
+            Node::Leaf(prefix, value) => Ok((prefix == path).then_some(value.as_slice())),
+            Node::Extension(prefix, child) => match path.strip_prefix(prefix.as_slice()) {
+                Some(rest) => child.get(rest),
+                None => Ok(None),
+            },
+            Node::Digest(hash) => Err(Error::UnresolvedNode(*hash)),
+        }
+    }
+
+    fn insert(&mut self, path: &[u8], value: Vec<u8>) -> Result<(), Error> {
+        match self {
+            Node::Null => *self = Node::Leaf(path.to_vec(), value),
+            Node::Branch(children, branch_value) => match path.split_first() {
+                None => *branch_value = Some(value),
+                Some((i, rest)) => children[*i as usize].insert(rest, value)?,
+            },
+            Node::Leaf(prefix, leaf_value) => {
+                if prefix == path {
+                    *leaf_value = value;
+                } else {
+                    let common = common_prefix(prefix, path);
+                    let mut branch = Node::Branch(Default::default(), None);
+                    branch.insert(&prefix[common..], core::mem::take(leaf_value))?;
+                    branch.insert(&path[common..], value)?;
+                    *self = Node::extend(&path[..common], branch);
+                }
+            }
+            Node::Extension(prefix, child) => {
+                let common = common_prefix(prefix, path);
+                if common == prefix.len() {
+                    child.insert(&path[common..], value)?;
+                } else {
+                    let mut children: [Node; 16] = Default::default();
+                    let child = core::mem::take(child.as_mut());
+                    children[prefix[common] as usize] = Node::extend(&prefix[common + 1..], child);
+                    let mut branch = Node::Branch(Box::new(children), None);
+                    branch.insert(&path[common..], value)?;
+                    *self = Node::extend(&path[..common], branch);
+                }
+            }
+            Node::Digest(hash) => return Err(Error::UnresolvedNode(*hash)),
+        }
+        Ok(())
+    }
+
+    fn remove(&mut self, path: &[u8]) -> Result<bool, Error> {
+        let removed = match self {
+            Node::Null => false,
+            Node::Branch(children, value) => match path.split_first() {
+                None => value.take().is_some(),
+                Some((i, rest)) => children[*i as usize].remove(rest)?,
+            },
+            Node::Leaf(prefix, _) => {
+                if prefix != path {
+                    return Ok(false);
+                }
+                *self = Node::Null;
+                true
+            }
+            Node::Extension(prefix, child) => match path.strip_prefix(prefix.as_slice()) {
+                Some(rest) => child.remove(rest)?,
+                None => false,
+            },
+            Node::Digest(hash) => return Err(Error::UnresolvedNode(*hash)),
+        };
+        if removed {
+            self.normalize()?;
+        }
+        Ok(removed)
+    }
+
+    // Restores the canonical form of a node after one of its children has
+    // been removed.
+    fn normalize(&mut self) -> Result<(), Error> {
+        match self {
+            Node::Branch(children, value) => {
+                let mut occupied = children
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, c)| **c != Node::Null);
+                match (occupied.next(), occupied.next(), value.take()) {
+                    (None, _, None) => *self = Node::Null,
+                    (None, _, Some(value)) => *self = Node::Leaf(vec![], value),
+                    (Some((i, _)), None, None) => {
+                        let child = core::mem::take(&mut children[i]);
+                        *self = Node::extend(&[i as u8], child);
+                        if let Node::Extension(_, child) = self {
+                            // Merging with a child needs to know its type.
+                            if let Node::Digest(hash) = child.as_ref() {
+                                return Err(Error::UnresolvedNode(*hash));
+                            }
+                        }
+                    }
+                    (_, _, branch_value) => *value = branch_value,
+                }
+            }
+            Node::Extension(prefix, child) => {
+                let prefix = core::mem::take(prefix);
+                let child = core::mem::take(child.as_mut());
+                *self = Node::extend(&prefix, child);
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    // Prepends `prefix` to the path of `node`, merging extensions and leaves.
+    fn extend(prefix: &[u8], node: Node) -> Node {
+        if prefix.is_empty() {
+            return node;
+        }
+        match node {
+            Node::Null => Node::Null,
+            Node::Leaf(path, value) => Node::Leaf([prefix, &path].concat(), value),
+            Node::Extension(path, child) => Node::Extension([prefix, &path].concat(), child),
+            node => Node::Extension(prefix.to_vec(), Box::new(node)),
+        }
+    }
+
+    fn proof(&self, path: &[u8], proof: &mut Vec<Vec<u8>>) -> Result<(), Error> {
+        let next = match self {
+            Node::Null | Node::Leaf(..) => None,
+            Node::Branch(children, _) => path
+                .split_first()
+                .map(|(i, rest)| (&children[*i as usize], rest)),
+            Node::Extension(prefix, child) => path
+                .strip_prefix(prefix.as_slice())
+                .map(|rest| (child.as_ref(), rest)),
+            Node::Digest(hash) => return Err(Error::UnresolvedNode(*hash)),
+        };
+        if let Some((child, rest)) = next {
+            if let Node::Digest(hash) = child {
+                return Err(Error::UnresolvedNode(*hash));
+            }
+            let encoded = child.encode();
+            if encoded.len() >= 32 {
+                proof.push(encoded);
+            }
+            child.proof(rest, proof)?;
+        }
+        Ok(())
+    }
+}
+
+fn common_prefix(a: &[u8], b: &[u8]) -> usize {
+    a.iter().zip(b).take_while(|(a, b)| a == b).count()
+}
+
+fn to_nibbles(key: &[u8]) -> Vec<u8> {
+    key.iter().flat_map(|b| [b >> 4, b & 0x0f]).collect()
+}
+
+// Hex-prefix encoding of a path, see appendix C of the yellow paper.
+fn encode_path(path: &[u8], is_leaf: bool) -> Vec<u8> {
+    let flag = if is_leaf { 0x20 } else { 0x00 };
+    let mut encoded = Vec::with_capacity(path.len() / 2 + 1);
+    let rest = if path.len() % 2 == 1 {
+        encoded.push(flag | 0x10 | path[0]);
+        &path[1..]
+    } else {
+        encoded.push(flag);
+        path
+    };
+    encoded.extend(rest.chunks(2).map(|pair| pair[0] << 4 | pair[1]));
+    encoded
+}
+
+fn decode_path(encoded: &[u8]) -> Result<(Vec<u8>, bool), Error> {
+    let (first, rest) = encoded.split_first().ok_or(Error::MalformedNode)?;
+    let (is_leaf, is_odd) = match first >> 4 {
+        0 => (false, false),
+        1 => (false, true),
+        2 => (true, false),
+        3 => (true, true),
+        _ => return Err(Error::MalformedNode),
+    };
+    let mut path = Vec::with_capacity(rest.len() * 2 + 1);
+    if is_odd {
+        path.push(first & 0x0f);
+    } else if first & 0x0f != 0 {
+        return Err(Error::MalformedNode);
+    }
+    path.extend(to_nibbles(rest));
+    Ok((path, is_leaf))
+}
+
+/// A Merkle-Patricia-Trie, possibly only partially known.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Trie {
+    root: Node,
+}
+
+impl Trie {
+    /// Creates an empty trie.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Builds the part of the trie with the given `root` that is covered by
+    /// the nodes of `proofs`.
+    ///
+    /// Nodes that do not belong to the trie are ignored. Querying or updating
+    /// a key outside of the proven part returns [Error::UnresolvedNode].
+    pub fn from_proofs<'a>(
+        root: &B256,
+        proofs: impl IntoIterator<Item = &'a [u8]>,
+    ) -> Result<Self, Error> {
+        if *root == EMPTY_ROOT {
+            return Ok(Self::new());
+        }
+        let nodes: HashMap<B256, &[u8]> = proofs.into_iter().map(|n| (keccak(n), n)).collect();
+        Ok(Self {
+            root: Node::resolve(*root, &nodes)?,
+        })
+    }
+
+    /// Returns the root hash of the trie.
+    pub fn hash(&self) -> B256 {
+        self.root.hash()
+    }
+
+    /// Returns the value stored under `key`, or `None` if the trie proves that
+    /// there is no such key.
+    pub fn get(&self, key: &[u8]) -> Result<Option<&[u8]>, Error> {
+        self.root.get(&to_nibbles(key))
+    }
+
+    /// Inserts `value` under `key`, replacing any previous value. An empty
+    /// value removes the key, as done by Ethereum clients.
+    ///
+    /// On error, the trie is left unchanged.
+    pub fn insert(&mut self, key: &[u8], value: Vec<u8>) -> Result<(), Error> {
+        if value.is_empty() {
+            return self.remove(key).map(|_| ());
+        }
+        let mut root = self.root.clone();
+        root.insert(&to_nibbles(key), value)?;
+        self.root = root;
+        Ok(())
+    }
+
+    /// Removes `key`, returning whether it was present.
+    ///
+    /// Removing a key may merge a branch with its only remaining child, in
+    /// which case that child must be resolved. On error, the trie is left
+    /// unchanged.
+    pub fn remove(&mut self, key: &[u8]) -> Result<bool, Error> {
+        let mut root = self.root.clone();
+        let removed = root.remove(&to_nibbles(key))?;
+        self.root = root;
+        Ok(removed)
+    }
+
+    /// Returns the nodes proving the inclusion or the exclusion of `key`, in
+    /// the format of `eth_getProof`.
+    pub fn proof(&self, key: &[u8]) -> Result<Vec<Vec<u8>>, Error> {
+        if self.root == Node::Null {
+            return Ok(vec![]);
+        }
+        if let Node::Digest(hash) = self.root {
+            return Err(Error::UnresolvedNode(hash));
+        }
+        let mut proof = vec![self.root.encode()];
+        self.root.proof(&to_nibbles(key), &mut proof)?;
+        Ok(proof)
+    }
+}
+
+/// Verifies a Merkle proof of `key` against `root`.
+///
+/// Returns the value stored under `key`, or `None` if the proof shows that the
+/// key is not in the trie.
+pub fn verify_proof(
+    root: &B256,
+    key: &[u8],
+    proof: impl IntoIterator<Item = impl AsRef<[u8]>>,
+) -> Result<Option<Vec<u8>>, Error> {
+    let proof: Vec<_> = proof.into_iter().collect();
+    let trie = Trie::from_proofs(root, proof.iter().map(|n| n.as_ref()))?;
+    Ok(trie.get(key)?.map(|value| value.to_vec()))
+}
+
+/// An Ethereum account, as stored in the state trie.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Account {
+    pub nonce: u64,
+    /// Balance, as a big endian 256-bit integer.
+    pub balance: B256,
+    pub storage_root: B256,
+    pub code_hash: B256,
+}
+
+impl Default for Account {
+    fn default() -> Self {
+        Self {
+            nonce: 0,
+            balance: B256::default(),
+            storage_root: EMPTY_ROOT,
+            code_hash: EMPTY_CODE_HASH,
+        }
+    }
+}
+
+impl Account {
+    /// Decodes an account from its RLP encoding.
+    pub fn decode(data: &[u8]) -> Result<Self, Error> {
+        let rlp = Rlp::new(data);
+        if !rlp.is_list() || rlp.item_count()? != 4 {
+            return Err(Error::MalformedAccount);
+        }
+        let hash = |i| -> Result<B256, Error> {
+            rlp.at(i)?
+                .data()?
+                .try_into()
+                .map_err(|_| Error::MalformedAccount)
+        };
+        Ok(Self {
+            nonce: rlp.val_at(0)?,
+            balance: decode_u256(rlp.at(1)?.data()?).ok_or(Error::MalformedAccount)?,
+            storage_root: hash(2)?,
+            code_hash: hash(3)?,
+        })
+    }
+
+    /// Returns the RLP encoding of the account.
+    pub fn encode(&self) -> Vec<u8> {
+        let mut stream = RlpStream::new_list(4);
+        stream.append(&self.nonce);
+        stream.append(&encode_u256(&self.balance));
+        stream.append(&self.storage_root.as_slice());
+        stream.append(&self.code_hash.as_slice());
+        stream.out().to_vec()
+    }
+}
+
+// Strips the leading zeros of a big endian integer.
+fn encode_u256(value: &B256) -> &[u8] {
+    let zeros = value.iter().take_while(|b| **b == 0).count();
+    &value[zeros..]
+}
+
+fn decode_u256(data: &[u8]) -> Option<B256> {
+    if data.len() > 32 || data.first() == Some(&0) {
+        return None;
+    }
+    let mut value = B256::default();
+    value[32 - data.len()..].copy_from_slice(data);
+    Some(value)
+}
+
+/// Encodes a storage value as stored in the storage trie.
+///
+/// Zero values are not stored, so they are encoded as an empty value.
+pub fn encode_storage_value(value: &B256) -> Vec<u8> {
+    match encode_u256(value) {
+        [] => vec![],
+        value => rlp::encode(&value).to_vec(),
+    }
+}
+
+/// Merkle proofs of an account and some of its storage slots.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AccountProof {
+    pub address: [u8; 20],
+    /// Nodes of the state trie proving the account.
+    pub proof: Vec<Vec<u8>>,
+    pub storage: Vec<StorageProof>,
+}
+
+/// Merkle proof of a storage slot.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StorageProof {
+    pub slot: B256,
+    /// Nodes of the storage trie proving the slot.
+    pub proof: Vec<Vec<u8>>,
+}
+
+/// Account and storage values proven by an [AccountProof].
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct VerifiedAccount {
+    pub address: [u8; 20],
+    /// The account, or `None` if it does not exist.
+    pub account: Option<Account>,
+    /// Values of the proven storage slots, as big endian 256-bit integers.
+    pub storage: Vec<(B256, B256)>,
+}
+
+impl AccountProof {
+    /// Verifies the account and storage proofs against the given state root.
+    pub fn verify(&self, state_root: &B256) -> Result<VerifiedAccount, Error> {
+        let account = verify_proof(state_root, &keccak(self.address), &self.proof)?
+            .map(|data| Account::decode(&data))
+            .transpose()?;
+        let storage_root = account.as_ref().map_or(EMPTY_ROOT, |a| a.storage_root);
+        let storage = self
+            .storage
+            .iter()
+            .map(|slot| {
+                let value = match verify_proof(&storage_root, &keccak(slot.slot), &slot.proof)? {
+                    Some(data) => {
+                        let rlp = Rlp::new(&data);
+                        decode_u256(rlp.data()?).ok_or(Error::MalformedValue)?
+                    }
+                    None => B256::default(),
+                };
+                Ok((slot.slot, value))
+            })
+            .collect::<Result<_, Error>>()?;
+        Ok(VerifiedAccount {
+            address: self.address,
+            account,
+            storage,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn h(s: &str) -> B256 {
+        hex::decode(s).unwrap().try_into().unwrap()
+    }
+
+    fn dogs() -> Trie {
+        let mut trie = Trie::new();
+        trie.insert(b"doe", b"reindeer".to_vec()).unwrap();
+        trie.insert(b"dog", b"puppy".to_vec()).unwrap();
+        trie.insert(b"dogglesworth", b"cat".to_vec()).unwrap();
+        trie
+    }
+
+    #[test]
+    fn empty() {
+        assert_eq!(Trie::new().hash(), EMPTY_ROOT);
+        assert_eq!(keccak(rlp::NULL_RLP), EMPTY_ROOT);
+        assert_eq!(keccak([]), EMPTY_CODE_HASH);
+    }
+
+    #[test]
+    fn root() {
+        // From the Ethereum trie tests.
+        let trie = dogs();
+        assert_eq!(
+            trie.hash(),
+            h("8aad789dff2f538bca5d8ea56e8abe10f4c7ba3a5dea95fea4cd6e7c3a1168d3")
+        );
+
+        // The root does not depend on the insertion order.
+        let mut other = Trie::new();
+        other.insert(b"dogglesworth", b"cat".to_vec()).unwrap();
+        other.insert(b"dog", b"puppy".to_vec()).unwrap();
+        other.insert(b"doe", b"reindeer".to_vec()).unwrap();
+        assert_eq!(other, trie);
+    }
+
+    #[test]
+    fn inclusion_and_exclusion() {
+        let trie = dogs();
+        let root = trie.hash();
+        for (key, value) in [
+            (&b"doe"[..], &b"reindeer"[..]),
+            (b"dog", b"puppy"),
+            (b"dogglesworth", b"cat"),
+        ] {
+            let proof = trie.proof(key).unwrap();
+            assert_eq!(
+                verify_proof(&root, key, &proof).unwrap().as_deref(),
+                Some(value)
+            );
+        }
+        for key in [&b"do"[..], b"dogs", b"cat", b""] {
+            let proof = trie.proof(key).unwrap();
+            assert_eq!(verify_proof(&root, key, &proof).unwrap(), None);
+        }
+
+        // A proof of one key cannot be used for another one.
+        let proof = trie.proof(b"doe").unwrap();
+        assert!(matches!(
+            verify_proof(&root, b"dogglesworth", &proof),
+            Err(Error::UnresolvedNode(_))
+        ));
+        // Nor against another root.
+        assert!(verify_proof(&EMPTY_ROOT, b"doe", &proof).unwrap().is_none());
+        let mut other = root;
+        other[0] ^= 1;
+        assert!(verify_proof(&other, b"doe", &proof).is_err());
+    }
+
+    #[test]
+    fn remove() {
+        let mut trie = dogs();
+        assert!(trie.remove(b"dogglesworth").unwrap());
+        assert!(!trie.remove(b"dogglesworth").unwrap());
+
+        let mut expected = Trie::new();
+        expected.insert(b"doe", b"reindeer".to_vec()).unwrap();
+        expected.insert(b"dog", b"puppy".to_vec()).unwrap();
+        assert_eq!(trie, expected);
+
+        trie.insert(b"doe", vec![]).unwrap();
+        trie.remove(b"dog").unwrap();
+        assert_eq!(trie.hash(), EMPTY_ROOT);
+    }
+
+    #[test]
+    fn update_partial() {
+        let mut full = Trie::new();
+        for i in 0u32..64 {
+            full.insert(&keccak(i.to_be_bytes()), rlp::encode(&i).to_vec())
+                .unwrap();
+        }
+        let key = keccak(7u32.to_be_bytes());
+        let absent = keccak(100u32.to_be_bytes());
+        let proofs = [full.proof(&key).unwrap(), full.proof(&absent).unwrap()].concat();
+        let mut partial =
+            Trie::from_proofs(&full.hash(), proofs.iter().map(Vec::as_slice)).unwrap();
+        assert_eq!(partial.hash(), full.hash());
+
+        // Updates of proven keys give the same root as on the full trie.
+        for trie in [&mut full, &mut partial] {
+            trie.insert(&key, b"updated".to_vec()).unwrap();
+            trie.insert(&absent, b"new".to_vec()).unwrap();
+        }
+        assert_eq!(partial.hash(), full.hash());
+
+        // Keys outside of the proofs are unresolved.
+        let other = keccak(8u32.to_be_bytes());
+        assert!(matches!(
+            partial.insert(&other, b"value".to_vec()),
+            Err(Error::UnresolvedNode(_))
+        ));
+        assert_eq!(partial.hash(), full.hash());
+    }
+
+    #[test]
+    fn remove_unresolved_sibling() {
+        let mut full = Trie::new();
+        full.insert(&[0x00], b"a".repeat(40)).unwrap();
+        full.insert(&[0x10], b"b".repeat(40)).unwrap();
+        let proof = full.proof(&[0x00]).unwrap();
+        let mut partial = Trie::from_proofs(&full.hash(), proof.iter().map(Vec::as_slice)).unwrap();
+
+        // The remaining sibling must be merged with its parent, which requires
+        // knowing its contents.
+        assert!(matches!(
+            partial.remove(&[0x00]),
+            Err(Error::UnresolvedNode(_))
+        ));
+        assert_eq!(partial.hash(), full.hash());
+
+        let proof = [proof, full.proof(&[0x10]).unwrap()].concat();
+        let mut partial = Trie::from_proofs(&full.hash(), proof.iter().map(Vec::as_slice)).unwrap();
+        partial.remove(&[0x00]).unwrap();
+        full.remove(&[0x00]).unwrap();
+        assert_eq!(partial.hash(), full.hash());
+    }
+
+    #[test]
+    fn account_proof() {
+        let address = [0x11; 20];
+        let slot = h("0000000000000000000000000000000000000000000000000000000000000001");
+        let mut value = B256::default();
+        value[31] = 42;
+
+        let mut storage = Trie::new();
+        storage
+            .insert(&keccak(slot), encode_storage_value(&value))
+            .unwrap();
+        let account = Account {
+            nonce: 1,
+            balance: value,
+            storage_root: storage.hash(),
+            ..Default::default()
+        };
+        assert_eq!(Account::decode(&account.encode()).unwrap(), account);
+
+        let mut state = Trie::new();
+        state.insert(&keccak(address), account.encode()).unwrap();
+        state
+            .insert(&keccak([0x22; 20]), Account::default().encode())
+            .unwrap();
+
+        let empty_slot = B256::default();
+        let proof = AccountProof {
+            address,
+            proof: state.proof(&keccak(address)).unwrap(),
+            storage: vec![
+                StorageProof {
+                    slot,
+                    proof: storage.proof(&keccak(slot)).unwrap(),
+                },
+                StorageProof {
+                    slot: empty_slot,
+                    proof: storage.proof(&keccak(empty_slot)).unwrap(),
+                },
+            ],
+        };
+        let verified = proof.verify(&state.hash()).unwrap();
+        assert_eq!(verified.account, Some(account));
+        assert_eq!(
+            verified.storage,
+            vec![(slot, value), (empty_slot, B256::default())]
+        );
+
+        // Missing accounts are proven with an exclusion proof.
+        let missing = [0x33; 20];
+        let proof = AccountProof {
+            address: missing,
+            proof: state.proof(&keccak(missing)).unwrap(),
+            storage: vec![StorageProof {
+                slot,
+                proof: vec![],
+            }],
+        };
+        let verified = proof.verify(&state.hash()).unwrap();
+        assert_eq!(verified.account, None);
+        assert_eq!(verified.storage, vec![(slot, B256::default())]);
+    }
+}
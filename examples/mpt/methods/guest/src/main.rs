@@ -0,0 +1,22 @@
+// Copyright 2024 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use mpt_core::{AccountProof, B256};
+use risc0_zkvm::guest::env;
+
+fn main() {
+    let (state_root, proof): (B256, AccountProof) = env::read();
+    let account = proof.verify(&state_root).unwrap();
+    env::commit(&(state_root, account));
+}
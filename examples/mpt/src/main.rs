@@ -0,0 +1,136 @@
+// Copyright 2024 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use clap::Parser;
+use ethers_core::types::{BlockId, BlockNumber};
+use ethers_providers::{Http, Provider};
+use mpt_core::{ethers::fetch_proof, AccountProof, VerifiedAccount, B256};
+use mpt_methods::VERIFY_ACCOUNT_ELF;
+use risc0_zkvm::{default_prover, ExecutorEnv};
+
+#[derive(Parser, Debug)]
+#[clap(author, version, about, long_about = None)]
+struct Args {
+    /// URL of an Ethereum RPC endpoint supporting `eth_getProof`.
+    #[clap(short, long)]
+    rpc_url: String,
+
+    /// Address of the account to prove.
+    #[clap(short, long)]
+    address: String,
+
+    /// Storage slots of the account to prove.
+    #[clap(short, long)]
+    slot: Vec<String>,
+
+    /// Block to take the proof at. Defaults to the latest block.
+    #[clap(short, long)]
+    block_numb: Option<u64>,
+}
+
+fn parse_hex<const N: usize>(value: &str) -> [u8; N] {
+    let bytes = hex::decode(value.trim_start_matches("0x")).expect("Invalid hex value");
+    let mut result = [0u8; N];
+    result[N.saturating_sub(bytes.len())..]
+        .copy_from_slice(&bytes[bytes.len().saturating_sub(N)..]);
+    result
+}
+
+#[tokio::main]
+async fn main() {
+    let args = Args::parse();
+    let address = parse_hex(&args.address);
+    let slots: Vec<[u8; 32]> = args.slot.iter().map(|slot| parse_hex(slot)).collect();
+    let block = match args.block_numb {
+        Some(numb) => BlockId::from(numb),
+        None => BlockId::from(BlockNumber::Latest),
+    };
+
+    let client = Provider::<Http>::try_from(args.rpc_url).expect("Invalid RPC url");
+    let (state_root, proof) = fetch_proof(&client, address, &slots, block)
+        .await
+        .expect("Failed to fetch the proof");
+
+    let (root, account) = tokio::task::spawn_blocking(move || verify(state_root, proof))
+        .await
+        .unwrap();
+
+    println!("State root: 0x{}", hex::encode(root));
+    match account.account {
+        Some(info) => {
+            println!("Account 0x{}", hex::encode(account.address));
+            println!("  nonce: {}", info.nonce);
+            println!("  balance: 0x{}", hex::encode(info.balance));
+            println!("  storage root: 0x{}", hex::encode(info.storage_root));
+            println!("  code hash: 0x{}", hex::encode(info.code_hash));
+        }
+        None => println!("Account 0x{} does not exist", hex::encode(account.address)),
+    }
+    for (slot, value) in account.storage {
+        println!("  0x{} = 0x{}", hex::encode(slot), hex::encode(value));
+    }
+}
+
+fn verify(state_root: B256, proof: AccountProof) -> (B256, VerifiedAccount) {
+    let env = ExecutorEnv::builder()
+        .write(&(state_root, proof))
+        .unwrap()
+        .build()
+        .unwrap();
+
+    // Obtain the default prover.
+    let prover = default_prover();
+
+    // Produce a receipt by proving the specified ELF binary.
+    let receipt = prover.prove(env, VERIFY_ACCOUNT_ELF).unwrap();
+
+    receipt.journal.decode().unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use mpt_core::{encode_storage_value, keccak, Account, AccountProof, StorageProof, Trie};
+
+    #[test]
+    fn verify_account() {
+        let address = [0x11; 20];
+        let slot = [0x01; 32];
+        let value = [0x02; 32];
+
+        let mut storage = Trie::new();
+        storage
+            .insert(&keccak(slot), encode_storage_value(&value))
+            .unwrap();
+        let account = Account {
+            nonce: 1,
+            storage_root: storage.hash(),
+            ..Default::default()
+        };
+        let mut state = Trie::new();
+        state.insert(&keccak(address), account.encode()).unwrap();
+
+        let proof = AccountProof {
+            address,
+            proof: state.proof(&keccak(address)).unwrap(),
+            storage: vec![StorageProof {
+                slot,
+                proof: storage.proof(&keccak(slot)).unwrap(),
+            }],
+        };
+        let (root, verified) = super::verify(state.hash(), proof);
+        assert_eq!(root, state.hash());
+        assert_eq!(verified.account, Some(account));
+        assert_eq!(verified.storage, vec![(slot, value)]);
+    }
+}
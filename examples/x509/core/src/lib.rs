@@ -0,0 +1,435 @@
+// Copyright 2024 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! X.509 certificate chain verification for use inside the zkVM guest.
+//!
+//! A chain is verified leaf first against a [RootStore] of trusted root
+//! certificates. The result is a [VerifiedSubject], which carries the digest
+//! of the root store so that a verifier of the receipt can check which set of
+//! roots the chain was validated against without the roots being committed
+//! in full.
+
+use p256::ecdsa::{
+    signature::Verifier as _, Signature as EcdsaSignature, VerifyingKey as EcdsaKey,
+};
+use risc0_zkvm::sha::{Digest, Impl, Sha256};
+use rsa::{
+    pkcs1::DecodeRsaPublicKey,
+    pkcs1v15::{Signature as RsaSignature, VerifyingKey as RsaKey},
+    RsaPublicKey,
+};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use x509_cert::{
+    der::{asn1::ObjectIdentifier, Decode, Encode},
+    ext::pkix::{BasicConstraints, KeyUsage},
+    Certificate,
+};
+
+const RSA_ENCRYPTION: ObjectIdentifier = ObjectIdentifier::new_unwrap("1.2.840.113549.1.1.1");
+const SHA_256_WITH_RSA_ENCRYPTION: ObjectIdentifier =
+    ObjectIdentifier::new_unwrap("1.2.840.113549.1.1.11");
+const ID_EC_PUBLIC_KEY: ObjectIdentifier = ObjectIdentifier::new_unwrap("1.2.840.10045.2.1");
+const SECP_256_R_1: ObjectIdentifier = ObjectIdentifier::new_unwrap("1.2.840.10045.3.1.7");
+const ECDSA_WITH_SHA_256: ObjectIdentifier = ObjectIdentifier::new_unwrap("1.2.840.10045.4.3.2");
+
+const ID_CE_SUBJECT_KEY_IDENTIFIER: ObjectIdentifier = ObjectIdentifier::new_unwrap("2.5.29.14");
+const ID_CE_KEY_USAGE: ObjectIdentifier = ObjectIdentifier::new_unwrap("2.5.29.15");
+const ID_CE_SUBJECT_ALT_NAME: ObjectIdentifier = ObjectIdentifier::new_unwrap("2.5.29.17");
+const ID_CE_BASIC_CONSTRAINTS: ObjectIdentifier = ObjectIdentifier::new_unwrap("2.5.29.19");
+const ID_CE_AUTHORITY_KEY_IDENTIFIER: ObjectIdentifier = ObjectIdentifier::new_unwrap("2.5.29.35");
+const ID_CE_EXT_KEY_USAGE: ObjectIdentifier = ObjectIdentifier::new_unwrap("2.5.29.37");
+
+/// Extensions this crate understands. Any other extension marked critical
+/// causes the certificate to be rejected, as required by RFC 5280.
+const KNOWN_EXTENSIONS: &[ObjectIdentifier] = &[
+    ID_CE_SUBJECT_KEY_IDENTIFIER,
+    ID_CE_KEY_USAGE,
+    ID_CE_SUBJECT_ALT_NAME,
+    ID_CE_BASIC_CONSTRAINTS,
+    ID_CE_AUTHORITY_KEY_IDENTIFIER,
+    ID_CE_EXT_KEY_USAGE,
+];
+
+#[derive(Error, Debug)]
+pub enum Error {
+    #[error("failed to decode certificate {index}: {source}")]
+    Decode {
+        index: usize,
+        source: x509_cert::der::Error,
+    },
+
+    #[error("failed to encode certificate {index}: {source}")]
+    Encode {
+        index: usize,
+        source: x509_cert::der::Error,
+    },
+
+    #[error("certificate chain is empty")]
+    EmptyChain,
+
+    #[error("issuer of certificate {0} does not match the subject of the next certificate")]
+    IssuerMismatch(usize),
+
+    #[error("certificate {0} is not valid at the given time")]
+    Expired(usize),
+
+    #[error("certificate {0} is not a certificate authority")]
+    NotCertificateAuthority(usize),
+
+    #[error("certificate {0} exceeds the path length constraint of its issuer")]
+    PathLengthExceeded(usize),
+
+    #[error("certificate {index} has an unsupported critical extension {oid}")]
+    UnsupportedCriticalExtension { index: usize, oid: ObjectIdentifier },
+
+    #[error("certificate {index} uses the unsupported signature algorithm {oid}")]
+    UnsupportedSignatureAlgorithm { index: usize, oid: ObjectIdentifier },
+
+    #[error("issuer of certificate {index} has an unsupported public key")]
+    UnsupportedPublicKey { index: usize },
+
+    #[error("signature on certificate {0} is invalid")]
+    InvalidSignature(usize),
+
+    #[error("chain does not terminate at a trusted root")]
+    UntrustedRoot,
+}
+
+/// Input to the guest, containing the chain to verify and the trusted roots.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct ChainInput {
+    /// DER-encoded certificates, leaf first. The root may be omitted.
+    pub chain: Vec<Vec<u8>>,
+    /// DER-encoded trusted root certificates.
+    pub roots: Vec<Vec<u8>>,
+    /// Verification time, in seconds since the UNIX epoch.
+    pub time: u64,
+}
+
+/// Claims about a leaf certificate whose chain was successfully verified.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct VerifiedSubject {
+    /// Digest of the root store the chain was verified against.
+    /// See [RootStore::digest].
+    pub root_store_digest: Digest,
+    /// Time at which the chain was verified, in seconds since the UNIX epoch.
+    pub verified_at: u64,
+    /// Distinguished name of the leaf subject, formatted as per RFC 4514.
+    pub subject: String,
+    /// Distinguished name of the leaf issuer, formatted as per RFC 4514.
+    pub issuer: String,
+    /// Big-endian serial number of the leaf certificate.
+    pub serial_number: Vec<u8>,
+    /// Start of the leaf validity period, in seconds since the UNIX epoch.
+    pub not_before: u64,
+    /// End of the leaf validity period, in seconds since the UNIX epoch.
+    pub not_after: u64,
+    /// SHA-256 digest of the DER-encoded leaf SubjectPublicKeyInfo.
+    pub public_key_digest: Digest,
+    /// Number of certificates in the verified path, including the root.
+    pub path_length: u32,
+}
+
+/// A set of trusted root certificates.
+pub struct RootStore {
+    roots: Vec<Certificate>,
+    digest: Digest,
+}
+
+impl RootStore {
+    /// Parse a root store from DER-encoded certificates.
+    pub fn from_der(roots: &[Vec<u8>]) -> Result<Self, Error> {
+        let mut digests: Vec<Digest> = roots.iter().map(|der| *Impl::hash_bytes(der)).collect();
+        digests.sort();
+        let mut concat = Vec::with_capacity(digests.len() * 32);
+        for digest in digests.iter() {
+            concat.extend_from_slice(digest.as_bytes());
+        }
+
+        let roots = roots
+            .iter()
+            .enumerate()
+            .map(|(index, der)| {
+                Certificate::from_der(der).map_err(|source| Error::Decode { index, source })
+            })
+            .collect::<Result<_, _>>()?;
+        Ok(Self {
+            roots,
+            digest: *Impl::hash_bytes(&concat),
+        })
+    }
+
+    /// Digest identifying this root store.
+    ///
+    /// It is the SHA-256 hash of the concatenated SHA-256 digests of each
+    /// DER-encoded root, sorted in ascending order, and so does not depend on
+    /// the order in which roots were supplied.
+    pub fn digest(&self) -> Digest {
+        self.digest
+    }
+}
+
+/// Verify a chain of DER-encoded certificates, leaf first, against the given
+/// root store at the given time, in seconds since the UNIX epoch.
+pub fn verify_chain(
+    chain: &[Vec<u8>],
+    roots: &RootStore,
+    time: u64,
+) -> Result<VerifiedSubject, Error> {
+    let certs = chain
+        .iter()
+        .enumerate()
+        .map(|(index, der)| {
+            Certificate::from_der(der).map_err(|source| Error::Decode { index, source })
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    let leaf = certs.first().ok_or(Error::EmptyChain)?;
+
+    for (index, cert) in certs.iter().enumerate() {
+        check_extensions(index, cert)?;
+        check_validity(index, cert, time)?;
+    }
+
+    // Each certificate must be issued by the one following it.
+    for (index, pair) in certs.windows(2).enumerate() {
+        let (cert, issuer) = (&pair[0], &pair[1]);
+        if cert.tbs_certificate.issuer != issuer.tbs_certificate.subject {
+            return Err(Error::IssuerMismatch(index));
+        }
+        check_issuer(index + 1, issuer, index)?;
+        verify_signature(index, cert, issuer)?;
+    }
+
+    // The last certificate must either be a trusted root, or be issued by one.
+    let index = certs.len() - 1;
+    let last = &certs[index];
+    let mut path_length = certs.len();
+    if !roots.roots.contains(last) {
+        let root = roots
+            .roots
+            .iter()
+            .find(|root| {
+                root.tbs_certificate.subject == last.tbs_certificate.issuer
+                    && verify_signature(index, last, root).is_ok()
+            })
+            .ok_or(Error::UntrustedRoot)?;
+        check_validity(index + 1, root, time)?;
+        check_issuer(index + 1, root, index)?;
+        path_length += 1;
+    }
+
+    let tbs = &leaf.tbs_certificate;
+    let spki = tbs
+        .subject_public_key_info
+        .to_der()
+        .map_err(|source| Error::Encode { index: 0, source })?;
+    Ok(VerifiedSubject {
+        root_store_digest: roots.digest(),
+        verified_at: time,
+        subject: tbs.subject.to_string(),
+        issuer: tbs.issuer.to_string(),
+        serial_number: tbs.serial_number.as_bytes().to_vec(),
+        not_before: tbs.validity.not_before.to_unix_duration().as_secs(),
+        not_after: tbs.validity.not_after.to_unix_duration().as_secs(),
+        public_key_digest: *Impl::hash_bytes(&spki),
+        path_length: path_length as u32,
+    })
+}
+
+fn check_validity(index: usize, cert: &Certificate, time: u64) -> Result<(), Error> {
+    let validity = &cert.tbs_certificate.validity;
+    let not_before = validity.not_before.to_unix_duration().as_secs();
+    let not_after = validity.not_after.to_unix_duration().as_secs();
+    if time < not_before || time > not_after {
+        return Err(Error::Expired(index));
+    }
+    Ok(())
+}
+
+fn check_extensions(index: usize, cert: &Certificate) -> Result<(), Error> {
+    for ext in cert.tbs_certificate.extensions.iter().flatten() {
+        if ext.critical && !KNOWN_EXTENSIONS.contains(&ext.extn_id) {
+            return Err(Error::UnsupportedCriticalExtension {
+                index,
+                oid: ext.extn_id,
+            });
+        }
+    }
+    Ok(())
+}
+
+/// Check that `issuer`, at position `index` in the path, may issue
+/// certificates with `below` intermediate certificates between it and the
+/// leaf.
+fn check_issuer(index: usize, issuer: &Certificate, below: usize) -> Result<(), Error> {
+    let mut is_ca = false;
+    for ext in issuer.tbs_certificate.extensions.iter().flatten() {
+        let value = ext.extn_value.as_bytes();
+        if ext.extn_id == ID_CE_BASIC_CONSTRAINTS {
+            let constraints = BasicConstraints::from_der(value)
+                .map_err(|source| Error::Decode { index, source })?;
+            is_ca = constraints.ca;
+            if let Some(max) = constraints.path_len_constraint {
+                if below > max as usize {
+                    return Err(Error::PathLengthExceeded(index));
+                }
+            }
+        } else if ext.extn_id == ID_CE_KEY_USAGE {
+            let usage =
+                KeyUsage::from_der(value).map_err(|source| Error::Decode { index, source })?;
+            if !usage.key_cert_sign() {
+                return Err(Error::NotCertificateAuthority(index));
+            }
+        }
+    }
+    if !is_ca {
+        return Err(Error::NotCertificateAuthority(index));
+    }
+    Ok(())
+}
+
+fn verify_signature(index: usize, cert: &Certificate, issuer: &Certificate) -> Result<(), Error> {
+    let message = cert
+        .tbs_certificate
+        .to_der()
+        .map_err(|source| Error::Encode { index, source })?;
+    let signature = cert
+        .signature
+        .as_bytes()
+        .ok_or(Error::InvalidSignature(index))?;
+    let spki = &issuer.tbs_certificate.subject_public_key_info;
+    let key = spki
+        .subject_public_key
+        .as_bytes()
+        .ok_or(Error::UnsupportedPublicKey { index })?;
+
+    match cert.signature_algorithm.oid {
+        SHA_256_WITH_RSA_ENCRYPTION => {
+            if spki.algorithm.oid != RSA_ENCRYPTION {
+                return Err(Error::UnsupportedPublicKey { index });
+            }
+            let key = RsaPublicKey::from_pkcs1_der(key)
+                .map_err(|_| Error::UnsupportedPublicKey { index })?;
+            let signature =
+                RsaSignature::try_from(signature).map_err(|_| Error::InvalidSignature(index))?;
+            RsaKey::<sha2::Sha256>::new(key)
+                .verify(&message, &signature)
+                .map_err(|_| Error::InvalidSignature(index))
+        }
+        ECDSA_WITH_SHA_256 => {
+            let curve = spki
+                .algorithm
+                .parameters
+                .as_ref()
+                .and_then(|params| params.decode_as::<ObjectIdentifier>().ok());
+            if spki.algorithm.oid != ID_EC_PUBLIC_KEY || curve != Some(SECP_256_R_1) {
+                return Err(Error::UnsupportedPublicKey { index });
+            }
+            let key = EcdsaKey::from_sec1_bytes(key)
+                .map_err(|_| Error::UnsupportedPublicKey { index })?;
+            let signature =
+                EcdsaSignature::from_der(signature).map_err(|_| Error::InvalidSignature(index))?;
+            key.verify(&message, &signature)
+                .map_err(|_| Error::InvalidSignature(index))
+        }
+        oid => Err(Error::UnsupportedSignatureAlgorithm { index, oid }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ROOT: &[u8] = include_bytes!("../testdata/root.der");
+    const INTERMEDIATE: &[u8] = include_bytes!("../testdata/intermediate.der");
+    const LEAF: &[u8] = include_bytes!("../testdata/leaf.der");
+
+    // 2025-01-01T00:00:00Z
+    const NOW: u64 = 1735689600;
+
+    fn chain() -> Vec<Vec<u8>> {
+        vec![LEAF.to_vec(), INTERMEDIATE.to_vec()]
+    }
+
+    fn roots() -> RootStore {
+        RootStore::from_der(&[ROOT.to_vec()]).unwrap()
+    }
+
+    #[test]
+    fn verify_valid_chain() {
+        let subject = verify_chain(&chain(), &roots(), NOW).unwrap();
+        assert_eq!(subject.subject, "CN=device.example.com,O=Example Corp,C=US");
+        assert_eq!(
+            subject.issuer,
+            "CN=Test Intermediate CA,O=RISC Zero Test,C=US"
+        );
+        assert_eq!(subject.serial_number, vec![3]);
+        assert_eq!(subject.root_store_digest, roots().digest());
+        assert_eq!(subject.path_length, 3);
+    }
+
+    #[test]
+    fn verify_chain_including_root() {
+        let mut chain = chain();
+        chain.push(ROOT.to_vec());
+        let subject = verify_chain(&chain, &roots(), NOW).unwrap();
+        assert_eq!(subject.path_length, 3);
+    }
+
+    #[test]
+    fn root_store_digest_is_order_independent() {
+        let a = RootStore::from_der(&[ROOT.to_vec(), INTERMEDIATE.to_vec()]).unwrap();
+        let b = RootStore::from_der(&[INTERMEDIATE.to_vec(), ROOT.to_vec()]).unwrap();
+        assert_eq!(a.digest(), b.digest());
+        assert_ne!(a.digest(), roots().digest());
+    }
+
+    #[test]
+    fn reject_untrusted_root() {
+        let roots = RootStore::from_der(&[]).unwrap();
+        assert!(matches!(
+            verify_chain(&chain(), &roots, NOW),
+            Err(Error::UntrustedRoot)
+        ));
+    }
+
+    #[test]
+    fn reject_expired() {
+        // 2030-01-01T00:00:00Z, after the leaf has expired.
+        assert!(matches!(
+            verify_chain(&chain(), &roots(), 1893456000),
+            Err(Error::Expired(0))
+        ));
+    }
+
+    #[test]
+    fn reject_tampered_leaf() {
+        let mut leaf = LEAF.to_vec();
+        // Flip a bit inside the subject common name.
+        let pos = leaf.windows(6).position(|w| w == b"device").unwrap();
+        leaf[pos] ^= 1;
+        let chain = vec![leaf, INTERMEDIATE.to_vec()];
+        assert!(matches!(
+            verify_chain(&chain, &roots(), NOW),
+            Err(Error::InvalidSignature(0))
+        ));
+    }
+
+    #[test]
+    fn reject_leaf_as_issuer() {
+        let chain = vec![LEAF.to_vec(), LEAF.to_vec()];
+        assert!(verify_chain(&chain, &roots(), NOW).is_err());
+    }
+}
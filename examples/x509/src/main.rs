@@ -0,0 +1,73 @@
+// Copyright 2024 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use risc0_zkvm::{default_prover, ExecutorEnv};
+use x509_core::{ChainInput, RootStore, VerifiedSubject};
+use x509_methods::VERIFY_CHAIN_ELF;
+
+const ROOT: &[u8] = include_bytes!("../core/testdata/root.der");
+const INTERMEDIATE: &[u8] = include_bytes!("../core/testdata/intermediate.der");
+const LEAF: &[u8] = include_bytes!("../core/testdata/leaf.der");
+
+// 2025-01-01T00:00:00Z
+const NOW: u64 = 1735689600;
+
+fn main() {
+    let input = ChainInput {
+        chain: vec![LEAF.to_vec(), INTERMEDIATE.to_vec()],
+        roots: vec![ROOT.to_vec()],
+        time: NOW,
+    };
+    let subject = verify(&input);
+
+    // The verifier only needs the digest of the root store it trusts.
+    let roots = RootStore::from_der(&input.roots).unwrap();
+    assert_eq!(subject.root_store_digest, roots.digest());
+
+    println!("Verified certificate chain for {}", subject.subject);
+    println!("  issued by {}", subject.issuer);
+    println!("  public key digest {}", subject.public_key_digest);
+}
+
+fn verify(input: &ChainInput) -> VerifiedSubject {
+    let env = ExecutorEnv::builder()
+        .write(input)
+        .unwrap()
+        .build()
+        .unwrap();
+
+    // Obtain the default prover.
+    let prover = default_prover();
+
+    // Produce a receipt by proving the specified ELF binary.
+    let receipt = prover.prove(env, VERIFY_CHAIN_ELF).unwrap();
+
+    receipt.journal.decode().unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use x509_core::ChainInput;
+
+    #[test]
+    fn main() {
+        let input = ChainInput {
+            chain: vec![super::LEAF.to_vec(), super::INTERMEDIATE.to_vec()],
+            roots: vec![super::ROOT.to_vec()],
+            time: super::NOW,
+        };
+        let subject = super::verify(&input);
+        assert_eq!(subject.subject, "CN=device.example.com,O=Example Corp,C=US");
+    }
+}
@@ -0,0 +1,104 @@
+// Copyright 2024 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use alloc::vec::Vec;
+use core::marker::PhantomData;
+
+use risc0_core::field::baby_bear::BabyBearElem;
+use risc0_zkp::core::{
+    digest::Digest,
+    hash::{poseidon2::unpadded_hash, sha},
+};
+
+/// Hash function of the leaves and nodes of a Merkle tree.
+///
+/// Leaf and node hashes are domain separated, so that the digest of a node
+/// cannot be passed off as the digest of a leaf.
+pub trait MerkleHash {
+    /// Hash a leaf value.
+    fn hash_leaf(value: &[u8]) -> Digest;
+
+    /// Hash the children of a node.
+    fn hash_node(left: &Digest, right: &Digest) -> Digest;
+}
+
+/// Poseidon2 over the Baby Bear field.
+///
+/// Values are packed three bytes per field element, after a domain tag and the
+/// length of the value. Nodes hash a domain tag followed by the sixteen field
+/// elements of their children.
+#[derive(Clone, Copy, Debug)]
+pub struct Poseidon2;
+
+impl Poseidon2 {
+    const LEAF_TAG: BabyBearElem = BabyBearElem::new(0);
+    const NODE_TAG: BabyBearElem = BabyBearElem::new(1);
+}
+
+impl MerkleHash for Poseidon2 {
+    fn hash_leaf(value: &[u8]) -> Digest {
+        let len = u32::try_from(value.len())
+            .ok()
+            .filter(|len| *len < risc0_core::field::baby_bear::P)
+            .expect("leaf value too large");
+        let mut elems = Vec::with_capacity(2 + value.len().div_ceil(3));
+        elems.push(Self::LEAF_TAG);
+        elems.push(BabyBearElem::new(len));
+        elems.extend(value.chunks(3).map(|chunk| {
+            let mut bytes = [0u8; 4];
+            bytes[..chunk.len()].copy_from_slice(chunk);
+            BabyBearElem::new(u32::from_le_bytes(bytes))
+        }));
+        to_digest(unpadded_hash(elems.iter()))
+    }
+
+    fn hash_node(left: &Digest, right: &Digest) -> Digest {
+        let elems: Vec<BabyBearElem> = core::iter::once(Self::NODE_TAG)
+            .chain(
+                left.as_words()
+                    .iter()
+                    .chain(right.as_words())
+                    .map(|word| BabyBearElem::new_raw(*word)),
+            )
+            .collect();
+        to_digest(unpadded_hash(elems.iter()))
+    }
+}
+
+fn to_digest(elems: [BabyBearElem; 8]) -> Digest {
+    Digest::new(elems.map(|elem| elem.as_u32_montgomery()))
+}
+
+/// SHA-256, with the given implementation.
+///
+/// Use `risc0_zkvm::sha::Impl` for the accelerated implementation in the
+/// guest. Leaves are hashed as the standard SHA-256 of a zero byte followed by
+/// the value, and nodes with a single application of the compression function
+/// to the pair of children.
+pub struct Sha256<S> {
+    phantom: PhantomData<S>,
+}
+
+impl<S: sha::Sha256> MerkleHash for Sha256<S> {
+    fn hash_leaf(value: &[u8]) -> Digest {
+        let mut bytes = Vec::with_capacity(1 + value.len());
+        bytes.push(0);
+        bytes.extend_from_slice(value);
+        *S::hash_bytes(&bytes)
+    }
+
+    fn hash_node(left: &Digest, right: &Digest) -> Digest {
+        *S::hash_pair(left, right)
+    }
+}
@@ -0,0 +1,242 @@
+// Copyright 2024 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+#![doc = include_str!("../README.md")]
+#![cfg_attr(not(feature = "std"), no_std)]
+#![deny(missing_docs)]
+
+extern crate alloc;
+
+mod hash;
+mod redact;
+mod tree;
+
+use alloc::vec::Vec;
+
+pub use risc0_zkp::core::digest::Digest;
+use serde::{Deserialize, Serialize};
+
+pub use self::{
+    hash::{MerkleHash, Poseidon2, Sha256},
+    redact::{JournalEntry, RedactedJournal},
+    tree::{Accumulator, VectorCommitment},
+};
+
+/// Commitment to the leaves of a Merkle tree.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct Commitment {
+    /// Root of the tree, or [Digest::ZERO] if it has no leaves.
+    pub root: Digest,
+    /// Number of leaves of the tree.
+    pub len: u32,
+}
+
+/// Path from a leaf to the root of a Merkle tree.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MerkleProof {
+    /// Index of the leaf.
+    pub index: u32,
+    /// Siblings of the nodes on the path, from the leaf up.
+    pub siblings: Vec<Digest>,
+}
+
+/// Witness that a value is not a member of an [Accumulator].
+///
+/// Holds the leaves of the accumulator immediately below and above the leaf
+/// hash of the value, if any.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NonMembershipProof {
+    /// Largest leaf below the leaf hash of the value, and its path.
+    pub lower: Option<(Digest, MerkleProof)>,
+    /// Smallest leaf above the leaf hash of the value, and its path.
+    pub upper: Option<(Digest, MerkleProof)>,
+}
+
+impl MerkleProof {
+    /// Root of the tree with `leaf` at the index of this proof.
+    pub fn root<H: MerkleHash>(&self, leaf: &Digest) -> Digest {
+        let mut node = *leaf;
+        for (level, sibling) in self.siblings.iter().enumerate() {
+            node = match (self.index >> level) & 1 {
+                0 => H::hash_node(&node, sibling),
+                _ => H::hash_node(sibling, &node),
+            };
+        }
+        node
+    }
+}
+
+impl Commitment {
+    /// Depth of a tree with `len` leaves.
+    fn depth(len: u32) -> usize {
+        len.max(1).next_power_of_two().trailing_zeros() as usize
+    }
+
+    /// Returns true if `leaf` is the leaf of the committed tree at the index
+    /// of `proof`.
+    pub fn verify_leaf<H: MerkleHash>(&self, leaf: &Digest, proof: &MerkleProof) -> bool {
+        proof.index < self.len
+            && proof.siblings.len() == Self::depth(self.len)
+            && proof.root::<H>(leaf) == self.root
+    }
+
+    /// Returns true if `value` is the value of a [VectorCommitment] at the
+    /// index of `proof`.
+    pub fn verify<H: MerkleHash>(&self, value: impl AsRef<[u8]>, proof: &MerkleProof) -> bool {
+        self.verify_leaf::<H>(&H::hash_leaf(value.as_ref()), proof)
+    }
+
+    /// Returns true if `journal` holds the entries of a [VectorCommitment] with
+    /// this commitment, some of which may be redacted.
+    pub fn verify_redacted<H: MerkleHash>(&self, journal: &RedactedJournal) -> bool {
+        journal.commitment::<H>() == *self
+    }
+
+    /// Returns true if `value` is a member of an [Accumulator].
+    pub fn verify_membership<H: MerkleHash>(
+        &self,
+        value: impl AsRef<[u8]>,
+        proof: &MerkleProof,
+    ) -> bool {
+        self.verify::<H>(value, proof)
+    }
+
+    /// Returns true if `value` is not a member of an [Accumulator].
+    pub fn verify_non_membership<H: MerkleHash>(
+        &self,
+        value: impl AsRef<[u8]>,
+        proof: &NonMembershipProof,
+    ) -> bool {
+        let key = H::hash_leaf(value.as_ref());
+        if let Some((lower, path)) = &proof.lower {
+            if !(*lower < key && self.verify_leaf::<H>(lower, path)) {
+                return false;
+            }
+        }
+        if let Some((upper, path)) = &proof.upper {
+            if !(key < *upper && self.verify_leaf::<H>(upper, path)) {
+                return false;
+            }
+        }
+        match (&proof.lower, &proof.upper) {
+            (Some((_, lower)), Some((_, upper))) => lower.index.checked_add(1) == Some(upper.index),
+            (Some((_, lower)), None) => lower.index == self.len - 1,
+            (None, Some((_, upper))) => upper.index == 0,
+            (None, None) => self.len == 0,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use risc0_zkp::core::hash::sha::cpu;
+
+    use super::*;
+
+    fn accumulator<H: MerkleHash>() {
+        let values: Vec<_> = (0..13u32).map(|i| i.to_le_bytes()).collect();
+        let set = Accumulator::<H>::new(&values);
+        let commitment = set.commitment();
+        assert_eq!(commitment.len, 13);
+
+        for value in &values {
+            let proof = set.prove_membership(value).unwrap();
+            assert!(commitment.verify_membership::<H>(value, &proof));
+            assert!(set.prove_non_membership(value).is_none());
+        }
+        for value in (13..40u32).map(|i| i.to_le_bytes()) {
+            assert!(set.prove_membership(value).is_none());
+            let proof = set.prove_non_membership(value).unwrap();
+            assert!(commitment.verify_non_membership::<H>(value, &proof));
+            assert!(!commitment.verify_non_membership::<H>(values[0], &proof));
+        }
+
+        // Dropping a neighbour must not prove non-membership of a member.
+        let member = values[5];
+        let proof = set.prove_membership(member).unwrap();
+        let key = H::hash_leaf(&member);
+        let forged = NonMembershipProof {
+            lower: None,
+            upper: Some((key, proof)),
+        };
+        assert!(!commitment.verify_non_membership::<H>(member, &forged));
+    }
+
+    #[test]
+    fn accumulator_poseidon2() {
+        accumulator::<Poseidon2>();
+    }
+
+    #[test]
+    fn accumulator_sha256() {
+        accumulator::<Sha256<cpu::Impl>>();
+    }
+
+    #[test]
+    fn empty_accumulator() {
+        let set = Accumulator::<Poseidon2>::new(Vec::<Vec<u8>>::new());
+        let commitment = set.commitment();
+        assert_eq!(commitment.root, Digest::ZERO);
+        let proof = set.prove_non_membership(b"a").unwrap();
+        assert!(commitment.verify_non_membership::<Poseidon2>(b"a", &proof));
+    }
+
+    #[test]
+    fn vector_commitment() {
+        let mut vector = VectorCommitment::<Poseidon2>::new(["a", "b", "c", "d", "e"]);
+        let commitment = vector.commitment();
+        let proof = vector.prove(2).unwrap();
+        assert!(commitment.verify::<Poseidon2>("c", &proof));
+        assert!(!commitment.verify::<Poseidon2>("d", &proof));
+        assert!(vector.prove(5).is_none());
+
+        vector.set(2, "z");
+        let updated = vector.commitment();
+        assert_ne!(updated, commitment);
+        let proof = vector.prove(2).unwrap();
+        assert!(updated.verify::<Poseidon2>("z", &proof));
+        assert_eq!(
+            updated,
+            VectorCommitment::<Poseidon2>::new(["a", "b", "z", "d", "e"]).commitment()
+        );
+    }
+
+    #[test]
+    fn redacted_journal() {
+        let entries = ["name: alice", "ssn: 123-45-6789", "balance: 100"];
+        let commitment = VectorCommitment::<Poseidon2>::new(entries).commitment();
+
+        let mut journal = RedactedJournal::new(entries);
+        assert!(journal.redact::<Poseidon2>(1));
+        assert!(!journal.redact::<Poseidon2>(3));
+        assert_eq!(journal.revealed(0), Some(b"name: alice".as_slice()));
+        assert_eq!(journal.revealed(1), None);
+        assert!(commitment.verify_redacted::<Poseidon2>(&journal));
+
+        // Altering a revealed entry, or dropping one, is detected.
+        let mut altered = journal.clone();
+        altered.entries[2] = JournalEntry::Revealed(b"balance: 1000".to_vec());
+        assert!(!commitment.verify_redacted::<Poseidon2>(&altered));
+        let mut truncated = journal.clone();
+        truncated.entries.pop();
+        assert!(!commitment.verify_redacted::<Poseidon2>(&truncated));
+    }
+
+    #[test]
+    fn leaf_encoding() {
+        // Trailing zero bytes must not collide with shorter values.
+        assert_ne!(Poseidon2::hash_leaf(b"a"), Poseidon2::hash_leaf(b"a\0"));
+        assert_ne!(Poseidon2::hash_leaf(b""), Poseidon2::hash_leaf(b"\0\0\0"));
+    }
+}
@@ -0,0 +1,93 @@
+// Copyright 2024 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use alloc::vec::Vec;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{tree::MerkleTree, Commitment, Digest, MerkleHash};
+
+/// Entry of a [RedactedJournal].
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum JournalEntry {
+    /// The value of the entry.
+    Revealed(Vec<u8>),
+    /// The leaf hash of the value of the entry, which is withheld.
+    Redacted(Digest),
+}
+
+/// Entries of a journal committed to with a [VectorCommitment](crate::VectorCommitment),
+/// some of which may be redacted.
+///
+/// A guest commits to its journal entries by writing their [Commitment] to its
+/// journal, rather than the entries themselves. The holder of the receipt and
+/// of the entries can share both with an auditor, replacing the entries the
+/// auditor must not see with their leaf hashes. The auditor verifies the
+/// receipt, then checks the redacted journal against the commitment decoded
+/// from the receipt journal with [Commitment::verify_redacted].
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RedactedJournal {
+    /// The entries, in order.
+    pub entries: Vec<JournalEntry>,
+}
+
+impl RedactedJournal {
+    /// Reveals all of `entries`.
+    pub fn new<I>(entries: I) -> Self
+    where
+        I: IntoIterator,
+        I::Item: AsRef<[u8]>,
+    {
+        let entries = entries
+            .into_iter()
+            .map(|entry| JournalEntry::Revealed(entry.as_ref().to_vec()))
+            .collect();
+        Self { entries }
+    }
+
+    /// Replaces the entry at `index` with its leaf hash.
+    ///
+    /// Returns false if there is no entry at `index`.
+    pub fn redact<H: MerkleHash>(&mut self, index: u32) -> bool {
+        let Some(entry) = self.entries.get_mut(index as usize) else {
+            return false;
+        };
+        if let JournalEntry::Revealed(value) = entry {
+            *entry = JournalEntry::Redacted(H::hash_leaf(value));
+        }
+        true
+    }
+
+    /// Returns the value of the entry at `index`, if it is revealed.
+    pub fn revealed(&self, index: u32) -> Option<&[u8]> {
+        match self.entries.get(index as usize)? {
+            JournalEntry::Revealed(value) => Some(value),
+            JournalEntry::Redacted(_) => None,
+        }
+    }
+
+    /// Commitment to the entries, computed from the revealed values and the
+    /// leaf hashes of the redacted ones.
+    pub fn commitment<H: MerkleHash>(&self) -> Commitment {
+        let leaves = self
+            .entries
+            .iter()
+            .map(|entry| match entry {
+                JournalEntry::Revealed(value) => H::hash_leaf(value),
+                JournalEntry::Redacted(leaf) => *leaf,
+            })
+            .collect();
+        MerkleTree::<H>::new(leaves).commitment()
+    }
+}
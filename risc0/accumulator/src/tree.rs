@@ -0,0 +1,224 @@
+// Copyright 2024 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use alloc::{vec, vec::Vec};
+use core::marker::PhantomData;
+
+use crate::{Commitment, Digest, MerkleHash, MerkleProof, NonMembershipProof};
+
+/// Complete Merkle tree, padded to a power of two leaves with [Digest::ZERO].
+pub(crate) struct MerkleTree<H> {
+    len: u32,
+    /// Layers of the tree, from the padded leaves up to the root.
+    layers: Vec<Vec<Digest>>,
+    phantom: PhantomData<H>,
+}
+
+impl<H: MerkleHash> MerkleTree<H> {
+    pub(crate) fn new(mut leaves: Vec<Digest>) -> Self {
+        let len = u32::try_from(leaves.len()).expect("too many leaves");
+        leaves.resize(leaves.len().max(1).next_power_of_two(), Digest::ZERO);
+        let mut layers = vec![leaves];
+        while layers.last().unwrap().len() > 1 {
+            let layer = layers
+                .last()
+                .unwrap()
+                .chunks(2)
+                .map(|pair| H::hash_node(&pair[0], &pair[1]))
+                .collect();
+            layers.push(layer);
+        }
+        Self {
+            len,
+            layers,
+            phantom: PhantomData,
+        }
+    }
+
+    pub(crate) fn commitment(&self) -> Commitment {
+        Commitment {
+            root: match self.len {
+                0 => Digest::ZERO,
+                _ => self.layers.last().unwrap()[0],
+            },
+            len: self.len,
+        }
+    }
+
+    fn leaf(&self, index: u32) -> Option<&Digest> {
+        if index >= self.len {
+            return None;
+        }
+        self.layers[0].get(index as usize)
+    }
+
+    fn prove(&self, index: u32) -> Option<MerkleProof> {
+        self.leaf(index)?;
+        let siblings = self.layers[..self.layers.len() - 1]
+            .iter()
+            .enumerate()
+            .map(|(level, layer)| layer[(index as usize >> level) ^ 1])
+            .collect();
+        Some(MerkleProof { index, siblings })
+    }
+
+    fn set(&mut self, index: u32, leaf: Digest) {
+        let mut idx = index as usize;
+        self.layers[0][idx] = leaf;
+        for level in 1..self.layers.len() {
+            let left = idx & !1;
+            let node = H::hash_node(
+                &self.layers[level - 1][left],
+                &self.layers[level - 1][left + 1],
+            );
+            idx >>= 1;
+            self.layers[level][idx] = node;
+        }
+    }
+}
+
+/// Host-side state of a commitment to an ordered list of values.
+///
+/// Generates the [MerkleProof] of the value at an index, checked in the guest
+/// with [Commitment::verify].
+pub struct VectorCommitment<H> {
+    tree: MerkleTree<H>,
+}
+
+impl<H: MerkleHash> VectorCommitment<H> {
+    /// Commit to `values`, in order.
+    pub fn new<I>(values: I) -> Self
+    where
+        I: IntoIterator,
+        I::Item: AsRef<[u8]>,
+    {
+        let leaves = values
+            .into_iter()
+            .map(|value| H::hash_leaf(value.as_ref()))
+            .collect();
+        Self {
+            tree: MerkleTree::new(leaves),
+        }
+    }
+
+    /// Number of values.
+    pub fn len(&self) -> u32 {
+        self.tree.len
+    }
+
+    /// Returns true if there are no values.
+    pub fn is_empty(&self) -> bool {
+        self.tree.len == 0
+    }
+
+    /// Commitment to the current values.
+    pub fn commitment(&self) -> Commitment {
+        self.tree.commitment()
+    }
+
+    /// Proof of the value at `index`, or [None] if it is out of bounds.
+    pub fn prove(&self, index: u32) -> Option<MerkleProof> {
+        self.tree.prove(index)
+    }
+
+    /// Replace the value at `index`, rehashing only its path to the root.
+    ///
+    /// Panics if `index` is out of bounds.
+    pub fn set(&mut self, index: u32, value: impl AsRef<[u8]>) {
+        assert!(index < self.tree.len, "index out of bounds");
+        self.tree.set(index, H::hash_leaf(value.as_ref()));
+    }
+}
+
+/// Host-side state of an accumulator of a set of values.
+///
+/// The leaves of the tree are the sorted leaf hashes of the values, so that
+/// the absence of a value is witnessed by the two adjacent leaves around its
+/// leaf hash. Proofs are checked in the guest with
+/// [Commitment::verify_membership] and [Commitment::verify_non_membership].
+pub struct Accumulator<H> {
+    tree: MerkleTree<H>,
+}
+
+impl<H: MerkleHash> Accumulator<H> {
+    /// Accumulate `values`, ignoring duplicates.
+    pub fn new<I>(values: I) -> Self
+    where
+        I: IntoIterator,
+        I::Item: AsRef<[u8]>,
+    {
+        let mut leaves: Vec<Digest> = values
+            .into_iter()
+            .map(|value| H::hash_leaf(value.as_ref()))
+            .collect();
+        leaves.sort_unstable();
+        leaves.dedup();
+        Self {
+            tree: MerkleTree::new(leaves),
+        }
+    }
+
+    /// Add `values` to the set, rebuilding the tree once.
+    pub fn extend<I>(&mut self, values: I)
+    where
+        I: IntoIterator,
+        I::Item: AsRef<[u8]>,
+    {
+        let mut leaves = self.leaves().to_vec();
+        leaves.extend(values.into_iter().map(|value| H::hash_leaf(value.as_ref())));
+        leaves.sort_unstable();
+        leaves.dedup();
+        self.tree = MerkleTree::new(leaves);
+    }
+
+    /// Number of values in the set.
+    pub fn len(&self) -> u32 {
+        self.tree.len
+    }
+
+    /// Returns true if the set is empty.
+    pub fn is_empty(&self) -> bool {
+        self.tree.len == 0
+    }
+
+    /// Commitment to the current set.
+    pub fn commitment(&self) -> Commitment {
+        self.tree.commitment()
+    }
+
+    /// Proof that `value` is a member of the set, or [None] if it is not.
+    pub fn prove_membership(&self, value: impl AsRef<[u8]>) -> Option<MerkleProof> {
+        let key = H::hash_leaf(value.as_ref());
+        let index = self.leaves().binary_search(&key).ok()?;
+        self.tree.prove(index as u32)
+    }
+
+    /// Proof that `value` is not a member of the set, or [None] if it is.
+    pub fn prove_non_membership(&self, value: impl AsRef<[u8]>) -> Option<NonMembershipProof> {
+        let key = H::hash_leaf(value.as_ref());
+        let index = self.leaves().binary_search(&key).err()? as u32;
+        let neighbour = |index: u32| {
+            let leaf = *self.tree.leaf(index)?;
+            Some((leaf, self.tree.prove(index)?))
+        };
+        Some(NonMembershipProof {
+            lower: index.checked_sub(1).and_then(neighbour),
+            upper: neighbour(index),
+        })
+    }
+
+    fn leaves(&self) -> &[Digest] {
+        &self.tree.layers[0][..self.tree.len as usize]
+    }
+}
@@ -0,0 +1,336 @@
+// Copyright 2024 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! On-disk cache of hashed [MemoryImage]s.
+//!
+//! The cache is opt-in: [MemoryImage::new] and [crate::compute_image_id] never
+//! use it, only [MemoryImage::load_cached] and [MemoryImage::load_cached_in]
+//! do.
+//!
+//! Each image is stored in a file named after the SHA-256 digest of its ELF,
+//! with the following little-endian layout:
+//!
+//! ```text
+//! magic: [u8; 8] | elf_digest: [u8; 32] | image_id: [u8; 32] | body_digest: [u8; 32] | body
+//! body: page_size: u32 | pc: u32 | num_pages: u32 | (page_idx: u32 | page: [u8; page_size])*
+//! ```
+//!
+//! where `body_digest` is the SHA-256 digest of `body` and `image_id` is the
+//! image ID of the decoded image, which is recomputed on load. A file which
+//! does not match this layout, the ELF or either digest is ignored and
+//! overwritten. These checks detect corrupted entries, not forged ones: anyone
+//! who can write to the cache directory can change the image which is run, so
+//! the directory must only be writable by the current user.
+//!
+//! Once the entries in the cache directory exceed [MAX_CACHE_SIZE] bytes, the
+//! oldest ones are evicted when storing a new one.
+
+use std::{
+    collections::BTreeMap,
+    fs,
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::SystemTime,
+};
+
+use anyhow::{bail, ensure, Context, Result};
+use risc0_zkp::core::{
+    digest::{Digest, DIGEST_BYTES},
+    hash::sha::{cpu::Impl, Sha256},
+};
+use risc0_zkvm_platform::{
+    memory::{GUEST_MAX_MEM, PAGE_TABLE},
+    PAGE_SIZE,
+};
+
+use crate::{MemoryImage, PageTableInfo, Program};
+
+const MAGIC: &[u8; 8] = b"R0IMAGE2";
+const HEADER_SIZE: usize = MAGIC.len() + 3 * DIGEST_BYTES;
+const EXTENSION: &str = "img";
+
+/// The total size in bytes of the entries kept in an image cache directory.
+const MAX_CACHE_SIZE: u64 = 1 << 30;
+
+impl MemoryImage {
+    /// Construct the initial memory image for the ELF binary `elf`, using the
+    /// image cache.
+    ///
+    /// The cache lives in the directory given by the `RISC0_IMAGE_CACHE_DIR`
+    /// environment variable, or in `risc0/images` under the cache directory of
+    /// the current user. If neither is available, the image is constructed
+    /// without caching. See [MemoryImage::load_cached_in].
+    pub fn load_cached(elf: &[u8]) -> Result<Self> {
+        let cache_dir = std::env::var_os("RISC0_IMAGE_CACHE_DIR")
+            .map(PathBuf::from)
+            .or_else(|| dirs::cache_dir().map(|dir| dir.join("risc0").join("images")));
+        match cache_dir {
+            Some(cache_dir) => Self::load_cached_in(elf, &cache_dir),
+            None => load_uncached(elf),
+        }
+    }
+
+    /// Construct the initial memory image for the ELF binary `elf`, using the
+    /// image cache in `cache_dir`.
+    ///
+    /// The image is loaded from the cache if it holds a valid entry for `elf`.
+    /// Otherwise, the image is constructed and hashed, and stored in the
+    /// cache, evicting the oldest entries once the cache exceeds 1 GiB.
+    /// Failing to write the cache is not an error.
+    pub fn load_cached_in(elf: &[u8], cache_dir: &Path) -> Result<Self> {
+        let elf_digest = *Impl::hash_bytes(elf);
+        let path = cache_dir.join(format!("{elf_digest}.{EXTENSION}"));
+        match fs::read(&path) {
+            Ok(bytes) => match decode(&bytes, &elf_digest) {
+                Ok(image) => return Ok(image),
+                Err(err) => tracing::debug!("ignoring cached image {}: {err}", path.display()),
+            },
+            Err(err) => tracing::debug!("no cached image {}: {err}", path.display()),
+        }
+
+        let image = load_uncached(elf)?;
+        if let Err(err) = store(&image, &elf_digest, cache_dir, &path) {
+            tracing::warn!("failed to cache image {}: {err}", path.display());
+        }
+        Ok(image)
+    }
+}
+
+fn load_uncached(elf: &[u8]) -> Result<MemoryImage> {
+    let program = Program::load_elf(elf, GUEST_MAX_MEM as u32)?;
+    MemoryImage::new(&program, PAGE_SIZE as u32)
+}
+
+fn store(image: &MemoryImage, elf_digest: &Digest, cache_dir: &Path, path: &Path) -> Result<()> {
+    let bytes = encode(image, elf_digest);
+    ensure!(
+        bytes.len() as u64 <= MAX_CACHE_SIZE,
+        "image is larger than the cache"
+    );
+    fs::create_dir_all(cache_dir)?;
+    evict(cache_dir, MAX_CACHE_SIZE - bytes.len() as u64)?;
+    // Write to a temporary file first, so that concurrent readers never see
+    // a partially written entry.
+    let tmp_path = path.with_extension(format!("{}.tmp", std::process::id()));
+    fs::write(&tmp_path, bytes)?;
+    fs::rename(&tmp_path, path).context("failed to rename cache entry")
+}
+
+/// Removes the oldest entries of `cache_dir` until the remaining ones take at
+/// most `limit` bytes.
+fn evict(cache_dir: &Path, limit: u64) -> Result<()> {
+    let mut entries: Vec<(SystemTime, u64, PathBuf)> = Vec::new();
+    for entry in fs::read_dir(cache_dir)? {
+        let path = entry?.path();
+        if path.extension().and_then(|ext| ext.to_str()) != Some(EXTENSION) {
+            continue;
+        }
+        // Entries may be replaced or evicted concurrently.
+        let Ok(metadata) = fs::metadata(&path) else {
+            continue;
+        };
+        let modified = metadata.modified().unwrap_or(SystemTime::UNIX_EPOCH);
+        entries.push((modified, metadata.len(), path));
+    }
+
+    let mut total: u64 = entries.iter().map(|(_, len, _)| len).sum();
+    entries.sort();
+    for (_, len, path) in entries {
+        if total <= limit {
+            break;
+        }
+        tracing::debug!("evicting cached image {}", path.display());
+        let _ = fs::remove_file(&path);
+        total -= len;
+    }
+    Ok(())
+}
+
+fn encode(image: &MemoryImage, elf_digest: &Digest) -> Vec<u8> {
+    let page_size = image.info.page_size as usize;
+    let mut body = Vec::with_capacity(12 + image.pages.len() * (4 + page_size));
+    body.extend(image.info.page_size.to_le_bytes());
+    body.extend(image.pc.to_le_bytes());
+    body.extend((image.pages.len() as u32).to_le_bytes());
+    for (page_idx, page) in image.pages.iter() {
+        body.extend(page_idx.to_le_bytes());
+        body.extend(page.iter());
+    }
+
+    let mut bytes = Vec::with_capacity(HEADER_SIZE + body.len());
+    bytes.extend(MAGIC);
+    bytes.extend(elf_digest.as_bytes());
+    bytes.extend(image.compute_id().as_bytes());
+    bytes.extend(Impl::hash_bytes(&body).as_bytes());
+    bytes.extend(body);
+    bytes
+}
+
+fn decode(bytes: &[u8], elf_digest: &Digest) -> Result<MemoryImage> {
+    ensure!(bytes.len() >= HEADER_SIZE + 12, "truncated cache entry");
+    let (header, body) = bytes.split_at(HEADER_SIZE);
+    let (magic, header) = header.split_at(MAGIC.len());
+    let (cached_elf_digest, header) = header.split_at(DIGEST_BYTES);
+    let (image_id, body_digest) = header.split_at(DIGEST_BYTES);
+    ensure!(magic == MAGIC, "unknown cache format");
+    ensure!(
+        cached_elf_digest == elf_digest.as_bytes(),
+        "ELF digest mismatch"
+    );
+    ensure!(
+        body_digest == Impl::hash_bytes(body).as_bytes(),
+        "body digest mismatch"
+    );
+
+    let mut words = body
+        .chunks_exact(4)
+        .map(|word| u32::from_le_bytes(word.try_into().unwrap()));
+    let (page_size, pc, num_pages) = match (words.next(), words.next(), words.next()) {
+        (Some(page_size), Some(pc), Some(num_pages)) => (page_size, pc, num_pages as usize),
+        _ => bail!("truncated cache entry"),
+    };
+    ensure!(page_size == PAGE_SIZE as u32, "page size mismatch");
+    let page_size = page_size as usize;
+    let pages_bytes = &body[12..];
+    ensure!(
+        pages_bytes.len() == num_pages * (4 + page_size),
+        "cache entry length mismatch"
+    );
+
+    let info = PageTableInfo::new(PAGE_TABLE.start() as u32, page_size as u32)?;
+    let mut pages = BTreeMap::new();
+    for entry in pages_bytes.chunks_exact(4 + page_size) {
+        let (page_idx, page) = entry.split_at(4);
+        let page_idx = u32::from_le_bytes(page_idx.try_into().unwrap());
+        ensure!(page_idx <= info.root_idx, "page index out of range");
+        pages.insert(page_idx, Arc::new(page.to_vec()));
+    }
+    ensure!(pages.len() == num_pages, "duplicate page index");
+    ensure!(
+        pages.contains_key(&info.root_idx),
+        "missing page table root"
+    );
+
+    let image = MemoryImage { pages, info, pc };
+    ensure!(
+        image.compute_id().as_bytes() == image_id,
+        "image ID mismatch"
+    );
+    Ok(image)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{fs, path::PathBuf};
+
+    use risc0_zkp::core::hash::sha::{cpu::Impl, Sha256};
+    use risc0_zkvm_methods::{HELLO_COMMIT_ELF, MULTI_TEST_ELF};
+
+    use super::{decode, encode, evict, load_uncached, HEADER_SIZE};
+    use crate::MemoryImage;
+
+    fn cache_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("risc0-binfmt-{name}-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        dir
+    }
+
+    #[test]
+    fn load_cached() {
+        let dir = cache_dir("load-cached");
+        let expected = load_uncached(MULTI_TEST_ELF).unwrap().compute_id();
+
+        let image = MemoryImage::load_cached_in(MULTI_TEST_ELF, &dir).unwrap();
+        assert_eq!(image.compute_id(), expected);
+        let entries: Vec<_> = fs::read_dir(&dir).unwrap().collect();
+        assert_eq!(entries.len(), 1);
+        let path = entries[0].as_ref().unwrap().path();
+        let bytes = fs::read(&path).unwrap();
+        let elf_digest = *Impl::hash_bytes(MULTI_TEST_ELF);
+        assert_eq!(bytes, encode(&image, &elf_digest));
+        assert_eq!(decode(&bytes, &elf_digest).unwrap().pages, image.pages);
+
+        let cached = MemoryImage::load_cached_in(MULTI_TEST_ELF, &dir).unwrap();
+        assert_eq!(cached.compute_id(), expected);
+        assert_eq!(cached.pages, image.pages);
+
+        // A corrupted entry is ignored and replaced.
+        let mut corrupted = bytes.clone();
+        *corrupted.last_mut().unwrap() ^= 1;
+        fs::write(&path, corrupted).unwrap();
+        let image = MemoryImage::load_cached_in(MULTI_TEST_ELF, &dir).unwrap();
+        assert_eq!(image.compute_id(), expected);
+        assert_eq!(fs::read(&path).unwrap(), bytes);
+
+        // An entry for another ELF is not used.
+        let other_path = dir.join(format!("{}.img", *Impl::hash_bytes(HELLO_COMMIT_ELF)));
+        fs::write(&other_path, &bytes).unwrap();
+        let image = MemoryImage::load_cached_in(HELLO_COMMIT_ELF, &dir).unwrap();
+        assert_eq!(
+            image.compute_id(),
+            load_uncached(HELLO_COMMIT_ELF).unwrap().compute_id()
+        );
+        assert_ne!(fs::read(&other_path).unwrap(), bytes);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn image_id_mismatch() {
+        let image = load_uncached(MULTI_TEST_ELF).unwrap();
+        let elf_digest = *Impl::hash_bytes(MULTI_TEST_ELF);
+        let mut bytes = encode(&image, &elf_digest);
+
+        // Changing the page table root is caught even if the body digest is
+        // updated to match.
+        let root_idx = image.info.root_idx;
+        let page_size = image.info.page_size as usize;
+        let root_pos = image.pages.keys().position(|&idx| idx == root_idx).unwrap();
+        let offset = HEADER_SIZE + 12 + root_pos * (4 + page_size) + 4;
+        bytes[offset] ^= 1;
+        let body_digest = *Impl::hash_bytes(&bytes[HEADER_SIZE..]);
+        bytes[HEADER_SIZE - 32..HEADER_SIZE].copy_from_slice(body_digest.as_bytes());
+        let err = decode(&bytes, &elf_digest).err().unwrap();
+        assert_eq!(err.to_string(), "image ID mismatch");
+    }
+
+    #[test]
+    fn eviction() {
+        let dir = cache_dir("eviction");
+        MemoryImage::load_cached_in(MULTI_TEST_ELF, &dir).unwrap();
+        MemoryImage::load_cached_in(HELLO_COMMIT_ELF, &dir).unwrap();
+        fs::write(dir.join("unrelated.txt"), b"kept").unwrap();
+        let old_path = dir.join(format!("{}.img", *Impl::hash_bytes(MULTI_TEST_ELF)));
+        let new_path = dir.join(format!("{}.img", *Impl::hash_bytes(HELLO_COMMIT_ELF)));
+        let old_len = fs::metadata(&old_path).unwrap().len();
+        let new_len = fs::metadata(&new_path).unwrap().len();
+
+        evict(&dir, old_len + new_len).unwrap();
+        assert!(old_path.exists() && new_path.exists());
+
+        // Make sure the newer entry has a later modification time.
+        std::thread::sleep(std::time::Duration::from_millis(10));
+        fs::write(&new_path, fs::read(&new_path).unwrap()).unwrap();
+        evict(&dir, new_len).unwrap();
+        assert!(!old_path.exists());
+        assert!(new_path.exists());
+        assert!(dir.join("unrelated.txt").exists());
+
+        evict(&dir, 0).unwrap();
+        assert!(!new_path.exists());
+        assert!(dir.join("unrelated.txt").exists());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -20,6 +20,48 @@ use anyhow::{anyhow, bail, Context, Result};
 use elf::{endian::LittleEndian, file::Class, ElfBytes};
 use risc0_zkvm_platform::WORD_SIZE;
 
+/// Names of the absolute symbols in which risc0-build records the major, minor
+/// and patch versions of risc0-zkvm in the guests it builds.
+pub const ZKVM_VERSION_SYMBOLS: [&str; 3] = [
+    "__risc0_zkvm_version_major",
+    "__risc0_zkvm_version_minor",
+    "__risc0_zkvm_version_patch",
+];
+
+/// Returns the `[major, minor, patch]` version of risc0-zkvm recorded by
+/// risc0-build in the symbol table of `input`, if any.
+///
+/// The symbols are absolute and not loaded in memory, so they do not change
+/// the image ID of the guest. Guests built without risc0-build, or with their
+/// symbols stripped, have no recorded version.
+pub fn zkvm_version(input: &[u8]) -> Result<Option<[u32; 3]>> {
+    let elf = ElfBytes::<LittleEndian>::minimal_parse(input)
+        .map_err(|err| anyhow!("Elf parse error: {err}"))?;
+    let Some((symbols, strings)) = elf
+        .symbol_table()
+        .map_err(|err| anyhow!("Elf symbol table error: {err}"))?
+    else {
+        return Ok(None);
+    };
+    let mut version = [None; 3];
+    for symbol in symbols.iter() {
+        let name = strings
+            .get(symbol.st_name as usize)
+            .map_err(|err| anyhow!("Elf string table error: {err}"))?;
+        if let Some(idx) = ZKVM_VERSION_SYMBOLS.iter().position(|x| *x == name) {
+            let value = symbol
+                .st_value
+                .try_into()
+                .map_err(|err| anyhow!("{name} was larger than 32 bits. {err}"))?;
+            version[idx] = Some(value);
+        }
+    }
+    match version {
+        [Some(major), Some(minor), Some(patch)] => Ok(Some([major, minor, patch])),
+        _ => Ok(None),
+    }
+}
+
 /// A RISC Zero program
 pub struct Program {
     /// The entrypoint of the program
@@ -106,3 +148,22 @@ impl Program {
         Ok(Program { entry, image })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use risc0_zkvm_methods::MULTI_TEST_ELF;
+
+    use super::zkvm_version;
+
+    #[test]
+    fn guest_zkvm_version() {
+        let expected = [
+            env!("CARGO_PKG_VERSION_MAJOR"),
+            env!("CARGO_PKG_VERSION_MINOR"),
+            env!("CARGO_PKG_VERSION_PATCH"),
+        ]
+        .map(|x| x.parse().unwrap());
+        assert_eq!(zkvm_version(MULTI_TEST_ELF).unwrap(), Some(expected));
+        assert!(zkvm_version(b"not an ELF").is_err());
+    }
+}
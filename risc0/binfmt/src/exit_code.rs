@@ -87,10 +87,52 @@ impl ExitCode {
     pub fn is_ok(&self) -> bool {
         matches!(self, ExitCode::Halted(0) | ExitCode::Paused(0))
     }
+
+    /// Resolves this exit code into the [SessionOutcome] seen by the
+    /// application, or `None` for [ExitCode::SystemSplit], which only ends a
+    /// segment and never a session.
+    pub fn outcome(&self) -> Option<SessionOutcome> {
+        match *self {
+            ExitCode::Halted(0) => Some(SessionOutcome::Completed),
+            ExitCode::Halted(user_exit) => Some(SessionOutcome::Faulted(user_exit)),
+            ExitCode::Paused(user_exit) => Some(SessionOutcome::Paused(user_exit)),
+            ExitCode::SystemSplit => None,
+            ExitCode::SessionLimit => Some(SessionOutcome::LimitExceeded),
+        }
+    }
 }
 
 impl Eq for ExitCode {}
 
+/// Outcome of a session, as seen by the application.
+///
+/// Unlike [ExitCode], this has no variant for the system splits used by the
+/// zkVM to break a session into segments, so applications need not
+/// special-case them. The raw [ExitCode] remains available on sessions and
+/// receipts.
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub enum SessionOutcome {
+    /// The guest halted with exit code 0.
+    Completed,
+
+    /// The guest halted with the given non-zero exit code, indicating an
+    /// error. A halted program cannot be resumed.
+    Faulted(u32),
+
+    /// The guest paused with the given exit code, and can be resumed.
+    Paused(u32),
+
+    /// The guest reached the session limit set by the host.
+    LimitExceeded,
+}
+
+impl SessionOutcome {
+    /// True if the guest completed, or paused with exit code 0.
+    pub fn is_ok(&self) -> bool {
+        matches!(self, SessionOutcome::Completed | SessionOutcome::Paused(0))
+    }
+}
+
 /// Error returned when a (system, user) exit code pair is an invalid
 /// representation.
 #[derive(Debug, Copy, Clone)]
@@ -104,3 +146,36 @@ impl fmt::Display for InvalidExitCodeError {
 
 #[cfg(feature = "std")]
 impl std::error::Error for InvalidExitCodeError {}
+
+#[cfg(test)]
+mod tests {
+    use super::{ExitCode, SessionOutcome};
+
+    #[test]
+    fn outcome() {
+        assert_eq!(
+            ExitCode::Halted(0).outcome(),
+            Some(SessionOutcome::Completed)
+        );
+        assert_eq!(
+            ExitCode::Halted(3).outcome(),
+            Some(SessionOutcome::Faulted(3))
+        );
+        assert_eq!(
+            ExitCode::Paused(0).outcome(),
+            Some(SessionOutcome::Paused(0))
+        );
+        assert_eq!(ExitCode::SystemSplit.outcome(), None);
+        assert_eq!(
+            ExitCode::SessionLimit.outcome(),
+            Some(SessionOutcome::LimitExceeded)
+        );
+        for exit_code in [
+            ExitCode::Halted(0),
+            ExitCode::Halted(3),
+            ExitCode::Paused(0),
+        ] {
+            assert_eq!(exit_code.is_ok(), exit_code.outcome().unwrap().is_ok());
+        }
+    }
+}
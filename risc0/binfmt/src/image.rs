@@ -14,7 +14,7 @@
 
 extern crate alloc;
 
-use alloc::{collections::BTreeMap, vec, vec::Vec};
+use alloc::{collections::BTreeMap, sync::Arc, vec, vec::Vec};
 
 use anyhow::{ensure, Result};
 use risc0_zkp::core::{
@@ -34,10 +34,19 @@ use crate::{elf::Program, Digestible, SystemState};
 /// This is an image of the full memory state of the zkVM, including the data,
 /// text, inputs, page table, and system memory. In addition to the memory image
 /// proper, this includes some metadata about the page table.
+///
+/// Pages are reference counted and copied on write, so a clone of an image
+/// shares every page that neither copy has modified. Hosts running many
+/// executions of the same guest can build its image once, and give a clone of
+/// it to each executor.
 #[derive(Clone, Serialize, Deserialize)]
 pub struct MemoryImage {
     /// Sparse memory memory image as a map from page index to page.
-    pub pages: BTreeMap<u32, Vec<u8>>,
+    ///
+    /// Pages are private so that they are only modified through
+    /// [MemoryImage::store_region_in_page] and [MemoryImage::set_page], which
+    /// preserve the sharing between clones.
+    pub(crate) pages: BTreeMap<u32, Arc<Vec<u8>>>,
 
     /// Metadata about the structure of the page table
     pub info: PageTableInfo,
@@ -190,15 +199,57 @@ impl MemoryImage {
         Ok(img)
     }
 
+    /// Construct an image from its `pages`, indexed by page, without computing
+    /// its page table.
+    ///
+    /// The pages include the page table, and are usually those of another
+    /// image, as returned by [MemoryImage::pages].
+    pub fn from_pages(
+        info: PageTableInfo,
+        pc: u32,
+        pages: impl IntoIterator<Item = (u32, Arc<Vec<u8>>)>,
+    ) -> Self {
+        Self {
+            pages: pages.into_iter().collect(),
+            info,
+            pc,
+        }
+    }
+
+    /// Returns the pages loaded in this image, by page index. Pages which are
+    /// not loaded are zero pages.
+    pub fn pages(&self) -> impl Iterator<Item = (u32, &[u8])> + '_ {
+        self.pages
+            .iter()
+            .map(|(page_idx, page)| (*page_idx, page.as_slice()))
+    }
+
+    /// Replaces the page specified by page_idx with `page`, which may be
+    /// shared with other images.
+    ///
+    /// The page table is not updated, see [MemoryImage::update_page].
+    pub fn set_page(&mut self, page_idx: u32, page: Arc<Vec<u8>>) {
+        self.pages.insert(page_idx, page);
+    }
+
     /// Load a page specified by page_idx. If no page is found, a zero page is
     /// returned.
     pub fn load_page(&self, page_idx: u32) -> Vec<u8> {
         self.pages
             .get(&page_idx)
-            .cloned()
+            .map(|page| page.to_vec())
             .unwrap_or_else(|| vec![0; self.info.page_size as usize])
     }
 
+    /// Returns the page specified by page_idx, sharing it with this image
+    /// unless it is a zero page.
+    pub fn share_page(&self, page_idx: u32) -> Arc<Vec<u8>> {
+        self.pages
+            .get(&page_idx)
+            .cloned()
+            .unwrap_or_else(|| Arc::new(vec![0; self.info.page_size as usize]))
+    }
+
     /// Writes the given byte array in this memory image at the given
     /// address.  The caller is responsible for ensuring the bytes do
     /// not overlap a page boundary.
@@ -208,8 +259,10 @@ impl MemoryImage {
             if addr as usize >= MEM_SIZE {
                 panic!("address {addr:08X} outside MEM_SIZE")
             }
-            vec![0_u8; self.info.page_size as usize]
+            Arc::new(vec![0_u8; self.info.page_size as usize])
         });
+        // Copy the page if it is shared with another image.
+        let page = Arc::make_mut(page);
         let page_start = self.info.get_page_addr(page_idx);
         page[(addr - page_start) as usize..(addr - page_start) as usize + bytes.len()]
             .clone_from_slice(bytes);
@@ -255,6 +308,11 @@ impl MemoryImage {
     pub fn update_page(&mut self, page_idx: u32) {
         let digest = self.hash_page(page_idx);
         let entry_addr = self.info.get_page_entry_addr(page_idx);
+        // Leave the page table page shared if the entry did not change.
+        let mut entry = [0_u8; DIGEST_BYTES];
+        if self.load_region_in_page(entry_addr, &mut entry).is_ok() && entry == digest.as_bytes() {
+            return;
+        }
         self.store_region_in_page(entry_addr, digest.as_bytes());
     }
 
@@ -339,6 +397,8 @@ fn hash_page_bytes(page: &[u8]) -> Digest {
 
 #[cfg(test)]
 mod tests {
+    use std::sync::Arc;
+
     use risc0_zkvm_methods::MULTI_TEST_ELF;
     use risc0_zkvm_platform::{
         memory::{GUEST_MAX_MEM, PAGE_TABLE, STACK_TOP, SYSTEM, TEXT_START},
@@ -371,6 +431,46 @@ mod tests {
         image.check(image.info.root_page_addr).unwrap();
     }
 
+    #[test]
+    fn shared_pages() {
+        const PAGE_SIZE: u32 = 1024;
+        let program = Program::load_elf(MULTI_TEST_ELF, GUEST_MAX_MEM as u32).unwrap();
+        let image = MemoryImage::new(&program, PAGE_SIZE).unwrap();
+        let mut clone = image.clone();
+        let shared = |a: &MemoryImage, b: &MemoryImage| {
+            a.pages
+                .iter()
+                .filter(|(idx, page)| b.pages.get(idx).is_some_and(|p| Arc::ptr_eq(page, p)))
+                .count()
+        };
+        assert_eq!(shared(&image, &clone), image.pages.len());
+
+        clone.store_region_in_page(TEXT_START, &[0xff; 4]);
+        clone.hash_pages();
+        assert_ne!(image.compute_id(), clone.compute_id());
+        image.check(TEXT_START).unwrap();
+        clone.check(TEXT_START).unwrap();
+
+        // Only the modified page and its path up the page table were copied.
+        let copied = image.pages.len() - shared(&image, &clone);
+        assert!(
+            copied <= 1 + image.info._layers.len() + 1,
+            "{copied} pages copied"
+        );
+    }
+
+    #[test]
+    fn from_pages() {
+        let program = Program::load_elf(MULTI_TEST_ELF, GUEST_MAX_MEM as u32).unwrap();
+        let image = MemoryImage::new(&program, 1024).unwrap();
+        let pages = image
+            .pages()
+            .map(|(page_idx, page)| (page_idx, Arc::new(page.to_vec())));
+        let copy = MemoryImage::from_pages(image.info.clone(), image.pc, pages);
+        assert_eq!(copy.compute_id(), image.compute_id());
+        assert!(copy.pages().eq(image.pages()));
+    }
+
     #[test]
     fn page_table_info() {
         const PAGE_SIZE_1K: u32 = 1024;
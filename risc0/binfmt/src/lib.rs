@@ -16,6 +16,8 @@
 
 #![cfg_attr(all(not(feature = "std"), not(test)), no_std)]
 
+#[cfg(all(feature = "std", not(target_os = "zkvm")))]
+mod cache;
 mod elf;
 mod exit_code;
 mod hash;
@@ -26,8 +28,8 @@ mod sys_state;
 #[cfg(not(target_os = "zkvm"))]
 pub use self::image::{MemoryImage, PageTableInfo};
 pub use crate::{
-    elf::Program,
-    exit_code::{ExitCode, InvalidExitCodeError},
+    elf::{zkvm_version, Program, ZKVM_VERSION_SYMBOLS},
+    exit_code::{ExitCode, InvalidExitCodeError, SessionOutcome},
     hash::{tagged_list, tagged_list_cons, tagged_struct, Digestible},
     sys_state::{read_sha_halfs, write_sha_halfs, DecodeError, SystemState},
 };
@@ -0,0 +1,173 @@
+// Copyright 2024 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::{
+    env,
+    fs::{self, File},
+    io::{Read, Write},
+    path::{Path, PathBuf},
+};
+
+use anyhow::{bail, Context, Result};
+use risc0_zkvm_platform::memory::TEXT_START;
+
+use crate::{
+    build_rust_runtime, detect_toolchain, get_guest_dir, risc0_rustc, sanitized_cmd,
+    GuestListEntry, RUSTUP_TOOLCHAIN_NAME,
+};
+
+/// Magic number of a raw LLVM bitcode file.
+const BITCODE_MAGIC: [u8; 4] = [b'B', b'C', 0xC0, 0xDE];
+
+/// Magic number of an LLVM bitcode file in a wrapper header.
+const BITCODE_WRAPPER_MAGIC: [u8; 4] = [0xDE, 0xC0, 0x17, 0x0B];
+
+/// Target features the bitcode is compiled for, independent of the attributes
+/// recorded by the compiler which emitted it.
+const TARGET_FEATURES: &str = "+m,-a,-c,-f,-d,-relax";
+
+/// Link LLVM bitcode files into a zkVM guest ELF named `name` in `target_dir`.
+///
+/// The bitcode must target `riscv32`, e.g. as emitted by `clang
+/// --target=riscv32 -march=rv32im -emit-llvm -c` or by `rustc --emit=llvm-bc`.
+/// All code generation happens at link time, with the `rust-lld` and LLVM of
+/// the risc0 toolchain and a fixed set of target options. As a result the
+/// image ID depends only on the bitcode and the installed risc0 toolchain, not
+/// on the version of the compiler used to emit the bitcode. Note that LLVM can
+/// only read bitcode emitted by the same or an older LLVM version.
+///
+/// `runtime_lib` is a static library providing the entrypoint of the guest,
+/// such as the one returned by [build_rust_runtime]; it calls a C `main`
+/// function and halts with its return value as the exit code.
+pub fn build_bitcode(
+    name: &str,
+    inputs: &[impl AsRef<Path>],
+    runtime_lib: &str,
+    target_dir: impl AsRef<Path>,
+) -> Result<GuestListEntry> {
+    if inputs.is_empty() {
+        bail!("No bitcode inputs for {name}");
+    }
+    for input in inputs {
+        check_bitcode(input.as_ref())?;
+    }
+
+    let out_dir = target_dir
+        .as_ref()
+        .join("riscv32im-risc0-zkvm-elf")
+        .join("bitcode");
+    fs::create_dir_all(&out_dir)?;
+    let elf_path = out_dir.join(name);
+
+    let mut cmd = sanitized_cmd(rust_lld()?.to_str().context("invalid rust-lld path")?);
+    cmd.args([
+        "-flavor",
+        "gnu",
+        "--lto-O3",
+        "--lto-CGO3",
+        "-mllvm",
+        &format!("-mattr={TARGET_FEATURES}"),
+        "-mllvm",
+        "-relocation-model=static",
+        &format!("-Ttext=0x{TEXT_START:08X}"),
+        "--fatal-warnings",
+        "--gc-sections",
+        "-o",
+    ])
+    .arg(&elf_path)
+    .args(inputs.iter().map(|input| input.as_ref()))
+    .arg(runtime_lib);
+
+    eprintln!("Linking bitcode: {:?}", cmd);
+    let status = cmd.status().context("Failed to run rust-lld")?;
+    if !status.success() {
+        bail!("Failed to link bitcode for {name}: {status}");
+    }
+
+    GuestListEntry::build(
+        name,
+        elf_path
+            .to_str()
+            .context("elf path contains invalid unicode")?,
+    )
+}
+
+/// Embeds a guest built from LLVM bitcode for use by host-side dependencies.
+///
+/// This links `inputs` against the [rust runtime](build_rust_runtime) with
+/// [build_bitcode] and writes the `*_ELF`, `*_ID` and `*_PATH` constants to
+/// `{name}.rs` in the cargo output directory, which can be included with:
+///
+/// ```text
+/// include!(concat!(env!("OUT_DIR"), "/my_method.rs"));
+/// ```
+pub fn embed_bitcode(name: &str, inputs: &[impl AsRef<Path>]) -> GuestListEntry {
+    detect_toolchain(RUSTUP_TOOLCHAIN_NAME);
+
+    for input in inputs {
+        println!("cargo:rerun-if-changed={}", input.as_ref().display());
+    }
+
+    let runtime_lib = build_rust_runtime();
+    let method = build_bitcode(name, inputs, &runtime_lib, get_guest_dir()).unwrap();
+
+    let out_dir_env = env::var_os("OUT_DIR").unwrap();
+    let methods_path = Path::new(&out_dir_env).join(format!("{name}.rs"));
+    File::create(methods_path)
+        .unwrap()
+        .write_all(method.codegen_consts().as_bytes())
+        .unwrap();
+    method
+}
+
+/// Returns an error if `path` is not an LLVM bitcode file.
+fn check_bitcode(path: &Path) -> Result<()> {
+    let mut magic = [0u8; 4];
+    File::open(path)
+        .and_then(|mut file| file.read_exact(&mut magic))
+        .with_context(|| format!("Failed to read {}", path.display()))?;
+    if magic != BITCODE_MAGIC && magic != BITCODE_WRAPPER_MAGIC {
+        bail!("{} is not an LLVM bitcode file", path.display());
+    }
+    Ok(())
+}
+
+/// Returns the path of the `rust-lld` shipped with the risc0 toolchain.
+fn rust_lld() -> Result<PathBuf> {
+    let rustc = risc0_rustc();
+    let output = sanitized_cmd(&rustc)
+        .args(["--print", "sysroot"])
+        .output()?;
+    let sysroot = String::from_utf8(output.stdout)?;
+    let output = sanitized_cmd(&rustc).arg("-vV").output()?;
+    let version = String::from_utf8(output.stdout)?;
+    let host = version
+        .lines()
+        .find_map(|line| line.strip_prefix("host: "))
+        .context("Failed to determine host triple of the risc0 toolchain")?;
+
+    let path = Path::new(sysroot.trim())
+        .join("lib")
+        .join("rustlib")
+        .join(host)
+        .join("bin")
+        .join("rust-lld");
+    if !path.exists() {
+        bail!(
+            "rust-lld not found in the risc0 toolchain: {}",
+            path.display()
+        );
+    }
+    Ok(path)
+}
@@ -24,7 +24,7 @@ use risc0_zkvm_platform::{
 };
 use tempfile::tempdir;
 
-use crate::get_env_var;
+use crate::{get_env_var, zkvm_version_link_args};
 
 const DOCKER_IGNORE: &str = r#"
 **/Dockerfile
@@ -103,9 +103,12 @@ fn create_dockerfile(
     features: &[String],
 ) -> Result<()> {
     let manifest_env = &[("CARGO_MANIFEST_PATH", manifest_path.to_str().unwrap())];
-    let rustflags = format!(
+    let mut rustflags = format!(
         "-C passes=loweratomic -C link-arg=-Ttext=0x{TEXT_START:08X} -C link-arg=--fatal-warnings",
     );
+    for arg in zkvm_version_link_args() {
+        rustflags.push_str(&format!(" -C {arg}"));
+    }
     let rustflags_env = &[("RUSTFLAGS", rustflags.as_str())];
 
     let common_args = vec![
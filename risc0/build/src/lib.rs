@@ -16,7 +16,9 @@
 #![deny(missing_docs)]
 #![deny(rustdoc::broken_intra_doc_links)]
 
+mod bitcode;
 mod docker;
+mod watch;
 
 use std::{
     borrow::Cow,
@@ -31,12 +33,14 @@ use std::{
 
 use anyhow::{Context, Result};
 use cargo_metadata::{Message, MetadataCommand, Package};
-use risc0_binfmt::compute_image_id;
+use risc0_binfmt::{compute_image_id, ZKVM_VERSION_SYMBOLS};
 use risc0_zkp::core::digest::DIGEST_WORDS;
 use risc0_zkvm_platform::memory;
 use serde::Deserialize;
 
+pub use bitcode::{build_bitcode, embed_bitcode};
 pub use docker::docker_build;
+pub use watch::{GuestWatcher, RunDiff, RunStats};
 
 const RUSTUP_TOOLCHAIN_NAME: &str = "risc0";
 
@@ -248,17 +252,21 @@ fn sanitized_cmd(tool: &str) -> Command {
     cmd
 }
 
-/// Creates a std::process::Command to execute the given cargo
-/// command in an environment suitable for targeting the zkvm guest.
-pub fn cargo_command(subcmd: &str, rust_flags: &[&str]) -> Command {
+/// Returns the path of the rustc of the risc0 toolchain.
+fn risc0_rustc() -> String {
     let rustc = sanitized_cmd("rustup")
         .args(["+risc0", "which", "rustc"])
         .output()
         .expect("rustup failed to find risc0 toolchain")
         .stdout;
 
-    let rustc = String::from_utf8(rustc).unwrap();
-    let rustc = rustc.trim();
+    String::from_utf8(rustc).unwrap().trim().to_string()
+}
+
+/// Creates a std::process::Command to execute the given cargo
+/// command in an environment suitable for targeting the zkvm guest.
+pub fn cargo_command(subcmd: &str, rust_flags: &[&str]) -> Command {
+    let rustc = risc0_rustc();
     println!("Using rustc: {rustc}");
 
     let mut cmd = sanitized_cmd("cargo");
@@ -310,6 +318,22 @@ pub fn cargo_command(subcmd: &str, rust_flags: &[&str]) -> Command {
     cmd
 }
 
+/// Linker arguments recording the risc0-zkvm version of this crate in the
+/// symbol table of guests, so that executors can detect guests built for
+/// another version, see [risc0_binfmt::zkvm_version].
+pub(crate) fn zkvm_version_link_args() -> Vec<String> {
+    let version = [
+        env!("CARGO_PKG_VERSION_MAJOR"),
+        env!("CARGO_PKG_VERSION_MINOR"),
+        env!("CARGO_PKG_VERSION_PATCH"),
+    ];
+    ZKVM_VERSION_SYMBOLS
+        .iter()
+        .zip(version)
+        .map(|(symbol, value)| format!("link-arg=--defsym={symbol}={value}"))
+        .collect()
+}
+
 /// Builds a static library providing a rust runtime.
 ///
 /// This can be used to build programs for the zkvm which don't depend on
@@ -402,11 +426,12 @@ fn build_guest_package<P>(
 
     fs::create_dir_all(target_dir.as_ref()).unwrap();
 
-    let mut cmd = if let Some(lib) = runtime_lib {
-        cargo_command("build", &["-C", &format!("link_arg={}", lib)])
-    } else {
-        cargo_command("build", &[])
-    };
+    let mut link_args = zkvm_version_link_args();
+    if let Some(lib) = runtime_lib {
+        link_args.push(format!("link_arg={}", lib));
+    }
+    let rust_flags: Vec<_> = link_args.iter().flat_map(|arg| ["-C", arg]).collect();
+    let mut cmd = cargo_command("build", &rust_flags);
 
     let features_str = guest_opts.features.join(",");
     if !features_str.is_empty() {
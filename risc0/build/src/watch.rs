@@ -0,0 +1,301 @@
+// Copyright 2024 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::{
+    collections::{HashMap, HashSet},
+    fmt, fs, io,
+    path::{Path, PathBuf},
+    thread,
+    time::{Duration, SystemTime},
+};
+
+use anyhow::{bail, Context, Result};
+use cargo_metadata::MetadataCommand;
+use risc0_zkp::core::digest::Digest;
+
+use crate::{
+    cargo_command, detect_toolchain, get_package, get_target_dir, guest_methods, is_debug,
+    GuestListEntry, RUSTUP_TOOLCHAIN_NAME,
+};
+
+/// Cycle counts of a single execution of a guest, as reported to a
+/// [GuestWatcher].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct RunStats {
+    /// Number of segments of the session.
+    pub segments: usize,
+    /// Cycles spent executing guest instructions.
+    pub user_cycles: u64,
+    /// Cycles of the session, including continuation overhead and padding.
+    pub total_cycles: u64,
+}
+
+/// Difference between the [RunStats] of two executions of the same guest.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RunDiff {
+    /// Stats of the previous execution.
+    pub prev: RunStats,
+    /// Stats of the latest execution.
+    pub next: RunStats,
+}
+
+impl fmt::Display for RunDiff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fn delta(prev: u64, next: u64) -> String {
+            let delta = next as i128 - prev as i128;
+            match prev {
+                0 => format!("{delta:+}"),
+                _ => format!("{delta:+} ({:+.2}%)", delta as f64 * 100.0 / prev as f64),
+            }
+        }
+        write!(
+            f,
+            "user cycles: {} {}, total cycles: {} {}, segments: {} {}",
+            self.next.user_cycles,
+            delta(self.prev.user_cycles, self.next.user_cycles),
+            self.next.total_cycles,
+            delta(self.prev.total_cycles, self.next.total_cycles),
+            self.next.segments,
+            delta(self.prev.segments as u64, self.next.segments as u64),
+        )
+    }
+}
+
+/// Rebuilds and re-executes a guest package each time its sources change.
+///
+/// The sources of the guest package and of its path dependencies are
+/// watched, so that edits to a local crate used by the guest also trigger a
+/// rebuild.
+///
+/// After each build, the image ID of every guest binary is recomputed and the
+/// binary is executed by the caller-provided function, typically running a
+/// fixed `ExecutorEnv` with the `risc0-zkvm` executor. The cycle counts of
+/// each run are reported next to their difference from the previous run.
+///
+/// ```no_run
+/// use risc0_build::{GuestWatcher, RunStats};
+///
+/// GuestWatcher::new("methods/guest/Cargo.toml")
+///     .run(|guest| {
+///         // Execute `guest.elf` here.
+///         Ok(RunStats::default())
+///     })
+///     .unwrap();
+/// ```
+pub struct GuestWatcher {
+    manifest_path: PathBuf,
+    features: Vec<String>,
+    poll_interval: Duration,
+}
+
+impl GuestWatcher {
+    /// Default interval between two scans of the guest sources.
+    pub const DEFAULT_POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+    /// Watch the guest package with the given `Cargo.toml`.
+    pub fn new(manifest_path: impl AsRef<Path>) -> Self {
+        Self {
+            manifest_path: manifest_path.as_ref().to_path_buf(),
+            features: Vec::new(),
+            poll_interval: Self::DEFAULT_POLL_INTERVAL,
+        }
+    }
+
+    /// Features for cargo to build the guest with.
+    pub fn features(mut self, features: Vec<String>) -> Self {
+        self.features = features;
+        self
+    }
+
+    /// Interval between two scans of the guest sources for changes.
+    pub fn poll_interval(mut self, poll_interval: Duration) -> Self {
+        self.poll_interval = poll_interval;
+        self
+    }
+
+    /// Build and execute the guest, then do so again on every change until
+    /// `execute` fails.
+    ///
+    /// Build failures are reported and the watcher waits for the next change.
+    pub fn run<F>(&self, mut execute: F) -> Result<()>
+    where
+        F: FnMut(&GuestListEntry) -> Result<RunStats>,
+    {
+        detect_toolchain(RUSTUP_TOOLCHAIN_NAME);
+
+        let manifest_path = self
+            .manifest_path
+            .canonicalize()
+            .with_context(|| format!("manifest_path: {:?}", self.manifest_path))?;
+        let src_dir = manifest_path.parent().unwrap().to_path_buf();
+        let target_dir = get_target_dir(&manifest_path).join("riscv-guest");
+
+        let mut prev_runs: HashMap<String, (Digest, RunStats)> = HashMap::new();
+        let mut roots = source_roots(&manifest_path, &src_dir);
+        let mut snapshot = scan_sources(&roots)?;
+        loop {
+            match self.build(&manifest_path, &target_dir) {
+                Ok(()) => {
+                    let pkg = get_package(&src_dir);
+                    for guest in guest_methods(&pkg, &target_dir) {
+                        let image_id = Digest::from(guest.image_id);
+                        let stats = execute(&guest)?;
+                        let prev = prev_runs.insert(guest.name.to_string(), (image_id, stats));
+                        match prev {
+                            Some((prev_id, _)) if prev_id == image_id => {
+                                eprintln!("{}: image ID unchanged: {image_id}", guest.name)
+                            }
+                            _ => eprintln!("{}: image ID: {image_id}", guest.name),
+                        }
+                        match prev {
+                            Some((_, prev)) => {
+                                eprintln!("{}: {}", guest.name, RunDiff { prev, next: stats })
+                            }
+                            None => eprintln!(
+                                "{}: user cycles: {}, total cycles: {}, segments: {}",
+                                guest.name, stats.user_cycles, stats.total_cycles, stats.segments
+                            ),
+                        }
+                    }
+                }
+                Err(err) => eprintln!("{err:?}"),
+            }
+
+            eprintln!(
+                "Watching {} and {} path dependencies for changes...",
+                src_dir.display(),
+                roots.len() - 1
+            );
+            loop {
+                thread::sleep(self.poll_interval);
+                if scan_sources(&roots)? != snapshot {
+                    break;
+                }
+            }
+            // The manifest may have changed, so look for path dependencies
+            // again.
+            roots = source_roots(&manifest_path, &src_dir);
+            snapshot = scan_sources(&roots)?;
+        }
+    }
+
+    fn build(&self, manifest_path: &Path, target_dir: &Path) -> Result<()> {
+        let mut cmd = cargo_command("build", &[]);
+        if !self.features.is_empty() {
+            cmd.args(["--features", &self.features.join(",")]);
+        }
+        cmd.arg("--manifest-path")
+            .arg(manifest_path)
+            .arg("--target-dir")
+            .arg(target_dir);
+        if !is_debug() {
+            cmd.arg("--release");
+        }
+        let status = cmd.status().context("Failed to run cargo build")?;
+        if !status.success() {
+            bail!("Guest build failed: {status}");
+        }
+        Ok(())
+    }
+}
+
+/// Returns the directories of the guest package in `src_dir` and of its path
+/// dependencies, leaving out those nested in another one.
+///
+/// Only `src_dir` is returned if `cargo metadata` fails, e.g. because the
+/// manifest is being edited.
+fn source_roots(manifest_path: &Path, src_dir: &Path) -> Vec<PathBuf> {
+    let meta = match MetadataCommand::new().manifest_path(manifest_path).exec() {
+        Ok(meta) => meta,
+        Err(err) => {
+            eprintln!("Failed to list the path dependencies of the guest: {err}");
+            return vec![src_dir.to_path_buf()];
+        }
+    };
+    // Walk the dependency graph of the guest, so that the other members of
+    // its workspace are left out.
+    let nodes: HashMap<_, _> = meta
+        .resolve
+        .iter()
+        .flat_map(|resolve| &resolve.nodes)
+        .map(|node| (&node.id, &node.dependencies))
+        .collect();
+    let mut deps: HashSet<_> = meta.resolve.iter().flat_map(|r| &r.root).collect();
+    let mut pending: Vec<_> = deps.iter().copied().collect();
+    while let Some(id) = pending.pop() {
+        for dep in nodes.get(id).copied().into_iter().flatten() {
+            if deps.insert(dep) {
+                pending.push(dep);
+            }
+        }
+    }
+    let mut roots = vec![src_dir.to_path_buf()];
+    for pkg in &meta.packages {
+        // Packages without a source are local: the guest itself and its path
+        // dependencies.
+        if pkg.source.is_some() || !deps.contains(&pkg.id) {
+            continue;
+        }
+        if let Some(dir) = pkg.manifest_path.parent() {
+            roots.push(dir.into());
+        }
+    }
+    // Nested directories sort right after the directory containing them.
+    roots.sort();
+    roots.dedup_by(|root, prev| root.starts_with(prev));
+    roots
+}
+
+/// Returns the modification time of every file under `roots`, skipping build
+/// outputs and hidden directories.
+///
+/// Files and directories removed during the scan, such as the temporary files
+/// of editors, are skipped.
+fn scan_sources(roots: &[PathBuf]) -> Result<HashMap<PathBuf, SystemTime>> {
+    let mut files = HashMap::new();
+    let mut pending = roots.to_vec();
+    while let Some(dir) = pending.pop() {
+        let Some(entries) = skip_not_found(fs::read_dir(&dir))? else {
+            continue;
+        };
+        for entry in entries {
+            let Some(entry) = skip_not_found(entry)? else {
+                continue;
+            };
+            let path = entry.path();
+            let name = entry.file_name();
+            let name = name.to_string_lossy();
+            if name == "target" || name.starts_with('.') {
+                continue;
+            }
+            let Some(metadata) = skip_not_found(entry.metadata())? else {
+                continue;
+            };
+            if metadata.is_dir() {
+                pending.push(path);
+            } else if let Some(modified) = skip_not_found(metadata.modified())? {
+                files.insert(path, modified);
+            }
+        }
+    }
+    Ok(files)
+}
+
+fn skip_not_found<T>(res: io::Result<T>) -> io::Result<Option<T>> {
+    match res {
+        Ok(value) => Ok(Some(value)),
+        Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(None),
+        Err(err) => Err(err),
+    }
+}
@@ -34,5 +34,7 @@ fn main() -> Result<()> {
         RisczeroCmd::BuildCrate(build) => build.run(BuildSubcommand::Build),
         #[cfg(feature = "experimental")]
         RisczeroCmd::Test(build) => build.run(BuildSubcommand::Test),
+        #[cfg(feature = "experimental")]
+        RisczeroCmd::Watch(cmd) => cmd.run(),
     }
 }
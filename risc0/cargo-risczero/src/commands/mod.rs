@@ -18,3 +18,5 @@ pub mod build_guest;
 pub mod build_toolchain;
 pub mod install;
 pub mod new;
+#[cfg(feature = "experimental")]
+pub mod watch;
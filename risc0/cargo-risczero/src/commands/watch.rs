@@ -0,0 +1,62 @@
+// Copyright 2024 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::{fs, path::PathBuf, time::Duration};
+
+use anyhow::Result;
+use clap::Parser;
+use risc0_build::{GuestWatcher, RunStats};
+use risc0_zkvm::{default_executor, ExecutorEnv};
+
+/// `cargo risczero watch`
+#[derive(Parser)]
+pub struct WatchCommand {
+    /// Location of the Cargo.toml for the guest code.
+    #[arg(long, default_value = "./Cargo.toml")]
+    pub manifest_path: PathBuf,
+
+    /// Feature flags passed to cargo.
+    #[arg(long, value_delimiter = ',')]
+    pub features: Vec<String>,
+
+    /// File whose contents are written to the guest's stdin.
+    #[arg(long)]
+    pub input: Option<PathBuf>,
+
+    /// Interval between scans of the guest sources, in milliseconds.
+    #[arg(long, default_value_t = 500)]
+    pub poll_interval_ms: u64,
+}
+
+impl WatchCommand {
+    /// Execute this command.
+    pub fn run(&self) -> Result<()> {
+        let input = match &self.input {
+            Some(path) => fs::read(path)?,
+            None => Vec::new(),
+        };
+        GuestWatcher::new(&self.manifest_path)
+            .features(self.features.clone())
+            .poll_interval(Duration::from_millis(self.poll_interval_ms))
+            .run(|guest| {
+                let env = ExecutorEnv::builder().stdin(input.as_slice()).build()?;
+                let session = default_executor().execute(env, &guest.elf)?;
+                Ok(RunStats {
+                    segments: session.segments.len(),
+                    user_cycles: session.segments.iter().map(|s| s.cycles as u64).sum(),
+                    total_cycles: session.segments.iter().map(|s| 1 << s.po2).sum(),
+                })
+            })
+    }
+}
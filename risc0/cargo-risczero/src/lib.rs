@@ -25,7 +25,7 @@ pub use self::commands::build::BuildSubcommand;
 use clap::{Parser, Subcommand};
 
 #[cfg(feature = "experimental")]
-use self::commands::build::BuildCommand;
+use self::commands::{build::BuildCommand, watch::WatchCommand};
 use self::commands::{
     build_guest::BuildGuest, build_toolchain::BuildToolchain, install::Install, new::NewCommand,
 };
@@ -64,6 +64,9 @@ pub enum RisczeroCmd {
     /// Build and test a crate for RISC Zero.
     #[cfg(feature = "experimental")]
     Test(BuildCommand),
+    /// Rebuild and execute guest code each time its sources change.
+    #[cfg(feature = "experimental")]
+    Watch(WatchCommand),
 }
 
 #[cfg(test)]
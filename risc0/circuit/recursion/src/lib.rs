@@ -37,6 +37,10 @@ use risc0_zkp::{
     taps::TapSet,
 };
 
+/// Version of the seals produced by the recursion programs of this circuit,
+/// such as lift, join and identity_p254.
+pub const SEAL_VERSION: u32 = 1;
+
 pub const REGISTER_GROUP_ACCUM: usize = 0;
 pub const REGISTER_GROUP_CODE: usize = 1;
 pub const REGISTER_GROUP_DATA: usize = 2;
@@ -30,6 +30,15 @@ use risc0_zkp::{
     taps::TapSet,
 };
 
+/// Reports the current version of this crate.
+pub const VERSION: &str = env!("CARGO_PKG_VERSION");
+
+/// Version of the segment seals produced by this circuit.
+///
+/// Incremented whenever a verifier can no longer check the seals of the
+/// previous version.
+pub const SEAL_VERSION: u32 = 1;
+
 pub struct CircuitImpl;
 
 pub const REGISTER_GROUP_ACCUM: usize = 0;
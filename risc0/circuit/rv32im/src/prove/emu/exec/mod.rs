@@ -15,7 +15,13 @@
 #[cfg(test)]
 mod tests;
 
-use std::{array, cell::RefCell, collections::BTreeSet, mem, rc::Rc};
+use std::{
+    array,
+    cell::RefCell,
+    collections::{BTreeSet, VecDeque},
+    mem,
+    rc::Rc,
+};
 
 use anyhow::{bail, Result};
 use crypto_bigint::{CheckedMul as _, Encoding as _, NonZero, U256, U512};
@@ -42,6 +48,7 @@ use sha2::digest::generic_array::GenericArray;
 
 use super::{
     addr::{ByteAddr, WordAddr},
+    guest_ptr::{GuestFault, GuestPtr},
     pager::PagedMemory,
     rv32im::{DecodedInstruction, EmuContext, Emulator, Instruction, TrapCause},
     BIGINT_CYCLES, SYSTEM_START,
@@ -182,6 +189,18 @@ impl<'a, 'b, S: Syscall> Executor<'a, 'b, S> {
         &mut self,
         segment_po2: usize,
         max_cycles: Option<u64>,
+        callback: F,
+    ) -> Result<ExecutorResult> {
+        self.run_until(segment_po2, max_cycles, None, callback)
+    }
+
+    // Like `run`, but when `end_cycle` is reached, ends the last segment with a
+    // system split rather than executing until the guest halts.
+    fn run_until<F: FnMut(Segment) -> Result<()>>(
+        &mut self,
+        segment_po2: usize,
+        max_cycles: Option<u64>,
+        end_cycle: Option<usize>,
         mut callback: F,
     ) -> Result<ExecutorResult> {
         // at least one HaltCycle needs to appear in the body
@@ -204,6 +223,16 @@ impl<'a, 'b, S: Syscall> Executor<'a, 'b, S> {
                 break;
             }
 
+            if let Some(end_cycle) = end_cycle {
+                if self.cycles.user == end_cycle {
+                    self.exit_code = Some(ExitCode::SystemSplit);
+                    break;
+                }
+                if self.cycles.user > end_cycle {
+                    bail!("execution overran the end cycle ({end_cycle})");
+                }
+            }
+
             if let Some(max_cycles) = max_cycles {
                 if self.cycles.user >= max_cycles as usize {
                     bail!("Session limit exceeded");
@@ -345,8 +374,8 @@ impl<'a, 'b, S: Syscall> Executor<'a, 'b, S> {
 impl<'a, 'b, S: Syscall> Executor<'a, 'b, S> {
     fn ecall_halt(&mut self) -> Result<bool> {
         let a0 = self.load_register(REG_A0)?;
-        let output_ptr = self.load_guest_addr_from_register(REG_A1)?;
-        let output: [u8; DIGEST_BYTES] = self.load_array_from_guest(output_ptr)?;
+        let output_ptr = self.load_guest_ptr(REG_A1, DIGEST_WORDS)?;
+        let output: [u8; DIGEST_BYTES] = self.load_array(output_ptr.addr())?;
 
         let halt_type = a0 & 0xff;
         let user_exit = (a0 >> 8) & 0xff;
@@ -367,10 +396,15 @@ impl<'a, 'b, S: Syscall> Executor<'a, 'b, S> {
     fn ecall_software(&mut self) -> Result<bool> {
         tracing::debug!("[{}] ecall_software", self.insn_cycles);
         let into_guest_ptr = ByteAddr(self.load_register(REG_A0)?);
-        if !is_guest_memory(into_guest_ptr.0) && !into_guest_ptr.is_null() {
-            bail!("{into_guest_ptr:?} is an invalid guest address");
-        }
-        let into_guest_len = self.load_register(REG_A1)? as usize;
+        let into_guest_len = self.load_register(REG_A1)?;
+        // The guest uses a null pointer to indicate that a transfer from host
+        // to guest is not needed. Otherwise, the buffer is checked before the
+        // host handles the syscall.
+        let into_guest = match into_guest_ptr.is_null() {
+            true => None,
+            false => Some(GuestPtr::words(into_guest_ptr.0, into_guest_len)?),
+        };
+        let into_guest_len = into_guest_len as usize;
         let name_ptr = self.load_guest_addr_from_register(REG_A2)?;
         let syscall_name = self.peek_string(name_ptr)?;
         let name_end = name_ptr + syscall_name.len();
@@ -397,11 +431,8 @@ impl<'a, 'b, S: Syscall> Executor<'a, 'b, S> {
             syscall
         };
 
-        // The guest uses a null pointer to indicate that a transfer from host
-        // to guest is not needed.
-        if !into_guest_ptr.is_null() {
-            Self::check_guest_addr(into_guest_ptr + into_guest_len)?;
-            self.store_region(into_guest_ptr, bytemuck::cast_slice(&syscall.to_guest))?
+        if let Some(into_guest) = into_guest {
+            self.store_region(into_guest.addr(), bytemuck::cast_slice(&syscall.to_guest))?
         }
 
         let (a0, a1) = syscall.regs;
@@ -418,13 +449,13 @@ impl<'a, 'b, S: Syscall> Executor<'a, 'b, S> {
 
     fn ecall_sha(&mut self) -> Result<bool> {
         tracing::debug!("[{}] ecall_sha", self.insn_cycles);
-        let state_out_ptr = self.load_guest_addr_from_register(REG_A0)?;
-        let state_in_ptr = self.load_guest_addr_from_register(REG_A1)?;
-        let mut block1_ptr = self.load_guest_addr_from_register(REG_A2)?;
-        let mut block2_ptr = self.load_guest_addr_from_register(REG_A3)?;
         let count = self.load_register(REG_A4)?;
+        let state_out_ptr = self.load_guest_ptr(REG_A0, DIGEST_WORDS)?;
+        let state_in_ptr = self.load_guest_ptr(REG_A1, DIGEST_WORDS)?;
+        let mut block1_ptr = self.load_sha_blocks_ptr(REG_A2, count)?.addr();
+        let mut block2_ptr = self.load_sha_blocks_ptr(REG_A3, count)?.addr();
 
-        let state_in: [u8; DIGEST_BYTES] = self.load_array_from_guest(state_in_ptr)?;
+        let state_in: [u8; DIGEST_BYTES] = self.load_array(state_in_ptr.addr())?;
         let mut state: [u32; DIGEST_WORDS] = bytemuck::cast_slice(&state_in).try_into().unwrap();
         for word in &mut state {
             *word = word.to_be();
@@ -456,7 +487,7 @@ impl<'a, 'b, S: Syscall> Executor<'a, 'b, S> {
             *word = u32::from_be(*word);
         }
 
-        self.store_region_into_guest(state_out_ptr, bytemuck::cast_slice(&state))?;
+        self.store_region(state_out_ptr.addr(), bytemuck::cast_slice(&state))?;
 
         self.pending.cycles += sha_cycles(count as usize);
         self.pending.pc = self.pc + WORD_SIZE;
@@ -466,10 +497,10 @@ impl<'a, 'b, S: Syscall> Executor<'a, 'b, S> {
 
     fn ecall_bigint(&mut self) -> Result<bool> {
         let op = self.load_register(REG_A1)?;
-        let z_ptr = self.load_guest_addr_from_register(REG_A0)?;
-        let x_ptr = self.load_guest_addr_from_register(REG_A2)?;
-        let y_ptr = self.load_guest_addr_from_register(REG_A3)?;
-        let n_ptr = self.load_guest_addr_from_register(REG_A4)?;
+        let z_ptr = self.load_guest_ptr(REG_A0, bigint::WIDTH_WORDS)?.addr();
+        let x_ptr = self.load_guest_ptr(REG_A2, bigint::WIDTH_WORDS)?.addr();
+        let y_ptr = self.load_guest_ptr(REG_A3, bigint::WIDTH_WORDS)?.addr();
+        let n_ptr = self.load_guest_ptr(REG_A4, bigint::WIDTH_WORDS)?.addr();
 
         let mut load_bigint_le_bytes = |ptr: ByteAddr| -> Result<[u8; bigint::WIDTH_BYTES]> {
             let mut arr = [0u32; bigint::WIDTH_WORDS];
@@ -526,16 +557,29 @@ impl<'a, 'b, S: Syscall> Executor<'a, 'b, S> {
         Self::check_guest_addr(addr)
     }
 
-    fn load_u32_from_guest(&mut self, addr: ByteAddr) -> Result<u32> {
-        Self::check_guest_addr(addr)?;
-        self.load_memory(addr.waddr())
+    // Loads the word aligned pointer to a buffer of `words` words.
+    fn load_guest_ptr(&mut self, idx: usize, words: usize) -> Result<GuestPtr> {
+        let addr = self.load_register(idx)?;
+        Ok(GuestPtr::words(addr, words as u32)?)
     }
 
-    fn load_array_from_guest<const N: usize>(&mut self, addr: ByteAddr) -> Result<[u8; N]> {
-        // Self::check_guest_addr_range(addr, addr + u32::try_from(N)?)?;
+    // Loads the pointer to the `count` blocks hashed by ecall_sha. Each block
+    // reads a digest from the pointer, which then moves on by a whole block.
+    fn load_sha_blocks_ptr(&mut self, idx: usize, count: u32) -> Result<GuestPtr> {
+        let addr = self.load_register(idx)?;
+        let words = match count {
+            0 => Some(0),
+            _ => (count - 1)
+                .checked_mul(BLOCK_WORDS as u32)
+                .and_then(|words| words.checked_add(DIGEST_WORDS as u32)),
+        };
+        let words = words.ok_or(GuestFault::OutOfBounds { addr, len: None })?;
+        Ok(GuestPtr::words(addr, words)?)
+    }
+
+    fn load_u32_from_guest(&mut self, addr: ByteAddr) -> Result<u32> {
         Self::check_guest_addr(addr)?;
-        Self::check_guest_addr(addr + N)?;
-        self.load_array(addr)
+        self.load_memory(addr.waddr())
     }
 
     fn load_array<const N: usize>(&mut self, addr: ByteAddr) -> Result<[u8; N]> {
@@ -574,12 +618,6 @@ impl<'a, 'b, S: Syscall> Executor<'a, 'b, S> {
         self.store_memory(addr.waddr(), data)
     }
 
-    fn store_region_into_guest(&mut self, addr: ByteAddr, slice: &[u8]) -> Result<()> {
-        Self::check_guest_addr(addr)?;
-        Self::check_guest_addr(addr + slice.len())?;
-        self.store_region(addr, slice)
-    }
-
     fn raw_store_u8(&mut self, addr: ByteAddr, byte: u8) -> Result<()> {
         let byte_offset = addr.0 as usize % WORD_SIZE;
         let word = self.peek_u32(addr)?;
@@ -724,6 +762,79 @@ impl<'a, 'b, S: Syscall> SyscallContext for Executor<'a, 'b, S> {
     }
 }
 
+// Replays the syscalls recorded in a segment, in order.
+struct SyscallReplay {
+    records: RefCell<VecDeque<SyscallRecord>>,
+}
+
+impl Syscall for SyscallReplay {
+    fn syscall(
+        &self,
+        syscall: &str,
+        _ctx: &mut dyn SyscallContext,
+        into_guest: &mut [u32],
+    ) -> Result<(u32, u32)> {
+        let Some(record) = self.records.borrow_mut().pop_front() else {
+            bail!("no recorded result for syscall {syscall}");
+        };
+        if record.to_guest.len() != into_guest.len() {
+            bail!(
+                "recorded result for syscall {syscall} has {} words, expected {}",
+                record.to_guest.len(),
+                into_guest.len()
+            );
+        }
+        into_guest.copy_from_slice(&record.to_guest);
+        Ok(record.regs)
+    }
+}
+
+impl Segment {
+    /// Splits this segment into consecutive segments of at most `2^po2` cycles.
+    ///
+    /// The execution of this segment is replayed from its partial image and
+    /// recorded syscalls, so the host is not involved and the result is
+    /// deterministic. The new segments are indexed from the index of this
+    /// segment, and together prove the same transition from the pre-state to
+    /// the post-state.
+    pub fn split(&self, po2: usize) -> Result<Vec<Segment>> {
+        if po2 < MIN_CYCLES_PO2 || po2 >= self.po2 {
+            bail!(
+                "Invalid po2 for splitting a segment of po2 {}: {po2}",
+                self.po2
+            );
+        }
+
+        let syscall_handler = SyscallReplay {
+            records: RefCell::new(self.syscalls.iter().cloned().collect()),
+        };
+        let end_cycle = (self.exit_code == ExitCode::SystemSplit).then_some(self.insn_cycles);
+        let mut segments = Vec::new();
+        Executor::new(self.partial_image.clone(), &syscall_handler, Vec::new()).run_until(
+            po2,
+            None,
+            end_cycle,
+            |mut segment| {
+                segment.index += self.index;
+                segments.push(segment);
+                Ok(())
+            },
+        )?;
+
+        let last = segments.last().unwrap();
+        if last.exit_code != self.exit_code
+            || last.post_state != self.post_state
+            || !syscall_handler.records.borrow().is_empty()
+        {
+            bail!(
+                "replay of segment {} diverged from its execution",
+                self.index
+            );
+        }
+        Ok(segments)
+    }
+}
+
 #[tracing::instrument(skip_all)]
 pub fn execute<S: Syscall>(
     image: MemoryImage,
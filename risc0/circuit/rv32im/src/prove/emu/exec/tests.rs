@@ -12,10 +12,10 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use std::cell::RefCell;
+use std::{cell::RefCell, collections::BTreeMap};
 
 use anyhow::Result;
-use risc0_binfmt::{Digestible, ExitCode, MemoryImage};
+use risc0_binfmt::{Digestible, ExitCode, MemoryImage, Program};
 use risc0_zkp::core::hash::sha::cpu::Impl as ShaImpl;
 use risc0_zkvm_platform::{
     syscall::reg_abi::{REG_A4, REG_A5},
@@ -27,6 +27,7 @@ use super::{Syscall, SyscallContext};
 use crate::prove::emu::{
     addr::ByteAddr,
     exec::DEFAULT_SEGMENT_LIMIT_PO2,
+    guest_ptr::GuestFault,
     testutil::{self, DEFAULT_SESSION_LIMIT},
 };
 
@@ -101,6 +102,74 @@ fn basic() {
     assert_eq!(segment.exit_code, ExitCode::Halted(0));
 }
 
+#[test]
+fn misaligned_sha() {
+    let program = Program {
+        entry: 0x4000,
+        image: BTreeMap::from([
+            (0x4000, 0x00300293), // li t0, 3 (ecall::SHA)
+            (0x4004, 0x00004537), // lui a0, 0x4
+            (0x4008, 0x00250513), // addi a0, a0, 2
+            (0x400c, 0x000045b7), // lui a1, 0x4
+            (0x4010, 0x00058613), // mv a2, a1
+            (0x4014, 0x00058693), // mv a3, a1
+            (0x4018, 0x00100713), // li a4, 1
+            (0x401c, 0x00000073), // ecall
+        ]),
+    };
+    let image = MemoryImage::new(&program, PAGE_SIZE as u32).unwrap();
+
+    let err = super::execute(
+        image,
+        DEFAULT_SEGMENT_LIMIT_PO2,
+        DEFAULT_SESSION_LIMIT,
+        &BasicSyscall::default(),
+    )
+    .err()
+    .unwrap();
+    assert_eq!(
+        err.downcast_ref::<GuestFault>(),
+        Some(&GuestFault::Misaligned {
+            addr: 0x4002,
+            align: 4
+        })
+    );
+}
+
+#[test]
+fn out_of_bounds_syscall() {
+    let program = Program {
+        entry: 0x4000,
+        image: BTreeMap::from([
+            (0x4000, 0x00200293), // li t0, 2 (ecall::SOFTWARE)
+            (0x4004, 0x0c000537), // lui a0, 0xc000
+            (0x4008, 0xff050513), // addi a0, a0, -16
+            (0x400c, 0x00800593), // li a1, 8
+            (0x4010, 0x00000073), // ecall
+        ]),
+    };
+    let image = MemoryImage::new(&program, PAGE_SIZE as u32).unwrap();
+
+    // The buffer is checked before the host handles the syscall.
+    let handler = BasicSyscall::default();
+    let err = super::execute(
+        image,
+        DEFAULT_SEGMENT_LIMIT_PO2,
+        DEFAULT_SESSION_LIMIT,
+        &handler,
+    )
+    .err()
+    .unwrap();
+    assert_eq!(
+        err.downcast_ref::<GuestFault>(),
+        Some(&GuestFault::OutOfBounds {
+            addr: 0x0bff_fff0,
+            len: Some(32)
+        })
+    );
+    assert!(handler.state().syscall.is_empty());
+}
+
 #[test]
 fn system_split() {
     let program = testutil::simple_loop();
@@ -122,3 +191,81 @@ fn system_split() {
         segments[0].post_state.digest::<ShaImpl>()
     );
 }
+
+#[test]
+fn split_segment() {
+    let program = testutil::simple_loop();
+    let image = MemoryImage::new(&program, PAGE_SIZE as u32).unwrap();
+
+    let session = super::execute(
+        image.clone(),
+        DEFAULT_SEGMENT_LIMIT_PO2,
+        DEFAULT_SESSION_LIMIT,
+        &BasicSyscall::default(),
+    )
+    .unwrap();
+    assert_eq!(session.segments.len(), 1);
+
+    let expected = super::execute(image, 14, DEFAULT_SESSION_LIMIT, &BasicSyscall::default())
+        .unwrap()
+        .segments;
+    let segments = session.segments[0].split(14).unwrap();
+    assert_eq!(segments.len(), expected.len());
+    for (segment, expected) in segments.iter().zip(expected.iter()) {
+        assert_eq!(segment.index, expected.index);
+        assert_eq!(segment.exit_code, expected.exit_code);
+        assert_eq!(segment.insn_cycles, expected.insn_cycles);
+        assert_eq!(segment.po2, expected.po2);
+        assert_eq!(
+            segment.pre_state.digest::<ShaImpl>(),
+            expected.pre_state.digest::<ShaImpl>()
+        );
+        assert_eq!(
+            segment.post_state.digest::<ShaImpl>(),
+            expected.post_state.digest::<ShaImpl>()
+        );
+    }
+
+    // A segment ending in a system split can be split further.
+    let program = Program {
+        entry: 0x4000,
+        image: BTreeMap::from([
+            (0x4000, 0x00000713), // li      a4,0
+            (0x4004, 0x000087b7), // lui     a5,0x8
+            (0x4008, 0x00170713), // add     a4,a4,1
+            (0x400c, 0xfef74ee3), // blt     a4,a5,8 <loop>
+            (0x4010, 0x010005b7), // lui     a1,0x1000
+            (0x4014, 0x00000073), // ecall
+        ]),
+    };
+    let image = MemoryImage::new(&program, PAGE_SIZE as u32).unwrap();
+    let session =
+        super::execute(image, 16, DEFAULT_SESSION_LIMIT, &BasicSyscall::default()).unwrap();
+    let segment = &session.segments[0];
+    assert_eq!(segment.exit_code, ExitCode::SystemSplit);
+    let parts = segment.split(14).unwrap();
+    assert!(parts.len() > 1);
+    assert_eq!(
+        parts[0].pre_state.digest::<ShaImpl>(),
+        segment.pre_state.digest::<ShaImpl>()
+    );
+    for pair in parts.windows(2) {
+        assert_eq!(pair[0].exit_code, ExitCode::SystemSplit);
+        assert_eq!(
+            pair[1].pre_state.digest::<ShaImpl>(),
+            pair[0].post_state.digest::<ShaImpl>()
+        );
+    }
+    let last = parts.last().unwrap();
+    assert_eq!(last.exit_code, ExitCode::SystemSplit);
+    assert_eq!(
+        last.post_state.digest::<ShaImpl>(),
+        segment.post_state.digest::<ShaImpl>()
+    );
+    assert_eq!(
+        parts.iter().map(|part| part.insn_cycles).sum::<usize>(),
+        segment.insn_cycles
+    );
+
+    assert!(segments[0].split(14).is_err());
+}
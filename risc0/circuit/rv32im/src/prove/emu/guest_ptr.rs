@@ -0,0 +1,184 @@
+// Copyright 2024 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Validation of the buffers passed by the guest to ecalls and syscalls.
+
+use std::fmt;
+
+use risc0_zkvm_platform::{
+    memory::{GUEST_MAX_MEM, GUEST_MIN_MEM},
+    WORD_SIZE,
+};
+
+use super::addr::ByteAddr;
+
+/// Error returned when the guest passes an invalid buffer to an ecall or a
+/// syscall.
+///
+/// The executor stops with this error before touching the buffer, so the
+/// fault can be told apart from host errors with `anyhow::Error::downcast_ref`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum GuestFault {
+    /// The buffer is not fully within guest memory.
+    OutOfBounds {
+        /// The start of the buffer.
+        addr: u32,
+        /// The length of the buffer, in bytes, if it fits in a `u32`.
+        len: Option<u32>,
+    },
+
+    /// The buffer is not aligned as required.
+    Misaligned {
+        /// The start of the buffer.
+        addr: u32,
+        /// The required alignment, in bytes.
+        align: u32,
+    },
+}
+
+impl fmt::Display for GuestFault {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::OutOfBounds {
+                addr,
+                len: Some(len),
+            } => write!(
+                f,
+                "0x{addr:08x} with length {len} is an invalid guest address"
+            ),
+            Self::OutOfBounds { addr, len: None } => write!(
+                f,
+                "0x{addr:08x} with an overflowing length is an invalid guest address"
+            ),
+            Self::Misaligned { addr, align } => {
+                write!(f, "0x{addr:08x} is not aligned to {align} bytes")
+            }
+        }
+    }
+}
+
+impl std::error::Error for GuestFault {}
+
+/// A buffer in guest memory, checked to be fully within guest memory and
+/// aligned.
+///
+/// Syscall handlers can use it to check the pointers and lengths read from
+/// guest registers before reading or writing guest memory:
+///
+/// ```ignore
+/// let buf = GuestPtr::new(ctx.peek_register(REG_A3)?, ctx.peek_register(REG_A4)?)?;
+/// let from_guest = ctx.peek_region(buf.addr(), buf.len())?;
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct GuestPtr {
+    addr: u32,
+    len: u32,
+}
+
+impl GuestPtr {
+    /// Checks the buffer of `len` bytes at `addr`, with no alignment
+    /// requirement.
+    pub fn new(addr: u32, len: u32) -> Result<Self, GuestFault> {
+        Self::aligned(addr, len, 1)
+    }
+
+    /// Checks the buffer of `len` bytes at `addr`, which must be a multiple
+    /// of `align`, a power of two.
+    pub fn aligned(addr: u32, len: u32, align: u32) -> Result<Self, GuestFault> {
+        debug_assert!(align.is_power_of_two());
+        if addr % align != 0 {
+            return Err(GuestFault::Misaligned { addr, align });
+        }
+        let in_bounds = (addr as usize) >= GUEST_MIN_MEM
+            && (addr as usize)
+                .checked_add(len as usize)
+                .is_some_and(|end| end <= GUEST_MAX_MEM);
+        if !in_bounds {
+            return Err(GuestFault::OutOfBounds {
+                addr,
+                len: Some(len),
+            });
+        }
+        Ok(Self { addr, len })
+    }
+
+    /// Checks the buffer of `words` words at `addr`, which must be word
+    /// aligned.
+    pub fn words(addr: u32, words: u32) -> Result<Self, GuestFault> {
+        let len = words
+            .checked_mul(WORD_SIZE as u32)
+            .ok_or(GuestFault::OutOfBounds { addr, len: None })?;
+        Self::aligned(addr, len, WORD_SIZE as u32)
+    }
+
+    /// The start of the buffer.
+    pub fn addr(&self) -> ByteAddr {
+        ByteAddr(self.addr)
+    }
+
+    /// The length of the buffer, in bytes.
+    pub fn len(&self) -> u32 {
+        self.len
+    }
+
+    /// Returns whether the buffer is empty.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bounds() {
+        let min = GUEST_MIN_MEM as u32;
+        let max = GUEST_MAX_MEM as u32;
+        assert_eq!(GuestPtr::new(min, 10).unwrap().len(), 10);
+        assert!(GuestPtr::new(max - 10, 10).is_ok());
+        assert!(GuestPtr::new(max, 0).is_ok());
+        assert_eq!(
+            GuestPtr::new(max - 10, 11),
+            Err(GuestFault::OutOfBounds {
+                addr: max - 10,
+                len: Some(11)
+            })
+        );
+        assert!(GuestPtr::new(0, 0).is_err());
+        assert!(GuestPtr::new(min - 1, 1).is_err());
+        assert!(GuestPtr::new(u32::MAX, 2).is_err());
+        assert_eq!(
+            GuestPtr::words(min, u32::MAX),
+            Err(GuestFault::OutOfBounds {
+                addr: min,
+                len: None
+            })
+        );
+    }
+
+    #[test]
+    fn alignment() {
+        let min = GUEST_MIN_MEM as u32;
+        assert_eq!(GuestPtr::words(min, 8).unwrap().len(), 32);
+        assert_eq!(
+            GuestPtr::words(min + 2, 8),
+            Err(GuestFault::Misaligned {
+                addr: min + 2,
+                align: 4
+            })
+        );
+        assert!(GuestPtr::new(min + 3, 5).is_ok());
+    }
+}
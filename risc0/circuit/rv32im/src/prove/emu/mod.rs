@@ -14,6 +14,7 @@
 
 pub mod addr;
 pub mod exec;
+pub mod guest_ptr;
 pub mod mux;
 mod pager;
 pub mod preflight;
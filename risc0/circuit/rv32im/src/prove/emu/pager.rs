@@ -15,6 +15,7 @@
 use std::{
     collections::{BTreeMap, BTreeSet, HashMap},
     mem::take,
+    sync::Arc,
 };
 
 use anyhow::Result;
@@ -135,24 +136,18 @@ impl PagedMemory {
         let pre_state = self.image.get_system_state();
         let info = &self.image.info;
 
-        let mut image = MemoryImage {
-            pages: BTreeMap::new(),
-            info: info.clone(),
-            pc: pre_state.pc,
-        };
+        let mut image = MemoryImage::from_pages(info.clone(), pre_state.pc, BTreeMap::new());
 
         for (page_idx, page_state) in &self.page_states {
-            // Copy 'original' version of all pages, this is just the subset of
+            // Share 'original' version of all pages, this is just the subset of
             // pages for the previous segment.
-            image
-                .pages
-                .insert(*page_idx, self.image.load_page(*page_idx));
+            image.set_page(*page_idx, self.image.share_page(*page_idx));
 
             // Update all 'dirty' pages into the image that accumulates over
             // segments.
             if *page_state == PageState::Dirty {
                 let page = self.page_cache.get(page_idx).unwrap();
-                self.image.pages.insert(*page_idx, page.0.clone());
+                self.image.set_page(*page_idx, Arc::new(page.0.clone()));
             }
         }
 
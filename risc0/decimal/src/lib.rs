@@ -0,0 +1,988 @@
+// Copyright 2024 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+#![doc = include_str!("../README.md")]
+#![cfg_attr(not(feature = "std"), no_std)]
+#![deny(missing_docs)]
+
+extern crate alloc;
+
+use alloc::{string::String, vec, vec::Vec};
+use core::{
+    cmp::Ordering,
+    fmt,
+    ops::{Add, Mul, Neg, Sub},
+    str::FromStr,
+};
+
+use serde::{Deserialize, Serialize};
+
+/// Base of the limbs of a [Decimal], the largest power of ten below `2^32`.
+const BASE: u32 = 1_000_000_000;
+
+/// Largest scale of a [Decimal].
+///
+/// Values are rescaled to a common scale to be added or compared, which takes
+/// memory linear in the difference of their scales, so the scale is bounded to
+/// keep this cost bounded for parsed and deserialized values.
+pub const MAX_SCALE: u32 = 4096;
+
+/// Number of decimal digits in a limb.
+const LIMB_DIGITS: u32 = 9;
+
+/// `2^32 mod BASE`.
+const BASE_REM_2_32: u64 = (1 << 32) - 4 * BASE as u64;
+
+const POW10: [u32; 10] = [
+    1,
+    10,
+    100,
+    1_000,
+    10_000,
+    100_000,
+    1_000_000,
+    10_000_000,
+    100_000_000,
+    1_000_000_000,
+];
+
+/// An arbitrary-precision decimal number.
+///
+/// The value of a [Decimal] is an integer mantissa of any size divided by
+/// `10^scale`, with a scale of at most [MAX_SCALE]. Additions, subtractions
+/// and multiplications are exact, and the scale of their result is large
+/// enough to hold it. Use [Decimal::round] to
+/// bring a result back to a fixed number of fractional digits, and
+/// [Decimal::checked_div] to divide with an explicit scale and rounding mode.
+///
+/// Equality and ordering compare values, so `1.0` and `1.00` are equal.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(try_from = "DecimalParts", into = "DecimalParts")]
+pub struct Decimal {
+    negative: bool,
+    /// Magnitude of the mantissa, as little-endian base `10^9` limbs without
+    /// leading zero limbs. Empty for zero.
+    limbs: Vec<u32>,
+    /// Number of fractional decimal digits.
+    scale: u32,
+}
+
+/// How [Decimal::round] and [Decimal::checked_div] discard digits.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RoundingMode {
+    /// Round towards zero.
+    Down,
+    /// Round away from zero.
+    Up,
+    /// Round towards negative infinity.
+    Floor,
+    /// Round towards positive infinity.
+    Ceiling,
+    /// Round to the nearest value, away from zero on ties.
+    HalfUp,
+    /// Round to the nearest value, to the even neighbor on ties.
+    HalfEven,
+}
+
+/// Error returned when parsing or deserializing an invalid [Decimal].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ParseDecimalError;
+
+impl fmt::Display for ParseDecimalError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("invalid decimal")
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for ParseDecimalError {}
+
+/// Serialized representation of a [Decimal], validated on deserialization.
+#[derive(Serialize, Deserialize)]
+struct DecimalParts {
+    negative: bool,
+    limbs: Vec<u32>,
+    scale: u32,
+}
+
+impl TryFrom<DecimalParts> for Decimal {
+    type Error = ParseDecimalError;
+
+    fn try_from(parts: DecimalParts) -> Result<Self, Self::Error> {
+        if parts.scale > MAX_SCALE || parts.limbs.iter().any(|&limb| limb >= BASE) {
+            return Err(ParseDecimalError);
+        }
+        Ok(Self::from_mag(parts.negative, parts.limbs, parts.scale))
+    }
+}
+
+impl From<Decimal> for DecimalParts {
+    fn from(value: Decimal) -> Self {
+        Self {
+            negative: value.negative,
+            limbs: value.limbs,
+            scale: value.scale,
+        }
+    }
+}
+
+impl Decimal {
+    /// The value zero.
+    pub const ZERO: Decimal = Decimal {
+        negative: false,
+        limbs: Vec::new(),
+        scale: 0,
+    };
+
+    /// Construct `mantissa / 10^scale`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `scale` is larger than [MAX_SCALE].
+    pub fn new(mantissa: i128, scale: u32) -> Self {
+        assert!(
+            scale <= MAX_SCALE,
+            "decimal scale {scale} exceeds MAX_SCALE"
+        );
+        Self::from_mag(
+            mantissa < 0,
+            limbs_from_u128(mantissa.unsigned_abs()),
+            scale,
+        )
+    }
+
+    fn from_mag(negative: bool, mut limbs: Vec<u32>, scale: u32) -> Self {
+        trim(&mut limbs);
+        Self {
+            negative: negative && !limbs.is_empty(),
+            limbs,
+            scale,
+        }
+    }
+
+    /// Number of fractional digits of this value.
+    pub fn scale(&self) -> u32 {
+        self.scale
+    }
+
+    /// Returns true if this value is zero.
+    pub fn is_zero(&self) -> bool {
+        self.limbs.is_empty()
+    }
+
+    /// Returns true if this value is less than zero.
+    pub fn is_negative(&self) -> bool {
+        self.negative
+    }
+
+    /// Returns the absolute value.
+    pub fn abs(&self) -> Self {
+        Self {
+            negative: false,
+            ..self.clone()
+        }
+    }
+
+    /// Returns this value with `scale` fractional digits, rounded according
+    /// to `mode` if digits are discarded.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `scale` is larger than [MAX_SCALE].
+    pub fn round(&self, scale: u32, mode: RoundingMode) -> Self {
+        assert!(
+            scale <= MAX_SCALE,
+            "decimal scale {scale} exceeds MAX_SCALE"
+        );
+        if scale >= self.scale {
+            let limbs = mul_pow10(&self.limbs, scale - self.scale);
+            return Self::from_mag(self.negative, limbs, scale);
+        }
+        let (mut limbs, half, inexact) = div_pow10(&self.limbs, self.scale - scale);
+        if round_away(mode, self.negative, is_odd(&limbs), half, inexact) {
+            add_small(&mut limbs, 1);
+        }
+        Self::from_mag(self.negative, limbs, scale)
+    }
+
+    /// Returns this value with trailing fractional zeros removed.
+    pub fn normalize(&self) -> Self {
+        let zeros = trailing_zeros(&self.limbs).min(self.scale);
+        let (limbs, _, _) = div_pow10(&self.limbs, zeros);
+        Self::from_mag(self.negative, limbs, self.scale - zeros)
+    }
+
+    /// Divides by `rhs`, returning a quotient with `scale` fractional digits
+    /// rounded according to `mode`, or [None] if `rhs` is zero or `scale` is
+    /// larger than [MAX_SCALE].
+    pub fn checked_div(&self, rhs: &Decimal, scale: u32, mode: RoundingMode) -> Option<Self> {
+        if rhs.is_zero() || scale > MAX_SCALE {
+            return None;
+        }
+        let negative = self.negative != rhs.negative;
+        // The mantissa of the quotient is
+        // `self.limbs * 10^(scale + rhs.scale - self.scale) / rhs.limbs`.
+        let shift = scale as i64 + rhs.scale as i64 - self.scale as i64;
+        let (num, den) = if shift >= 0 {
+            (mul_pow10(&self.limbs, shift as u32), rhs.limbs.clone())
+        } else {
+            (self.limbs.clone(), mul_pow10(&rhs.limbs, (-shift) as u32))
+        };
+        let (mut quot, mut rem) = div_rem(&num, &den);
+        let inexact = !rem.is_empty();
+        mul_small(&mut rem, 2);
+        let half = cmp_mag(&rem, &den);
+        if round_away(mode, negative, is_odd(&quot), half, inexact) {
+            add_small(&mut quot, 1);
+        }
+        Some(Self::from_mag(negative, quot, scale))
+    }
+
+    /// Multiplies by `rhs`, exactly, or returns [None] if the scale of the
+    /// product, the sum of the scales of the factors, is larger than
+    /// [MAX_SCALE].
+    pub fn checked_mul(&self, rhs: &Decimal) -> Option<Self> {
+        let scale = self
+            .scale
+            .checked_add(rhs.scale)
+            .filter(|&scale| scale <= MAX_SCALE)?;
+        Some(Self::from_mag(
+            self.negative != rhs.negative,
+            mul_mag(&self.limbs, &rhs.limbs),
+            scale,
+        ))
+    }
+
+    /// Raises this value to the power `exp`, exactly.
+    ///
+    /// The scale of the result is `exp` times the scale of this value.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the scale of the result is larger than [MAX_SCALE], see
+    /// [Decimal::checked_pow].
+    pub fn pow(&self, exp: u32) -> Self {
+        self.checked_pow(exp)
+            .expect("decimal scale exceeds MAX_SCALE")
+    }
+
+    /// Raises this value to the power `exp`, exactly, or returns [None] if the
+    /// scale of the result is larger than [MAX_SCALE].
+    pub fn checked_pow(&self, mut exp: u32) -> Option<Self> {
+        self.scale
+            .checked_mul(exp)
+            .filter(|&scale| scale <= MAX_SCALE)?;
+        let mut base = self.clone();
+        let mut acc = Self::from(1u32);
+        while exp > 0 {
+            if exp & 1 == 1 {
+                acc = acc.checked_mul(&base)?;
+            }
+            exp >>= 1;
+            if exp > 0 {
+                base = base.checked_mul(&base)?;
+            }
+        }
+        Some(acc)
+    }
+
+    /// Compares the magnitudes of `self` and `rhs`.
+    fn cmp_abs(&self, rhs: &Decimal) -> Ordering {
+        match self.scale.cmp(&rhs.scale) {
+            Ordering::Equal => cmp_mag(&self.limbs, &rhs.limbs),
+            Ordering::Less => cmp_mag(&mul_pow10(&self.limbs, rhs.scale - self.scale), &rhs.limbs),
+            Ordering::Greater => {
+                cmp_mag(&self.limbs, &mul_pow10(&rhs.limbs, self.scale - rhs.scale))
+            }
+        }
+    }
+
+    /// Adds `rhs` with its sign flipped if `negate` is set.
+    fn add_signed(&self, rhs: &Decimal, negate: bool) -> Decimal {
+        let scale = self.scale.max(rhs.scale);
+        let lhs_limbs = mul_pow10(&self.limbs, scale - self.scale);
+        let rhs_limbs = mul_pow10(&rhs.limbs, scale - rhs.scale);
+        let rhs_negative = rhs.negative != negate;
+        if self.negative == rhs_negative {
+            return Self::from_mag(self.negative, add_mag(&lhs_limbs, &rhs_limbs), scale);
+        }
+        match cmp_mag(&lhs_limbs, &rhs_limbs) {
+            Ordering::Less => Self::from_mag(rhs_negative, sub_mag(&rhs_limbs, &lhs_limbs), scale),
+            _ => Self::from_mag(self.negative, sub_mag(&lhs_limbs, &rhs_limbs), scale),
+        }
+    }
+}
+
+impl From<u64> for Decimal {
+    fn from(value: u64) -> Self {
+        Self::new(value as i128, 0)
+    }
+}
+
+impl From<i64> for Decimal {
+    fn from(value: i64) -> Self {
+        Self::new(value as i128, 0)
+    }
+}
+
+impl From<u32> for Decimal {
+    fn from(value: u32) -> Self {
+        Self::new(value as i128, 0)
+    }
+}
+
+impl From<i32> for Decimal {
+    fn from(value: i32) -> Self {
+        Self::new(value as i128, 0)
+    }
+}
+
+impl PartialEq for Decimal {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Decimal {}
+
+impl PartialOrd for Decimal {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Decimal {
+    fn cmp(&self, other: &Self) -> Ordering {
+        match (self.negative, other.negative) {
+            (false, true) => Ordering::Greater,
+            (true, false) => Ordering::Less,
+            (false, false) => self.cmp_abs(other),
+            (true, true) => other.cmp_abs(self),
+        }
+    }
+}
+
+impl Neg for &Decimal {
+    type Output = Decimal;
+
+    fn neg(self) -> Decimal {
+        Decimal::from_mag(!self.negative, self.limbs.clone(), self.scale)
+    }
+}
+
+impl Neg for Decimal {
+    type Output = Decimal;
+
+    fn neg(self) -> Decimal {
+        Decimal::from_mag(!self.negative, self.limbs, self.scale)
+    }
+}
+
+impl Add for &Decimal {
+    type Output = Decimal;
+
+    fn add(self, rhs: &Decimal) -> Decimal {
+        self.add_signed(rhs, false)
+    }
+}
+
+impl Sub for &Decimal {
+    type Output = Decimal;
+
+    fn sub(self, rhs: &Decimal) -> Decimal {
+        self.add_signed(rhs, true)
+    }
+}
+
+impl Mul for &Decimal {
+    type Output = Decimal;
+
+    /// # Panics
+    ///
+    /// Panics if the scale of the product is larger than [MAX_SCALE], see
+    /// [Decimal::checked_mul].
+    fn mul(self, rhs: &Decimal) -> Decimal {
+        self.checked_mul(rhs)
+            .expect("decimal scale exceeds MAX_SCALE")
+    }
+}
+
+macro_rules! impl_owned_op {
+    ($op:ident, $method:ident) => {
+        impl $op for Decimal {
+            type Output = Decimal;
+
+            fn $method(self, rhs: Decimal) -> Decimal {
+                (&self).$method(&rhs)
+            }
+        }
+    };
+}
+
+impl_owned_op!(Add, add);
+impl_owned_op!(Sub, sub);
+impl_owned_op!(Mul, mul);
+
+impl fmt::Display for Decimal {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut digits = String::new();
+        if let Some((top, rest)) = self.limbs.split_last() {
+            digits.push_str(&alloc::format!("{top}"));
+            for limb in rest.iter().rev() {
+                digits.push_str(&alloc::format!("{limb:09}"));
+            }
+        }
+        let scale = self.scale as usize;
+        if digits.len() <= scale {
+            let zeros = scale + 1 - digits.len();
+            digits.insert_str(0, &"0".repeat(zeros));
+        }
+        if self.negative {
+            f.write_str("-")?;
+        }
+        let (int, frac) = digits.split_at(digits.len() - scale);
+        f.write_str(int)?;
+        if !frac.is_empty() {
+            write!(f, ".{frac}")?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for Decimal {
+    type Err = ParseDecimalError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (negative, s) = match s.as_bytes().first() {
+            Some(b'-') => (true, &s[1..]),
+            Some(b'+') => (false, &s[1..]),
+            _ => (false, s),
+        };
+        let (int, frac) = s.split_once('.').unwrap_or((s, ""));
+        if int.is_empty() && frac.is_empty() {
+            return Err(ParseDecimalError);
+        }
+        let digits = || int.bytes().chain(frac.bytes());
+        if !digits().all(|digit| digit.is_ascii_digit()) {
+            return Err(ParseDecimalError);
+        }
+        let scale = u32::try_from(frac.len())
+            .ok()
+            .filter(|&scale| scale <= MAX_SCALE)
+            .ok_or(ParseDecimalError)?;
+
+        let digits: Vec<u8> = digits().map(|digit| digit - b'0').collect();
+        let limbs = digits
+            .rchunks(LIMB_DIGITS as usize)
+            .map(|chunk| {
+                chunk
+                    .iter()
+                    .fold(0u32, |limb, &digit| limb * 10 + digit as u32)
+            })
+            .collect();
+        Ok(Self::from_mag(negative, limbs, scale))
+    }
+}
+
+/// Returns true if rounding with `mode` moves the truncated magnitude away
+/// from zero, given how the discarded part compares to one half and whether
+/// it is non-zero.
+fn round_away(
+    mode: RoundingMode,
+    negative: bool,
+    odd: bool,
+    half: Ordering,
+    inexact: bool,
+) -> bool {
+    match mode {
+        RoundingMode::Down => false,
+        RoundingMode::Up => inexact,
+        RoundingMode::Floor => negative && inexact,
+        RoundingMode::Ceiling => !negative && inexact,
+        RoundingMode::HalfUp => half != Ordering::Less,
+        RoundingMode::HalfEven => half == Ordering::Greater || (half == Ordering::Equal && odd),
+    }
+}
+
+/// Splits `x` into `x / BASE` and `x % BASE`.
+///
+/// Uses only 32-bit divisions, which are single instructions in the zkVM,
+/// rather than a call to the much slower software 64-bit division.
+#[inline]
+fn div_rem_base(mut x: u64) -> (u64, u32) {
+    let mut quot = 0u64;
+    loop {
+        let hi = (x >> 32) as u32;
+        let lo = x as u32;
+        if hi == 0 {
+            return (quot + (lo / BASE) as u64, lo % BASE);
+        }
+        // x = hi * 2^32 + lo = 4 * BASE * hi + (hi * (2^32 mod BASE) + lo)
+        quot += 4 * hi as u64;
+        x = hi as u64 * BASE_REM_2_32 + lo as u64;
+    }
+}
+
+fn limbs_from_u128(mut value: u128) -> Vec<u32> {
+    let mut limbs = Vec::new();
+    while value != 0 {
+        limbs.push((value % BASE as u128) as u32);
+        value /= BASE as u128;
+    }
+    limbs
+}
+
+fn trim(limbs: &mut Vec<u32>) {
+    while limbs.last() == Some(&0) {
+        limbs.pop();
+    }
+}
+
+fn is_odd(limbs: &[u32]) -> bool {
+    // BASE is even, so the parity of a number is the parity of its low limb.
+    limbs.first().is_some_and(|limb| limb & 1 == 1)
+}
+
+fn cmp_mag(lhs: &[u32], rhs: &[u32]) -> Ordering {
+    lhs.len()
+        .cmp(&rhs.len())
+        .then_with(|| lhs.iter().rev().cmp(rhs.iter().rev()))
+}
+
+fn add_mag(lhs: &[u32], rhs: &[u32]) -> Vec<u32> {
+    let (long, short) = if lhs.len() >= rhs.len() {
+        (lhs, rhs)
+    } else {
+        (rhs, lhs)
+    };
+    let mut out = Vec::with_capacity(long.len() + 1);
+    let mut carry = 0;
+    for (i, &limb) in long.iter().enumerate() {
+        let mut sum = limb + short.get(i).copied().unwrap_or(0) + carry;
+        carry = (sum >= BASE) as u32;
+        if carry != 0 {
+            sum -= BASE;
+        }
+        out.push(sum);
+    }
+    if carry != 0 {
+        out.push(carry);
+    }
+    out
+}
+
+/// Returns `lhs - rhs`, which must not be negative.
+fn sub_mag(lhs: &[u32], rhs: &[u32]) -> Vec<u32> {
+    let mut out = Vec::with_capacity(lhs.len());
+    let mut borrow = 0;
+    for (i, &limb) in lhs.iter().enumerate() {
+        let sub = rhs.get(i).copied().unwrap_or(0) + borrow;
+        borrow = (limb < sub) as u32;
+        out.push(limb + borrow * BASE - sub);
+    }
+    debug_assert_eq!(borrow, 0);
+    trim(&mut out);
+    out
+}
+
+fn mul_mag(lhs: &[u32], rhs: &[u32]) -> Vec<u32> {
+    if lhs.is_empty() || rhs.is_empty() {
+        return Vec::new();
+    }
+    let mut out = vec![0u32; lhs.len() + rhs.len()];
+    for (i, &a) in lhs.iter().enumerate() {
+        let mut carry = 0u64;
+        for (j, &b) in rhs.iter().enumerate() {
+            // At most (BASE - 1)^2 + 2 * (BASE - 1), which fits in 64 bits.
+            let (hi, lo) = div_rem_base(a as u64 * b as u64 + out[i + j] as u64 + carry);
+            out[i + j] = lo;
+            carry = hi;
+        }
+        out[i + rhs.len()] = carry as u32;
+    }
+    trim(&mut out);
+    out
+}
+
+/// Multiplies `limbs` in place by `factor`, which must not exceed `BASE`.
+fn mul_small(limbs: &mut Vec<u32>, factor: u32) {
+    let mut carry = 0u64;
+    for limb in limbs.iter_mut() {
+        let (hi, lo) = div_rem_base(*limb as u64 * factor as u64 + carry);
+        *limb = lo;
+        carry = hi;
+    }
+    if carry != 0 {
+        limbs.push(carry as u32);
+    }
+}
+
+fn add_small(limbs: &mut Vec<u32>, mut carry: u32) {
+    for limb in limbs.iter_mut() {
+        if carry == 0 {
+            return;
+        }
+        *limb += carry;
+        carry = (*limb >= BASE) as u32;
+        if carry != 0 {
+            *limb -= BASE;
+        }
+    }
+    if carry != 0 {
+        limbs.push(carry);
+    }
+}
+
+/// Divides `limbs` in place by the non-zero `divisor`, returning the
+/// remainder.
+fn div_rem_small(limbs: &mut Vec<u32>, divisor: u32) -> u32 {
+    let mut rem = 0u64;
+    for limb in limbs.iter_mut().rev() {
+        let cur = rem * BASE as u64 + *limb as u64;
+        *limb = (cur / divisor as u64) as u32;
+        rem = cur % divisor as u64;
+    }
+    trim(limbs);
+    rem as u32
+}
+
+fn mul_pow10(limbs: &[u32], exp: u32) -> Vec<u32> {
+    if limbs.is_empty() {
+        return Vec::new();
+    }
+    let mut out = vec![0u32; (exp / LIMB_DIGITS) as usize];
+    out.extend_from_slice(limbs);
+    let rem = exp % LIMB_DIGITS;
+    if rem != 0 {
+        mul_small(&mut out, POW10[rem as usize]);
+    }
+    out
+}
+
+/// Divides `limbs` by `10^exp`, truncating.
+///
+/// Also returns how the discarded part compares to one half of `10^exp`, and
+/// whether it is non-zero.
+fn div_pow10(limbs: &[u32], exp: u32) -> (Vec<u32>, Ordering, bool) {
+    if exp == 0 {
+        return (limbs.to_vec(), Ordering::Less, false);
+    }
+    // The most significant discarded digit decides the comparison to one half,
+    // unless it is a 5.
+    let pos = exp - 1;
+    let idx = (pos / LIMB_DIGITS) as usize;
+    let pow = POW10[(pos % LIMB_DIGITS) as usize];
+    let (digit, below) = match limbs.get(idx) {
+        Some(&limb) => ((limb / pow) % 10, limb % pow != 0),
+        None => (0, false),
+    };
+    let below = below || limbs[..idx.min(limbs.len())].iter().any(|&limb| limb != 0);
+    let half = match digit.cmp(&5) {
+        Ordering::Equal if below => Ordering::Greater,
+        ord => ord,
+    };
+
+    let skip = (exp / LIMB_DIGITS) as usize;
+    let mut out = limbs.get(skip..).unwrap_or_default().to_vec();
+    let rem = exp % LIMB_DIGITS;
+    if rem != 0 {
+        div_rem_small(&mut out, POW10[rem as usize]);
+    }
+    (out, half, digit != 0 || below)
+}
+
+/// Number of trailing zero decimal digits of `limbs`.
+fn trailing_zeros(limbs: &[u32]) -> u32 {
+    let mut zeros = 0;
+    for &limb in limbs {
+        if limb == 0 {
+            zeros += LIMB_DIGITS;
+            continue;
+        }
+        let mut limb = limb;
+        while limb % 10 == 0 {
+            zeros += 1;
+            limb /= 10;
+        }
+        break;
+    }
+    zeros
+}
+
+/// Divides `num` by the non-zero `den`, returning the quotient and remainder.
+///
+/// This is algorithm D of Knuth's TAOCP vol. 2, section 4.3.1, in base `10^9`.
+fn div_rem(num: &[u32], den: &[u32]) -> (Vec<u32>, Vec<u32>) {
+    if cmp_mag(num, den) == Ordering::Less {
+        return (Vec::new(), num.to_vec());
+    }
+    if den.len() == 1 {
+        let mut quot = num.to_vec();
+        let rem = div_rem_small(&mut quot, den[0]);
+        let mut rem = vec![rem];
+        trim(&mut rem);
+        return (quot, rem);
+    }
+
+    // Normalize so that the top limb of the divisor is at least BASE / 2,
+    // which bounds the error of each estimated quotient limb by 2.
+    let norm = BASE / (den[den.len() - 1] + 1);
+    let mut u = num.to_vec();
+    mul_small(&mut u, norm);
+    u.resize(num.len() + 1, 0);
+    let mut v = den.to_vec();
+    mul_small(&mut v, norm);
+
+    let n = v.len();
+    let m = num.len() - n;
+    let base = BASE as u64;
+    let v_top = v[n - 1] as u64;
+    let v_next = v[n - 2] as u64;
+    let mut quot = vec![0u32; m + 1];
+    for j in (0..=m).rev() {
+        let top = u[j + n] as u64 * base + u[j + n - 1] as u64;
+        let mut qhat = top / v_top;
+        let mut rhat = top % v_top;
+        while qhat >= base || qhat * v_next > rhat * base + u[j + n - 2] as u64 {
+            qhat -= 1;
+            rhat += v_top;
+            if rhat >= base {
+                break;
+            }
+        }
+
+        // u[j..=j + n] -= qhat * v
+        let mut carry = 0u64;
+        let mut borrow = 0u32;
+        for (i, &limb) in v.iter().enumerate() {
+            let (hi, lo) = div_rem_base(qhat * limb as u64 + carry);
+            carry = hi;
+            let sub = lo + borrow;
+            borrow = (u[i + j] < sub) as u32;
+            u[i + j] = u[i + j] + borrow * BASE - sub;
+        }
+        let sub = carry + borrow as u64;
+        if (u[j + n] as u64) < sub {
+            // The estimate was one too large: add the divisor back.
+            qhat -= 1;
+            let mut carry = 0;
+            for (i, &limb) in v.iter().enumerate() {
+                let mut sum = u[i + j] + limb + carry;
+                carry = (sum >= BASE) as u32;
+                if carry != 0 {
+                    sum -= BASE;
+                }
+                u[i + j] = sum;
+            }
+            u[j + n] = (u[j + n] as u64 + carry as u64 - sub) as u32;
+        } else {
+            u[j + n] -= sub as u32;
+        }
+        quot[j] = qhat as u32;
+    }
+
+    u.truncate(n);
+    trim(&mut u);
+    div_rem_small(&mut u, norm);
+    trim(&mut quot);
+    (quot, u)
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::string::ToString;
+
+    use super::*;
+
+    fn dec(s: &str) -> Decimal {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn parse_display() {
+        for s in [
+            "0",
+            "1",
+            "-1",
+            "0.5",
+            "-0.005",
+            "123456789",
+            "1234567890",
+            "12345678901234567890.123456789012345678",
+            "1.000",
+        ] {
+            assert_eq!(dec(s).to_string(), s);
+        }
+        assert_eq!(dec("-0.00").to_string(), "0.00");
+        assert_eq!(dec("+.5").to_string(), "0.5");
+        assert_eq!(dec("7.").to_string(), "7");
+        for s in ["", "-", ".", "1.2.3", "1e5", "12a", " 1"] {
+            assert_eq!(s.parse::<Decimal>(), Err(ParseDecimalError), "{s:?}");
+        }
+    }
+
+    #[test]
+    fn arithmetic() {
+        assert_eq!(dec("1.5") + dec("2.25"), dec("3.75"));
+        assert_eq!(dec("1.5") - dec("2.25"), dec("-0.75"));
+        assert_eq!(dec("-1.5") - dec("-2.25"), dec("0.75"));
+        assert_eq!(
+            dec("999999999.999999999") + dec("0.000000001"),
+            dec("1000000000")
+        );
+        assert_eq!(
+            dec("1000000000") - dec("0.000000001"),
+            dec("999999999.999999999")
+        );
+        assert_eq!(dec("1.5") * dec("-2.5"), dec("-3.75"));
+        assert_eq!((dec("1.5") * dec("2")).to_string(), "3.0");
+        assert_eq!(
+            dec("123456789.987654321") * dec("987654321.123456789"),
+            dec("121932632103337905.662094193112635269")
+        );
+        assert_eq!(dec("1.1").pow(3), dec("1.331"));
+        assert_eq!(dec("-2").pow(0), dec("1"));
+    }
+
+    #[test]
+    fn ordering() {
+        assert_eq!(dec("1.0"), dec("1.00"));
+        assert!(dec("-2") < dec("-1.5"));
+        assert!(dec("0.1") > dec("0.09999"));
+        assert!(dec("-0.1") < dec("0"));
+        assert_eq!(dec("1.2300").normalize().to_string(), "1.23");
+        assert_eq!(dec("1000").normalize().to_string(), "1000");
+    }
+
+    #[test]
+    fn rounding() {
+        let cases = [
+            ("2.5", RoundingMode::HalfEven, "2"),
+            ("3.5", RoundingMode::HalfEven, "4"),
+            ("2.5", RoundingMode::HalfUp, "3"),
+            ("-2.5", RoundingMode::HalfUp, "-3"),
+            ("2.4999999999999", RoundingMode::HalfUp, "2"),
+            ("2.5000000000001", RoundingMode::HalfEven, "3"),
+            ("-2.1", RoundingMode::Floor, "-3"),
+            ("-2.1", RoundingMode::Ceiling, "-2"),
+            ("2.1", RoundingMode::Up, "3"),
+            ("2.9", RoundingMode::Down, "2"),
+            ("-0.4", RoundingMode::HalfUp, "0"),
+        ];
+        for (value, mode, expected) in cases {
+            assert_eq!(dec(value).round(0, mode), dec(expected), "{value} {mode:?}");
+        }
+        assert_eq!(dec("1.5").round(3, RoundingMode::Down).to_string(), "1.500");
+        assert_eq!(
+            dec("0.0000000000009")
+                .round(12, RoundingMode::HalfUp)
+                .to_string(),
+            "0.000000000001"
+        );
+    }
+
+    #[test]
+    fn division() {
+        let div = |a: &str, b: &str, scale, mode| dec(a).checked_div(&dec(b), scale, mode);
+        assert_eq!(div("1", "3", 5, RoundingMode::HalfUp), Some(dec("0.33333")));
+        assert_eq!(div("2", "3", 5, RoundingMode::HalfUp), Some(dec("0.66667")));
+        assert_eq!(div("-2", "3", 5, RoundingMode::Down), Some(dec("-0.66666")));
+        assert_eq!(div("1", "8", 2, RoundingMode::HalfEven), Some(dec("0.12")));
+        assert_eq!(div("3", "8", 2, RoundingMode::HalfEven), Some(dec("0.38")));
+        assert_eq!(div("1", "0", 2, RoundingMode::HalfEven), None);
+        assert_eq!(
+            div("1.23", "0.001", 0, RoundingMode::Down),
+            Some(dec("1230"))
+        );
+        assert_eq!(
+            div(
+                "121932632103337905.662094193112635269",
+                "987654321.123456789",
+                9,
+                RoundingMode::HalfUp
+            ),
+            Some(dec("123456789.987654321"))
+        );
+        // Exercise the add-back step with divisors spanning several limbs.
+        let num = dec("340282366920938463463374607431768211455");
+        for den in [
+            "18446744073709551615",
+            "999999999999999999",
+            "4294967297000000001",
+        ] {
+            let den = dec(den);
+            let quot = num.checked_div(&den, 0, RoundingMode::Down).unwrap();
+            let rem = &num - &(&quot * &den);
+            assert!(rem >= Decimal::ZERO && rem < den, "{num} / {den}");
+        }
+    }
+
+    #[test]
+    fn div_rem_base_matches_division() {
+        for x in [
+            0,
+            1,
+            999_999_999,
+            1_000_000_000,
+            u32::MAX as u64,
+            1 << 40,
+            u64::MAX,
+        ] {
+            let (q, r) = div_rem_base(x);
+            assert_eq!((q, r as u64), (x / BASE as u64, x % BASE as u64), "{x}");
+        }
+    }
+
+    #[test]
+    fn serde_round_trip() {
+        let value = dec("-12345678901234.5678");
+        let parts = DecimalParts::from(value.clone());
+        assert_eq!(Decimal::try_from(parts).unwrap(), value);
+        let invalid = DecimalParts {
+            negative: false,
+            limbs: vec![BASE],
+            scale: 0,
+        };
+        assert!(Decimal::try_from(invalid).is_err());
+        let too_precise = DecimalParts {
+            negative: false,
+            limbs: vec![1],
+            scale: u32::MAX,
+        };
+        assert!(Decimal::try_from(too_precise).is_err());
+    }
+
+    #[test]
+    fn scale_limit() {
+        let finest = Decimal::new(1, MAX_SCALE);
+        let s = alloc::format!("0.{}1", "0".repeat(MAX_SCALE as usize - 1));
+        assert_eq!(dec(&s), finest);
+        let s = alloc::format!("0.{}1", "0".repeat(MAX_SCALE as usize));
+        assert_eq!(s.parse::<Decimal>(), Err(ParseDecimalError));
+
+        let half = dec("0.5");
+        assert_eq!(half.checked_mul(&finest), None);
+        assert_eq!(
+            finest.checked_mul(&Decimal::from(2u32)),
+            Some(Decimal::new(2, MAX_SCALE))
+        );
+        assert_eq!(half.checked_pow(MAX_SCALE + 1), None);
+        assert_eq!(half.checked_pow(u32::MAX), None);
+        assert_eq!(half.checked_pow(2), Some(dec("0.25")));
+        assert_eq!(
+            half.checked_div(&finest, MAX_SCALE + 1, RoundingMode::Down),
+            None
+        );
+    }
+}
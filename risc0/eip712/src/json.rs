@@ -0,0 +1,147 @@
+// Copyright 2024 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::collections::BTreeMap;
+
+use serde_json::{json, Map, Value as Json};
+
+use crate::{Domain, TypedStruct, Value};
+
+impl TypedStruct {
+    /// The typed data of this struct as the message of `domain`, in the JSON
+    /// format of `eth_signTypedData_v4`.
+    ///
+    /// Wallets hash this to the same [TypedStruct::signing_hash], so they can
+    /// display the fields a guest attested to before signing them.
+    pub fn typed_data(&self, domain: &Domain) -> Json {
+        let domain = domain.to_struct();
+        let mut types = BTreeMap::new();
+        for item in [&domain, self] {
+            collect_types(item, &mut types);
+        }
+        json!({
+            "types": types,
+            "primaryType": self.name(),
+            "domain": to_json(&domain),
+            "message": to_json(self),
+        })
+    }
+}
+
+fn collect_types(item: &TypedStruct, types: &mut BTreeMap<String, Json>) {
+    let members: Vec<Json> = item
+        .fields()
+        .iter()
+        .map(|(name, value)| json!({ "name": name, "type": value.type_name() }))
+        .collect();
+    types.insert(item.name().to_string(), Json::Array(members));
+    for (_, value) in item.fields() {
+        if let Value::Struct(inner) = value {
+            collect_types(inner, types);
+        }
+    }
+}
+
+fn to_json(item: &TypedStruct) -> Json {
+    let fields: Map<String, Json> = item
+        .fields()
+        .iter()
+        .map(|(name, value)| {
+            let value = match value {
+                Value::Address(address) => json!(format!("0x{}", hex(address))),
+                Value::Bool(value) => json!(value),
+                Value::Uint(_, word) => json!(to_decimal(*word)),
+                Value::Bytes32(value) => json!(format!("0x{}", hex(value))),
+                Value::Bytes(value) => json!(format!("0x{}", hex(value))),
+                Value::String(value) => json!(value),
+                Value::Struct(inner) => to_json(inner),
+            };
+            (name.clone(), value)
+        })
+        .collect();
+    Json::Object(fields)
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{byte:02x}")).collect()
+}
+
+/// Decimal string of a 32-byte big-endian integer.
+fn to_decimal(mut word: [u8; 32]) -> String {
+    let mut digits = Vec::new();
+    loop {
+        let mut remainder = 0u32;
+        for byte in word.iter_mut() {
+            let acc = (remainder << 8) | *byte as u32;
+            *byte = (acc / 10) as u8;
+            remainder = acc % 10;
+        }
+        digits.push(b'0' + remainder as u8);
+        if word.iter().all(|byte| *byte == 0) {
+            break;
+        }
+    }
+    digits.reverse();
+    String::from_utf8(digits).unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decimal() {
+        assert_eq!(to_decimal([0u8; 32]), "0");
+        let Value::Uint(_, word) = Value::from(u128::MAX) else {
+            unreachable!()
+        };
+        assert_eq!(to_decimal(word), u128::MAX.to_string());
+        assert_eq!(
+            to_decimal([0xff; 32]),
+            "115792089237316195423570985008687907853269984665640564039457584007913129639935"
+        );
+    }
+
+    #[test]
+    fn typed_data() {
+        let domain = Domain::new().name("Journal").chain_id(11155111);
+        let message = TypedStruct::new("Attestation")
+            .field("imageId", [0xab; 32])
+            .field("owner", [0x11; 20])
+            .field("amount", 1000u64);
+        assert_eq!(
+            message.typed_data(&domain),
+            json!({
+                "types": {
+                    "EIP712Domain": [
+                        { "name": "name", "type": "string" },
+                        { "name": "chainId", "type": "uint256" },
+                    ],
+                    "Attestation": [
+                        { "name": "imageId", "type": "bytes32" },
+                        { "name": "owner", "type": "address" },
+                        { "name": "amount", "type": "uint64" },
+                    ],
+                },
+                "primaryType": "Attestation",
+                "domain": { "name": "Journal", "chainId": "11155111" },
+                "message": {
+                    "imageId": format!("0x{}", "ab".repeat(32)),
+                    "owner": format!("0x{}", "11".repeat(20)),
+                    "amount": "1000",
+                },
+            })
+        );
+    }
+}
@@ -0,0 +1,427 @@
+// Copyright 2024 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+#![doc = include_str!("../README.md")]
+#![cfg_attr(not(feature = "std"), no_std)]
+#![deny(missing_docs)]
+
+extern crate alloc;
+
+#[cfg(feature = "std")]
+mod json;
+
+use alloc::{
+    collections::BTreeMap,
+    format,
+    string::{String, ToString},
+    vec::Vec,
+};
+
+use sha3::{Digest as _, Keccak256};
+
+/// Keccak-256 hash of `data`.
+pub fn keccak256(data: impl AsRef<[u8]>) -> [u8; 32] {
+    Keccak256::digest(data).into()
+}
+
+/// Value of a field of a [TypedStruct].
+///
+/// Arrays are not supported.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Value {
+    /// An `address`.
+    Address([u8; 20]),
+    /// A `bool`.
+    Bool(bool),
+    /// A `uintN` of the given number of bits, as a 32-byte big-endian word.
+    Uint(u16, [u8; 32]),
+    /// A `bytes32`, such as an image ID or a digest.
+    Bytes32([u8; 32]),
+    /// Dynamic `bytes`.
+    Bytes(Vec<u8>),
+    /// A `string`.
+    String(String),
+    /// A nested struct.
+    Struct(TypedStruct),
+}
+
+impl Value {
+    /// A `uint256` from its 32-byte big-endian encoding.
+    pub fn uint256(be_bytes: [u8; 32]) -> Self {
+        Self::Uint(256, be_bytes)
+    }
+
+    /// Name of the Solidity type of the value.
+    pub fn type_name(&self) -> String {
+        match self {
+            Value::Address(_) => "address".to_string(),
+            Value::Bool(_) => "bool".to_string(),
+            Value::Uint(bits, _) => format!("uint{bits}"),
+            Value::Bytes32(_) => "bytes32".to_string(),
+            Value::Bytes(_) => "bytes".to_string(),
+            Value::String(_) => "string".to_string(),
+            Value::Struct(inner) => inner.name.clone(),
+        }
+    }
+
+    /// The 32-byte word of the value in `encodeData`.
+    fn encode(&self) -> [u8; 32] {
+        let mut word = [0u8; 32];
+        match self {
+            Value::Address(address) => word[12..].copy_from_slice(address),
+            Value::Bool(value) => word[31] = *value as u8,
+            Value::Uint(_, value) | Value::Bytes32(value) => word = *value,
+            Value::Bytes(value) => word = keccak256(value),
+            Value::String(value) => word = keccak256(value),
+            Value::Struct(inner) => word = inner.struct_hash(),
+        }
+        word
+    }
+}
+
+macro_rules! impl_from_uint {
+    ($($ty:ty),*) => {
+        $(
+            impl From<$ty> for Value {
+                fn from(value: $ty) -> Self {
+                    let mut word = [0u8; 32];
+                    let bytes = value.to_be_bytes();
+                    word[32 - bytes.len()..].copy_from_slice(&bytes);
+                    Self::Uint(<$ty>::BITS as u16, word)
+                }
+            }
+        )*
+    };
+}
+
+impl_from_uint!(u8, u16, u32, u64, u128);
+
+impl From<bool> for Value {
+    fn from(value: bool) -> Self {
+        Self::Bool(value)
+    }
+}
+
+impl From<[u8; 20]> for Value {
+    fn from(value: [u8; 20]) -> Self {
+        Self::Address(value)
+    }
+}
+
+impl From<[u8; 32]> for Value {
+    fn from(value: [u8; 32]) -> Self {
+        Self::Bytes32(value)
+    }
+}
+
+impl From<Vec<u8>> for Value {
+    fn from(value: Vec<u8>) -> Self {
+        Self::Bytes(value)
+    }
+}
+
+impl From<&str> for Value {
+    fn from(value: &str) -> Self {
+        Self::String(value.to_string())
+    }
+}
+
+impl From<String> for Value {
+    fn from(value: String) -> Self {
+        Self::String(value)
+    }
+}
+
+impl From<TypedStruct> for Value {
+    fn from(value: TypedStruct) -> Self {
+        Self::Struct(value)
+    }
+}
+
+/// Instance of an EIP-712 struct type, with its fields in declaration order.
+///
+/// The same builder is used by the host, to describe the typed data to a
+/// wallet, and by the guest, to commit its [TypedStruct::struct_hash] or
+/// [TypedStruct::signing_hash] to the journal.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TypedStruct {
+    name: String,
+    fields: Vec<(String, Value)>,
+}
+
+impl TypedStruct {
+    /// An empty struct of the type `name`.
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            fields: Vec::new(),
+        }
+    }
+
+    /// Append the field `name` with the given value.
+    pub fn field(mut self, name: impl Into<String>, value: impl Into<Value>) -> Self {
+        self.fields.push((name.into(), value.into()));
+        self
+    }
+
+    /// Name of the struct type.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Fields of the struct, in declaration order.
+    pub fn fields(&self) -> &[(String, Value)] {
+        &self.fields
+    }
+
+    /// Declaration of this type alone, such as `Person(string name,address wallet)`.
+    fn declaration(&self) -> String {
+        let members: Vec<String> = self
+            .fields
+            .iter()
+            .map(|(name, value)| format!("{} {name}", value.type_name()))
+            .collect();
+        format!("{}({})", self.name, members.join(","))
+    }
+
+    /// Collect the declarations of the struct types referenced by this one.
+    ///
+    /// Panics if two structs of the same type have different fields.
+    fn collect_types(&self, types: &mut BTreeMap<String, String>) {
+        for (_, value) in &self.fields {
+            if let Value::Struct(inner) = value {
+                let declaration = inner.declaration();
+                if let Some(existing) = types.insert(inner.name.clone(), declaration.clone()) {
+                    assert_eq!(
+                        existing, declaration,
+                        "conflicting definitions of struct type"
+                    );
+                }
+                inner.collect_types(types);
+            }
+        }
+    }
+
+    /// The `encodeType` of this struct: its declaration followed by those of
+    /// the referenced struct types, sorted by name.
+    pub fn encode_type(&self) -> String {
+        let mut types = BTreeMap::new();
+        self.collect_types(&mut types);
+        types.remove(&self.name);
+        let mut encoded = self.declaration();
+        for declaration in types.values() {
+            encoded.push_str(declaration);
+        }
+        encoded
+    }
+
+    /// The `typeHash` of this struct.
+    pub fn type_hash(&self) -> [u8; 32] {
+        keccak256(self.encode_type())
+    }
+
+    /// The `hashStruct` of this struct.
+    pub fn struct_hash(&self) -> [u8; 32] {
+        let mut hasher = Keccak256::new();
+        hasher.update(self.type_hash());
+        for (_, value) in &self.fields {
+            hasher.update(value.encode());
+        }
+        hasher.finalize().into()
+    }
+
+    /// The digest signed by a wallet for this struct as the message of
+    /// `domain`, as recovered by `ecrecover` in a contract.
+    pub fn signing_hash(&self, domain: &Domain) -> [u8; 32] {
+        let mut hasher = Keccak256::new();
+        hasher.update([0x19, 0x01]);
+        hasher.update(domain.separator());
+        hasher.update(self.struct_hash());
+        hasher.finalize().into()
+    }
+}
+
+/// The `EIP712Domain` of typed data.
+///
+/// Only the fields which are set are part of the domain type.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Domain {
+    name: Option<String>,
+    version: Option<String>,
+    chain_id: Option<u64>,
+    verifying_contract: Option<[u8; 20]>,
+    salt: Option<[u8; 32]>,
+}
+
+impl Domain {
+    /// An empty domain.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the name of the signing domain.
+    pub fn name(mut self, name: impl Into<String>) -> Self {
+        self.name = Some(name.into());
+        self
+    }
+
+    /// Set the version of the signing domain.
+    pub fn version(mut self, version: impl Into<String>) -> Self {
+        self.version = Some(version.into());
+        self
+    }
+
+    /// Set the chain ID of the signing domain.
+    pub fn chain_id(mut self, chain_id: u64) -> Self {
+        self.chain_id = Some(chain_id);
+        self
+    }
+
+    /// Set the address of the contract that verifies the signature.
+    pub fn verifying_contract(mut self, address: [u8; 20]) -> Self {
+        self.verifying_contract = Some(address);
+        self
+    }
+
+    /// Set the salt of the signing domain.
+    pub fn salt(mut self, salt: [u8; 32]) -> Self {
+        self.salt = Some(salt);
+        self
+    }
+
+    /// The domain as an `EIP712Domain` struct.
+    pub fn to_struct(&self) -> TypedStruct {
+        let mut domain = TypedStruct::new("EIP712Domain");
+        if let Some(name) = &self.name {
+            domain = domain.field("name", name.as_str());
+        }
+        if let Some(version) = &self.version {
+            domain = domain.field("version", version.as_str());
+        }
+        if let Some(chain_id) = self.chain_id {
+            let Value::Uint(_, word) = Value::from(chain_id) else {
+                unreachable!()
+            };
+            domain = domain.field("chainId", Value::uint256(word));
+        }
+        if let Some(address) = self.verifying_contract {
+            domain = domain.field("verifyingContract", Value::Address(address));
+        }
+        if let Some(salt) = self.salt {
+            domain = domain.field("salt", Value::Bytes32(salt));
+        }
+        domain
+    }
+
+    /// The domain separator, which is the `hashStruct` of the domain.
+    pub fn separator(&self) -> [u8; 32] {
+        self.to_struct().struct_hash()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn address(hex_str: &str) -> [u8; 20] {
+        hex::decode(&hex_str[2..]).unwrap().try_into().unwrap()
+    }
+
+    fn mail() -> (Domain, TypedStruct) {
+        // Example from the EIP-712 specification.
+        let domain = Domain::new()
+            .name("Ether Mail")
+            .version("1")
+            .chain_id(1)
+            .verifying_contract(address("0xCcCCccccCCCCcCCCCCCcCcCccCcCCCcCcccccccC"));
+        let person = |name: &str, wallet: &str| {
+            TypedStruct::new("Person")
+                .field("name", name)
+                .field("wallet", address(wallet))
+        };
+        let mail = TypedStruct::new("Mail")
+            .field(
+                "from",
+                person("Cow", "0xCD2a3d9F938E13CD947Ec05AbC7FE734Df8DD826"),
+            )
+            .field(
+                "to",
+                person("Bob", "0xbBbBBBBbbBBBbbbBbbBbbbbBBbBbbbbBbBbbBBbB"),
+            )
+            .field("contents", "Hello, Bob!");
+        (domain, mail)
+    }
+
+    #[test]
+    fn spec_example() {
+        let (domain, mail) = mail();
+        assert_eq!(
+            mail.encode_type(),
+            "Mail(Person from,Person to,string contents)Person(string name,address wallet)"
+        );
+        assert_eq!(
+            hex::encode(mail.type_hash()),
+            "a0cedeb2dc280ba39b857546d74f5549c3a1d7bdc2dd96bf881f76108e23dac2"
+        );
+        assert_eq!(
+            hex::encode(mail.struct_hash()),
+            "c52c0ee5d84264471806290a3f2c4cecfc5490626bf912d01f240d7a274b371e"
+        );
+        assert_eq!(
+            hex::encode(domain.separator()),
+            "f2cee375fa42b42143804025fc449deafd50cc031ca257e0b194a650a912090f"
+        );
+        assert_eq!(
+            hex::encode(mail.signing_hash(&domain)),
+            "be609aee343fb3c4b28e1df9e632fca64fcfaede20f02e86244efddf30957bd2"
+        );
+    }
+
+    #[test]
+    fn field_types() {
+        let value = TypedStruct::new("Claim")
+            .field("imageId", [1u8; 32])
+            .field("amount", 7u64)
+            .field("ok", true)
+            .field("data", vec![1u8, 2, 3]);
+        assert_eq!(
+            value.encode_type(),
+            "Claim(bytes32 imageId,uint64 amount,bool ok,bytes data)"
+        );
+
+        let mut expected = Vec::new();
+        expected.extend_from_slice(&value.type_hash());
+        expected.extend_from_slice(&[1u8; 32]);
+        expected.extend_from_slice(
+            &Value::uint256({
+                let mut word = [0u8; 32];
+                word[31] = 7;
+                word
+            })
+            .encode(),
+        );
+        expected.extend_from_slice(&Value::from(1u8).encode());
+        expected.extend_from_slice(&keccak256([1u8, 2, 3]));
+        assert_eq!(value.struct_hash(), keccak256(expected));
+    }
+
+    #[test]
+    #[should_panic(expected = "conflicting definitions")]
+    fn conflicting_types() {
+        TypedStruct::new("Pair")
+            .field("a", TypedStruct::new("Point").field("x", 1u32))
+            .field("b", TypedStruct::new("Point").field("y", 1u32))
+            .encode_type();
+    }
+}
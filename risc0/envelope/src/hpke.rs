@@ -0,0 +1,243 @@
+// Copyright 2024 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Single-shot HPKE ([RFC 9180]) in base mode, with the DHKEM(X25519,
+//! HKDF-SHA256), HKDF-SHA256 and ChaCha20Poly1305 suite.
+//!
+//! [RFC 9180]: https://www.rfc-editor.org/rfc/rfc9180.html
+
+use chacha20poly1305::{aead::Aead, aead::Payload, ChaCha20Poly1305, KeyInit};
+use hkdf::Hkdf;
+use sha2::Sha256;
+use x25519_dalek::{PublicKey, StaticSecret};
+
+use crate::Error;
+
+const KEM_ID: u16 = 0x0020;
+const KDF_ID: u16 = 0x0001;
+const AEAD_ID: u16 = 0x0003;
+const MODE_BASE: u8 = 0x00;
+
+fn kem_suite_id() -> Vec<u8> {
+    [b"KEM".as_slice(), &KEM_ID.to_be_bytes()].concat()
+}
+
+fn hpke_suite_id() -> Vec<u8> {
+    [
+        b"HPKE".as_slice(),
+        &KEM_ID.to_be_bytes(),
+        &KDF_ID.to_be_bytes(),
+        &AEAD_ID.to_be_bytes(),
+    ]
+    .concat()
+}
+
+fn labeled_extract(suite_id: &[u8], salt: &[u8], label: &[u8], ikm: &[u8]) -> [u8; 32] {
+    let labeled_ikm = [b"HPKE-v1".as_slice(), suite_id, label, ikm].concat();
+    let (prk, _) = Hkdf::<Sha256>::extract(Some(salt), &labeled_ikm);
+    prk.into()
+}
+
+fn labeled_expand(suite_id: &[u8], prk: &[u8; 32], label: &[u8], info: &[u8], okm: &mut [u8]) {
+    let len = (okm.len() as u16).to_be_bytes();
+    Hkdf::<Sha256>::from_prk(prk)
+        .expect("PRK is a full SHA-256 digest")
+        .expand_multi_info(&[&len, b"HPKE-v1", suite_id, label, info], okm)
+        .expect("output is at most 255 digests long");
+}
+
+fn extract_and_expand(dh: &[u8; 32], kem_context: &[u8]) -> [u8; 32] {
+    let suite_id = kem_suite_id();
+    let eae_prk = labeled_extract(&suite_id, b"", b"eae_prk", dh);
+    let mut shared_secret = [0u8; 32];
+    labeled_expand(
+        &suite_id,
+        &eae_prk,
+        b"shared_secret",
+        kem_context,
+        &mut shared_secret,
+    );
+    shared_secret
+}
+
+/// Encapsulates a fresh shared secret to `pk_r` with the ephemeral key `sk_e`,
+/// returning the shared secret and its encapsulation.
+fn encap(sk_e: &StaticSecret, pk_r: &PublicKey) -> Result<([u8; 32], [u8; 32]), Error> {
+    let dh = sk_e.diffie_hellman(pk_r);
+    if !dh.was_contributory() {
+        return Err(Error::InvalidKey);
+    }
+    let enc = PublicKey::from(sk_e).to_bytes();
+    let kem_context = [enc.as_slice(), pk_r.as_bytes()].concat();
+    Ok((extract_and_expand(dh.as_bytes(), &kem_context), enc))
+}
+
+fn decap(enc: &[u8; 32], sk_r: &StaticSecret) -> Result<[u8; 32], Error> {
+    let dh = sk_r.diffie_hellman(&PublicKey::from(*enc));
+    if !dh.was_contributory() {
+        return Err(Error::InvalidKey);
+    }
+    let kem_context = [enc.as_slice(), PublicKey::from(sk_r).as_bytes()].concat();
+    Ok(extract_and_expand(dh.as_bytes(), &kem_context))
+}
+
+/// Encryption context of a single message.
+pub(crate) struct Context {
+    key: [u8; 32],
+    base_nonce: [u8; 12],
+    exporter_secret: [u8; 32],
+}
+
+impl Context {
+    fn new(shared_secret: &[u8; 32], info: &[u8]) -> Self {
+        let suite_id = hpke_suite_id();
+        let psk_id_hash = labeled_extract(&suite_id, b"", b"psk_id_hash", b"");
+        let info_hash = labeled_extract(&suite_id, b"", b"info_hash", info);
+        let context = [[MODE_BASE].as_slice(), &psk_id_hash, &info_hash].concat();
+        let secret = labeled_extract(&suite_id, shared_secret, b"secret", b"");
+
+        let mut ctx = Self {
+            key: [0; 32],
+            base_nonce: [0; 12],
+            exporter_secret: [0; 32],
+        };
+        labeled_expand(&suite_id, &secret, b"key", &context, &mut ctx.key);
+        labeled_expand(
+            &suite_id,
+            &secret,
+            b"base_nonce",
+            &context,
+            &mut ctx.base_nonce,
+        );
+        labeled_expand(
+            &suite_id,
+            &secret,
+            b"exp",
+            &context,
+            &mut ctx.exporter_secret,
+        );
+        ctx
+    }
+
+    /// Sets up the context of a sender using the ephemeral key `sk_e`,
+    /// returning the context and the encapsulated key.
+    pub(crate) fn sender(
+        sk_e: &StaticSecret,
+        pk_r: &PublicKey,
+        info: &[u8],
+    ) -> Result<(Self, [u8; 32]), Error> {
+        let (shared_secret, enc) = encap(sk_e, pk_r)?;
+        Ok((Self::new(&shared_secret, info), enc))
+    }
+
+    /// Sets up the context of the recipient of the encapsulated key `enc`.
+    pub(crate) fn receiver(
+        enc: &[u8; 32],
+        sk_r: &StaticSecret,
+        info: &[u8],
+    ) -> Result<Self, Error> {
+        Ok(Self::new(&decap(enc, sk_r)?, info))
+    }
+
+    /// Encrypts the first and only message of this context.
+    pub(crate) fn seal(&self, aad: &[u8], plaintext: &[u8]) -> Vec<u8> {
+        ChaCha20Poly1305::new(&self.key.into())
+            .encrypt(
+                &self.base_nonce.into(),
+                Payload {
+                    msg: plaintext,
+                    aad,
+                },
+            )
+            .expect("message fits in a ChaCha20Poly1305 stream")
+    }
+
+    /// Decrypts the first and only message of this context.
+    pub(crate) fn open(&self, aad: &[u8], ciphertext: &[u8]) -> Result<Vec<u8>, Error> {
+        ChaCha20Poly1305::new(&self.key.into())
+            .decrypt(
+                &self.base_nonce.into(),
+                Payload {
+                    msg: ciphertext,
+                    aad,
+                },
+            )
+            .map_err(|_| Error::Decryption)
+    }
+
+    /// Derives a secret from this context, bound to `exporter_context`.
+    pub(crate) fn export(&self, exporter_context: &[u8]) -> [u8; 32] {
+        let mut secret = [0u8; 32];
+        labeled_expand(
+            &hpke_suite_id(),
+            &self.exporter_secret,
+            b"sec",
+            exporter_context,
+            &mut secret,
+        );
+        secret
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use hex::FromHex;
+
+    use super::*;
+
+    fn secret(hex: &str) -> StaticSecret {
+        StaticSecret::from(<[u8; 32]>::from_hex(hex).unwrap())
+    }
+
+    // RFC 9180, A.2.1: DHKEM(X25519, HKDF-SHA256), HKDF-SHA256, ChaCha20Poly1305
+    // in base mode.
+    #[test]
+    fn rfc9180_base_vector() {
+        let info = hex::decode("4f6465206f6e2061204772656369616e2055726e").unwrap();
+        let sk_e = secret("f4ec9b33b792c372c1d2c2063507b684ef925b8c75a42dbcbf57d63ccd381600");
+        let sk_r = secret("8057991eef8f1f1af18f4a9491d16a1ce333f695d4db8e38da75975c4478e0fb");
+        let pk_r = PublicKey::from(&sk_r);
+        assert_eq!(
+            hex::encode(pk_r.as_bytes()),
+            "4310ee97d88cc1f088a5576c77ab0cf5c3ac797f3d95139c6c84b5429c59662a"
+        );
+
+        let (sender, enc) = Context::sender(&sk_e, &pk_r, &info).unwrap();
+        assert_eq!(
+            hex::encode(enc),
+            "1afa08d3dec047a643885163f1180476fa7ddb54c6a8029ea33f95796bf2ac4a"
+        );
+        assert_eq!(
+            hex::encode(sender.key),
+            "ad2744de8e17f4ebba575b3f5f5a8fa1f69c2a07f6e7500bc60ca6e3e3ec1c91"
+        );
+        assert_eq!(hex::encode(sender.base_nonce), "5c4d98150661b848853b547f");
+        assert_eq!(
+            hex::encode(sender.exporter_secret),
+            "a3b010d4994890e2c6968a36f64470d3c824c8f5029942feb11e7a74b2921922"
+        );
+
+        let aad = b"Count-0";
+        let plaintext = b"Beauty is truth, truth beauty";
+        let ciphertext = sender.seal(aad, plaintext);
+        assert_eq!(
+            hex::encode(&ciphertext),
+            "1c5250d8034ec2b784ba2cfd69dbdb8af406cfe3ff938e131f0def8c8b60b4db21993c62ce81883d2dd1b51a28"
+        );
+
+        let receiver = Context::receiver(&enc, &sk_r, &info).unwrap();
+        assert_eq!(receiver.open(aad, &ciphertext).unwrap(), plaintext);
+        assert_eq!(receiver.export(b"ctx"), sender.export(b"ctx"));
+    }
+}
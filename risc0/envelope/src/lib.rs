@@ -0,0 +1,318 @@
+// Copyright 2024 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+#![doc = include_str!("../README.md")]
+#![deny(missing_docs)]
+
+mod hpke;
+
+use rand_core::OsRng;
+use risc0_zkvm::{sha::Digest, Artifact, Receipt};
+use subtle::ConstantTimeEq;
+use x25519_dalek::StaticSecret;
+
+use crate::hpke::Context;
+
+// Marks the start of an envelope, followed by its format version.
+const MAGIC: [u8; 4] = *b"R0EV";
+const VERSION: u8 = 1;
+
+// HPKE info string, binding the derived keys to this format.
+const INFO: &[u8] = b"risc0-envelope v1";
+
+// HPKE exporter context of the key commitment.
+const KEY_COMMITMENT: &[u8] = b"risc0-envelope key commitment";
+
+/// Length of an encoded [Header].
+pub const HEADER_LEN: usize = MAGIC.len() + 1 + 3 * 32;
+
+/// Error returned when sealing or opening an [Envelope].
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    /// The envelope or its header is not encoded correctly.
+    #[error("malformed envelope: {0}")]
+    Malformed(&'static str),
+
+    /// The recipient or encapsulated key is a low order point.
+    #[error("invalid X25519 public key")]
+    InvalidKey,
+
+    /// The envelope was not sealed to the key used to open it.
+    #[error("envelope is not sealed to this key")]
+    KeyCommitment,
+
+    /// The ciphertext or the header was modified.
+    #[error("failed to decrypt envelope")]
+    Decryption,
+
+    /// The sealed receipt does not have the claim digest of the header.
+    #[error("claim digest of the receipt does not match the envelope header")]
+    ClaimMismatch,
+
+    /// The receipt could not be serialized, deserialized or inspected.
+    #[error("receipt: {0}")]
+    Receipt(anyhow::Error),
+}
+
+/// X25519 secret key of the recipient of an [Envelope].
+pub struct SecretKey(StaticSecret);
+
+impl SecretKey {
+    /// Generates a random secret key.
+    pub fn generate() -> Self {
+        Self(StaticSecret::random_from_rng(OsRng))
+    }
+
+    /// Secret key from its 32 bytes.
+    pub fn from_bytes(bytes: [u8; 32]) -> Self {
+        Self(StaticSecret::from(bytes))
+    }
+
+    /// The 32 bytes of this secret key.
+    pub fn to_bytes(&self) -> [u8; 32] {
+        self.0.to_bytes()
+    }
+
+    /// The public key to seal envelopes to.
+    pub fn public_key(&self) -> PublicKey {
+        PublicKey(x25519_dalek::PublicKey::from(&self.0))
+    }
+}
+
+/// X25519 public key of the recipient of an [Envelope].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct PublicKey(x25519_dalek::PublicKey);
+
+impl PublicKey {
+    /// Public key from its 32 bytes.
+    pub fn from_bytes(bytes: [u8; 32]) -> Self {
+        Self(x25519_dalek::PublicKey::from(bytes))
+    }
+
+    /// The 32 bytes of this public key.
+    pub fn to_bytes(&self) -> [u8; 32] {
+        self.0.to_bytes()
+    }
+}
+
+/// Cleartext header of an [Envelope].
+///
+/// The header is authenticated as the associated data of the ciphertext, so it
+/// cannot be modified without failing [Envelope::open].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Header {
+    /// Digest of the [ReceiptClaim](risc0_zkvm::ReceiptClaim) of the sealed
+    /// receipt.
+    pub claim_digest: Digest,
+
+    /// HPKE encapsulated key, the ephemeral public key of the sender.
+    pub enc: [u8; 32],
+
+    /// Commitment to the encryption key, checked before decrypting so that
+    /// the ciphertext only opens under the key it was sealed with.
+    pub key_commitment: [u8; 32],
+}
+
+impl Header {
+    /// Encodes this header.
+    pub fn to_bytes(&self) -> [u8; HEADER_LEN] {
+        let mut bytes = [0u8; HEADER_LEN];
+        bytes[..4].copy_from_slice(&MAGIC);
+        bytes[4] = VERSION;
+        bytes[5..37].copy_from_slice(self.claim_digest.as_bytes());
+        bytes[37..69].copy_from_slice(&self.enc);
+        bytes[69..].copy_from_slice(&self.key_commitment);
+        bytes
+    }
+
+    /// Decodes the header at the start of `bytes`.
+    ///
+    /// Only the first [HEADER_LEN] bytes are read, so routing an envelope by
+    /// its claim does not require the ciphertext.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, Error> {
+        let bytes = bytes
+            .get(..HEADER_LEN)
+            .ok_or(Error::Malformed("truncated header"))?;
+        if bytes[..4] != MAGIC {
+            return Err(Error::Malformed("not an envelope"));
+        }
+        if bytes[4] != VERSION {
+            return Err(Error::Malformed("unsupported envelope version"));
+        }
+        Ok(Self {
+            claim_digest: Digest::try_from(&bytes[5..37]).unwrap(),
+            enc: bytes[37..69].try_into().unwrap(),
+            key_commitment: bytes[69..].try_into().unwrap(),
+        })
+    }
+}
+
+/// A [Receipt] and its journal, encrypted to the public key of a recipient.
+///
+/// The envelope is sealed with single-shot HPKE ([RFC 9180]) in base mode,
+/// using DHKEM(X25519, HKDF-SHA256), HKDF-SHA256 and ChaCha20Poly1305. Its
+/// [Header] carries the claim digest of the receipt in the clear, so that
+/// intermediaries can route it without being able to read it.
+///
+/// [RFC 9180]: https://www.rfc-editor.org/rfc/rfc9180.html
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Envelope {
+    /// The cleartext header.
+    pub header: Header,
+
+    /// The encrypted receipt.
+    pub ciphertext: Vec<u8>,
+}
+
+impl Envelope {
+    /// Encrypts `receipt` to `recipient`.
+    pub fn seal(receipt: &Receipt, recipient: &PublicKey) -> Result<Self, Error> {
+        let claim_digest = receipt
+            .claim_digest()
+            .map_err(|err| Error::Receipt(err.into()))?;
+        let plaintext = receipt.to_versioned_bytes().map_err(Error::Receipt)?;
+
+        let sk_e = StaticSecret::random_from_rng(OsRng);
+        let (context, enc) = Context::sender(&sk_e, &recipient.0, INFO)?;
+        let header = Header {
+            claim_digest,
+            enc,
+            key_commitment: context.export(KEY_COMMITMENT),
+        };
+        let ciphertext = context.seal(&header.to_bytes(), &plaintext);
+        Ok(Self { header, ciphertext })
+    }
+
+    /// Decrypts the receipt with the secret key of the recipient.
+    ///
+    /// The claim digest of the decrypted receipt is checked against the
+    /// header, but the receipt itself is not verified: call
+    /// [Receipt::verify] with the expected image ID before trusting it.
+    pub fn open(&self, secret_key: &SecretKey) -> Result<Receipt, Error> {
+        let context = Context::receiver(&self.header.enc, &secret_key.0, INFO)?;
+        let key_commitment = context.export(KEY_COMMITMENT);
+        if !bool::from(key_commitment.ct_eq(&self.header.key_commitment)) {
+            return Err(Error::KeyCommitment);
+        }
+        let plaintext = context.open(&self.header.to_bytes(), &self.ciphertext)?;
+
+        let receipt = Receipt::from_versioned_bytes(&plaintext).map_err(Error::Receipt)?;
+        let claim_digest = receipt
+            .claim_digest()
+            .map_err(|err| Error::Receipt(err.into()))?;
+        if claim_digest != self.header.claim_digest {
+            return Err(Error::ClaimMismatch);
+        }
+        Ok(receipt)
+    }
+
+    /// Encodes this envelope as its header followed by the ciphertext.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        [self.header.to_bytes().as_slice(), &self.ciphertext].concat()
+    }
+
+    /// Decodes an envelope written by [Envelope::to_bytes].
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, Error> {
+        Ok(Self {
+            header: Header::from_bytes(bytes)?,
+            ciphertext: bytes[HEADER_LEN..].to_vec(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use risc0_zkvm::{sha::Digest, ExitCode, InnerReceipt, MaybePruned, Receipt, ReceiptClaim};
+
+    use super::*;
+
+    fn receipt() -> Receipt {
+        let claim = ReceiptClaim {
+            pre: MaybePruned::Pruned(Digest::ZERO),
+            post: MaybePruned::Pruned(Digest::ZERO),
+            exit_code: ExitCode::Halted(0),
+            input: Digest::ZERO,
+            output: None.into(),
+        };
+        Receipt::new(InnerReceipt::Fake { claim }, b"journal".to_vec())
+    }
+
+    #[test]
+    fn round_trip() {
+        let receipt = receipt();
+        let secret_key = SecretKey::generate();
+        let envelope = Envelope::seal(&receipt, &secret_key.public_key()).unwrap();
+        assert_eq!(
+            envelope.header.claim_digest,
+            receipt.claim_digest().unwrap()
+        );
+
+        let bytes = envelope.to_bytes();
+        assert_eq!(
+            Header::from_bytes(&bytes[..HEADER_LEN]).unwrap(),
+            envelope.header
+        );
+        let opened = Envelope::from_bytes(&bytes)
+            .unwrap()
+            .open(&secret_key)
+            .unwrap();
+        assert_eq!(opened.journal.bytes, receipt.journal.bytes);
+        assert_eq!(opened.claim_digest().unwrap(), envelope.header.claim_digest);
+    }
+
+    #[test]
+    fn wrong_key() {
+        let envelope = Envelope::seal(&receipt(), &SecretKey::generate().public_key()).unwrap();
+        assert!(matches!(
+            envelope.open(&SecretKey::generate()),
+            Err(Error::KeyCommitment)
+        ));
+    }
+
+    #[test]
+    fn tampered() {
+        let secret_key = SecretKey::generate();
+        let envelope = Envelope::seal(&receipt(), &secret_key.public_key()).unwrap();
+
+        let mut rerouted = envelope.clone();
+        rerouted.header.claim_digest = Digest::ZERO;
+        assert!(matches!(rerouted.open(&secret_key), Err(Error::Decryption)));
+
+        let mut corrupted = envelope.clone();
+        corrupted.ciphertext[0] ^= 1;
+        assert!(matches!(
+            corrupted.open(&secret_key),
+            Err(Error::Decryption)
+        ));
+
+        let mut forged = envelope;
+        forged.header.key_commitment[0] ^= 1;
+        assert!(matches!(
+            forged.open(&secret_key),
+            Err(Error::KeyCommitment)
+        ));
+    }
+
+    #[test]
+    fn malformed() {
+        assert!(matches!(
+            Envelope::from_bytes(&[0; HEADER_LEN - 1]),
+            Err(Error::Malformed(_))
+        ));
+        assert!(matches!(
+            Envelope::from_bytes(&[0; HEADER_LEN]),
+            Err(Error::Malformed(_))
+        ));
+    }
+}
@@ -0,0 +1,171 @@
+// Copyright 2024 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Commitments to batches of claims settled by a single aggregated proof.
+//!
+//! An [AggregationTree] is a Merkle tree over the `(image_id, journal_digest)`
+//! pairs of a batch. Its root is committed to by the aggregated proof in place
+//! of a single claim digest, and each claim of the batch can then be settled
+//! on chain with its [InclusionProof].
+//!
+//! Hashes are chosen so that proofs can be checked with OpenZeppelin's
+//! `MerkleProof.verify`: leaves are hashed as
+//! `keccak256(bytes.concat(keccak256(abi.encode(imageId, journalDigest))))`,
+//! and each node is the `keccak256` of its children sorted as `bytes32`. A
+//! node without a sibling is carried to the next level unchanged. The leaf hash
+//! is rendered as Solidity in [AGGREGATION_LIBRARY].
+//!
+//! [AGGREGATION_LIBRARY]: crate::solidity::AGGREGATION_LIBRARY
+
+extern crate alloc;
+
+use alloc::vec::Vec;
+
+use anyhow::{bail, Error, Result};
+use ark_bn254::Fr;
+use risc0_zkp::core::digest::Digest;
+use serde::{Deserialize, Serialize};
+use sha3::{Digest as _, Keccak256};
+
+use crate::split_digest;
+
+/// A claim settled by an aggregated proof.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Claim {
+    /// Image ID of the guest program.
+    pub image_id: Digest,
+
+    /// Digest of the journal committed by the guest.
+    pub journal_digest: Digest,
+}
+
+impl Claim {
+    /// Returns the leaf committing to this claim.
+    pub fn leaf(&self) -> Digest {
+        let inner = Keccak256::new()
+            .chain_update(self.image_id.as_bytes())
+            .chain_update(self.journal_digest.as_bytes())
+            .finalize();
+        keccak(&inner)
+    }
+}
+
+/// Merkle tree over the claims of a batch.
+#[derive(Clone, Debug)]
+pub struct AggregationTree {
+    // Levels of the tree, starting with the leaves and ending with the root.
+    levels: Vec<Vec<Digest>>,
+}
+
+impl AggregationTree {
+    /// Builds the tree of `claims`, keeping their order.
+    pub fn new(claims: &[Claim]) -> Result<Self, Error> {
+        if claims.is_empty() {
+            bail!("cannot aggregate an empty batch");
+        }
+        let mut levels = alloc::vec![claims.iter().map(Claim::leaf).collect::<Vec<_>>()];
+        while levels[levels.len() - 1].len() > 1 {
+            let next = levels[levels.len() - 1]
+                .chunks(2)
+                .map(|pair| match pair {
+                    [left, right] => hash_pair(left, right),
+                    [single] => *single,
+                    _ => unreachable!(),
+                })
+                .collect();
+            levels.push(next);
+        }
+        Ok(Self { levels })
+    }
+
+    /// Returns the number of claims in the tree.
+    pub fn len(&self) -> usize {
+        self.levels[0].len()
+    }
+
+    /// Returns `true` if the tree holds no claims, which [Self::new] rejects.
+    pub fn is_empty(&self) -> bool {
+        self.levels[0].is_empty()
+    }
+
+    /// Returns the root of the tree.
+    pub fn root(&self) -> Digest {
+        self.levels[self.levels.len() - 1][0]
+    }
+
+    /// Returns the public inputs of the aggregated proof committing to the
+    /// root, as split by [split_digest].
+    pub fn public_inputs(&self) -> Result<(Fr, Fr), Error> {
+        split_digest(self.root())
+    }
+
+    /// Returns the proof that the claim at `index` is included in the tree, or
+    /// `None` if `index` is out of bounds.
+    pub fn proof(&self, index: usize) -> Option<InclusionProof> {
+        if index >= self.len() {
+            return None;
+        }
+        let mut siblings = Vec::new();
+        let mut position = index;
+        for level in &self.levels[..self.levels.len() - 1] {
+            if let Some(sibling) = level.get(position ^ 1) {
+                siblings.push(*sibling);
+            }
+            position /= 2;
+        }
+        Some(InclusionProof { index, siblings })
+    }
+}
+
+/// Proof that a [Claim] is included in an [AggregationTree].
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct InclusionProof {
+    /// Index of the claim in the batch.
+    pub index: usize,
+
+    /// Siblings of the nodes on the path from the leaf to the root, as passed
+    /// to `MerkleProof.verify`.
+    pub siblings: Vec<Digest>,
+}
+
+impl InclusionProof {
+    /// Returns the root of the tree that includes `claim`.
+    pub fn root(&self, claim: &Claim) -> Digest {
+        self.siblings
+            .iter()
+            .fold(claim.leaf(), |node, sibling| hash_pair(&node, sibling))
+    }
+
+    /// Checks that `claim` is included in the tree with the given `root`.
+    pub fn verify(&self, claim: &Claim, root: &Digest) -> bool {
+        self.root(claim).ct_eq(root)
+    }
+}
+
+fn keccak(data: &[u8]) -> Digest {
+    let hash: [u8; 32] = Keccak256::digest(data).into();
+    Digest::from(hash)
+}
+
+// Children are compared as `bytes32`, that is by their big-endian bytes.
+fn hash_pair(a: &Digest, b: &Digest) -> Digest {
+    let (a, b) = (a.as_bytes(), b.as_bytes());
+    let (left, right) = if a <= b { (a, b) } else { (b, a) };
+    let hash: [u8; 32] = Keccak256::new()
+        .chain_update(left)
+        .chain_update(right)
+        .finalize()
+        .into();
+    Digest::from(hash)
+}
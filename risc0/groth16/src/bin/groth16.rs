@@ -0,0 +1,222 @@
+// Copyright 2024 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::{fs, path::PathBuf};
+
+use anyhow::{Context, Result};
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use hex::FromHex;
+use risc0_circuit_recursion::control_id::{ALLOWED_IDS_ROOT, BN254_CONTROL_ID};
+use risc0_groth16::{
+    fr_from_hex_string,
+    pvk::{Registry, VersionedKey},
+    verifier::prepared_verifying_key,
+    Encoding, ProofJson, Seal, SealRef, Verifier, VerifyingKeyJson,
+};
+use risc0_zkp::core::digest::Digest;
+
+/// Inspects, converts and verifies RISC Zero Groth16 seals.
+///
+/// Seals are read from JSON files holding a serialized seal or a snarkjs
+/// proof, or from files holding the raw or hex encoded bytes of a seal, either
+/// uncompressed (256 bytes) or compressed (128 bytes).
+#[derive(Parser)]
+#[command(about, version, author)]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Verify that a seal attests to the claim with the given digest.
+    Verify {
+        #[command(flatten)]
+        seal: SealArgs,
+
+        #[command(flatten)]
+        key: KeyArgs,
+    },
+
+    /// Print the points of a seal and its encodings.
+    Inspect {
+        #[command(flatten)]
+        seal: SealArgs,
+    },
+
+    /// Convert a seal to another format, printed to stdout.
+    Convert {
+        #[command(flatten)]
+        seal: SealArgs,
+
+        /// The output format.
+        #[arg(long, value_enum)]
+        to: Format,
+
+        /// Negate the `a` point of the seal in the calldata, for verifier
+        /// contracts expecting `-a`.
+        #[arg(long)]
+        negate_a: bool,
+
+        #[command(flatten)]
+        key: KeyArgs,
+    },
+}
+
+#[derive(Args)]
+struct SealArgs {
+    /// The seal file.
+    #[arg(long)]
+    seal: PathBuf,
+}
+
+#[derive(Args)]
+struct KeyArgs {
+    /// Digest of the receipt claim attested to by the seal, in hex.
+    ///
+    /// Required to verify a seal or encode it as calldata.
+    #[arg(long)]
+    meta: Option<String>,
+
+    /// snarkjs verifying key to use instead of the RISC Zero verifying key.
+    #[arg(long)]
+    vkey: Option<PathBuf>,
+
+    /// Merkle root of the allowed recursion control IDs, in hex.
+    #[arg(long, default_value = ALLOWED_IDS_ROOT)]
+    allowed_ids_root: String,
+
+    /// Control ID of the BN254 identity recursion program, in hex.
+    #[arg(long, default_value = BN254_CONTROL_ID)]
+    control_id: String,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum Format {
+    /// Calldata of the Solidity verifier's `verifyProof`, in hex.
+    Calldata,
+    /// The uncompressed 256-byte seal, in hex.
+    Bytes,
+    /// The compressed 128-byte seal, in hex.
+    Compressed,
+    /// The seal as an uncompressed arkworks proof, in hex.
+    Arkworks,
+    /// The serialized seal as JSON.
+    Json,
+}
+
+const VERSION: &str = env!("CARGO_PKG_VERSION");
+
+fn main() -> Result<()> {
+    match Cli::parse().command {
+        Command::Verify { seal, key } => {
+            key.verifier(&seal.read()?)?.verify()?;
+            println!("OK");
+        }
+        Command::Inspect { seal } => inspect(&seal.read()?)?,
+        Command::Convert {
+            seal,
+            to,
+            negate_a,
+            key,
+        } => {
+            let seal = seal.read()?;
+            match to {
+                Format::Calldata => {
+                    let public_inputs = key.verifier(&seal)?.public_inputs()?;
+                    println!(
+                        "0x{}",
+                        hex::encode(seal.to_calldata(&public_inputs, negate_a)?)
+                    );
+                }
+                Format::Bytes => println!("0x{}", hex::encode(seal.to_vec())),
+                Format::Compressed => println!("0x{}", hex::encode(seal.compress()?)),
+                Format::Arkworks => {
+                    println!("0x{}", hex::encode(seal.to_bytes(Encoding::Arkworks)))
+                }
+                Format::Json => println!("{}", serde_json::to_string_pretty(&seal)?),
+            }
+        }
+    }
+    Ok(())
+}
+
+fn inspect(seal: &Seal) -> Result<()> {
+    let words = SealRef::try_from(seal)?;
+    let print = |name: &str, word: &[u8; 32]| println!("{name:8} 0x{}", hex::encode(word));
+    print("a.x", words.a[0]);
+    print("a.y", words.a[1]);
+    print("b.x.c1", words.b[0][0]);
+    print("b.x.c0", words.b[0][1]);
+    print("b.y.c1", words.b[1][0]);
+    print("b.y.c0", words.b[1][1]);
+    print("c.x", words.c[0]);
+    print("c.y", words.c[1]);
+    match seal.compress() {
+        Ok(compressed) => {
+            println!("normalized: {}", seal.is_normalized()?);
+            println!("compressed: 0x{}", hex::encode(compressed));
+        }
+        Err(err) => println!("invalid points: {err}"),
+    }
+    Ok(())
+}
+
+impl SealArgs {
+    fn read(&self) -> Result<Seal> {
+        let data = fs::read(&self.seal)
+            .with_context(|| format!("failed to read {}", self.seal.display()))?;
+        if let Ok(seal) = serde_json::from_slice::<Seal>(&data) {
+            return Ok(seal);
+        }
+        if let Ok(proof) = serde_json::from_slice::<ProofJson>(&data) {
+            return proof.try_into();
+        }
+        let text = String::from_utf8_lossy(&data);
+        let text = text.trim();
+        let bytes = Vec::from_hex(text.strip_prefix("0x").unwrap_or(text)).unwrap_or(data);
+        match bytes.len() {
+            128 => Seal::decompress(&bytes),
+            _ => Seal::from_vec(&bytes),
+        }
+    }
+}
+
+impl KeyArgs {
+    fn verifier(&self, seal: &Seal) -> Result<Verifier> {
+        let meta = self
+            .meta
+            .as_ref()
+            .context("the claim digest must be provided with --meta")?;
+        let pvk = match &self.vkey {
+            Some(path) => serde_json::from_slice::<VerifyingKeyJson>(&fs::read(path)?)?
+                .prepared_verifying_key()?,
+            None => prepared_verifying_key()?,
+        };
+        let key = VersionedKey {
+            pvk,
+            allowed_ids_root: Digest::from_hex(&self.allowed_ids_root)
+                .context("invalid allowed IDs root")?,
+            bn254_control_id: fr_from_hex_string(&self.control_id)?,
+        };
+        let mut registry = Registry::new();
+        registry.insert(VERSION, key);
+        Verifier::from_seal_versioned(
+            &registry,
+            VERSION,
+            seal.try_into()?,
+            Digest::from_hex(meta).context("invalid claim digest")?,
+        )
+    }
+}
@@ -0,0 +1,77 @@
+// Copyright 2024 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Groth16 verification backed by `substrate-bn`, enabled by the
+//! `substrate-bn` feature.
+//!
+//! The points are decoded from their arkworks encoding without validation,
+//! and then validated by `substrate-bn` when they are converted, which checks
+//! that each point is on the curve and in the prime order subgroup.
+
+use anyhow::{anyhow, Error, Result};
+use ark_bn254::{Bn254, G1Affine, G1Projective, G2Affine};
+use ark_ec::AffineRepr;
+use ark_ff::{BigInteger, PrimeField};
+use ark_groth16::{Proof, VerifyingKey};
+use ark_serialize::CanonicalDeserialize;
+use substrate_bn::{pairing_batch, AffineG1, AffineG2, Fq, Fq2, Group, Gt, G1, G2};
+
+use crate::Verifier;
+
+/// Checks the Groth16 pairing equation
+/// `e(A, B) = e(alpha, beta) * e(inputs, gamma) * e(C, delta)`.
+pub(crate) fn verify(verifier: &Verifier) -> Result<bool, Error> {
+    // The encoding of a prepared verifying key starts with the verifying key.
+    let vk = VerifyingKey::<Bn254>::deserialize_uncompressed_unchecked(&*verifier.encoded_pvk)
+        .map_err(|err| anyhow!(err))?;
+    let proof = Proof::<Bn254>::deserialize_uncompressed_unchecked(&*verifier.encoded_proof)
+        .map_err(|err| anyhow!(err))?;
+    let prepared_inputs =
+        G1Projective::deserialize_uncompressed_unchecked(&*verifier.encoded_prepared_inputs)
+            .map_err(|err| anyhow!(err))?;
+
+    let pairs = [
+        (-g1(&proof.a)?, g2(&proof.b)?),
+        (g1(&vk.alpha_g1)?, g2(&vk.beta_g2)?),
+        (g1(&prepared_inputs.into())?, g2(&vk.gamma_g2)?),
+        (g1(&proof.c)?, g2(&vk.delta_g2)?),
+    ];
+    Ok(pairing_batch(&pairs) == Gt::one())
+}
+
+fn fq(value: &ark_bn254::Fq) -> Result<Fq, Error> {
+    Fq::from_slice(&value.into_bigint().to_bytes_be())
+        .map_err(|_| anyhow!("Malformed field element"))
+}
+
+fn g1(point: &G1Affine) -> Result<G1, Error> {
+    match point.xy() {
+        Some((x, y)) => AffineG1::new(fq(x)?, fq(y)?)
+            .map(Into::into)
+            .map_err(|_| anyhow!("Malformed G1 point")),
+        None => Ok(G1::zero()),
+    }
+}
+
+fn g2(point: &G2Affine) -> Result<G2, Error> {
+    match point.xy() {
+        Some((x, y)) => AffineG2::new(
+            Fq2::new(fq(&x.c0)?, fq(&x.c1)?),
+            Fq2::new(fq(&y.c0)?, fq(&y.c1)?),
+        )
+        .map(Into::into)
+        .map_err(|_| anyhow!("Malformed G2 point")),
+        None => Ok(G2::zero()),
+    }
+}
@@ -17,15 +17,30 @@ extern crate alloc;
 use alloc::{vec, vec::Vec};
 
 use anyhow::{anyhow, Error, Result};
-use ark_bn254::{Bn254, Fr};
-use ark_groth16::{prepare_verifying_key, PreparedVerifyingKey, VerifyingKey};
+use ark_bn254::{Bn254, Fq, Fr, G1Affine, G2Affine};
+use ark_groth16::{prepare_verifying_key, PreparedVerifyingKey, Proof, VerifyingKey};
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
 use core::str::FromStr;
+use num_bigint::BigUint;
 use serde::{Deserialize, Serialize};
 
-use crate::{from_u256, g1_from_bytes, g2_from_bytes};
+use crate::{
+    encoding::{to_be_word, to_word},
+    from_u256, g1_from_bytes, g2_from_bytes, g2_from_json,
+    verifier::MalformedInput,
+    Encoding,
+};
 
 /// Groth16 seal object encoded in big endian.
+///
+/// With the `borsh` feature enabled, the seal also implements Borsh
+/// serialization, so that it can be decoded by Solana and NEAR programs
+/// without a JSON parser.
 #[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+#[cfg_attr(
+    feature = "borsh",
+    derive(borsh::BorshSerialize, borsh::BorshDeserialize)
+)]
 pub struct Seal {
     /// Proof 'a' value
     pub a: Vec<Vec<u8>>,
@@ -40,6 +55,13 @@ impl Seal {
     const G1_GROUP_SIZE: usize = Self::ELEMENT_SIZE * 2;
     const G2_GROUP_SIZE: usize = Self::ELEMENT_SIZE * 4;
     const SIZE: usize = Self::G1_GROUP_SIZE * 2 + Self::G2_GROUP_SIZE;
+    const COMPRESSED_SIZE: usize = Self::SIZE / 2;
+    const NUM_PUBLIC_INPUTS: usize = 5;
+    const CALLDATA_SIZE: usize = 4 + Self::SIZE + Self::ELEMENT_SIZE * Self::NUM_PUBLIC_INPUTS;
+
+    /// Function selector of the Groth16 verifier contract's
+    /// `verifyProof(uint256[2],uint256[2][2],uint256[2],uint256[5])`.
+    pub const VERIFY_PROOF_SELECTOR: [u8; 4] = [0x34, 0xba, 0xea, 0xb9];
 
     /// Serialize the Groth16 `Seal` into a `Vec<u8>`
     pub fn to_vec(&self) -> Vec<u8> {
@@ -65,10 +87,38 @@ impl Seal {
         result
     }
 
+    /// Serialize the Groth16 `Seal` into a `Vec<u8>` with the given encoding.
+    ///
+    /// The words of `a`, `b` and `c` are concatenated in this order. With
+    /// [Encoding::Evm], this is the same as [Seal::to_vec], and with
+    /// [Encoding::Arkworks], it can be deserialized by arkworks as an
+    /// uncompressed proof.
+    pub fn to_bytes(&self, encoding: Encoding) -> Vec<u8> {
+        Seal {
+            a: Encoding::Evm.transcode_g1(&self.a, encoding),
+            b: Encoding::Evm.transcode_g2(&self.b, encoding),
+            c: Encoding::Evm.transcode_g1(&self.c, encoding),
+        }
+        .to_vec()
+    }
+
+    /// Method to convert back from a `Vec<u8>` with the given encoding.
+    pub fn from_bytes(data: &[u8], encoding: Encoding) -> Result<Seal, Error> {
+        let seal = Self::from_vec(data)?;
+        Ok(Seal {
+            a: encoding.transcode_g1(&seal.a, Encoding::Evm),
+            b: encoding.transcode_g2(&seal.b, Encoding::Evm),
+            c: encoding.transcode_g1(&seal.c, Encoding::Evm),
+        })
+    }
+
     /// Method to convert back from a `Vec<u8>`
     pub fn from_vec(data: &[u8]) -> Result<Seal, Error> {
         if data.len() != Self::SIZE {
-            return Err(anyhow!("Data length mismatch"));
+            return Err(Error::msg(MalformedInput::SealLength {
+                expected: Self::SIZE,
+                found: data.len(),
+            }));
         }
 
         let mut offset = 0;
@@ -100,6 +150,273 @@ impl Seal {
 
         Ok(Seal { a, b, c })
     }
+
+    /// Serialize the Groth16 `Seal` into a `Vec<u8>` prefixed by the
+    /// big-endian [SEAL_VERSION](crate::SEAL_VERSION) of this crate.
+    pub fn to_versioned_bytes(&self) -> Vec<u8> {
+        let mut result = Vec::with_capacity(4 + Self::SIZE);
+        result.extend(crate::SEAL_VERSION.to_be_bytes());
+        result.extend(self.to_vec());
+        result
+    }
+
+    /// Method to convert back from the output of [Seal::to_versioned_bytes].
+    ///
+    /// Seals of another version are rejected with
+    /// [MalformedInput::SealVersion].
+    pub fn from_versioned_bytes(data: &[u8]) -> Result<Seal, Error> {
+        if data.len() != 4 + Self::SIZE {
+            return Err(Error::msg(MalformedInput::SealLength {
+                expected: 4 + Self::SIZE,
+                found: data.len(),
+            }));
+        }
+        let (version, seal) = data.split_at(4);
+        let found = u32::from_be_bytes([version[0], version[1], version[2], version[3]]);
+        if found != crate::SEAL_VERSION {
+            return Err(Error::msg(MalformedInput::SealVersion {
+                found,
+                supported: crate::SEAL_VERSION,
+            }));
+        }
+        Self::from_vec(seal)
+    }
+
+    /// Compress the Groth16 `Seal` into 128 bytes.
+    ///
+    /// Each point is replaced by its `x` coordinate, followed by `b` and `c`,
+    /// with the sign of `y` and the point at infinity flagged in the two most
+    /// significant bits of the first byte. The words are big-endian, and the
+    /// coefficients of `b` are in the `(c1, c0)` order of [Seal::to_vec].
+    ///
+    /// The points are decoded, so this fails if they are not on the curve.
+    pub fn compress(&self) -> Result<Vec<u8>, Error> {
+        let seal = SealRef::try_from(self)?;
+        let mut result = Vec::with_capacity(Self::COMPRESSED_SIZE);
+        compress_point(&g1_from_bytes(seal.a)?, &mut result)?;
+        compress_point(&g2_from_bytes(seal.b)?, &mut result)?;
+        compress_point(&g1_from_bytes(seal.c)?, &mut result)?;
+        Ok(result)
+    }
+
+    /// Method to convert back from the output of [Seal::compress].
+    pub fn decompress(data: &[u8]) -> Result<Seal, Error> {
+        if data.len() != Self::COMPRESSED_SIZE {
+            return Err(Error::msg(MalformedInput::SealLength {
+                expected: Self::COMPRESSED_SIZE,
+                found: data.len(),
+            }));
+        }
+        let (a, rest) = data.split_at(Self::ELEMENT_SIZE);
+        let (b, c) = rest.split_at(Self::ELEMENT_SIZE * 2);
+
+        let mut uncompressed = Vec::with_capacity(Self::SIZE);
+        decompress_point::<G1Affine>(a, &mut uncompressed)?;
+        decompress_point::<G2Affine>(b, &mut uncompressed)?;
+        decompress_point::<G1Affine>(c, &mut uncompressed)?;
+        Self::from_bytes(&uncompressed, Encoding::Arkworks)
+    }
+
+    /// Returns the canonical representative of this seal.
+    ///
+    /// Negating both `a` and `b` yields another valid proof of the same
+    /// statement. Of the two, the canonical seal is the one where the `y`
+    /// coordinate of `a` is at most `-y`; the other one is negated. Seals
+    /// verified with [Verifier::verify_strict](crate::Verifier::verify_strict)
+    /// are canonical, so their bytes can identify the proof.
+    ///
+    /// This only removes the sign malleability of the seal: the prover can
+    /// still re-randomize a proof into a different canonical seal.
+    pub fn normalize(&self) -> Result<Seal, Error> {
+        let seal = SealRef::try_from(self)?;
+        let mut proof = Proof::<Bn254> {
+            a: g1_from_bytes(seal.a)?,
+            b: g2_from_bytes(seal.b)?,
+            c: g1_from_bytes(seal.c)?,
+        };
+        if !is_canonical(&proof.a) {
+            proof.a = -proof.a;
+            proof.b = -proof.b;
+        }
+        let mut encoded = Vec::with_capacity(Self::SIZE);
+        proof
+            .serialize_uncompressed(&mut encoded)
+            .map_err(|err| anyhow!(err))?;
+        Self::from_bytes(&encoded, Encoding::Arkworks)
+    }
+
+    /// Checks whether this seal is its own [normalized](Seal::normalize) form.
+    pub fn is_normalized(&self) -> Result<bool, Error> {
+        Ok(is_canonical(&g1_from_bytes(SealRef::try_from(self)?.a)?))
+    }
+
+    /// Returns this seal with its `a` point negated.
+    ///
+    /// Some verifier contracts expect `-a` in the proof, so that the pairing
+    /// check is a single product equal to one. The seal must be negated again
+    /// to be verified by a [Verifier](crate::Verifier).
+    pub fn negate_a(&self) -> Result<Seal, Error> {
+        let a = -g1_from_bytes(SealRef::try_from(self)?.a)?;
+        Ok(Seal {
+            a: vec![to_be_word(&a.x), to_be_word(&a.y)],
+            b: self.b.clone(),
+            c: self.c.clone(),
+        })
+    }
+
+    /// Encode a call to the Groth16 verifier contract's `verifyProof` function
+    /// with this seal and `public_inputs`.
+    ///
+    /// With `negate_a`, the calldata holds the seal returned by
+    /// [Seal::negate_a], for verifier contracts expecting `-a`. Otherwise, this
+    /// is the inverse of [Seal::from_calldata].
+    pub fn to_calldata(&self, public_inputs: &[Fr], negate_a: bool) -> Result<Vec<u8>, Error> {
+        if public_inputs.len() != Self::NUM_PUBLIC_INPUTS {
+            return Err(Error::msg(MalformedInput::PublicInputsLength {
+                expected: Self::NUM_PUBLIC_INPUTS,
+                found: public_inputs.len(),
+            }));
+        }
+        let seal = match negate_a {
+            true => self.negate_a()?,
+            false => {
+                SealRef::try_from(self)?;
+                self.clone()
+            }
+        };
+        let mut calldata = Vec::with_capacity(Self::CALLDATA_SIZE);
+        calldata.extend(Self::VERIFY_PROOF_SELECTOR);
+        calldata.extend(seal.to_vec());
+        for input in public_inputs {
+            calldata.extend(to_be_word(input));
+        }
+        Ok(calldata)
+    }
+
+    /// Decode a `Seal` from the calldata of a call to the Groth16 verifier
+    /// contract's `verifyProof` function.
+    ///
+    /// The calldata must start with [Seal::VERIFY_PROOF_SELECTOR], followed by
+    /// the ABI encoded proof and public inputs. The public inputs are ignored.
+    /// Seals encoded with a negated `a` are returned as is, and can be restored
+    /// with [Seal::negate_a].
+    pub fn from_calldata(calldata: &[u8]) -> Result<Seal, Error> {
+        if calldata.len() != Self::CALLDATA_SIZE {
+            return Err(Error::msg(MalformedInput::SealLength {
+                expected: Self::CALLDATA_SIZE,
+                found: calldata.len(),
+            }));
+        }
+        let (selector, args) = calldata.split_at(Self::VERIFY_PROOF_SELECTOR.len());
+        if selector != Self::VERIFY_PROOF_SELECTOR {
+            let mut found = [0u8; 4];
+            found.copy_from_slice(selector);
+            return Err(Error::msg(MalformedInput::FunctionSelector { found }));
+        }
+        Self::from_vec(&args[..Self::SIZE])
+    }
+}
+
+// Whether `a` is the canonical representative of the pair `{a, -a}`, as the
+// point with the smallest `y` coordinate.
+pub(crate) fn is_canonical(a: &G1Affine) -> bool {
+    a.y <= -a.y
+}
+
+// Append the compressed arkworks serialization of a point, reversed into the
+// big-endian, `(c1, c0)` ordered layout of the EVM encoding.
+fn compress_point<P: CanonicalSerialize>(point: &P, out: &mut Vec<u8>) -> Result<(), Error> {
+    let start = out.len();
+    point
+        .serialize_compressed(&mut *out)
+        .map_err(|err| anyhow!(err))?;
+    out[start..].reverse();
+    Ok(())
+}
+
+// Decode a point compressed by `compress_point`, and append its uncompressed
+// arkworks serialization.
+fn decompress_point<P: CanonicalSerialize + CanonicalDeserialize>(
+    data: &[u8],
+    out: &mut Vec<u8>,
+) -> Result<(), Error> {
+    let mut compressed = data.to_vec();
+    compressed.reverse();
+    P::deserialize_compressed(&compressed[..])
+        .and_then(|point| point.serialize_uncompressed(&mut *out))
+        .map_err(|err| anyhow!(err))
+}
+
+/// Borrowed view of a Groth16 seal encoded in big endian.
+///
+/// A `SealRef` references the words of a seal in place, so it can be parsed
+/// from the bytes produced by [Seal::to_vec] without copying them, and
+/// decoded into curve points without intermediate allocations.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SealRef<'a> {
+    /// Proof 'a' value
+    pub a: [&'a [u8; 32]; 2],
+    /// Proof 'b' value
+    pub b: [[&'a [u8; 32]; 2]; 2],
+    /// Proof 'c' value
+    pub c: [&'a [u8; 32]; 2],
+}
+
+impl<'a> SealRef<'a> {
+    /// Borrows a seal from bytes in the format of [Seal::to_vec].
+    pub fn from_bytes(data: &'a [u8]) -> Result<Self, Error> {
+        if data.len() != Seal::SIZE {
+            return Err(Error::msg(MalformedInput::SealLength {
+                expected: Seal::SIZE,
+                found: data.len(),
+            }));
+        }
+        let mut words = data
+            .chunks_exact(Seal::ELEMENT_SIZE)
+            .map(|word| word.try_into().unwrap());
+        let mut next = || words.next().unwrap();
+        Ok(SealRef {
+            a: [next(), next()],
+            b: [[next(), next()], [next(), next()]],
+            c: [next(), next()],
+        })
+    }
+
+    /// Copies the borrowed words into an owned [Seal].
+    pub fn to_seal(&self) -> Seal {
+        Seal {
+            a: self.a.map(|word| word.to_vec()).to_vec(),
+            b: self
+                .b
+                .map(|coordinate| coordinate.map(|word| word.to_vec()).to_vec())
+                .to_vec(),
+            c: self.c.map(|word| word.to_vec()).to_vec(),
+        }
+    }
+}
+
+impl<'a> TryFrom<&'a Seal> for SealRef<'a> {
+    type Error = Error;
+    fn try_from(seal: &'a Seal) -> Result<Self, Error> {
+        let b = match seal.b.as_slice() {
+            [x, y] => [borrow_words(x)?, borrow_words(y)?],
+            _ => return Err(anyhow!("Malformed G2 field element")),
+        };
+        Ok(SealRef {
+            a: borrow_words(&seal.a)?,
+            b,
+            c: borrow_words(&seal.c)?,
+        })
+    }
+}
+
+// Borrow a pair of words, such as the coordinates of a G1 point or the
+// coefficients of a G2 coordinate.
+fn borrow_words(elem: &[Vec<u8>]) -> Result<[&[u8; 32]; 2], Error> {
+    match elem {
+        [first, second] => Ok([to_word(first)?, to_word(second)?]),
+        _ => Err(anyhow!("Malformed field element")),
+    }
 }
 
 impl TryFrom<ProofJson> for Seal {
@@ -108,20 +425,35 @@ impl TryFrom<ProofJson> for Seal {
         if proof.pi_a.len() < 2 {
             return Err(anyhow!("Malformed G1 element field"));
         }
-        let a = vec![from_u256(&proof.pi_a[0])?, from_u256(&proof.pi_a[1])?];
+        let a = vec![
+            from_u256(&proof.pi_a[0])?.to_vec(),
+            from_u256(&proof.pi_a[1])?.to_vec(),
+        ];
 
         if proof.pi_b.len() < 2 || proof.pi_b[0].len() < 2 || proof.pi_b[1].len() < 2 {
             return Err(anyhow!("Malformed G2 element field"));
         }
-        let b = vec![
-            vec![from_u256(&proof.pi_b[0][1])?, from_u256(&proof.pi_b[0][0])?],
-            vec![from_u256(&proof.pi_b[1][1])?, from_u256(&proof.pi_b[1][0])?],
-        ];
+        let b = Encoding::Snarkjs.transcode_g2(
+            &[
+                vec![
+                    Encoding::Snarkjs.u256(&proof.pi_b[0][0])?,
+                    Encoding::Snarkjs.u256(&proof.pi_b[0][1])?,
+                ],
+                vec![
+                    Encoding::Snarkjs.u256(&proof.pi_b[1][0])?,
+                    Encoding::Snarkjs.u256(&proof.pi_b[1][1])?,
+                ],
+            ],
+            Encoding::Evm,
+        );
 
         if proof.pi_c.len() < 2 {
             return Err(anyhow!("Malformed G1 element field"));
         }
-        let c = vec![from_u256(&proof.pi_c[0])?, from_u256(&proof.pi_c[1])?];
+        let c = vec![
+            from_u256(&proof.pi_c[0])?.to_vec(),
+            from_u256(&proof.pi_c[1])?.to_vec(),
+        ];
 
         Ok(Seal { a, b, c })
     }
@@ -154,59 +486,79 @@ pub struct VerifyingKeyJson {
 }
 
 impl VerifyingKeyJson {
+    /// Checks the structure of the verifying key.
+    ///
+    /// The key must be a Groth16 key over BN254 (`bn128` in snarkjs), hold
+    /// `nPublic + 1` IC points, and every coordinate must be a canonical
+    /// decimal or `0x` prefixed hexadecimal integer smaller than the modulus of
+    /// the base field. Points must have exactly three coordinates, the last
+    /// being the projective z coordinate 1. This does not check that the
+    /// points are on the curve, which is done when decoding them.
+    pub fn validate(&self) -> Result<(), Error> {
+        if self.protocol != "groth16" {
+            return Err(anyhow!(
+                "Unsupported protocol: expected groth16, found {}",
+                self.protocol
+            ));
+        }
+        if self.curve != "bn128" {
+            return Err(anyhow!(
+                "Unsupported curve: expected bn128, found {}",
+                self.curve
+            ));
+        }
+        if self.ic.len() != self.n_public as usize + 1 {
+            return Err(anyhow!(
+                "IC holds {} points, expected nPublic + 1 = {}",
+                self.ic.len(),
+                self.n_public as usize + 1
+            ));
+        }
+
+        validate_g1("vk_alpha_1", &self.vk_alpha_1)?;
+        validate_g2("vk_beta_2", &self.vk_beta_2)?;
+        validate_g2("vk_gamma_2", &self.vk_gamma_2)?;
+        validate_g2("vk_delta_2", &self.vk_delta_2)?;
+        for (i, coordinate) in self.vk_alphabeta_12.iter().enumerate() {
+            for (j, pair) in coordinate.iter().enumerate() {
+                for (k, value) in pair.iter().enumerate() {
+                    validate_fq(&format!("vk_alphabeta_12[{i}][{j}][{k}]"), value)?;
+                }
+            }
+        }
+        for (i, ic) in self.ic.iter().enumerate() {
+            validate_g1(&format!("IC[{i}]"), ic)?;
+        }
+        Ok(())
+    }
+
     /// Computes the prepared verifying key
     pub fn prepared_verifying_key(&self) -> Result<PreparedVerifyingKey<Bn254>, Error> {
+        self.validate()?;
         if self.vk_alpha_1.len() < 2 {
             return Err(anyhow!("Malformed G1 element field: vk_alpha_1"));
         }
-        let alpha_g1 = g1_from_bytes(&[
-            from_u256(&self.vk_alpha_1[0])?,
-            from_u256(&self.vk_alpha_1[1])?,
+        let alpha_g1 = g1_from_bytes([
+            &from_u256(&self.vk_alpha_1[0])?,
+            &from_u256(&self.vk_alpha_1[1])?,
         ])?;
 
         if self.vk_beta_2.len() < 2 || self.vk_beta_2[0].len() < 2 || self.vk_beta_2[1].len() < 2 {
             return Err(anyhow!("Malformed G2 element field: vk_beta_2"));
         }
-        let beta_g2 = g2_from_bytes(&vec![
-            vec![
-                from_u256(&self.vk_beta_2[0][1])?,
-                from_u256(&self.vk_beta_2[0][0])?,
-            ],
-            vec![
-                from_u256(&self.vk_beta_2[1][1])?,
-                from_u256(&self.vk_beta_2[1][0])?,
-            ],
-        ])?;
+        let beta_g2 = g2_from_json(&self.vk_beta_2)?;
 
         if self.vk_gamma_2.len() < 2 || self.vk_gamma_2[0].len() < 2 || self.vk_gamma_2[1].len() < 2
         {
             return Err(anyhow!("Malformed G2 element field: vk_gamma_2"));
         }
-        let gamma_g2 = g2_from_bytes(&vec![
-            vec![
-                from_u256(&self.vk_gamma_2[0][1])?,
-                from_u256(&self.vk_gamma_2[0][0])?,
-            ],
-            vec![
-                from_u256(&self.vk_gamma_2[1][1])?,
-                from_u256(&self.vk_gamma_2[1][0])?,
-            ],
-        ])?;
+        let gamma_g2 = g2_from_json(&self.vk_gamma_2)?;
 
         if self.vk_delta_2.len() < 2 || self.vk_delta_2[0].len() < 2 || self.vk_delta_2[1].len() < 2
         {
             return Err(anyhow!("Malformed G2 element field: vk_delta_2"));
         }
-        let delta_g2 = g2_from_bytes(&vec![
-            vec![
-                from_u256(&self.vk_delta_2[0][1])?,
-                from_u256(&self.vk_delta_2[0][0])?,
-            ],
-            vec![
-                from_u256(&self.vk_delta_2[1][1])?,
-                from_u256(&self.vk_delta_2[1][0])?,
-            ],
-        ])?;
+        let delta_g2 = g2_from_json(&self.vk_delta_2)?;
 
         let gamma_abc_g1 = self
             .ic
@@ -216,7 +568,7 @@ impl VerifyingKeyJson {
                 if ic.len() < 2 {
                     return Err(anyhow!("Malformed G1 element field: IC_{i}"));
                 }
-                g1_from_bytes(&[from_u256(&ic[0])?, from_u256(&ic[1])?])
+                g1_from_bytes([&from_u256(&ic[0])?, &from_u256(&ic[1])?])
             })
             .collect::<Result<Vec<_>, _>>()?;
 
@@ -232,6 +584,82 @@ impl VerifyingKeyJson {
     }
 }
 
+// Check a G1 point in snarkjs JSON format: affine coordinates followed by a
+// projective z coordinate of 1.
+fn validate_g1(name: &str, elem: &[String]) -> Result<(), Error> {
+    if elem.len() != 3 {
+        return Err(anyhow!("Malformed G1 element field: {name}"));
+    }
+    let mut coordinates = Vec::with_capacity(3);
+    for (i, value) in elem.iter().enumerate() {
+        coordinates.push(parse_fq(&format!("{name}[{i}]"), value)?);
+    }
+    if coordinates[2] != BigUint::from(1u32) {
+        return Err(anyhow!("Malformed G1 element field: {name}[2] is not 1"));
+    }
+    Ok(())
+}
+
+// Check a G2 point in snarkjs JSON format: affine coordinates followed by a
+// projective z coordinate of 1, each as a pair of base field elements.
+fn validate_g2(name: &str, elem: &[Vec<String>]) -> Result<(), Error> {
+    if elem.len() != 3 || elem.iter().any(|coordinate| coordinate.len() != 2) {
+        return Err(anyhow!("Malformed G2 element field: {name}"));
+    }
+    let mut coordinates = Vec::with_capacity(6);
+    for (i, coordinate) in elem.iter().enumerate() {
+        for (j, value) in coordinate.iter().enumerate() {
+            coordinates.push(parse_fq(&format!("{name}[{i}][{j}]"), value)?);
+        }
+    }
+    if coordinates[4..] != [BigUint::from(1u32), BigUint::from(0u32)] {
+        return Err(anyhow!(
+            "Malformed G2 element field: {name}[2] is not [1, 0]"
+        ));
+    }
+    Ok(())
+}
+
+// Check that `value` is an element of the base field of BN254.
+fn validate_fq(name: &str, value: &str) -> Result<(), Error> {
+    parse_fq(name, value).map(|_| ())
+}
+
+// Parse an element of the base field of BN254, written as a canonical decimal
+// integer, without sign or leading zeros, or as `0x` followed by at most 64
+// hexadecimal digits, and smaller than the field modulus.
+fn parse_fq(name: &str, value: &str) -> Result<BigUint, Error> {
+    let parsed = match value.strip_prefix("0x") {
+        Some(digits)
+            if !digits.is_empty()
+                && digits.len() <= 64
+                && digits.bytes().all(|b| b.is_ascii_hexdigit()) =>
+        {
+            BigUint::parse_bytes(digits.as_bytes(), 16)
+        }
+        None if !value.is_empty()
+            && value.bytes().all(|b| b.is_ascii_digit())
+            && (value == "0" || !value.starts_with('0')) =>
+        {
+            BigUint::parse_bytes(value.as_bytes(), 10)
+        }
+        _ => None,
+    };
+    let parsed = parsed.ok_or_else(|| {
+        anyhow!("Malformed field element {name}: {value:?} is not a canonical integer")
+    })?;
+    // Field elements are only deserialized from canonical words, so this
+    // rejects values at or above the modulus.
+    let mut word = parsed.to_bytes_le();
+    word.resize(word.len().max(32), 0);
+    if word.len() > 32 || Fq::deserialize_uncompressed(&word[..]).is_err() {
+        return Err(anyhow!(
+            "Malformed field element {name}: {value} is not smaller than the field modulus"
+        ));
+    }
+    Ok(parsed)
+}
+
 /// Groth16 Public witness encoded as JSON.
 #[derive(Serialize, Deserialize, Debug)]
 pub struct PublicInputsJson {
@@ -396,4 +824,97 @@ mod tests {
         assert_eq!(vk.n_public, 1);
         vk.prepared_verifying_key().unwrap();
     }
+
+    #[test]
+    fn test_vkey_validation() {
+        let vk = || -> VerifyingKeyJson {
+            serde_json::from_str(include_str!("../tests/data/verification_key.json")).unwrap()
+        };
+        vk().validate().unwrap();
+
+        let mut bad = vk();
+        bad.protocol = "plonk".to_string();
+        assert!(bad.validate().unwrap_err().to_string().contains("protocol"));
+
+        let mut bad = vk();
+        bad.curve = "bls12381".to_string();
+        assert!(bad.validate().unwrap_err().to_string().contains("curve"));
+
+        let mut bad = vk();
+        bad.n_public += 1;
+        assert!(bad.validate().unwrap_err().to_string().contains("nPublic"));
+
+        let mut bad = vk();
+        bad.vk_beta_2[1][0] = "0xzz".to_string();
+        let err = bad.validate().unwrap_err().to_string();
+        assert!(err.contains("vk_beta_2[1][0]"), "{err}");
+        assert!(bad.prepared_verifying_key().is_err());
+
+        // Coordinates may be hexadecimal.
+        let mut hex = vk();
+        let to_hex = |value: &mut String| {
+            let value_hex = BigUint::from_str(value).unwrap().to_str_radix(16);
+            *value = format!("0x{value_hex:0>64}");
+        };
+        hex.vk_alpha_1.iter_mut().for_each(to_hex);
+        for coordinate in hex.vk_beta_2.iter_mut().chain(hex.ic.iter_mut()) {
+            coordinate.iter_mut().for_each(to_hex);
+        }
+        hex.validate().unwrap();
+        assert_eq!(
+            hex.prepared_verifying_key().unwrap(),
+            vk().prepared_verifying_key().unwrap()
+        );
+
+        // The modulus of the base field.
+        let mut bad = vk();
+        bad.ic[0][1] =
+            "21888242871839275222246405745257275088696311157297823662689037894645226208583"
+                .to_string();
+        let err = bad.validate().unwrap_err().to_string();
+        assert!(err.contains("IC[0][1]"), "{err}");
+        assert!(err.contains("modulus"), "{err}");
+
+        // Signed, padded or overlong integers are not canonical.
+        for value in [
+            format!("-{}", vk().ic[0][1]),
+            format!("0{}", vk().ic[0][1]),
+            format!("+{}", vk().ic[0][1]),
+            format!("0x1{}", "0".repeat(64)),
+            "0x".to_string(),
+            String::new(),
+        ] {
+            let mut bad = vk();
+            bad.ic[0][1] = value.clone();
+            let err = bad.validate().unwrap_err().to_string();
+            assert!(err.contains("IC[0][1]"), "{value}: {err}");
+            assert!(err.contains("canonical"), "{value}: {err}");
+        }
+
+        // Points have exactly three coordinates, the last being 1.
+        let mut bad = vk();
+        bad.vk_alpha_1.pop();
+        let err = bad.validate().unwrap_err().to_string();
+        assert!(err.contains("vk_alpha_1"), "{err}");
+
+        let mut bad = vk();
+        bad.ic[1].push("1".to_string());
+        let err = bad.validate().unwrap_err().to_string();
+        assert!(err.contains("IC[1]"), "{err}");
+
+        let mut bad = vk();
+        bad.ic[1][2] = "2".to_string();
+        let err = bad.validate().unwrap_err().to_string();
+        assert!(err.contains("IC[1][2] is not 1"), "{err}");
+
+        let mut bad = vk();
+        bad.vk_gamma_2[2][1] = "1".to_string();
+        let err = bad.validate().unwrap_err().to_string();
+        assert!(err.contains("vk_gamma_2[2] is not [1, 0]"), "{err}");
+
+        let mut bad = vk();
+        bad.vk_delta_2[0].pop();
+        let err = bad.validate().unwrap_err().to_string();
+        assert!(err.contains("vk_delta_2"), "{err}");
+    }
 }
@@ -12,10 +12,17 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use std::{env::consts::ARCH, path::Path, process::Command};
+use std::{
+    env::consts::ARCH,
+    fs::OpenOptions,
+    io::Write,
+    path::{Path, PathBuf},
+    process::Command,
+};
 
 use anyhow::{bail, Result};
 use tempfile::tempdir;
+use zeroize::Zeroizing;
 
 use crate::{to_json, ProofJson, Seal};
 
@@ -31,18 +38,22 @@ pub fn stark_to_snark(identity_p254_seal_bytes: &[u8]) -> Result<Seal> {
 
     let tmp_dir = tempdir()?;
     let work_dir = std::env::var("RISC0_WORK_DIR");
+    let keep = work_dir.is_ok();
     let work_dir = work_dir
         .as_ref()
         .map(|x| Path::new(x))
         .unwrap_or(tmp_dir.path());
 
     tracing::debug!("seal-to-json");
-    std::fs::write(work_dir.join("seal.r0"), &identity_p254_seal_bytes)?;
-    let seal_path = work_dir.join("input.json");
+    // The witness inputs are created owner-only. Unless they were written to
+    // RISC0_WORK_DIR, which is kept for inspection, they only live on disk
+    // while the prover runs, and are overwritten with zeros before being
+    // removed, even if the prover fails.
+    let _seal_file = WitnessFile::create(work_dir.join("seal.r0"), identity_p254_seal_bytes, keep)?;
     let proof_path = work_dir.join("proof.json");
-    let mut seal_json = Vec::new();
-    to_json(identity_p254_seal_bytes, &mut seal_json)?;
-    std::fs::write(seal_path, seal_json)?;
+    let mut seal_json = Zeroizing::new(Vec::new());
+    to_json(identity_p254_seal_bytes, &mut *seal_json)?;
+    let _input_file = WitnessFile::create(work_dir.join("input.json"), &seal_json, keep)?;
 
     tracing::debug!("risc0-groth16-prover");
     let status = Command::new("docker")
@@ -62,6 +73,45 @@ pub fn stark_to_snark(identity_p254_seal_bytes: &[u8]) -> Result<Seal> {
     proof_json.try_into()
 }
 
+/// A witness input written for the prover, scrubbed and removed on drop
+/// unless `keep` is set.
+struct WitnessFile {
+    path: PathBuf,
+    len: usize,
+    keep: bool,
+}
+
+impl WitnessFile {
+    fn create(path: PathBuf, contents: &[u8], keep: bool) -> Result<Self> {
+        let mut options = OpenOptions::new();
+        options.write(true).create(true).truncate(true);
+        #[cfg(unix)]
+        std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+        // Construct the guard first so that a failed write is still cleaned up.
+        let file = Self {
+            path,
+            len: contents.len(),
+            keep,
+        };
+        options.open(&file.path)?.write_all(contents)?;
+        Ok(file)
+    }
+}
+
+impl Drop for WitnessFile {
+    fn drop(&mut self) {
+        if self.keep {
+            return;
+        }
+        if let Ok(mut file) = OpenOptions::new().write(true).open(&self.path) {
+            if file.write_all(&vec![0; self.len]).is_ok() {
+                let _ = file.sync_all();
+            }
+        }
+        let _ = std::fs::remove_file(&self.path);
+    }
+}
+
 fn is_docker_installed() -> bool {
     Command::new("docker")
         .arg("--version")
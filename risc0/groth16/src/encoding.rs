@@ -0,0 +1,272 @@
+// Copyright 2024 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Byte encodings of field elements and curve points.
+
+extern crate alloc;
+
+use alloc::vec::Vec;
+use core::str::FromStr;
+
+use anyhow::{anyhow, Error, Result};
+use ark_bn254::{Fr, G1Affine, G2Affine};
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+use num_bigint::BigInt;
+
+/// Encoding of the 32-byte words making up field elements and curve points.
+///
+/// A G1 point is a pair of words `[x, y]`, and a G2 point a pair of pairs
+/// `[[x0, x1], [y0, y1]]`, where the order of the two coefficients of each
+/// coordinate over the quadratic extension field depends on the encoding.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Encoding {
+    /// Big-endian words with the coefficients of G2 coordinates in `(c0, c1)`
+    /// order, as in the JSON files produced by snarkjs.
+    Snarkjs,
+    /// Little-endian words with the coefficients of G2 coordinates in
+    /// `(c0, c1)` order, as in the uncompressed arkworks serialization.
+    ///
+    /// arkworks stores flags in the two most significant bits of the `y`
+    /// coordinate, which are cleared when transcoding to another encoding.
+    Arkworks,
+    /// Big-endian words with the coefficients of G2 coordinates in `(c1, c0)`
+    /// order, as expected by the EVM precompiles and the Solidity verifier.
+    /// This is the encoding of a [Seal](crate::Seal).
+    Evm,
+}
+
+impl Encoding {
+    /// Parses a decimal or `0x` prefixed hexadecimal integer into a word.
+    pub fn u256(self, value: &str) -> Result<Vec<u8>, Error> {
+        Ok(self.u256_word(value)?.to_vec())
+    }
+
+    pub(crate) fn u256_word(self, value: &str) -> Result<[u8; 32], Error> {
+        let big_endian = if let Some(stripped) = value.strip_prefix("0x") {
+            hex::decode(stripped).map_err(|_| anyhow!("conversion from u256 failed"))?
+        } else {
+            BigInt::from_str(value)
+                .map_err(|_| anyhow!("conversion from u256 failed"))?
+                .to_bytes_be()
+                .1
+        };
+        let mut word = to_fixed_array(big_endian);
+        if !self.is_big_endian() {
+            word.reverse();
+        }
+        Ok(word)
+    }
+
+    /// Decodes a scalar of the field of the G1/G2 groups from a word.
+    pub fn fr(self, word: &[u8]) -> Result<Fr, Error> {
+        self.fr_from_word(to_word(word)?)
+    }
+
+    /// Decodes a G1 point.
+    pub fn g1(self, elem: &[Vec<u8>]) -> Result<G1Affine, Error> {
+        if elem.len() != 2 {
+            return Err(anyhow!("Malformed G1 field element"));
+        }
+        self.g1_from_words([to_word(&elem[0])?, to_word(&elem[1])?])
+    }
+
+    /// Decodes a G2 point.
+    pub fn g2(self, elem: &[Vec<Vec<u8>>]) -> Result<G2Affine, Error> {
+        if elem.len() != 2 || elem[0].len() != 2 || elem[1].len() != 2 {
+            return Err(anyhow!("Malformed G2 field element"));
+        }
+        self.g2_from_words([
+            [to_word(&elem[0][0])?, to_word(&elem[0][1])?],
+            [to_word(&elem[1][0])?, to_word(&elem[1][1])?],
+        ])
+    }
+
+    /// Decodes a scalar of the field of the G1/G2 groups from a borrowed
+    /// fixed-size word, without allocating.
+    pub fn fr_from_word(self, word: &[u8; 32]) -> Result<Fr, Error> {
+        let mut buf = *word;
+        self.word_to_arkworks(&mut buf);
+        Fr::deserialize_uncompressed(&buf[..]).map_err(|err| anyhow!(err))
+    }
+
+    /// Decodes a G1 point from borrowed fixed-size words, without allocating.
+    ///
+    /// The words are copied into a stack buffer in the arkworks layout, and
+    /// reversed in place when this encoding is big-endian.
+    pub fn g1_from_words(self, elem: [&[u8; 32]; 2]) -> Result<G1Affine, Error> {
+        let mut buf = [0u8; 64];
+        for (chunk, word) in buf.chunks_exact_mut(32).zip(elem) {
+            chunk.copy_from_slice(word);
+            self.word_to_arkworks(chunk);
+        }
+        G1Affine::deserialize_uncompressed(&buf[..]).map_err(|err| anyhow!(err))
+    }
+
+    /// Decodes a G2 point from borrowed fixed-size words, without allocating.
+    pub fn g2_from_words(self, elem: [[&[u8; 32]; 2]; 2]) -> Result<G2Affine, Error> {
+        let mut buf = [0u8; 128];
+        for (coordinate, words) in buf.chunks_exact_mut(64).zip(elem) {
+            for (chunk, word) in coordinate.chunks_exact_mut(32).zip(words) {
+                chunk.copy_from_slice(word);
+                self.word_to_arkworks(chunk);
+            }
+            if self.is_c1_first() {
+                let (c1, c0) = coordinate.split_at_mut(32);
+                c1.swap_with_slice(c0);
+            }
+        }
+        G2Affine::deserialize_uncompressed(&buf[..]).map_err(|err| anyhow!(err))
+    }
+
+    /// Re-encodes a word from this encoding to `to`.
+    pub fn transcode_word(self, word: &[u8], to: Encoding) -> Vec<u8> {
+        if self.is_big_endian() == to.is_big_endian() {
+            word.to_vec()
+        } else {
+            word.iter().rev().copied().collect()
+        }
+    }
+
+    /// Re-encodes the coordinates of a G1 point from this encoding to `to`,
+    /// without checking that they are a point on the curve.
+    pub fn transcode_g1(self, elem: &[Vec<u8>], to: Encoding) -> Vec<Vec<u8>> {
+        let mut elem: Vec<_> = elem
+            .iter()
+            .map(|word| self.transcode_word(word, Encoding::Arkworks))
+            .collect();
+        if self == Encoding::Arkworks && to != Encoding::Arkworks {
+            clear_flags(elem.last_mut());
+        }
+        Encoding::Arkworks.transcode_words(&elem, to)
+    }
+
+    /// Re-encodes the coordinates of a G2 point from this encoding to `to`,
+    /// without checking that they are a point on the curve.
+    pub fn transcode_g2(self, elem: &[Vec<Vec<u8>>], to: Encoding) -> Vec<Vec<Vec<u8>>> {
+        let mut elem: Vec<Vec<_>> = elem
+            .iter()
+            .map(|coordinate| {
+                let mut coordinate = self.transcode_words(coordinate, Encoding::Arkworks);
+                if self.is_c1_first() {
+                    coordinate.reverse();
+                }
+                coordinate
+            })
+            .collect();
+        if self == Encoding::Arkworks && to != Encoding::Arkworks {
+            clear_flags(elem.last_mut().and_then(|y| y.last_mut()));
+        }
+        elem.into_iter()
+            .map(|mut coordinate| {
+                if to.is_c1_first() {
+                    coordinate.reverse();
+                }
+                Encoding::Arkworks.transcode_words(&coordinate, to)
+            })
+            .collect()
+    }
+
+    fn transcode_words(self, words: &[Vec<u8>], to: Encoding) -> Vec<Vec<u8>> {
+        words
+            .iter()
+            .map(|word| self.transcode_word(word, to))
+            .collect()
+    }
+
+    // Converts a word from this encoding to arkworks in place.
+    fn word_to_arkworks(self, word: &mut [u8]) {
+        if self.is_big_endian() {
+            word.reverse();
+        }
+    }
+
+    fn is_big_endian(self) -> bool {
+        self != Encoding::Arkworks
+    }
+
+    fn is_c1_first(self) -> bool {
+        self == Encoding::Evm
+    }
+}
+
+// Clears the flags stored by arkworks in the most significant bits of a
+// little-endian word.
+fn clear_flags(word: Option<&mut Vec<u8>>) {
+    if let Some(last) = word.and_then(|word| word.last_mut()) {
+        *last &= 0x3f;
+    }
+}
+
+pub(crate) fn to_word(word: &[u8]) -> Result<&[u8; 32], Error> {
+    word.try_into()
+        .map_err(|_| anyhow!("Malformed field element"))
+}
+
+// Encodes a field element as a big-endian word, in the order expected by the
+// EVM.
+pub(crate) fn to_be_word<F: CanonicalSerialize>(value: &F) -> Vec<u8> {
+    let mut word = Vec::with_capacity(32);
+    value
+        .serialize_uncompressed(&mut word)
+        .expect("serializing into a Vec cannot fail");
+    word.reverse();
+    word
+}
+
+fn to_fixed_array(input: Vec<u8>) -> [u8; 32] {
+    let mut fixed_array = [0u8; 32];
+    let start = core::cmp::max(32, input.len()) - core::cmp::min(32, input.len());
+    fixed_array[start..].copy_from_slice(&input[input.len().saturating_sub(32)..]);
+    fixed_array
+}
+
+#[cfg(test)]
+mod tests {
+    use ark_ec::AffineRepr;
+    use ark_serialize::CanonicalSerialize;
+
+    use super::*;
+
+    #[test]
+    fn test_u256() {
+        let mut expected = [0u8; 32];
+        expected[30..].copy_from_slice(&[0x01, 0x02]);
+        assert_eq!(Encoding::Evm.u256("258").unwrap(), expected);
+        assert_eq!(Encoding::Snarkjs.u256("0x0102").unwrap(), expected);
+        expected.reverse();
+        assert_eq!(Encoding::Arkworks.u256("258").unwrap(), expected);
+        assert!(Encoding::Evm.u256("0xzz").is_err());
+    }
+
+    #[test]
+    fn test_g2_roundtrip() {
+        let point = G2Affine::generator();
+        let mut arkworks = Vec::new();
+        point.serialize_uncompressed(&mut arkworks).unwrap();
+        let words: Vec<Vec<Vec<u8>>> = arkworks
+            .chunks(64)
+            .map(|coordinate| coordinate.chunks(32).map(<[u8]>::to_vec).collect())
+            .collect();
+
+        for encoding in [Encoding::Snarkjs, Encoding::Arkworks, Encoding::Evm] {
+            let encoded = Encoding::Arkworks.transcode_g2(&words, encoding);
+            assert_eq!(encoding.g2(&encoded).unwrap(), point);
+            assert_eq!(encoding.transcode_g2(&encoded, Encoding::Arkworks), words);
+        }
+        let evm = Encoding::Arkworks.transcode_g2(&words, Encoding::Evm);
+        let snarkjs = Encoding::Arkworks.transcode_g2(&words, Encoding::Snarkjs);
+        assert_eq!(evm[0][0], snarkjs[0][1]);
+        assert_eq!(evm[1][1], snarkjs[1][0]);
+    }
+}
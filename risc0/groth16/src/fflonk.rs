@@ -0,0 +1,685 @@
+// Copyright 2024 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Verification of snarkjs fflonk proofs over the BN_254 curve.
+//!
+//! fflonk proofs are produced by snarkjs from the same Circom circuits as
+//! Groth16 and PLONK proofs. This module follows the API of the [plonk](crate::plonk)
+//! module: proofs and verifying keys are ingested from the snarkjs JSON
+//! files, a proof can be carried around as a big endian encoded [Seal], and a
+//! [Verifier] checks it against its public inputs.
+
+extern crate alloc;
+
+use alloc::{vec, vec::Vec};
+
+use anyhow::{anyhow, Error, Result};
+use ark_bn254::{Bn254, Fr, G1Affine, G1Projective, G2Affine};
+use ark_ec::{pairing::Pairing, AffineRepr, CurveGroup};
+use ark_ff::{Field, One, Zero};
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    fr_from_bytes, from_u256, g2_from_json,
+    plonk::{g1_from_seal_bytes, g1_to_bytes, Transcript},
+    Encoding, PublicInputsJson,
+};
+
+/// fflonk seal object encoded in big endian.
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+pub struct Seal {
+    /// Commitment to the wire and gate quotient polynomials
+    pub c1: Vec<Vec<u8>>,
+    /// Commitment to the permutation and permutation quotient polynomials
+    pub c2: Vec<Vec<u8>>,
+    /// First opening proof
+    pub w1: Vec<Vec<u8>>,
+    /// Second opening proof
+    pub w2: Vec<Vec<u8>>,
+    /// Evaluations in the order `ql`, `qr`, `qm`, `qo`, `qc`, `s1`, `s2`,
+    /// `s3`, `a`, `b`, `c`, `z`, `zw`, `t1w`, `t2w`, `inv`.
+    pub evals: Vec<Vec<u8>>,
+}
+
+impl Seal {
+    const ELEMENT_SIZE: usize = 32;
+    const G1_GROUP_SIZE: usize = Self::ELEMENT_SIZE * 2;
+    const NUM_COMMITMENTS: usize = 4;
+    const NUM_EVALS: usize = 16;
+    const SIZE: usize =
+        Self::G1_GROUP_SIZE * Self::NUM_COMMITMENTS + Self::ELEMENT_SIZE * Self::NUM_EVALS;
+
+    /// Serialize the fflonk `Seal` into a `Vec<u8>`, laid out as the
+    /// `bytes32[24]` proof argument of the snarkjs fflonk verifier contract.
+    pub fn to_vec(&self) -> Vec<u8> {
+        let mut result = Vec::with_capacity(Self::SIZE);
+        for commitment in [&self.c1, &self.c2, &self.w1, &self.w2] {
+            for item in commitment {
+                result.extend(item);
+            }
+        }
+        for item in &self.evals {
+            result.extend(item);
+        }
+        result
+    }
+
+    /// Method to convert back from a `Vec<u8>`
+    pub fn from_vec(data: &[u8]) -> Result<Seal, Error> {
+        if data.len() != Self::SIZE {
+            return Err(anyhow!("Data length mismatch"));
+        }
+
+        let mut elements = data
+            .chunks_exact(Self::ELEMENT_SIZE)
+            .map(|chunk| chunk.to_vec());
+        let mut g1 = || vec![elements.next().unwrap(), elements.next().unwrap()];
+        let (c1, c2, w1, w2) = (g1(), g1(), g1(), g1());
+        let evals = elements.collect();
+
+        Ok(Seal {
+            c1,
+            c2,
+            w1,
+            w2,
+            evals,
+        })
+    }
+}
+
+impl TryFrom<ProofJson> for Seal {
+    type Error = Error;
+    fn try_from(proof: ProofJson) -> Result<Self, Error> {
+        let polynomials = &proof.polynomials;
+        let e = &proof.evaluations;
+        let evals = [
+            &e.ql, &e.qr, &e.qm, &e.qo, &e.qc, &e.s1, &e.s2, &e.s3, &e.a, &e.b, &e.c, &e.z, &e.zw,
+            &e.t1w, &e.t2w, &e.inv,
+        ]
+        .into_iter()
+        .map(|eval| Encoding::Evm.u256(eval))
+        .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(Seal {
+            c1: g1_to_bytes(&polynomials.c1)?,
+            c2: g1_to_bytes(&polynomials.c2)?,
+            w1: g1_to_bytes(&polynomials.w1)?,
+            w2: g1_to_bytes(&polynomials.w2)?,
+            evals,
+        })
+    }
+}
+
+/// fflonk Proof encoded as JSON.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct ProofJson {
+    polynomials: PolynomialsJson,
+    evaluations: EvaluationsJson,
+    protocol: Option<String>,
+    curve: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+struct PolynomialsJson {
+    #[serde(rename = "C1")]
+    c1: Vec<String>,
+    #[serde(rename = "C2")]
+    c2: Vec<String>,
+    #[serde(rename = "W1")]
+    w1: Vec<String>,
+    #[serde(rename = "W2")]
+    w2: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+struct EvaluationsJson {
+    ql: String,
+    qr: String,
+    qm: String,
+    qo: String,
+    qc: String,
+    s1: String,
+    s2: String,
+    s3: String,
+    a: String,
+    b: String,
+    c: String,
+    z: String,
+    zw: String,
+    t1w: String,
+    t2w: String,
+    inv: String,
+}
+
+/// fflonk Verifying Key encoded as JSON.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct VerifyingKeyJson {
+    protocol: String,
+    curve: String,
+    #[serde(rename = "nPublic")]
+    n_public: u32,
+    power: u32,
+    k1: String,
+    k2: String,
+    w: String,
+    w3: String,
+    w4: String,
+    w8: String,
+    wr: String,
+    #[serde(rename = "X_2")]
+    x_2: Vec<Vec<String>>,
+    #[serde(rename = "C0")]
+    c0: Vec<String>,
+}
+
+impl VerifyingKeyJson {
+    /// Computes the verifying key
+    pub fn verifying_key(&self) -> Result<VerifyingKey, Error> {
+        if self.protocol != "fflonk" {
+            return Err(anyhow!("Unsupported protocol: {}", self.protocol));
+        }
+        let fr = |value: &str| fr_from_bytes(&from_u256(value)?);
+
+        if self.x_2.len() < 2 || self.x_2[0].len() < 2 || self.x_2[1].len() < 2 {
+            return Err(anyhow!("Malformed G2 element field: X_2"));
+        }
+        let x_2 = g2_from_json(&self.x_2)?;
+        let c0 = g1_to_bytes(&self.c0).map_err(|err| anyhow!("{err}: C0"))?;
+
+        let vk = VerifyingKey {
+            n_public: self.n_public,
+            power: self.power,
+            k1: fr(&self.k1)?,
+            k2: fr(&self.k2)?,
+            w: fr(&self.w)?,
+            w3: fr(&self.w3)?,
+            w4: fr(&self.w4)?,
+            w8: fr(&self.w8)?,
+            wr: fr(&self.wr)?,
+            x_2,
+            c0: g1_from_seal_bytes(&c0)?,
+        };
+        vk.check()?;
+        Ok(vk)
+    }
+}
+
+/// fflonk verifying key over the BN_254 curve.
+#[derive(Clone, Debug, PartialEq, CanonicalSerialize, CanonicalDeserialize)]
+pub struct VerifyingKey {
+    /// Number of public inputs
+    pub n_public: u32,
+    /// Base two logarithm of the size of the evaluation domain
+    pub power: u32,
+    /// Coset shift of the right wires
+    pub k1: Fr,
+    /// Coset shift of the output wires
+    pub k2: Fr,
+    /// Generator of the evaluation domain
+    pub w: Fr,
+    /// Primitive third root of unity
+    pub w3: Fr,
+    /// Primitive fourth root of unity
+    pub w4: Fr,
+    /// Primitive eighth root of unity
+    pub w8: Fr,
+    /// Cube root of the domain generator
+    pub wr: Fr,
+    /// Structured reference string element `[x]_2`
+    pub x_2: G2Affine,
+    /// Commitment to the selector and permutation polynomials
+    pub c0: G1Affine,
+}
+
+impl VerifyingKey {
+    // BN_254 scalar field has a two-adicity of 28.
+    const MAX_POWER: u32 = 28;
+
+    fn check(&self) -> Result<(), Error> {
+        if self.power == 0 || self.power > Self::MAX_POWER {
+            return Err(anyhow!("Unsupported domain size: 2^{}", self.power));
+        }
+        // `w` must be a primitive root of unity of order 2^power.
+        let half = (1..self.power).fold(self.w, |w, _| w.square());
+        if half != -Fr::one() {
+            return Err(anyhow!("Malformed domain generator"));
+        }
+        let one = Fr::one();
+        if self.w3 == one || self.w3.pow([3]) != one {
+            return Err(anyhow!("Malformed third root of unity"));
+        }
+        if self.w4.square() != -one {
+            return Err(anyhow!("Malformed fourth root of unity"));
+        }
+        if self.w8.pow([4]) != -one {
+            return Err(anyhow!("Malformed eighth root of unity"));
+        }
+        if self.wr.pow([3]) != self.w {
+            return Err(anyhow!("Malformed cube root of the domain generator"));
+        }
+        Ok(())
+    }
+}
+
+/// fflonk proof over the BN_254 curve.
+#[derive(Clone, Debug, PartialEq, CanonicalSerialize, CanonicalDeserialize)]
+pub struct Proof {
+    /// Commitment to the wire and gate quotient polynomials
+    pub c1: G1Affine,
+    /// Commitment to the permutation and permutation quotient polynomials
+    pub c2: G1Affine,
+    /// First opening proof
+    pub w1: G1Affine,
+    /// Second opening proof
+    pub w2: G1Affine,
+    /// Evaluation of the left selector
+    pub eval_ql: Fr,
+    /// Evaluation of the right selector
+    pub eval_qr: Fr,
+    /// Evaluation of the multiplication selector
+    pub eval_qm: Fr,
+    /// Evaluation of the output selector
+    pub eval_qo: Fr,
+    /// Evaluation of the constant selector
+    pub eval_qc: Fr,
+    /// Evaluation of the first permutation polynomial
+    pub eval_s1: Fr,
+    /// Evaluation of the second permutation polynomial
+    pub eval_s2: Fr,
+    /// Evaluation of the third permutation polynomial
+    pub eval_s3: Fr,
+    /// Evaluation of the left wire polynomial
+    pub eval_a: Fr,
+    /// Evaluation of the right wire polynomial
+    pub eval_b: Fr,
+    /// Evaluation of the output wire polynomial
+    pub eval_c: Fr,
+    /// Evaluation of the permutation accumulator
+    pub eval_z: Fr,
+    /// Evaluation of the permutation accumulator at the shifted challenge
+    pub eval_zw: Fr,
+    /// Evaluation of the first permutation quotient at the shifted challenge
+    pub eval_t1w: Fr,
+    /// Evaluation of the second permutation quotient at the shifted challenge
+    pub eval_t2w: Fr,
+    /// Batch inversion hint used by the verifier contract, unused here
+    pub eval_inv: Fr,
+}
+
+impl TryFrom<&Seal> for Proof {
+    type Error = Error;
+    fn try_from(seal: &Seal) -> Result<Self, Error> {
+        if seal.evals.len() != Seal::NUM_EVALS {
+            return Err(anyhow!("Malformed evaluations"));
+        }
+        let eval = |i: usize| Encoding::Evm.fr(&seal.evals[i]);
+        Ok(Proof {
+            c1: g1_from_seal_bytes(&seal.c1)?,
+            c2: g1_from_seal_bytes(&seal.c2)?,
+            w1: g1_from_seal_bytes(&seal.w1)?,
+            w2: g1_from_seal_bytes(&seal.w2)?,
+            eval_ql: eval(0)?,
+            eval_qr: eval(1)?,
+            eval_qm: eval(2)?,
+            eval_qo: eval(3)?,
+            eval_qc: eval(4)?,
+            eval_s1: eval(5)?,
+            eval_s2: eval(6)?,
+            eval_s3: eval(7)?,
+            eval_a: eval(8)?,
+            eval_b: eval(9)?,
+            eval_c: eval(10)?,
+            eval_z: eval(11)?,
+            eval_zw: eval(12)?,
+            eval_t1w: eval(13)?,
+            eval_t2w: eval(14)?,
+            eval_inv: eval(15)?,
+        })
+    }
+}
+
+/// fflonk `Verifier` instance over the BN_254 curve encoded in little endian.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct Verifier {
+    /// verifying key little endian encoded.
+    pub encoded_vk: Vec<u8>,
+    /// proof little endian encoded.
+    pub encoded_proof: Vec<u8>,
+    /// public inputs little endian encoded.
+    pub encoded_public_inputs: Vec<u8>,
+}
+
+impl Verifier {
+    /// Creates a new fflonk `Verifier` instance.
+    pub fn new(
+        seal: &Seal,
+        public_inputs: Vec<Fr>,
+        verifying_key: VerifyingKey,
+    ) -> Result<Self, Error> {
+        if public_inputs.len() != verifying_key.n_public as usize {
+            return Err(anyhow!(
+                "Expected {} public inputs, got {}",
+                verifying_key.n_public,
+                public_inputs.len()
+            ));
+        }
+
+        let mut encoded_vk = Vec::new();
+        verifying_key
+            .serialize_uncompressed(&mut encoded_vk)
+            .map_err(|err| anyhow!(err))?;
+
+        let mut encoded_proof = Vec::new();
+        Proof::try_from(seal)?
+            .serialize_uncompressed(&mut encoded_proof)
+            .map_err(|err| anyhow!(err))?;
+
+        let mut encoded_public_inputs = Vec::new();
+        public_inputs
+            .serialize_uncompressed(&mut encoded_public_inputs)
+            .map_err(|err| anyhow!(err))?;
+
+        Ok(Self {
+            encoded_vk,
+            encoded_proof,
+            encoded_public_inputs,
+        })
+    }
+
+    pub fn from_json(
+        proof: ProofJson,
+        public_inputs: PublicInputsJson,
+        verifying_key: VerifyingKeyJson,
+    ) -> Result<Self> {
+        Verifier::new(
+            &proof.try_into()?,
+            public_inputs.to_scalar()?,
+            verifying_key.verifying_key()?,
+        )
+    }
+
+    /// Decodes the verifying key.
+    pub fn verifying_key(&self) -> Result<VerifyingKey, Error> {
+        VerifyingKey::deserialize_uncompressed(&*self.encoded_vk).map_err(|err| anyhow!(err))
+    }
+
+    /// Decodes the fflonk proof.
+    pub fn proof(&self) -> Result<Proof, Error> {
+        Proof::deserialize_uncompressed(&*self.encoded_proof).map_err(|err| anyhow!(err))
+    }
+
+    /// Decodes the public inputs as scalars over the field of the G1/G2 groups.
+    pub fn public_inputs(&self) -> Result<Vec<Fr>, Error> {
+        Vec::<Fr>::deserialize_uncompressed(&*self.encoded_public_inputs)
+            .map_err(|err| anyhow!(err))
+    }
+
+    /// Verifies the fflonk proof, following the snarkjs verifier.
+    pub fn verify(&self) -> Result<(), Error> {
+        let vk = self.verifying_key()?;
+        vk.check()?;
+        let proof = self.proof()?;
+        let public_inputs = self.public_inputs()?;
+        if public_inputs.len() != vk.n_public as usize {
+            return Err(anyhow!("Public inputs length mismatch"));
+        }
+
+        let ch = Challenges::new(&vk, &proof, &public_inputs);
+        let inverse = |value: Fr| {
+            value
+                .inverse()
+                .ok_or_else(|| anyhow!("Evaluation challenge in the domain"))
+        };
+
+        // Evaluations of the vanishing polynomial and of the Lagrange basis
+        // polynomials of the public inputs at xi.
+        let xin = (0..vk.power).fold(ch.xi, |x, _| x.square());
+        let zh = xin - Fr::one();
+        let zh_inv = inverse(zh)?;
+        let n = Fr::from(1u64 << vk.power);
+        let mut lagrange = Vec::with_capacity(public_inputs.len().max(1));
+        let mut w = Fr::one();
+        for _ in 0..public_inputs.len().max(1) {
+            lagrange.push(w * zh * inverse(n * (ch.xi - w))?);
+            w *= vk.w;
+        }
+        let l1 = lagrange[0];
+        let pi = public_inputs
+            .iter()
+            .zip(&lagrange)
+            .fold(Fr::zero(), |pi, (input, l)| pi - *input * l);
+
+        // Quotients of the gate and permutation constraints at xi.
+        let t0 = (proof.eval_ql * proof.eval_a
+            + proof.eval_qr * proof.eval_b
+            + proof.eval_qm * proof.eval_a * proof.eval_b
+            + proof.eval_qo * proof.eval_c
+            + proof.eval_qc
+            + pi)
+            * zh_inv;
+        let t1 = (proof.eval_z - Fr::one()) * l1 * zh_inv;
+        let betaxi = ch.beta * ch.xi;
+        let t2 = ((proof.eval_a + betaxi + ch.gamma)
+            * (proof.eval_b + betaxi * vk.k1 + ch.gamma)
+            * (proof.eval_c + betaxi * vk.k2 + ch.gamma)
+            * proof.eval_z
+            - (proof.eval_a + ch.beta * proof.eval_s1 + ch.gamma)
+                * (proof.eval_b + ch.beta * proof.eval_s2 + ch.gamma)
+                * (proof.eval_c + ch.beta * proof.eval_s3 + ch.gamma)
+                * proof.eval_zw)
+            * zh_inv;
+
+        // Evaluations at y of the polynomials interpolating C0, C1 and C2 over
+        // their opening sets.
+        let horner =
+            |evals: &[Fr], h: Fr| evals.iter().rev().fold(Fr::zero(), |acc, e| acc * h + e);
+        let c0 = |h: Fr| {
+            let evals = [
+                proof.eval_ql,
+                proof.eval_qr,
+                proof.eval_qo,
+                proof.eval_qm,
+                proof.eval_qc,
+                proof.eval_s1,
+                proof.eval_s2,
+                proof.eval_s3,
+            ];
+            horner(&evals, h)
+        };
+        let c1 = |h: Fr| horner(&[proof.eval_a, proof.eval_b, proof.eval_c, t0], h);
+        let c2 = |h: Fr| horner(&[proof.eval_z, t1, t2], h);
+        let c2w = |h: Fr| horner(&[proof.eval_zw, proof.eval_t1w, proof.eval_t2w], h);
+
+        let mut r0 = Fr::zero();
+        for (h, l) in ch.s0.iter().zip(lagrange_single(&ch.s0, ch.xi, ch.y)?) {
+            r0 += c0(*h) * l;
+        }
+        let mut r1 = Fr::zero();
+        for (h, l) in ch.s1.iter().zip(lagrange_single(&ch.s1, ch.xi, ch.y)?) {
+            r1 += c1(*h) * l;
+        }
+        let xiw = ch.xi * vk.w;
+        let mut r2 = Fr::zero();
+        let (s2_lagrange, s2w_lagrange) = lagrange_double(&ch.s2, &ch.s2w, ch.xi, xiw, ch.y)?;
+        for (h, l) in ch.s2.iter().zip(s2_lagrange) {
+            r2 += c2(*h) * l;
+        }
+        for (h, l) in ch.s2w.iter().zip(s2w_lagrange) {
+            r2 += c2w(*h) * l;
+        }
+
+        // Vanishing polynomials of the opening sets at y.
+        let vanishing = |roots: &[Fr]| roots.iter().fold(Fr::one(), |acc, h| acc * (ch.y - h));
+        let zt0 = vanishing(&ch.s0);
+        let zt1 = vanishing(&ch.s1);
+        let zt2 = vanishing(&ch.s2) * vanishing(&ch.s2w);
+        let quotient1 = ch.alpha * zt0 * inverse(zt1)?;
+        let quotient2 = ch.alpha.square() * zt0 * inverse(zt2)?;
+
+        let f = proof.c1 * quotient1 + proof.c2 * quotient2 + vk.c0;
+        let e = G1Projective::from(G1Affine::generator()) * (r0 + r1 * quotient1 + r2 * quotient2);
+        let j = proof.w1 * zt0;
+        let a1 = f - e - j + proof.w2 * ch.y;
+
+        let pairing = Bn254::multi_pairing(
+            [(-a1).into_affine(), proof.w2],
+            [G2Affine::generator(), vk.x_2],
+        );
+        match pairing.is_zero() {
+            true => Ok(()),
+            false => Err(anyhow!("Invalid proof")),
+        }
+    }
+}
+
+/// Fiat-Shamir challenges of the snarkjs fflonk protocol, along with the
+/// opening sets derived from them.
+struct Challenges {
+    beta: Fr,
+    gamma: Fr,
+    alpha: Fr,
+    xi: Fr,
+    y: Fr,
+    // Eighth roots of xi, at which C0 is opened.
+    s0: [Fr; 8],
+    // Fourth roots of xi, at which C1 is opened.
+    s1: [Fr; 4],
+    // Cube roots of xi and of xi * w, at which C2 is opened.
+    s2: [Fr; 3],
+    s2w: [Fr; 3],
+}
+
+impl Challenges {
+    fn new(vk: &VerifyingKey, proof: &Proof, public_inputs: &[Fr]) -> Self {
+        let mut transcript = Transcript::default();
+
+        transcript.add_point(&vk.c0);
+        for input in public_inputs {
+            transcript.add_scalar(input);
+        }
+        transcript.add_point(&proof.c1);
+        let beta = transcript.challenge();
+
+        transcript.add_scalar(&beta);
+        let gamma = transcript.challenge();
+
+        transcript.add_scalar(&gamma);
+        transcript.add_point(&proof.c2);
+        let xi_seed = transcript.challenge();
+
+        // xi = xi_seed^24, so that xi_seed^3, xi_seed^6 and xi_seed^8 are
+        // respectively an eighth, a fourth and a cube root of xi.
+        let xi_seed2 = xi_seed.square();
+        let h0 = xi_seed2 * xi_seed;
+        let h1 = h0.square();
+        let h2 = h1 * xi_seed2;
+        let h3 = h2 * vk.wr;
+        let xi = h2.square() * h2;
+        let s0 = coset(h0, vk.w8);
+        let s1 = coset(h1, vk.w4);
+        let s2 = coset(h2, vk.w3);
+        let s2w = coset(h3, vk.w3);
+
+        transcript.add_scalar(&xi_seed);
+        for eval in [
+            proof.eval_ql,
+            proof.eval_qr,
+            proof.eval_qm,
+            proof.eval_qo,
+            proof.eval_qc,
+            proof.eval_s1,
+            proof.eval_s2,
+            proof.eval_s3,
+            proof.eval_a,
+            proof.eval_b,
+            proof.eval_c,
+            proof.eval_z,
+            proof.eval_zw,
+            proof.eval_t1w,
+            proof.eval_t2w,
+        ] {
+            transcript.add_scalar(&eval);
+        }
+        let alpha = transcript.challenge();
+
+        transcript.add_scalar(&alpha);
+        transcript.add_point(&proof.w1);
+        let y = transcript.challenge();
+
+        Self {
+            beta,
+            gamma,
+            alpha,
+            xi,
+            y,
+            s0,
+            s1,
+            s2,
+            s2w,
+        }
+    }
+}
+
+// Computes `[h, h * w, h * w^2, ...]`, where `w` is a primitive N-th root of
+// unity.
+fn coset<const N: usize>(h: Fr, w: Fr) -> [Fr; N] {
+    let mut roots = [h; N];
+    for i in 1..N {
+        roots[i] = roots[i - 1] * w;
+    }
+    roots
+}
+
+// Evaluates at `y` the Lagrange basis over `roots`, the k-th roots of `xi`.
+fn lagrange_single(roots: &[Fr], xi: Fr, y: Fr) -> Result<Vec<Fr>, Error> {
+    let k = roots.len() as u64;
+    let num = y.pow([k]) - xi;
+    roots
+        .iter()
+        .map(|h| {
+            let den = Fr::from(k) * h.pow([k - 1]) * (y - h);
+            Ok(num
+                * den
+                    .inverse()
+                    .ok_or_else(|| anyhow!("Opening challenge in the set"))?)
+        })
+        .collect()
+}
+
+// Evaluates at `y` the Lagrange basis over the union of the cube roots of
+// `xi` and of `xiw`.
+fn lagrange_double(
+    roots: &[Fr; 3],
+    roots_w: &[Fr; 3],
+    xi: Fr,
+    xiw: Fr,
+    y: Fr,
+) -> Result<(Vec<Fr>, Vec<Fr>), Error> {
+    let y3 = y.pow([3]);
+    let num = (y3 - xi) * (y3 - xiw);
+    let basis = |roots: &[Fr; 3], other: Fr| {
+        roots
+            .iter()
+            .map(|h| {
+                let den = Fr::from(3u64) * h.square() * (h.pow([3]) - other) * (y - h);
+                Ok(num
+                    * den
+                        .inverse()
+                        .ok_or_else(|| anyhow!("Opening challenge in the set"))?)
+            })
+            .collect::<Result<Vec<_>, Error>>()
+    };
+    Ok((basis(roots, xiw)?, basis(roots_w, xi)?))
+}
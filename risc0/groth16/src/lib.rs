@@ -13,89 +13,84 @@
 // limitations under the License.
 
 //! Core module used to implement Groth16.
+//!
+//! Verification of snarkjs PLONK and fflonk proofs is provided by the `plonk`
+//! and `fflonk` modules, behind the features of the same names.
 
-use core::str::FromStr;
-
-use anyhow::{anyhow, Error, Result};
+use anyhow::{Error, Result};
 use ark_bn254::{Fr, G1Affine, G2Affine};
-use ark_serialize::CanonicalDeserialize;
-use num_bigint::BigInt;
 use risc0_zkp::core::digest::Digest;
 
+#[cfg(feature = "aggregation")]
+pub mod aggregation;
+#[cfg(feature = "substrate-bn")]
+mod bn;
 pub mod data_structures;
 #[cfg(feature = "prove")]
 pub mod docker;
+mod encoding;
+#[cfg(feature = "fflonk")]
+pub mod fflonk;
+#[cfg(feature = "plonk")]
+pub mod plonk;
+pub mod pvk;
 #[cfg(feature = "prove")]
 mod seal_format;
 #[cfg(feature = "prove")]
 mod seal_to_json;
+#[cfg(feature = "solidity")]
+pub mod solidity;
 pub mod verifier;
 
-pub use data_structures::{ProofJson, PublicInputsJson, Seal, VerifyingKeyJson};
+pub use data_structures::{ProofJson, PublicInputsJson, Seal, SealRef, VerifyingKeyJson};
+pub use encoding::Encoding;
 #[cfg(feature = "prove")]
 pub use seal_to_json::to_json;
-pub use verifier::Verifier;
-
-// Deserialize a scalar field from bytes in big-endian format
-pub(crate) fn fr_from_bytes(scalar: &[u8]) -> Result<Fr, Error> {
-    let scalar: Vec<u8> = scalar.iter().rev().cloned().collect();
-    Fr::deserialize_uncompressed(&*scalar).map_err(|err| anyhow!(err))
-}
+#[cfg(feature = "tokio")]
+pub use verifier::verify_batch_async;
+pub use verifier::{verify_batch, verify_seal, Diagnostics, MalformedInput, Verifier};
 
-// Deserialize an element over the G1 group from bytes in big-endian format
-pub(crate) fn g1_from_bytes(elem: &[Vec<u8>]) -> Result<G1Affine, Error> {
-    if elem.len() != 2 {
-        return Err(anyhow!("Malformed G1 field element"));
-    }
-    let g1_affine: Vec<u8> = elem[0]
-        .iter()
-        .rev()
-        .chain(elem[1].iter().rev())
-        .cloned()
-        .collect();
+/// Version of the encoding of the seals produced by the Groth16 prover.
+///
+/// It is bumped whenever seals of the previous version can no longer be
+/// verified, and is embedded in the seals serialized by
+/// [Seal::to_versioned_bytes], so that mixed-version deployments fail with a
+/// seal version mismatch.
+pub const SEAL_VERSION: u32 = 1;
 
-    G1Affine::deserialize_uncompressed(&*g1_affine).map_err(|err| anyhow!(err))
+// Deserialize a scalar field from a big-endian word
+pub(crate) fn fr_from_bytes(scalar: &[u8; 32]) -> Result<Fr, Error> {
+    Encoding::Evm.fr_from_word(scalar)
 }
 
-// Deserialize an element over the G2 group from bytes in big-endian format
-pub(crate) fn g2_from_bytes(elem: &Vec<Vec<Vec<u8>>>) -> Result<G2Affine, Error> {
-    if elem.len() != 2 || elem[0].len() != 2 || elem[1].len() != 2 {
-        return Err(anyhow!("Malformed G2 field element"));
-    }
-    let g2_affine: Vec<u8> = elem[0][1]
-        .iter()
-        .rev()
-        .chain(elem[0][0].iter().rev())
-        .chain(elem[1][1].iter().rev())
-        .chain(elem[1][0].iter().rev())
-        .cloned()
-        .collect();
+// Deserialize an element over the G1 group from big-endian words
+pub(crate) fn g1_from_bytes(elem: [&[u8; 32]; 2]) -> Result<G1Affine, Error> {
+    Encoding::Evm.g1_from_words(elem)
+}
 
-    G2Affine::deserialize_uncompressed(&*g2_affine).map_err(|err| anyhow!(err))
+// Deserialize an element over the G2 group from big-endian words
+pub(crate) fn g2_from_bytes(elem: [[&[u8; 32]; 2]; 2]) -> Result<G2Affine, Error> {
+    Encoding::Evm.g2_from_words(elem)
 }
 
-// Convert the U256 value to a byte array in big-endian format
-pub(crate) fn from_u256(value: &str) -> Result<Vec<u8>, Error> {
-    let value = if let Some(stripped) = value.strip_prefix("0x") {
-        to_fixed_array(hex::decode(stripped).map_err(|_| anyhow!("conversion from u256 failed"))?)
-            .to_vec()
-    } else {
-        to_fixed_array(
-            BigInt::from_str(value)
-                .map_err(|_| anyhow!("conversion from u256 failed"))?
-                .to_bytes_be()
-                .1,
-        )
-        .to_vec()
-    };
-    Ok(value)
+// Decode a G2 point from the decimal strings of a snarkjs JSON file. The caller
+// checks that `elem` holds two pairs of strings.
+pub(crate) fn g2_from_json(elem: &[Vec<String>]) -> Result<G2Affine, Error> {
+    Encoding::Snarkjs.g2_from_words([
+        [
+            &Encoding::Snarkjs.u256_word(&elem[0][0])?,
+            &Encoding::Snarkjs.u256_word(&elem[0][1])?,
+        ],
+        [
+            &Encoding::Snarkjs.u256_word(&elem[1][0])?,
+            &Encoding::Snarkjs.u256_word(&elem[1][1])?,
+        ],
+    ])
 }
 
-fn to_fixed_array(input: Vec<u8>) -> [u8; 32] {
-    let mut fixed_array = [0u8; 32];
-    let start = core::cmp::max(32, input.len()) - core::cmp::min(32, input.len());
-    fixed_array[start..].copy_from_slice(&input[input.len().saturating_sub(32)..]);
-    fixed_array
+// Convert the U256 value to a word in big-endian format
+pub(crate) fn from_u256(value: &str) -> Result<[u8; 32], Error> {
+    Encoding::Evm.u256_word(value)
 }
 
 /// Splits the digest in half returning a scalar for each halve.
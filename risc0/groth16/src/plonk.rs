@@ -0,0 +1,646 @@
+// Copyright 2024 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Verification of snarkjs PLONK proofs over the BN_254 curve.
+//!
+//! This module mirrors the Groth16 API of this crate: proofs and verifying
+//! keys are ingested from the JSON files produced by snarkjs, a proof can be
+//! carried around as a big endian encoded [Seal], and a [Verifier] checks it
+//! against its public inputs.
+
+extern crate alloc;
+
+use alloc::{vec, vec::Vec};
+
+use anyhow::{anyhow, Error, Result};
+use ark_bn254::{Bn254, Fr, G1Affine, G1Projective, G2Affine};
+use ark_ec::{pairing::Pairing, AffineRepr, CurveGroup};
+use ark_ff::{BigInteger, Field, One, PrimeField, Zero};
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+use serde::{Deserialize, Serialize};
+use sha3::{Digest as _, Keccak256};
+
+use crate::{fr_from_bytes, from_u256, g2_from_json, Encoding, PublicInputsJson};
+
+/// PLONK seal object encoded in big endian.
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+pub struct Seal {
+    /// Commitment to the left wire polynomial
+    pub a: Vec<Vec<u8>>,
+    /// Commitment to the right wire polynomial
+    pub b: Vec<Vec<u8>>,
+    /// Commitment to the output wire polynomial
+    pub c: Vec<Vec<u8>>,
+    /// Commitment to the permutation polynomial
+    pub z: Vec<Vec<u8>>,
+    /// Commitment to the low part of the quotient polynomial
+    pub t1: Vec<Vec<u8>>,
+    /// Commitment to the middle part of the quotient polynomial
+    pub t2: Vec<Vec<u8>>,
+    /// Commitment to the high part of the quotient polynomial
+    pub t3: Vec<Vec<u8>>,
+    /// Opening proof at the evaluation challenge
+    pub wxi: Vec<Vec<u8>>,
+    /// Opening proof at the shifted evaluation challenge
+    pub wxiw: Vec<Vec<u8>>,
+    /// Evaluations of the wire, permutation and sigma polynomials, in the
+    /// order `a`, `b`, `c`, `s1`, `s2`, `zw`.
+    pub evals: Vec<Vec<u8>>,
+}
+
+impl Seal {
+    const ELEMENT_SIZE: usize = 32;
+    const G1_GROUP_SIZE: usize = Self::ELEMENT_SIZE * 2;
+    const NUM_COMMITMENTS: usize = 9;
+    const NUM_EVALS: usize = 6;
+    const SIZE: usize =
+        Self::G1_GROUP_SIZE * Self::NUM_COMMITMENTS + Self::ELEMENT_SIZE * Self::NUM_EVALS;
+
+    fn commitments(&self) -> [&Vec<Vec<u8>>; Self::NUM_COMMITMENTS] {
+        [
+            &self.a, &self.b, &self.c, &self.z, &self.t1, &self.t2, &self.t3, &self.wxi, &self.wxiw,
+        ]
+    }
+
+    /// Serialize the PLONK `Seal` into a `Vec<u8>`, laid out as the
+    /// `uint256[24]` proof argument of the snarkjs PLONK verifier contract.
+    pub fn to_vec(&self) -> Vec<u8> {
+        let mut result = Vec::with_capacity(Self::SIZE);
+        for commitment in self.commitments() {
+            for item in commitment {
+                result.extend(item);
+            }
+        }
+        for item in &self.evals {
+            result.extend(item);
+        }
+        result
+    }
+
+    /// Method to convert back from a `Vec<u8>`
+    pub fn from_vec(data: &[u8]) -> Result<Seal, Error> {
+        if data.len() != Self::SIZE {
+            return Err(anyhow!("Data length mismatch"));
+        }
+
+        let mut elements = data
+            .chunks_exact(Self::ELEMENT_SIZE)
+            .map(|chunk| chunk.to_vec());
+        let mut g1 = || vec![elements.next().unwrap(), elements.next().unwrap()];
+        let (a, b, c, z) = (g1(), g1(), g1(), g1());
+        let (t1, t2, t3) = (g1(), g1(), g1());
+        let (wxi, wxiw) = (g1(), g1());
+        let evals = elements.collect();
+
+        Ok(Seal {
+            a,
+            b,
+            c,
+            z,
+            t1,
+            t2,
+            t3,
+            wxi,
+            wxiw,
+            evals,
+        })
+    }
+}
+
+impl TryFrom<ProofJson> for Seal {
+    type Error = Error;
+    fn try_from(proof: ProofJson) -> Result<Self, Error> {
+        let evals = [
+            &proof.eval_a,
+            &proof.eval_b,
+            &proof.eval_c,
+            &proof.eval_s1,
+            &proof.eval_s2,
+            &proof.eval_zw,
+        ]
+        .into_iter()
+        .map(|eval| Encoding::Evm.u256(eval))
+        .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(Seal {
+            a: g1_to_bytes(&proof.a)?,
+            b: g1_to_bytes(&proof.b)?,
+            c: g1_to_bytes(&proof.c)?,
+            z: g1_to_bytes(&proof.z)?,
+            t1: g1_to_bytes(&proof.t1)?,
+            t2: g1_to_bytes(&proof.t2)?,
+            t3: g1_to_bytes(&proof.t3)?,
+            wxi: g1_to_bytes(&proof.wxi)?,
+            wxiw: g1_to_bytes(&proof.wxiw)?,
+            evals,
+        })
+    }
+}
+
+/// PLONK Proof encoded as JSON.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct ProofJson {
+    #[serde(rename = "A")]
+    a: Vec<String>,
+    #[serde(rename = "B")]
+    b: Vec<String>,
+    #[serde(rename = "C")]
+    c: Vec<String>,
+    #[serde(rename = "Z")]
+    z: Vec<String>,
+    #[serde(rename = "T1")]
+    t1: Vec<String>,
+    #[serde(rename = "T2")]
+    t2: Vec<String>,
+    #[serde(rename = "T3")]
+    t3: Vec<String>,
+    #[serde(rename = "Wxi")]
+    wxi: Vec<String>,
+    #[serde(rename = "Wxiw")]
+    wxiw: Vec<String>,
+    eval_a: String,
+    eval_b: String,
+    eval_c: String,
+    eval_s1: String,
+    eval_s2: String,
+    eval_zw: String,
+    protocol: Option<String>,
+    curve: Option<String>,
+}
+
+/// PLONK Verifying Key encoded as JSON.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct VerifyingKeyJson {
+    protocol: String,
+    curve: String,
+    #[serde(rename = "nPublic")]
+    n_public: u32,
+    power: u32,
+    k1: String,
+    k2: String,
+    #[serde(rename = "Qm")]
+    qm: Vec<String>,
+    #[serde(rename = "Ql")]
+    ql: Vec<String>,
+    #[serde(rename = "Qr")]
+    qr: Vec<String>,
+    #[serde(rename = "Qo")]
+    qo: Vec<String>,
+    #[serde(rename = "Qc")]
+    qc: Vec<String>,
+    #[serde(rename = "S1")]
+    s1: Vec<String>,
+    #[serde(rename = "S2")]
+    s2: Vec<String>,
+    #[serde(rename = "S3")]
+    s3: Vec<String>,
+    #[serde(rename = "X_2")]
+    x_2: Vec<Vec<String>>,
+    w: String,
+}
+
+impl VerifyingKeyJson {
+    /// Computes the verifying key
+    pub fn verifying_key(&self) -> Result<VerifyingKey, Error> {
+        if self.protocol != "plonk" {
+            return Err(anyhow!("Unsupported protocol: {}", self.protocol));
+        }
+        let g1 = |name: &str, elem: &[String]| {
+            let elem = g1_to_bytes(elem).map_err(|err| anyhow!("{err}: {name}"))?;
+            g1_from_seal_bytes(&elem)
+        };
+        let fr = |value: &str| fr_from_bytes(&from_u256(value)?);
+
+        if self.x_2.len() < 2 || self.x_2[0].len() < 2 || self.x_2[1].len() < 2 {
+            return Err(anyhow!("Malformed G2 element field: X_2"));
+        }
+        let x_2 = g2_from_json(&self.x_2)?;
+
+        let vk = VerifyingKey {
+            n_public: self.n_public,
+            power: self.power,
+            k1: fr(&self.k1)?,
+            k2: fr(&self.k2)?,
+            qm: g1("Qm", &self.qm)?,
+            ql: g1("Ql", &self.ql)?,
+            qr: g1("Qr", &self.qr)?,
+            qo: g1("Qo", &self.qo)?,
+            qc: g1("Qc", &self.qc)?,
+            s1: g1("S1", &self.s1)?,
+            s2: g1("S2", &self.s2)?,
+            s3: g1("S3", &self.s3)?,
+            x_2,
+            w: fr(&self.w)?,
+        };
+        vk.check()?;
+        Ok(vk)
+    }
+}
+
+/// PLONK verifying key over the BN_254 curve.
+#[derive(Clone, Debug, PartialEq, CanonicalSerialize, CanonicalDeserialize)]
+pub struct VerifyingKey {
+    /// Number of public inputs
+    pub n_public: u32,
+    /// Base two logarithm of the size of the evaluation domain
+    pub power: u32,
+    /// Coset shift of the right wires
+    pub k1: Fr,
+    /// Coset shift of the output wires
+    pub k2: Fr,
+    /// Commitment to the multiplication selector
+    pub qm: G1Affine,
+    /// Commitment to the left selector
+    pub ql: G1Affine,
+    /// Commitment to the right selector
+    pub qr: G1Affine,
+    /// Commitment to the output selector
+    pub qo: G1Affine,
+    /// Commitment to the constant selector
+    pub qc: G1Affine,
+    /// Commitment to the first permutation polynomial
+    pub s1: G1Affine,
+    /// Commitment to the second permutation polynomial
+    pub s2: G1Affine,
+    /// Commitment to the third permutation polynomial
+    pub s3: G1Affine,
+    /// Structured reference string element `[x]_2`
+    pub x_2: G2Affine,
+    /// Generator of the evaluation domain
+    pub w: Fr,
+}
+
+impl VerifyingKey {
+    // BN_254 scalar field has a two-adicity of 28.
+    const MAX_POWER: u32 = 28;
+
+    fn check(&self) -> Result<(), Error> {
+        if self.power == 0 || self.power > Self::MAX_POWER {
+            return Err(anyhow!("Unsupported domain size: 2^{}", self.power));
+        }
+        // `w` must be a primitive root of unity of order 2^power.
+        let half = (1..self.power).fold(self.w, |w, _| w.square());
+        if half != -Fr::one() {
+            return Err(anyhow!("Malformed domain generator"));
+        }
+        Ok(())
+    }
+}
+
+/// PLONK proof over the BN_254 curve.
+#[derive(Clone, Debug, PartialEq, CanonicalSerialize, CanonicalDeserialize)]
+pub struct Proof {
+    /// Commitment to the left wire polynomial
+    pub a: G1Affine,
+    /// Commitment to the right wire polynomial
+    pub b: G1Affine,
+    /// Commitment to the output wire polynomial
+    pub c: G1Affine,
+    /// Commitment to the permutation polynomial
+    pub z: G1Affine,
+    /// Commitment to the low part of the quotient polynomial
+    pub t1: G1Affine,
+    /// Commitment to the middle part of the quotient polynomial
+    pub t2: G1Affine,
+    /// Commitment to the high part of the quotient polynomial
+    pub t3: G1Affine,
+    /// Opening proof at the evaluation challenge
+    pub wxi: G1Affine,
+    /// Opening proof at the shifted evaluation challenge
+    pub wxiw: G1Affine,
+    /// Evaluation of the left wire polynomial
+    pub eval_a: Fr,
+    /// Evaluation of the right wire polynomial
+    pub eval_b: Fr,
+    /// Evaluation of the output wire polynomial
+    pub eval_c: Fr,
+    /// Evaluation of the first permutation polynomial
+    pub eval_s1: Fr,
+    /// Evaluation of the second permutation polynomial
+    pub eval_s2: Fr,
+    /// Evaluation of the permutation accumulator at the shifted challenge
+    pub eval_zw: Fr,
+}
+
+impl TryFrom<&Seal> for Proof {
+    type Error = Error;
+    fn try_from(seal: &Seal) -> Result<Self, Error> {
+        if seal.evals.len() != Seal::NUM_EVALS {
+            return Err(anyhow!("Malformed evaluations"));
+        }
+        let eval = |i: usize| Encoding::Evm.fr(&seal.evals[i]);
+        Ok(Proof {
+            a: g1_from_seal_bytes(&seal.a)?,
+            b: g1_from_seal_bytes(&seal.b)?,
+            c: g1_from_seal_bytes(&seal.c)?,
+            z: g1_from_seal_bytes(&seal.z)?,
+            t1: g1_from_seal_bytes(&seal.t1)?,
+            t2: g1_from_seal_bytes(&seal.t2)?,
+            t3: g1_from_seal_bytes(&seal.t3)?,
+            wxi: g1_from_seal_bytes(&seal.wxi)?,
+            wxiw: g1_from_seal_bytes(&seal.wxiw)?,
+            eval_a: eval(0)?,
+            eval_b: eval(1)?,
+            eval_c: eval(2)?,
+            eval_s1: eval(3)?,
+            eval_s2: eval(4)?,
+            eval_zw: eval(5)?,
+        })
+    }
+}
+
+/// PLONK `Verifier` instance over the BN_254 curve encoded in little endian.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct Verifier {
+    /// verifying key little endian encoded.
+    pub encoded_vk: Vec<u8>,
+    /// proof little endian encoded.
+    pub encoded_proof: Vec<u8>,
+    /// public inputs little endian encoded.
+    pub encoded_public_inputs: Vec<u8>,
+}
+
+impl Verifier {
+    /// Creates a new PLONK `Verifier` instance.
+    pub fn new(
+        seal: &Seal,
+        public_inputs: Vec<Fr>,
+        verifying_key: VerifyingKey,
+    ) -> Result<Self, Error> {
+        if public_inputs.len() != verifying_key.n_public as usize {
+            return Err(anyhow!(
+                "Expected {} public inputs, got {}",
+                verifying_key.n_public,
+                public_inputs.len()
+            ));
+        }
+
+        let mut encoded_vk = Vec::new();
+        verifying_key
+            .serialize_uncompressed(&mut encoded_vk)
+            .map_err(|err| anyhow!(err))?;
+
+        let mut encoded_proof = Vec::new();
+        Proof::try_from(seal)?
+            .serialize_uncompressed(&mut encoded_proof)
+            .map_err(|err| anyhow!(err))?;
+
+        let mut encoded_public_inputs = Vec::new();
+        public_inputs
+            .serialize_uncompressed(&mut encoded_public_inputs)
+            .map_err(|err| anyhow!(err))?;
+
+        Ok(Self {
+            encoded_vk,
+            encoded_proof,
+            encoded_public_inputs,
+        })
+    }
+
+    pub fn from_json(
+        proof: ProofJson,
+        public_inputs: PublicInputsJson,
+        verifying_key: VerifyingKeyJson,
+    ) -> Result<Self> {
+        Verifier::new(
+            &proof.try_into()?,
+            public_inputs.to_scalar()?,
+            verifying_key.verifying_key()?,
+        )
+    }
+
+    /// Decodes the verifying key.
+    pub fn verifying_key(&self) -> Result<VerifyingKey, Error> {
+        VerifyingKey::deserialize_uncompressed(&*self.encoded_vk).map_err(|err| anyhow!(err))
+    }
+
+    /// Decodes the PLONK proof.
+    pub fn proof(&self) -> Result<Proof, Error> {
+        Proof::deserialize_uncompressed(&*self.encoded_proof).map_err(|err| anyhow!(err))
+    }
+
+    /// Decodes the public inputs as scalars over the field of the G1/G2 groups.
+    pub fn public_inputs(&self) -> Result<Vec<Fr>, Error> {
+        Vec::<Fr>::deserialize_uncompressed(&*self.encoded_public_inputs)
+            .map_err(|err| anyhow!(err))
+    }
+
+    /// Verifies the PLONK proof, following the snarkjs verifier.
+    pub fn verify(&self) -> Result<(), Error> {
+        let vk = self.verifying_key()?;
+        vk.check()?;
+        let proof = self.proof()?;
+        let public_inputs = self.public_inputs()?;
+        if public_inputs.len() != vk.n_public as usize {
+            return Err(anyhow!("Public inputs length mismatch"));
+        }
+
+        let ch = Challenges::new(&vk, &proof, &public_inputs);
+
+        // Evaluations of the vanishing polynomial and of the Lagrange basis
+        // polynomials of the public inputs at xi.
+        let xin = (0..vk.power).fold(ch.xi, |x, _| x.square());
+        let zh = xin - Fr::one();
+        let n = Fr::from(1u64 << vk.power);
+        let mut lagrange = Vec::with_capacity(public_inputs.len().max(1));
+        let mut w = Fr::one();
+        for _ in 0..public_inputs.len().max(1) {
+            let denom = (n * (ch.xi - w))
+                .inverse()
+                .ok_or_else(|| anyhow!("Evaluation challenge in the domain"))?;
+            lagrange.push(w * zh * denom);
+            w *= vk.w;
+        }
+        let l1 = lagrange[0];
+        let pi = public_inputs
+            .iter()
+            .zip(&lagrange)
+            .fold(Fr::zero(), |pi, (input, l)| pi - *input * l);
+
+        let alpha2 = ch.alpha.square();
+        let e3a = proof.eval_a + ch.beta * proof.eval_s1 + ch.gamma;
+        let e3b = proof.eval_b + ch.beta * proof.eval_s2 + ch.gamma;
+        let e3c = proof.eval_c + ch.gamma;
+        let r0 = pi - l1 * alpha2 - e3a * e3b * e3c * proof.eval_zw * ch.alpha;
+
+        // Linearization commitment.
+        let d1 = vk.qm * (proof.eval_a * proof.eval_b)
+            + vk.ql * proof.eval_a
+            + vk.qr * proof.eval_b
+            + vk.qo * proof.eval_c
+            + vk.qc;
+        let betaxi = ch.beta * ch.xi;
+        let d2a = (proof.eval_a + betaxi + ch.gamma)
+            * (proof.eval_b + betaxi * vk.k1 + ch.gamma)
+            * (proof.eval_c + betaxi * vk.k2 + ch.gamma)
+            * ch.alpha;
+        let d2 = proof.z * (d2a + l1 * alpha2 + ch.u);
+        let d3 = vk.s3 * (e3a * e3b * ch.alpha * ch.beta * proof.eval_zw);
+        let d4 = (proof.t3 * xin.square() + proof.t2 * xin + proof.t1) * zh;
+        let d = d1 + d2 - d3 - d4;
+
+        let [v1, v2, v3, v4, v5] = ch.v;
+        let f = d + proof.a * v1 + proof.b * v2 + proof.c * v3 + vk.s1 * v4 + vk.s2 * v5;
+        let e = G1Projective::from(G1Affine::generator())
+            * (-r0
+                + v1 * proof.eval_a
+                + v2 * proof.eval_b
+                + v3 * proof.eval_c
+                + v4 * proof.eval_s1
+                + v5 * proof.eval_s2
+                + ch.u * proof.eval_zw);
+
+        let a1 = proof.wxiw * ch.u + proof.wxi;
+        let b1 = proof.wxi * ch.xi + proof.wxiw * (ch.u * ch.xi * vk.w) + f - e;
+
+        let pairing = Bn254::multi_pairing(
+            [(-a1).into_affine(), b1.into_affine()],
+            [vk.x_2, G2Affine::generator()],
+        );
+        match pairing.is_zero() {
+            true => Ok(()),
+            false => Err(anyhow!("Invalid proof")),
+        }
+    }
+}
+
+/// Fiat-Shamir challenges of the snarkjs PLONK protocol.
+struct Challenges {
+    beta: Fr,
+    gamma: Fr,
+    alpha: Fr,
+    xi: Fr,
+    v: [Fr; 5],
+    u: Fr,
+}
+
+impl Challenges {
+    fn new(vk: &VerifyingKey, proof: &Proof, public_inputs: &[Fr]) -> Self {
+        let mut transcript = Transcript::default();
+
+        for point in [vk.qm, vk.ql, vk.qr, vk.qo, vk.qc, vk.s1, vk.s2, vk.s3] {
+            transcript.add_point(&point);
+        }
+        for input in public_inputs {
+            transcript.add_scalar(input);
+        }
+        for point in [proof.a, proof.b, proof.c] {
+            transcript.add_point(&point);
+        }
+        let beta = transcript.challenge();
+
+        transcript.add_scalar(&beta);
+        let gamma = transcript.challenge();
+
+        transcript.add_scalar(&beta);
+        transcript.add_scalar(&gamma);
+        transcript.add_point(&proof.z);
+        let alpha = transcript.challenge();
+
+        transcript.add_scalar(&alpha);
+        for point in [proof.t1, proof.t2, proof.t3] {
+            transcript.add_point(&point);
+        }
+        let xi = transcript.challenge();
+
+        transcript.add_scalar(&xi);
+        for eval in [
+            proof.eval_a,
+            proof.eval_b,
+            proof.eval_c,
+            proof.eval_s1,
+            proof.eval_s2,
+            proof.eval_zw,
+        ] {
+            transcript.add_scalar(&eval);
+        }
+        let v1 = transcript.challenge();
+        let v2 = v1 * v1;
+        let v3 = v2 * v1;
+        let v4 = v3 * v1;
+        let v5 = v4 * v1;
+
+        transcript.add_point(&proof.wxi);
+        transcript.add_point(&proof.wxiw);
+        let u = transcript.challenge();
+
+        Self {
+            beta,
+            gamma,
+            alpha,
+            xi,
+            v: [v1, v2, v3, v4, v5],
+            u,
+        }
+    }
+}
+
+/// Keccak-256 transcript, matching the one used by snarkjs.
+#[derive(Default)]
+pub(crate) struct Transcript(Vec<u8>);
+
+impl Transcript {
+    pub(crate) fn add_point(&mut self, point: &G1Affine) {
+        match point.xy() {
+            Some((x, y)) => {
+                self.0.extend(to_bytes_be(*x));
+                self.0.extend(to_bytes_be(*y));
+            }
+            None => self.0.extend([0u8; 64]),
+        }
+    }
+
+    pub(crate) fn add_scalar(&mut self, scalar: &Fr) {
+        self.0.extend(to_bytes_be(*scalar));
+    }
+
+    // Hashes the transcript into a challenge and resets it.
+    pub(crate) fn challenge(&mut self) -> Fr {
+        let hash = Keccak256::digest(&self.0);
+        self.0.clear();
+        Fr::from_be_bytes_mod_order(&hash)
+    }
+}
+
+// Converts a G1 element in snarkjs JSON format to big endian bytes. The point
+// at infinity, which snarkjs encodes with a zero `z` coordinate, is mapped to
+// `(0, 0)` as done by the EVM precompiles.
+pub(crate) fn g1_to_bytes(elem: &[String]) -> Result<Vec<Vec<u8>>, Error> {
+    if elem.len() < 2 {
+        return Err(anyhow!("Malformed G1 element field"));
+    }
+    if elem.get(2).is_some_and(|z| z == "0") {
+        return Ok(vec![vec![0u8; 32], vec![0u8; 32]]);
+    }
+    Ok(vec![
+        Encoding::Evm.u256(&elem[0])?,
+        Encoding::Evm.u256(&elem[1])?,
+    ])
+}
+
+// Deserialize an element over the G1 group from bytes in big-endian format,
+// where `(0, 0)` is the point at infinity.
+pub(crate) fn g1_from_seal_bytes(elem: &[Vec<u8>]) -> Result<G1Affine, Error> {
+    if elem.len() == 2 && elem.iter().flatten().all(|byte| *byte == 0) {
+        return Ok(G1Affine::zero());
+    }
+    Encoding::Evm.g1(elem)
+}
+
+fn to_bytes_be<F: PrimeField>(value: F) -> Vec<u8> {
+    let bytes = value.into_bigint().to_bytes_be();
+    let mut result = vec![0u8; 32 - bytes.len()];
+    result.extend(bytes);
+    result
+}
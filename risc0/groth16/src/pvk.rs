@@ -0,0 +1,200 @@
+// Copyright 2024 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Registry of verification parameters for each version of the recursion
+//! circuit, allowing seals produced by older versions to be verified after an
+//! upgrade.
+//!
+//! The parameters of the releases listed in [CircuitVersion] are embedded in
+//! this crate, and returned by [for_version].
+
+extern crate alloc;
+
+use alloc::{collections::BTreeMap, string::String, vec::Vec};
+use core::fmt;
+
+use anyhow::{anyhow, Error, Result};
+use ark_bn254::{Bn254, Fr};
+use ark_groth16::PreparedVerifyingKey;
+use hex::FromHex;
+use risc0_zkp::core::digest::Digest;
+
+use crate::{fr_from_hex_string, verifier::prepared_verifying_key};
+
+// Constants from: risc0-circuit-recursion/src/control_id.rs
+// Update them along with the verifying key of the current release;
+// `test_embedded_keys` checks that they match the recursion circuit.
+const ALLOWED_IDS_ROOT: &str = "88c1f749250aba181168c33839d7a351671e7a5b7f3e746dde91ef6c6e9ef344";
+const BN254_CONTROL_ID: &str = "10ff834dbef62ccbba201ecd26a772e3036a075aacbaf47200679a11dcdcf10d";
+
+/// Release of the recursion circuit whose verification parameters are embedded
+/// in this crate.
+///
+/// Variants are added as new circuits are released, and removed once the
+/// seals they produce are no longer supported.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[non_exhaustive]
+pub enum CircuitVersion {
+    /// The recursion circuit of the 0.21 release.
+    V0_21,
+    /// The recursion circuit of the 0.22 release.
+    V0_22,
+}
+
+impl CircuitVersion {
+    /// The version of the circuit used by the current release.
+    pub const CURRENT: Self = Self::V0_22;
+
+    /// All versions with embedded parameters, from oldest to newest.
+    pub const ALL: &'static [Self] = &[Self::V0_21, Self::V0_22];
+
+    /// Returns the identifier of this version, as registered by
+    /// [Registry::embedded].
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::V0_21 => "0.21",
+            Self::V0_22 => "0.22",
+        }
+    }
+}
+
+impl fmt::Display for CircuitVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Returns the embedded verification parameters of `version`.
+pub fn for_version(version: CircuitVersion) -> Result<VersionedKey, Error> {
+    match version {
+        // Both releases use the key of the same trusted setup, and the same
+        // recursion programs.
+        CircuitVersion::V0_21 | CircuitVersion::V0_22 => Ok(VersionedKey {
+            pvk: prepared_verifying_key()?,
+            allowed_ids_root: Digest::from_hex(ALLOWED_IDS_ROOT).map_err(|err| anyhow!(err))?,
+            bn254_control_id: fr_from_hex_string(BN254_CONTROL_ID)?,
+        }),
+    }
+}
+
+/// Parameters needed to verify a Groth16 seal produced by a specific version
+/// of the recursion circuit.
+#[derive(Clone, Debug)]
+pub struct VersionedKey {
+    /// Prepared verifying key of the Groth16 circuit.
+    pub pvk: PreparedVerifyingKey<Bn254>,
+    /// Merkle root of the recursion control IDs allowed by the circuit.
+    pub allowed_ids_root: Digest,
+    /// Control ID of the BN254 identity recursion program.
+    pub bn254_control_id: Fr,
+}
+
+/// Maps version identifiers to [VersionedKey] parameters.
+#[derive(Clone, Debug, Default)]
+pub struct Registry {
+    keys: BTreeMap<String, VersionedKey>,
+}
+
+impl Registry {
+    /// Creates an empty `Registry`.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates a `Registry` holding the embedded parameters of every
+    /// [CircuitVersion], registered under [CircuitVersion::as_str].
+    pub fn embedded() -> Result<Self, Error> {
+        let mut registry = Self::new();
+        for &version in CircuitVersion::ALL {
+            registry.insert(version.as_str(), for_version(version)?);
+        }
+        Ok(registry)
+    }
+
+    /// Registers the parameters for `version`, returning the parameters
+    /// previously registered for it, if any.
+    pub fn insert(
+        &mut self,
+        version: impl Into<String>,
+        key: VersionedKey,
+    ) -> Option<VersionedKey> {
+        self.keys.insert(version.into(), key)
+    }
+
+    /// Returns the parameters registered for `version`.
+    pub fn get(&self, version: &str) -> Result<&VersionedKey, Error> {
+        self.keys
+            .get(version)
+            .ok_or_else(|| anyhow!("No verifying key registered for version {version}"))
+    }
+
+    /// Iterates over the registered versions, in lexicographic order.
+    pub fn versions(&self) -> impl Iterator<Item = &str> {
+        self.keys.keys().map(String::as_str)
+    }
+}
+
+/// Set of allowed control ID roots, each the Merkle root of the recursion
+/// control IDs of a zkVM release.
+///
+/// Verifying a seal against a set of roots accepts proofs produced under any
+/// of these releases, e.g. during a migration window.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct AllowedRoots {
+    roots: Vec<Digest>,
+}
+
+impl AllowedRoots {
+    /// Creates an empty set of roots.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Appends `root` to the set, unless it is already present.
+    pub fn with_root(mut self, root: Digest) -> Self {
+        self.insert(root);
+        self
+    }
+
+    /// Adds `root` to the set, returning whether it was not present.
+    pub fn insert(&mut self, root: Digest) -> bool {
+        if self.contains(&root) {
+            return false;
+        }
+        self.roots.push(root);
+        true
+    }
+
+    /// Checks whether `root` is allowed.
+    pub fn contains(&self, root: &Digest) -> bool {
+        self.roots.contains(root)
+    }
+
+    /// Iterates over the roots, in insertion order.
+    pub fn iter(&self) -> impl Iterator<Item = &Digest> {
+        self.roots.iter()
+    }
+}
+
+impl From<Digest> for AllowedRoots {
+    fn from(root: Digest) -> Self {
+        Self::new().with_root(root)
+    }
+}
+
+impl FromIterator<Digest> for AllowedRoots {
+    fn from_iter<I: IntoIterator<Item = Digest>>(iter: I) -> Self {
+        iter.into_iter().fold(Self::new(), Self::with_root)
+    }
+}
@@ -22,12 +22,15 @@ use risc0_zkp::core::{
     digest::{Digest, DIGEST_WORDS},
     hash::poseidon_254::digest_to_fr,
 };
+use zeroize::Zeroizing;
 
 use crate::seal_format::{IopType, K_SEAL_ELEMS, K_SEAL_TYPES, K_SEAL_WORDS};
 
 /// Convert a seal into a JSON format compatible with the `stark_verify` witness generator.
+///
+/// The copy of the seal read from `reader` is zeroized once converted.
 pub fn to_json<R: Read, W: Write>(mut reader: R, mut writer: W) -> Result<()> {
-    let mut iop = vec![0u32; K_SEAL_WORDS];
+    let mut iop = Zeroizing::new(vec![0u32; K_SEAL_WORDS]);
     reader.read_exact(bytemuck::cast_slice_mut(&mut iop))?;
 
     writeln!(writer, "{{\n  \"iop\" : [")?;
@@ -0,0 +1,326 @@
+// Copyright 2024 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Rendering of Solidity verifier contracts from Groth16 verifying keys.
+//!
+//! The contract rendered by [verifier_contract] is the one produced by
+//! `snarkjs zkey export solidityverifier`, so that a verifying key can be
+//! deployed on chain from the same [VerifyingKey] used to verify seals off
+//! chain. [verifying_key_constants] renders only the constants block of that
+//! contract, and [control_id_library] the public inputs that are fixed for a
+//! version of the recursion circuit. [AGGREGATION_LIBRARY] settles the claims
+//! of an aggregated proof.
+
+extern crate alloc;
+
+use alloc::{format, string::String};
+use core::fmt::Write;
+
+use ark_bn254::{Bn254, Fq, Fq2, Fr, G1Affine, G2Affine};
+use ark_ff::{BigInteger, PrimeField};
+use ark_groth16::VerifyingKey;
+
+use crate::{pvk::VersionedKey, split_digest};
+
+const HEADER: &str = r#"// SPDX-License-Identifier: GPL-3.0
+/*
+    Copyright 2021 0KIMS association.
+
+    This file is generated with [snarkJS](https://github.com/iden3/snarkjs).
+
+    snarkJS is a free software: you can redistribute it and/or modify it
+    under the terms of the GNU General Public License as published by
+    the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    snarkJS is distributed in the hope that it will be useful, but WITHOUT
+    ANY WARRANTY; without even the implied warranty of MERCHANTABILITY
+    or FITNESS FOR A PARTICULAR PURPOSE. See the GNU General Public
+    License for more details.
+
+    You should have received a copy of the GNU General Public License
+    along with snarkJS. If not, see <https://www.gnu.org/licenses/>.
+*/
+
+pragma solidity >=0.7.0 <0.9.0;
+
+contract Groth16Verifier {
+    // Scalar field size
+    uint256 constant r =
+        21888242871839275222246405745257275088548364400416034343698204186575808495617;
+    // Base field size
+    uint256 constant q =
+        21888242871839275222246405745257275088696311157297823662689037894645226208583;
+
+    // Verification Key data
+"#;
+
+const MEMORY: &str = r#"    // Memory data
+    uint16 constant pVk = 0;
+    uint16 constant pPairing = 128;
+
+    uint16 constant pLastMem = 896;
+
+    function verifyProof(
+        uint[2] calldata _pA,
+        uint[2][2] calldata _pB,
+        uint[2] calldata _pC,
+"#;
+
+const PAIRING_START: &str = r#"    ) public view returns (bool) {
+        assembly {
+            function checkField(v) {
+                if iszero(lt(v, q)) {
+                    mstore(0, 0)
+                    return(0, 0x20)
+                }
+            }
+
+            // G1 function to multiply a G1 value(x,y) to value in an address
+            function g1_mulAccC(pR, x, y, s) {
+                let success
+                let mIn := mload(0x40)
+                mstore(mIn, x)
+                mstore(add(mIn, 32), y)
+                mstore(add(mIn, 64), s)
+
+                success := staticcall(sub(gas(), 2000), 7, mIn, 96, mIn, 64)
+
+                if iszero(success) {
+                    mstore(0, 0)
+                    return(0, 0x20)
+                }
+
+                mstore(add(mIn, 64), mload(pR))
+                mstore(add(mIn, 96), mload(add(pR, 32)))
+
+                success := staticcall(sub(gas(), 2000), 6, mIn, 128, pR, 64)
+
+                if iszero(success) {
+                    mstore(0, 0)
+                    return(0, 0x20)
+                }
+            }
+
+            function checkPairing(pA, pB, pC, pubSignals, pMem) -> isOk {
+                let _pPairing := add(pMem, pPairing)
+                let _pVk := add(pMem, pVk)
+
+                mstore(_pVk, IC0x)
+                mstore(add(_pVk, 32), IC0y)
+
+                // Compute the linear combination vk_x
+
+"#;
+
+const PAIRING_END: &str = r#"                // -A
+                mstore(_pPairing, calldataload(pA))
+                mstore(
+                    add(_pPairing, 32),
+                    mod(sub(q, calldataload(add(pA, 32))), q)
+                )
+
+                // B
+                mstore(add(_pPairing, 64), calldataload(pB))
+                mstore(add(_pPairing, 96), calldataload(add(pB, 32)))
+                mstore(add(_pPairing, 128), calldataload(add(pB, 64)))
+                mstore(add(_pPairing, 160), calldataload(add(pB, 96)))
+
+                // alpha1
+                mstore(add(_pPairing, 192), alphax)
+                mstore(add(_pPairing, 224), alphay)
+
+                // beta2
+                mstore(add(_pPairing, 256), betax1)
+                mstore(add(_pPairing, 288), betax2)
+                mstore(add(_pPairing, 320), betay1)
+                mstore(add(_pPairing, 352), betay2)
+
+                // vk_x
+                mstore(add(_pPairing, 384), mload(add(pMem, pVk)))
+                mstore(add(_pPairing, 416), mload(add(pMem, add(pVk, 32))))
+
+                // gamma2
+                mstore(add(_pPairing, 448), gammax1)
+                mstore(add(_pPairing, 480), gammax2)
+                mstore(add(_pPairing, 512), gammay1)
+                mstore(add(_pPairing, 544), gammay2)
+
+                // C
+                mstore(add(_pPairing, 576), calldataload(pC))
+                mstore(add(_pPairing, 608), calldataload(add(pC, 32)))
+
+                // delta2
+                mstore(add(_pPairing, 640), deltax1)
+                mstore(add(_pPairing, 672), deltax2)
+                mstore(add(_pPairing, 704), deltay1)
+                mstore(add(_pPairing, 736), deltay2)
+
+                let success := staticcall(
+                    sub(gas(), 2000),
+                    8,
+                    _pPairing,
+                    768,
+                    _pPairing,
+                    0x20
+                )
+
+                isOk := and(success, mload(_pPairing))
+            }
+
+            let pMem := mload(0x40)
+            mstore(0x40, add(pMem, pLastMem))
+
+            // Validate that all evaluations ∈ F
+
+"#;
+
+const FOOTER: &str = r#"            // Validate all evaluations
+            let isValid := checkPairing(_pA, _pB, _pC, _pubSignals, pMem)
+
+            mstore(0, isValid)
+            return(0, 0x20)
+        }
+    }
+}
+"#;
+
+/// Solidity library settling a claim included in an
+/// [AggregationTree](crate::aggregation::AggregationTree).
+///
+/// `hash` computes the leaf of [Claim::leaf](crate::aggregation::Claim::leaf),
+/// and `verify` checks an [InclusionProof](crate::aggregation::InclusionProof)
+/// against the root committed to by the aggregated proof.
+pub const AGGREGATION_LIBRARY: &str = r#"pragma solidity ^0.8.20;
+
+import {MerkleProof} from "@openzeppelin/contracts/utils/cryptography/MerkleProof.sol";
+
+library AggregationLeaf {
+    function hash(bytes32 imageId, bytes32 journalDigest) internal pure returns (bytes32) {
+        return keccak256(bytes.concat(keccak256(abi.encode(imageId, journalDigest))));
+    }
+
+    function verify(bytes32[] calldata proof, bytes32 root, bytes32 imageId, bytes32 journalDigest)
+        internal
+        pure
+        returns (bool)
+    {
+        return MerkleProof.verifyCalldata(proof, root, hash(imageId, journalDigest));
+    }
+}
+"#;
+
+/// Renders a Solidity contract verifying Groth16 proofs for `vk`.
+///
+/// The contract exposes `verifyProof(uint[2], uint[2][2], uint[2], uint[N])`,
+/// taking the points of a [Seal](crate::Seal) and the `N` public inputs of the
+/// key. For the keys of the recursion circuit, the public inputs are laid out
+/// as described in [control_id_library].
+pub fn verifier_contract(vk: &VerifyingKey<Bn254>) -> String {
+    let num_inputs = vk.gamma_abc_g1.len().saturating_sub(1);
+    let mut contract = String::from(HEADER);
+    contract.push_str(&verifying_key_constants(vk));
+    contract.push_str(MEMORY);
+    writeln!(contract, "        uint[{num_inputs}] calldata _pubSignals").unwrap();
+    contract.push_str(PAIRING_START);
+    for i in 1..vk.gamma_abc_g1.len() {
+        let offset = (i - 1) * 32;
+        writeln!(
+            contract,
+            "                g1_mulAccC(_pVk, IC{i}x, IC{i}y, calldataload(add(pubSignals, {offset})))\n"
+        )
+        .unwrap();
+    }
+    contract.push_str(PAIRING_END);
+    for i in 0..vk.gamma_abc_g1.len() {
+        let offset = i * 32;
+        writeln!(
+            contract,
+            "            checkField(calldataload(add(_pubSignals, {offset})))\n"
+        )
+        .unwrap();
+    }
+    contract.push_str(FOOTER);
+    contract
+}
+
+/// Renders the constants holding `vk` in the contract of [verifier_contract].
+///
+/// This can be used to update an existing verifier contract with a new key.
+/// The coefficients of G2 points are in the order expected by the EVM
+/// precompiles, as in [Encoding::Evm](crate::Encoding::Evm).
+pub fn verifying_key_constants(vk: &VerifyingKey<Bn254>) -> String {
+    let mut constants = String::new();
+    push_g1(&mut constants, "alpha", &vk.alpha_g1);
+    push_g2(&mut constants, "beta", &vk.beta_g2);
+    push_g2(&mut constants, "gamma", &vk.gamma_g2);
+    push_g2(&mut constants, "delta", &vk.delta_g2);
+    for (i, ic) in vk.gamma_abc_g1.iter().enumerate() {
+        constants.push('\n');
+        push_g1(&mut constants, &format!("IC{i}"), ic);
+    }
+    constants.push('\n');
+    constants
+}
+
+/// Renders a Solidity library holding the public inputs of the recursion
+/// circuit that are fixed by `key`.
+///
+/// A seal for a claim is verified against five public inputs: the two halves
+/// of `allowed_ids_root`, as `CONTROL_ID_0` and `CONTROL_ID_1`, the two halves
+/// of the claim digest, and `BN254_CONTROL_ID`. The digests are split as done
+/// by [split_digest].
+pub fn control_id_library(key: &VersionedKey) -> String {
+    // The halves of a digest always fit in the scalar field.
+    let (control_id_0, control_id_1) = split_digest(key.allowed_ids_root).unwrap();
+    format!(
+        "pragma solidity ^0.8.9;\n\nlibrary ControlID {{\n{}{}{}}}\n",
+        library_constant("CONTROL_ID_0", control_id_0),
+        library_constant("CONTROL_ID_1", control_id_1),
+        library_constant("BN254_CONTROL_ID", key.bn254_control_id),
+    )
+}
+
+fn library_constant(name: &str, value: Fr) -> String {
+    format!(
+        "    uint256 public constant {name} = 0x{};\n",
+        hex::encode(value.into_bigint().to_bytes_be())
+    )
+}
+
+fn push_g1(out: &mut String, name: &str, point: &G1Affine) {
+    push_constant(out, &format!("{name}x"), &point.x);
+    push_constant(out, &format!("{name}y"), &point.y);
+}
+
+// G2 coordinates are rendered with the `c1` coefficient first.
+fn push_g2(out: &mut String, name: &str, point: &G2Affine) {
+    for (coordinate, value) in [("x", &point.x), ("y", &point.y)] {
+        let Fq2 { c0, c1 } = value;
+        push_constant(out, &format!("{name}{coordinate}1"), c1);
+        push_constant(out, &format!("{name}{coordinate}2"), c0);
+    }
+}
+
+// Constants are wrapped when they do not fit in 80 columns, as done by the
+// Solidity formatter used for the snarkjs output.
+fn push_constant(out: &mut String, name: &str, value: &Fq) {
+    let value = value.into_bigint().to_string();
+    let line = format!("    uint256 constant {name} = {value};");
+    if line.len() <= 80 {
+        writeln!(out, "{line}").unwrap();
+    } else {
+        writeln!(out, "    uint256 constant {name} =\n        {value};").unwrap();
+    }
+}
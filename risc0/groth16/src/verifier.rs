@@ -12,14 +12,21 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use core::fmt;
+
 use anyhow::{anyhow, Error, Result};
-use ark_bn254::{Bn254, Fr, G1Projective};
+use ark_bn254::{Bn254, Fq, Fr, G1Affine, G1Projective, G2Affine};
 use ark_groth16::{prepare_verifying_key, Groth16, PreparedVerifyingKey, Proof, VerifyingKey};
 use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+use risc0_zkp::core::{digest::Digest, hash::sha::Sha256};
 use serde::{Deserialize, Serialize};
 
 use crate::{
-    from_u256, g1_from_bytes, g2_from_bytes, ProofJson, PublicInputsJson, Seal, VerifyingKeyJson,
+    data_structures::is_canonical,
+    encoding::to_be_word,
+    from_u256, g1_from_bytes, g2_from_bytes,
+    pvk::{AllowedRoots, Registry, VersionedKey},
+    split_digest, ProofJson, PublicInputsJson, Seal, SealRef, VerifyingKeyJson,
 };
 
 // Constants from: risc0-ethereum/contracts/src/groth16/Groth16Verifier.sol
@@ -67,8 +74,109 @@ const IC4_Y: &str = "21663537384585072695701846972542344484111393047775983928357
 const IC5_X: &str = "6834578911681792552110317589222010969491336870276623105249474534788043166867";
 const IC5_Y: &str = "15060583660288623605191393599883223885678013570733629274538391874953353488393";
 
+/// Reasons for rejecting a seal or its public inputs when constructing a
+/// [Verifier].
+///
+/// These errors are returned before any pairing is computed. They indicate
+/// malformed input rather than an invalid proof, and can be recovered from the
+/// returned [Error] with [Error::downcast_ref].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MalformedInput {
+    /// The seal does not have the expected length in bytes.
+    SealLength {
+        /// The expected length.
+        expected: usize,
+        /// The length of the seal.
+        found: usize,
+    },
+    /// The named point of the seal is not on the curve, or not in its
+    /// prime-order subgroup.
+    InvalidPoint(&'static str),
+    /// The number of public inputs does not match the verifying key.
+    PublicInputsLength {
+        /// The number of public inputs of the verifying key.
+        expected: usize,
+        /// The number of public inputs provided.
+        found: usize,
+    },
+    /// The digest of the claim is zero, which no receipt attests to.
+    ZeroClaimDigest,
+    /// The seal is not [normalized](Seal::normalize), which is rejected by
+    /// [Verifier::verify_strict].
+    NonCanonicalSeal,
+    /// The seal was produced with a version of the encoding that is not
+    /// supported by this verifier.
+    SealVersion {
+        /// The version of the seal.
+        found: u32,
+        /// The [SEAL_VERSION](crate::SEAL_VERSION) supported by this verifier.
+        supported: u32,
+    },
+    /// The calldata does not start with
+    /// [Seal::VERIFY_PROOF_SELECTOR](crate::Seal::VERIFY_PROOF_SELECTOR).
+    FunctionSelector {
+        /// The selector of the calldata.
+        found: [u8; 4],
+    },
+}
+
+impl fmt::Display for MalformedInput {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::SealLength { expected, found } => write!(
+                f,
+                "Data length mismatch: expected {expected} bytes, found {found}"
+            ),
+            Self::InvalidPoint(name) => write!(f, "Seal point '{name}' is not a valid curve point"),
+            Self::PublicInputsLength { expected, found } => write!(
+                f,
+                "Public inputs length mismatch: expected {expected}, found {found}"
+            ),
+            Self::ZeroClaimDigest => write!(f, "Claim digest is zero"),
+            Self::NonCanonicalSeal => write!(f, "Seal is not in canonical form"),
+            Self::SealVersion { found, supported } => {
+                write!(f, "seal version {found}, verifier supports {supported}")
+            }
+            Self::FunctionSelector { found } => {
+                write!(
+                    f,
+                    "Function selector mismatch: found 0x{}",
+                    hex::encode(found)
+                )
+            }
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for MalformedInput {}
+
+/// Context of a proof rejected by [Verifier::verify].
+///
+/// It is attached to the returned [Error], whose message is unchanged, and can
+/// be recovered with [Error::downcast_ref] to be logged along with the failure.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Diagnostics {
+    /// The public inputs of the proof, as big-endian hex words.
+    pub public_inputs: Vec<String>,
+}
+
+impl fmt::Display for Diagnostics {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "public inputs: [{}]", self.public_inputs.join(", "))
+    }
+}
+
 /// Groth16 `Verifier` instance over the BN_254 curve encoded in little endian.
+///
+/// Each field holds the uncompressed arkworks encoding of its value. With the
+/// `borsh` feature enabled, the verifier is Borsh serialized as these four
+/// byte strings in declaration order, each prefixed by its `u32` length.
 #[derive(Clone, Debug, Deserialize, Serialize)]
+#[cfg_attr(
+    feature = "borsh",
+    derive(borsh::BorshSerialize, borsh::BorshDeserialize)
+)]
 pub struct Verifier {
     /// prepared verifying key little endian encoded.
     pub encoded_pvk: Vec<u8>,
@@ -76,6 +184,12 @@ pub struct Verifier {
     pub encoded_proof: Vec<u8>,
     /// prepared public inputs little endian encoded.
     pub encoded_prepared_inputs: Vec<u8>,
+    /// public inputs little endian encoded.
+    ///
+    /// Empty for verifiers serialized before this field was added, which
+    /// still verify but cannot decode their [public inputs](Self::public_inputs).
+    #[serde(default)]
+    pub encoded_public_inputs: Vec<u8>,
 }
 
 impl Verifier {
@@ -85,17 +199,48 @@ impl Verifier {
         public_inputs: Vec<Fr>,
         prepared_verifying_key: PreparedVerifyingKey<Bn254>,
     ) -> Result<Self, Error> {
+        Self::from_seal_ref(seal.try_into()?, public_inputs, prepared_verifying_key)
+    }
+
+    /// Creates a new Groth16 `Verifier` instance from a borrowed seal,
+    /// decoding its points directly from the referenced words.
+    pub fn from_seal_ref(
+        seal: SealRef<'_>,
+        public_inputs: Vec<Fr>,
+        prepared_verifying_key: PreparedVerifyingKey<Bn254>,
+    ) -> Result<Self, Error> {
+        let invalid = |name| move |_| Error::msg(MalformedInput::InvalidPoint(name));
+        let proof = Proof::<Bn254> {
+            a: g1_from_bytes(seal.a).map_err(invalid("a"))?,
+            b: g2_from_bytes(seal.b).map_err(invalid("b"))?,
+            c: g1_from_bytes(seal.c).map_err(invalid("c"))?,
+        };
+        Self::from_proof(&proof, public_inputs, prepared_verifying_key)
+    }
+
+    fn from_proof(
+        proof: &Proof<Bn254>,
+        public_inputs: Vec<Fr>,
+        prepared_verifying_key: PreparedVerifyingKey<Bn254>,
+    ) -> Result<Self, Error> {
+        let expected = prepared_verifying_key
+            .vk
+            .gamma_abc_g1
+            .len()
+            .saturating_sub(1);
+        if public_inputs.len() != expected {
+            return Err(Error::msg(MalformedInput::PublicInputsLength {
+                expected,
+                found: public_inputs.len(),
+            }));
+        }
+
         let mut encoded_pvk = Vec::new();
         prepared_verifying_key
             .serialize_uncompressed(&mut encoded_pvk)
             .map_err(|err| anyhow!(err))?;
 
         let mut encoded_proof = Vec::new();
-        let proof = Proof::<Bn254> {
-            a: g1_from_bytes(&seal.a)?,
-            b: g2_from_bytes(&seal.b)?,
-            c: g1_from_bytes(&seal.c)?,
-        };
         proof
             .serialize_uncompressed(&mut encoded_proof)
             .map_err(|err| anyhow!(err))?;
@@ -108,10 +253,16 @@ impl Verifier {
             .serialize_uncompressed(&mut encoded_prepared_inputs)
             .map_err(|err| anyhow!(err))?;
 
+        let mut encoded_public_inputs = Vec::new();
+        public_inputs
+            .serialize_uncompressed(&mut encoded_public_inputs)
+            .map_err(|err| anyhow!(err))?;
+
         Ok(Self {
             encoded_pvk,
             encoded_proof,
             encoded_prepared_inputs,
+            encoded_public_inputs,
         })
     }
 
@@ -127,46 +278,288 @@ impl Verifier {
         )
     }
 
+    /// Creates a `Verifier` for a seal attesting to the claim with digest
+    /// `claim_digest`, using the parameters registered for `version`.
+    pub fn from_seal_versioned(
+        registry: &Registry,
+        version: &str,
+        seal: SealRef<'_>,
+        claim_digest: Digest,
+    ) -> Result<Self> {
+        if claim_digest.ct_eq(&Digest::ZERO) {
+            return Err(Error::msg(MalformedInput::ZeroClaimDigest));
+        }
+        let key = registry.get(version)?;
+        Verifier::from_seal_ref(
+            seal,
+            receipt_public_inputs(key.allowed_ids_root, claim_digest, key.bn254_control_id)?,
+            key.pvk.clone(),
+        )
+    }
+
+    /// Verifies a seal attesting to the claim with digest `claim_digest`
+    /// under any of the `allowed_roots`, using the verifying key and control
+    /// ID of `key` and ignoring its own root.
+    ///
+    /// Returns the root under which the seal was produced.
+    pub fn verify_allowed_roots(
+        key: &VersionedKey,
+        allowed_roots: &AllowedRoots,
+        seal: SealRef<'_>,
+        claim_digest: Digest,
+    ) -> Result<Digest> {
+        if claim_digest.ct_eq(&Digest::ZERO) {
+            return Err(Error::msg(MalformedInput::ZeroClaimDigest));
+        }
+        for root in allowed_roots.iter() {
+            let public_inputs = receipt_public_inputs(*root, claim_digest, key.bn254_control_id)?;
+            if Verifier::from_seal_ref(seal, public_inputs, key.pvk.clone())?
+                .verify()
+                .is_ok()
+            {
+                return Ok(*root);
+            }
+        }
+        Err(anyhow!("Invalid proof for all allowed control roots"))
+    }
+
+    /// Decodes the prepared verifying key.
+    pub fn prepared_verifying_key(&self) -> Result<PreparedVerifyingKey<Bn254>, Error> {
+        PreparedVerifyingKey::deserialize_uncompressed(&*self.encoded_pvk)
+            .map_err(|err| anyhow!(err))
+    }
+
+    /// Decodes the Groth16 proof, giving access to the `a`, `b` and `c` points.
+    pub fn proof(&self) -> Result<Proof<Bn254>, Error> {
+        Proof::deserialize_uncompressed(&*self.encoded_proof).map_err(|err| anyhow!(err))
+    }
+
+    /// Decodes the public inputs as scalars over the field of the G1/G2 groups.
+    pub fn public_inputs(&self) -> Result<Vec<Fr>, Error> {
+        if self.encoded_public_inputs.is_empty() {
+            return Err(anyhow!("public inputs were not recorded by this verifier"));
+        }
+        Vec::<Fr>::deserialize_uncompressed(&*self.encoded_public_inputs)
+            .map_err(|err| anyhow!(err))
+    }
+
+    /// Decodes the public inputs prepared against the verifying key.
+    pub fn prepared_inputs(&self) -> Result<G1Projective, Error> {
+        G1Projective::deserialize_uncompressed(&*self.encoded_prepared_inputs)
+            .map_err(|err| anyhow!(err))
+    }
+
+    /// Returns the canonical encoding of the proof and public inputs, which
+    /// does not depend on the verifying key.
+    ///
+    /// The encoding is the concatenation of 32-byte big-endian words, in the
+    /// order of the arguments of the Solidity verifier's `verifyProof`:
+    ///
+    /// ```text
+    /// a.x | a.y | b.x.c1 | b.x.c0 | b.y.c1 | b.y.c0 | c.x | c.y | input[0] | ... | input[n-1]
+    /// ```
+    ///
+    /// The point at infinity is encoded as `(0, 0)`. The proof part is the
+    /// same as [Seal::to_vec].
+    pub fn canonical_encoding(&self) -> Result<Vec<u8>, Error> {
+        fn push_g1(buf: &mut Vec<u8>, p: &G1Affine) {
+            push_fq(buf, &p.x);
+            push_fq(buf, &p.y);
+        }
+        fn push_g2(buf: &mut Vec<u8>, p: &G2Affine) {
+            for fq in [&p.x.c1, &p.x.c0, &p.y.c1, &p.y.c0] {
+                push_fq(buf, fq);
+            }
+        }
+        fn push_fq(buf: &mut Vec<u8>, fq: &Fq) {
+            buf.extend(to_be_word(fq));
+        }
+
+        let proof = self.proof()?;
+        let public_inputs = self.public_inputs()?;
+        let mut encoding = Vec::with_capacity(32 * (8 + public_inputs.len()));
+        push_g1(&mut encoding, &proof.a);
+        push_g2(&mut encoding, &proof.b);
+        push_g1(&mut encoding, &proof.c);
+        for input in public_inputs {
+            encoding.extend(to_be_word(&input));
+        }
+        Ok(encoding)
+    }
+
+    /// Computes the SHA-256 digest of the [canonical
+    /// encoding](Verifier::canonical_encoding) of the proof and public inputs.
+    ///
+    /// Unlike a digest of the `Verifier` itself, it does not cover the
+    /// verifying key, so it is stable across key serializations and suitable
+    /// for deduplicating proofs or committing to them on-chain.
+    pub fn canonical_digest<S: Sha256>(&self) -> Result<Digest, Error> {
+        Ok(*S::hash_bytes(&self.canonical_encoding()?))
+    }
+
     /// Verifies the Groth16 proof.
+    ///
+    /// With the `substrate-bn` feature, the pairing check is computed by
+    /// `substrate-bn` rather than arkworks.
     pub fn verify(&self) -> Result<(), Error> {
-        let pvk = &PreparedVerifyingKey::deserialize_uncompressed(&*self.encoded_pvk)
-            .map_err(|err| anyhow!(err))?;
-        let proof =
-            &Proof::deserialize_uncompressed(&*self.encoded_proof).map_err(|err| anyhow!(err))?;
-        let prepared_inputs =
-            &G1Projective::deserialize_uncompressed(&*self.encoded_prepared_inputs)
-                .map_err(|err| anyhow!(err))?;
-        match Groth16::<Bn254>::verify_proof_with_prepared_inputs(pvk, proof, prepared_inputs)
-            .map_err(|err| anyhow!(err))?
-        {
+        #[cfg(not(feature = "substrate-bn"))]
+        let valid = {
+            let pvk = &self.prepared_verifying_key()?;
+            let proof = &self.proof()?;
+            let prepared_inputs = &self.prepared_inputs()?;
+            Groth16::<Bn254>::verify_proof_with_prepared_inputs(pvk, proof, prepared_inputs)
+                .map_err(|err| anyhow!(err))?
+        };
+        #[cfg(feature = "substrate-bn")]
+        let valid = crate::bn::verify(self)?;
+        match valid {
             true => Ok(()),
-            false => Err(anyhow!("Invalid proof")),
+            false => Err(match self.diagnostics() {
+                Ok(diagnostics) => Error::msg(diagnostics).context("Invalid proof"),
+                Err(_) => anyhow!("Invalid proof"),
+            }),
+        }
+    }
+
+    /// Returns the [Diagnostics] attached to the error of [Verifier::verify]
+    /// when the proof is rejected.
+    pub fn diagnostics(&self) -> Result<Diagnostics, Error> {
+        Ok(Diagnostics {
+            public_inputs: self
+                .public_inputs()?
+                .iter()
+                .map(|input| format!("0x{}", hex::encode(to_be_word(input))))
+                .collect(),
+        })
+    }
+
+    /// Verifies the Groth16 proof, rejecting it if the seal is not
+    /// [normalized](Seal::normalize).
+    ///
+    /// A valid proof has a second valid seal with `a` and `b` negated. Strict
+    /// verification accepts only one of the two, so that deduplication and
+    /// consensus layers can use the seal bytes as the identity of the proof.
+    pub fn verify_strict(&self) -> Result<(), Error> {
+        if !is_canonical(&self.proof()?.a) {
+            return Err(Error::msg(MalformedInput::NonCanonicalSeal));
         }
+        self.verify()
+    }
+
+    /// Verifies the Groth16 proof against the supplied verifying key, rather
+    /// than the prepared verifying key of this `Verifier`.
+    pub fn verify_with_vk(&self, verifying_key: &VerifyingKeyJson) -> Result<(), Error> {
+        Self::from_proof(
+            &self.proof()?,
+            self.public_inputs()?,
+            verifying_key.prepared_verifying_key()?,
+        )?
+        .verify()
+    }
+}
+
+// The public inputs of a Groth16 seal of a receipt: the halves of the allowed
+// control ID root and of the claim digest, then the BN254 control ID.
+fn receipt_public_inputs(
+    allowed_ids_root: Digest,
+    claim_digest: Digest,
+    bn254_control_id: Fr,
+) -> Result<Vec<Fr>> {
+    let (a0, a1) = split_digest(allowed_ids_root)?;
+    let (c0, c1) = split_digest(claim_digest)?;
+    Ok(vec![a0, a1, c0, c1, bn254_control_id])
+}
+
+#[cfg(feature = "tokio")]
+impl Verifier {
+    /// Verifies the Groth16 proof on the tokio blocking thread pool.
+    ///
+    /// The pairing check is CPU bound, so async services should use this
+    /// rather than calling [Verifier::verify] from a worker thread.
+    pub async fn verify_async(self) -> Result<(), Error> {
+        tokio::task::spawn_blocking(move || self.verify()).await?
+    }
+}
+
+/// Verifies a batch of Groth16 proofs concurrently on the tokio blocking
+/// thread pool.
+///
+/// Returns an error naming the index of an invalid proof, if any.
+#[cfg(feature = "tokio")]
+pub async fn verify_batch_async(
+    verifiers: impl IntoIterator<Item = Verifier>,
+) -> Result<(), Error> {
+    use anyhow::Context;
+
+    let mut tasks = tokio::task::JoinSet::new();
+    for (idx, verifier) in verifiers.into_iter().enumerate() {
+        tasks.spawn_blocking(move || {
+            verifier
+                .verify()
+                .with_context(|| format!("invalid proof at index {idx}"))
+        });
     }
+    while let Some(result) = tasks.join_next().await {
+        result??;
+    }
+    Ok(())
+}
+
+/// Verifies a batch of Groth16 proofs.
+///
+/// With the `parallel` feature, the proofs are verified concurrently on the
+/// rayon thread pool, in addition to the parallelism of each pairing check.
+///
+/// Returns an error naming the index of an invalid proof, if any.
+pub fn verify_batch(verifiers: &[Verifier]) -> Result<(), Error> {
+    use anyhow::Context;
+
+    let verify = |(idx, verifier): (usize, &Verifier)| {
+        verifier
+            .verify()
+            .with_context(|| format!("invalid proof at index {idx}"))
+    };
+    #[cfg(feature = "parallel")]
+    {
+        use rayon::prelude::*;
+        verifiers.par_iter().enumerate().try_for_each(verify)
+    }
+    #[cfg(not(feature = "parallel"))]
+    verifiers.iter().enumerate().try_for_each(verify)
+}
+
+/// Verifies a Groth16 `seal` over `public_inputs` against the supplied
+/// verifying key.
+pub fn verify_seal(
+    verifying_key: &VerifyingKeyJson,
+    seal: &Seal,
+    public_inputs: Vec<Fr>,
+) -> Result<(), Error> {
+    Verifier::new(seal, public_inputs, verifying_key.prepared_verifying_key()?)?.verify()
 }
 
 /// Computes the default prepared verifying key, used by Bonsai.
 pub fn prepared_verifying_key() -> Result<PreparedVerifyingKey<Bn254>, Error> {
-    let alpha_g1 = g1_from_bytes(&[from_u256(ALPHA_X)?, from_u256(ALPHA_Y)?])?;
-    let beta_g2 = g2_from_bytes(&vec![
-        vec![from_u256(BETA_X1)?, from_u256(BETA_X2)?],
-        vec![from_u256(BETA_Y1)?, from_u256(BETA_Y2)?],
+    let alpha_g1 = g1_from_bytes([&from_u256(ALPHA_X)?, &from_u256(ALPHA_Y)?])?;
+    let beta_g2 = g2_from_bytes([
+        [&from_u256(BETA_X1)?, &from_u256(BETA_X2)?],
+        [&from_u256(BETA_Y1)?, &from_u256(BETA_Y2)?],
     ])?;
-    let gamma_g2 = g2_from_bytes(&vec![
-        vec![from_u256(GAMMA_X1)?, from_u256(GAMMA_X2)?],
-        vec![from_u256(GAMMA_Y1)?, from_u256(GAMMA_Y2)?],
+    let gamma_g2 = g2_from_bytes([
+        [&from_u256(GAMMA_X1)?, &from_u256(GAMMA_X2)?],
+        [&from_u256(GAMMA_Y1)?, &from_u256(GAMMA_Y2)?],
     ])?;
-    let delta_g2 = g2_from_bytes(&vec![
-        vec![from_u256(DELTA_X1)?, from_u256(DELTA_X2)?],
-        vec![from_u256(DELTA_Y1)?, from_u256(DELTA_Y2)?],
+    let delta_g2 = g2_from_bytes([
+        [&from_u256(DELTA_X1)?, &from_u256(DELTA_X2)?],
+        [&from_u256(DELTA_Y1)?, &from_u256(DELTA_Y2)?],
     ])?;
 
-    let ic0 = g1_from_bytes(&[from_u256(IC0_X)?, from_u256(IC0_Y)?])?;
-    let ic1 = g1_from_bytes(&[from_u256(IC1_X)?, from_u256(IC1_Y)?])?;
-    let ic2 = g1_from_bytes(&[from_u256(IC2_X)?, from_u256(IC2_Y)?])?;
-    let ic3 = g1_from_bytes(&[from_u256(IC3_X)?, from_u256(IC3_Y)?])?;
-    let ic4 = g1_from_bytes(&[from_u256(IC4_X)?, from_u256(IC4_Y)?])?;
-    let ic5 = g1_from_bytes(&[from_u256(IC5_X)?, from_u256(IC5_Y)?])?;
+    let ic0 = g1_from_bytes([&from_u256(IC0_X)?, &from_u256(IC0_Y)?])?;
+    let ic1 = g1_from_bytes([&from_u256(IC1_X)?, &from_u256(IC1_Y)?])?;
+    let ic2 = g1_from_bytes([&from_u256(IC2_X)?, &from_u256(IC2_Y)?])?;
+    let ic3 = g1_from_bytes([&from_u256(IC3_X)?, &from_u256(IC3_Y)?])?;
+    let ic4 = g1_from_bytes([&from_u256(IC4_X)?, &from_u256(IC4_Y)?])?;
+    let ic5 = g1_from_bytes([&from_u256(IC5_X)?, &from_u256(IC5_Y)?])?;
     let gamma_abc_g1 = vec![ic0, ic1, ic2, ic3, ic4, ic5];
 
     let vk = VerifyingKey::<Bn254> {
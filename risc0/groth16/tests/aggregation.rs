@@ -0,0 +1,83 @@
+// Copyright 2024 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+#[cfg(all(test, feature = "aggregation", feature = "solidity"))]
+mod tests {
+    use risc0_groth16::{
+        aggregation::{AggregationTree, Claim},
+        solidity::AGGREGATION_LIBRARY,
+        split_digest,
+    };
+    use risc0_zkp::core::digest::Digest;
+    use sha3::{Digest as _, Keccak256};
+
+    fn claims(count: u32) -> Vec<Claim> {
+        (0..count)
+            .map(|i| Claim {
+                image_id: Digest::from([i, 1, 2, 3, 4, 5, 6, 7]),
+                journal_digest: Digest::from([7, 6, 5, 4, 3, 2, 1, i]),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_leaf() {
+        let claim = claims(1)[0];
+        let mut encoded = claim.image_id.as_bytes().to_vec();
+        encoded.extend_from_slice(claim.journal_digest.as_bytes());
+        let leaf = Keccak256::digest(Keccak256::digest(&encoded));
+        assert_eq!(claim.leaf().as_bytes(), leaf.as_slice());
+
+        // A single claim is its own root.
+        let tree = AggregationTree::new(&[claim]).unwrap();
+        assert_eq!(tree.root(), claim.leaf());
+        assert!(tree.proof(0).unwrap().siblings.is_empty());
+        assert!(AGGREGATION_LIBRARY
+            .contains("keccak256(bytes.concat(keccak256(abi.encode(imageId, journalDigest))))"));
+    }
+
+    #[test]
+    fn test_inclusion_proofs() {
+        assert!(AggregationTree::new(&[]).is_err());
+        for count in 1..=9 {
+            let claims = claims(count);
+            let tree = AggregationTree::new(&claims).unwrap();
+            assert_eq!(tree.len(), claims.len());
+            assert_eq!(
+                tree.public_inputs().unwrap(),
+                split_digest(tree.root()).unwrap()
+            );
+            for (index, claim) in claims.iter().enumerate() {
+                let proof = tree.proof(index).unwrap();
+                assert_eq!(proof.index, index);
+                assert!(proof.verify(claim, &tree.root()));
+
+                let tampered = Claim {
+                    journal_digest: Digest::ZERO,
+                    ..*claim
+                };
+                assert!(!proof.verify(&tampered, &tree.root()));
+            }
+            assert!(tree.proof(claims.len()).is_none());
+        }
+    }
+
+    #[test]
+    fn test_root_commits_to_claims() {
+        let mut claims = claims(4);
+        let root = AggregationTree::new(&claims).unwrap().root();
+        claims[0].journal_digest = Digest::ZERO;
+        assert_ne!(AggregationTree::new(&claims).unwrap().root(), root);
+    }
+}
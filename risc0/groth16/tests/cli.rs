@@ -0,0 +1,86 @@
+// Copyright 2024 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+#[cfg(all(test, feature = "cli"))]
+mod tests {
+    use std::process::{Command, Output};
+
+    use risc0_groth16::{ProofJson, Seal};
+
+    const TEST_PROOF_PATH: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/data/proof.json");
+    const TEST_PROOF: &str = include_str!("data/proof.json");
+
+    fn groth16(args: &[&str]) -> Output {
+        Command::new(env!("CARGO_BIN_EXE_groth16"))
+            .args(args)
+            .output()
+            .unwrap()
+    }
+
+    fn stdout(output: Output) -> String {
+        assert!(output.status.success());
+        String::from_utf8(output.stdout).unwrap().trim().to_string()
+    }
+
+    #[test]
+    fn test_convert() {
+        let seal: Seal = serde_json::from_str::<ProofJson>(TEST_PROOF)
+            .unwrap()
+            .try_into()
+            .unwrap();
+        let bytes = stdout(groth16(&[
+            "convert",
+            "--seal",
+            TEST_PROOF_PATH,
+            "--to",
+            "bytes",
+        ]));
+        assert_eq!(bytes, format!("0x{}", hex::encode(seal.to_vec())));
+
+        // Compressed seals are read back.
+        let compressed = stdout(groth16(&[
+            "convert",
+            "--seal",
+            TEST_PROOF_PATH,
+            "--to",
+            "compressed",
+        ]));
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("seal.hex");
+        std::fs::write(&path, compressed).unwrap();
+        let path = path.to_str().unwrap();
+        assert_eq!(
+            stdout(groth16(&["convert", "--seal", path, "--to", "bytes"])),
+            bytes
+        );
+
+        let inspect = stdout(groth16(&["inspect", "--seal", path]));
+        assert!(inspect.contains(&format!("a.x      0x{}", hex::encode(&seal.a[0]))));
+        assert!(inspect.contains("normalized: false"));
+    }
+
+    #[test]
+    fn test_verify() {
+        // The claim digest is required.
+        assert!(!groth16(&["verify", "--seal", TEST_PROOF_PATH])
+            .status
+            .success());
+
+        // The test proof was not produced by the recursion circuit.
+        let meta = hex::encode([1u8; 32]);
+        let output = groth16(&["verify", "--seal", TEST_PROOF_PATH, "--meta", &meta]);
+        assert!(!output.status.success());
+        assert!(String::from_utf8_lossy(&output.stderr).contains("Invalid proof"));
+    }
+}
@@ -0,0 +1,84 @@
+// Copyright 2024 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+#[cfg(all(test, feature = "plonk"))]
+mod tests {
+    use risc0_groth16::{
+        plonk::{ProofJson, Seal, Verifier, VerifyingKeyJson},
+        PublicInputsJson,
+    };
+
+    const TEST_VERIFICATION_KEY: &str = include_str!("data/plonk_verification_key.json");
+    const TEST_PROOF: &str = include_str!("data/plonk_proof.json");
+    const TEST_PUBLIC_INPUTS: &str = include_str!("data/plonk_public.json");
+
+    fn test_public_inputs() -> PublicInputsJson {
+        PublicInputsJson {
+            values: serde_json::from_str(TEST_PUBLIC_INPUTS).unwrap(),
+        }
+    }
+
+    #[test]
+    fn test_verify() {
+        let verifying_key: VerifyingKeyJson = serde_json::from_str(TEST_VERIFICATION_KEY).unwrap();
+        let proof: ProofJson = serde_json::from_str(TEST_PROOF).unwrap();
+        let verifier = Verifier::from_json(proof, test_public_inputs(), verifying_key).unwrap();
+        verifier.verify().unwrap();
+    }
+
+    #[test]
+    fn test_verify_wrong_public_inputs() {
+        let verifying_key: VerifyingKeyJson = serde_json::from_str(TEST_VERIFICATION_KEY).unwrap();
+        let proof: ProofJson = serde_json::from_str(TEST_PROOF).unwrap();
+        let mut public_inputs = test_public_inputs();
+        public_inputs.values[1] = "13".to_string();
+        let verifier = Verifier::from_json(proof, public_inputs, verifying_key).unwrap();
+        assert!(verifier.verify().is_err());
+
+        // The number of public inputs must match the verifying key.
+        let verifying_key: VerifyingKeyJson = serde_json::from_str(TEST_VERIFICATION_KEY).unwrap();
+        let proof: ProofJson = serde_json::from_str(TEST_PROOF).unwrap();
+        let mut public_inputs = test_public_inputs();
+        public_inputs.values.pop();
+        assert!(Verifier::from_json(proof, public_inputs, verifying_key).is_err());
+    }
+
+    #[test]
+    fn test_seal_roundtrip() {
+        let verifying_key: VerifyingKeyJson = serde_json::from_str(TEST_VERIFICATION_KEY).unwrap();
+        let seal: Seal = serde_json::from_str::<ProofJson>(TEST_PROOF)
+            .unwrap()
+            .try_into()
+            .unwrap();
+        let encoded = seal.to_vec();
+        assert_eq!(encoded.len(), 24 * 32);
+        assert_eq!(Seal::from_vec(&encoded).unwrap(), seal);
+        assert!(Seal::from_vec(&encoded[1..]).is_err());
+
+        let scalars = test_public_inputs().to_scalar().unwrap();
+        let vk = verifying_key.verifying_key().unwrap();
+        let verifier = Verifier::new(&seal, scalars.clone(), vk.clone()).unwrap();
+        assert_eq!(verifier.public_inputs().unwrap(), scalars);
+        assert_eq!(verifier.verifying_key().unwrap(), vk);
+        verifier.verify().unwrap();
+
+        // Tampering with an evaluation invalidates the proof.
+        let mut encoded = encoded;
+        let last = encoded.len() - 1;
+        encoded[last] ^= 1;
+        let tampered = Seal::from_vec(&encoded).unwrap();
+        let verifier = Verifier::new(&tampered, scalars, vk).unwrap();
+        assert!(verifier.verify().is_err());
+    }
+}
@@ -0,0 +1,65 @@
+// Copyright 2024 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+#[cfg(all(test, feature = "solidity"))]
+mod tests {
+    use risc0_groth16::{
+        fr_from_hex_string,
+        pvk::VersionedKey,
+        solidity::{control_id_library, verifier_contract, verifying_key_constants},
+        split_digest,
+        verifier::prepared_verifying_key,
+        VerifyingKeyJson,
+    };
+    use risc0_zkp::core::digest::Digest;
+
+    const GROTH16_VERIFIER: &str = include_str!("../../../compact_proof/groth16/verifier.sol");
+    const TEST_VERIFICATION_KEY: &str = include_str!("data/verification_key.json");
+
+    #[test]
+    fn test_verifier_contract() {
+        let vk = prepared_verifying_key().unwrap().vk;
+        assert_eq!(verifier_contract(&vk), GROTH16_VERIFIER);
+        assert!(GROTH16_VERIFIER.contains(&verifying_key_constants(&vk)));
+
+        let verifying_key: VerifyingKeyJson = serde_json::from_str(TEST_VERIFICATION_KEY).unwrap();
+        let vk = verifying_key.prepared_verifying_key().unwrap().vk;
+        let contract = verifier_contract(&vk);
+        assert!(contract.contains("uint[1] calldata _pubSignals"));
+        assert!(contract.contains("IC1x"));
+        assert!(!contract.contains("IC2x"));
+    }
+
+    #[test]
+    fn test_control_id_library() {
+        let allowed_ids_root = Digest::from([1, 2, 3, 4, 5, 6, 7, 8]);
+        let key = VersionedKey {
+            pvk: prepared_verifying_key().unwrap(),
+            allowed_ids_root,
+            bn254_control_id: fr_from_hex_string("2a").unwrap(),
+        };
+        let library = control_id_library(&key);
+        assert!(library.contains("library ControlID {"));
+
+        let constant = |name: &str| {
+            let start = library.find(&format!("{name} = 0x")).unwrap() + name.len() + 5;
+            let end = start + library[start..].find(';').unwrap();
+            fr_from_hex_string(&library[start..end]).unwrap()
+        };
+        let (control_id_0, control_id_1) = split_digest(allowed_ids_root).unwrap();
+        assert_eq!(constant("CONTROL_ID_0"), control_id_0);
+        assert_eq!(constant("CONTROL_ID_1"), control_id_1);
+        assert_eq!(constant("BN254_CONTROL_ID"), key.bn254_control_id);
+    }
+}
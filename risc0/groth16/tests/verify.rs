@@ -14,12 +14,112 @@
 
 #[cfg(test)]
 mod tests {
-    use risc0_groth16::{ProofJson, PublicInputsJson, Verifier, VerifyingKeyJson};
+    use ark_bn254::{Bn254, Fr, G1Affine, G2Affine};
+    use ark_ec::{AffineRepr, CurveGroup};
+    use ark_ff::Field;
+    use ark_groth16::{prepare_verifying_key, Groth16, PreparedVerifyingKey, Proof, VerifyingKey};
+    use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+    use risc0_groth16::{
+        fr_from_hex_string,
+        pvk::{for_version, AllowedRoots, CircuitVersion, Registry, VersionedKey},
+        split_digest,
+        verifier::prepared_verifying_key,
+        verify_batch, verify_seal, Diagnostics, Encoding, MalformedInput, ProofJson,
+        PublicInputsJson, Seal, SealRef, Verifier, VerifyingKeyJson, SEAL_VERSION,
+    };
+    use risc0_zkp::core::{
+        digest::Digest,
+        hash::sha::{cpu, Sha256},
+    };
 
     const TEST_VERIFICATION_KEY: &str = include_str!("data/verification_key.json");
     const TEST_PROOF: &str = include_str!("data/proof.json");
     const TEST_PUBLIC_INPUTS: &str = include_str!("data/public.json");
 
+    /// The test proof, with the verifying key and public inputs it verifies
+    /// with.
+    struct Fixture {
+        verifying_key: VerifyingKeyJson,
+        pvk: PreparedVerifyingKey<Bn254>,
+        seal: Seal,
+        public_inputs: Vec<Fr>,
+    }
+
+    impl Fixture {
+        fn load() -> Self {
+            let verifying_key: VerifyingKeyJson =
+                serde_json::from_str(TEST_VERIFICATION_KEY).unwrap();
+            let pvk = verifying_key.prepared_verifying_key().unwrap();
+            let seal = serde_json::from_str::<ProofJson>(TEST_PROOF)
+                .unwrap()
+                .try_into()
+                .unwrap();
+            let public_inputs = PublicInputsJson {
+                values: serde_json::from_str(TEST_PUBLIC_INPUTS).unwrap(),
+            }
+            .to_scalar()
+            .unwrap();
+            Self {
+                verifying_key,
+                pvk,
+                seal,
+                public_inputs,
+            }
+        }
+
+        fn verifier(&self) -> Verifier {
+            Verifier::new(&self.seal, self.public_inputs.clone(), self.pvk.clone()).unwrap()
+        }
+
+        // A key for a circuit with the same number of public inputs, which
+        // did not produce the proof.
+        fn other_key(&self) -> VerifyingKeyJson {
+            let mut key: serde_json::Value = serde_json::from_str(TEST_VERIFICATION_KEY).unwrap();
+            key["vk_delta_2"] = key["vk_gamma_2"].clone();
+            serde_json::from_value(key).unwrap()
+        }
+    }
+
+    // Returns a key for a circuit with `public_inputs.len()` public inputs,
+    // and a seal verifying against `public_inputs` with it, built from the
+    // trapdoor of the key.
+    fn trapdoor_seal(public_inputs: &[Fr]) -> (PreparedVerifyingKey<Bn254>, Seal) {
+        let g1 = G1Affine::generator();
+        let g2 = G2Affine::generator();
+        let [alpha, beta, gamma, delta, a, b] = [2u64, 3, 5, 7, 11, 13].map(Fr::from);
+        let ic: Vec<Fr> = (0..=public_inputs.len() as u64)
+            .map(|i| Fr::from(17 + i))
+            .collect();
+        let vk = VerifyingKey::<Bn254> {
+            alpha_g1: (g1 * alpha).into_affine(),
+            beta_g2: (g2 * beta).into_affine(),
+            gamma_g2: (g2 * gamma).into_affine(),
+            delta_g2: (g2 * delta).into_affine(),
+            gamma_abc_g1: ic.iter().map(|x| (g1 * x).into_affine()).collect(),
+        };
+
+        // e(a, b) = e(alpha, beta) e(l, gamma) e(c, delta), where l is the
+        // combination of the IC points with the public inputs.
+        let l = ic[0]
+            + ic[1..]
+                .iter()
+                .zip(public_inputs)
+                .map(|(x, y)| *x * y)
+                .sum::<Fr>();
+        let c = (a * b - alpha * beta - l * gamma) * delta.inverse().unwrap();
+        let proof = Proof::<Bn254> {
+            a: (g1 * a).into_affine(),
+            b: (g2 * b).into_affine(),
+            c: (g1 * c).into_affine(),
+        };
+        let mut encoded = Vec::new();
+        proof.serialize_uncompressed(&mut encoded).unwrap();
+        (
+            prepare_verifying_key(&vk),
+            Seal::from_bytes(&encoded, Encoding::Arkworks).unwrap(),
+        )
+    }
+
     #[test]
     fn test_verify() {
         let verifying_key: VerifyingKeyJson = serde_json::from_str(TEST_VERIFICATION_KEY).unwrap();
@@ -30,4 +130,561 @@ mod tests {
         let verifier = Verifier::from_json(proof, public_inputs, verifying_key).unwrap();
         verifier.verify().unwrap();
     }
+
+    #[test]
+    fn test_serde_without_public_inputs() {
+        let verifier = Fixture::load().verifier();
+
+        // Verifiers serialized before `encoded_public_inputs` was added.
+        let mut json = serde_json::to_value(&verifier).unwrap();
+        json.as_object_mut()
+            .unwrap()
+            .remove("encoded_public_inputs")
+            .unwrap();
+        let legacy: Verifier = serde_json::from_value(json).unwrap();
+        assert!(legacy.encoded_public_inputs.is_empty());
+        assert_eq!(legacy.encoded_proof, verifier.encoded_proof);
+        legacy.verify().unwrap();
+        assert!(legacy.public_inputs().is_err());
+
+        let json = serde_json::to_string(&verifier).unwrap();
+        let decoded: Verifier = serde_json::from_str(&json).unwrap();
+        assert_eq!(
+            decoded.public_inputs().unwrap(),
+            verifier.public_inputs().unwrap()
+        );
+    }
+
+    #[test]
+    fn test_verify_tampered() {
+        let verifier = Fixture::load().verifier();
+
+        // A point that is no longer on the curve.
+        let mut tampered = verifier.clone();
+        tampered.encoded_proof[0] ^= 1;
+        assert!(tampered.verify().is_err());
+
+        // A valid point that does not satisfy the pairing equation.
+        let mut tampered = verifier.clone();
+        let a = tampered.encoded_proof[..64].to_vec();
+        let c_start = tampered.encoded_proof.len() - 64;
+        tampered.encoded_proof[c_start..].copy_from_slice(&a);
+        let err = tampered.verify().unwrap_err();
+        assert_eq!(err.to_string(), "Invalid proof");
+        let diagnostics = err.downcast_ref::<Diagnostics>().unwrap();
+        assert_eq!(diagnostics, &tampered.diagnostics().unwrap());
+        assert_eq!(diagnostics.public_inputs, [format!("0x{:064x}", 33)]);
+    }
+
+    #[test]
+    fn test_verify_batch() {
+        let verifier = Fixture::load().verifier();
+        verify_batch(&vec![verifier.clone(); 4]).unwrap();
+        verify_batch(&[]).unwrap();
+
+        let mut tampered = verifier.clone();
+        tampered.encoded_proof[0] ^= 1;
+        let err = verify_batch(&[verifier.clone(), tampered, verifier]).unwrap_err();
+        assert_eq!(err.to_string(), "invalid proof at index 1");
+    }
+
+    #[cfg(feature = "tokio")]
+    #[tokio::test]
+    async fn test_verify_async() {
+        let verifier = Fixture::load().verifier();
+        verifier.clone().verify_async().await.unwrap();
+
+        let mut tampered = verifier.clone();
+        tampered.encoded_proof[0] ^= 1;
+        assert!(tampered.clone().verify_async().await.is_err());
+
+        risc0_groth16::verify_batch_async(vec![verifier.clone(); 4])
+            .await
+            .unwrap();
+        let err = risc0_groth16::verify_batch_async([verifier.clone(), tampered, verifier])
+            .await
+            .unwrap_err();
+        assert_eq!(err.to_string(), "invalid proof at index 1");
+    }
+
+    #[test]
+    fn test_verify_with_vk() {
+        let fixture = Fixture::load();
+        let Fixture {
+            verifying_key,
+            seal,
+            public_inputs,
+            ..
+        } = &fixture;
+        let other_key = fixture.other_key();
+        verifying_key.validate().unwrap();
+        other_key.validate().unwrap();
+
+        let verifier = Verifier::new(
+            seal,
+            public_inputs.clone(),
+            other_key.prepared_verifying_key().unwrap(),
+        )
+        .unwrap();
+        assert!(verifier.verify().is_err());
+        verifier.verify_with_vk(verifying_key).unwrap();
+        assert!(verifier.verify_with_vk(&other_key).is_err());
+
+        verify_seal(verifying_key, seal, public_inputs.clone()).unwrap();
+        assert!(verify_seal(&other_key, seal, public_inputs.clone()).is_err());
+        let wrong_inputs = vec![fr_from_hex_string("34").unwrap()];
+        assert!(verify_seal(verifying_key, seal, wrong_inputs).is_err());
+        assert!(verify_seal(verifying_key, seal, vec![]).is_err());
+    }
+
+    #[test]
+    fn test_accessors() {
+        let fixture = Fixture::load();
+        let verifier = fixture.verifier();
+        let Fixture {
+            pvk,
+            seal,
+            public_inputs: scalars,
+            ..
+        } = fixture;
+
+        assert_eq!(verifier.public_inputs().unwrap(), scalars);
+        assert_eq!(
+            verifier.prepared_inputs().unwrap(),
+            Groth16::<Bn254>::prepare_inputs(&pvk, &scalars).unwrap()
+        );
+        assert_eq!(verifier.prepared_verifying_key().unwrap().vk, pvk.vk);
+        let decoded = verifier.proof().unwrap();
+        let rebuilt = Verifier::new(&seal, scalars, pvk).unwrap();
+        assert_eq!(rebuilt.proof().unwrap(), decoded);
+    }
+
+    #[test]
+    fn test_canonical_digest() {
+        let fixture = Fixture::load();
+        let Fixture {
+            seal,
+            public_inputs,
+            ..
+        } = &fixture;
+        let verifier = fixture.verifier();
+
+        let encoding = verifier.canonical_encoding().unwrap();
+        assert_eq!(encoding.len(), 9 * 32);
+        assert_eq!(encoding[..256], seal.to_vec());
+        // The single public input of the test circuit is 33.
+        assert_eq!(encoding[256..], [[0; 31].as_slice(), &[33]].concat());
+
+        // The digest does not depend on the verifying key.
+        let other = Verifier::new(
+            seal,
+            public_inputs.clone(),
+            fixture.other_key().prepared_verifying_key().unwrap(),
+        )
+        .unwrap();
+        assert_ne!(other.encoded_pvk, verifier.encoded_pvk);
+        let digest = verifier.canonical_digest::<cpu::Impl>().unwrap();
+        assert_eq!(digest, other.canonical_digest::<cpu::Impl>().unwrap());
+        assert_eq!(digest, *cpu::Impl::hash_bytes(&encoding));
+    }
+
+    #[test]
+    fn test_seal_encodings() {
+        let fixture = Fixture::load();
+        let verifier = fixture.verifier();
+        let seal = fixture.seal;
+
+        assert_eq!(seal.to_bytes(Encoding::Evm), seal.to_vec());
+        let arkworks = seal.to_bytes(Encoding::Arkworks);
+        assert_eq!(
+            Proof::<Bn254>::deserialize_uncompressed(&*arkworks).unwrap(),
+            verifier.proof().unwrap()
+        );
+        assert_eq!(
+            Seal::from_bytes(&verifier.encoded_proof, Encoding::Arkworks).unwrap(),
+            seal
+        );
+        for encoding in [Encoding::Snarkjs, Encoding::Arkworks, Encoding::Evm] {
+            let bytes = seal.to_bytes(encoding);
+            assert_eq!(Seal::from_bytes(&bytes, encoding).unwrap(), seal);
+        }
+        assert_ne!(
+            seal.to_bytes(Encoding::Snarkjs),
+            seal.to_bytes(Encoding::Evm)
+        );
+    }
+
+    #[test]
+    fn test_seal_from_calldata() {
+        let seal = Fixture::load().seal;
+        let mut calldata = Seal::VERIFY_PROOF_SELECTOR.to_vec();
+        calldata.extend(seal.to_vec());
+        calldata.extend([0u8; 5 * 32]);
+        assert_eq!(Seal::from_calldata(&calldata).unwrap(), seal);
+        let zero = fr_from_hex_string("00").unwrap();
+        assert_eq!(seal.to_calldata(&[zero; 5], false).unwrap(), calldata);
+        assert!(seal.to_calldata(&[zero; 4], false).is_err());
+
+        // Calldata with a negated `a`.
+        let negated = seal.to_calldata(&[zero; 5], true).unwrap();
+        assert_eq!(negated[4 + 64..], calldata[4 + 64..]);
+        let negated = Seal::from_calldata(&negated).unwrap();
+        assert_eq!(negated, seal.negate_a().unwrap());
+        assert_eq!(
+            Encoding::Evm.g1(&negated.a).unwrap(),
+            -Encoding::Evm.g1(&seal.a).unwrap()
+        );
+        assert_eq!(negated.negate_a().unwrap(), seal);
+
+        // Truncated calldata.
+        let err = Seal::from_calldata(&calldata[..calldata.len() - 1]).unwrap_err();
+        assert_eq!(
+            err.downcast_ref::<MalformedInput>(),
+            Some(&MalformedInput::SealLength {
+                expected: calldata.len(),
+                found: calldata.len() - 1,
+            })
+        );
+
+        // Wrong function selector.
+        calldata[0] ^= 1;
+        let err = Seal::from_calldata(&calldata).unwrap_err();
+        assert_eq!(
+            err.downcast_ref::<MalformedInput>(),
+            Some(&MalformedInput::FunctionSelector {
+                found: [0x35, 0xba, 0xea, 0xb9],
+            })
+        );
+    }
+
+    #[test]
+    fn test_seal_ref() {
+        let Fixture {
+            pvk,
+            seal,
+            public_inputs,
+            ..
+        } = Fixture::load();
+        let bytes = seal.to_vec();
+        let seal_ref = SealRef::from_bytes(&bytes).unwrap();
+        assert_eq!(seal_ref, SealRef::try_from(&seal).unwrap());
+        assert_eq!(seal_ref.to_seal(), seal);
+
+        let verifier =
+            Verifier::from_seal_ref(seal_ref, public_inputs.clone(), pvk.clone()).unwrap();
+        verifier.verify().unwrap();
+        assert_eq!(
+            verifier.encoded_proof,
+            Verifier::new(&seal, public_inputs, pvk)
+                .unwrap()
+                .encoded_proof
+        );
+
+        assert!(SealRef::from_bytes(&bytes[1..]).is_err());
+        let mut malformed = seal.clone();
+        malformed.b[1].pop();
+        assert!(SealRef::try_from(&malformed).is_err());
+    }
+
+    #[test]
+    fn test_seal_compression() {
+        let seal = Fixture::load().seal;
+        let compressed = seal.compress().unwrap();
+        assert_eq!(compressed.len(), 128);
+        assert_eq!(Seal::decompress(&compressed).unwrap(), seal);
+
+        // The compressed points hold the x coordinates of the seal, with the
+        // flags in the most significant bits.
+        let mut x = compressed[..32].to_vec();
+        x[0] &= 0x3f;
+        assert_eq!(x, seal.a[0]);
+        let mut x = compressed[32..96].to_vec();
+        x[0] &= 0x3f;
+        assert_eq!(x, [seal.b[0][0].clone(), seal.b[0][1].clone()].concat());
+
+        // Flipping the sign of y decompresses to a different point.
+        let mut negated = compressed.clone();
+        negated[0] ^= 0x80;
+        assert_ne!(Seal::decompress(&negated).unwrap().a, seal.a);
+
+        assert!(Seal::decompress(&compressed[1..]).is_err());
+        assert!(Seal::decompress(&[0xff; 128]).is_err());
+    }
+
+    #[test]
+    fn test_seal_normalize() {
+        let Fixture {
+            pvk,
+            seal,
+            public_inputs,
+            ..
+        } = Fixture::load();
+
+        // Negate a and b through the arkworks encoding of the proof.
+        let verifier = Verifier::new(&seal, public_inputs.clone(), pvk.clone()).unwrap();
+        let proof = verifier.proof().unwrap();
+        let negated = Proof::<Bn254> {
+            a: -proof.a,
+            b: -proof.b,
+            c: proof.c,
+        };
+        let mut encoded = Vec::new();
+        negated.serialize_uncompressed(&mut encoded).unwrap();
+        let negated = Seal::from_bytes(&encoded, Encoding::Arkworks).unwrap();
+        assert_ne!(negated, seal);
+
+        let normalized = seal.normalize().unwrap();
+        assert_eq!(negated.normalize().unwrap(), normalized);
+        assert_eq!(normalized.normalize().unwrap(), normalized);
+        assert!(normalized.is_normalized().unwrap());
+        assert_ne!(
+            seal.is_normalized().unwrap(),
+            negated.is_normalized().unwrap()
+        );
+
+        // Both seals verify, but only the normalized one in strict mode.
+        for seal in [&seal, &negated] {
+            let verifier = Verifier::new(seal, public_inputs.clone(), pvk.clone()).unwrap();
+            verifier.verify().unwrap();
+            if seal == &normalized {
+                verifier.verify_strict().unwrap();
+            } else {
+                let err = verifier.verify_strict().unwrap_err();
+                assert_eq!(
+                    err.downcast_ref::<MalformedInput>(),
+                    Some(&MalformedInput::NonCanonicalSeal)
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_malformed_input() {
+        let Fixture {
+            pvk,
+            seal,
+            public_inputs,
+            ..
+        } = Fixture::load();
+        let malformed = |err: anyhow::Error| *err.downcast_ref::<MalformedInput>().unwrap();
+
+        let bytes = seal.to_vec();
+        assert_eq!(
+            malformed(SealRef::from_bytes(&bytes[1..]).unwrap_err()),
+            MalformedInput::SealLength {
+                expected: bytes.len(),
+                found: bytes.len() - 1
+            }
+        );
+
+        let mut tampered = bytes.clone();
+        tampered[31] ^= 1;
+        let err = Verifier::from_seal_ref(
+            SealRef::from_bytes(&tampered).unwrap(),
+            public_inputs.clone(),
+            pvk.clone(),
+        )
+        .unwrap_err();
+        assert_eq!(malformed(err), MalformedInput::InvalidPoint("a"));
+
+        let err = Verifier::new(&seal, public_inputs[1..].to_vec(), pvk).unwrap_err();
+        assert_eq!(
+            malformed(err),
+            MalformedInput::PublicInputsLength {
+                expected: public_inputs.len(),
+                found: public_inputs.len() - 1
+            }
+        );
+
+        let mut registry = Registry::new();
+        registry.insert(
+            "v1",
+            VersionedKey {
+                pvk: prepared_verifying_key().unwrap(),
+                allowed_ids_root: Digest::from([1, 2, 3, 4, 5, 6, 7, 8]),
+                bn254_control_id: fr_from_hex_string("01").unwrap(),
+            },
+        );
+        let err = Verifier::from_seal_versioned(
+            &registry,
+            "v1",
+            SealRef::try_from(&seal).unwrap(),
+            Digest::ZERO,
+        )
+        .unwrap_err();
+        assert_eq!(malformed(err), MalformedInput::ZeroClaimDigest);
+    }
+
+    #[test]
+    fn test_seal_version() {
+        let seal = Fixture::load().seal;
+        let bytes = seal.to_versioned_bytes();
+        assert_eq!(bytes[..4], SEAL_VERSION.to_be_bytes());
+        assert_eq!(Seal::from_versioned_bytes(&bytes).unwrap(), seal);
+        assert!(Seal::from_versioned_bytes(&bytes[1..]).is_err());
+
+        let mut future = bytes.clone();
+        future[..4].copy_from_slice(&(SEAL_VERSION + 1).to_be_bytes());
+        let err = Seal::from_versioned_bytes(&future).unwrap_err();
+        assert_eq!(
+            err.downcast_ref::<MalformedInput>(),
+            Some(&MalformedInput::SealVersion {
+                found: SEAL_VERSION + 1,
+                supported: SEAL_VERSION,
+            })
+        );
+        assert_eq!(
+            err.to_string(),
+            format!(
+                "seal version {}, verifier supports {SEAL_VERSION}",
+                SEAL_VERSION + 1
+            )
+        );
+    }
+
+    #[test]
+    fn test_registry() {
+        let seal = Fixture::load().seal;
+        let allowed_ids_root = Digest::from([1, 2, 3, 4, 5, 6, 7, 8]);
+        let claim_digest = Digest::from([8, 7, 6, 5, 4, 3, 2, 1]);
+        let bn254_control_id = fr_from_hex_string("01").unwrap();
+
+        let mut registry = Registry::new();
+        let key = VersionedKey {
+            pvk: prepared_verifying_key().unwrap(),
+            allowed_ids_root,
+            bn254_control_id,
+        };
+        assert!(registry.insert("v1", key.clone()).is_none());
+        assert!(registry.insert("v0", key.clone()).is_none());
+        assert!(registry.insert("v1", key).is_some());
+        assert_eq!(registry.versions().collect::<Vec<_>>(), ["v0", "v1"]);
+
+        let seal = SealRef::try_from(&seal).unwrap();
+        assert!(Verifier::from_seal_versioned(&registry, "v2", seal, claim_digest).is_err());
+
+        let verifier = Verifier::from_seal_versioned(&registry, "v1", seal, claim_digest).unwrap();
+        let (a0, a1) = split_digest(allowed_ids_root).unwrap();
+        let (c0, c1) = split_digest(claim_digest).unwrap();
+        assert_eq!(
+            verifier.public_inputs().unwrap(),
+            vec![a0, a1, c0, c1, bn254_control_id]
+        );
+        // The test proof was not produced by the registered circuit.
+        assert!(verifier.verify().is_err());
+
+        // A seal verifies with the key of the circuit that produced it.
+        let (pvk, trapdoor) = trapdoor_seal(&[a0, a1, c0, c1, bn254_control_id]);
+        registry.insert(
+            "v2",
+            VersionedKey {
+                pvk,
+                allowed_ids_root,
+                bn254_control_id,
+            },
+        );
+        let trapdoor = SealRef::try_from(&trapdoor).unwrap();
+        Verifier::from_seal_versioned(&registry, "v2", trapdoor, claim_digest)
+            .unwrap()
+            .verify()
+            .unwrap();
+        let other_claim = Digest::from([1; 8]);
+        assert!(
+            Verifier::from_seal_versioned(&registry, "v2", trapdoor, other_claim)
+                .unwrap()
+                .verify()
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_allowed_roots() {
+        let root0 = Digest::from([1, 2, 3, 4, 5, 6, 7, 8]);
+        let root1 = Digest::from([8, 7, 6, 5, 4, 3, 2, 1]);
+        let roots = AllowedRoots::from(root0).with_root(root1).with_root(root0);
+        assert_eq!(roots.iter().collect::<Vec<_>>(), [&root0, &root1]);
+        assert_eq!(roots, [root0, root1, root1].into_iter().collect());
+        assert!(roots.contains(&root1));
+        assert!(!roots.contains(&Digest::ZERO));
+
+        let seal = Fixture::load().seal;
+        let seal = SealRef::try_from(&seal).unwrap();
+        let key = for_version(CircuitVersion::CURRENT).unwrap();
+        let claim_digest = Digest::from([1; 8]);
+        let err = Verifier::verify_allowed_roots(&key, &roots, seal, Digest::ZERO).unwrap_err();
+        assert_eq!(
+            err.downcast_ref::<MalformedInput>(),
+            Some(&MalformedInput::ZeroClaimDigest)
+        );
+        // The test proof was not produced by the recursion circuit.
+        assert!(Verifier::verify_allowed_roots(&key, &roots, seal, claim_digest).is_err());
+        assert!(
+            Verifier::verify_allowed_roots(&key, &AllowedRoots::new(), seal, claim_digest).is_err()
+        );
+
+        // A seal produced under one of the allowed roots verifies, returning
+        // that root.
+        let (r0, r1) = split_digest(root1).unwrap();
+        let (c0, c1) = split_digest(claim_digest).unwrap();
+        let (pvk, trapdoor) = trapdoor_seal(&[r0, r1, c0, c1, key.bn254_control_id]);
+        let key = VersionedKey { pvk, ..key };
+        let trapdoor = SealRef::try_from(&trapdoor).unwrap();
+        assert_eq!(
+            Verifier::verify_allowed_roots(&key, &roots, trapdoor, claim_digest).unwrap(),
+            root1
+        );
+        assert!(Verifier::verify_allowed_roots(
+            &key,
+            &AllowedRoots::from(root0),
+            trapdoor,
+            claim_digest
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_embedded_keys() {
+        use hex::FromHex;
+        use risc0_circuit_recursion::control_id::{ALLOWED_IDS_ROOT, BN254_CONTROL_ID};
+
+        let key = for_version(CircuitVersion::CURRENT).unwrap();
+        assert_eq!(key.pvk, prepared_verifying_key().unwrap());
+        assert_eq!(
+            key.allowed_ids_root,
+            Digest::from_hex(ALLOWED_IDS_ROOT).unwrap()
+        );
+        assert_eq!(
+            key.bn254_control_id,
+            fr_from_hex_string(BN254_CONTROL_ID).unwrap()
+        );
+
+        let registry = Registry::embedded().unwrap();
+        assert_eq!(
+            registry.versions().collect::<Vec<_>>(),
+            CircuitVersion::ALL
+                .iter()
+                .map(|version| version.as_str())
+                .collect::<Vec<_>>()
+        );
+        assert!(registry.get(&CircuitVersion::CURRENT.to_string()).is_ok());
+
+        let previous = registry.get(&CircuitVersion::V0_21.to_string()).unwrap();
+        assert_eq!(previous.pvk, key.pvk);
+        assert_eq!(previous.allowed_ids_root, key.allowed_ids_root);
+    }
+
+    #[cfg(feature = "borsh")]
+    #[test]
+    fn test_borsh_roundtrip() {
+        let fixture = Fixture::load();
+        let seal = &fixture.seal;
+        let encoded = borsh::to_vec(seal).unwrap();
+        assert_eq!(&borsh::from_slice::<Seal>(&encoded).unwrap(), seal);
+
+        let verifier = fixture.verifier();
+        let encoded = borsh::to_vec(&verifier).unwrap();
+        let decoded: Verifier = borsh::from_slice(&encoded).unwrap();
+        assert_eq!(decoded.encoded_proof, verifier.encoded_proof);
+        decoded.verify().unwrap();
+    }
 }
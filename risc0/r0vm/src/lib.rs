@@ -16,8 +16,12 @@ use std::{fs, io, path::PathBuf, rc::Rc};
 
 use clap::{Args, Parser, ValueEnum};
 use risc0_zkvm::{
-    get_prover_server, ApiServer, ExecutorEnv, ExecutorImpl, ProverOpts, ProverServer,
-    VerifierContext,
+    get_prover_server, ApiServer, Artifact, ChromeTraceLayer, ExecutorEnv, ExecutorImpl,
+    ProverOpts, ProverServer, VerifierContext,
+};
+use tracing_subscriber::{
+    filter::{EnvFilter, LevelFilter},
+    prelude::*,
 };
 
 /// Runs a RISC-V ELF binary within the RISC Zero ZKVM.
@@ -63,6 +67,12 @@ struct Cli {
     /// to read it.
     #[arg(long, env = "RISC0_PPROF_OUT")]
     pprof_out: Option<PathBuf>,
+
+    /// Write the timings of the execution and proving phases to this file,
+    /// as a Chrome trace which can be loaded in Perfetto
+    /// (<https://ui.perfetto.dev>).
+    #[arg(long, env = "RISC0_CHROME_TRACE")]
+    chrome_trace: Option<PathBuf>,
 }
 
 #[derive(Args)]
@@ -89,11 +99,18 @@ enum HashFn {
 }
 
 pub fn main() {
-    tracing_subscriber::fmt()
-        .with_env_filter(tracing_subscriber::filter::EnvFilter::from_default_env())
-        .init();
-
     let args = Cli::parse();
+
+    // The trace is written when the guard is dropped, at the end of main.
+    let (chrome_trace, _chrome_trace_guard) = args
+        .chrome_trace
+        .as_ref()
+        .map(ChromeTraceLayer::new)
+        .unzip();
+    tracing_subscriber::registry()
+        .with(tracing_subscriber::fmt::layer().with_filter(EnvFilter::from_default_env()))
+        .with(chrome_trace.with_filter(LevelFilter::INFO))
+        .init();
     if let Some(port) = args.mode.port {
         run_server(port);
         return;
@@ -140,7 +157,7 @@ pub fn main() {
     let ctx = VerifierContext::default();
     let receipt = prover.prove_session(&ctx, &session).unwrap();
 
-    let receipt_data = bincode::serialize(&receipt).unwrap();
+    let receipt_data = receipt.to_versioned_bytes().unwrap();
     let receipt_bytes = bytemuck::cast_slice(&receipt_data);
     if let Some(receipt_file) = args.receipt.as_ref() {
         fs::write(receipt_file, receipt_bytes).expect("Unable to write receipt file");
@@ -163,6 +180,7 @@ impl Cli {
         let opts = ProverOpts {
             hashfn: hashfn.to_string(),
             prove_guest_errors: self.prove_guest_errors,
+            ..Default::default()
         };
 
         get_prover_server(&opts).unwrap()
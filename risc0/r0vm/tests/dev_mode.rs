@@ -14,7 +14,7 @@
 
 use assert_cmd::Command;
 use assert_fs::{fixture::PathChild, TempDir};
-use risc0_zkvm::{serde::to_vec, Receipt};
+use risc0_zkvm::{serde::to_vec, Artifact, Receipt};
 use risc0_zkvm_methods::{multi_test::MultiTestSpec, MULTI_TEST_PATH};
 
 fn run_dev_mode() -> Receipt {
@@ -33,7 +33,7 @@ fn run_dev_mode() -> Receipt {
     cmd.assert().success();
 
     let data = std::fs::read(receipt_file).unwrap();
-    bincode::deserialize(&data).unwrap()
+    Receipt::from_versioned_bytes(&data).unwrap()
 }
 
 #[test]
@@ -16,7 +16,7 @@ use std::path::Path;
 
 use assert_cmd::Command;
 use assert_fs::{fixture::PathChild, TempDir};
-use risc0_zkvm::Receipt;
+use risc0_zkvm::{Artifact, Receipt};
 use risc0_zkvm_methods::STANDARD_LIB_ID;
 
 const STDIN_MSG: &str = "Hello world from stdin!\n";
@@ -29,7 +29,7 @@ fn expected_stdout() -> String {
 
 fn load_receipt(path: &Path) -> Receipt {
     let data = std::fs::read(path).unwrap();
-    bincode::deserialize(&data).unwrap()
+    Receipt::from_versioned_bytes(&data).unwrap()
 }
 
 #[test]
@@ -22,6 +22,7 @@ use bytemuck::{Pod, PodCastError, Zeroable};
 use hex::{FromHex, FromHexError};
 pub use risc0_zkvm_platform::WORD_SIZE;
 use serde::{Deserialize, Serialize};
+use subtle::ConstantTimeEq;
 
 /// The number of words in the representation of a [Digest].
 pub const DIGEST_WORDS: usize = 8;
@@ -70,6 +71,15 @@ impl Digest {
     pub fn as_mut_bytes(&mut self) -> &mut [u8] {
         bytemuck::cast_slice_mut(&mut self.0)
     }
+
+    /// Compares two digests in constant time, with [ConstantTimeEq].
+    ///
+    /// Unlike `==`, which returns at the first differing word, every word is
+    /// compared, so the time taken does not reveal the length of the common
+    /// prefix. Use this when one of the digests is derived from secret data.
+    pub fn ct_eq(&self, other: &Digest) -> bool {
+        self.0[..].ct_eq(&other.0[..]).into()
+    }
 }
 
 impl Default for Digest {
@@ -253,6 +263,18 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_ct_eq() {
+        let digest = Digest::from([1, 2, 3, 4, 5, 6, 7, 8]);
+        assert!(digest.ct_eq(&digest.clone()));
+        assert!(Digest::ZERO.ct_eq(&Digest::default()));
+        for i in 0..8 {
+            let mut other = digest;
+            other.as_mut_words()[i] ^= 1 << i;
+            assert!(!digest.ct_eq(&other));
+        }
+    }
+
     #[test]
     fn test_roundtrip() {
         const HEX: &str = "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad";
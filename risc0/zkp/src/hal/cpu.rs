@@ -607,12 +607,14 @@ impl<F: Field> Hal for CpuHal<F> {
 
 #[cfg(test)]
 mod tests {
+    use std::rc::Rc;
+
     use hex::FromHex;
     use rand::thread_rng;
     use risc0_core::field::baby_bear::BabyBear;
 
     use super::*;
-    use crate::core::hash::sha::Sha256HashSuite;
+    use crate::{core::hash::sha::Sha256HashSuite, hal::testutil};
 
     #[test]
     #[should_panic]
@@ -623,6 +625,12 @@ mod tests {
         hal.eltwise_add_elem(&a, &b, &b);
     }
 
+    #[test]
+    fn ntt_round_trip() {
+        let hal: CpuHal<BabyBear> = CpuHal::new(Sha256HashSuite::new_suite());
+        testutil::ntt_round_trip(Rc::new(hal));
+    }
+
     #[test]
     fn fp() {
         let hal: CpuHal<BabyBear> = CpuHal::new(Sha256HashSuite::new_suite());
@@ -17,6 +17,7 @@ use std::{cell::RefCell, fmt::Debug, marker::PhantomData, rc::Rc};
 use bytemuck::Pod;
 use cust::{
     device::DeviceAttribute,
+    error::CudaError,
     function::{BlockSize, GridSize},
     memory::{DevicePointer, GpuBuffer},
     prelude::*,
@@ -28,7 +29,7 @@ use risc0_core::field::{
 };
 use risc0_sys::cuda::*;
 
-use super::{Buffer, Hal, TRACKER};
+use super::{Buffer, Hal, OutOfMemory, TRACKER};
 use crate::{
     core::{
         digest::Digest,
@@ -323,11 +324,13 @@ struct RawBuffer {
 impl RawBuffer {
     pub fn new(name: &'static str, size: usize) -> Self {
         tracing::trace!("alloc: {size} bytes, {name}");
+        let buf = match unsafe { DeviceBuffer::uninitialized(size) } {
+            Ok(buf) => buf,
+            Err(CudaError::OutOfMemory) => std::panic::panic_any(OutOfMemory { name, size }),
+            Err(err) => panic!("{err}"),
+        };
         TRACKER.lock().unwrap().alloc(size);
-        Self {
-            name,
-            buf: unsafe { DeviceBuffer::uninitialized(size).unwrap() },
-        }
+        Self { name, buf }
     }
 }
 
@@ -858,6 +861,8 @@ fn div_ceil(a: u32, b: u32) -> u32 {
 
 #[cfg(test)]
 mod tests {
+    use std::rc::Rc;
+
     use serial_test::serial;
     use test_log::test;
 
@@ -867,108 +872,108 @@ mod tests {
     #[test]
     #[should_panic]
     fn check_req() {
-        testutil::check_req(CudaHalSha256::new());
+        testutil::check_req(Rc::new(CudaHalSha256::new()));
     }
 
     #[test]
     #[serial]
     fn eltwise_add_elem() {
-        testutil::eltwise_add_elem(CudaHalSha256::new());
+        testutil::eltwise_add_elem(Rc::new(CudaHalSha256::new()));
     }
 
     #[test]
     #[serial]
     fn eltwise_copy_elem() {
-        testutil::eltwise_copy_elem(CudaHalSha256::new());
+        testutil::eltwise_copy_elem(Rc::new(CudaHalSha256::new()));
     }
 
     #[test]
     #[serial]
     fn eltwise_sum_extelem() {
-        testutil::eltwise_sum_extelem(CudaHalSha256::new());
+        testutil::eltwise_sum_extelem(Rc::new(CudaHalSha256::new()));
     }
 
     #[test]
     #[serial]
     fn hash_rows_sha256() {
-        testutil::hash_rows(CudaHalSha256::new());
+        testutil::hash_rows(Rc::new(CudaHalSha256::new()));
     }
 
     #[test]
     #[serial]
     fn hash_fold_sha256() {
-        testutil::hash_fold(CudaHalSha256::new());
+        testutil::hash_fold(Rc::new(CudaHalSha256::new()));
     }
 
     #[test]
     #[serial]
     fn hash_rows_poseidon() {
-        testutil::hash_rows(CudaHalPoseidon::new());
+        testutil::hash_rows(Rc::new(CudaHalPoseidon::new()));
     }
 
     #[test]
     #[serial]
     fn hash_fold_poseidon() {
-        testutil::hash_fold(CudaHalPoseidon::new());
+        testutil::hash_fold(Rc::new(CudaHalPoseidon::new()));
     }
 
     #[test]
     #[serial]
     fn hash_rows_poseidon2() {
-        testutil::hash_rows(CudaHalPoseidon2::new());
+        testutil::hash_rows(Rc::new(CudaHalPoseidon2::new()));
     }
 
     #[test]
     #[serial]
     fn hash_fold_poseidon2() {
-        testutil::hash_fold(CudaHalPoseidon2::new());
+        testutil::hash_fold(Rc::new(CudaHalPoseidon2::new()));
     }
 
     #[test]
     #[serial]
     fn fri_fold() {
-        testutil::fri_fold(CudaHalSha256::new());
+        testutil::fri_fold(Rc::new(CudaHalSha256::new()));
     }
 
     #[test]
     #[serial]
     fn batch_expand_into_evaluate_ntt() {
-        testutil::batch_expand_into_evaluate_ntt(CudaHalSha256::new());
+        testutil::batch_expand_into_evaluate_ntt(Rc::new(CudaHalSha256::new()));
     }
 
     #[test]
     #[serial]
     fn batch_interpolate_ntt() {
-        testutil::batch_interpolate_ntt(CudaHalSha256::new());
+        testutil::batch_interpolate_ntt(Rc::new(CudaHalSha256::new()));
     }
 
     #[test]
     #[serial]
     fn batch_bit_reverse() {
-        testutil::batch_bit_reverse(CudaHalSha256::new());
+        testutil::batch_bit_reverse(Rc::new(CudaHalSha256::new()));
     }
 
     #[test]
     #[serial]
     fn batch_evaluate_any() {
-        testutil::batch_evaluate_any(CudaHalSha256::new());
+        testutil::batch_evaluate_any(Rc::new(CudaHalSha256::new()));
     }
 
     #[test]
     #[serial]
     fn gather_sample() {
-        testutil::gather_sample(CudaHalSha256::new());
+        testutil::gather_sample(Rc::new(CudaHalSha256::new()));
     }
 
     #[test]
     #[serial]
     fn zk_shift() {
-        testutil::zk_shift(CudaHalSha256::new());
+        testutil::zk_shift(Rc::new(CudaHalSha256::new()));
     }
 
     #[test]
     #[serial]
     fn mix_poly_coeffs() {
-        testutil::mix_poly_coeffs(CudaHalSha256::new());
+        testutil::mix_poly_coeffs(Rc::new(CudaHalSha256::new()));
     }
 }
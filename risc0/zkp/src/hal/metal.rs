@@ -825,6 +825,8 @@ fn compute_launch_params(n_bits: u32, s_bits: u32, c_size: u32) -> (MTLSize, MTL
 
 #[cfg(test)]
 mod tests {
+    use std::rc::Rc;
+
     use test_log::test;
 
     use super::{MetalHalPoseidon, MetalHalPoseidon2, MetalHalSha256};
@@ -832,97 +834,97 @@ mod tests {
 
     #[test]
     fn batch_bit_reverse() {
-        testutil::batch_bit_reverse(MetalHalSha256::new());
+        testutil::batch_bit_reverse(Rc::new(MetalHalSha256::new()));
     }
 
     #[test]
     fn batch_evaluate_any() {
-        testutil::batch_evaluate_any(MetalHalSha256::new());
+        testutil::batch_evaluate_any(Rc::new(MetalHalSha256::new()));
     }
 
     #[test]
     fn batch_expand_into_evaluate_ntt() {
-        testutil::batch_expand_into_evaluate_ntt(MetalHalSha256::new());
+        testutil::batch_expand_into_evaluate_ntt(Rc::new(MetalHalSha256::new()));
     }
 
     #[test]
     fn batch_interpolate_ntt() {
-        testutil::batch_interpolate_ntt(MetalHalSha256::new());
+        testutil::batch_interpolate_ntt(Rc::new(MetalHalSha256::new()));
     }
 
     #[test]
     #[should_panic]
     fn check_req() {
-        testutil::check_req(MetalHalSha256::new());
+        testutil::check_req(Rc::new(MetalHalSha256::new()));
     }
 
     #[test]
     fn eltwise_add_fp() {
-        testutil::eltwise_add_elem(MetalHalSha256::new());
+        testutil::eltwise_add_elem(Rc::new(MetalHalSha256::new()));
     }
 
     #[test]
     fn eltwise_copy_fp() {
-        testutil::eltwise_copy_elem(MetalHalSha256::new());
+        testutil::eltwise_copy_elem(Rc::new(MetalHalSha256::new()));
     }
 
     #[test]
     fn eltwise_sum_extelem() {
-        testutil::eltwise_sum_extelem(MetalHalSha256::new());
+        testutil::eltwise_sum_extelem(Rc::new(MetalHalSha256::new()));
     }
 
     #[test]
     fn fri_fold() {
-        testutil::fri_fold(MetalHalSha256::new());
+        testutil::fri_fold(Rc::new(MetalHalSha256::new()));
     }
 
     #[test]
     fn mix_poly_coeffs() {
-        testutil::mix_poly_coeffs(MetalHalSha256::new());
+        testutil::mix_poly_coeffs(Rc::new(MetalHalSha256::new()));
     }
 
     #[test]
     fn hash_fold_sha256() {
-        testutil::hash_fold(MetalHalSha256::new());
+        testutil::hash_fold(Rc::new(MetalHalSha256::new()));
     }
 
     #[test]
     fn hash_rows_sha256() {
-        testutil::hash_rows(MetalHalSha256::new());
+        testutil::hash_rows(Rc::new(MetalHalSha256::new()));
     }
 
     #[test]
     fn hash_fold_poseidon() {
-        testutil::hash_fold(MetalHalPoseidon::new());
+        testutil::hash_fold(Rc::new(MetalHalPoseidon::new()));
     }
 
     #[test]
     fn hash_rows_poseidon() {
-        testutil::hash_rows(MetalHalPoseidon::new());
+        testutil::hash_rows(Rc::new(MetalHalPoseidon::new()));
     }
 
     #[test]
     fn hash_fold_poseidon2() {
-        testutil::hash_fold(MetalHalPoseidon2::new());
+        testutil::hash_fold(Rc::new(MetalHalPoseidon2::new()));
     }
 
     #[test]
     fn hash_rows_poseidon2() {
-        testutil::hash_rows(MetalHalPoseidon2::new());
+        testutil::hash_rows(Rc::new(MetalHalPoseidon2::new()));
     }
 
     #[test]
     fn slice() {
-        testutil::slice(MetalHalSha256::new());
+        testutil::slice(Rc::new(MetalHalSha256::new()));
     }
 
     #[test]
     fn zk_shift() {
-        testutil::zk_shift(MetalHalSha256::new());
+        testutil::zk_shift(Rc::new(MetalHalSha256::new()));
     }
 
     #[test]
     fn gather_sample() {
-        testutil::gather_sample(MetalHalSha256::new());
+        testutil::gather_sample(Rc::new(MetalHalSha256::new()));
     }
 }
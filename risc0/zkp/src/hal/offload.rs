@@ -0,0 +1,928 @@
+// Copyright 2024 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Offloading of hashing and NTTs to an external accelerator, such as an FPGA.
+//!
+//! An [Accelerator] performs the most expensive operations of the prover on
+//! buffers transferred from the host. The [OffloadHal] runs everything else on
+//! the CPU, and falls back to the CPU for the offloaded operations when the
+//! accelerator is absent or fails.
+//!
+//! Accelerators in another process or on another machine are reached through
+//! the [TcpAccelerator] transport, and served with [serve]. [CpuAccelerator]
+//! is a reference implementation in software, to test transports and
+//! accelerators against.
+//!
+//! Field elements are transferred as `u32` words, in the in-memory
+//! representation of [BabyBearElem], and digests as eight `u32` words.
+
+use std::{
+    cell::Cell,
+    io::{BufReader, BufWriter, Read, Write},
+    net::{TcpListener, TcpStream},
+    panic::{catch_unwind, AssertUnwindSafe},
+    sync::Mutex,
+};
+
+use anyhow::{anyhow, bail, Result};
+use risc0_core::field::{
+    baby_bear::{BabyBear, BabyBearElem, BabyBearExtElem},
+    RootsOfUnity,
+};
+
+use super::{
+    cpu::{CpuBuffer, CpuHal},
+    Buffer, CircuitHal, Hal,
+};
+use crate::core::{
+    digest::Digest,
+    hash::{
+        poseidon::PoseidonHashSuite, poseidon2::Poseidon2HashSuite,
+        poseidon_254::Poseidon254HashSuite, sha::Sha256HashSuite, HashSuite,
+    },
+};
+
+/// Operations offloaded by the [OffloadHal].
+///
+/// Each call transfers its inputs to the device, and returns once the results
+/// are transferred back. Hashes are named by [HashSuite::name], e.g. `sha-256`
+/// or `poseidon2`. An error makes the [OffloadHal] stop using the accelerator.
+pub trait Accelerator {
+    /// Returns the name of the device, for logging.
+    fn name(&self) -> String;
+
+    /// Returns whether the device implements the hash of the suite `suite`.
+    fn supports_hash(&self, suite: &str) -> Result<bool>;
+
+    /// Hashes each of the `rows` columns of the row-major `matrix`, as
+    /// [Hal::hash_rows] does.
+    fn hash_rows(&self, suite: &str, matrix: &[u32], rows: usize) -> Result<Vec<Digest>>;
+
+    /// Hashes each pair of consecutive digests of `input`, as [Hal::hash_fold]
+    /// does.
+    fn hash_fold(&self, suite: &str, input: &[Digest]) -> Result<Vec<Digest>>;
+
+    /// Interpolates `count` polynomials from their evaluations in `io`, as
+    /// [Hal::batch_interpolate_ntt] does.
+    fn batch_interpolate_ntt(&self, io: &mut [u32], count: usize) -> Result<()>;
+
+    /// Evaluates `count` polynomials of `input` on a domain expanded by
+    /// `2^expand_bits` into `output`, as [Hal::batch_expand_into_evaluate_ntt]
+    /// does.
+    fn batch_expand_into_evaluate_ntt(
+        &self,
+        output: &mut [u32],
+        input: &[u32],
+        count: usize,
+        expand_bits: usize,
+    ) -> Result<()>;
+}
+
+/// A [Hal] offloading hashing and NTTs to an [Accelerator], and running the
+/// rest on the CPU.
+///
+/// If there is no accelerator, or once it fails or lacks the hash of the
+/// suite, all operations run on the CPU, so the results are the same.
+pub struct OffloadHal {
+    cpu: CpuHal<BabyBear>,
+    device: Option<Box<dyn Accelerator>>,
+    hash_offloaded: bool,
+    failed: Cell<bool>,
+}
+
+impl OffloadHal {
+    /// Creates a HAL offloading to `device`, if any.
+    pub fn new(suite: HashSuite<BabyBear>, device: Option<Box<dyn Accelerator>>) -> Self {
+        let hash_offloaded = match &device {
+            Some(device) => match device.supports_hash(&suite.name) {
+                Ok(supported) => supported,
+                Err(err) => {
+                    tracing::warn!("accelerator {}: {err}", device.name());
+                    false
+                }
+            },
+            None => false,
+        };
+        Self {
+            cpu: CpuHal::new(suite),
+            device,
+            hash_offloaded,
+            failed: Cell::new(false),
+        }
+    }
+
+    /// Creates a HAL offloading to the accelerator at the address in the
+    /// `RISC0_ACCELERATOR` environment variable, or to no accelerator if it is
+    /// unset or cannot be reached.
+    pub fn from_env(suite: HashSuite<BabyBear>) -> Self {
+        let device =
+            std::env::var("RISC0_ACCELERATOR")
+                .ok()
+                .and_then(|addr| match TcpAccelerator::connect(&addr) {
+                    Ok(device) => Some(Box::new(device) as Box<dyn Accelerator>),
+                    Err(err) => {
+                        tracing::warn!("no accelerator at {addr}, proving on the CPU: {err}");
+                        None
+                    }
+                });
+        Self::new(suite, device)
+    }
+
+    /// Returns whether operations are still offloaded to the accelerator.
+    pub fn is_offloading(&self) -> bool {
+        self.device.is_some() && !self.failed.get()
+    }
+
+    /// Runs `op` on the accelerator, and returns whether it succeeded.
+    fn offload(&self, name: &str, op: impl FnOnce(&dyn Accelerator) -> Result<()>) -> bool {
+        let Some(device) = self.device.as_deref() else {
+            return false;
+        };
+        if self.failed.get() {
+            return false;
+        }
+        match op(device) {
+            Ok(()) => true,
+            Err(err) => {
+                tracing::warn!(
+                    "accelerator {} failed {name}, falling back to the CPU: {err}",
+                    device.name()
+                );
+                self.failed.set(true);
+                false
+            }
+        }
+    }
+}
+
+fn words(elems: &[BabyBearElem]) -> &[u32] {
+    bytemuck::cast_slice(elems)
+}
+
+/// Returns the size of each of `count` polynomials of `len` elements in total,
+/// which must be a power of two fitting an NTT expanded by `2^expand_bits`.
+fn ntt_row_size(len: usize, count: usize, expand_bits: usize) -> Result<usize> {
+    if count == 0 || len % count != 0 {
+        bail!("{len} elements are not {count} polynomials");
+    }
+    let row_size = len / count;
+    if !row_size.is_power_of_two() {
+        bail!("polynomials of {row_size} elements are not a power of two");
+    }
+    let po2 = row_size.trailing_zeros() as usize;
+    if expand_bits > BabyBearElem::MAX_ROU_PO2 || po2 + expand_bits > BabyBearElem::MAX_ROU_PO2 {
+        bail!(
+            "NTT of 2^{po2} elements expanded by 2^{expand_bits} exceeds the maximum of 2^{}",
+            BabyBearElem::MAX_ROU_PO2
+        );
+    }
+    Ok(row_size)
+}
+
+fn check_len<T>(results: &[T], expected: usize) -> Result<()> {
+    if results.len() != expected {
+        bail!("expected {expected} results, got {}", results.len());
+    }
+    Ok(())
+}
+
+impl Hal for OffloadHal {
+    type Field = BabyBear;
+    type Elem = BabyBearElem;
+    type ExtElem = BabyBearExtElem;
+    type Buffer<T: Clone + std::fmt::Debug + PartialEq + bytemuck::Pod> = CpuBuffer<T>;
+
+    fn has_unified_memory(&self) -> bool {
+        true
+    }
+
+    fn get_hash_suite(&self) -> &HashSuite<Self::Field> {
+        self.cpu.get_hash_suite()
+    }
+
+    fn alloc_digest(&self, name: &'static str, size: usize) -> Self::Buffer<Digest> {
+        self.cpu.alloc_digest(name, size)
+    }
+
+    fn alloc_elem(&self, name: &'static str, size: usize) -> Self::Buffer<Self::Elem> {
+        self.cpu.alloc_elem(name, size)
+    }
+
+    fn alloc_extelem(&self, name: &'static str, size: usize) -> Self::Buffer<Self::ExtElem> {
+        self.cpu.alloc_extelem(name, size)
+    }
+
+    fn alloc_u32(&self, name: &'static str, size: usize) -> Self::Buffer<u32> {
+        self.cpu.alloc_u32(name, size)
+    }
+
+    fn copy_from_digest(&self, name: &'static str, slice: &[Digest]) -> Self::Buffer<Digest> {
+        self.cpu.copy_from_digest(name, slice)
+    }
+
+    fn copy_from_elem(&self, name: &'static str, slice: &[Self::Elem]) -> Self::Buffer<Self::Elem> {
+        self.cpu.copy_from_elem(name, slice)
+    }
+
+    fn copy_from_extelem(
+        &self,
+        name: &'static str,
+        slice: &[Self::ExtElem],
+    ) -> Self::Buffer<Self::ExtElem> {
+        self.cpu.copy_from_extelem(name, slice)
+    }
+
+    fn copy_from_u32(&self, name: &'static str, slice: &[u32]) -> Self::Buffer<u32> {
+        self.cpu.copy_from_u32(name, slice)
+    }
+
+    fn batch_expand_into_evaluate_ntt(
+        &self,
+        output: &Self::Buffer<Self::Elem>,
+        input: &Self::Buffer<Self::Elem>,
+        count: usize,
+        expand_bits: usize,
+    ) {
+        let offloaded = self.offload("batch_expand_into_evaluate_ntt", |device| {
+            let mut result = vec![0; output.size()];
+            device.batch_expand_into_evaluate_ntt(
+                &mut result,
+                words(&input.as_slice()),
+                count,
+                expand_bits,
+            )?;
+            output
+                .as_slice_mut()
+                .copy_from_slice(bytemuck::cast_slice(&result));
+            Ok(())
+        });
+        if !offloaded {
+            self.cpu
+                .batch_expand_into_evaluate_ntt(output, input, count, expand_bits);
+        }
+    }
+
+    fn batch_interpolate_ntt(&self, io: &Self::Buffer<Self::Elem>, count: usize) {
+        let offloaded = self.offload("batch_interpolate_ntt", |device| {
+            let mut result = words(&io.as_slice()).to_vec();
+            device.batch_interpolate_ntt(&mut result, count)?;
+            io.as_slice_mut()
+                .copy_from_slice(bytemuck::cast_slice(&result));
+            Ok(())
+        });
+        if !offloaded {
+            self.cpu.batch_interpolate_ntt(io, count);
+        }
+    }
+
+    fn batch_bit_reverse(&self, io: &Self::Buffer<Self::Elem>, count: usize) {
+        self.cpu.batch_bit_reverse(io, count);
+    }
+
+    fn batch_evaluate_any(
+        &self,
+        coeffs: &Self::Buffer<Self::Elem>,
+        poly_count: usize,
+        which: &Self::Buffer<u32>,
+        xs: &Self::Buffer<Self::ExtElem>,
+        out: &Self::Buffer<Self::ExtElem>,
+    ) {
+        self.cpu
+            .batch_evaluate_any(coeffs, poly_count, which, xs, out);
+    }
+
+    fn zk_shift(&self, io: &Self::Buffer<Self::Elem>, count: usize) {
+        self.cpu.zk_shift(io, count);
+    }
+
+    fn mix_poly_coeffs(
+        &self,
+        out: &Self::Buffer<Self::ExtElem>,
+        mix_start: &Self::ExtElem,
+        mix: &Self::ExtElem,
+        input: &Self::Buffer<Self::Elem>,
+        combos: &Self::Buffer<u32>,
+        input_size: usize,
+        count: usize,
+    ) {
+        self.cpu
+            .mix_poly_coeffs(out, mix_start, mix, input, combos, input_size, count);
+    }
+
+    fn eltwise_add_elem(
+        &self,
+        output: &Self::Buffer<Self::Elem>,
+        input1: &Self::Buffer<Self::Elem>,
+        input2: &Self::Buffer<Self::Elem>,
+    ) {
+        self.cpu.eltwise_add_elem(output, input1, input2);
+    }
+
+    fn eltwise_sum_extelem(
+        &self,
+        output: &Self::Buffer<Self::Elem>,
+        input: &Self::Buffer<Self::ExtElem>,
+    ) {
+        self.cpu.eltwise_sum_extelem(output, input);
+    }
+
+    fn eltwise_copy_elem(
+        &self,
+        output: &Self::Buffer<Self::Elem>,
+        input: &Self::Buffer<Self::Elem>,
+    ) {
+        self.cpu.eltwise_copy_elem(output, input);
+    }
+
+    fn fri_fold(
+        &self,
+        output: &Self::Buffer<Self::Elem>,
+        input: &Self::Buffer<Self::Elem>,
+        mix: &Self::ExtElem,
+    ) {
+        self.cpu.fri_fold(output, input, mix);
+    }
+
+    fn hash_rows(&self, output: &Self::Buffer<Digest>, matrix: &Self::Buffer<Self::Elem>) {
+        let offloaded = self.hash_offloaded
+            && self.offload("hash_rows", |device| {
+                let suite = &self.get_hash_suite().name;
+                let result = device.hash_rows(suite, words(&matrix.as_slice()), output.size())?;
+                check_len(&result, output.size())?;
+                output.as_slice_mut().copy_from_slice(&result);
+                Ok(())
+            });
+        if !offloaded {
+            self.cpu.hash_rows(output, matrix);
+        }
+    }
+
+    fn hash_fold(&self, io: &Self::Buffer<Digest>, input_size: usize, output_size: usize) {
+        let offloaded = self.hash_offloaded
+            && self.offload("hash_fold", |device| {
+                let suite = &self.get_hash_suite().name;
+                let input = io.slice(input_size, input_size).as_slice().to_vec();
+                let result = device.hash_fold(suite, &input)?;
+                check_len(&result, output_size)?;
+                io.slice(output_size, output_size)
+                    .as_slice_mut()
+                    .copy_from_slice(&result);
+                Ok(())
+            });
+        if !offloaded {
+            self.cpu.hash_fold(io, input_size, output_size);
+        }
+    }
+
+    fn gather_sample(
+        &self,
+        dst: &Self::Buffer<Self::Elem>,
+        src: &Self::Buffer<Self::Elem>,
+        idx: usize,
+        size: usize,
+        stride: usize,
+    ) {
+        self.cpu.gather_sample(dst, src, idx, size, stride);
+    }
+}
+
+/// Runs the [CircuitHal] `C` of the CPU HAL with an [OffloadHal], whose
+/// buffers are those of the CPU HAL.
+pub struct OffloadCircuitHal<C>(pub C);
+
+impl<C: CircuitHal<CpuHal<BabyBear>>> CircuitHal<OffloadHal> for OffloadCircuitHal<C> {
+    fn eval_check(
+        &self,
+        check: &CpuBuffer<BabyBearElem>,
+        groups: &[&CpuBuffer<BabyBearElem>],
+        globals: &[&CpuBuffer<BabyBearElem>],
+        poly_mix: BabyBearExtElem,
+        po2: usize,
+        steps: usize,
+    ) {
+        self.0
+            .eval_check(check, groups, globals, poly_mix, po2, steps);
+    }
+}
+
+fn hash_suite(name: &str) -> Option<HashSuite<BabyBear>> {
+    let suite = match name {
+        "sha-256" => Sha256HashSuite::new_suite(),
+        "poseidon" => PoseidonHashSuite::new_suite(),
+        "poseidon2" => Poseidon2HashSuite::new_suite(),
+        "poseidon254" => Poseidon254HashSuite::new_suite(),
+        _ => return None,
+    };
+    Some(suite)
+}
+
+/// An [Accelerator] running on the CPU, as a reference for devices and
+/// transports.
+#[derive(Default)]
+pub struct CpuAccelerator;
+
+impl CpuAccelerator {
+    fn hal(suite: &str) -> Result<CpuHal<BabyBear>> {
+        let suite = hash_suite(suite).ok_or_else(|| anyhow!("unsupported hash suite {suite}"))?;
+        Ok(CpuHal::new(suite))
+    }
+}
+
+impl Accelerator for CpuAccelerator {
+    fn name(&self) -> String {
+        "cpu".into()
+    }
+
+    fn supports_hash(&self, suite: &str) -> Result<bool> {
+        Ok(hash_suite(suite).is_some())
+    }
+
+    fn hash_rows(&self, suite: &str, matrix: &[u32], rows: usize) -> Result<Vec<Digest>> {
+        if rows == 0 || matrix.len() % rows != 0 {
+            bail!("matrix of {} elements has no {rows} rows", matrix.len());
+        }
+        let hal = Self::hal(suite)?;
+        let matrix = hal.copy_from_elem("matrix", bytemuck::cast_slice(matrix));
+        let output = hal.alloc_digest("output", rows);
+        hal.hash_rows(&output, &matrix);
+        let result = output.as_slice().to_vec();
+        Ok(result)
+    }
+
+    fn hash_fold(&self, suite: &str, input: &[Digest]) -> Result<Vec<Digest>> {
+        if input.len() % 2 != 0 {
+            bail!("odd number of digests: {}", input.len());
+        }
+        let hal = Self::hal(suite)?;
+        let io = hal.alloc_digest("io", input.len() * 2);
+        io.slice(input.len(), input.len())
+            .as_slice_mut()
+            .copy_from_slice(input);
+        hal.hash_fold(&io, input.len(), input.len() / 2);
+        let result = io
+            .slice(input.len() / 2, input.len() / 2)
+            .as_slice()
+            .to_vec();
+        Ok(result)
+    }
+
+    fn batch_interpolate_ntt(&self, io: &mut [u32], count: usize) -> Result<()> {
+        ntt_row_size(io.len(), count, 0)?;
+        let hal = Self::hal("sha-256")?;
+        let buf = hal.copy_from_elem("io", bytemuck::cast_slice(io));
+        hal.batch_interpolate_ntt(&buf, count);
+        io.copy_from_slice(words(&buf.as_slice()));
+        Ok(())
+    }
+
+    fn batch_expand_into_evaluate_ntt(
+        &self,
+        output: &mut [u32],
+        input: &[u32],
+        count: usize,
+        expand_bits: usize,
+    ) -> Result<()> {
+        ntt_row_size(input.len(), count, expand_bits)?;
+        if output.len() != input.len() << expand_bits {
+            bail!(
+                "cannot expand {} elements into {} as {count} polynomials",
+                input.len(),
+                output.len()
+            );
+        }
+        let hal = Self::hal("sha-256")?;
+        let input = hal.copy_from_elem("input", bytemuck::cast_slice(input));
+        let buf = hal.alloc_elem("output", output.len());
+        hal.batch_expand_into_evaluate_ntt(&buf, &input, count, expand_bits);
+        output.copy_from_slice(words(&buf.as_slice()));
+        Ok(())
+    }
+}
+
+// Operations of the TCP transport.
+const OP_SUPPORTS_HASH: u32 = 1;
+const OP_HASH_ROWS: u32 = 2;
+const OP_HASH_FOLD: u32 = 3;
+const OP_INTERPOLATE_NTT: u32 = 4;
+const OP_EXPAND_INTO_EVALUATE_NTT: u32 = 5;
+
+const STATUS_OK: u32 = 0;
+const STATUS_ERROR: u32 = 1;
+
+// Limits of the TCP transport, so that a peer cannot make the other side
+// allocate without bound before any data is validated.
+const MAX_WORDS: usize = 1 << 30;
+const MAX_STRING_LEN: usize = 1 << 16;
+
+/// A request of the TCP transport.
+///
+/// Every value is a little-endian `u32`. A request is its operation, the
+/// length and UTF-8 bytes of the hash suite, the number and values of its
+/// parameters, then the length and words of its data. A response is a status,
+/// then either the length and words of the result, or the length and UTF-8
+/// bytes of an error message.
+struct Request {
+    op: u32,
+    suite: String,
+    params: Vec<u32>,
+    data: Vec<u32>,
+}
+
+fn write_u32(writer: &mut impl Write, value: u32) -> Result<()> {
+    writer.write_all(&value.to_le_bytes())?;
+    Ok(())
+}
+
+fn read_u32(reader: &mut impl Read) -> Result<u32> {
+    let mut bytes = [0; 4];
+    reader.read_exact(&mut bytes)?;
+    Ok(u32::from_le_bytes(bytes))
+}
+
+fn write_words(writer: &mut impl Write, words: &[u32]) -> Result<()> {
+    write_u32(writer, words.len().try_into()?)?;
+    writer.write_all(bytemuck::cast_slice(words))?;
+    Ok(())
+}
+
+fn read_words(reader: &mut impl Read) -> Result<Vec<u32>> {
+    let len = read_u32(reader)? as usize;
+    if len > MAX_WORDS {
+        bail!("{len} words exceed the maximum of {MAX_WORDS}");
+    }
+    let mut words = vec![0u32; len];
+    reader.read_exact(bytemuck::cast_slice_mut(&mut words))?;
+    Ok(words)
+}
+
+fn read_string(reader: &mut impl Read) -> Result<String> {
+    let len = read_u32(reader)? as usize;
+    if len > MAX_STRING_LEN {
+        bail!("string of {len} bytes exceeds the maximum of {MAX_STRING_LEN}");
+    }
+    let mut bytes = vec![0; len];
+    reader.read_exact(&mut bytes)?;
+    Ok(String::from_utf8(bytes)?)
+}
+
+fn write_string(writer: &mut impl Write, value: &str) -> Result<()> {
+    write_u32(writer, value.len().try_into()?)?;
+    writer.write_all(value.as_bytes())?;
+    Ok(())
+}
+
+impl Request {
+    fn write(&self, writer: &mut impl Write) -> Result<()> {
+        write_u32(writer, self.op)?;
+        write_string(writer, &self.suite)?;
+        write_words(writer, &self.params)?;
+        write_words(writer, &self.data)?;
+        Ok(())
+    }
+
+    fn read(reader: &mut impl Read) -> Result<Self> {
+        Ok(Self {
+            op: read_u32(reader)?,
+            suite: read_string(reader)?,
+            params: read_words(reader)?,
+            data: read_words(reader)?,
+        })
+    }
+
+    fn param(&self, idx: usize) -> Result<usize> {
+        let param = self
+            .params
+            .get(idx)
+            .ok_or_else(|| anyhow!("missing parameter {idx}"))?;
+        Ok(*param as usize)
+    }
+
+    /// Performs this request on `device`.
+    fn run(self, device: &dyn Accelerator) -> Result<Vec<u32>> {
+        match self.op {
+            OP_SUPPORTS_HASH => Ok(vec![device.supports_hash(&self.suite)? as u32]),
+            OP_HASH_ROWS => {
+                let result = device.hash_rows(&self.suite, &self.data, self.param(0)?)?;
+                Ok(bytemuck::cast_slice(&result).to_vec())
+            }
+            OP_HASH_FOLD => {
+                if self.data.len() % 8 != 0 {
+                    bail!("{} words are not digests", self.data.len());
+                }
+                let result = device.hash_fold(&self.suite, bytemuck::cast_slice(&self.data))?;
+                Ok(bytemuck::cast_slice(&result).to_vec())
+            }
+            OP_INTERPOLATE_NTT => {
+                let count = self.param(0)?;
+                let mut io = self.data;
+                device.batch_interpolate_ntt(&mut io, count)?;
+                Ok(io)
+            }
+            OP_EXPAND_INTO_EVALUATE_NTT => {
+                let len = self.param(2)?;
+                if len > MAX_WORDS {
+                    bail!("{len} words exceed the maximum of {MAX_WORDS}");
+                }
+                let mut output = vec![0; len];
+                device.batch_expand_into_evaluate_ntt(
+                    &mut output,
+                    &self.data,
+                    self.param(0)?,
+                    self.param(1)?,
+                )?;
+                Ok(output)
+            }
+            op => bail!("unknown operation {op}"),
+        }
+    }
+}
+
+/// An [Accelerator] reached over TCP, served by [serve].
+pub struct TcpAccelerator {
+    addr: String,
+    stream: Mutex<TcpStream>,
+}
+
+impl TcpAccelerator {
+    /// Connects to the accelerator served at `addr`.
+    pub fn connect(addr: &str) -> Result<Self> {
+        let stream = TcpStream::connect(addr)?;
+        stream.set_nodelay(true)?;
+        Ok(Self {
+            addr: addr.to_string(),
+            stream: Mutex::new(stream),
+        })
+    }
+
+    fn call(&self, op: u32, suite: &str, params: &[usize], data: &[u32]) -> Result<Vec<u32>> {
+        let request = Request {
+            op,
+            suite: suite.to_string(),
+            params: params
+                .iter()
+                .map(|&param| param.try_into())
+                .collect::<Result<_, _>>()?,
+            data: data.to_vec(),
+        };
+        let mut stream = self.stream.lock().unwrap();
+        let mut writer = BufWriter::new(&*stream);
+        request.write(&mut writer)?;
+        writer.flush()?;
+        drop(writer);
+
+        let mut reader = BufReader::new(&mut *stream);
+        match read_u32(&mut reader)? {
+            STATUS_OK => read_words(&mut reader),
+            STATUS_ERROR => bail!(read_string(&mut reader)?),
+            status => bail!("unknown status {status}"),
+        }
+    }
+}
+
+impl Accelerator for TcpAccelerator {
+    fn name(&self) -> String {
+        self.addr.clone()
+    }
+
+    fn supports_hash(&self, suite: &str) -> Result<bool> {
+        let result = self.call(OP_SUPPORTS_HASH, suite, &[], &[])?;
+        Ok(result.first() == Some(&1))
+    }
+
+    fn hash_rows(&self, suite: &str, matrix: &[u32], rows: usize) -> Result<Vec<Digest>> {
+        let result = self.call(OP_HASH_ROWS, suite, &[rows], matrix)?;
+        if result.len() % 8 != 0 {
+            bail!("{} words are not digests", result.len());
+        }
+        Ok(bytemuck::cast_slice(&result).to_vec())
+    }
+
+    fn hash_fold(&self, suite: &str, input: &[Digest]) -> Result<Vec<Digest>> {
+        let result = self.call(OP_HASH_FOLD, suite, &[], bytemuck::cast_slice(input))?;
+        if result.len() % 8 != 0 {
+            bail!("{} words are not digests", result.len());
+        }
+        Ok(bytemuck::cast_slice(&result).to_vec())
+    }
+
+    fn batch_interpolate_ntt(&self, io: &mut [u32], count: usize) -> Result<()> {
+        let result = self.call(OP_INTERPOLATE_NTT, "", &[count], io)?;
+        check_len(&result, io.len())?;
+        io.copy_from_slice(&result);
+        Ok(())
+    }
+
+    fn batch_expand_into_evaluate_ntt(
+        &self,
+        output: &mut [u32],
+        input: &[u32],
+        count: usize,
+        expand_bits: usize,
+    ) -> Result<()> {
+        let result = self.call(
+            OP_EXPAND_INTO_EVALUATE_NTT,
+            "",
+            &[count, expand_bits, output.len()],
+            input,
+        )?;
+        check_len(&result, output.len())?;
+        output.copy_from_slice(&result);
+        Ok(())
+    }
+}
+
+/// Serves `device` to [TcpAccelerator] clients on `listener`, one connection
+/// at a time.
+///
+/// Errors of the device are returned to the client, while errors and panics
+/// of a connection only close it.
+pub fn serve(listener: TcpListener, device: &dyn Accelerator) -> Result<()> {
+    for stream in listener.incoming() {
+        let stream = stream?;
+        match catch_unwind(AssertUnwindSafe(|| serve_connection(&stream, device))) {
+            Ok(Ok(())) => {}
+            Ok(Err(err)) => tracing::debug!("accelerator connection closed: {err}"),
+            Err(_) => tracing::warn!("accelerator connection closed by a panic"),
+        }
+    }
+    Ok(())
+}
+
+fn serve_connection(stream: &TcpStream, device: &dyn Accelerator) -> Result<()> {
+    stream.set_nodelay(true)?;
+    let mut reader = BufReader::new(stream);
+    loop {
+        let request = Request::read(&mut reader)?;
+        let mut writer = BufWriter::new(stream);
+        match request.run(device) {
+            Ok(result) => {
+                write_u32(&mut writer, STATUS_OK)?;
+                write_words(&mut writer, &result)?;
+            }
+            Err(err) => {
+                write_u32(&mut writer, STATUS_ERROR)?;
+                write_string(&mut writer, &err.to_string())?;
+            }
+        }
+        writer.flush()?;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::rc::Rc;
+
+    use super::*;
+    use crate::hal::testutil;
+
+    struct BrokenAccelerator;
+
+    impl Accelerator for BrokenAccelerator {
+        fn name(&self) -> String {
+            "broken".into()
+        }
+
+        fn supports_hash(&self, _suite: &str) -> Result<bool> {
+            Ok(true)
+        }
+
+        fn hash_rows(&self, _suite: &str, _matrix: &[u32], _rows: usize) -> Result<Vec<Digest>> {
+            bail!("device lost")
+        }
+
+        fn hash_fold(&self, _suite: &str, _input: &[Digest]) -> Result<Vec<Digest>> {
+            bail!("device lost")
+        }
+
+        fn batch_interpolate_ntt(&self, io: &mut [u32], _count: usize) -> Result<()> {
+            io.fill(0);
+            bail!("device lost")
+        }
+
+        fn batch_expand_into_evaluate_ntt(
+            &self,
+            _output: &mut [u32],
+            _input: &[u32],
+            _count: usize,
+            _expand_bits: usize,
+        ) -> Result<()> {
+            bail!("device lost")
+        }
+    }
+
+    struct PanickingAccelerator;
+
+    impl Accelerator for PanickingAccelerator {
+        fn name(&self) -> String {
+            "panicking".into()
+        }
+
+        fn supports_hash(&self, _suite: &str) -> Result<bool> {
+            panic!("device panicked")
+        }
+
+        fn hash_rows(&self, _suite: &str, _matrix: &[u32], _rows: usize) -> Result<Vec<Digest>> {
+            panic!("device panicked")
+        }
+
+        fn hash_fold(&self, _suite: &str, _input: &[Digest]) -> Result<Vec<Digest>> {
+            panic!("device panicked")
+        }
+
+        fn batch_interpolate_ntt(&self, _io: &mut [u32], _count: usize) -> Result<()> {
+            panic!("device panicked")
+        }
+
+        fn batch_expand_into_evaluate_ntt(
+            &self,
+            _output: &mut [u32],
+            _input: &[u32],
+            _count: usize,
+            _expand_bits: usize,
+        ) -> Result<()> {
+            panic!("device panicked")
+        }
+    }
+
+    fn check(hal: &Rc<OffloadHal>) {
+        testutil::ntt_round_trip(hal.clone());
+        testutil::hash_rows(hal.clone());
+        testutil::hash_fold(hal.clone());
+    }
+
+    #[test]
+    fn tcp() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap().to_string();
+        std::thread::spawn(move || serve(listener, &CpuAccelerator));
+
+        for suite in [
+            Sha256HashSuite::new_suite(),
+            Poseidon2HashSuite::new_suite(),
+        ] {
+            let device = TcpAccelerator::connect(&addr).unwrap();
+            let hal = Rc::new(OffloadHal::new(suite, Some(Box::new(device))));
+            assert!(hal.hash_offloaded);
+            check(&hal);
+            assert!(hal.is_offloading());
+        }
+
+        // Errors of the device are returned to the client.
+        let device = TcpAccelerator::connect(&addr).unwrap();
+        assert!(!device.supports_hash("unknown").unwrap());
+        assert!(device.hash_rows("unknown", &[0; 16], 1).is_err());
+        assert!(device.batch_interpolate_ntt(&mut [0; 16], 3).is_err());
+        assert!(device.batch_interpolate_ntt(&mut [0; 12], 1).is_err());
+        assert!(device
+            .batch_expand_into_evaluate_ntt(&mut [0; 16], &[0; 16], 1, 64)
+            .is_err());
+        assert!(device.supports_hash("sha-256").unwrap());
+    }
+
+    #[test]
+    fn panic() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap().to_string();
+        std::thread::spawn(move || serve(listener, &PanickingAccelerator));
+
+        // A panic closes the connection, and the next one is still served.
+        for _ in 0..2 {
+            let device = TcpAccelerator::connect(&addr).unwrap();
+            assert!(device.supports_hash("sha-256").is_err());
+        }
+    }
+
+    #[test]
+    fn limits() {
+        let mut wire = Vec::new();
+        write_u32(&mut wire, MAX_WORDS as u32 + 1).unwrap();
+        assert!(read_words(&mut wire.as_slice()).is_err());
+        assert!(read_string(&mut wire.as_slice()).is_err());
+    }
+
+    #[test]
+    fn fallback() {
+        let hal = Rc::new(OffloadHal::new(
+            Sha256HashSuite::new_suite(),
+            Some(Box::new(BrokenAccelerator)),
+        ));
+        assert!(hal.is_offloading());
+        check(&hal);
+        assert!(!hal.is_offloading());
+
+        let hal = Rc::new(OffloadHal::new(Sha256HashSuite::new_suite(), None));
+        assert!(!hal.is_offloading());
+        check(&hal);
+    }
+}
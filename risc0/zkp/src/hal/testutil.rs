@@ -0,0 +1,370 @@
+// Copyright 2024 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Conformance tests of [Hal] implementations.
+//!
+//! Each check runs an operation of the HAL under test on random inputs, and
+//! panics if its results differ from those of the reference [CpuHal], or from
+//! the expected results. Third-party HALs can run all of them with
+//! [conformance].
+
+use std::rc::Rc;
+
+use rand::{thread_rng, RngCore};
+use risc0_core::field::{baby_bear::BabyBearElem, Elem, ExtElem};
+
+use super::{dual::DualHal, Hal};
+use crate::{
+    core::digest::Digest,
+    hal::{cpu::CpuHal, Buffer},
+    FRI_FOLD, INV_RATE,
+};
+
+/// Runs all the checks of this module against `hal`.
+///
+/// Some checks allocate buffers of hundreds of megabytes, and each check is
+/// also run on the CPU HAL, so this may take minutes.
+pub fn conformance<H: Hal<Elem = BabyBearElem>>(hal: H) {
+    let hal = Rc::new(hal);
+    eltwise_add_elem(hal.clone());
+    eltwise_copy_elem(hal.clone());
+    eltwise_sum_extelem(hal.clone());
+    gather_sample(hal.clone());
+    batch_bit_reverse(hal.clone());
+    batch_expand_into_evaluate_ntt(hal.clone());
+    batch_interpolate_ntt(hal.clone());
+    ntt_round_trip(hal.clone());
+    batch_evaluate_any(hal.clone());
+    mix_poly_coeffs(hal.clone());
+    zk_shift(hal.clone());
+    fri_fold(hal.clone());
+    hash_rows(hal.clone());
+    hash_fold(hal.clone());
+    slice(hal);
+}
+
+const COUNTS: [usize; 7] = [1, 9, 12, 1001, 1024, 1025, 1024 * 1024];
+const DATA_SIZE: usize = 223;
+
+fn generate_elem<H: Hal, R: RngCore>(hal: &H, rng: &mut R, size: usize) -> H::Buffer<H::Elem> {
+    let values: Vec<H::Elem> = (0..size).map(|_| H::Elem::random(rng)).collect();
+    hal.copy_from_elem("values", &values)
+}
+
+fn generate_extelem<H: Hal, R: RngCore>(
+    hal: &H,
+    rng: &mut R,
+    size: usize,
+) -> H::Buffer<H::ExtElem> {
+    let values: Vec<H::ExtElem> = (0..size).map(|_| H::ExtElem::random(rng)).collect();
+    hal.copy_from_extelem("values", &values)
+}
+
+/// Checks [Hal::batch_bit_reverse] against the CPU HAL.
+pub fn batch_bit_reverse<H: Hal>(hal_gpu: Rc<H>) {
+    let mut rng = thread_rng();
+    let hal_cpu = CpuHal::new(hal_gpu.get_hash_suite().clone());
+    let hal = DualHal::new(Rc::new(hal_cpu), hal_gpu);
+
+    let steps = 1 << 12;
+    let count = DATA_SIZE;
+    let domain = steps * INV_RATE;
+    let io_size = count * domain;
+
+    let io = generate_elem(&hal, &mut rng, io_size);
+    hal.batch_bit_reverse(&io, count);
+}
+
+/// Checks [Hal::batch_evaluate_any] against the CPU HAL.
+pub fn batch_evaluate_any<H: Hal>(hal_gpu: Rc<H>) {
+    let mut rng = thread_rng();
+    let hal_cpu = CpuHal::new(hal_gpu.get_hash_suite().clone());
+    let hal = DualHal::new(Rc::new(hal_cpu), hal_gpu);
+
+    let eval_size = 865;
+    let poly_count = 223;
+    let steps = 1 << 16;
+    let coeffs_size = steps * poly_count;
+
+    let z = H::ExtElem::random(&mut rng);
+    let z_pow = z.pow(H::ExtElem::EXT_SIZE);
+
+    let coeffs = generate_elem(&hal, &mut rng, coeffs_size);
+    let which = hal.copy_from_u32("which", &vec![0; eval_size]);
+    let xs = hal.copy_from_extelem("xs", &vec![z_pow; eval_size]);
+    let out = hal.alloc_extelem("out", eval_size);
+
+    hal.batch_evaluate_any(&coeffs, poly_count, &which, &xs, &out);
+}
+
+/// Checks [Hal::batch_expand_into_evaluate_ntt] against the CPU HAL.
+pub fn batch_expand_into_evaluate_ntt<H: Hal>(hal_gpu: Rc<H>) {
+    let mut rng = thread_rng();
+    let hal_cpu = CpuHal::new(hal_gpu.get_hash_suite().clone());
+    let hal = DualHal::new(Rc::new(hal_cpu), hal_gpu);
+
+    let count = DATA_SIZE;
+    let expand_bits = 2;
+    let steps = 1 << 16;
+    let domain = steps * INV_RATE;
+    let input_size = count * steps;
+    let output_size = count * domain;
+
+    let input = generate_elem(&hal, &mut rng, input_size);
+    let output = hal.alloc_elem("output", output_size);
+    hal.batch_expand_into_evaluate_ntt(&output, &input, count, expand_bits);
+}
+
+/// Checks [Hal::batch_interpolate_ntt] against the CPU HAL.
+pub fn batch_interpolate_ntt<H: Hal>(hal_gpu: Rc<H>) {
+    let mut rng = thread_rng();
+    let hal_cpu = CpuHal::new(hal_gpu.get_hash_suite().clone());
+    let hal = DualHal::new(Rc::new(hal_cpu), hal_gpu);
+
+    let count = DATA_SIZE;
+    let steps = 1 << 16;
+    let domain = steps * INV_RATE;
+    let io_size = count * domain;
+
+    let io = generate_elem(&hal, &mut rng, io_size);
+    hal.batch_interpolate_ntt(&io, count);
+}
+
+/// Checks that [Hal::batch_interpolate_ntt] followed by
+/// [Hal::batch_expand_into_evaluate_ntt] without expansion returns the
+/// original evaluations.
+pub fn ntt_round_trip<H: Hal>(hal: Rc<H>) {
+    let mut rng = thread_rng();
+    let count = DATA_SIZE;
+    let steps = 1 << 12;
+    let size = count * steps;
+
+    let input = generate_elem(hal.as_ref(), &mut rng, size);
+    let coeffs = hal.alloc_elem("coeffs", size);
+    hal.eltwise_copy_elem(&coeffs, &input);
+    hal.batch_interpolate_ntt(&coeffs, count);
+    let output = hal.alloc_elem("output", size);
+    hal.batch_expand_into_evaluate_ntt(&output, &coeffs, count, 0);
+    output.view(|output| {
+        input.view(|input| assert_eq!(output, input));
+    });
+}
+
+/// Checks that [Hal::gather_sample] gathers the given column.
+pub fn gather_sample<H: Hal>(hal: Rc<H>) {
+    let mut rng = thread_rng();
+    let rows = 1000;
+    let cols = 900;
+    let idx = 400;
+    let src_size = rows * cols;
+    let src = hal.alloc_elem("src", src_size);
+    let dst = hal.alloc_elem("dst", rows);
+    src.view_mut(|buf| {
+        for x in 0..cols {
+            for y in 0..rows {
+                let value = H::Elem::random(&mut rng);
+                buf[y * cols + x] = value;
+            }
+        }
+    });
+    hal.gather_sample(&dst, &src, idx, rows, cols);
+    src.view(|src| {
+        dst.view(|dst| {
+            for y in 0..rows {
+                assert_eq!(src[y * cols + idx], dst[y]);
+            }
+        });
+    });
+}
+
+/// Adds buffers of different sizes, which a HAL must reject by panicking.
+pub fn check_req<H: Hal>(hal: Rc<H>) {
+    let a = hal.alloc_elem("a", 10);
+    let b = hal.alloc_elem("b", 20);
+    hal.eltwise_add_elem(&a, &b, &b);
+}
+
+/// Checks [Hal::eltwise_add_elem] against additions on the host.
+pub fn eltwise_add_elem<H: Hal>(hal_gpu: Rc<H>) {
+    for (x, count) in COUNTS.iter().enumerate() {
+        let a = hal_gpu.alloc_elem("a", *count);
+        let b = hal_gpu.alloc_elem("b", *count);
+        let o = hal_gpu.alloc_elem("o", *count);
+        let mut golden = Vec::with_capacity(*count);
+
+        let mut rng = thread_rng();
+        a.view_mut(|a| {
+            b.view_mut(|b| {
+                assert_eq!(a.len(), b.len());
+                for i in 0..a.len() {
+                    a[i] = H::Elem::random(&mut rng);
+                    b[i] = H::Elem::random(&mut rng);
+                }
+                for i in 0..a.len() {
+                    golden.push(a[i] + b[i]);
+                }
+            });
+        });
+
+        hal_gpu.eltwise_add_elem(&o, &a, &b);
+
+        o.view(|o| {
+            for i in 0..o.len() {
+                assert_eq!(o[i], golden[i], "x: {x}, count: {count}, i: {i}");
+            }
+        });
+    }
+}
+
+/// Checks that [Hal::eltwise_copy_elem] copies its input.
+pub fn eltwise_copy_elem<H: Hal>(hal_gpu: Rc<H>) {
+    let mut rng = thread_rng();
+    for count in COUNTS {
+        let input = generate_elem(hal_gpu.as_ref(), &mut rng, count);
+        let output = hal_gpu.alloc_elem("output", count);
+        hal_gpu.eltwise_copy_elem(&output, &input);
+        output.view(|output| {
+            input.view(|input| assert_eq!(output, input));
+        });
+    }
+}
+
+/// Checks [Hal::eltwise_sum_extelem] against the CPU HAL.
+pub fn eltwise_sum_extelem<H: Hal>(hal_gpu: Rc<H>) {
+    const COUNT: usize = 1024 * 1024;
+
+    let mut rng = thread_rng();
+    let hal_cpu = CpuHal::new(hal_gpu.get_hash_suite().clone());
+    let hal = DualHal::new(Rc::new(hal_cpu), hal_gpu);
+
+    let input = generate_extelem(&hal, &mut rng, COUNT);
+    let output = hal.alloc_elem("output", COUNT);
+    hal.eltwise_sum_extelem(&output, &input);
+}
+
+/// Checks [Hal::fri_fold] against the CPU HAL.
+pub fn fri_fold<H: Hal>(hal_gpu: Rc<H>) {
+    let mut rng = thread_rng();
+    let hal_cpu = CpuHal::new(hal_gpu.get_hash_suite().clone());
+    let hal = DualHal::new(Rc::new(hal_cpu), hal_gpu);
+    for count in COUNTS {
+        let output_size = count * H::ExtElem::EXT_SIZE;
+        let input_size = output_size * FRI_FOLD;
+
+        let output = hal.alloc_elem("output", output_size);
+        let mix = H::ExtElem::random(&mut rng);
+        let input = generate_elem(&hal, &mut rng, input_size);
+        hal.fri_fold(&output, &input, &mix);
+    }
+}
+
+/// Checks [Hal::mix_poly_coeffs] against the CPU HAL.
+pub fn mix_poly_coeffs<H: Hal>(hal_gpu: Rc<H>) {
+    let mut rng = thread_rng();
+    let hal_cpu = CpuHal::new(hal_gpu.get_hash_suite().clone());
+    let hal = DualHal::new(Rc::new(hal_cpu), hal_gpu);
+
+    let combo_count = 100;
+    let steps = 1 << 12;
+    let domain = steps * INV_RATE;
+    let input_size = H::CHECK_SIZE * domain;
+    let output_size = steps * (combo_count + 1);
+    let combos = vec![0; H::CHECK_SIZE];
+    let mix_start = H::ExtElem::random(&mut rng);
+    let mix = H::ExtElem::random(&mut rng);
+
+    let output = hal.alloc_extelem("output", output_size);
+    let combos = hal.copy_from_u32("combos", &combos);
+    let input = generate_elem(&hal, &mut rng, input_size);
+
+    hal.mix_poly_coeffs(
+        &output,
+        &mix_start,
+        &mix,
+        &input,
+        &combos,
+        H::CHECK_SIZE,
+        steps,
+    );
+}
+
+/// Checks [Hal::hash_fold] against the CPU HAL.
+pub fn hash_fold<H: Hal>(hal_gpu: Rc<H>) {
+    const INPUTS: usize = 1024;
+    const OUTPUTS: usize = INPUTS / 2;
+    let mut rng = thread_rng();
+    let hal_cpu = CpuHal::new(hal_gpu.get_hash_suite().clone());
+    let hal = DualHal::new(Rc::new(hal_cpu), hal_gpu);
+    let io = hal.alloc_digest("io", INPUTS * 2);
+    io.view_mut(|g| {
+        for i in 0..INPUTS {
+            g[i + INPUTS] = Digest::from([
+                rng.next_u32() / 3,
+                rng.next_u32() / 3,
+                rng.next_u32() / 3,
+                rng.next_u32() / 3,
+                rng.next_u32() / 3,
+                rng.next_u32() / 3,
+                rng.next_u32() / 3,
+                rng.next_u32() / 3,
+            ]);
+        }
+    });
+    hal.hash_fold(&io, INPUTS, OUTPUTS);
+}
+
+/// Checks [Hal::hash_rows] against the CPU HAL.
+pub fn hash_rows<H: Hal<Elem = BabyBearElem>>(hal_gpu: Rc<H>) {
+    let mut rng = thread_rng();
+    let hal_cpu = CpuHal::new(hal_gpu.get_hash_suite().clone());
+    let hal = DualHal::new(Rc::new(hal_cpu), hal_gpu);
+    let rows = [1, 2, 3, 4, 10];
+    let cols = [16, 32, 64, 128];
+    for row_count in rows {
+        for col_count in cols {
+            let matrix_size = row_count * col_count;
+            let matrix = generate_elem(&hal, &mut rng, matrix_size);
+            let output = hal.alloc_digest("output", row_count);
+            hal.hash_rows(&output, &matrix);
+        }
+    }
+}
+
+/// Checks [Hal::hash_rows] into a slice of a buffer against the CPU HAL.
+pub fn slice<H: Hal<Elem = BabyBearElem>>(hal_gpu: Rc<H>) {
+    let mut rng = thread_rng();
+    let hal_cpu = CpuHal::new(hal_gpu.get_hash_suite().clone());
+    let hal = DualHal::new(Rc::new(hal_cpu), hal_gpu);
+
+    let rows = 4096;
+    let cols = 256;
+    let matrix_size = rows * cols;
+
+    let nodes = hal.alloc_digest("nodes", rows * 2);
+    let matrix = generate_elem(&hal, &mut rng, matrix_size);
+    hal.hash_rows(&nodes.slice(rows, rows), &matrix);
+}
+
+/// Checks [Hal::zk_shift] against the CPU HAL.
+pub fn zk_shift<H: Hal>(hal_gpu: Rc<H>) {
+    let mut rng = thread_rng();
+    let hal_cpu = CpuHal::new(hal_gpu.get_hash_suite().clone());
+    let hal = DualHal::new(Rc::new(hal_cpu), hal_gpu);
+    let counts = [(1000, (1 << 8)), (900, (1 << 12))];
+    for (poly_count, steps) in counts {
+        let count = poly_count * steps;
+        let io = generate_elem(&hal, &mut rng, count);
+        hal.zk_shift(&io, poly_count);
+    }
+}
@@ -54,6 +54,16 @@ impl<'a, F: Field> VerifyRoundInfo<'a, F> {
     }
 }
 
+/// State of the FRI verifier between queries.
+pub(crate) struct FriVerifier<'a, F: Field> {
+    rounds: Vec<VerifyRoundInfo<'a, F>>,
+    final_coeffs: &'a [F::Elem],
+    degree: usize,
+    orig_domain: usize,
+    gen: F::Elem,
+    poly_buf: Vec<F::ExtElem>,
+}
+
 impl<'a, F, C> Verifier<'a, F, C>
 where
     F: Field,
@@ -100,12 +110,25 @@ where
     pub fn fri_verify<InnerFn>(
         &self,
         iop: &mut ReadIOP<'a, F>,
-        mut degree: usize,
+        degree: usize,
         mut inner: InnerFn,
     ) -> Result<(), VerificationError>
     where
         InnerFn: FnMut(&mut ReadIOP<'a, F>, usize) -> Result<F::ExtElem, VerificationError>,
     {
+        let mut fri = self.fri_prepare(iop, degree);
+        for _ in 0..QUERIES {
+            self.fri_query(&mut fri, iop, &mut inner)?;
+        }
+        Ok(())
+    }
+
+    /// Read the commitments of the folding rounds and the final polynomial.
+    pub(crate) fn fri_prepare(
+        &self,
+        iop: &mut ReadIOP<'a, F>,
+        mut degree: usize,
+    ) -> FriVerifier<'a, F> {
         let hashfn = self.suite.hashfn.as_ref();
         let orig_domain = INV_RATE * degree;
         let mut domain = orig_domain;
@@ -133,29 +156,47 @@ where
         iop.commit(&final_digest);
         // Get the generator for the final polynomial evaluations
         let gen = <F::Elem as RootsOfUnity>::ROU_FWD[log2_ceil(domain)];
-        // Do queries
-        let mut poly_buf: Vec<F::ExtElem> = Vec::with_capacity(degree);
-        for _ in 0..QUERIES {
-            let mut pos = iop.random_bits(log2_ceil(orig_domain)) as usize;
-            // Do the 'inner' verification for this index
-            let mut goal = inner(iop, pos)?;
-            // Verify the per-round proofs
-            for round in &mut rounds {
-                self.verify_query(round, iop, &mut pos, &mut goal)?;
-            }
-            // Do final verification
-            let x = gen.pow(pos);
+        FriVerifier {
+            rounds,
+            final_coeffs,
+            degree,
+            orig_domain,
+            gen,
+            poly_buf: Vec::with_capacity(degree),
+        }
+    }
+
+    /// Check a single FRI query, of the [QUERIES] in a seal.
+    pub(crate) fn fri_query<InnerFn>(
+        &self,
+        fri: &mut FriVerifier<'a, F>,
+        iop: &mut ReadIOP<'a, F>,
+        inner: &mut InnerFn,
+    ) -> Result<(), VerificationError>
+    where
+        InnerFn: FnMut(&mut ReadIOP<'a, F>, usize) -> Result<F::ExtElem, VerificationError>,
+    {
+        let degree = fri.degree;
+        let mut pos = iop.random_bits(log2_ceil(fri.orig_domain)) as usize;
+        // Do the 'inner' verification for this index
+        let mut goal = inner(iop, pos)?;
+        // Verify the per-round proofs
+        for round in &mut fri.rounds {
+            self.verify_query(round, iop, &mut pos, &mut goal)?;
+        }
+        // Do final verification
+        let x = fri.gen.pow(pos);
 
-            poly_buf.clear();
-            poly_buf.extend((0..degree).map(|i| {
-                F::ExtElem::from_subelems(
-                    (0..F::ExtElem::EXT_SIZE).map(|j| final_coeffs[j * degree + i]),
-                )
-            }));
-            let fx = self.poly_eval(poly_buf.as_slice(), F::ExtElem::from_subfield(&x));
-            if fx != goal {
-                return Err(VerificationError::InvalidProof);
-            }
+        let final_coeffs = fri.final_coeffs;
+        fri.poly_buf.clear();
+        fri.poly_buf.extend((0..degree).map(|i| {
+            F::ExtElem::from_subelems(
+                (0..F::ExtElem::EXT_SIZE).map(|j| final_coeffs[j * degree + i]),
+            )
+        }));
+        let fx = self.poly_eval(fri.poly_buf.as_slice(), F::ExtElem::from_subfield(&x));
+        if fx != goal {
+            return Err(VerificationError::InvalidProof);
         }
         Ok(())
     }
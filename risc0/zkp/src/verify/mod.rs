@@ -18,9 +18,10 @@ mod fri;
 mod merkle;
 mod read_iop;
 
-use alloc::{vec, vec::Vec};
+use alloc::{string::String, vec, vec::Vec};
 use core::{cell::RefCell, fmt, iter::zip};
 
+use fri::FriVerifier;
 pub(crate) use merkle::MerkleTreeVerifier;
 pub use read_iop::ReadIOP;
 use risc0_core::field::{Elem, ExtElem, Field, RootsOfUnity};
@@ -48,6 +49,8 @@ pub enum VerificationError {
     InvalidHashSuite,
     FaultStateMismatch,
     ValidFaultReceipt,
+    SealVersionMismatch { found: u32, supported: u32 },
+    PolicyViolation(String),
 }
 
 impl fmt::Debug for VerificationError {
@@ -80,6 +83,12 @@ impl fmt::Display for VerificationError {
             VerificationError::ValidFaultReceipt => {
                 write!(f, "Receipt is a valid fault proof")
             }
+            VerificationError::SealVersionMismatch { found, supported } => {
+                write!(f, "seal version {found}, verifier supports {supported}")
+            }
+            VerificationError::PolicyViolation(reason) => {
+                write!(f, "rejected by verifier policy: {reason}")
+            }
         }
     }
 }
@@ -87,6 +96,18 @@ impl fmt::Display for VerificationError {
 #[cfg(feature = "std")]
 impl std::error::Error for VerificationError {}
 
+/// Checks that a seal of version `found` can be verified by a verifier
+/// supporting seals of version `supported`.
+///
+/// Circuits export the version of the seals they produce, so that seals from
+/// an incompatible version are rejected before attempting to verify them.
+pub fn check_seal_version(found: u32, supported: u32) -> Result<(), VerificationError> {
+    match found == supported {
+        true => Ok(()),
+        false => Err(VerificationError::SealVersionMismatch { found, supported }),
+    }
+}
+
 trait VerifyParams<F: Field> {
     const CHECK_SIZE: usize = INV_RATE * F::ExtElem::EXT_SIZE;
 }
@@ -98,6 +119,20 @@ struct TapCache<F: Field> {
     check_mix_pows: Vec<F::ExtElem>,
 }
 
+/// Commitments and randomness of a seal needed to answer the FRI queries.
+struct Deep<'a, F: Field> {
+    code_merkle: MerkleTreeVerifier<'a>,
+    data_merkle: MerkleTreeVerifier<'a>,
+    accum_merkle: MerkleTreeVerifier<'a>,
+    check_merkle: MerkleTreeVerifier<'a>,
+    mix: F::ExtElem,
+    combo_u: Vec<F::ExtElem>,
+    z: F::ExtElem,
+    back_one: F::Elem,
+    gen: F::Elem,
+    size: usize,
+}
+
 pub(crate) struct Verifier<'a, F, C>
 where
     F: Field,
@@ -218,11 +253,29 @@ where
             return Err(VerificationError::ReceiptFormatError);
         }
 
-        let taps = self.circuit.get_taps();
-        let hashfn = self.suite.hashfn.as_ref();
-
         // Make IOP
         let mut iop = ReadIOP::new(seal, self.suite.rng.as_ref());
+        let deep = self.prepare(&mut iop, check_code)?;
+        // tracing::debug!("FRI-verify, size = {size}");
+        self.fri_verify(&mut iop, deep.size, |iop, idx| {
+            self.deep_query(&deep, iop, idx)
+        })?;
+        iop.verify_complete();
+        Ok(())
+    }
+
+    /// Check the constraints at the DEEP query point, reading the seal up to
+    /// the start of the FRI proof.
+    fn prepare<CheckCodeFn>(
+        &mut self,
+        iop: &mut ReadIOP<'a, F>,
+        check_code: CheckCodeFn,
+    ) -> Result<Deep<'a, F>, VerificationError>
+    where
+        CheckCodeFn: Fn(u32, &Digest) -> Result<(), VerificationError>,
+    {
+        let taps = self.circuit.get_taps();
+        let hashfn = self.suite.hashfn.as_ref();
 
         // At the start of the protocol, seed the Fiat-Shamir transcript with context information
         // about the proof system and circuit.
@@ -230,7 +283,7 @@ where
         iop.commit(&hashfn.hash_elem_slice(&C::CIRCUIT_INFO.encode()));
 
         // Read any execution state
-        self.execute(&mut iop);
+        self.execute(iop);
 
         // Get the size
         assert!(self.po2 as usize <= MAX_CYCLES_PO2);
@@ -247,7 +300,7 @@ where
         // The code merkle tree contains the control instructions for the zkVM.
         #[cfg(not(target_os = "zkvm"))]
         tracing::debug!("code_merkle");
-        let code_merkle = MerkleTreeVerifier::new(&mut iop, hashfn, domain, code_size, QUERIES);
+        let code_merkle = MerkleTreeVerifier::new(iop, hashfn, domain, code_size, QUERIES);
         // tracing::debug!("codeRoot = {}", code_merkle.root());
         check_code(self.po2, code_merkle.root())?;
 
@@ -257,7 +310,7 @@ where
         // accesses sorted by location used by PLONK.
         #[cfg(not(target_os = "zkvm"))]
         tracing::debug!("data_merkle");
-        let data_merkle = MerkleTreeVerifier::new(&mut iop, hashfn, domain, data_size, QUERIES);
+        let data_merkle = MerkleTreeVerifier::new(iop, hashfn, domain, data_size, QUERIES);
         // tracing::debug!("dataRoot = {}", data_merkle.root());
 
         // Prep accumulation
@@ -277,7 +330,7 @@ where
         // implement a look-up table.
         #[cfg(not(target_os = "zkvm"))]
         tracing::debug!("accum_merkle");
-        let accum_merkle = MerkleTreeVerifier::new(&mut iop, hashfn, domain, accum_size, QUERIES);
+        let accum_merkle = MerkleTreeVerifier::new(iop, hashfn, domain, accum_size, QUERIES);
         // tracing::debug!("accumRoot = {}", accum_merkle.root());
 
         // Get a pseudorandom value with which to mix the constraint polynomials.
@@ -286,8 +339,7 @@ where
 
         #[cfg(not(target_os = "zkvm"))]
         tracing::debug!("check_merkle");
-        let check_merkle =
-            MerkleTreeVerifier::new(&mut iop, hashfn, domain, Self::CHECK_SIZE, QUERIES);
+        let check_merkle = MerkleTreeVerifier::new(iop, hashfn, domain, Self::CHECK_SIZE, QUERIES);
         // tracing::debug!("checkRoot = {}", check_merkle.root());
 
         // Get a pseudorandom DEEP query point
@@ -404,21 +456,47 @@ where
         // tracing::debug!("cur_mix: {cur_mix:?}");
 
         let gen = <F::Elem as RootsOfUnity>::ROU_FWD[log2_ceil(domain)];
-        // tracing::debug!("FRI-verify, size = {size}");
-        self.fri_verify(&mut iop, size, |iop, idx| {
-            // tracing::debug!("fri_verify");
-            let x = gen.pow(idx);
-            let rows = [
-                accum_merkle.verify(iop, hashfn, idx)?,
-                code_merkle.verify(iop, hashfn, idx)?,
-                data_merkle.verify(iop, hashfn, idx)?,
-            ];
-            let check_row = check_merkle.verify(iop, hashfn, idx)?;
-            let ret = self.fri_eval_taps(taps, mix, &combo_u, check_row, back_one, x, z, rows);
-            Ok(ret)
-        })?;
-        iop.verify_complete();
-        Ok(())
+        Ok(Deep {
+            code_merkle,
+            data_merkle,
+            accum_merkle,
+            check_merkle,
+            mix,
+            combo_u,
+            z,
+            back_one,
+            gen,
+            size,
+        })
+    }
+
+    /// Evaluate the DEEP polynomial at the FRI query `idx`, checking the rows
+    /// of the trace against their Merkle roots.
+    fn deep_query(
+        &self,
+        deep: &Deep<'a, F>,
+        iop: &mut ReadIOP<'a, F>,
+        idx: usize,
+    ) -> Result<F::ExtElem, VerificationError> {
+        let taps = self.circuit.get_taps();
+        let hashfn = self.suite.hashfn.as_ref();
+        let x = deep.gen.pow(idx);
+        let rows = [
+            deep.accum_merkle.verify(iop, hashfn, idx)?,
+            deep.code_merkle.verify(iop, hashfn, idx)?,
+            deep.data_merkle.verify(iop, hashfn, idx)?,
+        ];
+        let check_row = deep.check_merkle.verify(iop, hashfn, idx)?;
+        Ok(self.fri_eval_taps(
+            taps,
+            deep.mix,
+            &deep.combo_u,
+            check_row,
+            deep.back_one,
+            x,
+            deep.z,
+            rows,
+        ))
     }
 
     /// Read the globals (i.e. outputs) from the IOP, and mix them into the Fiat-Shamir state.
@@ -471,3 +549,130 @@ where
 {
     Verifier::<F, C>::new(circuit, suite).verify(seal, check_code)
 }
+
+/// Progress of a [StepVerifier].
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Progress {
+    /// Verification is not complete, with the fraction of the work done so far.
+    Pending(f32),
+    /// The seal is valid.
+    Done,
+}
+
+enum StepState<'a, F: Field> {
+    Start,
+    Queries {
+        iop: ReadIOP<'a, F>,
+        deep: Deep<'a, F>,
+        fri: FriVerifier<'a, F>,
+        done: usize,
+    },
+    Done,
+    Failed,
+}
+
+/// Verifier of a seal which does a bounded amount of work per call, so that
+/// verification can be spread across the frames or ticks of a UI thread.
+///
+/// The work is split into [StepVerifier::WORK] units of similar cost: reading
+/// the seal and checking the constraints at the DEEP query point, and then
+/// each of the FRI queries.
+pub struct StepVerifier<'a, F: Field, C, CheckCode> {
+    verifier: Verifier<'a, F, C>,
+    seal: &'a [u32],
+    check_code: CheckCode,
+    state: StepState<'a, F>,
+}
+
+impl<'a, F, C, CheckCode> StepVerifier<'a, F, C, CheckCode>
+where
+    F: Field,
+    C: CircuitCoreDef<F>,
+    CheckCode: Fn(u32, &Digest) -> Result<(), VerificationError>,
+{
+    /// Total units of work of a verification.
+    pub const WORK: usize = 1 + QUERIES;
+
+    /// Prepare to verify a seal for the given circuit and code checking
+    /// function, without doing any work yet.
+    pub fn new(
+        circuit: &'a C,
+        suite: &'a HashSuite<F>,
+        seal: &'a [u32],
+        check_code: CheckCode,
+    ) -> Self {
+        Self {
+            verifier: Verifier::new(circuit, suite),
+            seal,
+            check_code,
+            state: StepState::Start,
+        }
+    }
+
+    /// Do up to `budget` units of work.
+    ///
+    /// Returns [Progress::Done] once the whole seal is verified. After an
+    /// error, every subsequent call fails.
+    pub fn step(&mut self, budget: usize) -> Result<Progress, VerificationError> {
+        for _ in 0..budget {
+            if let Err(err) = self.advance() {
+                self.state = StepState::Failed;
+                return Err(err);
+            }
+            if let StepState::Done = self.state {
+                break;
+            }
+        }
+        self.progress()
+    }
+
+    /// Progress of the verification so far.
+    pub fn progress(&self) -> Result<Progress, VerificationError> {
+        match &self.state {
+            StepState::Start => Ok(Progress::Pending(0.0)),
+            StepState::Queries { done, .. } => {
+                Ok(Progress::Pending((1 + done) as f32 / Self::WORK as f32))
+            }
+            StepState::Done => Ok(Progress::Done),
+            StepState::Failed => Err(VerificationError::InvalidProof),
+        }
+    }
+
+    fn advance(&mut self) -> Result<(), VerificationError> {
+        match &mut self.state {
+            StepState::Start => {
+                if self.seal.is_empty() {
+                    return Err(VerificationError::ReceiptFormatError);
+                }
+                let mut iop = ReadIOP::new(self.seal, self.verifier.suite.rng.as_ref());
+                let deep = self.verifier.prepare(&mut iop, &self.check_code)?;
+                let fri = self.verifier.fri_prepare(&mut iop, deep.size);
+                self.state = StepState::Queries {
+                    iop,
+                    deep,
+                    fri,
+                    done: 0,
+                };
+            }
+            StepState::Queries {
+                iop,
+                deep,
+                fri,
+                done,
+            } => {
+                let verifier = &self.verifier;
+                verifier.fri_query(fri, iop, &mut |iop, idx| {
+                    verifier.deep_query(deep, iop, idx)
+                })?;
+                *done += 1;
+                if *done == QUERIES {
+                    iop.verify_complete();
+                    self.state = StepState::Done;
+                }
+            }
+            StepState::Done => {}
+            StepState::Failed => return Err(VerificationError::InvalidProof),
+        }
+        Ok(())
+    }
+}
@@ -46,6 +46,7 @@ fn prove_segment(group: &mut BenchGroup, hashfn: &str) {
         let opts = ProverOpts {
             hashfn: hashfn.to_string(),
             prove_guest_errors: false,
+            ..Default::default()
         };
         let prover = get_prover_server(&opts).unwrap();
         let ctx = VerifierContext::default();
@@ -109,6 +109,13 @@ fn main() {
             env::pause(exit_code);
             env::log("after");
         }
+        MultiTestSpec::PauseResume(count) => {
+            for _ in 0..count {
+                let value: u32 = env::read();
+                env::commit(&value);
+                env::pause(0);
+            }
+        }
         MultiTestSpec::ReadWriteMem { values } => {
             for (addr, value) in values.into_iter() {
                 if value != 0 {
@@ -132,6 +139,7 @@ fn main() {
             }
             env::commit(&Digest::try_from(hash).unwrap())
         }
+        MultiTestSpec::CommitDigest { digest } => env::commit_digest(digest),
         MultiTestSpec::Syscall { count } => {
             let mut input: &[u8] = &[];
             let mut input_len: usize = 0;
@@ -33,12 +33,17 @@ pub enum MultiTestSpec {
         data: Vec<u8>,
         num_iter: u32,
     },
+    CommitDigest {
+        digest: Digest,
+    },
     EventTrace,
     Profiler,
     Panic,
     Fault,
     Halt(u8),
     PauseContinue(u8),
+    /// Read a u32, commit it and pause, the given number of times.
+    PauseResume(u32),
     ReadWriteMem {
         /// Tuples of (address, value). Zero means read the value and
         /// output it; nonzero means write that value.
@@ -137,6 +137,7 @@ pub mod nr {
     declare_syscall!(pub SYS_WRITE);
     declare_syscall!(pub SYS_VERIFY);
     declare_syscall!(pub SYS_VERIFY_INTEGRITY);
+    declare_syscall!(pub SYS_COMMIT_DIGEST);
 }
 
 impl SyscallName {
@@ -779,6 +780,29 @@ pub unsafe extern "C" fn sys_verify_integrity(claim_digest: *const [u32; DIGEST_
     }
 }
 
+/// Send the digest committed by the guest in place of the journal digest to
+/// the host.
+///
+/// The guest must use the same digest for the journal field of its output, so
+/// that the host can construct the claim it proves.
+///
+/// # Safety
+///
+/// `digest` must be aligned and dereferenceable.
+#[cfg(feature = "export-syscalls")]
+#[no_mangle]
+pub unsafe extern "C" fn sys_commit_digest(digest: *const [u32; DIGEST_WORDS]) {
+    unsafe {
+        syscall_2(
+            nr::SYS_COMMIT_DIGEST,
+            null_mut(),
+            0,
+            digest as u32,
+            DIGEST_BYTES as u32,
+        )
+    };
+}
+
 // Make sure we only get one of these since it's stateful.
 #[cfg(not(feature = "export-syscalls"))]
 extern "C" {
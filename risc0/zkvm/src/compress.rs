@@ -0,0 +1,307 @@
+// Copyright 2024 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Compressed guest input.
+//!
+//! Large inputs can be provided to the guest from a compressed archive in two
+//! ways:
+//!
+//! * With [ExecutorEnvBuilder::write_compressed], the host decompresses the
+//!   archive as the guest reads the data from [env::stdin]. This costs no
+//!   guest cycles.
+//! * With [ExecutorEnvBuilder::write_compressed_bytes], the compressed bytes
+//!   are the guest input, and the guest reads them with [env::read_compressed]
+//!   and decompresses them with [Compressed::decompress]. This lets the guest
+//!   bind the archive, e.g. by committing its digest, at the cost of the
+//!   decompression cycles.
+//!
+//! [ExecutorEnvBuilder::write_compressed]: crate::ExecutorEnvBuilder::write_compressed
+//! [ExecutorEnvBuilder::write_compressed_bytes]: crate::ExecutorEnvBuilder::write_compressed_bytes
+//! [env::stdin]: crate::guest::env::stdin
+//! [env::read_compressed]: crate::guest::env::read_compressed
+
+use alloc::vec::Vec;
+
+use anyhow::{anyhow, bail, Result};
+use ruzstd::{BlockDecodingStrategy, FrameDecoder};
+use serde::{Deserialize, Serialize};
+
+/// Compression format of an archive.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[non_exhaustive]
+pub enum Codec {
+    /// [Zstandard](https://facebook.github.io/zstd/), possibly made of several
+    /// frames. Frame checksums, if present, are checked.
+    Zstd,
+}
+
+/// Compressed data written by the host with
+/// [ExecutorEnvBuilder::write_compressed_bytes](crate::ExecutorEnvBuilder::write_compressed_bytes).
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Compressed {
+    /// The compression format of [Compressed::data].
+    pub codec: Codec,
+    /// The compressed bytes.
+    #[serde(with = "bytes")]
+    pub data: Vec<u8>,
+}
+
+impl Compressed {
+    /// Decompresses the data.
+    pub fn decompress(&self) -> Result<Vec<u8>> {
+        decompress(self.codec, &self.data)
+    }
+}
+
+/// Decompresses `data` compressed with `codec`.
+pub fn decompress(codec: Codec, data: &[u8]) -> Result<Vec<u8>> {
+    match codec {
+        Codec::Zstd => zstd_decompress(data),
+    }
+}
+
+fn zstd_decompress(mut data: &[u8]) -> Result<Vec<u8>> {
+    let mut output = Vec::new();
+    let mut decoder = FrameDecoder::new();
+    while !data.is_empty() {
+        decoder
+            .reset(&mut data)
+            .map_err(|err| anyhow!("invalid zstd frame: {err}"))?;
+        decoder
+            .decode_blocks(&mut data, BlockDecodingStrategy::All)
+            .map_err(|err| anyhow!("invalid zstd frame: {err}"))?;
+        // The checksum is computed as the decoded data is collected.
+        output.extend(decoder.collect().unwrap_or_default());
+        if let (Some(expected), Some(actual)) = (
+            decoder.get_checksum_from_data(),
+            decoder.get_calculated_checksum(),
+        ) {
+            if expected != actual {
+                bail!("zstd frame checksum mismatch");
+            }
+        }
+    }
+    Ok(output)
+}
+
+/// Returns a reader of the data decompressed from the `codec` archive read
+/// from `reader`, which fails once more than `limit` bytes are decompressed.
+///
+/// The archive is decompressed as the data is read, so neither is held in
+/// memory as a whole.
+#[cfg(feature = "std")]
+pub fn decompressor<'a>(
+    codec: Codec,
+    reader: impl std::io::BufRead + 'a,
+    limit: u64,
+) -> impl std::io::Read + 'a {
+    match codec {
+        Codec::Zstd => ZstdReader {
+            source: reader,
+            decoder: FrameDecoder::new(),
+            in_frame: false,
+            buf: Vec::new(),
+            pos: 0,
+            limit,
+            remaining: limit,
+        },
+    }
+}
+
+/// A reader decompressing a Zstandard archive, possibly made of several
+/// frames, as it is read.
+#[cfg(feature = "std")]
+struct ZstdReader<R> {
+    source: R,
+    decoder: FrameDecoder,
+    in_frame: bool,
+    buf: Vec<u8>,
+    pos: usize,
+    limit: u64,
+    remaining: u64,
+}
+
+#[cfg(feature = "std")]
+impl<R: std::io::BufRead> ZstdReader<R> {
+    // The number of bytes decoded at a time.
+    const CHUNK_SIZE: usize = 1 << 16;
+
+    /// Decompresses more of the archive into the buffer, and returns whether
+    /// the end of the archive was reached instead.
+    fn fill(&mut self) -> Result<bool> {
+        if !self.in_frame {
+            if self.source.fill_buf()?.is_empty() {
+                return Ok(true);
+            }
+            self.decoder
+                .reset(&mut self.source)
+                .map_err(|err| anyhow!("invalid zstd frame: {err}"))?;
+            self.in_frame = true;
+        }
+        self.decoder
+            .decode_blocks(
+                &mut self.source,
+                BlockDecodingStrategy::UptoBytes(Self::CHUNK_SIZE),
+            )
+            .map_err(|err| anyhow!("invalid zstd frame: {err}"))?;
+        // The checksum is computed as the decoded data is collected.
+        self.buf = self.decoder.collect().unwrap_or_default();
+        self.pos = 0;
+        if self.decoder.is_finished() {
+            if let (Some(expected), Some(actual)) = (
+                self.decoder.get_checksum_from_data(),
+                self.decoder.get_calculated_checksum(),
+            ) {
+                if expected != actual {
+                    bail!("zstd frame checksum mismatch");
+                }
+            }
+            self.in_frame = false;
+        }
+        self.remaining = self
+            .remaining
+            .checked_sub(self.buf.len() as u64)
+            .ok_or_else(|| anyhow!("decompressed data exceeds {} bytes", self.limit))?;
+        Ok(false)
+    }
+}
+
+#[cfg(feature = "std")]
+impl<R: std::io::BufRead> std::io::Read for ZstdReader<R> {
+    fn read(&mut self, out: &mut [u8]) -> std::io::Result<usize> {
+        while self.pos == self.buf.len() {
+            match self.fill() {
+                Ok(true) => return Ok(0),
+                Ok(false) => {}
+                Err(err) => return Err(std::io::Error::new(std::io::ErrorKind::InvalidData, err)),
+            }
+        }
+        let len = out.len().min(self.buf.len() - self.pos);
+        out[..len].copy_from_slice(&self.buf[self.pos..self.pos + len]);
+        self.pos += len;
+        Ok(len)
+    }
+}
+
+mod bytes {
+    use alloc::vec::Vec;
+    use core::fmt;
+
+    use serde::{de, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(data: &[u8], serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_bytes(data)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<u8>, D::Error> {
+        struct BytesVisitor;
+
+        impl<'de> de::Visitor<'de> for BytesVisitor {
+            type Value = Vec<u8>;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                formatter.write_str("a byte string")
+            }
+
+            fn visit_bytes<E: de::Error>(self, v: &[u8]) -> Result<Self::Value, E> {
+                Ok(v.to_vec())
+            }
+
+            fn visit_byte_buf<E: de::Error>(self, v: Vec<u8>) -> Result<Self::Value, E> {
+                Ok(v)
+            }
+        }
+
+        deserializer.deserialize_byte_buf(BytesVisitor)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Read;
+
+    use super::{decompress, decompressor, Codec, Compressed};
+    use crate::serde::{from_slice, to_vec};
+
+    // `zstd --check` of `HELLO`.
+    const HELLO: &[u8] = b"hello zkvm, hello zkvm, hello zkvm, hello zkvm!";
+    const HELLO_ZST: &[u8] = &[
+        0x28, 0xb5, 0x2f, 0xfd, 0x24, 0x2f, 0x9d, 0x00, 0x00, 0x68, 0x68, 0x65, 0x6c, 0x6c, 0x6f,
+        0x20, 0x7a, 0x6b, 0x76, 0x6d, 0x2c, 0x20, 0x21, 0x01, 0x00, 0x77, 0x4a, 0x12, 0xe7, 0x6d,
+        0x82, 0xba,
+    ];
+
+    #[test]
+    fn zstd() {
+        assert_eq!(decompress(Codec::Zstd, HELLO_ZST).unwrap(), HELLO);
+        assert_eq!(decompress(Codec::Zstd, &[]).unwrap(), b"");
+
+        // Concatenated frames.
+        let frames = [HELLO_ZST, HELLO_ZST].concat();
+        assert_eq!(
+            decompress(Codec::Zstd, &frames).unwrap(),
+            [HELLO, HELLO].concat()
+        );
+
+        // Corrupted checksum.
+        let mut corrupted = HELLO_ZST.to_vec();
+        *corrupted.last_mut().unwrap() ^= 1;
+        assert!(decompress(Codec::Zstd, &corrupted).is_err());
+
+        // Truncated frame.
+        assert!(decompress(Codec::Zstd, &HELLO_ZST[..20]).is_err());
+    }
+
+    #[test]
+    fn zstd_streaming() {
+        let read = |data: &[u8], limit| {
+            let mut output = Vec::new();
+            decompressor(Codec::Zstd, data, limit)
+                .read_to_end(&mut output)
+                .map(|_| output)
+        };
+        assert_eq!(read(HELLO_ZST, 1 << 20).unwrap(), HELLO);
+        assert_eq!(read(&HELLO_ZST[..0], 0).unwrap(), b"");
+
+        // Concatenated frames.
+        let frames = [HELLO_ZST, HELLO_ZST].concat();
+        assert_eq!(read(&frames[..], 1 << 20).unwrap(), [HELLO, HELLO].concat());
+
+        // Decompressed size limit.
+        assert_eq!(read(HELLO_ZST, HELLO.len() as u64).unwrap(), HELLO);
+        assert!(read(HELLO_ZST, HELLO.len() as u64 - 1).is_err());
+
+        // Corrupted checksum.
+        let mut corrupted = HELLO_ZST.to_vec();
+        *corrupted.last_mut().unwrap() ^= 1;
+        assert!(read(&corrupted[..], 1 << 20).is_err());
+
+        // Truncated frame.
+        assert!(read(&HELLO_ZST[..20], 1 << 20).is_err());
+    }
+
+    #[test]
+    fn serde_roundtrip() {
+        let compressed = Compressed {
+            codec: Codec::Zstd,
+            data: HELLO_ZST.to_vec(),
+        };
+        let words = to_vec(&compressed).unwrap();
+        // The codec, the length, and the padded bytes.
+        assert_eq!(words.len(), 2 + HELLO_ZST.len().div_ceil(4));
+        let decoded: Compressed = from_slice(&words).unwrap();
+        assert_eq!(decoded, compressed);
+        assert_eq!(decoded.decompress().unwrap(), HELLO);
+    }
+}
@@ -74,13 +74,16 @@ use bytemuck::Pod;
 use risc0_zkvm_platform::{
     align_up, fileno,
     syscall::{
-        self, sys_alloc_words, sys_cycle_count, sys_halt, sys_log, sys_pause, sys_read,
-        sys_read_words, sys_verify, sys_verify_integrity, sys_write, syscall_2, SyscallName,
+        self, sys_alloc_words, sys_commit_digest, sys_cycle_count, sys_halt, sys_log, sys_pause,
+        sys_read, sys_read_words, sys_verify, sys_verify_integrity, sys_write, syscall_2,
+        SyscallName,
     },
     WORD_SIZE,
 };
 use serde::{de::DeserializeOwned, Serialize};
 
+#[cfg(feature = "compress")]
+use crate::compress::Compressed;
 use crate::{
     serde::{Deserializer, Serializer, WordRead, WordWrite},
     sha::{
@@ -97,6 +100,13 @@ static mut HASHER: OnceCell<Sha256> = OnceCell::new();
 /// [verify_integrity] calls made by the guest.
 static mut ASSUMPTIONS_DIGEST: MaybePruned<Assumptions> = MaybePruned::Pruned(Digest::ZERO);
 
+/// Digest committed with [commit_digest], in place of the digest of the
+/// journal.
+static mut COMMITTED_DIGEST: Option<Digest> = None;
+
+/// Whether any data was committed to the journal.
+static mut JOURNAL_WRITTEN: bool = false;
+
 /// A random 16 byte value initialized to random data, provided by the host, on
 /// guest start and upon resuming from a pause. Setting this value ensures that
 /// the total memory image has at least 128 bits of entropy, preventing
@@ -116,7 +126,10 @@ pub(crate) fn init() {
 pub(crate) fn finalize(halt: bool, user_exit: u8) {
     unsafe {
         let hasher = HASHER.take();
-        let journal_digest: Digest = hasher.unwrap().finalize().as_slice().try_into().unwrap();
+        let journal_digest: Digest = match COMMITTED_DIGEST.take() {
+            Some(digest) => digest,
+            None => hasher.unwrap().finalize().as_slice().try_into().unwrap(),
+        };
         let output = Output {
             journal: MaybePruned::Pruned(journal_digest),
             assumptions: MaybePruned::Pruned(ASSUMPTIONS_DIGEST.digest()),
@@ -141,7 +154,8 @@ pub fn exit(exit_code: u8) -> ! {
 
 /// Pause the execution of the zkVM.
 ///
-/// Execution may be continued at a later time.
+/// Execution may be continued at a later time, e.g. by the host with
+/// `ExecutorImpl::resume` after it provides more input.
 /// Use an exit code of 0 to indicate success, and non-zero to indicate an error.
 pub fn pause(exit_code: u8) {
     finalize(false, exit_code);
@@ -415,6 +429,23 @@ pub fn read_slice<T: Pod>(slice: &mut [T]) {
     stdin().read_slice(slice)
 }
 
+/// Read an archive written by the host with
+/// [ExecutorEnvBuilder::write_compressed_bytes](crate::ExecutorEnvBuilder::write_compressed_bytes).
+///
+/// # Example
+///
+/// ```rust,ignore
+/// use risc0_zkvm::{guest::env, sha::{Impl, Sha256}};
+///
+/// let archive = env::read_compressed();
+/// env::commit(&*Impl::hash_bytes(&archive.data));
+/// let data = archive.decompress().unwrap();
+/// ```
+#[cfg(feature = "compress")]
+pub fn read_compressed() -> Compressed {
+    read()
+}
+
 /// Serialize the given data and write it to the STDOUT of the zkVM.
 ///
 /// This is available to the host as the private output on the prover.
@@ -535,6 +566,41 @@ pub fn commit_slice<T: Pod>(slice: &[T]) {
     journal().write_slice(slice);
 }
 
+/// Commit an application-defined digest, such as a state root, in place of
+/// the journal.
+///
+/// The digest is set as the journal digest of the [Output] of the
+/// [ReceiptClaim], so that verifying the receipt takes a constant amount of
+/// data, e.g. on-chain, regardless of the size of the data committed to by the
+/// digest. The receipt then has an empty journal, and is verified with
+/// `Receipt::verify_with_digest`.
+///
+/// # Panics
+///
+/// Panics if data was committed to the journal. Data can no longer be
+/// committed to the journal after this call, and a later call replaces the
+/// committed digest.
+///
+/// # Example
+///
+/// ```no_run
+/// use risc0_zkvm::{guest::env, sha::Digest};
+///
+/// let state_root = Digest::from([1u32; 8]);
+/// env::commit_digest(state_root);
+/// ```
+pub fn commit_digest(digest: impl Into<Digest>) {
+    let digest = digest.into();
+    unsafe {
+        assert!(
+            !JOURNAL_WRITTEN,
+            "commit_digest cannot be called after committing to the journal"
+        );
+        sys_commit_digest(digest.as_ref());
+        COMMITTED_DIGEST = Some(digest);
+    }
+}
+
 /// Return the number of processor cycles that have occurred since the guest
 /// began.
 ///
@@ -563,8 +629,13 @@ pub fn stderr() -> FdWriter<impl for<'a> Fn(&'a [u8])> {
 
 /// Return a writer for the JOURNAL.
 pub fn journal() -> FdWriter<impl for<'a> Fn(&'a [u8])> {
-    FdWriter::new(fileno::JOURNAL, |bytes| {
-        unsafe { HASHER.get_mut().unwrap_unchecked().update(bytes) };
+    FdWriter::new(fileno::JOURNAL, |bytes| unsafe {
+        assert!(
+            COMMITTED_DIGEST.is_none(),
+            "cannot commit to the journal after commit_digest"
+        );
+        JOURNAL_WRITTEN = true;
+        HASHER.get_mut().unwrap_unchecked().update(bytes);
     })
 }
 
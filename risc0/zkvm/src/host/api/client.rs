@@ -20,7 +20,7 @@ use prost::Message;
 
 use super::{
     malformed_err, pb, Asset, AssetRequest, ConnectionWrapper, Connector, ParentProcessConnector,
-    SessionInfo,
+    ProveInfo, SessionInfo,
 };
 use crate::{
     get_version,
@@ -69,6 +69,17 @@ impl Client {
 
     /// Prove the specified ELF binary.
     pub fn prove(&self, env: &ExecutorEnv<'_>, opts: ProverOpts, binary: Asset) -> Result<Receipt> {
+        Ok(self.prove_info(env, opts, binary)?.receipt)
+    }
+
+    /// Prove the specified ELF binary, returning the [ProveInfo] of the
+    /// proven session.
+    pub fn prove_info(
+        &self,
+        env: &ExecutorEnv<'_>,
+        opts: ProverOpts,
+        binary: Asset,
+    ) -> Result<ProveInfo> {
         let mut conn = self.connect()?;
 
         let request = pb::api::ServerRequest {
@@ -84,16 +95,24 @@ impl Client {
         };
         conn.send(request)?;
 
-        let asset = self.prove_handler(&mut conn, env)?;
+        let done = self.prove_handler(&mut conn, env)?;
 
         let code = conn.close()?;
         if code != 0 {
             bail!("Child finished with: {code}");
         }
 
-        let receipt_bytes = asset.as_bytes()?;
+        let receipt_bytes = done.receipt.ok_or(malformed_err())?.as_bytes()?;
         let receipt_pb = pb::core::Receipt::decode(receipt_bytes)?;
-        receipt_pb.try_into()
+        Ok(ProveInfo {
+            receipt: receipt_pb.try_into()?,
+            stats: done.stats.ok_or(malformed_err())?.try_into()?,
+            fallbacks: done
+                .fallbacks
+                .into_iter()
+                .map(TryInto::try_into)
+                .collect::<Result<_>>()?,
+        })
     }
 
     /// Execute the specified ELF binary.
@@ -396,6 +415,10 @@ impl Client {
             write_fds: env.posix_io.borrow().write_fds.keys().cloned().collect(),
             segment_limit_po2: env.segment_limit_po2,
             session_limit: env.session_limit,
+            time_limit_ms: env
+                .time_limit
+                .map(|limit| limit.as_millis().try_into())
+                .transpose()?,
             trace_events: (!env.trace.is_empty()).then_some(()),
             pprof_out: env
                 .pprof_out
@@ -505,7 +528,7 @@ impl Client {
         &self,
         conn: &mut ConnectionWrapper,
         env: &ExecutorEnv<'_>,
-    ) -> Result<pb::api::Asset> {
+    ) -> Result<pb::api::OnProveDone> {
         loop {
             let reply: pb::api::ServerReply = conn.recv()?;
             tracing::trace!("rx: {reply:?}");
@@ -524,9 +547,7 @@ impl Client {
                         pb::api::client_callback::Kind::SessionDone(_) => {
                             return Err(anyhow!("Illegal client callback"))
                         }
-                        pb::api::client_callback::Kind::ProveDone(done) => {
-                            return done.receipt.ok_or(malformed_err())
-                        }
+                        pb::api::client_callback::Kind::ProveDone(done) => return Ok(done),
                     }
                 }
                 pb::api::server_reply::Kind::Error(err) => return Err(err.into()),
@@ -12,7 +12,7 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use std::{collections::BTreeMap, path::PathBuf};
+use std::{path::PathBuf, sync::Arc};
 
 use anyhow::{anyhow, bail, Result};
 use prost::{Message, Name};
@@ -25,8 +25,8 @@ use crate::{
         receipt::{decode_receipt_claim_from_seal, CompositeReceipt, InnerReceipt, SegmentReceipt},
         recursion::SuccinctReceipt,
     },
-    Assumptions, ExitCode, Journal, MaybePruned, Output, ProverOpts, Receipt, ReceiptClaim,
-    TraceEvent,
+    Artifact, Assumptions, ExitCode, FallbackKind, Journal, MaybePruned, Output, ProverOpts,
+    Receipt, ReceiptClaim, SegmentFallback, SessionStats, TraceEvent,
 };
 
 mod ver {
@@ -70,7 +70,7 @@ impl TryFrom<SuccinctReceipt> for Asset {
     type Error = anyhow::Error;
 
     fn try_from(succinct_receipt: SuccinctReceipt) -> Result<Self> {
-        Ok(Asset::Inline(bincode::serialize(&succinct_receipt)?.into()))
+        Ok(Asset::Inline(succinct_receipt.to_versioned_bytes()?.into()))
     }
 }
 
@@ -78,7 +78,7 @@ impl TryFrom<SegmentReceipt> for Asset {
     type Error = anyhow::Error;
 
     fn try_from(segment_receipt: SegmentReceipt) -> Result<Self> {
-        Ok(Asset::Inline(bincode::serialize(&segment_receipt)?.into()))
+        Ok(Asset::Inline(segment_receipt.to_versioned_bytes()?.into()))
     }
 }
 
@@ -200,6 +200,8 @@ impl From<pb::api::ProverOpts> for ProverOpts {
         Self {
             hashfn: opts.hashfn,
             prove_guest_errors: opts.prove_guest_errors,
+            max_segments: opts.max_segments.map(|limit| limit as usize),
+            max_total_cycles: opts.max_total_cycles,
         }
     }
 }
@@ -209,18 +211,71 @@ impl From<ProverOpts> for pb::api::ProverOpts {
         Self {
             hashfn: opts.hashfn,
             prove_guest_errors: opts.prove_guest_errors,
+            max_segments: opts.max_segments.map(|limit| limit as u64),
+            max_total_cycles: opts.max_total_cycles,
         }
     }
 }
 
+impl From<SessionStats> for pb::api::SessionStats {
+    fn from(value: SessionStats) -> Self {
+        Self {
+            segments: value.segments as u64,
+            total_cycles: value.total_cycles,
+            user_cycles: value.user_cycles,
+        }
+    }
+}
+
+impl TryFrom<pb::api::SessionStats> for SessionStats {
+    type Error = anyhow::Error;
+
+    fn try_from(value: pb::api::SessionStats) -> Result<Self> {
+        Ok(Self {
+            segments: value.segments.try_into()?,
+            total_cycles: value.total_cycles,
+            user_cycles: value.user_cycles,
+        })
+    }
+}
+
+impl From<SegmentFallback> for pb::api::SegmentFallback {
+    fn from(value: SegmentFallback) -> Self {
+        Self {
+            index: value.index,
+            po2: value.po2,
+            kind: Some(match value.kind {
+                FallbackKind::Split(po2) => pb::api::segment_fallback::Kind::Split(po2),
+                FallbackKind::Prover => pb::api::segment_fallback::Kind::Prover(()),
+            }),
+            error: value.error,
+        }
+    }
+}
+
+impl TryFrom<pb::api::SegmentFallback> for SegmentFallback {
+    type Error = anyhow::Error;
+
+    fn try_from(value: pb::api::SegmentFallback) -> Result<Self> {
+        Ok(Self {
+            index: value.index,
+            po2: value.po2,
+            kind: match value.kind.ok_or(malformed_err())? {
+                pb::api::segment_fallback::Kind::Split(po2) => FallbackKind::Split(po2),
+                pb::api::segment_fallback::Kind::Prover(()) => FallbackKind::Prover,
+            },
+            error: value.error,
+        })
+    }
+}
+
 impl From<MemoryImage> for pb::core::MemoryImage {
     fn from(value: MemoryImage) -> Self {
         let pages = value
-            .pages
-            .iter()
+            .pages()
             .map(|(addr, data)| pb::core::PageEntry {
-                addr: *addr,
-                data: data.clone(),
+                addr,
+                data: data.to_vec(),
             })
             .collect();
         Self {
@@ -240,17 +295,15 @@ impl TryFrom<pb::core::MemoryImage> for MemoryImage {
         if version > ver::MEMORY_IMAGE.value {
             bail!("Incompatible MemoryImage version: {version}");
         }
-        let pages = BTreeMap::from_iter(
-            value
-                .pages
-                .into_iter()
-                .map(|entry| (entry.addr, entry.data)),
-        );
-        Ok(Self {
+        let pages = value
+            .pages
+            .into_iter()
+            .map(|entry| (entry.addr, Arc::new(entry.data)));
+        Ok(Self::from_pages(
+            value.info.ok_or(malformed_err())?.try_into()?,
+            value.pc,
             pages,
-            info: value.info.ok_or(malformed_err())?.try_into()?,
-            pc: value.pc,
-        })
+        ))
     }
 }
 
@@ -38,7 +38,7 @@ use anyhow::{anyhow, Context, Result};
 use bytes::{Buf, BufMut, Bytes};
 use prost::Message;
 
-use crate::{ExitCode, Journal};
+use crate::{ExitCode, Journal, Receipt};
 
 mod pb {
     pub(crate) mod api {
@@ -313,6 +313,63 @@ pub struct SegmentInfo {
     pub cycles: u32,
 }
 
+/// Provides information about the result of proving a session.
+#[derive(Clone, Debug)]
+pub struct ProveInfo {
+    /// The [Receipt] of the session.
+    pub receipt: Receipt,
+
+    /// The [SessionStats] of the execution that was proven.
+    pub stats: SessionStats,
+
+    /// The segments that the prover could not prove as executed, in the order
+    /// they were encountered.
+    pub fallbacks: Vec<SegmentFallback>,
+}
+
+/// Records a segment that the prover could not prove as executed, e.g.
+/// because it ran out of device memory.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SegmentFallback {
+    /// The index of the segment in the executed session.
+    pub index: u32,
+
+    /// The po2 of the segment, or of the part of it, that could not be proven.
+    pub po2: u32,
+
+    /// How the segment was proven instead.
+    pub kind: FallbackKind,
+
+    /// The error that caused the fallback.
+    pub error: String,
+}
+
+/// How a segment was proven after the prover failed to prove it.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FallbackKind {
+    /// The segment was split into segments of the given po2, which were proven
+    /// in its place.
+    Split(u32),
+
+    /// The segment was proven by the fallback prover, e.g. on the CPU.
+    Prover,
+}
+
+/// Provides statistics about the execution of a session.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct SessionStats {
+    /// The number of segments in the session.
+    pub segments: usize,
+
+    /// The total number of cycles, including padding to a power of 2 in each
+    /// segment.
+    pub total_cycles: u64,
+
+    /// The number of user cycles without any overhead for continuations or po2
+    /// padding.
+    pub user_cycles: u64,
+}
+
 impl Asset {
     /// Return the bytes for this asset.
     pub fn as_bytes(&self) -> Result<Bytes> {
@@ -16,6 +16,7 @@ use std::{
     error::Error as StdError,
     io::{BufReader, Error as IoError, ErrorKind as IoErrorKind, Read, Write},
     path::{Path, PathBuf},
+    time::Duration,
 };
 
 use anyhow::{anyhow, bail, Result};
@@ -29,8 +30,8 @@ use crate::{
         client::slice_io::SliceIo, recursion::SuccinctReceipt, server::session::NullSegmentRef,
     },
     receipt_claim::{MaybePruned, ReceiptClaim},
-    ExecutorEnv, ExecutorImpl, ProverOpts, Receipt, Segment, SegmentReceipt, TraceCallback,
-    TraceEvent, VerifierContext,
+    Artifact, ExecutorEnv, ExecutorImpl, ProverOpts, Receipt, Segment, SegmentReceipt,
+    TraceCallback, TraceEvent, VerifierContext,
 };
 
 /// A server implementation for handling requests by clients of the zkVM.
@@ -270,7 +271,7 @@ impl Server {
             let mut exec = ExecutorImpl::from_elf(env, &bytes)?;
 
             let session = exec.run_with_callback(|segment| {
-                let segment_bytes = bincode::serialize(&segment)?;
+                let segment_bytes = segment.to_versioned_bytes()?;
                 let asset = pb::api::Asset::from_bytes(
                     &segments_out,
                     segment_bytes.into(),
@@ -342,9 +343,9 @@ impl Server {
             let opts: ProverOpts = request.opts.ok_or(malformed_err())?.into();
             let prover = get_prover_server(&opts)?;
             let ctx = VerifierContext::default();
-            let receipt = prover.prove_with_ctx(env, &ctx, &bytes)?;
+            let info = prover.prove_info(env, &ctx, &bytes)?;
 
-            let receipt_pb: pb::core::Receipt = receipt.into();
+            let receipt_pb: pb::core::Receipt = info.receipt.into();
             let receipt_bytes = receipt_pb.encode_to_vec();
             let asset = pb::api::Asset::from_bytes(
                 &request.receipt_out.ok_or(malformed_err())?,
@@ -357,6 +358,8 @@ impl Server {
                     kind: Some(pb::api::client_callback::Kind::ProveDone(
                         pb::api::OnProveDone {
                             receipt: Some(asset),
+                            stats: Some(info.stats.into()),
+                            fallbacks: info.fallbacks.into_iter().map(Into::into).collect(),
                         },
                     )),
                 })),
@@ -381,7 +384,7 @@ impl Server {
         fn inner(request: pb::api::ProveSegmentRequest) -> Result<pb::api::ProveSegmentReply> {
             let opts: ProverOpts = request.opts.ok_or(malformed_err())?.into();
             let segment_bytes = request.segment.ok_or(malformed_err())?.as_bytes()?;
-            let segment: Segment = bincode::deserialize(&segment_bytes)?;
+            let segment = Segment::from_versioned_bytes(&segment_bytes)?;
 
             let prover = get_prover_server(&opts)?;
             let ctx = VerifierContext::default();
@@ -420,7 +423,7 @@ impl Server {
         fn inner(request: pb::api::LiftRequest) -> Result<pb::api::LiftReply> {
             let opts: ProverOpts = request.opts.ok_or(malformed_err())?.into();
             let receipt_bytes = request.receipt.ok_or(malformed_err())?.as_bytes()?;
-            let segment_receipt: SegmentReceipt = bincode::deserialize(&receipt_bytes)?;
+            let segment_receipt = SegmentReceipt::from_versioned_bytes(&receipt_bytes)?;
 
             let prover = get_prover_server(&opts)?;
             let receipt = prover.lift(&segment_receipt)?;
@@ -454,10 +457,10 @@ impl Server {
         fn inner(request: pb::api::JoinRequest) -> Result<pb::api::JoinReply> {
             let opts: ProverOpts = request.opts.ok_or(malformed_err())?.into();
             let left_receipt_bytes = request.left_receipt.ok_or(malformed_err())?.as_bytes()?;
-            let left_succinct_receipt: SuccinctReceipt = bincode::deserialize(&left_receipt_bytes)?;
+            let left_succinct_receipt = SuccinctReceipt::from_versioned_bytes(&left_receipt_bytes)?;
             let right_receipt_bytes = request.right_receipt.ok_or(malformed_err())?.as_bytes()?;
-            let right_succinct_receipt: SuccinctReceipt =
-                bincode::deserialize(&right_receipt_bytes)?;
+            let right_succinct_receipt =
+                SuccinctReceipt::from_versioned_bytes(&right_receipt_bytes)?;
 
             let prover = get_prover_server(&opts)?;
             let receipt = prover.join(&left_succinct_receipt, &right_succinct_receipt)?;
@@ -498,14 +501,14 @@ impl Server {
                 .conditional_receipt
                 .ok_or(malformed_err())?
                 .as_bytes()?;
-            let conditional_succinct_receipt: SuccinctReceipt =
-                bincode::deserialize(&conditional_receipt_bytes)?;
+            let conditional_succinct_receipt =
+                SuccinctReceipt::from_versioned_bytes(&conditional_receipt_bytes)?;
             let assumption_receipt_bytes = request
                 .assumption_receipt
                 .ok_or(malformed_err())?
                 .as_bytes()?;
-            let assumption_succinct_receipt: SuccinctReceipt =
-                bincode::deserialize(&assumption_receipt_bytes)?;
+            let assumption_succinct_receipt =
+                SuccinctReceipt::from_versioned_bytes(&assumption_receipt_bytes)?;
 
             let prover = get_prover_server(&opts)?;
             let receipt =
@@ -544,7 +547,7 @@ impl Server {
         fn inner(request: pb::api::IdentityP254Request) -> Result<pb::api::IdentityP254Reply> {
             let opts: ProverOpts = request.opts.ok_or(malformed_err())?.into();
             let receipt_bytes = request.receipt.ok_or(malformed_err())?.as_bytes()?;
-            let succinct_receipt: SuccinctReceipt = bincode::deserialize(&receipt_bytes)?;
+            let succinct_receipt = SuccinctReceipt::from_versioned_bytes(&receipt_bytes)?;
 
             let prover = get_prover_server(&opts)?;
             let receipt = prover.identity_p254(&succinct_receipt)?;
@@ -603,6 +606,9 @@ fn build_env<'a>(
         env_builder.segment_limit_po2(segment_limit_po2);
     }
     env_builder.session_limit(request.session_limit);
+    if let Some(time_limit_ms) = request.time_limit_ms {
+        env_builder.time_limit(Duration::from_millis(time_limit_ms));
+    }
     if let Some(_) = request.trace_events {
         let proxy = TraceProxy::new(conn.try_clone()?);
         env_builder.trace_callback(proxy);
@@ -0,0 +1,292 @@
+// Copyright 2024 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Version tags embedded in serialized artifacts.
+
+use std::fmt;
+
+use anyhow::{anyhow, bail, Result};
+use risc0_zkp::verify::{check_seal_version, VerificationError};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+
+use crate::{Receipt, SegmentReceipt, SuccinctReceipt};
+
+// Marks the start of a versioned artifact, so that artifacts written before
+// version tags were introduced are reported as such.
+const MAGIC: [u8; 4] = *b"R0AF";
+
+/// Versions of the zkVM and of the rv32im circuit that wrote an artifact.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ArtifactVersion {
+    /// Version of the `risc0-zkvm` crate.
+    pub zkvm: String,
+
+    /// Version of the `risc0-circuit-rv32im` crate.
+    pub circuit: String,
+
+    /// [SEAL_VERSION](risc0_circuit_rv32im::SEAL_VERSION) of the rv32im
+    /// circuit.
+    pub rv32im_seal: u32,
+
+    /// [SEAL_VERSION](risc0_circuit_recursion::SEAL_VERSION) of the recursion
+    /// circuit.
+    pub recursion_seal: u32,
+}
+
+impl ArtifactVersion {
+    /// The versions of this build.
+    pub fn current() -> Self {
+        Self {
+            zkvm: crate::VERSION.to_string(),
+            circuit: risc0_circuit_rv32im::VERSION.to_string(),
+            rv32im_seal: risc0_circuit_rv32im::SEAL_VERSION,
+            recursion_seal: risc0_circuit_recursion::SEAL_VERSION,
+        }
+    }
+
+    /// Checks that the seals of an artifact written with the versions in
+    /// `self` can be verified by a build with the versions in `other`.
+    pub fn check_seal_versions(&self, other: &ArtifactVersion) -> Result<(), VerificationError> {
+        check_seal_version(self.rv32im_seal, other.rv32im_seal)?;
+        check_seal_version(self.recursion_seal, other.recursion_seal)
+    }
+
+    /// Returns `true` if an artifact written with the versions in `self` can
+    /// be loaded by a build with the versions in `other`.
+    ///
+    /// Artifacts are compatible across patch releases. Pre-releases are only
+    /// compatible with themselves.
+    pub fn is_compatible(&self, other: &ArtifactVersion) -> bool {
+        is_compatible(&self.zkvm, &other.zkvm)
+            && is_compatible(&self.circuit, &other.circuit)
+            && self.check_seal_versions(other).is_ok()
+    }
+}
+
+impl fmt::Display for ArtifactVersion {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "risc0-zkvm {} (rv32im circuit {}, seal versions {}/{})",
+            self.zkvm, self.circuit, self.rv32im_seal, self.recursion_seal
+        )
+    }
+}
+
+/// Error returned when loading an artifact written by an incompatible version
+/// of the zkVM.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ArtifactVersionMismatch {
+    /// The kind of artifact, such as `Segment` or `Receipt`.
+    pub kind: String,
+
+    /// The versions that wrote the artifact.
+    pub found: ArtifactVersion,
+
+    /// The versions of this build.
+    pub expected: ArtifactVersion,
+}
+
+impl fmt::Display for ArtifactVersionMismatch {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{} was serialized by {}, which is incompatible with {}",
+            self.kind, self.found, self.expected
+        )
+    }
+}
+
+impl std::error::Error for ArtifactVersionMismatch {}
+
+#[derive(Serialize, Deserialize)]
+struct Header {
+    magic: [u8; 4],
+    kind: String,
+    version: ArtifactVersion,
+}
+
+/// A value that is serialized together with the versions that wrote it.
+///
+/// The encoding is a bincode header holding the [ArtifactVersion], followed by
+/// the bincode encoding of the value. Loading checks the header before
+/// decoding the value, so that an artifact written by an incompatible version
+/// fails with an [ArtifactVersionMismatch] rather than a decoding error.
+pub trait Artifact: Serialize + DeserializeOwned {
+    /// The name of this kind of artifact, as reported in errors.
+    const KIND: &'static str;
+
+    /// Serialize this artifact, tagged with the [current](ArtifactVersion::current)
+    /// versions.
+    fn to_versioned_bytes(&self) -> Result<Vec<u8>> {
+        let header = Header {
+            magic: MAGIC,
+            kind: Self::KIND.to_string(),
+            version: ArtifactVersion::current(),
+        };
+        let mut bytes = bincode::serialize(&header)?;
+        bincode::serialize_into(&mut bytes, self)?;
+        Ok(bytes)
+    }
+
+    /// Deserialize an artifact written by [Artifact::to_versioned_bytes],
+    /// checking that it was written by a compatible version.
+    fn from_versioned_bytes(bytes: &[u8]) -> Result<Self> {
+        if !bytes.starts_with(&MAGIC) {
+            bail!(
+                "{} has no version tag: it was serialized by a release of risc0-zkvm that predates version tags, or is corrupted",
+                Self::KIND
+            );
+        }
+        let mut reader = bytes;
+        let header: Header = bincode::deserialize_from(&mut reader)
+            .map_err(|err| anyhow!("malformed {} version tag: {err}", Self::KIND))?;
+        if header.kind != Self::KIND {
+            bail!("expected a {}, found a {}", Self::KIND, header.kind);
+        }
+        let expected = ArtifactVersion::current();
+        header.version.check_seal_versions(&expected)?;
+        if !header.version.is_compatible(&expected) {
+            return Err(ArtifactVersionMismatch {
+                kind: header.kind,
+                found: header.version,
+                expected,
+            }
+            .into());
+        }
+        Ok(bincode::deserialize(reader)?)
+    }
+}
+
+impl Artifact for Receipt {
+    const KIND: &'static str = "Receipt";
+}
+
+impl Artifact for SegmentReceipt {
+    const KIND: &'static str = "SegmentReceipt";
+}
+
+impl Artifact for SuccinctReceipt {
+    const KIND: &'static str = "SuccinctReceipt";
+}
+
+fn is_compatible(found: &str, expected: &str) -> bool {
+    let (Ok(found), Ok(expected)) = (
+        semver::Version::parse(found),
+        semver::Version::parse(expected),
+    ) else {
+        return false;
+    };
+    if !found.pre.is_empty() || !expected.pre.is_empty() {
+        return found == expected;
+    }
+    found.major == expected.major && found.minor == expected.minor
+}
+
+#[cfg(test)]
+mod tests {
+    use risc0_zkp::verify::VerificationError;
+
+    use super::{Artifact, ArtifactVersion, ArtifactVersionMismatch, Header, MAGIC};
+    use crate::{sha::Digest, ExitCode, InnerReceipt, MaybePruned, Receipt, ReceiptClaim};
+
+    fn receipt() -> Receipt {
+        let claim = ReceiptClaim {
+            pre: MaybePruned::Pruned(Digest::ZERO),
+            post: MaybePruned::Pruned(Digest::ZERO),
+            exit_code: ExitCode::Halted(0),
+            input: Digest::ZERO,
+            output: None.into(),
+        };
+        Receipt::new(InnerReceipt::Fake { claim }, b"journal".to_vec())
+    }
+
+    fn with_header(version: ArtifactVersion) -> Vec<u8> {
+        let header = Header {
+            magic: MAGIC,
+            kind: Receipt::KIND.to_string(),
+            version,
+        };
+        let mut bytes = bincode::serialize(&header).unwrap();
+        bincode::serialize_into(&mut bytes, &receipt()).unwrap();
+        bytes
+    }
+
+    #[test]
+    fn roundtrip() {
+        let bytes = receipt().to_versioned_bytes().unwrap();
+        assert_eq!(Receipt::from_versioned_bytes(&bytes).unwrap(), receipt());
+    }
+
+    #[test]
+    fn unversioned() {
+        let bytes = bincode::serialize(&receipt()).unwrap();
+        let err = Receipt::from_versioned_bytes(&bytes).unwrap_err();
+        assert!(err.to_string().contains("no version tag"));
+    }
+
+    #[test]
+    fn mismatch() {
+        let found = ArtifactVersion {
+            zkvm: "0.1.0".to_string(),
+            circuit: "0.1.0".to_string(),
+            ..ArtifactVersion::current()
+        };
+        let err = Receipt::from_versioned_bytes(&with_header(found.clone())).unwrap_err();
+        assert_eq!(
+            err.downcast_ref::<ArtifactVersionMismatch>(),
+            Some(&ArtifactVersionMismatch {
+                kind: "Receipt".to_string(),
+                found,
+                expected: ArtifactVersion::current(),
+            })
+        );
+
+        let mut patch = semver::Version::parse(crate::VERSION).unwrap();
+        patch.patch += 1;
+        let compatible = ArtifactVersion {
+            zkvm: patch.to_string(),
+            ..ArtifactVersion::current()
+        };
+        assert_eq!(
+            compatible.is_compatible(&ArtifactVersion::current()),
+            patch.pre.is_empty()
+        );
+    }
+
+    #[test]
+    fn seal_mismatch() {
+        let current = ArtifactVersion::current();
+        let found = ArtifactVersion {
+            recursion_seal: current.recursion_seal + 1,
+            ..current.clone()
+        };
+        assert!(!found.is_compatible(&current));
+        let err = Receipt::from_versioned_bytes(&with_header(found.clone())).unwrap_err();
+        assert_eq!(
+            err.downcast_ref::<VerificationError>(),
+            Some(&VerificationError::SealVersionMismatch {
+                found: found.recursion_seal,
+                supported: current.recursion_seal,
+            })
+        );
+        assert_eq!(
+            err.to_string(),
+            format!(
+                "seal version {}, verifier supports {}",
+                found.recursion_seal, current.recursion_seal
+            )
+        );
+    }
+}
@@ -0,0 +1,236 @@
+// Copyright 2024 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Export of the executor and prover phase timings in the Chrome trace event
+//! format.
+
+use std::{
+    cell::Cell,
+    fs::File,
+    io::{BufWriter, Write},
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, Mutex,
+    },
+    time::Instant,
+};
+
+use anyhow::Result;
+use serde_json::{json, Map, Value};
+use tracing::{
+    field::{Field, Visit},
+    span::{Attributes, Id, Record},
+    Subscriber,
+};
+use tracing_subscriber::{layer::Context, registry::LookupSpan, Layer};
+
+/// A [Layer] recording the time spent in each span, such as the execution,
+/// the witness generation and the commitments of each segment, FRI and the
+/// recursion programs, as Chrome trace events.
+///
+/// The events are written to a JSON file when the [ChromeTraceGuard] returned
+/// with the layer is dropped, and can be loaded in Perfetto
+/// (<https://ui.perfetto.dev>) or `chrome://tracing`.
+///
+/// ```no_run
+/// use tracing_subscriber::prelude::*;
+///
+/// let (layer, _guard) = risc0_zkvm::ChromeTraceLayer::new("trace.json");
+/// tracing_subscriber::registry().with(layer).init();
+/// ```
+pub struct ChromeTraceLayer {
+    events: Arc<Mutex<Vec<Value>>>,
+    start: Instant,
+}
+
+/// Writes the events recorded by a [ChromeTraceLayer] when dropped.
+pub struct ChromeTraceGuard {
+    events: Arc<Mutex<Vec<Value>>>,
+    path: PathBuf,
+}
+
+// Fields of a span, and the time it was last entered.
+struct SpanData {
+    args: Map<String, Value>,
+    entered: Option<Instant>,
+}
+
+struct ArgsVisitor<'a>(&'a mut Map<String, Value>);
+
+impl Visit for ArgsVisitor<'_> {
+    fn record_u64(&mut self, field: &Field, value: u64) {
+        self.0.insert(field.name().into(), value.into());
+    }
+
+    fn record_i64(&mut self, field: &Field, value: i64) {
+        self.0.insert(field.name().into(), value.into());
+    }
+
+    fn record_bool(&mut self, field: &Field, value: bool) {
+        self.0.insert(field.name().into(), value.into());
+    }
+
+    fn record_str(&mut self, field: &Field, value: &str) {
+        self.0.insert(field.name().into(), value.into());
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+        self.0
+            .insert(field.name().into(), format!("{value:?}").into());
+    }
+}
+
+// Chrome traces identify threads by number.
+fn thread_id() -> u64 {
+    static NEXT_ID: AtomicU64 = AtomicU64::new(1);
+    thread_local! {
+        static ID: Cell<u64> = const { Cell::new(0) };
+    }
+    ID.with(|id| {
+        if id.get() == 0 {
+            id.set(NEXT_ID.fetch_add(1, Ordering::Relaxed));
+        }
+        id.get()
+    })
+}
+
+impl ChromeTraceLayer {
+    /// Creates a layer recording the events to be written to `path`.
+    pub fn new(path: impl AsRef<Path>) -> (Self, ChromeTraceGuard) {
+        let events = Arc::new(Mutex::new(Vec::new()));
+        let layer = Self {
+            events: events.clone(),
+            start: Instant::now(),
+        };
+        let guard = ChromeTraceGuard {
+            events,
+            path: path.as_ref().to_path_buf(),
+        };
+        (layer, guard)
+    }
+}
+
+impl<S> Layer<S> for ChromeTraceLayer
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+{
+    fn on_new_span(&self, attrs: &Attributes<'_>, id: &Id, ctx: Context<'_, S>) {
+        let mut args = Map::new();
+        attrs.record(&mut ArgsVisitor(&mut args));
+        if let Some(span) = ctx.span(id) {
+            span.extensions_mut().insert(SpanData {
+                args,
+                entered: None,
+            });
+        }
+    }
+
+    fn on_record(&self, id: &Id, values: &Record<'_>, ctx: Context<'_, S>) {
+        if let Some(span) = ctx.span(id) {
+            if let Some(data) = span.extensions_mut().get_mut::<SpanData>() {
+                values.record(&mut ArgsVisitor(&mut data.args));
+            }
+        }
+    }
+
+    fn on_enter(&self, id: &Id, ctx: Context<'_, S>) {
+        if let Some(span) = ctx.span(id) {
+            if let Some(data) = span.extensions_mut().get_mut::<SpanData>() {
+                data.entered = Some(Instant::now());
+            }
+        }
+    }
+
+    fn on_exit(&self, id: &Id, ctx: Context<'_, S>) {
+        let Some(span) = ctx.span(id) else {
+            return;
+        };
+        let mut extensions = span.extensions_mut();
+        let Some(data) = extensions.get_mut::<SpanData>() else {
+            return;
+        };
+        let Some(entered) = data.entered.take() else {
+            return;
+        };
+        // A complete event, with times in microseconds.
+        let event = json!({
+            "name": span.name(),
+            "cat": span.metadata().target(),
+            "ph": "X",
+            "ts": entered.duration_since(self.start).as_secs_f64() * 1e6,
+            "dur": entered.elapsed().as_secs_f64() * 1e6,
+            "pid": 1,
+            "tid": thread_id(),
+            "args": data.args,
+        });
+        self.events.lock().unwrap().push(event);
+    }
+}
+
+impl ChromeTraceGuard {
+    /// Writes the events recorded so far to the file of the layer.
+    pub fn flush(&self) -> Result<()> {
+        let events = self.events.lock().unwrap();
+        let mut file = BufWriter::new(File::create(&self.path)?);
+        serde_json::to_writer(&mut file, &json!({ "traceEvents": *events }))?;
+        file.flush()?;
+        Ok(())
+    }
+}
+
+impl Drop for ChromeTraceGuard {
+    fn drop(&mut self) {
+        if let Err(err) = self.flush() {
+            eprintln!(
+                "failed to write the Chrome trace to {}: {err}",
+                self.path.display()
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tracing_subscriber::prelude::*;
+
+    use super::*;
+
+    #[test]
+    fn nested_spans() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("trace.json");
+        let (layer, guard) = ChromeTraceLayer::new(&path);
+        let subscriber = tracing_subscriber::registry().with(layer);
+        tracing::subscriber::with_default(subscriber, || {
+            tracing::info_span!("prove_segment", index = 3).in_scope(|| {
+                tracing::info_span!("fri_prove").in_scope(|| {});
+            });
+        });
+        drop(guard);
+
+        let trace: Value = serde_json::from_slice(&std::fs::read(&path).unwrap()).unwrap();
+        let events = trace["traceEvents"].as_array().unwrap();
+        assert_eq!(events.len(), 2);
+        let (inner, outer) = (&events[0], &events[1]);
+        assert_eq!(inner["name"], "fri_prove");
+        assert_eq!(outer["name"], "prove_segment");
+        assert_eq!(outer["ph"], "X");
+        assert_eq!(outer["args"]["index"], 3);
+        assert_eq!(inner["tid"], outer["tid"]);
+        let end = |event: &Value| event["ts"].as_f64().unwrap() + event["dur"].as_f64().unwrap();
+        assert!(outer["ts"].as_f64() <= inner["ts"].as_f64());
+        assert!(end(inner) <= end(outer));
+    }
+}
@@ -17,11 +17,13 @@
 use std::{
     cell::RefCell,
     collections::HashMap,
+    fmt,
     io::{BufRead, BufReader, Cursor, Read, Write},
     mem,
     path::{Path, PathBuf},
     rc::Rc,
     sync::Arc,
+    time::Duration,
 };
 
 use anyhow::Result;
@@ -31,10 +33,12 @@ use risc0_zkvm_platform::{self, fileno};
 use serde::Serialize;
 use tempfile::TempDir;
 
+#[cfg(feature = "compress")]
+use crate::compress::{self, Codec, Compressed};
 use crate::{
     host::client::{
         posix_io::PosixIo,
-        slice_io::{slice_io_from_fn, SliceIo, SliceIoTable},
+        slice_io::{slice_io_from_fn, slice_io_from_send_fn, SliceIo, SliceIoTable},
     },
     serde::to_vec,
     Assumption, TraceCallback,
@@ -73,6 +77,7 @@ pub struct ExecutorEnv<'a> {
     pub(crate) args: Vec<String>,
     pub(crate) segment_limit_po2: Option<u32>,
     pub(crate) session_limit: Option<u64>,
+    pub(crate) time_limit: Option<Duration>,
     pub(crate) posix_io: Rc<RefCell<PosixIo<'a>>>,
     pub(crate) slice_io: Rc<RefCell<SliceIoTable<'a>>>,
     pub(crate) input: Vec<u8>,
@@ -80,8 +85,43 @@ pub struct ExecutorEnv<'a> {
     pub(crate) assumptions: Rc<RefCell<Assumptions>>,
     pub(crate) segment_path: Option<SegmentPath>,
     pub(crate) pprof_out: Option<PathBuf>,
+    pub(crate) version_skew: VersionSkew,
 }
 
+/// What the executor does with a guest built for another version of
+/// risc0-zkvm than the host, see [ExecutorEnvBuilder::version_skew].
+///
+/// Versions are compared by family: the major and minor versions before 1.0,
+/// and the major version after. Guests built without a recorded version, and
+/// guests run from a [MemoryImage](risc0_binfmt::MemoryImage), are always
+/// accepted.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum VersionSkew {
+    /// Run the guest without checking its version.
+    Ignore,
+    /// Log a warning and run the guest.
+    #[default]
+    Warn,
+    /// Refuse to run the guest.
+    Error,
+}
+
+/// Error returned when an execution exceeds the
+/// [time limit](ExecutorEnvBuilder::time_limit) of its [ExecutorEnv].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TimeLimitExceeded {
+    /// The time limit of the execution.
+    pub limit: Duration,
+}
+
+impl fmt::Display for TimeLimitExceeded {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Time limit exceeded: {:?}", self.limit)
+    }
+}
+
+impl std::error::Error for TimeLimitExceeded {}
+
 impl<'a> ExecutorEnv<'a> {
     /// Construct a [ExecutorEnvBuilder].
     ///
@@ -156,6 +196,59 @@ impl<'a> ExecutorEnvBuilder<'a> {
         self
     }
 
+    /// Set a time limit on the wall-clock duration of the execution.
+    ///
+    /// Execution is aborted with a [TimeLimitExceeded] error once the limit is
+    /// exceeded. The limit is checked at the end of each segment, and before
+    /// and after each syscall, so unlike the [session limit](Self::session_limit)
+    /// it also bounds a guest that spends its time waiting on the host.
+    ///
+    /// Callbacks added with [io_callback_send](Self::io_callback_send) run on
+    /// their own thread, and execution is aborted at the limit even if they
+    /// block. [SliceIo] handlers are given the deadline, see
+    /// [SliceIo::handle_io_until].
+    ///
+    /// # Limitations
+    ///
+    /// Other host syscall handlers are not cancelled: the readers and writers
+    /// of the environment, and [io_callback](Self::io_callback) callbacks, run
+    /// to completion, and the limit is only checked once they return. A
+    /// handler that blocks forever, e.g. a [stdin](Self::stdin) that never
+    /// delivers data, keeps the execution running past the limit.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::time::Duration;
+    ///
+    /// use risc0_zkvm::ExecutorEnv;
+    ///
+    /// let env = ExecutorEnv::builder()
+    ///     .time_limit(Duration::from_secs(60))
+    ///     .build()
+    ///     .unwrap();
+    /// ```
+    pub fn time_limit(&mut self, limit: Duration) -> &mut Self {
+        self.inner.time_limit = Some(limit);
+        self
+    }
+
+    /// Set what happens when the guest was built for another version family
+    /// of risc0-zkvm than the host, which warns by default.
+    ///
+    /// Guests and hosts of different versions may disagree on the syscalls,
+    /// the serialization of the inputs or the receipt format.
+    ///
+    /// The version is read from the symbols of the guest ELF, so it is only
+    /// checked for guests given as an ELF, e.g. by
+    /// [ExecutorImpl::from_elf](crate::ExecutorImpl::from_elf). Guests given
+    /// as a [MemoryImage](risc0_binfmt::MemoryImage) are run without checking
+    /// it.
+    pub fn version_skew(&mut self, policy: VersionSkew) -> &mut Self {
+        self.inner.version_skew = policy;
+        self
+    }
+
     /// Add environment variables to the guest environment.
     ///
     /// # Example
@@ -269,6 +362,57 @@ impl<'a> ExecutorEnvBuilder<'a> {
         self
     }
 
+    /// Set the zkVM guest stdin to the data decompressed from a `codec`
+    /// archive.
+    ///
+    /// The archive is decompressed on the host as the guest reads the data
+    /// from [env::stdin](crate::guest::env::stdin), so this costs no guest
+    /// cycles, and neither the archive nor the data is held in memory as a
+    /// whole. Reading fails once more than `limit` bytes are decompressed, so
+    /// that a small archive cannot exhaust the memory of the host.
+    ///
+    /// Like [ExecutorEnvBuilder::stdin], this replaces the data written with
+    /// [ExecutorEnvBuilder::write] and similar methods.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use risc0_zkvm::{compress::Codec, ExecutorEnv};
+    ///
+    /// let archive = std::fs::File::open("input.zst").unwrap();
+    /// let env = ExecutorEnv::builder()
+    ///     .write_compressed(archive, Codec::Zstd, 1 << 30)
+    ///     .build()
+    ///     .unwrap();
+    /// ```
+    #[cfg(feature = "compress")]
+    pub fn write_compressed(
+        &mut self,
+        reader: impl Read + 'a,
+        codec: Codec,
+        limit: u64,
+    ) -> &mut Self {
+        self.stdin(compress::decompressor(codec, BufReader::new(reader), limit))
+    }
+
+    /// Write a `codec` archive to the zkVM guest stdin, to be decompressed by
+    /// the guest.
+    ///
+    /// The guest reads the archive with
+    /// [env::read_compressed](crate::guest::env::read_compressed). Since the
+    /// compressed bytes are part of the guest input, the guest can bind them,
+    /// e.g. by committing their digest, before decompressing them.
+    #[cfg(feature = "compress")]
+    pub fn write_compressed_bytes(
+        &mut self,
+        mut reader: impl Read,
+        codec: Codec,
+    ) -> Result<&mut Self> {
+        let mut data = Vec::new();
+        reader.read_to_end(&mut data)?;
+        self.write(&Compressed { codec, data })
+    }
+
     /// Add a posix-style standard input.
     pub fn stdin(&mut self, reader: impl Read + 'a) -> &mut Self {
         self.read_fd(fileno::STDIN, BufReader::new(reader))
@@ -318,6 +462,25 @@ impl<'a> ExecutorEnvBuilder<'a> {
         self
     }
 
+    /// Add a handler for simple I/O handling, which is abandoned if it runs
+    /// past the [time limit](Self::time_limit).
+    ///
+    /// When a time limit is set, each call of `callback` runs on a new thread,
+    /// and execution is aborted with a [TimeLimitExceeded] error if it has not
+    /// returned by the deadline. The thread is left to finish on its own.
+    /// Without a time limit, this is the same as [io_callback](Self::io_callback).
+    pub fn io_callback_send<C: AsRef<str>>(
+        &mut self,
+        channel: C,
+        callback: impl Fn(Bytes) -> Result<Bytes> + Send + Sync + 'static,
+    ) -> &mut Self {
+        self.inner
+            .slice_io
+            .borrow_mut()
+            .with_handler(channel.as_ref(), slice_io_from_send_fn(callback));
+        self
+    }
+
     /// Add an [Assumption] to the [ExecutorEnv], for use in [composition].
     ///
     /// During execution, when the guest calls `env::verify` or
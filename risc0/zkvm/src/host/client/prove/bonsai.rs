@@ -15,10 +15,15 @@
 use std::time::Duration;
 
 use anyhow::{anyhow, bail, ensure, Context, Result};
-use bonsai_sdk::alpha::Client;
+use bonsai_sdk::alpha::{Client, ReceiptVerifier};
+use hex::FromHex;
 
 use super::Prover;
-use crate::{compute_image_id, sha::Digestible, ExecutorEnv, ProverOpts, Receipt, VerifierContext};
+use crate::{
+    compute_image_id,
+    sha::{Digest, Digestible},
+    ExecutorEnv, ProverOpts, Receipt, VerifierContext,
+};
 
 /// An implementation of a [Prover] that runs proof workloads via Bonsai.
 ///
@@ -76,53 +81,71 @@ impl Prover for BonsaiProver {
         let session = client.create_session(image_id_hex, input_id, receipts_ids)?;
         tracing::debug!("Bonsai proving SessionID: {}", session.uuid);
 
-        loop {
-            // The session has already been started in the executor. Poll bonsai to check if
-            // the proof request succeeded.
-            let res = session.status(&client)?;
-            if res.status == "RUNNING" {
-                std::thread::sleep(Duration::from_secs(5));
-                continue;
-            }
-            if res.status == "SUCCEEDED" {
-                // Download the receipt, containing the output
-                let receipt_url = res
-                    .receipt_url
-                    .ok_or(anyhow!("API error, missing receipt on completed session"))?;
-
-                let stats = res
-                    .stats
-                    .context("Missing stats object on Bonsai status res")?;
-                tracing::debug!(
-                    "Bonsai usage: cycles: {} total_cycles: {}",
-                    stats.cycles,
-                    stats.total_cycles
-                );
-
-                let receipt_buf = client.download(&receipt_url)?;
-                let receipt: Receipt = bincode::deserialize(&receipt_buf)?;
-
-                if opts.prove_guest_errors {
-                    receipt.verify_integrity_with_context(ctx)?;
-                    ensure!(
-                        receipt.get_claim()?.pre.digest() == image_id,
-                        "received unexpected image ID: expected {}, found {}",
-                        hex::encode(image_id),
-                        hex::encode(receipt.get_claim()?.pre.digest())
-                    );
-                } else {
-                    receipt.verify_with_context(ctx, image_id)?;
-                }
-                return Ok(receipt);
-            } else {
-                bail!(
-                    "Bonsai prover workflow [{}] exited: {} err: {}",
-                    session.uuid,
-                    res.status,
-                    res.error_msg
-                        .unwrap_or("Bonsai workflow missing error_msg".into()),
-                );
-            }
+        // The session has already been started in the executor. Poll bonsai until
+        // the proof request is done, however long it takes.
+        let res = session.poll_until_done(&client, Duration::MAX, Duration::from_secs(5))?;
+        if res.status != "SUCCEEDED" {
+            bail!(
+                "Bonsai prover workflow [{}] exited: {} err: {}",
+                session.uuid,
+                res.status,
+                res.error_msg
+                    .unwrap_or("Bonsai workflow missing error_msg".into()),
+            );
         }
+
+        // Download the receipt, containing the output
+        let receipt_url = res
+            .receipt_url
+            .ok_or(anyhow!("API error, missing receipt on completed session"))?;
+
+        let stats = res
+            .stats
+            .context("Missing stats object on Bonsai status res")?;
+        tracing::debug!(
+            "Bonsai usage: cycles: {} total_cycles: {}",
+            stats.cycles,
+            stats.total_cycles
+        );
+
+        let receipt_buf = client.download(&receipt_url)?;
+        let receipt: Receipt = bincode::deserialize(&receipt_buf)?;
+
+        if opts.prove_guest_errors {
+            receipt.verify_integrity_with_context(ctx)?;
+            ensure!(
+                receipt.get_claim()?.pre.digest() == image_id,
+                "received unexpected image ID: expected {}, found {}",
+                hex::encode(image_id),
+                hex::encode(receipt.get_claim()?.pre.digest())
+            );
+        } else {
+            receipt.verify_with_context(ctx, image_id)?;
+        }
+        Ok(receipt)
+    }
+}
+
+/// A [ReceiptVerifier] checking the receipts downloaded by a Bonsai [Client]
+/// with [Receipt::verify].
+///
+/// ```no_run
+/// use bonsai_sdk::alpha::Client;
+/// use risc0_zkvm::BonsaiReceiptVerifier;
+///
+/// let client = Client::from_env(risc0_zkvm::VERSION)
+///     .expect("Failed to construct sdk client")
+///     .with_receipt_verifier(BonsaiReceiptVerifier);
+/// ```
+#[derive(Clone, Copy, Debug, Default)]
+pub struct BonsaiReceiptVerifier;
+
+impl ReceiptVerifier for BonsaiReceiptVerifier {
+    fn verify(&self, receipt: &[u8], image_id: &str) -> Result<(), String> {
+        let image_id = Digest::from_hex(image_id)
+            .map_err(|err| format!("invalid image ID {image_id}: {err}"))?;
+        let receipt: Receipt =
+            bincode::deserialize(receipt).map_err(|err| format!("malformed receipt: {err}"))?;
+        receipt.verify(image_id).map_err(|err| err.to_string())
     }
 }
@@ -19,7 +19,7 @@ use anyhow::{ensure, Result};
 use super::{Executor, Prover, ProverOpts};
 use crate::{
     compute_image_id, host::api::AssetRequest, sha::Digestible, ApiClient, Asset, ExecutorEnv,
-    Receipt, SessionInfo, VerifierContext,
+    ProveInfo, Receipt, SessionInfo, VerifierContext,
 };
 
 /// An implementation of a [Prover] that runs proof workloads via an external
@@ -37,22 +37,23 @@ impl ExternalProver {
             r0vm_path: r0vm_path.as_ref().to_path_buf(),
         }
     }
-}
 
-impl Prover for ExternalProver {
-    fn prove_with_ctx(
+    /// Prove the specified ELF binary, returning the [ProveInfo] of the
+    /// proven session, after verifying its [Receipt].
+    pub fn prove_info(
         &self,
         env: ExecutorEnv<'_>,
         ctx: &VerifierContext,
         elf: &[u8],
         opts: &ProverOpts,
-    ) -> Result<Receipt> {
+    ) -> Result<ProveInfo> {
         tracing::debug!("Launching {}", &self.r0vm_path.to_string_lossy());
 
         let image_id = compute_image_id(elf)?;
         let client = ApiClient::new_sub_process(&self.r0vm_path)?;
         let binary = Asset::Inline(elf.to_vec().into());
-        let receipt = client.prove(&env, opts.clone(), binary)?;
+        let info = client.prove_info(&env, opts.clone(), binary)?;
+        let receipt = &info.receipt;
         if opts.prove_guest_errors {
             receipt.verify_integrity_with_context(ctx)?;
             ensure!(
@@ -65,7 +66,19 @@ impl Prover for ExternalProver {
             receipt.verify_with_context(ctx, image_id)?;
         }
 
-        Ok(receipt)
+        Ok(info)
+    }
+}
+
+impl Prover for ExternalProver {
+    fn prove_with_ctx(
+        &self,
+        env: ExecutorEnv<'_>,
+        ctx: &VerifierContext,
+        elf: &[u8],
+        opts: &ProverOpts,
+    ) -> Result<Receipt> {
+        Ok(self.prove_info(env, ctx, elf, opts)?.receipt)
     }
 
     fn get_name(&self) -> String {
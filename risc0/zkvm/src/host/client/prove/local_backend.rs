@@ -0,0 +1,313 @@
+// Copyright 2024 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::{collections::HashMap, rc::Rc, sync::Mutex, time::Instant};
+
+use anyhow::Result;
+use bonsai_sdk::alpha::{
+    responses::{SessionMetadata, SessionStats, SessionStatusRes, VersionInfo},
+    Backend, ImgUploadOutcome, SdkErr, SessionId, SessionOpts, API_VERSION,
+};
+
+use super::Executor;
+use crate::{ExecutorEnv, ProveInfo, ProverOpts, Receipt, VerifierContext};
+
+const RECEIPT_URL_PREFIX: &str = "local://receipts/";
+
+/// A [Backend] of the Bonsai SDK running Sessions in-process, rather than on
+/// Bonsai.
+///
+/// A [Client](bonsai_sdk::alpha::Client) constructed with
+/// [Client::from_backend](bonsai_sdk::alpha::Client::from_backend) and this
+/// backend proves locally through the same calls as a client of Bonsai, e.g.
+/// to fall back to local proving, or to run offline in development, where
+/// `RISC0_DEV_MODE` makes fake receipts.
+///
+/// Images must be ELF binaries. Sessions are run to completion when created,
+/// with the local prover when the `prove` feature is enabled, and with `r0vm`
+/// otherwise, so their status is final as soon as they exist. Their
+/// [SessionMetadata] is returned in their status, but has no effect on
+/// scheduling. SNARK conversion is not supported.
+#[derive(Default)]
+pub struct LocalBackend {
+    state: Mutex<State>,
+}
+
+#[derive(Default)]
+struct State {
+    next_id: u64,
+    images: HashMap<String, Vec<u8>>,
+    inputs: HashMap<String, Vec<u8>>,
+    receipts: HashMap<String, Vec<u8>>,
+    sessions: HashMap<String, LocalSession>,
+}
+
+impl State {
+    fn new_id(&mut self) -> String {
+        self.next_id += 1;
+        format!("local-{}", self.next_id)
+    }
+}
+
+struct LocalSession {
+    elapsed_time: f64,
+    metadata: SessionMetadata,
+    result: Result<SessionOutput, String>,
+}
+
+struct SessionOutput {
+    segments: usize,
+    total_cycles: u64,
+    cycles: u64,
+    journal: Vec<u8>,
+    /// Serialized receipt, unless the session is execute-only.
+    receipt: Option<Vec<u8>>,
+}
+
+impl LocalBackend {
+    /// Construct a [LocalBackend] without any images, inputs or sessions.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn state(&self) -> std::sync::MutexGuard<'_, State> {
+        self.state.lock().unwrap_or_else(|err| err.into_inner())
+    }
+
+    fn session<T>(
+        &self,
+        session_id: &SessionId,
+        f: impl FnOnce(&LocalSession) -> Result<T, SdkErr>,
+    ) -> Result<T, SdkErr> {
+        let state = self.state();
+        let session = state
+            .sessions
+            .get(&session_id.uuid)
+            .ok_or_else(|| SdkErr::NotFound(format!("session {}", session_id.uuid)))?;
+        f(session)
+    }
+
+    fn output<T>(
+        &self,
+        session_id: &SessionId,
+        f: impl FnOnce(&SessionOutput) -> Option<T>,
+    ) -> Result<T, SdkErr> {
+        self.session(session_id, |session| {
+            session
+                .result
+                .as_ref()
+                .ok()
+                .and_then(f)
+                .ok_or_else(|| SdkErr::NotFound(format!("output of session {}", session_id.uuid)))
+        })
+    }
+}
+
+#[cfg(feature = "prove")]
+fn prover() -> Rc<super::local::LocalProver> {
+    Rc::new(super::local::LocalProver::new("local"))
+}
+
+#[cfg(not(feature = "prove"))]
+fn prover() -> Rc<super::external::ExternalProver> {
+    Rc::new(super::external::ExternalProver::new(
+        "ipc",
+        super::get_r0vm_path(),
+    ))
+}
+
+#[cfg(feature = "prove")]
+fn prove_info(env: ExecutorEnv<'_>, elf: &[u8]) -> Result<ProveInfo> {
+    let prover = crate::get_prover_server(&ProverOpts::default())?;
+    prover.prove_info(env, &VerifierContext::default(), elf)
+}
+
+#[cfg(not(feature = "prove"))]
+fn prove_info(env: ExecutorEnv<'_>, elf: &[u8]) -> Result<ProveInfo> {
+    prover().prove_info(
+        env,
+        &VerifierContext::default(),
+        elf,
+        &ProverOpts::default(),
+    )
+}
+
+fn executor_env(input: &[u8], assumptions: &[Vec<u8>]) -> Result<ExecutorEnv<'static>> {
+    let mut env = ExecutorEnv::builder();
+    // Empty uploads stand for the absence of assumptions.
+    for receipt in assumptions.iter().filter(|receipt| !receipt.is_empty()) {
+        env.add_assumption(bincode::deserialize::<Receipt>(receipt)?);
+    }
+    env.write_slice(input).build()
+}
+
+fn run_session(
+    elf: &[u8],
+    input: &[u8],
+    assumptions: &[Vec<u8>],
+    execute_only: bool,
+) -> Result<SessionOutput> {
+    let env = executor_env(input, assumptions)?;
+    if execute_only {
+        let info = prover().execute(env, elf)?;
+        return Ok(SessionOutput {
+            segments: info.segments.len(),
+            total_cycles: info.segments.iter().map(|s| 1u64 << s.po2).sum(),
+            cycles: info.segments.iter().map(|s| u64::from(s.cycles)).sum(),
+            journal: info.journal.bytes,
+            receipt: None,
+        });
+    }
+
+    // The guest is executed once, by the prover, which reports the statistics
+    // of that execution along with the receipt.
+    let info = prove_info(env, elf)?;
+    Ok(SessionOutput {
+        segments: info.stats.segments,
+        total_cycles: info.stats.total_cycles,
+        cycles: info.stats.user_cycles,
+        journal: info.receipt.journal.bytes.clone(),
+        receipt: Some(bincode::serialize(&info.receipt)?),
+    })
+}
+
+impl Backend for LocalBackend {
+    fn has_img(&self, image_id: &str) -> Result<bool, SdkErr> {
+        Ok(self.state().images.contains_key(image_id))
+    }
+
+    fn upload_img(&self, image_id: &str, buf: Vec<u8>) -> Result<ImgUploadOutcome, SdkErr> {
+        let mut state = self.state();
+        if state.images.contains_key(image_id) {
+            return Ok(ImgUploadOutcome::AlreadyExists);
+        }
+        state.images.insert(image_id.to_string(), buf);
+        Ok(ImgUploadOutcome::Uploaded)
+    }
+
+    fn upload_input(&self, buf: Vec<u8>) -> Result<String, SdkErr> {
+        let mut state = self.state();
+        let input_id = state.new_id();
+        state.inputs.insert(input_id.clone(), buf);
+        Ok(input_id)
+    }
+
+    fn upload_receipt(&self, buf: Vec<u8>) -> Result<String, SdkErr> {
+        let mut state = self.state();
+        let receipt_id = state.new_id();
+        state.receipts.insert(receipt_id.clone(), buf);
+        Ok(receipt_id)
+    }
+
+    fn create_session(
+        &self,
+        img_id: String,
+        input_id: String,
+        assumptions: Vec<String>,
+        opts: SessionOpts,
+    ) -> Result<SessionId, SdkErr> {
+        let (elf, input, assumptions) = {
+            let state = self.state();
+            let elf = state
+                .images
+                .get(&img_id)
+                .cloned()
+                .ok_or_else(|| SdkErr::NotFound(format!("image {img_id}")))?;
+            let input = state
+                .inputs
+                .get(&input_id)
+                .cloned()
+                .ok_or_else(|| SdkErr::NotFound(format!("input {input_id}")))?;
+            let assumptions = assumptions
+                .iter()
+                .map(|id| {
+                    state
+                        .receipts
+                        .get(id)
+                        .cloned()
+                        .ok_or_else(|| SdkErr::NotFound(format!("receipt {id}")))
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+            (elf, input, assumptions)
+        };
+
+        // The state is not locked while proving, so that other sessions can
+        // be polled meanwhile.
+        let start = Instant::now();
+        let result = run_session(&elf, &input, &assumptions, opts.execute_only)
+            .map_err(|err| format!("{err:?}"));
+        let session = LocalSession {
+            elapsed_time: start.elapsed().as_secs_f64(),
+            metadata: opts.metadata,
+            result,
+        };
+
+        let mut state = self.state();
+        let session_id = state.new_id();
+        state.sessions.insert(session_id.clone(), session);
+        Ok(SessionId::new(session_id))
+    }
+
+    fn session_status(&self, session_id: &SessionId) -> Result<SessionStatusRes, SdkErr> {
+        self.session(session_id, |session| {
+            let (status, receipt_url, error_msg, stats) = match &session.result {
+                Ok(output) => (
+                    "SUCCEEDED",
+                    output
+                        .receipt
+                        .as_ref()
+                        .map(|_| format!("{RECEIPT_URL_PREFIX}{}", session_id.uuid)),
+                    None,
+                    Some(SessionStats {
+                        segments: output.segments,
+                        total_cycles: output.total_cycles,
+                        cycles: output.cycles,
+                    }),
+                ),
+                Err(err) => ("FAILED", None, Some(err.clone()), None),
+            };
+            Ok(SessionStatusRes {
+                status: status.to_string(),
+                receipt_url,
+                error_msg,
+                state: None,
+                elapsed_time: Some(session.elapsed_time),
+                stats,
+                metadata: Some(session.metadata.clone()),
+            })
+        })
+    }
+
+    fn exec_only_journal(&self, session_id: &SessionId) -> Result<Vec<u8>, SdkErr> {
+        self.output(session_id, |output| Some(output.journal.clone()))
+    }
+
+    fn receipt_download(&self, session_id: &SessionId) -> Result<Vec<u8>, SdkErr> {
+        self.output(session_id, |output| output.receipt.clone())
+    }
+
+    fn download(&self, url: &str) -> Result<Vec<u8>, SdkErr> {
+        let session_id = url
+            .strip_prefix(RECEIPT_URL_PREFIX)
+            .ok_or_else(|| SdkErr::NotFound(url.to_string()))?;
+        self.receipt_download(&SessionId::new(session_id.to_string()))
+    }
+
+    fn version(&self) -> Result<VersionInfo, SdkErr> {
+        Ok(VersionInfo {
+            risc0_zkvm: vec![crate::VERSION.to_string()],
+            api: vec![API_VERSION.to_string()],
+        })
+    }
+}
@@ -16,8 +16,9 @@ pub(crate) mod bonsai;
 pub(crate) mod external;
 #[cfg(feature = "prove")]
 pub(crate) mod local;
+pub(crate) mod local_backend;
 
-use std::{path::PathBuf, rc::Rc};
+use std::{fmt, path::PathBuf, rc::Rc};
 
 use anyhow::Result;
 use serde::{Deserialize, Serialize};
@@ -104,6 +105,16 @@ pub struct ProverOpts {
     // moment if there is a better place. At some point before 1.0, this option should be moved or
     // dropped.
     pub prove_guest_errors: bool,
+    /// Maximum number of segments of a session that will be proven.
+    ///
+    /// Sessions with more segments fail with [ProvingBudgetExceeded] after
+    /// execution, before any segment is proven. Not enforced by the
+    /// [BonsaiProver].
+    pub max_segments: Option<usize>,
+    /// Maximum number of total cycles of a session that will be proven.
+    ///
+    /// Enforced as [ProverOpts::max_segments] is.
+    pub max_total_cycles: Option<u64>,
 }
 
 impl Default for ProverOpts {
@@ -113,6 +124,8 @@ impl Default for ProverOpts {
         Self {
             hashfn: "poseidon2".to_string(),
             prove_guest_errors: false,
+            max_segments: None,
+            max_total_cycles: None,
         }
     }
 }
@@ -123,10 +136,87 @@ impl ProverOpts {
         Self {
             hashfn: "sha-256".to_string(),
             prove_guest_errors: false,
+            max_segments: None,
+            max_total_cycles: None,
+        }
+    }
+
+    /// Refuse to prove sessions with more than `max_segments` segments.
+    pub fn max_segments(self, max_segments: usize) -> Self {
+        Self {
+            max_segments: Some(max_segments),
+            ..self
+        }
+    }
+
+    /// Refuse to prove sessions running more than `max_total_cycles` cycles.
+    pub fn max_total_cycles(self, max_total_cycles: u64) -> Self {
+        Self {
+            max_total_cycles: Some(max_total_cycles),
+            ..self
+        }
+    }
+
+    /// Check the size of an executed session against the proving budget of
+    /// these options.
+    pub fn check_budget(
+        &self,
+        segments: usize,
+        total_cycles: u64,
+    ) -> Result<(), ProvingBudgetExceeded> {
+        if let Some(limit) = self.max_segments.filter(|&limit| segments > limit) {
+            return Err(ProvingBudgetExceeded::Segments { limit, segments });
         }
+        if let Some(limit) = self.max_total_cycles.filter(|&limit| total_cycles > limit) {
+            return Err(ProvingBudgetExceeded::TotalCycles {
+                limit,
+                total_cycles,
+            });
+        }
+        Ok(())
     }
 }
 
+/// Error returned when a session exceeds the proving budget of its
+/// [ProverOpts].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProvingBudgetExceeded {
+    /// The session has more segments than [ProverOpts::max_segments].
+    Segments {
+        /// The maximum number of segments.
+        limit: usize,
+        /// The number of segments of the session.
+        segments: usize,
+    },
+    /// The session ran more cycles than [ProverOpts::max_total_cycles].
+    TotalCycles {
+        /// The maximum number of total cycles.
+        limit: u64,
+        /// The total cycles of the session.
+        total_cycles: u64,
+    },
+}
+
+impl fmt::Display for ProvingBudgetExceeded {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Segments { limit, segments } => write!(
+                f,
+                "Proving budget exceeded: {segments} segments, limit is {limit}"
+            ),
+            Self::TotalCycles {
+                limit,
+                total_cycles,
+            } => write!(
+                f,
+                "Proving budget exceeded: {total_cycles} total cycles, limit is {limit}"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for ProvingBudgetExceeded {}
+
 /// Return a default [Prover] based on environment variables and feature flags.
 ///
 /// The `RISC0_PROVER` environment variable, if specified, will select the
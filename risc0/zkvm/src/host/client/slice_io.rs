@@ -12,9 +12,15 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use std::{cell::RefCell, collections::BTreeMap, rc::Rc};
+use std::{
+    cell::RefCell,
+    collections::BTreeMap,
+    rc::Rc,
+    sync::{mpsc, Arc},
+    time::Instant,
+};
 
-use anyhow::Result;
+use anyhow::{anyhow, Result};
 use bytes::Bytes;
 
 /// An I/O handler that returns arbitrary data to the guest.
@@ -32,6 +38,20 @@ pub trait SliceIo {
     /// `from_guest`, and this function is to return the data the host is
     /// sending to the guest.
     fn handle_io(&mut self, syscall: &str, from_guest: Bytes) -> Result<Bytes>;
+
+    /// Host side I/O handling, which should give up once `deadline` passes.
+    ///
+    /// The executor calls this with the deadline of its time limit, if any.
+    /// By default, the deadline is ignored and [SliceIo::handle_io] is called.
+    fn handle_io_until(
+        &mut self,
+        syscall: &str,
+        from_guest: Bytes,
+        deadline: Option<Instant>,
+    ) -> Result<Bytes> {
+        let _ = deadline;
+        self.handle_io(syscall, from_guest)
+    }
 }
 
 #[derive(Clone, Default)]
@@ -59,6 +79,51 @@ impl<'a> SliceIo for FnWrapper<'a> {
     }
 }
 
+type SendCallback = Arc<dyn Fn(Bytes) -> Result<Bytes> + Send + Sync>;
+
+struct SendFnWrapper {
+    callback: SendCallback,
+}
+
+pub fn slice_io_from_send_fn(
+    callback: impl Fn(Bytes) -> Result<Bytes> + Send + Sync + 'static,
+) -> impl SliceIo {
+    SendFnWrapper {
+        callback: Arc::new(callback),
+    }
+}
+
+impl SliceIo for SendFnWrapper {
+    fn handle_io(&mut self, _syscall: &str, from_guest: Bytes) -> Result<Bytes> {
+        (self.callback)(from_guest)
+    }
+
+    // Run the callback on its own thread, and stop waiting for it once the
+    // deadline passes. The thread is left to finish on its own.
+    fn handle_io_until(
+        &mut self,
+        syscall: &str,
+        from_guest: Bytes,
+        deadline: Option<Instant>,
+    ) -> Result<Bytes> {
+        let Some(deadline) = deadline else {
+            return self.handle_io(syscall, from_guest);
+        };
+        let (tx, rx) = mpsc::channel();
+        let callback = self.callback.clone();
+        std::thread::spawn(move || tx.send(callback(from_guest)));
+        match rx.recv_timeout(deadline.saturating_duration_since(Instant::now())) {
+            Ok(result) => result,
+            Err(mpsc::RecvTimeoutError::Timeout) => {
+                Err(anyhow!("I/O handler for {syscall} timed out"))
+            }
+            Err(mpsc::RecvTimeoutError::Disconnected) => {
+                Err(anyhow!("I/O handler for {syscall} panicked"))
+            }
+        }
+    }
+}
+
 impl<'a> SliceIoTable<'a> {
     pub fn with_handler(&mut self, channel: &str, handler: impl SliceIo + 'a) -> &mut Self {
         self.inner
@@ -71,4 +136,14 @@ impl<'a> SliceIo for Rc<RefCell<dyn SliceIo + 'a>> {
     fn handle_io(&mut self, syscall: &str, from_guest: Bytes) -> Result<Bytes> {
         self.borrow_mut().handle_io(syscall, from_guest)
     }
+
+    fn handle_io_until(
+        &mut self,
+        syscall: &str,
+        from_guest: Bytes,
+        deadline: Option<Instant>,
+    ) -> Result<Bytes> {
+        self.borrow_mut()
+            .handle_io_until(syscall, from_guest, deadline)
+    }
 }
@@ -15,7 +15,12 @@
 #[cfg(any(feature = "client", feature = "prove"))]
 pub(crate) mod api;
 #[cfg(feature = "client")]
+pub(crate) mod artifact;
+#[cfg(feature = "prove")]
+pub(crate) mod chrome_trace;
+#[cfg(feature = "client")]
 pub(crate) mod client;
+pub(crate) mod policy;
 pub(crate) mod receipt;
 pub(crate) mod recursion;
 #[cfg(feature = "prove")]
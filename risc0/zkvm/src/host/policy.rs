@@ -0,0 +1,187 @@
+// Copyright 2024 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Deployment-level checks run by [Receipt::verify](crate::Receipt::verify).
+
+use alloc::{collections::BTreeMap, format};
+
+use risc0_zkp::{core::digest::Digest, verify::VerificationError};
+
+use crate::{sha::Digestible, InnerReceipt, Receipt, ReceiptClaim, VerifierContext};
+
+/// Source of the current time of a [VerifierContext], in seconds since the
+/// UNIX epoch.
+///
+/// Replacing the [SystemClock] lets time-dependent [VerifierPolicy] checks be
+/// tested, or use a trusted time source.
+pub trait Clock: Send + Sync {
+    /// Returns the current time, in seconds since the UNIX epoch.
+    fn now(&self) -> u64;
+}
+
+/// [Clock] reading the system time.
+#[cfg(feature = "std")]
+#[derive(Clone, Copy, Debug, Default)]
+pub struct SystemClock;
+
+#[cfg(feature = "std")]
+impl Clock for SystemClock {
+    fn now(&self) -> u64 {
+        std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map_or(0, |elapsed| elapsed.as_secs())
+    }
+}
+
+/// A check run on every receipt verified with a [VerifierContext].
+///
+/// Policies run after the seal, image ID and exit code of a receipt are
+/// verified, and before its journal is checked. They enforce the rules of a
+/// deployment in one place, such as rejecting revoked image IDs with
+/// [RevokedImages], or receipts from a deprecated circuit with
+/// [DeprecatedControlIds], by returning [VerificationError::PolicyViolation].
+pub trait VerifierPolicy: Send + Sync {
+    /// Checks the `receipt` proving `claim`, verified with `ctx`.
+    fn check(
+        &self,
+        ctx: &VerifierContext,
+        receipt: &Receipt,
+        claim: &ReceiptClaim,
+    ) -> Result<(), VerificationError>;
+}
+
+// Returns whether something revoked or deprecated from `time` is rejected at
+// the current time of `ctx`. Without a clock, things revoked in the future are
+// already rejected.
+fn rejected_at(ctx: &VerifierContext, time: u64) -> bool {
+    match ctx.now() {
+        Some(now) => now >= time,
+        None => true,
+    }
+}
+
+/// [VerifierPolicy] rejecting the receipts of revoked image IDs.
+#[derive(Clone, Debug, Default)]
+pub struct RevokedImages {
+    // Time each image ID is revoked from, in seconds since the UNIX epoch.
+    revoked: BTreeMap<Digest, u64>,
+}
+
+impl RevokedImages {
+    /// Construct an empty revocation list.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Rejects all receipts of `image_id`.
+    pub fn revoke(self, image_id: impl Into<Digest>) -> Self {
+        self.revoke_at(image_id, 0)
+    }
+
+    /// Rejects the receipts of `image_id` verified at or after `time`, in
+    /// seconds since the UNIX epoch, according to the [Clock] of the
+    /// [VerifierContext].
+    pub fn revoke_at(mut self, image_id: impl Into<Digest>, time: u64) -> Self {
+        self.revoked.insert(image_id.into(), time);
+        self
+    }
+}
+
+impl VerifierPolicy for RevokedImages {
+    fn check(
+        &self,
+        ctx: &VerifierContext,
+        _receipt: &Receipt,
+        claim: &ReceiptClaim,
+    ) -> Result<(), VerificationError> {
+        let image_id = claim.pre.digest();
+        let Some(&time) = self.revoked.get(&image_id) else {
+            return Ok(());
+        };
+        if rejected_at(ctx, time) {
+            return Err(VerificationError::PolicyViolation(format!(
+                "image ID {image_id} is revoked"
+            )));
+        }
+        Ok(())
+    }
+}
+
+/// [VerifierPolicy] rejecting the receipts of deprecated recursion programs,
+/// such as those of a previous release of the circuit.
+///
+/// The control ID of the recursion program is checked for
+/// [SuccinctReceipt](crate::SuccinctReceipt)s, and for the succinct receipts
+/// proving the assumptions of a [CompositeReceipt](crate::CompositeReceipt).
+#[derive(Clone, Debug, Default)]
+pub struct DeprecatedControlIds {
+    // Time each control ID is deprecated from, in seconds since the UNIX epoch.
+    deprecated: BTreeMap<Digest, u64>,
+}
+
+impl DeprecatedControlIds {
+    /// Construct an empty deprecation list.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Rejects all receipts of the recursion program with `control_id`.
+    pub fn deprecate(self, control_id: impl Into<Digest>) -> Self {
+        self.deprecate_at(control_id, 0)
+    }
+
+    /// Rejects the receipts of the recursion program with `control_id`
+    /// verified at or after `time`, in seconds since the UNIX epoch, according
+    /// to the [Clock] of the [VerifierContext].
+    pub fn deprecate_at(mut self, control_id: impl Into<Digest>, time: u64) -> Self {
+        self.deprecated.insert(control_id.into(), time);
+        self
+    }
+
+    fn check_inner(
+        &self,
+        ctx: &VerifierContext,
+        inner: &InnerReceipt,
+    ) -> Result<(), VerificationError> {
+        match inner {
+            InnerReceipt::Succinct(receipt) => {
+                let control_id = receipt.control_id;
+                match self.deprecated.get(&control_id) {
+                    Some(&time) if rejected_at(ctx, time) => {
+                        Err(VerificationError::PolicyViolation(format!(
+                            "control ID {control_id} is deprecated"
+                        )))
+                    }
+                    _ => Ok(()),
+                }
+            }
+            InnerReceipt::Composite(receipt) => receipt
+                .assumptions
+                .iter()
+                .try_for_each(|assumption| self.check_inner(ctx, assumption)),
+            InnerReceipt::Compact(_) | InnerReceipt::Fake { .. } => Ok(()),
+        }
+    }
+}
+
+impl VerifierPolicy for DeprecatedControlIds {
+    fn check(
+        &self,
+        ctx: &VerifierContext,
+        receipt: &Receipt,
+        _claim: &ReceiptClaim,
+    ) -> Result<(), VerificationError> {
+        self.check_inner(ctx, &receipt.inner)
+    }
+}
@@ -14,11 +14,11 @@
 
 //! Manages the output and cryptographic data for a proven computation.
 
-use alloc::{collections::BTreeMap, string::String, vec, vec::Vec};
+use alloc::{boxed::Box, collections::BTreeMap, string::String, sync::Arc, vec, vec::Vec};
 use core::fmt::Debug;
 
 use anyhow::Result;
-use risc0_binfmt::{ExitCode, SystemState};
+use risc0_binfmt::{ExitCode, SessionOutcome, SystemState};
 use risc0_circuit_recursion::control_id::{ALLOWED_IDS_ROOT, BN254_CONTROL_ID};
 use risc0_circuit_rv32im::{
     control_id::{BLAKE2B_CONTROL_ID, POSEIDON2_CONTROL_ID, SHA256_CONTROL_ID},
@@ -26,7 +26,10 @@ use risc0_circuit_rv32im::{
 };
 use risc0_core::field::baby_bear::BabyBear;
 use risc0_groth16::{
-    fr_from_hex_string, split_digest, verifier::prepared_verifying_key, Seal, Verifier,
+    fr_from_hex_string,
+    pvk::{AllowedRoots, Registry, VersionedKey},
+    verifier::prepared_verifying_key,
+    MalformedInput, Seal, SealRef, Verifier,
 };
 use risc0_zkp::{
     core::{
@@ -42,8 +45,9 @@ use risc0_zkp::{
 use risc0_zkvm_platform::WORD_SIZE;
 use serde::{de::DeserializeOwned, Deserialize, Serialize};
 
+use super::policy::{Clock, VerifierPolicy};
 // Make succinct receipt available through this `receipt` module.
-pub use super::recursion::SuccinctReceipt;
+pub use super::recursion::{SuccinctReceipt, SuccinctVerifier};
 use crate::{
     serde::{from_slice, Error},
     sha::{Digestible, Sha256},
@@ -155,19 +159,7 @@ impl Receipt {
         image_id: impl Into<Digest>,
     ) -> Result<(), VerificationError> {
         tracing::debug!("Receipt::verify_with_context");
-        self.inner.verify_integrity_with_context(ctx)?;
-
-        // NOTE: Post-state digest and input digest are unconstrained by this method.
-        let claim = self.inner.get_claim()?;
-        if claim.pre.digest() != image_id.into() {
-            return Err(VerificationError::ImageVerificationError);
-        }
-
-        // Check the exit code. This verification method requires execution to be
-        // successful.
-        if !claim.exit_code.is_ok() {
-            return Err(VerificationError::UnexpectedExitCode);
-        };
+        let claim = self.verify_successful(ctx, image_id.into())?;
 
         // Finally check the output hash in the decoded claim against the expected
         // output.
@@ -177,7 +169,7 @@ impl Receipt {
             assumptions: Assumptions(vec![]).into(),
         };
 
-        if claim.output.digest() != expected_output.digest() {
+        if !claim.output.digest().ct_eq(&expected_output.digest()) {
             let empty_output = claim.output.is_none() && self.journal.bytes.is_empty();
             if !empty_output {
                 tracing::debug!(
@@ -194,6 +186,76 @@ impl Receipt {
         Ok(())
     }
 
+    /// Verify that this receipt proves a successful execution of the zkVM from
+    /// the given `image_id`, in which the guest committed `digest` with
+    /// `env::commit_digest` in place of a journal.
+    ///
+    /// The journal of such a receipt is empty, and the committed digest is
+    /// checked against the journal digest of the proven [ReceiptClaim]
+    /// instead, without assumptions.
+    pub fn verify_with_digest(
+        &self,
+        image_id: impl Into<Digest>,
+        digest: impl Into<Digest>,
+    ) -> Result<(), VerificationError> {
+        self.verify_with_digest_and_context(&VerifierContext::default(), image_id, digest)
+    }
+
+    /// Verify that this receipt proves a successful execution of the zkVM from
+    /// the given `image_id`, in which the guest committed `digest` with
+    /// `env::commit_digest` in place of a journal, using the given
+    /// [VerifierContext].
+    pub fn verify_with_digest_and_context(
+        &self,
+        ctx: &VerifierContext,
+        image_id: impl Into<Digest>,
+        digest: impl Into<Digest>,
+    ) -> Result<(), VerificationError> {
+        tracing::debug!("Receipt::verify_with_digest_and_context");
+        let claim = self.verify_successful(ctx, image_id.into())?;
+
+        let expected_output = Output::from_digest(digest);
+        if !self.journal.bytes.is_empty() || !claim.output.digest().ct_eq(&expected_output.digest())
+        {
+            tracing::debug!(
+                "expected output digest: 0x{}, decoded output digest: 0x{}",
+                hex::encode(expected_output.digest()),
+                hex::encode(claim.output.digest()),
+            );
+            return Err(VerificationError::JournalDigestMismatch);
+        }
+
+        Ok(())
+    }
+
+    // Verify the seal, and that it proves a successful execution of
+    // `image_id`, returning the proven claim.
+    fn verify_successful(
+        &self,
+        ctx: &VerifierContext,
+        image_id: Digest,
+    ) -> Result<ReceiptClaim, VerificationError> {
+        self.inner.verify_integrity_with_context(ctx)?;
+
+        // NOTE: Post-state digest and input digest are unconstrained by this method.
+        let claim = self.inner.get_claim()?;
+        if !claim.pre.digest().ct_eq(&image_id) {
+            return Err(VerificationError::ImageVerificationError);
+        }
+
+        // Check the exit code. This verification method requires execution to be
+        // successful.
+        if !claim.exit_code.is_ok() {
+            return Err(VerificationError::UnexpectedExitCode);
+        };
+
+        for policy in &ctx.policies {
+            policy.check(ctx, self, &claim)?;
+        }
+
+        Ok(claim)
+    }
+
     /// Verify the integrity of this receipt, ensuring the claim and journal
     /// are attested to by the seal.
     ///
@@ -224,7 +286,7 @@ impl Receipt {
             assumptions: Assumptions(vec![]).into(),
         });
 
-        if claim.output.digest() != expected_output.digest() {
+        if !claim.output.digest().ct_eq(&expected_output.digest()) {
             let empty_output = claim.output.is_none() && self.journal.bytes.is_empty();
             if !empty_output {
                 tracing::debug!(
@@ -245,6 +307,155 @@ impl Receipt {
     pub fn get_claim(&self) -> Result<ReceiptClaim, VerificationError> {
         self.inner.get_claim()
     }
+
+    /// Returns the digest of the [ReceiptClaim] of this receipt.
+    ///
+    /// This is the claim digest attested to by the Groth16 seal of a
+    /// [CompactReceipt], also known as the `receipt_meta`, to be passed along
+    /// with the seal to Groth16 verifiers.
+    pub fn claim_digest(&self) -> Result<Digest, VerificationError> {
+        Ok(self.get_claim()?.digest())
+    }
+
+    /// Returns the [SessionOutcome] of the execution proven by this receipt.
+    ///
+    /// Fails with [VerificationError::UnexpectedExitCode] if the receipt only
+    /// proves a segment ended by a system split. The raw [ExitCode] is
+    /// available from [Receipt::get_claim].
+    pub fn outcome(&self) -> Result<SessionOutcome, VerificationError> {
+        self.get_claim()?
+            .exit_code
+            .outcome()
+            .ok_or(VerificationError::UnexpectedExitCode)
+    }
+
+    /// Verify that this receipt proves a successful execution of the zkVM from
+    /// the given `image_id`, as [Receipt::verify] does, attaching the
+    /// [VerificationDiagnostics] of this receipt to the error on failure.
+    pub fn verify_with_diagnostics(
+        &self,
+        image_id: impl Into<Digest>,
+    ) -> Result<(), VerificationFailure> {
+        let image_id = image_id.into();
+        self.verify(image_id).map_err(|error| VerificationFailure {
+            error,
+            diagnostics: Box::new(self.diagnostics(image_id)),
+        })
+    }
+
+    /// Collects the values checked by [Receipt::verify] against the given
+    /// `image_id`, to be logged when verification fails.
+    ///
+    /// The values are read from the receipt without verifying it, so they
+    /// must not be trusted.
+    pub fn diagnostics(&self, image_id: impl Into<Digest>) -> VerificationDiagnostics {
+        use hex::FromHex;
+
+        let claim = self.get_claim().ok();
+        let mut diagnostics = VerificationDiagnostics {
+            expected_image_id: Some(image_id.into()),
+            image_id: claim.as_ref().map(|claim| claim.pre.digest()),
+            expected_journal_digest: Some(self.journal.digest()),
+            journal_digest: claim.as_ref().and_then(|claim| {
+                let output = claim.output.as_value().ok()?.as_ref()?;
+                Some(output.journal.digest())
+            }),
+            ..Default::default()
+        };
+        match &self.inner {
+            InnerReceipt::Succinct(receipt) => {
+                diagnostics.expected_control_root = Digest::from_hex(ALLOWED_IDS_ROOT).ok();
+                diagnostics.control_root = receipt.control_root().ok();
+            }
+            InnerReceipt::Compact(receipt) => {
+                diagnostics.expected_control_root = Digest::from_hex(ALLOWED_IDS_ROOT).ok();
+                diagnostics.public_inputs = receipt.public_inputs().unwrap_or_default();
+            }
+            _ => {}
+        }
+        diagnostics
+    }
+}
+
+/// Values checked by [Receipt::verify], collected by [Receipt::diagnostics].
+///
+/// Values that cannot be read from the receipt, or do not apply to its kind,
+/// are left empty.
+#[derive(Clone, Debug, Default, Deserialize, Serialize, PartialEq)]
+pub struct VerificationDiagnostics {
+    /// The image ID the receipt was verified against.
+    pub expected_image_id: Option<Digest>,
+    /// The image ID of the [ReceiptClaim].
+    pub image_id: Option<Digest>,
+    /// The digest of the [Journal] of the receipt.
+    pub expected_journal_digest: Option<Digest>,
+    /// The journal digest of the [ReceiptClaim] output.
+    pub journal_digest: Option<Digest>,
+    /// The root of the recursion control IDs accepted by this version of the
+    /// zkVM, for [SuccinctReceipt]s and [CompactReceipt]s.
+    pub expected_control_root: Option<Digest>,
+    /// The root of the recursion control IDs committed to by a
+    /// [SuccinctReceipt].
+    pub control_root: Option<Digest>,
+    /// The public inputs of the Groth16 seal of a [CompactReceipt], as
+    /// big-endian hex words.
+    pub public_inputs: Vec<String>,
+}
+
+impl core::fmt::Display for VerificationDiagnostics {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        let digests = [
+            ("expected_image_id", &self.expected_image_id),
+            ("image_id", &self.image_id),
+            ("expected_journal_digest", &self.expected_journal_digest),
+            ("journal_digest", &self.journal_digest),
+            ("expected_control_root", &self.expected_control_root),
+            ("control_root", &self.control_root),
+        ];
+        let mut sep = "";
+        for (name, digest) in digests {
+            if let Some(digest) = digest {
+                write!(f, "{sep}{name}: {digest}")?;
+                sep = ", ";
+            }
+        }
+        if !self.public_inputs.is_empty() {
+            write!(f, "{sep}public_inputs: [{}]", self.public_inputs.join(", "))?;
+        }
+        Ok(())
+    }
+}
+
+/// Error returned by [Receipt::verify_with_diagnostics].
+#[derive(Debug, PartialEq)]
+pub struct VerificationFailure {
+    /// The reason the receipt was rejected.
+    pub error: VerificationError,
+    /// The values checked by the verification.
+    pub diagnostics: Box<VerificationDiagnostics>,
+}
+
+impl core::fmt::Display for VerificationFailure {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "{} ({})", self.error, self.diagnostics)
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for VerificationFailure {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        Some(&self.error)
+    }
+}
+
+impl TryFrom<&Receipt> for Seal {
+    type Error = VerificationError;
+
+    /// Extracts the Groth16 seal of a receipt whose inner receipt is a
+    /// [CompactReceipt].
+    fn try_from(receipt: &Receipt) -> Result<Self, VerificationError> {
+        receipt.inner.compact()?.try_into()
+    }
 }
 
 /// A journal is a record of all public commitments for a given proof session.
@@ -380,24 +591,34 @@ pub struct CompactReceipt {
     pub claim: ReceiptClaim,
 }
 
+impl TryFrom<&CompactReceipt> for Seal {
+    type Error = VerificationError;
+
+    fn try_from(receipt: &CompactReceipt) -> Result<Self, VerificationError> {
+        Seal::from_vec(&receipt.seal).map_err(|_| VerificationError::ReceiptFormatError)
+    }
+}
+
 impl CompactReceipt {
     /// Verify the integrity of this receipt, ensuring the claim is attested
     /// to by the seal.
     pub fn verify_integrity(&self) -> Result<(), VerificationError> {
-        use hex::FromHex;
-        let (a0, a1) = split_digest(
-            Digest::from_hex(ALLOWED_IDS_ROOT)
-                .map_err(|_| VerificationError::ReceiptFormatError)?,
-        )
-        .map_err(|_| VerificationError::ReceiptFormatError)?;
-        let (c0, c1) =
-            split_digest(self.claim.digest()).map_err(|_| VerificationError::ReceiptFormatError)?;
-        let id_p254_hash = fr_from_hex_string(BN254_CONTROL_ID)
-            .map_err(|_| VerificationError::ReceiptFormatError)?;
-        Verifier::new(
-            &Seal::from_vec(&self.seal).map_err(|_| VerificationError::ReceiptFormatError)?,
-            vec![a0, a1, c0, c1, id_p254_hash],
-            prepared_verifying_key().map_err(|_| VerificationError::ReceiptFormatError)?,
+        self.verify_integrity_versioned(&groth16_registry()?, crate::VERSION)
+    }
+
+    /// Verify the integrity of this receipt using the verification
+    /// parameters registered for `version`, such as those of a previous
+    /// release of the recursion circuit.
+    pub fn verify_integrity_versioned(
+        &self,
+        registry: &Registry,
+        version: &str,
+    ) -> Result<(), VerificationError> {
+        Verifier::from_seal_versioned(
+            registry,
+            version,
+            SealRef::from_bytes(&self.seal).map_err(|_| VerificationError::ReceiptFormatError)?,
+            self.claim.digest(),
         )
         .map_err(|_| VerificationError::ReceiptFormatError)?
         .verify()
@@ -406,6 +627,133 @@ impl CompactReceipt {
         // Everything passed
         Ok(())
     }
+
+    /// Verify the integrity of this receipt, accepting seals produced under
+    /// any of the `allowed_roots`, such as the control ID roots of previous
+    /// releases during a migration window.
+    ///
+    /// The verifying key and BN254 control ID of this version of the zkVM are
+    /// used.
+    pub fn verify_integrity_allowed_roots(
+        &self,
+        allowed_roots: &AllowedRoots,
+    ) -> Result<(), VerificationError> {
+        let registry = groth16_registry()?;
+        let key = registry
+            .get(crate::VERSION)
+            .map_err(|_| VerificationError::ReceiptFormatError)?;
+        Verifier::verify_allowed_roots(
+            key,
+            allowed_roots,
+            SealRef::from_bytes(&self.seal).map_err(|_| VerificationError::ReceiptFormatError)?,
+            self.claim.digest(),
+        )
+        .map_err(|err| match err.is::<MalformedInput>() {
+            true => VerificationError::ReceiptFormatError,
+            false => VerificationError::InvalidProof,
+        })?;
+        Ok(())
+    }
+
+    // Public inputs of the seal for this version of the zkVM, as hex words, for
+    // the diagnostics of a failed verification.
+    fn public_inputs(&self) -> Option<Vec<String>> {
+        let seal = SealRef::from_bytes(&self.seal).ok()?;
+        let verifier = Verifier::from_seal_versioned(
+            &groth16_registry().ok()?,
+            crate::VERSION,
+            seal,
+            self.claim.digest(),
+        )
+        .ok()?;
+        Some(verifier.diagnostics().ok()?.public_inputs)
+    }
+}
+
+#[cfg(feature = "client")]
+impl CompactReceipt {
+    /// Construct a [CompactReceipt] from a SNARK receipt returned by the
+    /// Bonsai API for the guest with the given `image_id`.
+    ///
+    /// The SNARK receipt only carries the post state digest and journal of
+    /// the execution, from which the [ReceiptClaim] of a successful execution
+    /// is reconstructed as in [verify_groth16_seal]. The digest of the claim is
+    /// the public input of the seal:
+    ///
+    /// ```no_run
+    /// # use bonsai_sdk::alpha::responses::SnarkReceipt;
+    /// # use risc0_zkvm::{sha::Digestible, CompactReceipt, InnerReceipt, Receipt};
+    /// # fn f(snark_receipt: SnarkReceipt, image_id: [u32; 8]) {
+    /// let compact = CompactReceipt::from_bonsai_snark(&snark_receipt, image_id).unwrap();
+    /// let claim_digest = compact.claim.digest();
+    /// let receipt = Receipt::new(InnerReceipt::Compact(compact), snark_receipt.journal);
+    /// receipt.verify(image_id).unwrap();
+    /// # }
+    /// ```
+    pub fn from_bonsai_snark(
+        receipt: &bonsai_sdk::alpha::responses::SnarkReceipt,
+        image_id: impl Into<Digest>,
+    ) -> Result<Self, VerificationError> {
+        let seal =
+            Seal::try_from(receipt.clone()).map_err(|_| VerificationError::ReceiptFormatError)?;
+        let post_state_digest = Digest::try_from(receipt.post_state_digest.as_slice())
+            .map_err(|_| VerificationError::ReceiptFormatError)?;
+        Ok(Self {
+            seal: seal.to_vec(),
+            claim: halted_claim(image_id.into(), post_state_digest, &receipt.journal),
+        })
+    }
+}
+
+/// Returns a Groth16 [Registry] containing the verification parameters of
+/// this version of the zkVM, registered under [crate::VERSION].
+///
+/// Parameters for other versions may be added to the returned registry to
+/// verify [CompactReceipt]s produced by them.
+pub fn groth16_registry() -> Result<Registry, VerificationError> {
+    use hex::FromHex;
+    let key = VersionedKey {
+        pvk: prepared_verifying_key().map_err(|_| VerificationError::ReceiptFormatError)?,
+        allowed_ids_root: Digest::from_hex(ALLOWED_IDS_ROOT)
+            .map_err(|_| VerificationError::ReceiptFormatError)?,
+        bn254_control_id: fr_from_hex_string(BN254_CONTROL_ID)
+            .map_err(|_| VerificationError::ReceiptFormatError)?,
+    };
+    let mut registry = Registry::new();
+    registry.insert(crate::VERSION, key);
+    Ok(registry)
+}
+
+/// Verify that a Groth16 `seal` attests to a successful execution of the guest
+/// with the given `image_id`, which committed exactly `journal`.
+///
+/// The [ReceiptClaim] is recomputed from the image ID, the digest of the
+/// post-execution [SystemState] and the journal, and the seal is verified
+/// against its digest, so a valid seal for any other journal is rejected. The
+/// post-state digest is part of the claim and must be provided alongside the
+/// seal by the prover.
+pub fn verify_groth16_seal(
+    seal: &Seal,
+    image_id: impl Into<Digest>,
+    post_state_digest: impl Into<Digest>,
+    journal: &[u8],
+) -> Result<(), VerificationError> {
+    let claim = halted_claim(image_id.into(), post_state_digest.into(), journal);
+    let seal = SealRef::try_from(seal).map_err(|_| VerificationError::ReceiptFormatError)?;
+    Verifier::from_seal_versioned(&groth16_registry()?, crate::VERSION, seal, claim.digest())
+        .map_err(|_| VerificationError::ReceiptFormatError)?
+        .verify()
+        .map_err(|_| VerificationError::InvalidProof)
+}
+
+// Claim of an execution of `image_id` which halted with exit code 0, without
+// assumptions, as proven by the Groth16 seals of Bonsai and on-chain verifiers.
+fn halted_claim(image_id: Digest, post_state_digest: Digest, journal: &[u8]) -> ReceiptClaim {
+    ReceiptClaim::ok(
+        image_id,
+        post_state_digest,
+        Output::from_digest(journal.digest()),
+    )
 }
 
 /// A receipt composed of one or more [SegmentReceipt] structs proving a single
@@ -565,7 +913,7 @@ impl CompositeReceipt {
             };
 
             // If these digests do not match, this receipt is internally inconsistent.
-            if self_output.digest() != claim.output.digest() {
+            if !self_output.digest().ct_eq(&claim.output.digest()) {
                 let empty_output = claim.output.is_none()
                     && self
                         .journal_digest
@@ -670,7 +1018,7 @@ impl SegmentReceipt {
         // Receipt is consistent with the claim encoded on the seal. Now check against the
         // claim on the struct.
         let decoded_claim = decode_receipt_claim_from_seal(&self.seal)?;
-        if decoded_claim.digest() != self.claim.digest() {
+        if !decoded_claim.digest().ct_eq(&self.claim.digest()) {
             tracing::debug!(
                 "decoded segment receipt claim does not match claim field:\ndecoded: {:#?},\nexpected: {:#?}",
                 decoded_claim,
@@ -753,6 +1101,35 @@ impl From<ReceiptClaim> for Assumption {
 pub struct VerifierContext {
     /// A registry of hash functions to be used by the verification process.
     pub suites: BTreeMap<String, HashSuite<BabyBear>>,
+
+    /// Policies checked by [Receipt::verify_with_context] on each receipt.
+    pub policies: Vec<Arc<dyn VerifierPolicy>>,
+
+    /// Source of the current time for the policies, if any.
+    ///
+    /// Defaults to the [SystemClock](crate::SystemClock) when the `std`
+    /// feature is enabled.
+    pub clock: Option<Arc<dyn Clock>>,
+}
+
+impl VerifierContext {
+    /// Adds a [VerifierPolicy] to be checked on each verified receipt.
+    pub fn with_policy(mut self, policy: impl VerifierPolicy + 'static) -> Self {
+        self.policies.push(Arc::new(policy));
+        self
+    }
+
+    /// Replaces the [Clock] of this context.
+    pub fn with_clock(mut self, clock: impl Clock + 'static) -> Self {
+        self.clock = Some(Arc::new(clock));
+        self
+    }
+
+    /// Returns the current time of the [Clock] of this context, in seconds
+    /// since the UNIX epoch.
+    pub fn now(&self) -> Option<u64> {
+        self.clock.as_ref().map(|clock| clock.now())
+    }
 }
 
 fn decode_system_state_from_io(
@@ -814,6 +1191,11 @@ impl Default for VerifierContext {
                 ("poseidon2".into(), Poseidon2HashSuite::new_suite()),
                 ("sha-256".into(), Sha256HashSuite::new_suite()),
             ]),
+            policies: Vec::new(),
+            #[cfg(feature = "std")]
+            clock: Some(Arc::new(super::policy::SystemClock)),
+            #[cfg(not(feature = "std"))]
+            clock: None,
         }
     }
 }
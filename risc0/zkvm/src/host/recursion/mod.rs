@@ -31,6 +31,6 @@ pub use self::prove::{
     identity_p254, join, lift, poseidon2_hal_pair, resolve, Program, Prover, ProverOpts,
     RECURSION_PO2,
 };
-pub use self::receipt::{valid_control_ids, SuccinctReceipt};
+pub use self::receipt::{valid_control_ids, SuccinctReceipt, SuccinctVerifier};
 
 const CIRCUIT: risc0_circuit_recursion::CircuitImpl = risc0_circuit_recursion::CircuitImpl::new();
@@ -79,6 +79,7 @@ pub struct RecursionReceipt {
 /// resulting in a recursion circuit STARK proof. This recursion proof has a single
 /// constant-time verification procedure, with respect to the original segment length, and is then
 /// used as the input to all other recursion programs (e.g. join, resolve, and identity_p254).
+#[tracing::instrument(skip_all)]
 pub fn lift(segment_receipt: &SegmentReceipt) -> Result<SuccinctReceipt> {
     tracing::debug!("Proving lift: claim = {:#?}", segment_receipt.claim);
     let mut prover = Prover::new_lift(&segment_receipt.seal, ProverOpts::default())?;
@@ -98,6 +99,7 @@ pub fn lift(segment_receipt: &SegmentReceipt) -> Result<SuccinctReceipt> {
 ///
 /// By repeated application of the join program, any number of receipts for execution spans within
 /// the same session can be compressed into a single receipt for the entire session.
+#[tracing::instrument(skip_all)]
 pub fn join(a: &SuccinctReceipt, b: &SuccinctReceipt) -> Result<SuccinctReceipt> {
     tracing::debug!("Proving join: a.claim = {:#?}", a.claim);
     tracing::debug!("Proving join: b.claim = {:#?}", b.claim);
@@ -130,6 +132,7 @@ pub fn join(a: &SuccinctReceipt, b: &SuccinctReceipt) -> Result<SuccinctReceipt>
 ///
 /// By applying the resolve program, a conditional receipt (i.e. a receipt for an execution using
 /// the `env::verify` API to logically verify a receipt) can be made into an unconditional receipt.
+#[tracing::instrument(skip_all)]
 pub fn resolve(
     conditional: &SuccinctReceipt,
     assumption: &SuccinctReceipt,
@@ -178,6 +181,7 @@ pub fn resolve(
 /// The identity_p254 program is used as the last step in the prover pipeline before running the
 /// Groth16 prover. In Groth16 over BN254, it is much more efficient to verify a STARK that was
 /// produced with Poseidon over the BN254 base field compared to using Posidon over BabyBear.
+#[tracing::instrument(skip_all)]
 pub fn identity_p254(a: &SuccinctReceipt) -> Result<SuccinctReceipt> {
     let hal_pair = poseidon254_hal_pair();
     let (hal, circuit_hal) = (hal_pair.hal.as_ref(), hal_pair.circuit_hal.as_ref());
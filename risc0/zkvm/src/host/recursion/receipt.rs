@@ -17,8 +17,12 @@ use alloc::{collections::VecDeque, vec::Vec};
 use risc0_binfmt::read_sha_halfs;
 use risc0_circuit_recursion::{control_id::RECURSION_CONTROL_IDS, CircuitImpl};
 use risc0_circuit_rv32im::control_id::POSEIDON2_CONTROL_ID;
-use risc0_core::field::baby_bear::BabyBearElem;
-use risc0_zkp::{adapter::CircuitInfo, core::digest::Digest, verify::VerificationError};
+use risc0_core::field::baby_bear::{BabyBear, BabyBearElem};
+use risc0_zkp::{
+    adapter::CircuitInfo,
+    core::digest::Digest,
+    verify::{Progress, StepVerifier, VerificationError},
+};
 use serde::{Deserialize, Serialize};
 
 use super::CIRCUIT;
@@ -39,6 +43,18 @@ pub fn valid_control_ids() -> Vec<Digest> {
     all_ids
 }
 
+/// Check that a control ID is one of the Poseidon2 or recursion circuit
+/// variants we accept.
+fn check_control_id(_: u32, control_id: &Digest) -> Result<(), VerificationError> {
+    valid_control_ids()
+        .iter()
+        .find(|x| *x == control_id)
+        .map(|_| ())
+        .ok_or(VerificationError::ControlVerificationError {
+            control_id: *control_id,
+        })
+}
+
 /// A succinct receipt, produced via recursion, proving the execution of the zkVM.
 ///
 /// Using recursion, a [crate::CompositeReceipt] can be compressed to form a [SuccinctReceipt]. In this
@@ -72,19 +88,6 @@ impl SuccinctReceipt {
         &self,
         ctx: &VerifierContext,
     ) -> Result<(), VerificationError> {
-        // Assemble the list of control IDs, and therefore circuit variants, we will
-        // accept.
-        let valid_ids = valid_control_ids();
-        let check_code = |_, control_id: &Digest| -> Result<(), VerificationError> {
-            valid_ids
-                .iter()
-                .find(|x| *x == control_id)
-                .map(|_| ())
-                .ok_or(VerificationError::ControlVerificationError {
-                    control_id: *control_id,
-                })
-        };
-
         // All receipts from the recursion circuit use Poseidon2 as the FRI hash
         // function.
         let suite = ctx
@@ -94,8 +97,33 @@ impl SuccinctReceipt {
 
         // Verify the receipt itself is correct, and therefore the encoded globals are
         // reliable.
-        risc0_zkp::verify::verify(&CIRCUIT, suite, &self.seal, check_code)?;
+        risc0_zkp::verify::verify(&CIRCUIT, suite, &self.seal, check_control_id)?;
+        self.verify_claim()
+    }
 
+    /// Prepare to verify the integrity of this receipt a few steps at a time,
+    /// with [SuccinctVerifier::step].
+    pub fn verifier<'a>(
+        &'a self,
+        ctx: &'a VerifierContext,
+    ) -> Result<SuccinctVerifier<'a>, VerificationError> {
+        let suite = ctx
+            .suites
+            .get("poseidon2")
+            .ok_or(VerificationError::InvalidHashSuite)?;
+        Ok(SuccinctVerifier {
+            receipt: self,
+            inner: StepVerifier::new(
+                &CIRCUIT,
+                suite,
+                &self.seal,
+                check_control_id as CheckControlId,
+            ),
+        })
+    }
+
+    /// Check that the globals of the seal commit to the claim of this receipt.
+    fn verify_claim(&self) -> Result<(), VerificationError> {
         // Extract the globals from the seal
         let output_elems: &[BabyBearElem] =
             bytemuck::cast_slice(&self.seal[..CircuitImpl::OUTPUT_SIZE]);
@@ -109,7 +137,7 @@ impl SuccinctReceipt {
         // Verify the output hash matches that data
         let output_hash =
             read_sha_halfs(&mut seal_claim).map_err(|_| VerificationError::ReceiptFormatError)?;
-        if output_hash != self.claim.digest() {
+        if !output_hash.ct_eq(&self.claim.digest()) {
             tracing::debug!(
                 "succinct receipt claim does not match the output digest: claim: {:#?}, digest expected: {output_hash:?}",
                 self.claim,
@@ -120,8 +148,51 @@ impl SuccinctReceipt {
         Ok(())
     }
 
+    /// Reads the root of the control IDs allowed by the recursion program
+    /// from the globals of the seal, without verifying it.
+    pub(crate) fn control_root(&self) -> Result<Digest, VerificationError> {
+        let output_elems: &[BabyBearElem] = bytemuck::cast_slice(
+            self.seal
+                .get(..CircuitImpl::OUTPUT_SIZE)
+                .ok_or(VerificationError::ReceiptFormatError)?,
+        );
+        let mut globals: VecDeque<u32> = output_elems.iter().map(|elem| elem.as_u32()).collect();
+        read_sha_halfs(&mut globals).map_err(|_| VerificationError::ReceiptFormatError)
+    }
+
     /// Return the seal for this receipt, as a vector of bytes.
     pub fn get_seal_bytes(&self) -> Vec<u8> {
         self.seal.iter().flat_map(|x| x.to_le_bytes()).collect()
     }
 }
+
+type CheckControlId = fn(u32, &Digest) -> Result<(), VerificationError>;
+
+/// Resumable verifier of a [SuccinctReceipt], created by
+/// [SuccinctReceipt::verifier].
+///
+/// Lets clients without threads, such as wasm and mobile UIs, spread the
+/// verification of a receipt across frames or ticks.
+pub struct SuccinctVerifier<'a> {
+    receipt: &'a SuccinctReceipt,
+    inner: StepVerifier<'a, BabyBear, CircuitImpl, CheckControlId>,
+}
+
+impl<'a> SuccinctVerifier<'a> {
+    /// Total units of work of a verification.
+    pub const WORK: usize = StepVerifier::<'a, BabyBear, CircuitImpl, CheckControlId>::WORK;
+
+    /// Do up to `budget` units of work, of [SuccinctVerifier::WORK] in total.
+    ///
+    /// Returns [Progress::Done] once the seal is verified and attests to the
+    /// claim of the receipt.
+    pub fn step(&mut self, budget: usize) -> Result<Progress, VerificationError> {
+        match self.inner.step(budget)? {
+            Progress::Done => {
+                self.receipt.verify_claim()?;
+                Ok(Progress::Done)
+            }
+            pending => Ok(pending),
+        }
+    }
+}
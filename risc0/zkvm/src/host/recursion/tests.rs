@@ -17,6 +17,7 @@ use risc0_zkp::{
     adapter::CircuitInfo,
     core::digest::{Digest, DIGEST_WORDS},
     field::baby_bear::BabyBearElem,
+    verify::{Progress, VerificationError},
 };
 use risc0_zkvm_methods::{multi_test::MultiTestSpec, MULTI_TEST_ELF, MULTI_TEST_ID};
 use serial_test::serial;
@@ -27,8 +28,8 @@ use super::{
     ProverOpts as RecursionProverOpts,
 };
 use crate::{
-    get_prover_server, ExecutorEnv, ExecutorImpl, InnerReceipt, ProverOpts, Receipt,
-    SegmentReceipt, Session, VerifierContext,
+    get_prover_server, DeprecatedControlIds, ExecutorEnv, ExecutorImpl, InnerReceipt, MaybePruned,
+    ProverOpts, Receipt, SegmentReceipt, Session, SuccinctVerifier, VerifierContext,
 };
 
 // Failure on older mac minis in the lab with Intel UHD 630 graphics:
@@ -142,6 +143,7 @@ fn generate_busy_loop_segments(hashfn: &str) -> (Session, Vec<SegmentReceipt>) {
     let opts = ProverOpts {
         hashfn: hashfn.to_string(),
         prove_guest_errors: false,
+        ..Default::default()
     };
     let prover = get_prover_server(&opts).unwrap();
 
@@ -190,11 +192,23 @@ fn test_recursion_lift_join_identity_e2e() {
     // std::fs::write("recursion.seal", seal);
 
     // Validate the Session rollup + journal data
+    let control_id = rollup.control_id;
     let rollup_receipt = Receipt::new(
         InnerReceipt::Succinct(rollup),
         session.journal.unwrap().bytes,
     );
     rollup_receipt.verify(MULTI_TEST_ID).unwrap();
+
+    // Receipts of deprecated recursion programs are rejected.
+    let verify = |policy: DeprecatedControlIds| {
+        let ctx = VerifierContext::default().with_policy(policy);
+        rollup_receipt.verify_with_context(&ctx, MULTI_TEST_ID)
+    };
+    verify(DeprecatedControlIds::new().deprecate(Digest::ZERO)).unwrap();
+    assert!(matches!(
+        verify(DeprecatedControlIds::new().deprecate(control_id)),
+        Err(VerificationError::PolicyViolation(_))
+    ));
 }
 
 #[cfg_attr(
@@ -254,3 +268,39 @@ fn test_recursion_lift_resolve_e2e() {
 
     receipt.verify(MULTI_TEST_ID).unwrap();
 }
+
+#[cfg_attr(
+    not(all(feature = "metal", target_os = "macos", target_arch = "x86_64")),
+    test
+)]
+#[serial]
+fn test_recursion_step_verify() {
+    let (_, segments) = generate_busy_loop_segments("poseidon2");
+    let receipt = lift(&segments[0]).unwrap();
+    let ctx = VerifierContext::default();
+
+    let mut verifier = receipt.verifier(&ctx).unwrap();
+    let mut steps = 0;
+    let mut last = 0.0;
+    loop {
+        steps += 1;
+        match verifier.step(8).unwrap() {
+            Progress::Pending(fraction) => {
+                assert!(fraction > last);
+                last = fraction;
+            }
+            Progress::Done => break,
+        }
+    }
+    assert_eq!(steps, SuccinctVerifier::WORK.div_ceil(8));
+
+    // The claim is only checked once the seal is verified.
+    let mut tampered = receipt.clone();
+    tampered.claim.pre = MaybePruned::Pruned(Digest::ZERO);
+    let mut verifier = tampered.verifier(&ctx).unwrap();
+    assert!(matches!(verifier.step(1), Ok(Progress::Pending(_))));
+    assert_eq!(
+        verifier.step(SuccinctVerifier::WORK).unwrap_err(),
+        VerificationError::JournalDigestMismatch
+    );
+}
@@ -12,11 +12,19 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use std::{cell::RefCell, io::Write, mem, rc::Rc, sync::Arc, time::Instant};
+use std::{
+    cell::RefCell,
+    io::{BufReader, Cursor, Read, Write},
+    mem,
+    rc::Rc,
+    sync::Arc,
+    time::Instant,
+};
 
-use anyhow::{Context as _, Result};
+use anyhow::{bail, Context as _, Result};
+use bytemuck::Pod;
 use human_repr::HumanDuration as _;
-use risc0_binfmt::{MemoryImage, Program};
+use risc0_binfmt::{zkvm_version, ExitCode, MemoryImage, Program};
 use risc0_circuit_rv32im::prove::emu::{
     addr::ByteAddr,
     exec::{
@@ -25,17 +33,21 @@ use risc0_circuit_rv32im::prove::emu::{
     },
 };
 use risc0_zkp::core::digest::Digest;
-use risc0_zkvm_platform::{fileno, memory::GUEST_MAX_MEM, PAGE_SIZE};
+use risc0_zkvm_platform::{
+    fileno, memory::GUEST_MAX_MEM, syscall::nr::SYS_COMMIT_DIGEST, PAGE_SIZE,
+};
+use semver::Version;
 use tempfile::tempdir;
 
 use crate::{
-    host::client::env::SegmentPath, Assumption, Assumptions, ExecutorEnv, FileSegmentRef, Output,
-    Segment, SegmentRef, Session,
+    host::client::env::{SegmentPath, TimeLimitExceeded, VersionSkew},
+    Assumption, Assumptions, ExecutorEnv, FileSegmentRef, MaybePruned, Output, Segment, SegmentRef,
+    Session,
 };
 
 use super::{
     profiler::Profiler,
-    syscall::{SyscallContext, SyscallTable},
+    syscall::{SysCommitDigest, SyscallContext, SyscallTable},
 };
 
 // The Executor provides an implementation for the execution phase.
@@ -46,6 +58,9 @@ pub struct ExecutorImpl<'a> {
     image: MemoryImage,
     pub(crate) syscall_table: SyscallTable<'a>,
     profiler: Option<Rc<RefCell<Profiler>>>,
+    deadline: Option<Instant>,
+    exit_code: Option<ExitCode>,
+    input: Vec<u8>,
 }
 
 impl<'a> ExecutorImpl<'a> {
@@ -56,6 +71,15 @@ impl<'a> ExecutorImpl<'a> {
     /// work will be done in each segment. This is the execution phase:
     /// the guest program is executed to determine how its proof should be
     /// divided into subparts.
+    ///
+    /// Clones of a [MemoryImage] share the pages they do not modify, so hosts
+    /// running many executions of the same guest at once can build its image
+    /// once and pass a clone of it to each executor.
+    ///
+    /// A [MemoryImage] does not carry the symbols recording the risc0-zkvm
+    /// version of the guest, so the [version
+    /// skew](crate::ExecutorEnvBuilder::version_skew) of `env` is not checked.
+    /// Use [ExecutorImpl::from_elf] to check it.
     pub fn new(env: ExecutorEnv<'a>, image: MemoryImage) -> Result<Self> {
         Self::with_details(env, image, None)
     }
@@ -76,10 +100,25 @@ impl<'a> ExecutorImpl<'a> {
     ///     .unwrap();
     /// let mut exec = ExecutorImpl::from_elf(env, BENCH_ELF).unwrap();
     /// ```
-    pub fn from_elf(mut env: ExecutorEnv<'a>, elf: &[u8]) -> Result<Self> {
+    pub fn from_elf(env: ExecutorEnv<'a>, elf: &[u8]) -> Result<Self> {
+        check_guest_version(zkvm_version(elf)?, &crate::get_version()?, env.version_skew)?;
         let program = Program::load_elf(elf, GUEST_MAX_MEM as u32)?;
         let image = MemoryImage::new(&program, PAGE_SIZE as u32)?;
+        Self::with_elf_image(env, elf, image)
+    }
+
+    /// Construct a new [ExecutorImpl] like [ExecutorImpl::from_elf], loading
+    /// the initial memory image through the image cache.
+    ///
+    /// See [MemoryImage::load_cached] for where the cache lives and how its
+    /// entries are checked.
+    pub fn from_elf_cached(env: ExecutorEnv<'a>, elf: &[u8]) -> Result<Self> {
+        check_guest_version(zkvm_version(elf)?, &crate::get_version()?, env.version_skew)?;
+        let image = MemoryImage::load_cached(elf)?;
+        Self::with_elf_image(env, elf, image)
+    }
 
+    fn with_elf_image(mut env: ExecutorEnv<'a>, elf: &[u8], image: MemoryImage) -> Result<Self> {
         let profiler = if env.pprof_out.is_some() {
             let profiler = Rc::new(RefCell::new(Profiler::new(elf, None)?));
             env.trace.push(profiler.clone());
@@ -102,6 +141,9 @@ impl<'a> ExecutorImpl<'a> {
             image,
             syscall_table,
             profiler,
+            deadline: None,
+            exit_code: None,
+            input: Vec::new(),
         })
     }
 
@@ -118,6 +160,7 @@ impl<'a> ExecutorImpl<'a> {
 
     /// Run the executor until [crate::ExitCode::Halted] or
     /// [crate::ExitCode::Paused] is reached, producing a [Session] as a result.
+    #[tracing::instrument(name = "execute", skip_all)]
     pub fn run_with_callback<F>(&mut self, mut callback: F) -> Result<Session>
     where
         F: FnMut(Segment) -> Result<Box<dyn SegmentRef>>,
@@ -127,6 +170,9 @@ impl<'a> ExecutorImpl<'a> {
             .posix_io
             .borrow_mut()
             .with_write_fd(fileno::JOURNAL, journal.clone());
+        let committed_digest = SysCommitDigest::default();
+        self.syscall_table
+            .with_syscall(SYS_COMMIT_DIGEST, committed_digest.clone());
 
         let segment_limit_po2 = self
             .env
@@ -134,9 +180,10 @@ impl<'a> ExecutorImpl<'a> {
             .unwrap_or(DEFAULT_SEGMENT_LIMIT_PO2 as u32) as usize;
 
         let mut refs = Vec::new();
+        let start_time = Instant::now();
+        self.deadline = self.env.time_limit.map(|limit| start_time + limit);
         let mut exec = Executor::new(self.image.clone(), self, self.env.trace.clone());
 
-        let start_time = Instant::now();
         let result = exec.run(segment_limit_po2, self.env.session_limit, |inner| {
             let output = inner
                 .exit_code
@@ -149,7 +196,10 @@ impl<'a> ExecutorImpl<'a> {
                         })
                         .map(|journal| {
                             Ok(Output {
-                                journal: journal.into(),
+                                journal: match *committed_digest.0.borrow() {
+                                    Some(digest) => MaybePruned::Pruned(digest),
+                                    None => journal.into(),
+                                },
                                 assumptions: Assumptions(
                                     self.env
                                         .assumptions
@@ -178,7 +228,7 @@ impl<'a> ExecutorImpl<'a> {
             };
             let segment_ref = callback(segment.into())?;
             refs.push(segment_ref);
-            Ok(())
+            self.check_time_limit()
         })?;
         let elapsed = start_time.elapsed();
 
@@ -194,6 +244,11 @@ impl<'a> ExecutorImpl<'a> {
             );
         };
 
+        let committed_digest = session_journal
+            .is_some()
+            .then(|| committed_digest.0.take())
+            .flatten();
+
         // Take (clear out) the list of accessed assumptions.
         // Leave the assumptions cache so it can be used if execution is resumed from pause.
         let assumptions = mem::take(&mut self.env.assumptions.borrow_mut().accessed);
@@ -204,8 +259,9 @@ impl<'a> ExecutorImpl<'a> {
         }
 
         self.image = result.post_image.clone();
+        self.exit_code = Some(result.exit_code);
 
-        let session = Session::new(
+        let mut session = Session::new(
             refs,
             session_journal,
             result.exit_code,
@@ -216,6 +272,9 @@ impl<'a> ExecutorImpl<'a> {
             result.pre_state,
             result.post_state,
         );
+        if let Some(digest) = committed_digest {
+            session = session.with_committed_digest(digest);
+        }
 
         tracing::info_span!("executor").in_scope(|| {
             tracing::info!("execution time: {}", elapsed.human_duration());
@@ -224,10 +283,83 @@ impl<'a> ExecutorImpl<'a> {
 
         Ok(session)
     }
+
+    /// Continue the execution of a guest paused with `env::pause`, producing
+    /// the [Session] of the next exchange.
+    ///
+    /// Input for the next exchange is provided beforehand with
+    /// [ExecutorImpl::write], [ExecutorImpl::write_slice] or
+    /// [ExecutorImpl::stdin].
+    ///
+    /// # Example
+    /// ```
+    /// use risc0_zkvm::{ExecutorEnv, ExecutorImpl, ExitCode};
+    /// use risc0_zkvm_methods::{multi_test::MultiTestSpec, MULTI_TEST_ELF};
+    ///
+    /// let env = ExecutorEnv::builder()
+    ///     .write(&MultiTestSpec::PauseResume(1))
+    ///     .unwrap()
+    ///     .write(&7u32)
+    ///     .unwrap()
+    ///     .build()
+    ///     .unwrap();
+    /// let mut exec = ExecutorImpl::from_elf(env, MULTI_TEST_ELF).unwrap();
+    /// let session = exec.run().unwrap();
+    /// assert_eq!(session.exit_code, ExitCode::Paused(0));
+    /// let session = exec.resume().unwrap();
+    /// assert_eq!(session.exit_code, ExitCode::Halted(0));
+    /// ```
+    pub fn resume(&mut self) -> Result<Session> {
+        match self.exit_code {
+            Some(ExitCode::Paused(_)) => {}
+            exit_code => bail!("cannot resume a guest that is not paused: {exit_code:?}"),
+        }
+        if !self.input.is_empty() {
+            let reader = Cursor::new(mem::take(&mut self.input));
+            self.stdin(reader);
+        }
+        self.run()
+    }
+
+    /// Write input data to the guest stdin for the next [ExecutorImpl::resume].
+    ///
+    /// This function will serialize `data` like [ExecutorEnvBuilder::write].
+    /// The guest stdin is replaced on resume, so any input left unread in the
+    /// previous exchange is dropped.
+    ///
+    /// [ExecutorEnvBuilder::write]: crate::ExecutorEnvBuilder::write
+    pub fn write<T: serde::Serialize>(&mut self, data: &T) -> Result<&mut Self> {
+        Ok(self.write_slice(&crate::serde::to_vec(data)?))
+    }
+
+    /// Write a slice to the guest stdin for the next [ExecutorImpl::resume].
+    pub fn write_slice<T: Pod>(&mut self, slice: &[T]) -> &mut Self {
+        self.input.extend_from_slice(bytemuck::cast_slice(slice));
+        self
+    }
+
+    /// Replace the guest stdin with `reader`.
+    pub fn stdin(&mut self, reader: impl Read + 'a) -> &mut Self {
+        self.env
+            .posix_io
+            .borrow_mut()
+            .with_read_fd(fileno::STDIN, BufReader::new(reader));
+        self
+    }
+
+    fn check_time_limit(&self) -> Result<()> {
+        if let (Some(limit), Some(deadline)) = (self.env.time_limit, self.deadline) {
+            if Instant::now() >= deadline {
+                return Err(TimeLimitExceeded { limit }.into());
+            }
+        }
+        Ok(())
+    }
 }
 
 struct ContextAdapter<'a> {
     ctx: &'a mut dyn NewSyscallContext,
+    deadline: Option<Instant>,
 }
 
 impl<'a> SyscallContext for ContextAdapter<'a> {
@@ -242,6 +374,10 @@ impl<'a> SyscallContext for ContextAdapter<'a> {
     fn load_u8(&mut self, addr: u32) -> Result<u8> {
         self.ctx.peek_u8(ByteAddr(addr))
     }
+
+    fn deadline(&self) -> Option<Instant> {
+        self.deadline
+    }
 }
 
 impl<'a> NewSyscall for ExecutorImpl<'a> {
@@ -251,12 +387,20 @@ impl<'a> NewSyscall for ExecutorImpl<'a> {
         ctx: &mut dyn NewSyscallContext,
         into_guest: &mut [u32],
     ) -> Result<(u32, u32)> {
-        let mut ctx = ContextAdapter { ctx };
-        self.syscall_table
+        self.check_time_limit()?;
+        let mut ctx = ContextAdapter {
+            ctx,
+            deadline: self.deadline,
+        };
+        let result = self
+            .syscall_table
             .get_syscall(&syscall)
             .context(format!("Unknown syscall: {syscall:?}"))?
             .borrow_mut()
-            .syscall(&syscall, &mut ctx, into_guest)
+            .syscall(&syscall, &mut ctx, into_guest);
+        // A handler which gave up at the deadline fails with its own error.
+        self.check_time_limit()?;
+        result
     }
 }
 
@@ -275,3 +419,36 @@ impl Write for Journal {
         self.buf.borrow_mut().flush()
     }
 }
+
+// Versions of the same family are expected to be compatible: the major and
+// minor versions before 1.0, and the major version after.
+fn version_family(version: [u64; 3]) -> (u64, u64) {
+    match version {
+        [0, minor, _] => (0, minor),
+        [major, ..] => (major, 0),
+    }
+}
+
+pub(crate) fn check_guest_version(
+    guest: Option<[u32; 3]>,
+    host: &Version,
+    policy: VersionSkew,
+) -> Result<()> {
+    let Some([major, minor, patch]) = guest.map(|version| version.map(u64::from)) else {
+        return Ok(());
+    };
+    if policy == VersionSkew::Ignore
+        || version_family([major, minor, patch])
+            == version_family([host.major, host.minor, host.patch])
+    {
+        return Ok(());
+    }
+    let msg = format!(
+        "guest built with risc0-zkvm {major}.{minor}.{patch} may be incompatible with host risc0-zkvm {host}"
+    );
+    if policy == VersionSkew::Error {
+        bail!(msg);
+    }
+    tracing::warn!("{msg}");
+    Ok(())
+}
@@ -14,7 +14,7 @@
 
 //! Handlers for two-way private I/O between host and guest.
 
-use std::{cell::RefCell, cmp::min, collections::HashMap, rc::Rc, str::from_utf8};
+use std::{cell::RefCell, cmp::min, collections::HashMap, rc::Rc, str::from_utf8, time::Instant};
 
 use anyhow::{anyhow, bail, Result};
 use bytes::Bytes;
@@ -63,6 +63,12 @@ pub trait SyscallContext {
     /// Loads an individual byte from memory.
     fn load_u8(&mut self, addr: u32) -> Result<u8>;
 
+    /// Returns the instant at which the time limit of the execution expires,
+    /// if any. Handlers which may block should give up by then.
+    fn deadline(&self) -> Option<Instant> {
+        None
+    }
+
     /// Loads bytes from the given region of memory.
     fn load_region(&mut self, addr: u32, size: u32) -> Result<Vec<u8>> {
         let mut region = Vec::new();
@@ -191,6 +197,29 @@ impl Syscall for SysRandom {
     }
 }
 
+/// Records the digest committed by the guest in place of the journal digest.
+#[derive(Clone, Default)]
+pub(crate) struct SysCommitDigest(pub(crate) Rc<RefCell<Option<Digest>>>);
+
+impl Syscall for SysCommitDigest {
+    fn syscall(
+        &mut self,
+        _syscall: &str,
+        ctx: &mut dyn SyscallContext,
+        _to_guest: &mut [u32],
+    ) -> Result<(u32, u32)> {
+        let buf_ptr = ctx.load_register(REG_A3);
+        let buf_len = ctx.load_register(REG_A4);
+        let digest: Digest = ctx
+            .load_region(buf_ptr, buf_len)?
+            .try_into()
+            .map_err(|vec| anyhow!("failed to convert to [u8; DIGEST_BYTES]: {vec:?}"))?;
+        tracing::debug!("SYS_COMMIT_DIGEST: {digest}");
+        self.0.replace(Some(digest));
+        Ok((0, 0))
+    }
+}
+
 #[derive(Clone)]
 pub(crate) struct SysVerify {
     pub(crate) assumptions: Rc<RefCell<Assumptions>>,
@@ -435,7 +464,7 @@ impl<'a> Syscall for SysSliceIo<'a> {
                 // and save what it returns.
                 assert_eq!(to_guest.len(), 0);
                 let mut handler = self.handler.borrow_mut();
-                let result = handler.handle_io(syscall, from_guest.into())?;
+                let result = handler.handle_io_until(syscall, from_guest.into(), ctx.deadline())?;
                 let len = result.len() as u32;
                 *stored_result = Some(result);
                 (len, 0)
@@ -17,6 +17,7 @@ use std::{
     io::Cursor,
     str::from_utf8,
     sync::Mutex,
+    time::{Duration, Instant},
 };
 
 use anyhow::Result;
@@ -33,6 +34,7 @@ use test_log::test;
 use crate::{
     host::server::{
         exec::{
+            executor::check_guest_version,
             profiler::{Frame, Profiler},
             syscall::{Syscall, SyscallContext},
         },
@@ -40,7 +42,8 @@ use crate::{
     },
     serde::to_vec,
     sha::{Digest, Digestible},
-    ExecutorEnv, ExecutorImpl, ExitCode,
+    ExecutorEnv, ExecutorEnvBuilder, ExecutorImpl, ExitCode, Session, SessionOutcome,
+    TimeLimitExceeded, VersionSkew,
 };
 
 fn run_test(spec: MultiTestSpec) {
@@ -100,6 +103,46 @@ fn basic() {
     assert_eq!(segment.index, 0);
 }
 
+#[test]
+fn split_segments() {
+    let env = ExecutorEnv::builder()
+        .write(&MultiTestSpec::BusyLoop { cycles: 1 << 16 })
+        .unwrap()
+        .segment_limit_po2(16)
+        .build()
+        .unwrap();
+    let mut session = ExecutorImpl::from_elf(env, MULTI_TEST_ELF)
+        .unwrap()
+        .run()
+        .unwrap();
+    let claim = session.get_claim().unwrap().digest();
+    let count = session.segments.len();
+
+    session.split_segments(14).unwrap();
+    let segments: Vec<_> = session
+        .segments
+        .iter()
+        .map(|x| x.resolve().unwrap())
+        .collect();
+    assert!(segments.len() > count);
+    for (index, segment) in segments.iter().enumerate() {
+        assert_eq!(segment.index, index as u32);
+        assert!(segment.po2() <= 14);
+    }
+    assert_eq!(
+        session.total_cycles,
+        segments.iter().map(|x| 1 << x.po2()).sum::<u64>()
+    );
+    for pair in segments.windows(2) {
+        assert_eq!(pair[0].inner.exit_code, ExitCode::SystemSplit);
+        assert_eq!(
+            pair[0].inner.post_state.digest(),
+            pair[1].inner.pre_state.digest()
+        );
+    }
+    assert_eq!(session.get_claim().unwrap().digest(), claim);
+}
+
 #[test]
 fn system_split() {
     let entry = 0x4000;
@@ -126,6 +169,7 @@ fn system_split() {
     let mut exec = ExecutorImpl::new(env, image).unwrap();
     let session = exec.run().unwrap();
     assert_eq!(session.exit_code, ExitCode::Halted(0));
+    assert_eq!(session.outcome().unwrap(), SessionOutcome::Completed);
     let segments: Vec<_> = session
         .segments
         .iter()
@@ -201,6 +245,107 @@ fn host_syscall_callback_panic() {
     assert_eq!(session.exit_code, ExitCode::Halted(0));
 }
 
+#[test]
+fn version_skew() {
+    let host = semver::Version::new(0, 21, 3);
+    for policy in [VersionSkew::Ignore, VersionSkew::Warn, VersionSkew::Error] {
+        check_guest_version(None, &host, policy).unwrap();
+        check_guest_version(Some([0, 21, 0]), &host, policy).unwrap();
+    }
+    check_guest_version(Some([0, 20, 1]), &host, VersionSkew::Ignore).unwrap();
+    check_guest_version(Some([0, 20, 1]), &host, VersionSkew::Warn).unwrap();
+    let err = check_guest_version(Some([0, 20, 1]), &host, VersionSkew::Error).unwrap_err();
+    assert!(err.to_string().contains("risc0-zkvm 0.20.1"));
+
+    let host = semver::Version::new(1, 2, 0);
+    check_guest_version(Some([1, 0, 5]), &host, VersionSkew::Error).unwrap();
+    check_guest_version(Some([2, 0, 0]), &host, VersionSkew::Error).unwrap_err();
+
+    // Guests built by risc0-build record its version.
+    let env = ExecutorEnv::builder()
+        .version_skew(VersionSkew::Error)
+        .write(&MultiTestSpec::DoNothing)
+        .unwrap()
+        .build()
+        .unwrap();
+    ExecutorImpl::from_elf(env, MULTI_TEST_ELF).unwrap();
+}
+
+#[test]
+fn time_limit() {
+    fn run_session(env: &mut ExecutorEnvBuilder, spec: MultiTestSpec) -> Result<Session> {
+        let env = env.write(&spec).unwrap().build().unwrap();
+        ExecutorImpl::from_elf(env, MULTI_TEST_ELF).unwrap().run()
+    }
+
+    let session = run_session(
+        ExecutorEnv::builder().time_limit(Duration::from_secs(60)),
+        MultiTestSpec::BusyLoop { cycles: 1 << 16 },
+    )
+    .unwrap();
+    assert_eq!(session.exit_code, ExitCode::Halted(0));
+
+    // A busy guest is stopped at the end of a segment.
+    let limit = Duration::from_millis(1);
+    let err = run_session(
+        ExecutorEnv::builder()
+            .segment_limit_po2(16)
+            .time_limit(limit),
+        MultiTestSpec::BusyLoop { cycles: 1 << 24 },
+    )
+    .unwrap_err();
+    assert_eq!(
+        err.downcast_ref::<TimeLimitExceeded>(),
+        Some(&TimeLimitExceeded { limit })
+    );
+
+    // A guest waiting on a slow host is stopped between syscalls.
+    let limit = Duration::from_millis(100);
+    let err = run_session(
+        ExecutorEnv::builder()
+            .io_callback(SYS_MULTI_TEST, |_| {
+                std::thread::sleep(Duration::from_millis(10));
+                Ok(Bytes::new())
+            })
+            .time_limit(limit),
+        MultiTestSpec::Syscall { count: 1000 },
+    )
+    .unwrap_err();
+    assert_eq!(
+        err.downcast_ref::<TimeLimitExceeded>(),
+        Some(&TimeLimitExceeded { limit })
+    );
+
+    // A guest waiting on a host callback which never returns is stopped at
+    // the deadline.
+    let start = Instant::now();
+    let err = run_session(
+        ExecutorEnv::builder()
+            .io_callback_send(SYS_MULTI_TEST, |_| {
+                std::thread::sleep(Duration::from_secs(3600));
+                Ok(Bytes::new())
+            })
+            .time_limit(limit),
+        MultiTestSpec::Syscall { count: 1 },
+    )
+    .unwrap_err();
+    assert_eq!(
+        err.downcast_ref::<TimeLimitExceeded>(),
+        Some(&TimeLimitExceeded { limit })
+    );
+    assert!(start.elapsed() < Duration::from_secs(60));
+
+    // Callbacks which return in time run as usual.
+    let session = run_session(
+        ExecutorEnv::builder()
+            .io_callback_send(SYS_MULTI_TEST, |_| Ok(Bytes::new()))
+            .time_limit(Duration::from_secs(60)),
+        MultiTestSpec::Syscall { count: 10 },
+    )
+    .unwrap();
+    assert_eq!(session.exit_code, ExitCode::Halted(0));
+}
+
 #[test]
 fn sha_accel() {
     run_test(MultiTestSpec::ShaConforms);
@@ -480,6 +625,33 @@ fn large_io_bytes() {
     assert_eq!(&buf, actual);
 }
 
+#[test]
+fn pause_resume() {
+    let env = ExecutorEnv::builder()
+        .write(&MultiTestSpec::PauseResume(2))
+        .unwrap()
+        .write(&1u32)
+        .unwrap()
+        .build()
+        .unwrap();
+    let mut exec = ExecutorImpl::from_elf(env, MULTI_TEST_ELF).unwrap();
+    assert!(exec.resume().is_err());
+
+    let session = exec.run().unwrap();
+    assert_eq!(session.exit_code, ExitCode::Paused(0));
+    assert_eq!(session.journal.unwrap().decode::<u32>().unwrap(), 1);
+
+    // Each exchange reads the input provided before it is resumed.
+    exec.write(&2u32).unwrap();
+    let session = exec.resume().unwrap();
+    assert_eq!(session.exit_code, ExitCode::Paused(0));
+    assert_eq!(session.journal.unwrap().decode::<u32>().unwrap(), 2);
+
+    let session = exec.resume().unwrap();
+    assert_eq!(session.exit_code, ExitCode::Halted(0));
+    assert!(exec.resume().is_err());
+}
+
 mod sys_verify {
     use risc0_zkvm_methods::{
         multi_test::MultiTestSpec, HELLO_COMMIT_ELF, HELLO_COMMIT_ID, MULTI_TEST_ELF, MULTI_TEST_ID,
@@ -734,6 +906,25 @@ fn large_sha() {
     assert_eq!(expected, actual);
 }
 
+#[test]
+fn commit_digest() {
+    let digest = Digest::from([1, 2, 3, 4, 5, 6, 7, 8]);
+    let env = ExecutorEnv::builder()
+        .write(&MultiTestSpec::CommitDigest { digest })
+        .unwrap()
+        .build()
+        .unwrap();
+    let session = ExecutorImpl::from_elf(env, MULTI_TEST_ELF)
+        .unwrap()
+        .run()
+        .unwrap();
+    assert!(session.journal.as_ref().unwrap().bytes.is_empty());
+    assert_eq!(session.committed_digest, Some(digest));
+    let claim = session.get_claim().unwrap();
+    let output = claim.output.as_value().unwrap().as_ref().unwrap();
+    assert_eq!(output.journal_digest(), digest);
+}
+
 #[test]
 fn std_stdio() {
     const STDIN: &str = "Hello world from stdin!\n";
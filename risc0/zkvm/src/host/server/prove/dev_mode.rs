@@ -19,7 +19,7 @@ use crate::{
         receipt::{InnerReceipt, SegmentReceipt, SuccinctReceipt},
         server::session::null_callback,
     },
-    ExecutorEnv, ExecutorImpl, ProverServer, Receipt, Segment, Session, VerifierContext,
+    ExecutorEnv, ExecutorImpl, ProveInfo, ProverServer, Receipt, Segment, Session, VerifierContext,
 };
 
 /// An implementation of a [ProverServer] for development and testing purposes.
@@ -63,16 +63,15 @@ impl ProverServer for DevModeProver {
         ))
     }
 
-    /// Prove the specified ELF binary using the specified [VerifierContext].
-    fn prove_with_ctx(
+    fn prove_info(
         &self,
         env: ExecutorEnv<'_>,
         ctx: &VerifierContext,
         elf: &[u8],
-    ) -> Result<Receipt> {
+    ) -> Result<ProveInfo> {
         let mut exec = ExecutorImpl::from_elf(env, elf)?;
         let session = exec.run_with_callback(null_callback)?;
-        self.prove_session(ctx, &session)
+        self.prove_session_info(ctx, &session)
     }
 
     fn prove_segment(&self, _ctx: &VerifierContext, _segment: &Segment) -> Result<SegmentReceipt> {
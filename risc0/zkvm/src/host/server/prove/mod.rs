@@ -28,8 +28,11 @@ use risc0_zkp::hal::{CircuitHal, Hal};
 
 use self::{dev_mode::DevModeProver, prover_impl::ProverImpl};
 use crate::{
-    host::receipt::{CompositeReceipt, InnerReceipt, SegmentReceipt, SuccinctReceipt},
-    is_dev_mode, ExecutorEnv, ExecutorImpl, ProverOpts, Receipt, Segment, Session, VerifierContext,
+    host::receipt::{
+        CompactReceipt, CompositeReceipt, InnerReceipt, SegmentReceipt, SuccinctReceipt,
+    },
+    is_dev_mode, ExecutorEnv, ExecutorImpl, ProveInfo, ProverOpts, Receipt, Segment, Session,
+    VerifierContext,
 };
 
 /// A ProverServer can execute a given ELF binary and produce a [Receipt]
@@ -47,14 +50,34 @@ pub trait ProverServer {
         ctx: &VerifierContext,
         elf: &[u8],
     ) -> Result<Receipt> {
+        Ok(self.prove_info(env, ctx, elf)?.receipt)
+    }
+
+    /// Prove the specified ELF binary using the specified [VerifierContext],
+    /// returning the [ProveInfo] of the session, which is executed once.
+    fn prove_info(
+        &self,
+        env: ExecutorEnv<'_>,
+        ctx: &VerifierContext,
+        elf: &[u8],
+    ) -> Result<ProveInfo> {
         let mut exec = ExecutorImpl::from_elf(env, elf)?;
         let session = exec.run()?;
-        self.prove_session(ctx, &session)
+        self.prove_session_info(ctx, &session)
     }
 
     /// Prove the specified [Session].
     fn prove_session(&self, ctx: &VerifierContext, session: &Session) -> Result<Receipt>;
 
+    /// Prove the specified [Session], returning its [ProveInfo].
+    fn prove_session_info(&self, ctx: &VerifierContext, session: &Session) -> Result<ProveInfo> {
+        Ok(ProveInfo {
+            receipt: self.prove_session(ctx, session)?,
+            stats: session.stats(),
+            fallbacks: Vec::new(),
+        })
+    }
+
     /// Prove the specified [Segment].
     fn prove_segment(&self, ctx: &VerifierContext, segment: &Segment) -> Result<SegmentReceipt>;
 
@@ -121,6 +144,24 @@ pub trait ProverServer {
             },
         )
     }
+
+    /// Wrap a [SuccinctReceipt] into a [CompactReceipt] proving the same claim with a Groth16
+    /// seal.
+    ///
+    /// The receipt is converted by the identity_p254 recursion program, whose seal is encoded as
+    /// the witness inputs of the Circom circuit and proven by the Groth16 prover. This is the
+    /// same conversion applied by Bonsai, and requires Docker on an x86 architecture. The
+    /// [CompactReceipt] is verified before it is returned.
+    fn compact(&self, receipt: &SuccinctReceipt) -> Result<CompactReceipt> {
+        let ident_receipt = self.identity_p254(receipt)?;
+        let seal = risc0_groth16::docker::stark_to_snark(&ident_receipt.get_seal_bytes())?;
+        let compact_receipt = CompactReceipt {
+            seal: seal.to_vec(),
+            claim: receipt.claim.clone(),
+        };
+        compact_receipt.verify_integrity()?;
+        Ok(compact_receipt)
+    }
 }
 
 /// A pair of [Hal] and [CircuitHal].
@@ -154,7 +195,7 @@ mod cuda {
     use risc0_circuit_rv32im::prove::hal::cuda::{CudaCircuitHalPoseidon2, CudaCircuitHalSha256};
     use risc0_zkp::hal::cuda::{CudaHalPoseidon2, CudaHalSha256};
 
-    use super::{HalPair, ProverImpl, ProverServer};
+    use super::{cpu, HalPair, ProverImpl, ProverServer};
     use crate::ProverOpts;
 
     pub fn get_prover_server(opts: &ProverOpts) -> Result<Rc<dyn ProverServer>> {
@@ -162,22 +203,29 @@ mod cuda {
             "sha-256" => {
                 let hal = Rc::new(CudaHalSha256::new());
                 let circuit_hal = Rc::new(CudaCircuitHalSha256::new(hal.clone()));
-                Ok(Rc::new(ProverImpl::new(
-                    "cuda",
-                    HalPair { hal, circuit_hal },
-                )))
+                Ok(Rc::new(
+                    ProverImpl::new("cuda", HalPair { hal, circuit_hal })
+                        .with_fallback(cpu_fallback(opts))
+                        .with_budget(opts),
+                ))
             }
             "poseidon2" => {
                 let hal = Rc::new(CudaHalPoseidon2::new());
                 let circuit_hal = Rc::new(CudaCircuitHalPoseidon2::new(hal.clone()));
-                Ok(Rc::new(ProverImpl::new(
-                    "cuda",
-                    HalPair { hal, circuit_hal },
-                )))
+                Ok(Rc::new(
+                    ProverImpl::new("cuda", HalPair { hal, circuit_hal })
+                        .with_fallback(cpu_fallback(opts))
+                        .with_budget(opts),
+                ))
             }
             _ => bail!("Unsupported hashfn: {}", opts.hashfn),
         }
     }
+
+    fn cpu_fallback(opts: &ProverOpts) -> impl Fn() -> Result<Rc<dyn ProverServer>> {
+        let opts = opts.clone();
+        move || cpu::get_prover_server(&opts)
+    }
 }
 
 #[cfg(feature = "metal")]
@@ -198,18 +246,16 @@ mod metal {
             "sha-256" => {
                 let hal = Rc::new(MetalHalSha256::new());
                 let circuit_hal = Rc::new(MetalCircuitHal::<MetalHashSha256>::new(hal.clone()));
-                Ok(Rc::new(ProverImpl::new(
-                    "metal",
-                    HalPair { hal, circuit_hal },
-                )))
+                Ok(Rc::new(
+                    ProverImpl::new("metal", HalPair { hal, circuit_hal }).with_budget(opts),
+                ))
             }
             "poseidon2" => {
                 let hal = Rc::new(MetalHalPoseidon2::new());
                 let circuit_hal = Rc::new(MetalCircuitHal::<MetalHashPoseidon2>::new(hal.clone()));
-                Ok(Rc::new(ProverImpl::new(
-                    "metal",
-                    HalPair { hal, circuit_hal },
-                )))
+                Ok(Rc::new(
+                    ProverImpl::new("metal", HalPair { hal, circuit_hal }).with_budget(opts),
+                ))
             }
             _ => bail!("Unsupported hashfn: {}", opts.hashfn),
         }
@@ -239,7 +285,7 @@ mod cpu {
         let hal = Rc::new(CpuHal::new(suite));
         let circuit_hal = Rc::new(CpuCircuitHal::new());
         let hal_pair = HalPair { hal, circuit_hal };
-        Ok(Rc::new(ProverImpl::new("cpu", hal_pair)))
+        Ok(Rc::new(ProverImpl::new("cpu", hal_pair).with_budget(opts)))
     }
 }
 
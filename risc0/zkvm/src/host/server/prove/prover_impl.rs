@@ -12,9 +12,11 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use std::{cell::OnceCell, rc::Rc};
+
 use anyhow::{bail, Result};
 use risc0_core::field::baby_bear::{BabyBear, Elem, ExtElem};
-use risc0_zkp::hal::{CircuitHal, Hal};
+use risc0_zkp::hal::{catch_out_of_memory, CircuitHal, Hal, OutOfMemory};
 
 use super::{HalPair, ProverServer};
 use crate::{
@@ -23,9 +25,12 @@ use crate::{
         recursion::{identity_p254, join, lift, resolve},
     },
     sha::Digestible,
-    Receipt, Segment, Session, VerifierContext,
+    FallbackKind, ProveInfo, ProverOpts, Receipt, Segment, SegmentFallback, Session,
+    VerifierContext,
 };
 
+type MakeProver = Box<dyn Fn() -> Result<Rc<dyn ProverServer>>>;
+
 /// An implementation of a Prover that runs locally.
 pub struct ProverImpl<H, C>
 where
@@ -34,6 +39,9 @@ where
 {
     name: String,
     hal_pair: HalPair<H, C>,
+    make_fallback: Option<MakeProver>,
+    fallback: OnceCell<Rc<dyn ProverServer>>,
+    budget: ProverOpts,
 }
 
 impl<H, C> ProverImpl<H, C>
@@ -46,16 +54,171 @@ where
         Self {
             name: name.to_string(),
             hal_pair,
+            make_fallback: None,
+            fallback: OnceCell::new(),
+            budget: ProverOpts::default(),
+        }
+    }
+
+    /// Refuse to prove sessions exceeding the
+    /// [max_segments](ProverOpts::max_segments) or
+    /// [max_total_cycles](ProverOpts::max_total_cycles) of `opts`.
+    pub fn with_budget(mut self, opts: &ProverOpts) -> Self {
+        self.budget = opts.clone();
+        self
+    }
+
+    /// Prove segments with the prover built by `make_fallback` when this
+    /// prover runs out of device memory, rather than failing the whole
+    /// session.
+    ///
+    /// A segment of a [Session] that does not fit in device memory is first
+    /// split into segments of half its po2, down to the minimum po2, and only
+    /// the parts that still do not fit are proven by the fallback, e.g. on the
+    /// CPU. The fallback is built the first time it is needed. Fallbacks are
+    /// returned in the [ProveInfo] of the session, and reported to its
+    /// [SessionEvents](crate::SessionEvents) hooks.
+    pub fn with_fallback(
+        mut self,
+        make_fallback: impl Fn() -> Result<Rc<dyn ProverServer>> + 'static,
+    ) -> Self {
+        self.make_fallback = Some(Box::new(make_fallback));
+        self
+    }
+
+    fn fallback(&self) -> Result<Option<&Rc<dyn ProverServer>>> {
+        let Some(make_fallback) = self.make_fallback.as_ref() else {
+            return Ok(None);
+        };
+        if self.fallback.get().is_none() {
+            let _ = self.fallback.set(make_fallback()?);
         }
+        Ok(self.fallback.get())
+    }
+
+    /// Prove `segment` of a session, splitting it when it does not fit in
+    /// device memory, and return its receipts in order.
+    #[tracing::instrument(
+        name = "segment",
+        skip_all,
+        fields(index = segment.index, po2 = segment.po2())
+    )]
+    fn prove_segment_or_fallback(
+        &self,
+        ctx: &VerifierContext,
+        index: u32,
+        segment: &Segment,
+        fallbacks: &mut Vec<SegmentFallback>,
+        on_fallback: &dyn Fn(&str),
+    ) -> Result<Vec<SegmentReceipt>> {
+        let err = match catch_out_of_memory(|| self.prove_segment_inner(ctx, segment)) {
+            Ok(receipt) => return Ok(vec![receipt?]),
+            Err(err) => err,
+        };
+
+        let split_po2 = segment.po2() - 1;
+        let parts = match segment.split(split_po2) {
+            Ok(parts) => parts,
+            Err(split_err) => {
+                tracing::debug!("segment {index} cannot be split: {split_err}");
+                let Some(fallback) = self.fallback()? else {
+                    return Err(err.into());
+                };
+                tracing::warn!(
+                    "{} prover ran out of memory on segment {index} (po2 {}), falling back: {err}",
+                    self.name,
+                    segment.po2()
+                );
+                report_fallback(
+                    index,
+                    segment,
+                    FallbackKind::Prover,
+                    &err,
+                    fallbacks,
+                    on_fallback,
+                );
+                return Ok(vec![fallback.prove_segment(ctx, segment)?]);
+            }
+        };
+
+        tracing::warn!(
+            "{} prover ran out of memory on segment {index} (po2 {}), splitting to po2 {split_po2}: {err}",
+            self.name,
+            segment.po2()
+        );
+        let kind = FallbackKind::Split(split_po2 as u32);
+        report_fallback(index, segment, kind, &err, fallbacks, on_fallback);
+        let mut receipts = Vec::new();
+        for part in parts.iter() {
+            receipts.extend(self.prove_segment_or_fallback(
+                ctx,
+                index,
+                part,
+                fallbacks,
+                on_fallback,
+            )?);
+        }
+        Ok(receipts)
+    }
+
+    fn prove_segment_inner(
+        &self,
+        ctx: &VerifierContext,
+        segment: &Segment,
+    ) -> Result<SegmentReceipt> {
+        use risc0_circuit_rv32im::prove::{engine::SegmentProverImpl, SegmentProver as _};
+
+        use crate::host::receipt::decode_receipt_claim_from_seal;
+
+        let hashfn = self.hal_pair.hal.get_hash_suite().name.clone();
+
+        let prover =
+            SegmentProverImpl::new(self.hal_pair.hal.clone(), self.hal_pair.circuit_hal.clone());
+        let seal = prover.prove_segment(&segment.inner)?;
+
+        let mut claim = decode_receipt_claim_from_seal(&seal)?;
+        claim.output = segment.output.clone().into();
+
+        let receipt = SegmentReceipt {
+            seal,
+            index: segment.index as u32,
+            hashfn,
+            claim,
+        };
+        receipt.verify_integrity_with_context(ctx)?;
+
+        Ok(receipt)
     }
 }
 
+fn report_fallback(
+    index: u32,
+    segment: &Segment,
+    kind: FallbackKind,
+    err: &OutOfMemory,
+    fallbacks: &mut Vec<SegmentFallback>,
+    on_fallback: &dyn Fn(&str),
+) {
+    let error = err.to_string();
+    on_fallback(&error);
+    fallbacks.push(SegmentFallback {
+        index,
+        po2: segment.po2() as u32,
+        kind,
+        error,
+    });
+}
+
 impl<H, C> ProverServer for ProverImpl<H, C>
 where
     H: Hal<Field = BabyBear, Elem = Elem, ExtElem = ExtElem>,
     C: CircuitHal<H>,
 {
     fn prove_session(&self, ctx: &VerifierContext, session: &Session) -> Result<Receipt> {
+        Ok(self.prove_session_info(ctx, session)?.receipt)
+    }
+
+    fn prove_session_info(&self, ctx: &VerifierContext, session: &Session) -> Result<ProveInfo> {
         tracing::debug!(
             "prove_session: {}, exit_code = {:?}, journal = {:?}, segments: {}",
             self.name,
@@ -63,17 +226,36 @@ where
             session.journal.as_ref().map(|x| hex::encode(x)),
             session.segments.len()
         );
+        self.budget
+            .check_budget(session.segments.len(), session.total_cycles)?;
         let mut segments = Vec::new();
+        let mut fallbacks = Vec::new();
         for segment_ref in session.segments.iter() {
             let segment = segment_ref.resolve()?;
             for hook in &session.hooks {
                 hook.on_pre_prove_segment(&segment);
             }
-            segments.push(self.prove_segment(ctx, &segment)?);
+            let on_fallback = |err: &str| {
+                for hook in &session.hooks {
+                    hook.on_prove_segment_fallback(&segment, err);
+                }
+            };
+            segments.extend(self.prove_segment_or_fallback(
+                ctx,
+                segment.index,
+                &segment,
+                &mut fallbacks,
+                &on_fallback,
+            )?);
             for hook in &session.hooks {
                 hook.on_post_prove_segment(&segment);
             }
         }
+        // Split segments are proven in place of the segment they were split
+        // from, so the receipts are indexed by their position.
+        for (index, receipt) in segments.iter_mut().enumerate() {
+            receipt.index = index as u32;
+        }
         // TODO(#982): Support unresolved assumptions here.
         let assumptions = session
             .assumptions
@@ -83,7 +265,10 @@ where
         let composite_receipt = CompositeReceipt {
             segments,
             assumptions,
-            journal_digest: session.journal.as_ref().map(|journal| journal.digest()),
+            journal_digest: session
+                .journal
+                .as_ref()
+                .map(|journal| session.committed_digest.unwrap_or_else(|| journal.digest())),
         };
 
         // Verify the receipt to catch if something is broken in the proving process.
@@ -108,7 +293,13 @@ where
         );
 
         // Verify the receipt to catch if something is broken in the proving process.
-        receipt.verify_integrity_with_context(ctx)?;
+        // The journal of a receipt with a committed digest is empty, and the
+        // digest is checked with the rest of the claim below.
+        if session.committed_digest.is_some() {
+            receipt.inner.verify_integrity_with_context(ctx)?;
+        } else {
+            receipt.verify_integrity_with_context(ctx)?;
+        }
         if receipt.get_claim()?.digest() != session.get_claim()?.digest() {
             tracing::debug!("receipt and session claim do not match");
             tracing::debug!("receipt claim: {:#?}", receipt.get_claim()?);
@@ -120,32 +311,30 @@ where
             );
         }
 
-        Ok(receipt)
+        Ok(ProveInfo {
+            receipt,
+            stats: session.stats(),
+            fallbacks,
+        })
     }
 
     fn prove_segment(&self, ctx: &VerifierContext, segment: &Segment) -> Result<SegmentReceipt> {
-        use risc0_circuit_rv32im::prove::{engine::SegmentProverImpl, SegmentProver as _};
-
-        use crate::host::receipt::decode_receipt_claim_from_seal;
-
-        let hashfn = self.hal_pair.hal.get_hash_suite().name.clone();
-
-        let prover =
-            SegmentProverImpl::new(self.hal_pair.hal.clone(), self.hal_pair.circuit_hal.clone());
-        let seal = prover.prove_segment(&segment.inner)?;
-
-        let mut claim = decode_receipt_claim_from_seal(&seal)?;
-        claim.output = segment.output.clone().into();
-
-        let receipt = SegmentReceipt {
-            seal,
-            index: segment.index as u32,
-            hashfn,
-            claim,
-        };
-        receipt.verify_integrity_with_context(ctx)?;
-
-        Ok(receipt)
+        // A single segment receipt is returned, so the segment is not split.
+        match catch_out_of_memory(|| self.prove_segment_inner(ctx, segment)) {
+            Ok(receipt) => receipt,
+            Err(err) => {
+                let Some(fallback) = self.fallback()? else {
+                    return Err(err.into());
+                };
+                tracing::warn!(
+                    "{} prover ran out of memory on segment {} (po2 {}), falling back: {err}",
+                    self.name,
+                    segment.index,
+                    segment.po2()
+                );
+                fallback.prove_segment(ctx, segment)
+            }
+        }
     }
 
     fn get_peak_memory_usage(&self) -> usize {
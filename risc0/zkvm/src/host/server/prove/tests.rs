@@ -29,16 +29,18 @@ use test_log::test;
 
 use super::{get_prover_server, HalPair, ProverImpl};
 use crate::{
+    compute_image_id,
     host::server::testutils,
     serde::{from_slice, to_vec},
-    ExecutorEnv, ExecutorImpl, ExitCode, ProverOpts, ProverServer, Receipt, Session,
-    VerifierContext,
+    Clock, ExecutorEnv, ExecutorImpl, ExitCode, LocalBackend, ProverOpts, ProverServer,
+    ProvingBudgetExceeded, Receipt, RevokedImages, Session, VerifierContext,
 };
 
 fn prover_opts_fast() -> ProverOpts {
     ProverOpts {
         hashfn: "sha-256".to_string(),
         prove_guest_errors: false,
+        ..Default::default()
     }
 }
 
@@ -58,6 +60,7 @@ fn prove_nothing(hashfn: &str) -> Result<Receipt> {
     let opts = ProverOpts {
         hashfn: hashfn.to_string(),
         prove_guest_errors: false,
+        ..Default::default()
     };
     get_prover_server(&opts).unwrap().prove(env, MULTI_TEST_ELF)
 }
@@ -93,6 +96,33 @@ fn receipt_serde() {
     decoded.verify(MULTI_TEST_ID).unwrap();
 }
 
+#[test]
+#[cfg_attr(feature = "cuda", serial)]
+fn commit_digest() {
+    let digest = Digest::from([1, 2, 3, 4, 5, 6, 7, 8]);
+    let env = ExecutorEnv::builder()
+        .write(&MultiTestSpec::CommitDigest { digest })
+        .unwrap()
+        .build()
+        .unwrap();
+    let receipt = get_prover_server(&prover_opts_fast())
+        .unwrap()
+        .prove(env, MULTI_TEST_ELF)
+        .unwrap();
+    assert!(receipt.journal.bytes.is_empty());
+    receipt.verify_with_digest(MULTI_TEST_ID, digest).unwrap();
+    assert_eq!(
+        receipt
+            .verify_with_digest(MULTI_TEST_ID, Digest::ZERO)
+            .unwrap_err(),
+        VerificationError::JournalDigestMismatch
+    );
+    assert_eq!(
+        receipt.verify(MULTI_TEST_ID).unwrap_err(),
+        VerificationError::JournalDigestMismatch
+    );
+}
+
 #[test]
 #[cfg_attr(feature = "cuda", serial)]
 fn check_image_id() {
@@ -105,6 +135,102 @@ fn check_image_id() {
         receipt.verify(image_id).unwrap_err(),
         VerificationError::ImageVerificationError
     );
+
+    let failure = receipt.verify_with_diagnostics(image_id).unwrap_err();
+    assert_eq!(failure.error, VerificationError::ImageVerificationError);
+    assert_eq!(failure.diagnostics.expected_image_id, Some(image_id));
+    assert_eq!(
+        failure.diagnostics.image_id,
+        Some(Digest::from(MULTI_TEST_ID))
+    );
+    assert_eq!(
+        failure.diagnostics.journal_digest,
+        failure.diagnostics.expected_journal_digest
+    );
+}
+
+#[test]
+#[cfg_attr(feature = "cuda", serial)]
+fn verifier_policy() {
+    struct FixedClock(u64);
+
+    impl Clock for FixedClock {
+        fn now(&self) -> u64 {
+            self.0
+        }
+    }
+
+    let receipt = prove_nothing("sha-256").unwrap();
+    let verify = |policy: RevokedImages, now: u64| {
+        let ctx = VerifierContext::default()
+            .with_policy(policy)
+            .with_clock(FixedClock(now));
+        receipt.verify_with_context(&ctx, MULTI_TEST_ID)
+    };
+
+    verify(RevokedImages::new().revoke(Digest::ZERO), 0).unwrap();
+    verify(RevokedImages::new().revoke_at(MULTI_TEST_ID, 100), 99).unwrap();
+    assert!(matches!(
+        verify(RevokedImages::new().revoke_at(MULTI_TEST_ID, 100), 100),
+        Err(VerificationError::PolicyViolation(_))
+    ));
+    assert!(matches!(
+        verify(RevokedImages::new().revoke(MULTI_TEST_ID), 0),
+        Err(VerificationError::PolicyViolation(_))
+    ));
+}
+
+#[test]
+#[cfg_attr(feature = "cuda", serial)]
+fn local_backend() {
+    use bonsai_sdk::alpha::{Client, SdkErr, SessionOpts};
+
+    let client = Client::from_backend(LocalBackend::new(), crate::VERSION)
+        .unwrap()
+        .with_receipt_verifier(crate::BonsaiReceiptVerifier);
+    client.check_compat().unwrap();
+    let image_id = hex::encode(compute_image_id(MULTI_TEST_ELF).unwrap());
+    client
+        .upload_img(&image_id, MULTI_TEST_ELF.to_vec())
+        .unwrap();
+    let input = to_vec(&MultiTestSpec::DoNothing).unwrap();
+    let input_id = client
+        .upload_input(bytemuck::cast_slice(&input).to_vec())
+        .unwrap();
+
+    let session = client
+        .create_session(image_id.clone(), input_id.clone(), vec![])
+        .unwrap();
+    let res = session.status(&client).unwrap();
+    assert_eq!(res.status, "SUCCEEDED");
+    let receipt: Receipt =
+        bincode::deserialize(&client.download(&res.receipt_url.unwrap()).unwrap()).unwrap();
+    receipt.verify(MULTI_TEST_ID).unwrap();
+
+    // Receipts are verified against the given image ID.
+    client
+        .verified_receipt_download(&session, &image_id)
+        .unwrap();
+    assert!(matches!(
+        client.verified_receipt_download(&session, &hex::encode(Digest::ZERO)),
+        Err(SdkErr::InvalidReceipt(_))
+    ));
+
+    // Execute-only sessions report their cycles, without a receipt.
+    let mut opts = SessionOpts {
+        execute_only: true,
+        ..Default::default()
+    };
+    opts.metadata.labels.insert("tenant".into(), "test".into());
+    let session = client
+        .create_session_with_opts(image_id, input_id, vec![], opts.clone())
+        .unwrap();
+    let res = session.status(&client).unwrap();
+    assert_eq!(res.status, "SUCCEEDED");
+    assert_eq!(res.receipt_url, None);
+    assert_eq!(res.metadata, Some(opts.metadata));
+    assert!(res.stats.unwrap().segments > 0);
+    assert!(session.exec_only_journal(&client).unwrap().is_empty());
 }
 
 #[test]
@@ -441,14 +567,54 @@ fn continuation() {
     }
 }
 
+#[test]
+fn proving_budget() {
+    let program = testutil::simple_loop();
+    let image = MemoryImage::new(&program, PAGE_SIZE as u32).unwrap();
+    let env = ExecutorEnv::builder()
+        .segment_limit_po2(14)
+        .build()
+        .unwrap();
+    let session = ExecutorImpl::new(env, image).unwrap().run().unwrap();
+    let segments = session.segments.len();
+    let total_cycles = session.total_cycles;
+
+    let prove = |opts: ProverOpts| {
+        get_prover_server(&opts)
+            .unwrap()
+            .prove_session(&VerifierContext::default(), &session)
+    };
+    let err = prove(prover_opts_fast().max_segments(segments - 1)).unwrap_err();
+    assert_eq!(
+        err.downcast_ref::<ProvingBudgetExceeded>(),
+        Some(&ProvingBudgetExceeded::Segments {
+            limit: segments - 1,
+            segments
+        })
+    );
+    let err = prove(prover_opts_fast().max_total_cycles(total_cycles - 1)).unwrap_err();
+    assert_eq!(
+        err.downcast_ref::<ProvingBudgetExceeded>(),
+        Some(&ProvingBudgetExceeded::TotalCycles {
+            limit: total_cycles - 1,
+            total_cycles
+        })
+    );
+    prove(
+        prover_opts_fast()
+            .max_segments(segments)
+            .max_total_cycles(total_cycles),
+    )
+    .unwrap();
+}
+
 #[cfg(feature = "docker")]
 #[test]
 fn stark2snark() {
     use crate::{
-        get_prover_server, recursion::identity_p254, CompactReceipt, ExecutorEnv, ExecutorImpl,
-        InnerReceipt, ProverOpts, Receipt, VerifierContext,
+        get_prover_server, sha::Digestible, verify_groth16_seal, ExecutorEnv, ExecutorImpl,
+        Groth16Seal, InnerReceipt, ProverOpts, Receipt, VerifierContext,
     };
-    use risc0_groth16::docker::stark_to_snark;
     use risc0_zkvm_methods::{multi_test::MultiTestSpec, MULTI_TEST_ELF, MULTI_TEST_ID};
 
     let env = ExecutorEnv::builder()
@@ -472,20 +638,44 @@ fn stark2snark() {
     let succinct_receipt = prover.compress(composite_receipt).unwrap();
     let journal = session.journal.unwrap().bytes;
 
-    tracing::info!("identity_p254");
-    let ident_receipt = identity_p254(&succinct_receipt).unwrap();
-    let seal_bytes = ident_receipt.get_seal_bytes();
+    tracing::info!("compact");
+    let compact_receipt = prover.compact(&succinct_receipt).unwrap();
+    assert_eq!(compact_receipt.claim, claim);
 
-    tracing::info!("stark-to-snark");
-    let seal = stark_to_snark(&seal_bytes).unwrap().to_vec();
+    tracing::info!("verify_groth16_seal");
+    let groth16_seal = Groth16Seal::from_vec(&compact_receipt.seal).unwrap();
+    let post_state_digest = claim.post.digest();
+    verify_groth16_seal(&groth16_seal, MULTI_TEST_ID, post_state_digest, &journal).unwrap();
+    assert_eq!(
+        verify_groth16_seal(&groth16_seal, MULTI_TEST_ID, post_state_digest, b"forged"),
+        Err(VerificationError::InvalidProof)
+    );
 
     tracing::info!("Receipt");
-    let receipt = Receipt::new(
-        InnerReceipt::Compact(CompactReceipt { seal, claim }),
-        journal,
-    );
+    let receipt = Receipt::new(InnerReceipt::Compact(compact_receipt), journal);
 
     receipt.verify(MULTI_TEST_ID).unwrap();
+    assert_eq!(Groth16Seal::try_from(&receipt).unwrap(), groth16_seal);
+    assert_eq!(receipt.claim_digest().unwrap(), claim.digest());
+
+    #[cfg(feature = "client")]
+    {
+        tracing::info!("SnarkReceipt");
+        let snark_receipt = bonsai_sdk::alpha::responses::SnarkReceipt {
+            snark: groth16_seal,
+            post_state_digest: post_state_digest.as_bytes().to_vec(),
+            journal: receipt.journal.bytes.clone(),
+        };
+        let compact_receipt =
+            crate::CompactReceipt::from_bonsai_snark(&snark_receipt, MULTI_TEST_ID).unwrap();
+        assert_eq!(compact_receipt.claim.digest(), claim.digest());
+        Receipt::new(
+            InnerReceipt::Compact(compact_receipt),
+            snark_receipt.journal,
+        )
+        .verify(MULTI_TEST_ID)
+        .unwrap();
+    }
 }
 
 mod sys_verify {
@@ -515,6 +705,7 @@ mod sys_verify {
         let opts = ProverOpts {
             hashfn: "sha-256".to_string(),
             prove_guest_errors: true,
+            ..Default::default()
         };
 
         let env = ExecutorEnvBuilder::default()
@@ -21,14 +21,14 @@ use std::{
     path::{Path, PathBuf},
 };
 
-use anyhow::{ensure, Result};
+use anyhow::{anyhow, ensure, Result};
 use risc0_binfmt::{MemoryImage, SystemState};
 use risc0_circuit_rv32im::prove::segment::Segment as CircuitSegment;
 use serde::{Deserialize, Serialize};
 
 use crate::{
-    host::client::env::SegmentPath, sha::Digest, Assumption, Assumptions, ExitCode, Journal,
-    Output, ReceiptClaim,
+    host::client::env::SegmentPath, sha::Digest, Artifact, Assumption, Assumptions, ExitCode,
+    Journal, MaybePruned, Output, ReceiptClaim, SessionOutcome, SessionStats,
 };
 
 #[derive(Clone, Default, Serialize, Deserialize, Debug)]
@@ -53,6 +53,10 @@ pub struct Session {
     /// The data publicly committed by the guest program.
     pub journal: Option<Journal>,
 
+    /// The digest committed by the guest program with `env::commit_digest` in
+    /// place of the digest of the journal, which is then empty.
+    pub committed_digest: Option<Digest>,
+
     /// The [ExitCode] of the session.
     pub exit_code: ExitCode,
 
@@ -97,6 +101,10 @@ pub struct Segment {
     pub(crate) output: Option<Output>,
 }
 
+impl Artifact for Segment {
+    const KIND: &'static str = "Segment";
+}
+
 impl Segment {
     /// Give the power of two length of this [Segment]
     ///
@@ -104,6 +112,32 @@ impl Segment {
     pub fn po2(&self) -> usize {
         self.inner.po2
     }
+
+    /// Split this [Segment] into consecutive segments of at most `2^po2`
+    /// cycles.
+    ///
+    /// The execution of this segment is replayed from the syscall results
+    /// recorded in it, without re-executing the [Session] from the start. The
+    /// new segments are indexed from the index of this segment, so they must
+    /// be re-indexed along with any later segments of the session, as
+    /// [Session::split_segments] does.
+    pub(crate) fn split(&self, po2: usize) -> Result<Vec<Segment>> {
+        let inner = self.inner.split(po2)?;
+        let last = inner.len() - 1;
+        Ok(inner
+            .into_iter()
+            .enumerate()
+            .map(|(idx, inner)| Segment {
+                index: inner.index as u32,
+                output: if idx == last {
+                    self.output.clone()
+                } else {
+                    None
+                },
+                inner,
+            })
+            .collect())
+    }
 }
 
 /// A reference to a [Segment].
@@ -125,6 +159,12 @@ pub trait SessionEvents {
     /// Fired after the proving of a segment ends.
     #[allow(unused)]
     fn on_post_prove_segment(&self, segment: &Segment) {}
+
+    /// Fired when the prover fails to prove a segment, e.g. because it ran out
+    /// of device memory, and splits it into smaller segments or proves it with
+    /// its fallback prover instead.
+    #[allow(unused)]
+    fn on_prove_segment_fallback(&self, segment: &Segment, error: &str) {}
 }
 
 impl Session {
@@ -143,6 +183,7 @@ impl Session {
         Self {
             segments,
             journal: journal.map(|x| Journal::new(x)),
+            committed_digest: None,
             exit_code,
             post_image,
             assumptions,
@@ -154,11 +195,64 @@ impl Session {
         }
     }
 
+    /// Sets the digest committed by the guest program in place of the digest
+    /// of its journal, see [Session::committed_digest].
+    pub fn with_committed_digest(mut self, digest: Digest) -> Self {
+        self.committed_digest = Some(digest);
+        self
+    }
+
     /// Add a hook to be called during the proving phase.
     pub fn add_hook<E: SessionEvents + 'static>(&mut self, hook: E) {
         self.hooks.push(Box::new(hook));
     }
 
+    /// Returns the [SessionOutcome] of this [Session], resolved from its
+    /// [ExitCode].
+    pub fn outcome(&self) -> Result<SessionOutcome> {
+        self.exit_code
+            .outcome()
+            .ok_or_else(|| anyhow!("Session ended with exit code {:?}", self.exit_code))
+    }
+
+    /// Split the [Segment]s of this [Session] longer than `2^po2` cycles, for
+    /// provers with less memory than the executor assumed.
+    ///
+    /// The execution of each split segment is replayed from the syscall
+    /// results recorded in it, without re-executing the session from the
+    /// start. All the segments are then re-indexed to stay consecutive, so
+    /// every segment is resolved, and held in memory by a [SimpleSegmentRef].
+    pub fn split_segments(&mut self, po2: usize) -> Result<()> {
+        let mut segments = Vec::new();
+        for segment_ref in self.segments.iter() {
+            let segment = segment_ref.resolve()?;
+            if segment.po2() > po2 {
+                segments.extend(segment.split(po2)?);
+            } else {
+                segments.push(segment);
+            }
+        }
+        self.total_cycles = segments.iter().map(|segment| 1 << segment.po2()).sum();
+        self.segments = segments
+            .into_iter()
+            .enumerate()
+            .map(|(index, mut segment)| {
+                segment.index = index as u32;
+                Box::new(SimpleSegmentRef::new(segment)) as Box<dyn SegmentRef>
+            })
+            .collect();
+        Ok(())
+    }
+
+    /// Returns the [SessionStats] of this [Session].
+    pub fn stats(&self) -> SessionStats {
+        SessionStats {
+            segments: self.segments.len(),
+            total_cycles: self.total_cycles,
+            user_cycles: self.user_cycles,
+        }
+    }
+
     /// Calculate for the [ReceiptClaim] associated with this [Session]. The
     /// [ReceiptClaim] is the claim that will be proven if this [Session]
     /// is passed to the [crate::Prover].
@@ -171,7 +265,10 @@ impl Session {
                 .as_ref()
                 .map(|journal| -> Result<_> {
                     Ok(Output {
-                        journal: journal.bytes.clone().into(),
+                        journal: match self.committed_digest {
+                            Some(digest) => MaybePruned::Pruned(digest),
+                            None => journal.bytes.clone().into(),
+                        },
                         assumptions: Assumptions(
                             self.assumptions
                                 .iter()
@@ -274,8 +371,7 @@ pub struct FileSegmentRef {
 impl SegmentRef for FileSegmentRef {
     fn resolve(&self) -> Result<Segment> {
         let contents = fs::read(&self.path)?;
-        let segment = bincode::deserialize(&contents)?;
-        Ok(segment)
+        Segment::from_versioned_bytes(&contents)
     }
 }
 
@@ -294,7 +390,7 @@ impl FileSegmentRef {
     /// This builds a FileSegmentRef that stores `segment` in a file at `path`.
     pub fn new(segment: &Segment, dir: &SegmentPath) -> Result<Self> {
         let path = dir.path().join(format!("{}.bincode", segment.index));
-        fs::write(&path, bincode::serialize(&segment)?)?;
+        fs::write(&path, segment.to_versioned_bytes()?)?;
         Ok(Self {
             path,
             _dir: dir.clone(),
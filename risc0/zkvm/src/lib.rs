@@ -51,6 +51,7 @@
 //! | Feature          | Target(s)         | Implies    | Description                                                                                                                                                  |
 //! | ---------------- | ----------------- | ---------- | ------------------------------------------------------------------------------------------------------------------------------------------------------------ |
 //! | client           | all except rv32im | std        | Enables the client API.                                                                                                                                      |
+//! | compress         | all               |            | Enables compressed guest input from Zstandard archives.                                                                                                      |
 //! | cuda             |                   | prove, std | Enables CUDA GPU acceleration for the prover. Requires CUDA toolkit to be installed.                                                                         |
 //! | disable-dev-mode | all except rv32im |            | Disables dev mode so that proving and verifying may not be faked. Used to prevent a misplaced `RISC0_DEV_MODE` from breaking security in production systems. |
 //! | metal            | macos             | prove, std | Enables Metal GPU acceleration for the prover.                                                                                                               |
@@ -71,6 +72,8 @@
 
 extern crate alloc;
 
+#[cfg(feature = "compress")]
+pub mod compress;
 pub mod guest;
 #[cfg(not(target_os = "zkvm"))]
 mod host;
@@ -88,7 +91,7 @@ pub use anyhow::Result;
 #[cfg(not(target_os = "zkvm"))]
 #[cfg(any(feature = "client", feature = "prove"))]
 pub use bytes::Bytes;
-pub use risc0_binfmt::{ExitCode, InvalidExitCodeError, SystemState};
+pub use risc0_binfmt::{ExitCode, InvalidExitCodeError, SessionOutcome, SystemState};
 pub use risc0_zkvm_platform::{align_up, declare_syscall, memory::GUEST_MAX_MEM, PAGE_SIZE};
 
 pub use self::receipt_claim::{Assumptions, MaybePruned, Output, PrunedValueError, ReceiptClaim};
@@ -96,6 +99,7 @@ pub use self::receipt_claim::{Assumptions, MaybePruned, Output, PrunedValueError
 pub use {
     self::host::{
         api::server::Server as ApiServer,
+        chrome_trace::{ChromeTraceGuard, ChromeTraceLayer},
         client::prove::local::LocalProver,
         recursion::RECURSION_PO2,
         server::{
@@ -106,7 +110,10 @@ pub use {
             },
         },
     },
-    risc0_circuit_rv32im::prove::engine::loader::Loader,
+    risc0_circuit_rv32im::prove::{
+        emu::guest_ptr::{GuestFault, GuestPtr},
+        engine::loader::Loader,
+    },
     risc0_groth16::{
         docker::stark_to_snark, to_json as seal_to_json, ProofJson as Groth16ProofJson,
     },
@@ -115,13 +122,18 @@ pub use {
 pub use {
     self::host::{
         api::{
-            client::Client as ApiClient, Asset, AssetRequest, Connector, SegmentInfo, SessionInfo,
+            client::Client as ApiClient, Asset, AssetRequest, Connector, FallbackKind, ProveInfo,
+            SegmentFallback, SegmentInfo, SessionInfo, SessionStats,
         },
+        artifact::{Artifact, ArtifactVersion, ArtifactVersionMismatch},
         client::{
-            env::{ExecutorEnv, ExecutorEnvBuilder},
+            env::{ExecutorEnv, ExecutorEnvBuilder, TimeLimitExceeded, VersionSkew},
             prove::{
-                bonsai::BonsaiProver, default_executor, default_prover, external::ExternalProver,
-                Executor, Prover, ProverOpts,
+                bonsai::{BonsaiProver, BonsaiReceiptVerifier},
+                default_executor, default_prover,
+                external::ExternalProver,
+                local_backend::LocalBackend,
+                Executor, Prover, ProverOpts, ProvingBudgetExceeded,
             },
         },
     },
@@ -130,17 +142,28 @@ pub use {
 #[cfg(not(target_os = "zkvm"))]
 pub use {
     self::host::{
+        policy::{Clock, DeprecatedControlIds, RevokedImages, VerifierPolicy},
         receipt::{
-            Assumption, CompactReceipt, CompositeReceipt, InnerReceipt, Journal, Receipt,
-            SegmentReceipt, SuccinctReceipt, VerifierContext,
+            groth16_registry, verify_groth16_seal, Assumption, CompactReceipt, CompositeReceipt,
+            InnerReceipt, Journal, Receipt, SegmentReceipt, SuccinctReceipt, SuccinctVerifier,
+            VerificationDiagnostics, VerificationFailure, VerifierContext,
         },
         recursion::ALLOWED_IDS_ROOT,
     },
     risc0_binfmt::compute_image_id,
     risc0_circuit_rv32im::control_id::POSEIDON2_CONTROL_ID,
-    risc0_groth16::Seal as Groth16Seal,
+    risc0_groth16::{
+        pvk::{
+            AllowedRoots as Groth16AllowedRoots, Registry as Groth16Registry,
+            VersionedKey as Groth16VersionedKey,
+        },
+        Seal as Groth16Seal, SealRef as Groth16SealRef,
+    },
 };
 
+#[cfg(all(not(target_os = "zkvm"), feature = "std"))]
+pub use self::host::policy::SystemClock;
+
 use semver::Version;
 
 /// Reports the current version of this crate.
@@ -75,6 +75,26 @@ pub struct ReceiptClaim {
 }
 
 impl ReceiptClaim {
+    /// Construct the [ReceiptClaim] of an execution of `image_id` which halted
+    /// with exit code 0 in the post state of digest `post_state_digest`, with
+    /// the given [Output].
+    ///
+    /// The pre and post states are pruned, as they are in the claims verified
+    /// on-chain.
+    pub fn ok(
+        image_id: impl Into<Digest>,
+        post_state_digest: impl Into<Digest>,
+        output: Output,
+    ) -> Self {
+        Self {
+            pre: MaybePruned::Pruned(image_id.into()),
+            post: MaybePruned::Pruned(post_state_digest.into()),
+            exit_code: ExitCode::Halted(0),
+            input: Digest::ZERO,
+            output: Some(output).into(),
+        }
+    }
+
     /// Decode a [ReceiptClaim] from a list of [u32]'s
     pub fn decode(flat: &mut VecDeque<u32>) -> Result<Self, DecodeError> {
         let input = read_sha_halfs(flat)?;
@@ -179,6 +199,42 @@ pub struct Output {
     pub assumptions: MaybePruned<Assumptions>,
 }
 
+impl Output {
+    /// Construct an [Output] committing to `journal`, with no assumptions.
+    pub fn new(journal: impl Into<Vec<u8>>) -> Self {
+        Self {
+            journal: MaybePruned::Value(journal.into()),
+            assumptions: Assumptions::default().into(),
+        }
+    }
+
+    /// Construct an [Output] committing to `digest` in place of the digest of
+    /// a journal, with no assumptions.
+    ///
+    /// This is the output of a guest which committed an application-defined
+    /// digest, such as a state root, with `env::commit_digest`. Verifying the
+    /// claim then takes a constant amount of data, regardless of the size of
+    /// the data committed to by the digest.
+    pub fn from_digest(digest: impl Into<Digest>) -> Self {
+        Self {
+            journal: MaybePruned::Pruned(digest.into()),
+            assumptions: Assumptions::default().into(),
+        }
+    }
+
+    /// Set the assumptions of this [Output].
+    pub fn with_assumptions(mut self, assumptions: impl Into<MaybePruned<Assumptions>>) -> Self {
+        self.assumptions = assumptions.into();
+        self
+    }
+
+    /// The digest of the journal, or the application-defined digest committed
+    /// in its place.
+    pub fn journal_digest(&self) -> Digest {
+        self.journal.digest::<sha::Impl>()
+    }
+}
+
 impl Digestible for Output {
     /// Hash the [Output] to get a digest of the struct.
     fn digest<S: Sha256>(&self) -> Digest {
@@ -223,6 +279,13 @@ impl Assumptions {
     }
 }
 
+impl FromIterator<MaybePruned<ReceiptClaim>> for Assumptions {
+    /// Collect the assumptions, in order from the head of the list.
+    fn from_iter<I: IntoIterator<Item = MaybePruned<ReceiptClaim>>>(iter: I) -> Self {
+        Self(iter.into_iter().collect())
+    }
+}
+
 impl Deref for Assumptions {
     type Target = [MaybePruned<ReceiptClaim>];
 
@@ -658,6 +721,29 @@ mod tests {
         }
     }
 
+    #[test]
+    fn output_builders() {
+        let journal = b"hello world".to_vec();
+        let output = Output::new(journal.clone());
+        assert_eq!(output.journal_digest(), journal.digest());
+        assert_eq!(
+            output.digest(),
+            Output::from_digest(journal.digest()).digest()
+        );
+
+        let assumption: MaybePruned<ReceiptClaim> = MaybePruned::Pruned(Digest::ZERO);
+        let assumptions: Assumptions = [assumption.clone()].into_iter().collect();
+        let output = output.with_assumptions(assumptions.clone());
+        assert_eq!(output.assumptions, MaybePruned::Value(assumptions));
+
+        let state_root = Digest::from([7u32; 8]);
+        let claim = ReceiptClaim::ok(Digest::ZERO, Digest::ZERO, Output::from_digest(state_root));
+        let output = claim.output.as_value().unwrap().as_ref().unwrap();
+        assert_eq!(output.journal_digest(), state_root);
+        assert!(output.assumptions.is_empty());
+        assert_eq!(claim.exit_code, ExitCode::Halted(0));
+    }
+
     #[test]
     fn merge_receipt_claim() {
         let claim = MaybePruned::Value(ReceiptClaim {